@@ -0,0 +1,92 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use rusqlite::Connection;
+use thiserror::Error as ThisError;
+
+mod queries;
+
+// Same defaults as the indexer, both can be changed with the environment
+const DEFAULT_INDEX_DB: &str = "superpet-index.db";
+const DEFAULT_API_ADDR: &str = "127.0.0.1:8080";
+
+const LEADERBOARD_SIZE: u32 = 20;
+
+// Read-only connection to the event database, shared by the handlers
+type Db = Arc<Mutex<Connection>>;
+
+#[derive(Debug, ThisError)]
+enum ApiError {
+    #[error("database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("pet not found")]
+    PetNotFound,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::PetNotFound => StatusCode::NOT_FOUND,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let path = std::env::var("SUPERPET_INDEX_DB").unwrap_or(DEFAULT_INDEX_DB.to_string());
+    let addr: SocketAddr = std::env::var("SUPERPET_API_ADDR")
+        .unwrap_or(DEFAULT_API_ADDR.to_string())
+        .parse()
+        .expect("SUPERPET_API_ADDR is not a socket address");
+    let db = Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .unwrap_or_else(|e| panic!("can't open {path}, run the indexer first: {e}"));
+
+    let app = Router::new()
+        .route("/pets/:account", get(pets_of))
+        .route("/pet/:id", get(pet))
+        .route("/leaderboard", get(leaderboard))
+        .route("/listings", get(listings))
+        .with_state(Arc::new(Mutex::new(db)));
+
+    println!("serving {path} on http://{addr}");
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .expect("server error");
+}
+
+// Pets owned by the account, given as an SS58 address
+async fn pets_of(
+    State(db): State<Db>,
+    Path(account): Path<String>,
+) -> Result<Json<Vec<queries::OwnedPet>>, ApiError> {
+    Ok(Json(queries::pets_of(&db.lock().unwrap(), &account)?))
+}
+
+async fn pet(
+    State(db): State<Db>,
+    Path(pet_id): Path<u32>,
+) -> Result<Json<queries::PetDetails>, ApiError> {
+    let pet = queries::pet(&db.lock().unwrap(), pet_id)?;
+    pet.map(Json).ok_or(ApiError::PetNotFound)
+}
+
+async fn leaderboard(State(db): State<Db>) -> Result<Json<Vec<queries::Ranking>>, ApiError> {
+    Ok(Json(queries::leaderboard(
+        &db.lock().unwrap(),
+        LEADERBOARD_SIZE,
+    )?))
+}
+
+async fn listings(State(db): State<Db>) -> Result<Json<Vec<queries::Listing>>, ApiError> {
+    Ok(Json(queries::listings(&db.lock().unwrap())?))
+}
@@ -0,0 +1,160 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+// The indexer stores one row per pet event, see `indexer.rs` in the game. Owners and
+// listings aren't stored, they follow from the latest event of each kind for a pet.
+
+// Latest ownership change of every pet: minted to `account`, or moved to `other`
+const OWNERS: &str = "
+    SELECT pet_id, CASE name WHEN 'PetMinted' THEN account ELSE other END AS owner, block
+    FROM events e
+    WHERE name IN ('PetMinted', 'PetTransfered', 'Sold')
+      AND (block, idx) = (
+        SELECT block, idx FROM events
+        WHERE pet_id = e.pet_id AND name IN ('PetMinted', 'PetTransfered', 'Sold')
+        ORDER BY block DESC, idx DESC LIMIT 1
+      )
+";
+
+// Pets whose latest market event is a listing
+const LISTINGS: &str = "
+    SELECT pet_id, account AS seller, amount AS price, block
+    FROM events e
+    WHERE name = 'PetListed'
+      AND (block, idx) = (
+        SELECT block, idx FROM events
+        WHERE pet_id = e.pet_id
+          AND name IN ('PetListed', 'ListingCancelled', 'PetTransfered', 'Sold')
+        ORDER BY block DESC, idx DESC LIMIT 1
+      )
+";
+
+#[derive(Serialize)]
+pub struct OwnedPet {
+    pub pet_id: u32,
+    // Block the account got the pet in
+    pub since_block: u32,
+}
+
+#[derive(Serialize)]
+pub struct PetEvent {
+    pub block: u32,
+    pub event: String,
+    pub account: Option<String>,
+    pub other: Option<String>,
+    // Balances don't fit in JSON numbers, so amounts are strings
+    pub amount: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PetDetails {
+    pub pet_id: u32,
+    pub owner: Option<String>,
+    pub listed_price: Option<String>,
+    pub history: Vec<PetEvent>,
+}
+
+#[derive(Serialize)]
+pub struct Ranking {
+    pub pet_id: u32,
+    pub owner: Option<String>,
+    pub wins: u32,
+    pub experience: u64,
+}
+
+#[derive(Serialize)]
+pub struct Listing {
+    pub pet_id: u32,
+    pub seller: String,
+    pub price: String,
+    pub block: u32,
+}
+
+pub fn pets_of(db: &Connection, account: &str) -> rusqlite::Result<Vec<OwnedPet>> {
+    let mut query = db.prepare_cached(&format!(
+        "SELECT pet_id, block FROM ({OWNERS}) WHERE owner = ?1"
+    ))?;
+    let pets = query.query_map([account], |row| {
+        Ok(OwnedPet {
+            pet_id: row.get(0)?,
+            since_block: row.get(1)?,
+        })
+    })?;
+    pets.collect()
+}
+
+// The pet with its whole history, `None` if the index never saw it
+pub fn pet(db: &Connection, pet_id: u32) -> rusqlite::Result<Option<PetDetails>> {
+    let mut query = db.prepare_cached(
+        "SELECT block, name, account, other, amount FROM events WHERE pet_id = ?1
+         ORDER BY block, idx",
+    )?;
+    let history = query
+        .query_map([pet_id], |row| {
+            Ok(PetEvent {
+                block: row.get(0)?,
+                event: row.get(1)?,
+                account: row.get(2)?,
+                other: row.get(3)?,
+                amount: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if history.is_empty() {
+        return Ok(None);
+    }
+    let owner = db
+        .query_row(
+            &format!("SELECT owner FROM ({OWNERS}) WHERE pet_id = ?1"),
+            [pet_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let listed_price = db
+        .query_row(
+            &format!("SELECT price FROM ({LISTINGS}) WHERE pet_id = ?1"),
+            [pet_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(Some(PetDetails {
+        pet_id,
+        owner,
+        listed_price,
+        history,
+    }))
+}
+
+// Pets with the most battle wins
+pub fn leaderboard(db: &Connection, limit: u32) -> rusqlite::Result<Vec<Ranking>> {
+    let mut query = db.prepare_cached(&format!(
+        "SELECT b.pet_id, o.owner, count(*) AS wins, sum(CAST(b.amount AS INTEGER))
+         FROM events b LEFT JOIN ({OWNERS}) o ON o.pet_id = b.pet_id
+         WHERE b.name = 'BattleResolved'
+         GROUP BY b.pet_id
+         ORDER BY wins DESC, b.pet_id
+         LIMIT ?1"
+    ))?;
+    let rankings = query.query_map(params![limit], |row| {
+        Ok(Ranking {
+            pet_id: row.get(0)?,
+            owner: row.get(1)?,
+            wins: row.get(2)?,
+            experience: row.get(3)?,
+        })
+    })?;
+    rankings.collect()
+}
+
+pub fn listings(db: &Connection) -> rusqlite::Result<Vec<Listing>> {
+    let mut query = db.prepare_cached(&format!("{LISTINGS} ORDER BY block DESC"))?;
+    let listings = query.query_map([], |row| {
+        Ok(Listing {
+            pet_id: row.get(0)?,
+            seller: row.get(1)?,
+            price: row.get(2)?,
+            block: row.get(3)?,
+        })
+    })?;
+    listings.collect()
+}
@@ -1,15 +1,40 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, PetModuleConfig,
+	PetSpecies, SS58Prefix, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
-use sc_service::ChainType;
+use sc_service::{config::MultiaddrWithPeerId, ChainType, Properties};
+use sc_telemetry::TelemetryEndpoints;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
-use sp_core::{sr25519, Pair, Public};
+use sp_core::{crypto::Ss58Codec, ed25519, sr25519, Pair, Public};
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
 // The URL for the telemetry server.
-// const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
+const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
+
+/// Bootnodes of the public testnet, as `/dns/<host>/tcp/30333/p2p/<peer id>`. Each node
+/// keeps the secret key it got from `key generate-node-key`, only its peer id goes here.
+const TESTNET_BOOTNODES: &[&str] = &[];
+
+/// Public keys of the testnet validators in SS58, their Aura (sr25519) and Grandpa (ed25519)
+/// keys. The secret keys never leave the validators, where they're added with
+/// `author_insertKey`. Public dev seeds like `//Alice` must not be used here: anyone could
+/// author and finalize blocks with them.
+const TESTNET_AUTHORITIES: &[(&str, &str)] = &[];
+
+/// SS58 address of the testnet sudo account, held by the operators.
+const TESTNET_SUDO: &str = "";
+
+/// SS58 addresses of the accounts endowed at genesis besides sudo, e.g. the operators'
+/// faucet. Dev accounts like `//Alice` must not be used here: anyone could spend from them.
+const TESTNET_ENDOWED: &[&str] = &[];
+
+/// Pets that exist from genesis on the testnet, owned by accounts in SS58.
+/// \[owner, petid, name, species\]
+const TESTNET_PETS: &[(&str, u32, &str, PetSpecies)] = &[];
+
+/// A pet that exists from genesis. \[owner, petid, name, species\]
+type GenesisPet = (AccountId, u32, Vec<u8>, PetSpecies);
 
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
@@ -59,6 +84,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Genesis pets
+				vec![],
 				true,
 			)
 		},
@@ -107,6 +134,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Genesis pets
+				vec![],
 				true,
 			)
 		},
@@ -124,12 +153,101 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// A testnet account from its SS58 address, `what` tells which one it is in errors.
+fn account_from_ss58(what: &str, address: &str) -> Result<AccountId, String> {
+	let public = sr25519::Public::from_ss58check(address)
+		.map_err(|e| format!("Invalid testnet {what} {address:?}: {e:?}"))?;
+	Ok(AccountPublic::from(public).into_account())
+}
+
+/// The Aura and Grandpa keys of a validator from their SS58 addresses.
+fn authority_keys_from_ss58(aura: &str, grandpa: &str) -> Result<(AuraId, GrandpaId), String> {
+	let aura = sr25519::Public::from_ss58check(aura)
+		.map_err(|e| format!("Invalid testnet Aura key {aura}: {e:?}"))?;
+	let grandpa = ed25519::Public::from_ss58check(grandpa)
+		.map_err(|e| format!("Invalid testnet Grandpa key {grandpa}: {e:?}"))?;
+	Ok((aura.into(), grandpa.into()))
+}
+
+/// The public testnet the client's Testnet preset connects to. Its validators, sudo account,
+/// endowed accounts and bootnodes are the operators' own, see `TESTNET_AUTHORITIES`. The
+/// operators publish its raw spec from `build-spec --chain testnet --raw`.
+pub fn testnet_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Testnet wasm not available".to_string())?;
+
+	if TESTNET_AUTHORITIES.is_empty() {
+		return Err("The testnet validator keys aren't set in TESTNET_AUTHORITIES".to_string());
+	}
+	let authorities = TESTNET_AUTHORITIES
+		.iter()
+		.map(|(aura, grandpa)| authority_keys_from_ss58(aura, grandpa))
+		.collect::<Result<Vec<_>, _>>()?;
+	let sudo = account_from_ss58("sudo account", TESTNET_SUDO)?;
+	let mut endowed = vec![sudo.clone()];
+	for address in TESTNET_ENDOWED {
+		endowed.push(account_from_ss58("endowed account", address)?);
+	}
+	let pets = TESTNET_PETS
+		.iter()
+		.map(|(owner, id, name, species)| {
+			let owner = account_from_ss58("pet owner", owner)?;
+			Ok((owner, *id, name.as_bytes().to_vec(), species.clone()))
+		})
+		.collect::<Result<Vec<GenesisPet>, String>>()?;
+
+	let bootnodes = TESTNET_BOOTNODES
+		.iter()
+		.map(|addr| addr.parse::<MultiaddrWithPeerId>().map_err(|e| e.to_string()))
+		.collect::<Result<Vec<_>, _>>()?;
+	let telemetry = TelemetryEndpoints::new(vec![(STAGING_TELEMETRY_URL.to_string(), 0)])
+		.map_err(|e| e.to_string())?;
+
+	let mut properties = Properties::new();
+	properties.insert("tokenSymbol".into(), "PET".into());
+	properties.insert("tokenDecimals".into(), 12.into());
+	properties.insert("ss58Format".into(), SS58Prefix::get().into());
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"SuperPet Testnet",
+		// ID
+		"superpet_testnet",
+		ChainType::Live,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				authorities.clone(),
+				// Sudo account
+				sudo.clone(),
+				// Pre-funded accounts
+				endowed.clone(),
+				// Genesis pets
+				pets.clone(),
+				true,
+			)
+		},
+		// Bootnodes
+		bootnodes,
+		// Telemetry
+		Some(telemetry),
+		// Protocol ID
+		Some("superpet"),
+		None,
+		// Properties
+		Some(properties),
+		// Extensions
+		None,
+	))
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	initial_pets: Vec<GenesisPet>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -152,5 +270,10 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		pet_module: PetModuleConfig {
+			pets: initial_pets,
+			// Snakes are rare
+			species_caps: vec![(PetSpecies::Snake, 100)],
+		},
 	}
 }
@@ -0,0 +1,296 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{dispatch::DispatchResult, RuntimeDebug};
+use scale_info::TypeInfo;
+
+type PetId = u32;
+
+/// Finds the pets to insure and revives the ones that died of neglect.
+pub trait InsuredPets<AccountId, Balance> {
+	fn pet_of(owner: &AccountId) -> Option<PetId>;
+	/// What reviving a pet costs the pool.
+	fn revival_cost() -> Balance;
+	/// Give the abandoned pet back to its owner, paid by `payer`.
+	fn revive(payer: &AccountId, pet_id: PetId) -> DispatchResult;
+}
+
+/// What a policy pays when the insured pet is abandoned.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum Cover {
+	/// The pet goes back to its owner right away.
+	Revival,
+	/// The owner receives `Payout`.
+	Payout,
+}
+
+/// The pet covered by a policy, and how.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Policy {
+	pub pet_id: PetId,
+	pub cover: Cover,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{AccountIdConversion, Saturating, Zero},
+		Permill,
+	};
+	use sp_std::prelude::*;
+
+	type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency premiums and payouts are paid in.
+		type Currency: Currency<Self::AccountId>;
+
+		/// Finds and revives insured pets.
+		type Pets: InsuredPets<Self::AccountId, BalanceOf<Self>>;
+
+		/// The account of the pool premiums are paid to.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The premium charged to each policyholder every era.
+		#[pallet::constant]
+		type Premium: Get<BalanceOf<Self>>;
+
+		/// The amount paid to the owner of an abandoned pet with `Cover::Payout`.
+		#[pallet::constant]
+		type Payout: Get<BalanceOf<Self>>;
+
+		/// The length of an era, premiums are charged when an era starts.
+		#[pallet::constant]
+		type EraLength: Get<Self::BlockNumber>;
+
+		/// The share of the claims of every policy the pool must be able to pay before it
+		/// takes a new policy.
+		#[pallet::constant]
+		type MinCoverRatio: Get<Permill>;
+
+		/// The maximum number of policies.
+		#[pallet::constant]
+		type MaxPolicies: Get<u32>;
+	}
+
+	/// The policy of each insured owner.
+	#[pallet::storage]
+	pub type Policies<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Policy>;
+
+	/// The insured owners, charged a premium every era.
+	#[pallet::storage]
+	pub type Policyholders<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxPolicies>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The owner insured the pet, and paid the first premium.
+		PolicyTaken { owner: T::AccountId, pet_id: PetId, cover: Cover },
+		/// The owner cancelled the policy.
+		PolicyCancelled { owner: T::AccountId, pet_id: PetId },
+		/// The premium of an era is paid.
+		PremiumPaid { owner: T::AccountId, amount: BalanceOf<T> },
+		/// The owner couldn't pay the premium, the policy is over.
+		PolicyLapsed { owner: T::AccountId, pet_id: PetId },
+		/// The insured pet was abandoned and the policy paid out, `amount` left the pool.
+		ClaimPaid { owner: T::AccountId, pet_id: PetId, cover: Cover, amount: BalanceOf<T> },
+		/// The insured pet was abandoned but the pool couldn't pay, the policy goes on.
+		ClaimUnpaid { owner: T::AccountId, pet_id: PetId },
+		/// Someone added funds to the pool.
+		PoolFunded { who: T::AccountId, amount: BalanceOf<T> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		AccountHasNoPet,
+		AlreadyInsured,
+		NotInsured,
+		PoolInsolvent,
+		TooManyPolicies,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Charge the premiums when an era starts, dropping the policies that can't be paid.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			if !(now % T::EraLength::get().max(1u32.into())).is_zero() {
+				return Weight::zero();
+			}
+			let holders = Policyholders::<T>::get();
+			let count = holders.len() as u64;
+			let mut lapsed = Vec::new();
+			for owner in holders {
+				if Self::charge_premium(&owner).is_err() {
+					lapsed.push(owner);
+				}
+			}
+			for owner in lapsed {
+				if let Some(policy) = Self::end_policy(&owner) {
+					Self::deposit_event(Event::PolicyLapsed { owner, pet_id: policy.pet_id });
+				}
+			}
+			T::DbWeight::get().reads_writes(1 + 2 * count, 1 + 2 * count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Insure your pet against neglect, paying the first `Premium` now and the next ones
+		/// when each era starts. If the pet is abandoned, the pool revives it or pays `Payout`.
+		///
+		/// - cover: What the policy pays when the pet is abandoned
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn insure(origin: OriginFor<T>, cover: Cover) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let pet_id = T::Pets::pet_of(&owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			ensure!(!Policies::<T>::contains_key(&owner), Error::<T>::AlreadyInsured);
+
+			// The pool must hold part of what it would owe if every pet was abandoned
+			let policies = Policyholders::<T>::decode_len().unwrap_or(0) as u32 + 1;
+			let exposure = Self::claim_cost(Cover::Revival)
+				.max(Self::claim_cost(Cover::Payout))
+				.saturating_mul(policies.into());
+			ensure!(
+				Self::pool_balance() >= T::MinCoverRatio::get() * exposure,
+				Error::<T>::PoolInsolvent
+			);
+
+			Policyholders::<T>::try_mutate(|holders| holders.try_push(owner.clone()))
+				.map_err(|_| Error::<T>::TooManyPolicies)?;
+			Self::charge_premium(&owner)?;
+			Policies::<T>::insert(&owner, Policy { pet_id, cover });
+
+			Self::deposit_event(Event::PolicyTaken { owner, pet_id, cover });
+
+			Ok(().into())
+		}
+
+		/// Cancel your policy, premiums already paid aren't refunded.
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn cancel_policy(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let policy = Self::end_policy(&owner).ok_or(Error::<T>::NotInsured)?;
+
+			Self::deposit_event(Event::PolicyCancelled { owner, pet_id: policy.pet_id });
+
+			Ok(().into())
+		}
+
+		/// Add funds to the pool, e.g. from the pet treasury.
+		///
+		/// - amount: The amount moved to the pool
+		#[pallet::call_index(2)]
+		#[pallet::weight(0)]
+		pub fn fund_pool(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			T::Currency::transfer(
+				&who,
+				&Self::pool_account(),
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			Self::deposit_event(Event::PoolFunded { who, amount });
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account holding the pooled premiums.
+		pub fn pool_account() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// The funds of the pool that can be paid out, the pool account is kept alive.
+		pub fn pool_balance() -> BalanceOf<T> {
+			T::Currency::free_balance(&Self::pool_account())
+				.saturating_sub(T::Currency::minimum_balance())
+		}
+
+		fn claim_cost(cover: Cover) -> BalanceOf<T> {
+			match cover {
+				Cover::Revival => T::Pets::revival_cost(),
+				Cover::Payout => T::Payout::get(),
+			}
+		}
+
+		fn charge_premium(owner: &T::AccountId) -> DispatchResult {
+			let amount = T::Premium::get();
+			T::Currency::transfer(
+				owner,
+				&Self::pool_account(),
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			Self::deposit_event(Event::PremiumPaid { owner: owner.clone(), amount });
+			Ok(())
+		}
+
+		fn end_policy(owner: &T::AccountId) -> Option<Policy> {
+			let policy = Policies::<T>::take(owner)?;
+			Policyholders::<T>::mutate(|holders| holders.retain(|holder| holder != owner));
+			Some(policy)
+		}
+
+		/// Pay the claim of an insured pet that was abandoned. The policy ends once paid, and
+		/// stays when the pool can't pay.
+		pub fn claim(owner: &T::AccountId, pet_id: PetId) {
+			let Some(policy) = Policies::<T>::get(owner).filter(|p| p.pet_id == pet_id) else {
+				return;
+			};
+			let amount = Self::claim_cost(policy.cover);
+			let pool = Self::pool_account();
+			let paid = if Self::pool_balance() < amount {
+				Err(Error::<T>::PoolInsolvent.into())
+			} else {
+				match policy.cover {
+					Cover::Revival => T::Pets::revive(&pool, pet_id),
+					Cover::Payout => {
+						T::Currency::transfer(&pool, owner, amount, ExistenceRequirement::KeepAlive)
+					},
+				}
+			};
+			if paid.is_err() {
+				Self::deposit_event(Event::ClaimUnpaid { owner: owner.clone(), pet_id });
+				return;
+			}
+			Self::end_policy(owner);
+
+			Self::deposit_event(Event::ClaimPaid {
+				owner: owner.clone(),
+				pet_id,
+				cover: policy.cover,
+				amount,
+			});
+		}
+	}
+}
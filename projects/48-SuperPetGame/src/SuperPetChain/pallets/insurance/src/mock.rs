@@ -0,0 +1,140 @@
+use crate::{self as pallet_insurance, InsuredPets, PetId};
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure, parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, Currency, ExistenceRequirement, WithdrawReasons},
+	PalletId,
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		InsuranceModule: pallet_insurance,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// What reviving a pet costs, like the adoption fee of the runtime.
+pub const REVIVAL_COST: u64 = 20;
+
+parameter_types! {
+	pub const InsurancePalletId: PalletId = PalletId(*b"py/insur");
+	pub const MinCoverRatio: Permill = Permill::from_percent(50);
+	/// The owner of each pet. Accounts 1 to 3 own pets 1 to 3, account 4 owns none.
+	pub static PetOwners: Vec<(u64, PetId)> = vec![(1, 1), (2, 2), (3, 3)];
+	/// The pets in the adoption pool, which can be revived.
+	pub static Abandoned: Vec<PetId> = vec![];
+	/// The pets revived, and who paid for it.
+	pub static Revived: Vec<(u64, PetId)> = vec![];
+}
+
+/// Pets owned as listed in `PetOwners`, revived out of `Abandoned` for `REVIVAL_COST`.
+pub struct MockPets;
+
+impl InsuredPets<u64, u64> for MockPets {
+	fn pet_of(owner: &u64) -> Option<PetId> {
+		PetOwners::get()
+			.into_iter()
+			.find(|(account, _)| account == owner)
+			.map(|(_, id)| id)
+	}
+
+	fn revival_cost() -> u64 {
+		REVIVAL_COST
+	}
+
+	fn revive(payer: &u64, pet_id: PetId) -> DispatchResult {
+		let mut abandoned = Abandoned::get();
+		ensure!(abandoned.contains(&pet_id), DispatchError::Other("not abandoned"));
+		// Like the adoption fee, the cost is burned
+		let _ = Balances::withdraw(
+			payer,
+			REVIVAL_COST,
+			WithdrawReasons::FEE,
+			ExistenceRequirement::KeepAlive,
+		)?;
+		abandoned.retain(|id| *id != pet_id);
+		Abandoned::set(abandoned);
+		let mut revived = Revived::get();
+		revived.push((*payer, pet_id));
+		Revived::set(revived);
+		Ok(())
+	}
+}
+
+impl pallet_insurance::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = MockPets;
+	type PalletId = InsurancePalletId;
+	type Premium = ConstU64<5>;
+	type Payout = ConstU64<30>;
+	type EraLength = ConstU64<10>;
+	type MinCoverRatio = MinCoverRatio;
+	type MaxPolicies = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+// Every account has a balance of 100, the pool has nothing.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=5).map(|who| (who, 100)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,180 @@
+use crate::{mock::*, *};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, Hooks},
+};
+
+/// Account 5 moves `amount` to the pool.
+fn funded_pool(amount: u64) {
+	assert_ok!(InsuranceModule::fund_pool(RuntimeOrigin::signed(5), amount));
+}
+
+#[test]
+fn fund_pool_works() {
+	new_test_ext().execute_with(|| {
+		funded_pool(50);
+
+		assert_eq!(Balances::free_balance(InsuranceModule::pool_account()), 50);
+		// The pool account is kept alive
+		assert_eq!(InsuranceModule::pool_balance(), 49);
+		System::assert_last_event(Event::PoolFunded { who: 5, amount: 50 }.into());
+		assert_noop!(
+			InsuranceModule::fund_pool(RuntimeOrigin::signed(5), 50),
+			pallet_balances::Error::<Test>::KeepAlive
+		);
+	});
+}
+
+#[test]
+fn insure_pays_the_first_premium() {
+	new_test_ext().execute_with(|| {
+		funded_pool(50);
+
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Revival));
+
+		assert_eq!(Policies::<Test>::get(1), Some(Policy { pet_id: 1, cover: Cover::Revival }));
+		assert_eq!(Policyholders::<Test>::get().into_inner(), vec![1]);
+		assert_eq!(Balances::free_balance(1), 95);
+		assert_eq!(Balances::free_balance(InsuranceModule::pool_account()), 55);
+		System::assert_has_event(Event::PremiumPaid { owner: 1, amount: 5 }.into());
+		System::assert_last_event(
+			Event::PolicyTaken { owner: 1, pet_id: 1, cover: Cover::Revival }.into(),
+		);
+	});
+}
+
+#[test]
+fn insure_checks_the_pet_and_the_policies() {
+	new_test_ext().execute_with(|| {
+		funded_pool(50);
+		assert_noop!(
+			InsuranceModule::insure(RuntimeOrigin::signed(4), Cover::Payout),
+			Error::<Test>::AccountHasNoPet
+		);
+
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Payout));
+		assert_noop!(
+			InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Revival),
+			Error::<Test>::AlreadyInsured
+		);
+
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(2), Cover::Payout));
+		assert_noop!(
+			InsuranceModule::insure(RuntimeOrigin::signed(3), Cover::Payout),
+			Error::<Test>::TooManyPolicies
+		);
+	});
+}
+
+#[test]
+fn the_pool_holds_part_of_every_claim_before_insuring() {
+	new_test_ext().execute_with(|| {
+		// Half of the largest claim, the payout of 30
+		assert_noop!(
+			InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Revival),
+			Error::<Test>::PoolInsolvent
+		);
+		funded_pool(16);
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Revival));
+
+		// The pool holds 20 of the 30 two policies need
+		assert_noop!(
+			InsuranceModule::insure(RuntimeOrigin::signed(2), Cover::Revival),
+			Error::<Test>::PoolInsolvent
+		);
+	});
+}
+
+#[test]
+fn cancel_policy_keeps_the_premiums_paid() {
+	new_test_ext().execute_with(|| {
+		funded_pool(50);
+		assert_noop!(
+			InsuranceModule::cancel_policy(RuntimeOrigin::signed(1)),
+			Error::<Test>::NotInsured
+		);
+
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Payout));
+		assert_ok!(InsuranceModule::cancel_policy(RuntimeOrigin::signed(1)));
+
+		assert_eq!(Policies::<Test>::get(1), None);
+		assert!(Policyholders::<Test>::get().is_empty());
+		assert_eq!(Balances::free_balance(1), 95);
+		System::assert_last_event(Event::PolicyCancelled { owner: 1, pet_id: 1 }.into());
+	});
+}
+
+#[test]
+fn premiums_are_charged_when_an_era_starts() {
+	new_test_ext().execute_with(|| {
+		funded_pool(50);
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Payout));
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(2), Cover::Payout));
+		// Paying the next premium would leave nothing
+		Balances::make_free_balance_be(&2, 5);
+
+		InsuranceModule::on_initialize(9);
+		assert_eq!(Balances::free_balance(1), 95);
+
+		InsuranceModule::on_initialize(10);
+		assert_eq!(Balances::free_balance(1), 90);
+		assert_eq!(Balances::free_balance(InsuranceModule::pool_account()), 65);
+		System::assert_last_event(Event::PolicyLapsed { owner: 2, pet_id: 2 }.into());
+		assert_eq!(Policies::<Test>::get(2), None);
+		assert_eq!(Policyholders::<Test>::get().into_inner(), vec![1]);
+	});
+}
+
+#[test]
+fn claims_revive_the_pet_or_pay_the_owner() {
+	new_test_ext().execute_with(|| {
+		funded_pool(100);
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Revival));
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(2), Cover::Payout));
+		let pool = InsuranceModule::pool_account();
+		Abandoned::set(vec![1, 2]);
+
+		// Claims are only paid for the insured pet
+		InsuranceModule::claim(&1, 2);
+		assert!(Revived::get().is_empty());
+
+		InsuranceModule::claim(&1, 1);
+		assert_eq!(Revived::get(), vec![(pool, 1)]);
+		assert_eq!(Balances::free_balance(pool), 110 - REVIVAL_COST);
+		assert_eq!(Policies::<Test>::get(1), None);
+		System::assert_last_event(
+			Event::ClaimPaid { owner: 1, pet_id: 1, cover: Cover::Revival, amount: REVIVAL_COST }
+				.into(),
+		);
+
+		InsuranceModule::claim(&2, 2);
+		assert_eq!(Balances::free_balance(2), 95 + 30);
+		assert_eq!(Balances::free_balance(pool), 90 - 30);
+		assert!(Policyholders::<Test>::get().is_empty());
+		System::assert_last_event(
+			Event::ClaimPaid { owner: 2, pet_id: 2, cover: Cover::Payout, amount: 30 }.into(),
+		);
+	});
+}
+
+#[test]
+fn unpaid_claims_keep_the_policy() {
+	new_test_ext().execute_with(|| {
+		funded_pool(30);
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(1), Cover::Payout));
+		assert_ok!(InsuranceModule::insure(RuntimeOrigin::signed(2), Cover::Revival));
+		// The pool paid other claims, it holds 29 of the payout of 30
+		Balances::make_free_balance_be(&InsuranceModule::pool_account(), 30);
+
+		InsuranceModule::claim(&1, 1);
+		assert_eq!(Balances::free_balance(1), 95);
+		System::assert_last_event(Event::ClaimUnpaid { owner: 1, pet_id: 1 }.into());
+		assert!(Policies::<Test>::contains_key(1));
+
+		// The pet couldn't be revived
+		InsuranceModule::claim(&2, 2);
+		System::assert_last_event(Event::ClaimUnpaid { owner: 2, pet_id: 2 }.into());
+		assert!(Policies::<Test>::contains_key(2));
+		assert_eq!(Balances::free_balance(InsuranceModule::pool_account()), 30);
+	});
+}
@@ -0,0 +1,247 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::{dispatch::DispatchResult, traits::Currency};
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// What happens to a pet when its owner uses an item on it.
+pub trait UseItem<AccountId> {
+	fn use_item(who: &AccountId, item: pallet::Item) -> DispatchResult;
+}
+
+impl<AccountId> UseItem<AccountId> for () {
+	fn use_item(_who: &AccountId, _item: pallet::Item) -> DispatchResult {
+		Ok(())
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{ExistenceRequirement, OnUnbalanced, WithdrawReasons},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Saturating;
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency used to buy items.
+		type Currency: Currency<Self::AccountId>;
+
+		/// The price of a single item.
+		#[pallet::constant]
+		type ItemPrice: Get<BalanceOf<Self>>;
+
+		/// Handler for the money paid for items, e.g. a treasury pot.
+		type OnItemSale: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Applies the effect of an item used on the owner's pet.
+		type ItemUser: UseItem<Self::AccountId>;
+	}
+
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum Item {
+		Apple,
+		Fish,
+		Carrot,
+		Ball,
+		Rope,
+		Hat,
+		Scarf,
+		Heater,
+		CaretakerBadge,
+		ChampionBadge,
+		DreamerBadge,
+	}
+
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum ItemCategory {
+		Food,
+		Toy,
+		Accessory,
+		Incubator,
+		/// Achievement rewards, bound to the account that earned them.
+		Badge,
+	}
+
+	impl Item {
+		pub const ALL: [Item; 11] = [
+			Item::Apple,
+			Item::Fish,
+			Item::Carrot,
+			Item::Ball,
+			Item::Rope,
+			Item::Hat,
+			Item::Scarf,
+			Item::Heater,
+			Item::CaretakerBadge,
+			Item::ChampionBadge,
+			Item::DreamerBadge,
+		];
+
+		pub fn category(&self) -> ItemCategory {
+			match self {
+				Item::Apple | Item::Fish | Item::Carrot => ItemCategory::Food,
+				Item::Ball | Item::Rope => ItemCategory::Toy,
+				Item::Hat | Item::Scarf => ItemCategory::Accessory,
+				Item::Heater => ItemCategory::Incubator,
+				Item::CaretakerBadge | Item::ChampionBadge | Item::DreamerBadge =>
+					ItemCategory::Badge,
+			}
+		}
+
+		/// Whether the item stays with the account that got it: it can't be bought, used or
+		/// transferred.
+		pub fn is_soulbound(&self) -> bool {
+			self.category() == ItemCategory::Badge
+		}
+	}
+
+	/// Number of each item held by an account.
+	#[pallet::storage]
+	pub type Inventory<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, Item, u32, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Items were bought. \[owner, item, amount, cost\]
+		ItemsBought(T::AccountId, Item, u32, BalanceOf<T>),
+		/// An item was used on the owner's pet. \[owner, item\]
+		ItemUsed(T::AccountId, Item),
+		/// Items were given for free, e.g. as a quest reward. \[owner, item, amount\]
+		ItemsGiven(T::AccountId, Item, u32),
+		/// Items changed hands, e.g. sold together with a pet. \[from, to, item, amount\]
+		ItemsTransferred(T::AccountId, T::AccountId, Item, u32),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		ZeroAmount,
+		NotEnoughItems,
+		/// Badges are earned, not sold.
+		NotForSale,
+		/// Badges can't be used or change hands.
+		Soulbound,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Buy items at `ItemPrice` each.
+		///
+		/// - item: The item to buy
+		/// - amount: How many items to buy
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn buy(origin: OriginFor<T>, item: Item, amount: u32) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			ensure!(amount > 0, Error::<T>::ZeroAmount);
+			ensure!(!item.is_soulbound(), Error::<T>::NotForSale);
+
+			let cost = T::ItemPrice::get().saturating_mul(amount.into());
+			let paid = T::Currency::withdraw(
+				&sender,
+				cost,
+				WithdrawReasons::TRANSFER,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::OnItemSale::on_unbalanced(paid);
+
+			Inventory::<T>::mutate(&sender, item, |count| *count = count.saturating_add(amount));
+
+			Self::deposit_event(Event::ItemsBought(sender, item, amount, cost));
+
+			Ok(().into())
+		}
+
+		/// Use one item on your pet. Food feeds the pet, incubators make its egg hatch sooner.
+		///
+		/// - item: The item to use
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn use_item(origin: OriginFor<T>, item: Item) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			ensure!(!item.is_soulbound(), Error::<T>::Soulbound);
+
+			Inventory::<T>::try_mutate_exists(&sender, item, |count| -> DispatchResult {
+				let left =
+					count.unwrap_or_default().checked_sub(1).ok_or(Error::<T>::NotEnoughItems)?;
+				*count = Some(left).filter(|left| *left > 0);
+				Ok(())
+			})?;
+			T::ItemUser::use_item(&sender, item)?;
+
+			Self::deposit_event(Event::ItemUsed(sender, item));
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Add items to an inventory without paying for them.
+		pub fn give(who: &T::AccountId, item: Item, amount: u32) {
+			Inventory::<T>::mutate(who, item, |count| *count = count.saturating_add(amount));
+
+			Self::deposit_event(Event::ItemsGiven(who.clone(), item, amount));
+		}
+
+		/// Move items from one inventory to another. Badges never move.
+		pub fn transfer(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			item: Item,
+			amount: u32,
+		) -> DispatchResult {
+			ensure!(!item.is_soulbound(), Error::<T>::Soulbound);
+			Inventory::<T>::try_mutate_exists(from, item, |count| -> DispatchResult {
+				let left = count
+					.unwrap_or_default()
+					.checked_sub(amount)
+					.ok_or(Error::<T>::NotEnoughItems)?;
+				*count = Some(left).filter(|left| *left > 0);
+				Ok(())
+			})?;
+			Inventory::<T>::mutate(to, item, |count| *count = count.saturating_add(amount));
+
+			Self::deposit_event(Event::ItemsTransferred(from.clone(), to.clone(), item, amount));
+
+			Ok(())
+		}
+
+		/// The items of a category an account holds, and how many of each.
+		pub fn held(who: &T::AccountId, category: ItemCategory) -> Vec<(Item, u32)> {
+			Item::ALL
+				.into_iter()
+				.filter(|item| item.category() == category)
+				.map(|item| (item, Inventory::<T>::get(who, item)))
+				.filter(|(_, count)| *count > 0)
+				.collect()
+		}
+
+		/// The badges an account earned, and how many times.
+		pub fn badges(who: &T::AccountId) -> Vec<(Item, u32)> {
+			Self::held(who, ItemCategory::Badge)
+		}
+	}
+}
@@ -0,0 +1,107 @@
+use crate::{mock::*, *};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
+use sp_runtime::DispatchError;
+
+#[test]
+fn buy_pays_the_price_of_each_item() {
+	new_test_ext().execute_with(|| {
+		let issuance = Balances::total_issuance();
+
+		assert_ok!(ItemsModule::buy(RuntimeOrigin::signed(1), Item::Apple, 3));
+
+		assert_eq!(Inventory::<Test>::get(1, Item::Apple), 3);
+		assert_eq!(Balances::free_balance(1), 94);
+		// The mock hands the payment to `()`, which burns it
+		assert_eq!(Balances::total_issuance(), issuance - 6);
+		System::assert_last_event(Event::ItemsBought(1, Item::Apple, 3, 6).into());
+	});
+}
+
+#[test]
+fn buy_checks_the_amount_item_and_funds() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ItemsModule::buy(RuntimeOrigin::signed(1), Item::Apple, 0),
+			Error::<Test>::ZeroAmount
+		);
+		assert_noop!(
+			ItemsModule::buy(RuntimeOrigin::signed(1), Item::CaretakerBadge, 1),
+			Error::<Test>::NotForSale
+		);
+		assert_noop!(
+			ItemsModule::buy(RuntimeOrigin::signed(1), Item::Apple, 51),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		// Spending everything would kill the account
+		assert_noop!(
+			ItemsModule::buy(RuntimeOrigin::signed(1), Item::Apple, 50),
+			pallet_balances::Error::<Test>::KeepAlive
+		);
+	});
+}
+
+#[test]
+fn use_item_takes_one_item_for_the_pet() {
+	new_test_ext().execute_with(|| {
+		ItemsModule::give(&1, Item::Apple, 2);
+		System::assert_last_event(Event::ItemsGiven(1, Item::Apple, 2).into());
+
+		assert_ok!(ItemsModule::use_item(RuntimeOrigin::signed(1), Item::Apple));
+		assert_eq!(Inventory::<Test>::get(1, Item::Apple), 1);
+		System::assert_last_event(Event::ItemUsed(1, Item::Apple).into());
+
+		assert_ok!(ItemsModule::use_item(RuntimeOrigin::signed(1), Item::Apple));
+		assert!(!Inventory::<Test>::contains_key(1, Item::Apple));
+		assert_eq!(UsedItems::get(), vec![(1, Item::Apple), (1, Item::Apple)]);
+
+		assert_noop!(
+			ItemsModule::use_item(RuntimeOrigin::signed(1), Item::Apple),
+			Error::<Test>::NotEnoughItems
+		);
+	});
+}
+
+#[test]
+fn items_are_kept_when_there_is_no_pet_to_use_them_on() {
+	new_test_ext().execute_with(|| {
+		ItemsModule::give(&4, Item::Apple, 1);
+
+		assert_noop!(
+			ItemsModule::use_item(RuntimeOrigin::signed(4), Item::Apple),
+			DispatchError::Other("no pet")
+		);
+		assert_eq!(Inventory::<Test>::get(4, Item::Apple), 1);
+	});
+}
+
+#[test]
+fn transfer_moves_items_between_inventories() {
+	new_test_ext().execute_with(|| {
+		ItemsModule::give(&1, Item::Hat, 3);
+
+		assert_ok!(ItemsModule::transfer(&1, &2, Item::Hat, 2));
+		System::assert_last_event(Event::ItemsTransferred(1, 2, Item::Hat, 2).into());
+		assert_eq!(ItemsModule::held(&1, ItemCategory::Accessory), vec![(Item::Hat, 1)]);
+		assert_eq!(ItemsModule::held(&2, ItemCategory::Accessory), vec![(Item::Hat, 2)]);
+
+		assert_noop!(ItemsModule::transfer(&1, &2, Item::Hat, 2), Error::<Test>::NotEnoughItems);
+	});
+}
+
+#[test]
+fn badges_stay_with_the_account_that_earned_them() {
+	new_test_ext().execute_with(|| {
+		ItemsModule::give(&1, Item::CaretakerBadge, 1);
+
+		assert_noop!(
+			ItemsModule::use_item(RuntimeOrigin::signed(1), Item::CaretakerBadge),
+			Error::<Test>::Soulbound
+		);
+		assert_noop!(
+			ItemsModule::transfer(&1, &2, Item::CaretakerBadge, 1),
+			Error::<Test>::Soulbound
+		);
+		assert_eq!(ItemsModule::badges(&1), vec![(Item::CaretakerBadge, 1)]);
+		assert!(ItemsModule::badges(&2).is_empty());
+	});
+}
@@ -0,0 +1,34 @@
+//! Runtime API of the pet game, for clients to read what storage alone can't tell them.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_items::Item;
+use pallet_pet::{MintBlockReason, Moment, Species, SpeciesSupply};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// `Profile` is the runtime's `pallet_pet::PetProfile`.
+	pub trait PetApi<Profile, AccountId> where Profile: Codec, AccountId: Codec {
+		/// Population and cap of every species, so a client can tell which ones are sold out.
+		fn species_supply() -> Vec<SpeciesSupply>;
+
+		/// Badges earned by the owner of a pet and how many times, none if nobody owns it.
+		fn badges(pet_id: u32) -> Vec<(Item, u32)>;
+
+		/// Owner, info, stats, equipment, achievements and recent history of a pet, so a
+		/// client's pet detail screen needs one call. `None` if nobody owns it.
+		fn pet_profile(pet_id: u32) -> Option<Profile>;
+
+		/// The wall-clock time of a block, estimated from the time of the latest one, so a
+		/// client can show when blocks happened or will. `None` before the first block.
+		fn block_moment(block: u32) -> Option<Moment>;
+
+		/// Whether `account` can mint a pet of `species` now, or why not, so a client can
+		/// grey out the choice with the reason before the player tries.
+		fn can_mint(account: AccountId, species: Species) -> Result<(), MintBlockReason>;
+
+		/// Whether `account` can receive a pet, by buying, adopting or as a gift, or why not.
+		fn can_own(account: AccountId) -> Result<(), MintBlockReason>;
+	}
+}
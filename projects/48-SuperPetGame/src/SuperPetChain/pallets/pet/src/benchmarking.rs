@@ -0,0 +1,172 @@
+//! Benchmarks of the care calls and of the work the pallet does on its own in its hooks,
+//! generating the weights in `weights.rs`.
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, BenchmarkError};
+use frame_support::{
+	traits::{Currency, EnsureOrigin, Get, Hooks, ReservableCurrency},
+	weights::Weight,
+};
+use frame_system::RawOrigin;
+
+/// Give `count` accounts a pet each, never fed.
+fn neglected_pets<T: Config>(count: u32) {
+	for id in 0..count {
+		let owner: T::AccountId = account("owner", id, 0);
+		let pet = PetInfo { name: Default::default(), species: Species::Turtle };
+		PetsInfo::<T>::insert(&owner, (id, pet));
+		PetOwners::<T>::insert(id, owner);
+	}
+}
+
+/// Make abandoning the pets of `neglected_pets` cost the most: each is listed with a bundle,
+/// shared with `MaxCoOwners` co-owners who consent to trade it like its owner, and insured
+/// by `BenchmarkHelper`.
+fn worst_case_abandon<T: Config>(count: u32) {
+	for id in 0..count {
+		let owner: T::AccountId = account("owner", id, 0);
+		Listings::<T>::insert(id, T::Currency::minimum_balance());
+		ListingBundles::<T>::insert(id, BoundedVec::default());
+		ListedBy::<T>::insert(id, &owner);
+		let co_owners: Vec<T::AccountId> =
+			(0..T::MaxCoOwners::get()).map(|i| account("co-owner", id, i)).collect();
+		for co_owner in &co_owners {
+			CoOwnedPets::<T>::insert(co_owner, id);
+			TradeConsents::<T>::insert(id, co_owner, ());
+		}
+		TradeConsents::<T>::insert(id, &owner, ());
+		CoOwners::<T>::insert(id, BoundedVec::truncate_from(co_owners));
+		T::BenchmarkHelper::worst_case_abandon(&owner, id);
+	}
+}
+
+/// Give pet 0 to an owner sharing it with a co-owner, whose calls go through every role
+/// lookup of `ensure_can`, on day one. The pet is one care short of the first reward of
+/// `StreakRewards`: put to sleep today if `slept`, fed today otherwise. Returns the owner and
+/// the co-owner.
+fn pet_one_care_from_reward<T: Config>(slept: bool) -> (T::AccountId, T::AccountId) {
+	frame_system::Pallet::<T>::set_block_number(T::BlocksPerDay::get());
+	let owner: T::AccountId = account("owner", 0, 0);
+	let co_owner: T::AccountId = account("co-owner", 0, 0);
+	let pet = PetInfo { name: Default::default(), species: Species::Turtle };
+	PetsInfo::<T>::insert(&owner, (0, pet));
+	PetOwners::<T>::insert(0, &owner);
+	CoOwners::<T>::insert(0, BoundedVec::truncate_from(vec![co_owner.clone()]));
+	CoOwnedPets::<T>::insert(&co_owner, 0);
+
+	let day = Pallet::<T>::current_day();
+	let activity = if slept {
+		Activity { day, sleeps: 1, ..Default::default() }
+	} else {
+		Activity { day, feeds: 1, ..Default::default() }
+	};
+	DailyActivity::<T>::insert(0, activity);
+	if let Some((milestone, ..)) = T::StreakRewards::get().first() {
+		let days = milestone.saturating_sub(1);
+		CareStreaks::<T>::insert(0, CareStreak { days, last_day: day.saturating_sub(1) });
+	}
+	(owner, co_owner)
+}
+
+/// Check that the care of `pet_one_care_from_reward` completed the day.
+fn assert_day_completed<T: Config>() {
+	let activity = DailyActivity::<T>::get(0);
+	assert!(activity.feeds > 0 && activity.sleeps > 0);
+	assert_eq!(CareStreaks::<T>::get(0).last_day, Pallet::<T>::current_day());
+}
+
+benchmarks! {
+	// Feeding the pet as its co-owner, which completes the day and earns a streak reward
+	feed {
+		let (_, co_owner) = pet_one_care_from_reward::<T>(true);
+	}: _(RawOrigin::Signed(co_owner))
+	verify {
+		assert_eq!(PetVitals::<T>::get(0).last_fed, T::BlocksPerDay::get());
+		assert_day_completed::<T>();
+	}
+
+	// Putting the pet to sleep as its co-owner, which completes the day like `feed`
+	sleep {
+		let (_, co_owner) = pet_one_care_from_reward::<T>(false);
+	}: _(RawOrigin::Signed(co_owner))
+	verify {
+		assert_eq!(PetVitals::<T>::get(0).last_slept, Some(T::BlocksPerDay::get()));
+		assert_day_completed::<T>();
+	}
+
+	// Hatching the egg as its co-owner
+	hatch {
+		let (_, co_owner) = pet_one_care_from_reward::<T>(true);
+		Eggs::<T>::insert(0, T::BlocksPerDay::get());
+	}: _(RawOrigin::Signed(co_owner))
+	verify {
+		assert!(!Eggs::<T>::contains_key(0));
+	}
+
+	// Running a repeating feed, which completes the day like `feed`
+	run_scheduled_care {
+		let (owner, _) = pet_one_care_from_reward::<T>(true);
+		let now = T::BlocksPerDay::get();
+		let schedule = CareSchedule { next: now, every: Some(T::MinCareInterval::get()) };
+		ScheduledCare::<T>::insert(&owner, CareAction::Feed, schedule);
+		let origin = T::CareSchedulerOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin, owner.clone(), CareAction::Feed)
+	verify {
+		assert_eq!(PetVitals::<T>::get(0).last_fed, now);
+		assert_day_completed::<T>();
+	}
+
+	// Sweeping `n` neglected pets in one `on_idle`, within `MaxIdleWeight`
+	adoption_sweep {
+		let n in 0 .. 100;
+		neglected_pets::<T>(n);
+		worst_case_abandon::<T>(n);
+		DeferredTasks::<T>::put(BoundedVec::truncate_from(vec![DeferredTask::AdoptionSweep]));
+		let now = T::AbandonAfter::get();
+		frame_system::Pallet::<T>::set_block_number(now);
+	}: {
+		Pallet::<T>::on_idle(now, Weight::MAX);
+	}
+	verify {
+		// Insured pets are revived right away, the others wait in the pool
+		assert!((0..n).all(|id| {
+			AdoptionPool::<T>::contains_key(id) || PetVitals::<T>::get(id).last_fed == now
+		}));
+		assert!(CoOwnedPets::<T>::iter().next().is_none());
+		assert!(DeferredTasks::<T>::get().is_empty());
+	}
+
+	// Refunding `n` offers expiring at the same block, up to `MaxOffersPerBlock`
+	expire_offers {
+		let n in 0 .. T::MaxOffersPerBlock::get();
+		// As few pets as `MaxOffersPerPet` allows
+		let per_pet = T::MaxOffersPerPet::get().max(1);
+		neglected_pets::<T>(n / per_pet + 1);
+		let expires_at = T::OfferDuration::get();
+		let mut expiring = BoundedVec::<_, T::MaxOffersPerBlock>::default();
+		for i in 0..n {
+			let pet_id = i / per_pet;
+			let buyer: T::AccountId = account("buyer", i, 0);
+			let price = T::Currency::minimum_balance();
+			let deposit = T::OfferDeposit::get();
+			T::Currency::make_free_balance_be(&buyer, (price + deposit) * 10u32.into());
+			T::Currency::reserve(&buyer, price + deposit)?;
+			OfferDeposits::<T>::insert(pet_id, &buyer, deposit);
+			Offers::<T>::mutate(pet_id, |offers| {
+				let _ = offers.try_push(Offer { buyer: buyer.clone(), price, expires_at });
+			});
+			let _ = expiring.try_push((pet_id, buyer));
+		}
+		OfferExpiries::<T>::insert(expires_at, expiring);
+	}: {
+		Pallet::<T>::on_initialize(expires_at);
+	}
+	verify {
+		assert!(OfferExpiries::<T>::get(expires_at).is_empty());
+		assert!(Offers::<T>::iter().next().is_none());
+		assert!(OfferDeposits::<T>::iter().next().is_none());
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -2,15 +2,181 @@
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod replay;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{BalanceStatus, Currency, ReservableCurrency},
+};
+use pet_gene::Dna;
+use sp_std::vec::Vec;
+
 type PetId = u32;
 
+/// Wall-clock time in milliseconds since the Unix epoch, as pallet-timestamp keeps it.
+pub type Moment = u64;
+
+/// The id of an entry of the stats block registry, see `StatsBlocks`.
+pub type StatsBlockId = u32;
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// Called after each pet mint.
+pub trait OnPetMinted<AccountId> {
+	fn on_pet_minted(owner: &AccountId, pet_id: PetId);
+}
+
+impl<AccountId> OnPetMinted<AccountId> for () {
+	fn on_pet_minted(_owner: &AccountId, _pet_id: PetId) {}
+}
+
+/// Called after a neglected pet is moved to the adoption pool.
+pub trait OnPetAbandoned<AccountId> {
+	fn on_pet_abandoned(owner: &AccountId, pet_id: PetId);
+}
+
+impl<AccountId> OnPetAbandoned<AccountId> for () {
+	fn on_pet_abandoned(_owner: &AccountId, _pet_id: PetId) {}
+}
+
+/// Tells which pets can't change hands for now, e.g. rented ones, and which are kept out of
+/// battles, e.g. staked ones.
+pub trait PetLocks {
+	fn is_locked(pet_id: PetId) -> bool;
+
+	fn is_benched(_pet_id: PetId) -> bool {
+		false
+	}
+}
+
+impl PetLocks for () {
+	fn is_locked(_pet_id: PetId) -> bool {
+		false
+	}
+}
+
+/// Sets up for the benchmarks what the pallet can't reach, e.g. the insurance policy that
+/// makes abandoning a pet cost the most.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<AccountId> {
+	/// Make `OnAbandon` take its costliest path when the pet of `owner` is abandoned.
+	fn worst_case_abandon(_owner: &AccountId, _pet_id: PetId) {}
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl<AccountId> BenchmarkHelper<AccountId> for () {}
+
+/// Moves the items sold together with a pet in a bundle, and tells the items shown on its
+/// profile.
+pub trait BundleItems<AccountId, Item> {
+	fn count(who: &AccountId, item: Item) -> u32;
+	fn transfer(from: &AccountId, to: &AccountId, item: Item, amount: u32) -> DispatchResult;
+
+	/// The accessories of the account, worn by its pet.
+	fn equipment(_who: &AccountId) -> Vec<(Item, u32)> {
+		Vec::new()
+	}
+
+	/// The achievement badges of the account.
+	fn achievements(_who: &AccountId) -> Vec<(Item, u32)> {
+		Vec::new()
+	}
+
+	/// Hand out reward items, e.g. for care streaks.
+	fn give(_who: &AccountId, _item: Item, _amount: u32) {}
+}
+
+/// Dispatches `run_scheduled_care` for the owner of a pet at later blocks, with an origin
+/// `CareSchedulerOrigin` accepts, e.g. through the scheduler pallet.
+pub trait CareScheduler<AccountId, BlockNumber> {
+	/// Run `action` for `owner` at block `when`, then every `every` blocks if any.
+	fn schedule(
+		owner: &AccountId,
+		action: CareAction,
+		when: BlockNumber,
+		every: Option<BlockNumber>,
+	) -> DispatchResult;
+
+	fn cancel(owner: &AccountId, action: CareAction) -> DispatchResult;
+}
+
+impl<AccountId, BlockNumber> CareScheduler<AccountId, BlockNumber> for () {
+	fn schedule(
+		_owner: &AccountId,
+		_action: CareAction,
+		_when: BlockNumber,
+		_every: Option<BlockNumber>,
+	) -> DispatchResult {
+		Ok(())
+	}
+
+	fn cancel(_owner: &AccountId, _action: CareAction) -> DispatchResult {
+		Ok(())
+	}
+}
+
+impl<AccountId, Item> BundleItems<AccountId, Item> for () {
+	fn count(_who: &AccountId, _item: Item) -> u32 {
+		0
+	}
+
+	fn transfer(_from: &AccountId, _to: &AccountId, _item: Item, _amount: u32) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// The other accounts of a player's household, whose pets are cared for together with the
+/// pet of the player's account, e.g. its derivative accounts.
+pub trait HouseholdMembers<AccountId> {
+	fn members(head: &AccountId) -> Vec<AccountId>;
+}
+
+impl<AccountId> HouseholdMembers<AccountId> for () {
+	fn members(_head: &AccountId) -> Vec<AccountId> {
+		Vec::new()
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{ExistenceRequirement, OnUnbalanced, Randomness, UnixTime, WithdrawReasons},
+	};
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{
+			Hash, IdentifyAccount, SaturatedConversion, Saturating, UniqueSaturatedInto, Verify,
+			Zero,
+		},
+		Permill,
+	};
+	use sp_std::prelude::*;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -21,6 +187,210 @@ pub mod pallet {
 		/// The maximum length of a metadata string.
 		#[pallet::constant]
 		type StringLimit: Get<u32>;
+
+		/// Weight information for the work done in the hooks.
+		type WeightInfo: WeightInfo;
+
+		/// Sets up the worst cases the benchmarks can't reach through the pallet.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<Self::AccountId>;
+
+		/// The currency used to pay for pets on the marketplace, offers are reserved in it.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Handler for the market fee taken from each sale, e.g. a treasury pot.
+		type OnMarketFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The market fee used until governance sets one.
+		#[pallet::constant]
+		type DefaultMarketFee: Get<Permill>;
+
+		/// The origin allowed to change the market fee and the royalty cap.
+		type MarketFeeOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The highest royalty breeders can ask until governance sets another cap.
+		#[pallet::constant]
+		type DefaultMaxRoyalty: Get<Permill>;
+
+		/// Signature over a `ScorePayload`, produced off chain by a trusted game key.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// The public key matching `OffchainSignature`.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// The origin allowed to set the game server key and the care rules.
+		type GameAdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The highest score a single submission can award.
+		#[pallet::constant]
+		type MaxScore: Get<u32>;
+
+		/// The maximum number of game keys an account can register.
+		#[pallet::constant]
+		type MaxGameKeys: Get<u32>;
+
+		/// Experience won by the pet winning a battle.
+		#[pallet::constant]
+		type BattleExperience: Get<u32>;
+
+		/// The origin allowed to pause and unpause the game.
+		type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The length of a day, `DailyActivity` starts over each day.
+		#[pallet::constant]
+		type BlocksPerDay: Get<Self::BlockNumber>;
+
+		/// Notified of every mint, e.g. to pay referral bonuses.
+		type OnMint: OnPetMinted<Self::AccountId>;
+
+		/// How long a pet can go unfed before anyone can move it to the adoption pool.
+		#[pallet::constant]
+		type AbandonAfter: Get<Self::BlockNumber>;
+
+		/// How long the owner of an abandoned pet can reclaim it, before it can be adopted.
+		#[pallet::constant]
+		type ReclaimWindow: Get<Self::BlockNumber>;
+
+		/// The fee paid to `OnMarketFee` to adopt a pet from the adoption pool.
+		#[pallet::constant]
+		type AdoptionFee: Get<BalanceOf<Self>>;
+
+		/// How long a new pet stays an egg, zero hatches pets right away.
+		#[pallet::constant]
+		type IncubationPeriod: Get<Self::BlockNumber>;
+
+		/// The blocks each incubator item takes off the time left before an egg hatches.
+		#[pallet::constant]
+		type IncubationBoost: Get<Self::BlockNumber>;
+
+		/// The fee paid for each rename of a pet after the first one.
+		#[pallet::constant]
+		type RenameFee: Get<BalanceOf<Self>>;
+
+		/// Handler for the rename fees, `()` burns them.
+		type OnRenameFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// How long an offer stays open before it's refunded.
+		#[pallet::constant]
+		type OfferDuration: Get<Self::BlockNumber>;
+
+		/// The maximum number of open offers on a pet.
+		#[pallet::constant]
+		type MaxOffersPerPet: Get<u32>;
+
+		/// The maximum number of offers expiring at the same block.
+		#[pallet::constant]
+		type MaxOffersPerBlock: Get<u32>;
+
+		/// Reserved with each offer on top of its price and returned with it, unless the offer
+		/// is left stale: `cleanup` then pays it to the account that called it.
+		#[pallet::constant]
+		type OfferDeposit: Get<BalanceOf<Self>>;
+
+		/// The items that can be sold together with a pet.
+		type BundleItem: Parameter + MaxEncodedLen + Copy;
+
+		/// Checks and moves the items of a bundle, e.g. the items pallet.
+		type Items: BundleItems<Self::AccountId, Self::BundleItem>;
+
+		/// The maximum number of different items in a bundle.
+		#[pallet::constant]
+		type MaxBundleItems: Get<u32>;
+
+		/// Pets that can't be transferred, sold or abandoned, e.g. while they are rented, and
+		/// pets that can't battle.
+		type Locks: PetLocks;
+
+		/// Handler for abandoned pets, e.g. an insurance paying their owners.
+		type OnAbandon: OnPetAbandoned<Self::AccountId>;
+
+		/// The care rules used until governance sets others.
+		#[pallet::constant]
+		type DefaultCareRules: Get<CareParameters>;
+
+		/// The most weight deferred tasks can use in a block, out of what the block left.
+		#[pallet::constant]
+		type MaxIdleWeight: Get<Weight>;
+
+		/// The fee paid for each trade message.
+		#[pallet::constant]
+		type MessageFee: Get<BalanceOf<Self>>;
+
+		/// Handler for the trade message fees, `()` burns them.
+		type OnMessageFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The maximum length of a trade message, in bytes.
+		#[pallet::constant]
+		type MaxMessageLen: Get<u32>;
+
+		/// The length of a messaging era, the count of messages of each account starts over
+		/// with every era.
+		#[pallet::constant]
+		type MessageEra: Get<Self::BlockNumber>;
+
+		/// The maximum number of trade messages an account can send in an era.
+		#[pallet::constant]
+		type MaxMessagesPerEra: Get<u32>;
+
+		/// The maximum number of badges of the owner kept in the memorial of a pet.
+		#[pallet::constant]
+		type MaxMemorialBadges: Get<u32>;
+
+		/// Runs the care players schedule ahead of time, e.g. the scheduler pallet.
+		type CareScheduler: CareScheduler<Self::AccountId, Self::BlockNumber>;
+
+		/// The origin `CareScheduler` dispatches `run_scheduled_care` with, which players
+		/// can't sign.
+		type CareSchedulerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The fewest blocks between two runs of a repeating care action.
+		#[pallet::constant]
+		type MinCareInterval: Get<Self::BlockNumber>;
+
+		/// The wall-clock time of blocks, e.g. the timestamp pallet. Care is recorded with it,
+		/// so clients can tell when it happened even if block production paused meanwhile.
+		type UnixTime: UnixTime;
+
+		/// The items given to the owner of a pet whose care streak reaches a number of days,
+		/// e.g. more at 7, 30 and 100 days.
+		#[pallet::constant]
+		type StreakRewards: Get<Vec<(u32, Self::BundleItem, u32)>>;
+
+		/// The source of the rolls of the game, battles and the DNA of new pets. The game only
+		/// asks it for a hash per subject, so any source can be swapped in without touching the
+		/// game logic.
+		///
+		/// Which source is a security trade-off. `pallet_insecure_randomness_collective_flip`
+		/// needs nothing but is only fit for development: it mixes the hashes of the last 81
+		/// blocks, which their authors chose, so an author can withhold a block to reroll
+		/// a battle, and anyone can predict a roll made in the next block. A production chain
+		/// should use a VRF output authors can't choose, e.g.
+		/// `pallet_babe::RandomnessFromOneEpochAgo` once it runs BABE. Its output is known an
+		/// epoch ahead, which is fine as long as rolls are made when a call lands rather than
+		/// committed to in advance.
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+		/// The households of players. A pet neglected while the others of its household are
+		/// fed gets jealous, which cuts the experience the pet of the player's account earns.
+		type Household: HouseholdMembers<Self::AccountId>;
+
+		/// The maximum number of co-owners of a pet.
+		#[pallet::constant]
+		type MaxCoOwners: Get<u32>;
+
+		/// Who signs for a pet with co-owners to be traded.
+		#[pallet::constant]
+		type CoOwnerConsent: Get<CoOwnerConsent>;
+	}
+
+	#[pallet::extra_constants]
+	impl<T: Config> Pallet<T> {
+		/// The `pet_core::GAME_RULES_VERSION` stats and battles are computed with. Clients
+		/// check it against the rules they were built with.
+		#[allow(non_snake_case)]
+		pub fn GameRulesVersion() -> u32 {
+			pet_core::GAME_RULES_VERSION
+		}
 	}
 
 	#[derive(
@@ -33,6 +403,10 @@ pub mod pallet {
 		Rabbit,
 	}
 
+	impl Species {
+		pub const ALL: [Species; 3] = [Species::Turtle, Species::Snake, Species::Rabbit];
+	}
+
 	#[derive(
 		Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
 	)]
@@ -42,128 +416,3050 @@ pub mod pallet {
 		pub species: Species,
 	}
 
-	/// Onchain storage for pet info.
-	#[pallet::storage]
-	pub type PetsInfo<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, (PetId, PetInfo<T>)>;
+	/// The care a pet got, which its stats are derived from, kept together so they're read and
+	/// written at once.
+	#[derive(
+		Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
+	)]
+	pub struct Vitals<BlockNumber> {
+		/// The last time the pet was fed, or hatched, minted or adopted.
+		pub last_fed: BlockNumber,
+		/// `None` until the pet first sleeps.
+		pub last_slept: Option<BlockNumber>,
+		/// How many times the pet was fed and put to sleep in its life, since storage version 1.
+		pub feeds: u32,
+		pub sleeps: u32,
+		/// The wall-clock times of `last_fed` and `last_slept`, since storage version 2. `None`
+		/// for care from before, or from the genesis block which has no time.
+		pub fed_at: Option<Moment>,
+		pub slept_at: Option<Moment>,
+	}
 
-	/// Store the last feed time of a pet, use block number for time reference.
-	#[pallet::storage]
-	pub type LastFeedTime<T: Config> =
-		StorageMap<_, Blake2_128Concat, PetId, T::BlockNumber, ValueQuery>;
+	impl<BlockNumber: Copy + UniqueSaturatedInto<pet_core::BlockNumber>> Vitals<BlockNumber> {
+		/// What `pet_core` derives the stats of the pet from, with its `experience`.
+		pub fn care(&self, experience: u64) -> pet_core::Care {
+			pet_core::Care {
+				last_feed: Some(self.last_fed.saturated_into()),
+				last_sleep: self.last_slept.map(|block| block.saturated_into()),
+				experience,
+			}
+		}
+	}
 
-	/// Store the last sleep time of a pet, use block number for time reference.
-	#[pallet::storage]
-	pub type LastSleepTime<T: Config> = StorageMap<_, Blake2_128Concat, PetId, T::BlockNumber>;
+	/// How many pets of a species exist, and how many can, returned by `PetApi`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct SpeciesSupply {
+		pub species: Species,
+		pub population: u32,
+		/// `None` when the species is unlimited.
+		pub cap: Option<u32>,
+	}
 
-	/// Events for this module.
-	#[pallet::event]
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event<T: Config> {
-		/// A new pet is minted. \[owner, petid\]
-		PetMinted(T::AccountId, u32),
-		/// Pet is transfered. \[from, to, petid\]
-		PetTransfered(T::AccountId, T::AccountId, u32),
-		/// Pet is feeded. \[owner, petid\]
-		PetFeeded(T::AccountId, u32),
-		/// Pet is sleep. \[owner, petid\]
-		PetSleeped(T::AccountId, u32),
+	/// Why an account can't get a new pet right now, returned by `PetApi` so clients can
+	/// grey out buttons before a call fails. Each reason fails the calls with its `Error`.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum MintBlockReason {
+		/// The game is paused.
+		GamePaused,
+		/// The account already has a pet, the most an account can have.
+		MaxPetsReached,
+		/// The account is a game key, game keys never own pets.
+		GameKey,
+		/// The species reached its cap.
+		SpeciesSoldOut,
+		/// The account co-owns a pet, co-owners care for that one.
+		CoOwner,
 	}
 
-	/// Errors for this module.
-	#[pallet::error]
-	pub enum Error<T> {
-		AccountAlreadyHasPet,
-		AccountHasNoPet,
+	impl<T: Config> From<MintBlockReason> for Error<T> {
+		fn from(reason: MintBlockReason) -> Self {
+			match reason {
+				MintBlockReason::GamePaused => Error::<T>::GamePaused,
+				MintBlockReason::MaxPetsReached => Error::<T>::AccountAlreadyHasPet,
+				MintBlockReason::GameKey => Error::<T>::GameKeyCannotOwnPet,
+				MintBlockReason::CoOwner => Error::<T>::CoOwnerCannotOwnPet,
+				MintBlockReason::SpeciesSoldOut => Error::<T>::SpeciesSoldOut,
+			}
+		}
 	}
 
-	/// Dispatchables for this module.
-	#[pallet::call]
-	impl<T: Config> Pallet<T> {
-		/// Mint a new pet by reserving a certain mount of token.
-		/// One user can have many pets, but one pet can only be owned by one user.
-		/// The id of the pet is unique and can be set by its owner.
-		///
-		/// - name: The name of the pet
-		/// - speies: The species of the pet
-		/// - id: The id of the pet
-		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
-		pub fn mint(
-			origin: OriginFor<T>,
-			name: BoundedVec<u8, T::StringLimit>,
-			species: Species,
-			id: u32,
-		) -> DispatchResultWithPostInfo {
-			let sender = ensure_signed(origin)?;
+	/// What a pet did during a day, read by quests.
+	#[derive(
+		Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
+	)]
+	pub struct Activity {
+		/// The day the counters are for, block number divided by `BlocksPerDay`.
+		pub day: u32,
+		pub feeds: u32,
+		pub sleeps: u32,
+		pub battles_won: u32,
+	}
 
-			ensure!(!PetsInfo::<T>::contains_key(&sender), Error::<T>::AccountAlreadyHasPet);
+	/// The days in a row a pet was both fed and put to sleep.
+	#[derive(
+		Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
+	)]
+	pub struct CareStreak {
+		pub days: u32,
+		/// The last of these days, the streak goes on if the pet is cared for the day after.
+		pub last_day: u32,
+	}
 
-			let pet = PetInfo {
-				name,
-				species,
-			};
+	/// A pet whose owner stopped feeding it, waiting in the adoption pool.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct AbandonedPet<T: Config> {
+		pub owner: T::AccountId,
+		pub pet: PetInfo<T>,
+		/// When the pet was abandoned, the owner can reclaim it for `ReclaimWindow` blocks.
+		pub since: T::BlockNumber,
+	}
 
-			PetsInfo::<T>::insert(&sender, (id, pet));
+	/// What is kept of a pet its owner let go instead of reviving it.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Memorial<T: Config> {
+		pub pet: PetInfo<T>,
+		pub dna: Option<Dna>,
+		pub level: u32,
+		pub experience: u64,
+		/// The badges of the owner when the pet was let go.
+		pub achievements: BoundedVec<(T::BundleItem, u32), T::MaxMemorialBadges>,
+		pub since: T::BlockNumber,
+	}
 
-			Self::deposit_event(Event::PetMinted(sender, id));
+	/// Care a player can schedule for their pet ahead of time.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum CareAction {
+		Feed,
+		Sleep,
+	}
 
-			Ok(().into())
+	/// How an account is allowed to act on a pet.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Role {
+		/// The account owning the pet.
+		Owner,
+		/// A game key registered by the owner.
+		GameKey,
+		/// An account the owner shares the pet with, see `add_co_owner`.
+		CoOwner,
+	}
+
+	/// What a call does with a pet, checked against the role of the signer by `ensure_can`.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Action {
+		/// Feed the pet, put it to sleep or hatch it.
+		Care,
+		/// Challenge another pet with it.
+		Battle,
+		/// Sell the pet, give it away or let it go, and accept offers on it. The co-owners of
+		/// the pet have a say, see `CoOwnerConsent`.
+		Trade,
+		/// Turn down a trade: reject an offer or take the pet off the market. Whoever may
+		/// trade the pet may, without the consent of the others.
+		Decline,
+		/// Rename the pet, dress it, set its royalty or schedule its care.
+		Manage,
+	}
+
+	impl Action {
+		/// Whether an account with `role` may take the action. Game keys play with the pet,
+		/// co-owners also take part in trading it, only its owner can change it.
+		pub fn allows(self, role: Role) -> bool {
+			match (self, role) {
+				(_, Role::Owner) => true,
+				(Action::Care | Action::Battle, Role::GameKey | Role::CoOwner) => true,
+				(Action::Trade | Action::Decline, Role::CoOwner) => true,
+				(Action::Trade | Action::Decline | Action::Manage, Role::GameKey) => false,
+				(Action::Manage, Role::CoOwner) => false,
+			}
 		}
+	}
 
-		/// Transfer a pet
-		///
-		/// - receiver: The receiver of the pet
-		/// - id: The id of the pet
-		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
-		pub fn transfer(
-			origin: OriginFor<T>,
-			receiver: T::AccountId,
-		) -> DispatchResultWithPostInfo {
-			let sender = ensure_signed(origin)?;
+	/// Who signs for a pet with co-owners to be traded.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum CoOwnerConsent {
+		/// The owner or any co-owner, alone.
+		Either,
+		/// The owner and every co-owner: the one trading signs the call, the others consent
+		/// beforehand with `consent_to_trade`.
+		All,
+	}
+
+	/// The next block a scheduled care action runs at, and how often it repeats if it does.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub struct CareSchedule<BlockNumber> {
+		pub next: BlockNumber,
+		pub every: Option<BlockNumber>,
+	}
 
-			let (id, pet) = PetsInfo::<T>::get(&sender).ok_or(Error::<T>::AccountHasNoPet)?;
-			ensure!(!PetsInfo::<T>::contains_key(&receiver), Error::<T>::AccountAlreadyHasPet);
+	/// An offer to buy a pet, listed or not. The price is reserved from the buyer until the
+	/// offer is accepted, rejected, withdrawn or expires.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Offer<T: Config> {
+		pub buyer: T::AccountId,
+		pub price: BalanceOf<T>,
+		/// The offer is refunded when this block starts.
+		pub expires_at: T::BlockNumber,
+	}
 
-			PetsInfo::<T>::insert(&receiver, (id, pet));
-			PetsInfo::<T>::remove(&sender);
+	/// Where a pet comes from. The breeder receives `royalty` of every sale they aren't the
+	/// seller of.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct PetProvenance<T: Config> {
+		pub breeder: Option<T::AccountId>,
+		pub royalty: Permill,
+	}
 
-			Self::deposit_event(Event::PetTransfered(sender, receiver, id));
+	/// How fast the stats of pets drop and when pets get hungry or tired, see
+	/// `pet_core::Rules`. Clients read them to show the same stats as the chain.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub struct CareParameters {
+		/// Blocks a full stomach lasts.
+		pub fullness_lasts: u32,
+		/// Blocks a full night of sleep lasts.
+		pub energy_lasts: u32,
+		/// Fullness below which a pet is hungry, out of `pet_core::FULL`.
+		pub hungry_below: u16,
+		/// Energy below which a pet is tired, out of `pet_core::FULL`.
+		pub tired_below: u16,
+	}
 
-			Ok(().into())
+	impl From<CareParameters> for pet_core::Rules {
+		fn from(rules: CareParameters) -> Self {
+			pet_core::Rules {
+				fullness_lasts: rules.fullness_lasts,
+				energy_lasts: rules.energy_lasts,
+				hungry_below: rules.hungry_below,
+				tired_below: rules.tired_below,
+			}
 		}
+	}
 
-		/// Feed the pet.
-		///
-		/// - id: The id of the pet
-		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
-		pub fn feed(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
-			let sender = ensure_signed(origin)?;
-			let (id, _) = PetsInfo::<T>::get(&sender).ok_or(Error::<T>::AccountHasNoPet)?;
+	/// The combat stats of the pets referring to it, see `pet_core::StatsBlock`. What a pet
+	/// looks like, its species included, is kept apart in `PetInfo`.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub struct StatsBlock {
+		/// How strong the pet is in battles, out of `pet_core::FULL`.
+		pub power: u16,
+	}
+
+	impl StatsBlock {
+		/// The stats of pets without a registered stats block.
+		pub const NEUTRAL: StatsBlock = StatsBlock { power: pet_core::StatsBlock::NEUTRAL.power };
+	}
 
-			LastFeedTime::<T>::insert(id, frame_system::Pallet::<T>::block_number());
+	impl From<StatsBlock> for pet_core::StatsBlock {
+		fn from(block: StatsBlock) -> Self {
+			pet_core::StatsBlock { power: block.power }
+		}
+	}
 
-			Self::deposit_event(Event::PetFeeded(sender, id));
+	/// The roll of a battle and the weights of its pets, see `pet_core::BattleLog`. Clients
+	/// replay the battle from it.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub struct BattleLog {
+		pub seed: u64,
+		pub challenger_weight: u64,
+		pub opponent_weight: u64,
+	}
 
-			Ok(().into())
+	impl From<BattleLog> for pet_core::BattleLog {
+		fn from(log: BattleLog) -> Self {
+			pet_core::BattleLog {
+				seed: log.seed,
+				challenger_weight: log.challenger_weight,
+				opponent_weight: log.opponent_weight,
+			}
 		}
+	}
 
-		/// Pet is sleep.
-		///
-		/// - id: The id of the pet
-		#[pallet::call_index(3)]
-		#[pallet::weight(0)]
-		pub fn sleep(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
-			let sender = ensure_signed(origin)?;
-			let (id, _) = PetsInfo::<T>::get(&sender).ok_or(Error::<T>::AccountHasNoPet)?;
+	/// Work too heavy for a single block, done bit by bit in `on_idle`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum DeferredTask {
+		/// Move every pet left unfed for `AbandonAfter` blocks to the adoption pool, queued
+		/// each day.
+		AdoptionSweep,
+	}
 
-			LastSleepTime::<T>::insert(id, frame_system::Pallet::<T>::block_number());
+	/// The most tasks waiting in `DeferredTasks`.
+	pub const MAX_DEFERRED_TASKS: u32 = 8;
 
-			Self::deposit_event(Event::PetSleeped(sender, id));
+	/// The longest storage key a deferred task resumes from.
+	pub const MAX_CURSOR_LEN: u32 = 128;
 
-			Ok(().into())
-		}
+	/// The number of entries kept in the history of a pet, older ones are dropped.
+	pub const MAX_HISTORY: u32 = 10;
+
+	/// Something that happened to a pet, kept in its recent history.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub enum HistoryEntry<T: Config> {
+		/// The pet is minted.
+		Minted,
+		/// The egg hatched.
+		Hatched,
+		/// The pet got a new name.
+		Renamed,
+		/// The pet is given away, or sold when there is a price.
+		ChangedHands { from: T::AccountId, to: T::AccountId, price: Option<BalanceOf<T>> },
+		/// The pet is adopted from the adoption pool.
+		Adopted { adopter: T::AccountId },
+		/// The pet fought a battle.
+		Battled { opponent: PetId, won: bool },
+		/// The pet received the cosmetic of a seasonal event.
+		CosmeticClaimed { cosmetic: Cosmetic },
+	}
+
+	/// The stats of a pet at the block a profile is read at, see `pet_core::Stats`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct ProfileStats {
+		pub fullness: u16,
+		pub energy: u16,
+		pub level: u32,
+		pub hungry: bool,
+		pub tired: bool,
+	}
+
+	/// Everything a client shows about a pet, returned by `PetApi` in one call.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct PetProfile<T: Config> {
+		pub pet_id: PetId,
+		pub owner: T::AccountId,
+		pub info: PetInfo<T>,
+		pub dna: Option<Dna>,
+		pub stats: ProfileStats,
+		pub experience: u64,
+		pub last_feed: T::BlockNumber,
+		pub last_sleep: Option<T::BlockNumber>,
+		/// The block the pet can hatch from while it's an egg.
+		pub hatch_at: Option<T::BlockNumber>,
+		/// The price it's listed at, if it's for sale.
+		pub listing: Option<BalanceOf<T>>,
+		pub breeder: Option<T::AccountId>,
+		pub cosmetics: Vec<Cosmetic>,
+		pub equipped_cosmetic: Option<Cosmetic>,
+		/// The accessories of the owner.
+		pub equipment: Vec<(T::BundleItem, u32)>,
+		/// The badges of the owner.
+		pub achievements: Vec<(T::BundleItem, u32)>,
+		pub today: Activity,
+		/// The latest entries of its history, oldest first.
+		pub history: Vec<(T::BlockNumber, HistoryEntry<T>)>,
+		/// The wall-clock times of `last_feed` and `last_sleep`, when they're known. Last, so
+		/// clients reading profiles without them can still decode the rest.
+		pub fed_at: Option<Moment>,
+		pub slept_at: Option<Moment>,
+		/// After the wall-clock times for the same reason.
+		pub co_owners: Vec<T::AccountId>,
+		/// The combat stats the pet battles with, from its entry in `StatsBlocks`.
+		pub stats_block: StatsBlock,
+	}
+
+	/// A cosmetic given during a seasonal event. It's bound to the pet that claimed it: it
+	/// stays with the pet when the pet changes hands, and can't be moved on its own.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	pub enum Cosmetic {
+		SnowHat,
+		PumpkinMask,
+		FlowerCrown,
+		PartyHat,
+	}
 
+	/// When the cosmetic of a seasonal event can be claimed.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct SeasonalEvent<T: Config> {
+		/// The first block of the event.
+		pub starts: T::BlockNumber,
+		/// The event is over when this block starts.
+		pub ends: T::BlockNumber,
+	}
+
+	/// The data signed off chain to award a mini-game score to a pet.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct ScorePayload {
+		pub pet_id: PetId,
+		pub score: u32,
+		pub nonce: u64,
+	}
+
+	/// Onchain storage for pet info.
+	#[pallet::storage]
+	pub type PetsInfo<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, (PetId, PetInfo<T>)>;
+
+	/// Who listed each pet for sale, the owner or a co-owner. Signing the listing stands for
+	/// their consent to the sale, the others' is checked again when the pet is bought.
+	#[pallet::storage]
+	pub type ListedBy<T: Config> = StorageMap<_, Blake2_128Concat, PetId, T::AccountId>;
+
+	/// The owner of each pet, used to look a pet up by its id.
+	#[pallet::storage]
+	pub type PetOwners<T: Config> = StorageMap<_, Blake2_128Concat, PetId, T::AccountId>;
+
+	/// DNA of each pet, its visual traits are derived from it with `pet_gene::traits`.
+	#[pallet::storage]
+	pub type PetDna<T: Config> = StorageMap<_, Blake2_128Concat, PetId, Dna>;
+
+	/// The care times and counters of each pet, in block numbers.
+	#[pallet::storage]
+	pub type PetVitals<T: Config> =
+		StorageMap<_, Blake2_128Concat, PetId, Vitals<T::BlockNumber>, ValueQuery>;
+
+	/// Pets listed on the marketplace and their asking price.
+	#[pallet::storage]
+	pub type Listings<T: Config> = StorageMap<_, Blake2_128Concat, PetId, BalanceOf<T>>;
+
+	/// Items sold together with a listed pet, and how many of each.
+	#[pallet::storage]
+	pub type ListingBundles<T: Config> =
+		StorageMap<_, Blake2_128Concat, PetId, BoundedVec<(T::BundleItem, u32), T::MaxBundleItems>>;
+
+	#[pallet::type_value]
+	pub fn DefaultMarketFee<T: Config>() -> Permill {
+		T::DefaultMarketFee::get()
+	}
+
+	/// The share of every sale that goes to `OnMarketFee`.
+	#[pallet::storage]
+	pub type MarketFee<T: Config> = StorageValue<_, Permill, ValueQuery, DefaultMarketFee<T>>;
+
+	#[pallet::type_value]
+	pub fn DefaultMaxRoyalty<T: Config>() -> Permill {
+		T::DefaultMaxRoyalty::get()
+	}
+
+	/// The highest share of a sale paid as royalty, whatever the breeder asked.
+	#[pallet::storage]
+	pub type MaxRoyalty<T: Config> = StorageValue<_, Permill, ValueQuery, DefaultMaxRoyalty<T>>;
+
+	#[pallet::type_value]
+	pub fn DefaultCareRules<T: Config>() -> CareParameters {
+		T::DefaultCareRules::get()
+	}
+
+	/// The care rules in force.
+	#[pallet::storage]
+	pub type CareRules<T: Config> =
+		StorageValue<_, CareParameters, ValueQuery, DefaultCareRules<T>>;
+
+	/// The registry of combat stats. Changing an entry rebalances every pet referring to it.
+	#[pallet::storage]
+	pub type StatsBlocks<T: Config> = StorageMap<_, Blake2_128Concat, StatsBlockId, StatsBlock>;
+
+	/// The stats block new pets of each species get.
+	#[pallet::storage]
+	pub type SpeciesStatsBlocks<T: Config> = StorageMap<_, Blake2_128Concat, Species, StatsBlockId>;
+
+	/// The stats block of each pet, since storage version 3. It's kept out of `PetInfo`, which
+	/// is only about the looks of the pet. See `Pallet::stats_block` for the stats it resolves
+	/// to.
+	#[pallet::storage]
+	pub type PetStatsBlocks<T: Config> = StorageMap<_, Blake2_128Concat, PetId, StatsBlockId>;
+
+	/// The breeder of each minted pet and the royalty they ask on resales.
+	#[pallet::storage]
+	pub type Provenance<T: Config> = StorageMap<_, Blake2_128Concat, PetId, PetProvenance<T>>;
+
+	/// The key of the game server allowed to sign mini-game scores.
+	#[pallet::storage]
+	pub type GameServerKey<T: Config> = StorageValue<_, T::AccountId>;
+
+	/// The last score nonce used for each pet, a submission must use a higher one.
+	#[pallet::storage]
+	pub type ScoreNonce<T: Config> = StorageMap<_, Blake2_128Concat, PetId, u64, ValueQuery>;
+
+	/// Experience earned by each pet.
+	#[pallet::storage]
+	pub type Experience<T: Config> = StorageMap<_, Blake2_128Concat, PetId, u64, ValueQuery>;
+
+	/// Game keys and the account they act for. A game key can only feed and put the pet
+	/// to sleep, it can't transfer or sell it.
+	#[pallet::storage]
+	pub type GameKeys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	/// Game keys proposed by an account and not accepted yet, by key then proposing account.
+	/// An account only becomes a game key once it accepts, as it can't own a pet after.
+	#[pallet::storage]
+	pub type GameKeyProposals<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, ()>;
+
+	/// The game keys registered by each account.
+	#[pallet::storage]
+	pub type OwnerGameKeys<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<T::AccountId, T::MaxGameKeys>,
+		ValueQuery,
+	>;
+
+	/// What each pet did during the last day it was active.
+	#[pallet::storage]
+	pub type DailyActivity<T: Config> =
+		StorageMap<_, Blake2_128Concat, PetId, Activity, ValueQuery>;
+
+	/// The latest care streak of each pet, see `Pallet::care_streak` for the current one.
+	#[pallet::storage]
+	pub type CareStreaks<T: Config> =
+		StorageMap<_, Blake2_128Concat, PetId, CareStreak, ValueQuery>;
+
+	/// The number of pets of each species.
+	#[pallet::storage]
+	pub type SpeciesPopulation<T: Config> =
+		StorageMap<_, Blake2_128Concat, Species, u32, ValueQuery>;
+
+	/// The maximum number of pets of a species, species without a cap are unlimited.
+	#[pallet::storage]
+	pub type SpeciesCaps<T: Config> = StorageMap<_, Blake2_128Concat, Species, u32>;
+
+	/// Whether the game is paused. While it is, players can't call anything.
+	#[pallet::storage]
+	pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Pets left unfed for `AbandonAfter` blocks, waiting to be reclaimed or adopted.
+	#[pallet::storage]
+	pub type AdoptionPool<T: Config> = StorageMap<_, Blake2_128Concat, PetId, AbandonedPet<T>>;
+
+	/// How many times each pet was renamed, only the first rename is free.
+	#[pallet::storage]
+	pub type RenameCount<T: Config> = StorageMap<_, Blake2_128Concat, PetId, u32, ValueQuery>;
+
+	/// Open offers on each pet.
+	#[pallet::storage]
+	pub type Offers<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PetId,
+		BoundedVec<Offer<T>, T::MaxOffersPerPet>,
+		ValueQuery,
+	>;
+
+	/// The offers expiring at each block, refunded when the block starts.
+	#[pallet::storage]
+	pub type OfferExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<(PetId, T::AccountId), T::MaxOffersPerBlock>,
+		ValueQuery,
+	>;
+
+	/// The deposit reserved with each open offer, see `OfferDeposit`. Offers made before it
+	/// was introduced have none.
+	#[pallet::storage]
+	pub type OfferDeposits<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, PetId, Blake2_128Concat, T::AccountId, BalanceOf<T>>;
+
+	/// Pets that are still eggs, and the block from which they can hatch.
+	#[pallet::storage]
+	pub type Eggs<T: Config> = StorageMap<_, Blake2_128Concat, PetId, T::BlockNumber>;
+
+	/// Tasks waiting for spare block weight, the first one is in progress.
+	#[pallet::storage]
+	pub type DeferredTasks<T: Config> =
+		StorageValue<_, BoundedVec<DeferredTask, ConstU32<MAX_DEFERRED_TASKS>>, ValueQuery>;
+
+	/// The raw storage key the task in progress stopped at, it resumes after it.
+	#[pallet::storage]
+	pub type TaskCursor<T: Config> = StorageValue<_, BoundedVec<u8, ConstU32<MAX_CURSOR_LEN>>>;
+
+	/// The scheduled seasonal events, by the cosmetic they give.
+	#[pallet::storage]
+	pub type SeasonalEvents<T: Config> =
+		StorageMap<_, Blake2_128Concat, Cosmetic, SeasonalEvent<T>>;
+
+	/// The cosmetics each pet claimed, and the block they were claimed at.
+	#[pallet::storage]
+	pub type PetCosmetics<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, PetId, Blake2_128Concat, Cosmetic, T::BlockNumber>;
+
+	/// The cosmetic each pet wears, one of its own.
+	#[pallet::storage]
+	pub type EquippedCosmetic<T: Config> = StorageMap<_, Blake2_128Concat, PetId, Cosmetic>;
+
+	/// The era each account last sent a trade message in, and how many it sent in that era.
+	#[pallet::storage]
+	pub type MessagesSent<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (u32, u32), ValueQuery>;
+
+	/// The pets each account let go, by pet id.
+	#[pallet::storage]
+	pub type Memorials<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, PetId, Memorial<T>>;
+
+	/// The care each account scheduled for its pet, one schedule per action.
+	#[pallet::storage]
+	pub type ScheduledCare<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		CareAction,
+		CareSchedule<T::BlockNumber>,
+	>;
+
+	/// The latest `MAX_HISTORY` things that happened to each pet, and the block they did.
+	#[pallet::storage]
+	pub type PetHistory<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PetId,
+		BoundedVec<(T::BlockNumber, HistoryEntry<T>), ConstU32<MAX_HISTORY>>,
+		ValueQuery,
+	>;
+
+	/// The co-owners of each pet, who care for it with its owner.
+	#[pallet::storage]
+	pub type CoOwners<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PetId,
+		BoundedVec<T::AccountId, T::MaxCoOwners>,
+		ValueQuery,
+	>;
+
+	/// The pet each co-owner co-owns. Like game keys, co-owners act on a single pet.
+	#[pallet::storage]
+	pub type CoOwnedPets<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, PetId>;
+
+	/// The owner and co-owners of each pet consenting to trade it, under
+	/// `CoOwnerConsent::All`.
+	#[pallet::storage]
+	pub type TradeConsents<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, PetId, Blake2_128Concat, T::AccountId, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Pets that exist from genesis. \[owner, petid, name, species\]
+		pub pets: Vec<(T::AccountId, PetId, Vec<u8>, Species)>,
+		/// Initial population caps. \[species, cap\]
+		pub species_caps: Vec<(Species, u32)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { pets: Vec::new(), species_caps: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (species, cap) in &self.species_caps {
+				SpeciesCaps::<T>::insert(species, cap);
+			}
+			Pallet::<T>::register_species_stats_blocks();
+			for (owner, id, name, species) in &self.pets {
+				assert!(!PetsInfo::<T>::contains_key(owner), "Account owns more than one pet");
+				assert!(!PetOwners::<T>::contains_key(id), "Pet id is used more than once");
+
+				let name = name.clone().try_into().expect("Pet name is longer than StringLimit");
+				let pet = PetInfo { name, species: species.clone() };
+				PetsInfo::<T>::insert(owner, (*id, pet));
+				PetOwners::<T>::insert(id, owner);
+				PetDna::<T>::insert(id, Pallet::<T>::new_dna(*id));
+				PetVitals::<T>::insert(id, Vitals::default());
+				Pallet::<T>::assign_stats_block(*id, species);
+				Pallet::<T>::add_to_population(species)
+					.expect("Genesis pets exceed their species cap");
+			}
+		}
+	}
+
+	/// Events for this module. They are deposited with the accounts and pets they are about as
+	/// topics, see `Pallet::account_topic` and `Pallet::pet_topic`.
+	#[pallet::event]
+	pub enum Event<T: Config> {
+		/// A new pet is minted by `payer`, for another `owner` when it's a gift.
+		PetMinted { owner: T::AccountId, pet_id: PetId, species: Species, payer: T::AccountId },
+		/// Pet is transfered.
+		PetTransfered { from: T::AccountId, to: T::AccountId, pet_id: PetId },
+		/// Pet is feeded.
+		PetFeeded { owner: T::AccountId, pet_id: PetId },
+		/// Pet is sleep.
+		PetSleeped { owner: T::AccountId, pet_id: PetId },
+		/// Pet is listed for sale.
+		PetListed { seller: T::AccountId, pet_id: PetId, price: BalanceOf<T> },
+		/// Pet is listed for sale together with items of the seller's inventory.
+		BundleListed {
+			seller: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			items: BoundedVec<(T::BundleItem, u32), T::MaxBundleItems>,
+		},
+		/// Pet listing is cancelled.
+		ListingCancelled { seller: T::AccountId, pet_id: PetId },
+		/// Pet is sold, the seller receives `price - fee - royalty`.
+		Sold {
+			seller: T::AccountId,
+			buyer: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			fee: BalanceOf<T>,
+			royalty: BalanceOf<T>,
+		},
+		/// The market fee is changed.
+		MarketFeeSet { fee: Permill },
+		/// The breeder changed the royalty asked on the pet's resales.
+		RoyaltySet { breeder: T::AccountId, pet_id: PetId, royalty: Permill },
+		/// The royalty cap is changed.
+		MaxRoyaltySet { royalty: Permill },
+		/// The game server key is changed.
+		GameServerKeySet { key: Option<T::AccountId> },
+		/// A signed score is accepted, `experience` is the pet's new total.
+		ScoreSubmitted { pet_id: PetId, score: u32, experience: u64 },
+		/// `owner` proposes `key` as its game key, the key has to accept.
+		GameKeyProposed { owner: T::AccountId, key: T::AccountId },
+		/// A game key is registered.
+		GameKeyAdded { owner: T::AccountId, key: T::AccountId },
+		/// A game key is removed.
+		GameKeyRemoved { owner: T::AccountId, key: T::AccountId },
+		/// A battle is over, the winner earned `experience`. `log` replays it.
+		BattleResolved {
+			challenger: T::AccountId,
+			opponent: T::AccountId,
+			winner: PetId,
+			loser: PetId,
+			experience: u32,
+			log: BattleLog,
+		},
+		/// Pet is renamed, `fee` is zero for its first rename.
+		PetRenamed {
+			owner: T::AccountId,
+			pet_id: PetId,
+			name: BoundedVec<u8, T::StringLimit>,
+			fee: BalanceOf<T>,
+		},
+		/// The care times or the experience of a pet changed, with their new values.
+		StatsChanged {
+			pet_id: PetId,
+			last_feed: T::BlockNumber,
+			last_sleep: Option<T::BlockNumber>,
+			experience: u64,
+			/// The wall-clock times of `last_feed` and `last_sleep`, when they're known.
+			fed_at: Option<Moment>,
+			slept_at: Option<Moment>,
+		},
+		/// The game is paused.
+		GamePaused,
+		/// The game is running again.
+		GameUnpaused,
+		/// The population cap of a species is changed, `None` removes it.
+		SpeciesCapSet { species: Species, cap: Option<u32> },
+		/// Pet went unfed for too long and is moved to the adoption pool.
+		PetAbandoned { owner: T::AccountId, pet_id: PetId },
+		/// The owner of an abandoned pet took it back.
+		PetReclaimed { owner: T::AccountId, pet_id: PetId },
+		/// An abandoned pet went back to its owner, `payer` paid the adoption fee.
+		PetRevived { owner: T::AccountId, payer: T::AccountId, pet_id: PetId },
+		/// Pet is adopted from the adoption pool.
+		PetAdopted { adopter: T::AccountId, previous_owner: T::AccountId, pet_id: PetId },
+		/// A new pet starts as an egg, it can hatch from `hatch_at`.
+		EggLaid { owner: T::AccountId, pet_id: PetId, hatch_at: T::BlockNumber },
+		/// An incubator item brought the hatch block of an egg closer.
+		EggWarmed { owner: T::AccountId, pet_id: PetId, hatch_at: T::BlockNumber },
+		/// The egg hatched, the pet can be cared for.
+		PetHatched { owner: T::AccountId, pet_id: PetId },
+		/// An offer is made on a pet, its price is reserved until `expires_at`.
+		OfferMade {
+			buyer: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			expires_at: T::BlockNumber,
+		},
+		/// The buyer took back its offer.
+		OfferWithdrawn { buyer: T::AccountId, pet_id: PetId },
+		/// The owner turned an offer down, it's refunded.
+		OfferRejected { owner: T::AccountId, buyer: T::AccountId, pet_id: PetId },
+		/// The owner accepted an offer, the seller receives `price - fee - royalty`.
+		OfferAccepted {
+			seller: T::AccountId,
+			buyer: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			fee: BalanceOf<T>,
+			royalty: BalanceOf<T>,
+		},
+		/// An offer ran out of time and is refunded.
+		OfferExpired { buyer: T::AccountId, pet_id: PetId },
+		/// The care rules are changed.
+		CareRulesSet { rules: CareParameters },
+		/// A deferred task went through all its work.
+		DeferredTaskDone { task: DeferredTask },
+		/// A seasonal event is scheduled, its cosmetic can be claimed from `starts` until
+		/// `ends`.
+		SeasonalEventScheduled { cosmetic: Cosmetic, starts: T::BlockNumber, ends: T::BlockNumber },
+		/// A seasonal event is cancelled, cosmetics already claimed are kept.
+		SeasonalEventCancelled { cosmetic: Cosmetic },
+		/// A pet received the cosmetic of a seasonal event.
+		CosmeticClaimed { owner: T::AccountId, pet_id: PetId, cosmetic: Cosmetic },
+		/// A pet put on one of its cosmetics, or took it off when `None`.
+		CosmeticEquipped { owner: T::AccountId, pet_id: PetId, cosmetic: Option<Cosmetic> },
+		/// A message between the owner of a pet on the market and someone interested in it.
+		TradeMessageSent {
+			pet_id: PetId,
+			from: T::AccountId,
+			to: T::AccountId,
+			text: BoundedVec<u8, T::MaxMessageLen>,
+			fee: BalanceOf<T>,
+		},
+		/// The owner let the pet go instead of reviving it, it's kept in `Memorials`.
+		PetMemorialized { owner: T::AccountId, pet_id: PetId, level: u32 },
+		/// Care of the pet of `owner` is scheduled at block `when`, then every `every` blocks.
+		CareScheduled {
+			owner: T::AccountId,
+			action: CareAction,
+			when: T::BlockNumber,
+			every: Option<T::BlockNumber>,
+		},
+		/// A care schedule is cancelled.
+		CareCancelled { owner: T::AccountId, action: CareAction },
+		/// Scheduled care ran, `done` is false when it couldn't, e.g. while the game is paused
+		/// or the pet is gone. A repeating schedule goes on either way.
+		ScheduledCareRan { owner: T::AccountId, action: CareAction, done: bool },
+		/// `by` refunded `offers` stale offers on a pet, and was paid their deposits, `reward`.
+		PetCleanedUp { by: T::AccountId, pet_id: PetId, offers: u32, reward: BalanceOf<T> },
+		/// The care streak of a pet reached `days`, and its owner got `amount` of `item`.
+		StreakRewarded {
+			owner: T::AccountId,
+			pet_id: PetId,
+			days: u32,
+			item: T::BundleItem,
+			amount: u32,
+		},
+		/// The owner of a pet shares it with `co_owner`.
+		CoOwnerAdded { owner: T::AccountId, pet_id: PetId, co_owner: T::AccountId },
+		/// `co_owner` no longer co-owns the pet, removed by the owner or leaving.
+		CoOwnerRemoved { owner: T::AccountId, pet_id: PetId, co_owner: T::AccountId },
+		/// The owner or a co-owner of a pet consents to trade it, or takes it back.
+		TradeConsentSet { account: T::AccountId, pet_id: PetId, consent: bool },
+		/// An entry of the stats block registry is registered or changed.
+		StatsBlockSet { block_id: StatsBlockId, block: StatsBlock },
+		/// New pets of a species get another stats block.
+		SpeciesStatsBlockSet { species: Species, block_id: StatsBlockId },
+	}
+
+	impl<T: Config> Event<T> {
+		/// The accounts and pets the event is about.
+		fn subjects(&self) -> (Vec<&T::AccountId>, Vec<PetId>) {
+			match self {
+				Event::PetMinted { owner, pet_id, payer, .. } if owner != payer =>
+					(vec![owner, payer], vec![*pet_id]),
+				Event::PetMinted { owner, pet_id, .. } |
+				Event::PetFeeded { owner, pet_id } |
+				Event::PetSleeped { owner, pet_id } |
+				Event::PetRenamed { owner, pet_id, .. } |
+				Event::PetAbandoned { owner, pet_id } |
+				Event::PetReclaimed { owner, pet_id } |
+				Event::EggLaid { owner, pet_id, .. } |
+				Event::EggWarmed { owner, pet_id, .. } |
+				Event::PetHatched { owner, pet_id } |
+				Event::PetListed { seller: owner, pet_id, .. } |
+				Event::BundleListed { seller: owner, pet_id, .. } |
+				Event::ListingCancelled { seller: owner, pet_id } |
+				Event::RoyaltySet { breeder: owner, pet_id, .. } |
+				Event::OfferMade { buyer: owner, pet_id, .. } |
+				Event::OfferWithdrawn { buyer: owner, pet_id } |
+				Event::OfferExpired { buyer: owner, pet_id } |
+				Event::CosmeticClaimed { owner, pet_id, .. } |
+				Event::CosmeticEquipped { owner, pet_id, .. } |
+				Event::PetMemorialized { owner, pet_id, .. } |
+				Event::PetCleanedUp { by: owner, pet_id, .. } |
+				Event::TradeConsentSet { account: owner, pet_id, .. } |
+				Event::StreakRewarded { owner, pet_id, .. } => (vec![owner], vec![*pet_id]),
+				Event::PetTransfered { from, to, pet_id } |
+				Event::Sold { seller: from, buyer: to, pet_id, .. } |
+				Event::OfferAccepted { seller: from, buyer: to, pet_id, .. } |
+				Event::OfferRejected { owner: from, buyer: to, pet_id } |
+				Event::PetRevived { owner: from, payer: to, pet_id } |
+				Event::PetAdopted { previous_owner: from, adopter: to, pet_id } |
+				Event::TradeMessageSent { from, to, pet_id, .. } |
+				Event::CoOwnerAdded { owner: from, pet_id, co_owner: to } |
+				Event::CoOwnerRemoved { owner: from, pet_id, co_owner: to } =>
+					(vec![from, to], vec![*pet_id]),
+				Event::BattleResolved { challenger, opponent, winner, loser, .. } =>
+					(vec![challenger, opponent], vec![*winner, *loser]),
+				Event::GameKeyProposed { owner, key } |
+				Event::GameKeyAdded { owner, key } |
+				Event::GameKeyRemoved { owner, key } =>
+					(vec![owner, key], vec![]),
+				Event::CareScheduled { owner, .. } |
+				Event::CareCancelled { owner, .. } |
+				Event::ScheduledCareRan { owner, .. } => (vec![owner], vec![]),
+				Event::ScoreSubmitted { pet_id, .. } | Event::StatsChanged { pet_id, .. } =>
+					(vec![], vec![*pet_id]),
+				// Settings of the whole game
+				_ => (vec![], vec![]),
+			}
+		}
+
+		/// The entries the event adds to the history of its pets. Care and offers are left
+		/// out, they happen too often to tell the story of a pet.
+		fn history(&self) -> Vec<(PetId, HistoryEntry<T>)> {
+			match self {
+				Event::PetMinted { pet_id, .. } => vec![(*pet_id, HistoryEntry::Minted)],
+				Event::PetHatched { pet_id, .. } => vec![(*pet_id, HistoryEntry::Hatched)],
+				Event::PetRenamed { pet_id, .. } => vec![(*pet_id, HistoryEntry::Renamed)],
+				Event::PetTransfered { from, to, pet_id } => vec![(
+					*pet_id,
+					HistoryEntry::ChangedHands { from: from.clone(), to: to.clone(), price: None },
+				)],
+				Event::Sold { seller, buyer, pet_id, price, .. } |
+				Event::OfferAccepted { seller, buyer, pet_id, price, .. } => vec![(
+					*pet_id,
+					HistoryEntry::ChangedHands {
+						from: seller.clone(),
+						to: buyer.clone(),
+						price: Some(*price),
+					},
+				)],
+				Event::PetAdopted { adopter, pet_id, .. } =>
+					vec![(*pet_id, HistoryEntry::Adopted { adopter: adopter.clone() })],
+				Event::BattleResolved { winner, loser, .. } => vec![
+					(*winner, HistoryEntry::Battled { opponent: *loser, won: true }),
+					(*loser, HistoryEntry::Battled { opponent: *winner, won: false }),
+				],
+				Event::CosmeticClaimed { pet_id, cosmetic, .. } =>
+					vec![(*pet_id, HistoryEntry::CosmeticClaimed { cosmetic: *cosmetic })],
+				_ => vec![],
+			}
+		}
+	}
+
+	/// Errors for this module.
+	#[pallet::error]
+	pub enum Error<T> {
+		AccountAlreadyHasPet,
+		AccountHasNoPet,
+		PetIdAlreadyUsed,
+		PetNotFound,
+		PetNotForSale,
+		CannotBuyOwnPet,
+		InvalidScoreSignature,
+		StaleScoreNonce,
+		ScoreTooHigh,
+		GameKeyInUse,
+		GameKeyNotFound,
+		TooManyGameKeys,
+		GameKeyCannotOwnPet,
+		OpponentHasNoPet,
+		CannotBattleOwnPet,
+		GamePaused,
+		SpeciesSoldOut,
+		PetNotNeglected,
+		PetNotAbandoned,
+		NotPreviousOwner,
+		ReclaimWindowClosed,
+		ReclaimWindowOpen,
+		PetIsEgg,
+		NotAnEgg,
+		EggNotReady,
+		CannotOfferOnOwnPet,
+		OfferAlreadyMade,
+		OfferNotFound,
+		TooManyOffers,
+		EmptyBundle,
+		BundleItemsMissing,
+		NotBreeder,
+		RoyaltyTooHigh,
+		PetLocked,
+		InvalidCareRules,
+		NotOwner,
+		NotAuthorized,
+		NameTooLong,
+		InvalidSeasonalEvent,
+		SeasonalEventNotFound,
+		SeasonalEventNotActive,
+		CosmeticAlreadyClaimed,
+		CosmeticNotOwned,
+		PetBenched,
+		PetNotOnMarket,
+		NotTradeParty,
+		EmptyMessage,
+		MessageTooLong,
+		TooManyMessages,
+		NameNotUtf8,
+		CareInThePast,
+		CareTooFrequent,
+		CareNotScheduled,
+		CareNotDue,
+		NothingToCleanUp,
+		TooManyCoOwners,
+		/// The account owns a pet, is a game key or already co-owns a pet.
+		CoOwnerInUse,
+		CoOwnerNotFound,
+		CoOwnerCannotOwnPet,
+		/// A co-owner or the owner of the pet didn't consent to trade it.
+		TradeNotConsented,
+		/// The power of a stats block is zero or above `pet_core::StatsBlock::MAX_POWER`.
+		InvalidStatsBlock,
+		StatsBlockNotFound,
+		GameKeyNotProposed,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Refund the offers expiring at this block, and queue the adoption sweep when a day
+		/// starts.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let expiring = OfferExpiries::<T>::take(now);
+			let mut weight = T::WeightInfo::expire_offers(expiring.len() as u32);
+			for (pet_id, buyer) in expiring {
+				if Self::take_offer(pet_id, &buyer).is_some() {
+					Self::deposit_event(Event::OfferExpired { buyer, pet_id });
+				}
+			}
+
+			if (now % T::BlocksPerDay::get().max(1u32.into())).is_zero() {
+				Self::defer(DeferredTask::AdoptionSweep);
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(1, 1));
+			}
+			weight
+		}
+
+		/// Work on the deferred tasks with the weight the block left, up to `MaxIdleWeight`.
+		/// Nothing is done while the game is paused, owners couldn't feed their pets then.
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let limit = remaining_weight.min(T::MaxIdleWeight::get());
+			// Reading the pause flag, the queue and the cursor, then storing where the task
+			// stopped or closing it
+			let mut used = T::WeightInfo::adoption_sweep(0);
+			if limit.any_lt(used) {
+				return Weight::zero();
+			}
+			if Paused::<T>::get() {
+				return T::DbWeight::get().reads(1);
+			}
+			let Some(task) = DeferredTasks::<T>::get().first().cloned() else {
+				return T::DbWeight::get().reads(2);
+			};
+
+			let done = match task {
+				DeferredTask::AdoptionSweep => Self::sweep_neglected(now, limit, &mut used),
+			};
+			if done {
+				DeferredTasks::<T>::mutate(|tasks| {
+					if !tasks.is_empty() {
+						tasks.remove(0);
+					}
+				});
+				TaskCursor::<T>::kill();
+				Self::deposit_event(Event::DeferredTaskDone { task });
+			}
+			used
+		}
+	}
+
+	/// Dispatchables for this module.
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Mint a new pet by reserving a certain mount of token.
+		/// One user can have many pets, but one pet can only be owned by one user.
+		/// The id of the pet is unique and can be set by its owner.
+		///
+		/// - name: The name of the pet
+		/// - speies: The species of the pet
+		/// - id: The id of the pet
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn mint(
+			origin: OriginFor<T>,
+			name: Vec<u8>,
+			species: Species,
+			id: u32,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			Self::do_mint(&sender, &sender, name, species, id)?;
+
+			Ok(().into())
+		}
+
+		/// Transfer a pet
+		///
+		/// - receiver: The receiver of the pet
+		/// - id: The id of the pet
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			receiver: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+
+			let (sender, id, pet) = Self::ensure_can(origin, None, Action::Trade)?;
+			Self::ensure_unlocked(id)?;
+			Self::ensure_can_own(&receiver)?;
+
+			Self::move_pet(&sender, &receiver, id, pet);
+
+			Self::deposit_event(Event::PetTransfered { from: sender, to: receiver, pet_id: id });
+
+			Ok(().into())
+		}
+
+		/// Feed the pet. Can be called by the owner or one of its game keys.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::feed())]
+		pub fn feed(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (owner, _, _) = Self::ensure_can(origin, None, Action::Care)?;
+			Self::feed_pet(&owner)?;
+
+			Ok(().into())
+		}
+
+		/// Pet is sleep. Can be called by the owner or one of its game keys.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::sleep())]
+		pub fn sleep(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (owner, _, _) = Self::ensure_can(origin, None, Action::Care)?;
+			Self::sleep_pet(&owner)?;
+
+			Ok(().into())
+		}
+
+		/// List the pet for sale on the marketplace, or update its price.
+		///
+		/// - price: The price asked for the pet
+		#[pallet::call_index(4)]
+		#[pallet::weight(0)]
+		pub fn list_for_sale(
+			origin: OriginFor<T>,
+			price: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let signer = ensure_signed(origin.clone())?;
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Trade)?;
+			Self::ensure_unlocked(id)?;
+
+			Listings::<T>::insert(id, price);
+			ListingBundles::<T>::remove(id);
+			ListedBy::<T>::insert(id, signer);
+
+			Self::deposit_event(Event::PetListed { seller: sender, pet_id: id, price });
+
+			Ok(().into())
+		}
+
+		/// Remove the pet from the marketplace.
+		#[pallet::call_index(5)]
+		#[pallet::weight(0)]
+		pub fn cancel_listing(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Decline)?;
+			ensure!(Listings::<T>::contains_key(id), Error::<T>::PetNotForSale);
+
+			Listings::<T>::remove(id);
+			ListingBundles::<T>::remove(id);
+			ListedBy::<T>::remove(id);
+
+			Self::deposit_event(Event::ListingCancelled { seller: sender, pet_id: id });
+
+			Ok(().into())
+		}
+
+		/// Buy a listed pet, and the items of its bundle if it was listed as one. The market
+		/// fee is taken from the price and handed to `OnMarketFee`, the breeder's royalty goes
+		/// to the breeder and the rest to the seller.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(6)]
+		#[pallet::weight(0)]
+		pub fn buy(origin: OriginFor<T>, id: PetId) -> DispatchResultWithPostInfo {
+			let buyer = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let price = Listings::<T>::get(id).ok_or(Error::<T>::PetNotForSale)?;
+			let seller = PetOwners::<T>::get(id).ok_or(Error::<T>::PetNotFound)?;
+			Self::ensure_unlocked(id)?;
+			ensure!(buyer != seller, Error::<T>::CannotBuyOwnPet);
+			Self::ensure_can_own(&buyer)?;
+			let (_, pet) = PetsInfo::<T>::get(&seller).ok_or(Error::<T>::PetNotFound)?;
+			// The co-owners may have taken their consent back since the pet was listed
+			let lister = ListedBy::<T>::get(id).unwrap_or_else(|| seller.clone());
+			Self::ensure_trade_consent(id, &seller, &lister)?;
+
+			let (fee, royalty) = Self::pay_for_pet(&buyer, &seller, id, price)?;
+
+			// The seller may have used or sold some of the items since listing them
+			if let Some(items) = ListingBundles::<T>::get(id) {
+				Self::ensure_bundle_owned(&seller, &items)?;
+				for (item, amount) in items {
+					T::Items::transfer(&seller, &buyer, item, amount)?;
+				}
+			}
+			Self::move_pet(&seller, &buyer, id, pet);
+
+			Self::deposit_event(Event::Sold { seller, buyer, pet_id: id, price, fee, royalty });
+
+			Ok(().into())
+		}
+
+		/// Set the share of each sale taken as market fee.
+		///
+		/// - fee: The new market fee
+		#[pallet::call_index(7)]
+		#[pallet::weight(0)]
+		pub fn set_market_fee(origin: OriginFor<T>, fee: Permill) -> DispatchResultWithPostInfo {
+			T::MarketFeeOrigin::ensure_origin(origin)?;
+
+			MarketFee::<T>::put(fee);
+
+			Self::deposit_event(Event::MarketFeeSet { fee });
+
+			Ok(().into())
+		}
+
+		/// Set the royalty cap, royalties asked above it are paid at the cap.
+		///
+		/// - royalty: The new royalty cap
+		#[pallet::call_index(26)]
+		#[pallet::weight(0)]
+		pub fn set_max_royalty(
+			origin: OriginFor<T>,
+			royalty: Permill,
+		) -> DispatchResultWithPostInfo {
+			T::MarketFeeOrigin::ensure_origin(origin)?;
+
+			MaxRoyalty::<T>::put(royalty);
+
+			Self::deposit_event(Event::MaxRoyaltySet { royalty });
+
+			Ok(().into())
+		}
+
+		/// Set the key of the game server that signs mini-game scores.
+		///
+		/// - key: The new key, `None` disables server signed scores
+		#[pallet::call_index(8)]
+		#[pallet::weight(0)]
+		pub fn set_game_server_key(
+			origin: OriginFor<T>,
+			key: Option<T::AccountId>,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+
+			GameServerKey::<T>::set(key.clone());
+
+			Self::deposit_event(Event::GameServerKeySet { key });
+
+			Ok(().into())
+		}
+
+		/// Award a mini-game score to a pet as experience, less the cut of its household's
+		/// jealousy.
+		/// The `ScorePayload` must be signed by the game server key or one of the owner's game
+		/// keys, and its nonce must be higher than the last one used for the pet so it can't be
+		/// replayed.
+		///
+		/// - pet_id: The id of the pet
+		/// - score: The score reached in the mini-game
+		/// - nonce: The nonce of the score
+		/// - signature: The signature of the encoded `ScorePayload`
+		#[pallet::call_index(9)]
+		#[pallet::weight(0)]
+		pub fn submit_score(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			score: u32,
+			nonce: u64,
+			signature: T::OffchainSignature,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			ensure!(PetOwners::<T>::contains_key(pet_id), Error::<T>::PetNotFound);
+			ensure!(score <= T::MaxScore::get(), Error::<T>::ScoreTooHigh);
+			ensure!(nonce > ScoreNonce::<T>::get(pet_id), Error::<T>::StaleScoreNonce);
+
+			let payload = ScorePayload { pet_id, score, nonce };
+			ensure!(Self::is_score_signer(&payload, &signature), Error::<T>::InvalidScoreSignature);
+
+			ScoreNonce::<T>::insert(pet_id, nonce);
+			let gain = Self::experience_gain(pet_id, score as u64);
+			let experience = Experience::<T>::mutate(pet_id, |xp| {
+				*xp = xp.saturating_add(gain);
+				*xp
+			});
+
+			Self::deposit_event(Event::ScoreSubmitted { pet_id, score, experience });
+			Self::deposit_stats(pet_id);
+
+			Ok(().into())
+		}
+
+		/// Propose a game key that can feed the pet and put it to sleep on behalf of the
+		/// sender, so a hot key can be kept in the client without exposing the main key. The
+		/// key is only registered once it accepts with `accept_game_key`, as a game key can't
+		/// own a pet.
+		///
+		/// - key: The account of the game key
+		#[pallet::call_index(10)]
+		#[pallet::weight(0)]
+		pub fn propose_game_key(
+			origin: OriginFor<T>,
+			key: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+			// Game keys act for their owner, not for keys of their own
+			ensure!(!GameKeys::<T>::contains_key(&sender), Error::<T>::NotAuthorized);
+			ensure!(key != sender, Error::<T>::GameKeyInUse);
+
+			GameKeyProposals::<T>::insert(&key, &sender, ());
+
+			Self::deposit_event(Event::GameKeyProposed { owner: sender, key });
+
+			Ok(().into())
+		}
+
+		/// Remove a game key. The owner removes one of its keys, and a game key can remove
+		/// itself by passing its own account.
+		///
+		/// - key: The account of the game key
+		#[pallet::call_index(11)]
+		#[pallet::weight(0)]
+		pub fn remove_game_key(
+			origin: OriginFor<T>,
+			key: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let owner = match GameKeys::<T>::get(&sender) {
+				Some(owner) if key == sender => owner,
+				_ => sender,
+			};
+			Self::revoke_game_key(&owner, key)?;
+
+			Ok(().into())
+		}
+
+		/// Challenge the pet of another account. The battle is resolved right away, each pet's
+		/// chance to win grows with its experience and drops when it's hungry or tired, and the
+		/// winner earns `BattleExperience`, less the cut of its household's jealousy.
+		/// Can be called by the owner or one of its game keys.
+		///
+		/// - opponent: The owner of the challenged pet
+		#[pallet::call_index(12)]
+		#[pallet::weight(0)]
+		pub fn challenge(
+			origin: OriginFor<T>,
+			opponent: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (challenger, _, _) = Self::ensure_can(origin, None, Action::Battle)?;
+
+			Self::battle(challenger, opponent)?;
+
+			Ok(().into())
+		}
+
+		/// Rename the pet. The first rename is free, the next ones cost `RenameFee`, which
+		/// goes to `OnRenameFee`.
+		///
+		/// - name: The new name of the pet
+		#[pallet::call_index(13)]
+		#[pallet::weight(0)]
+		pub fn rename(origin: OriginFor<T>, name: Vec<u8>) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let name = Self::bounded_name(name)?;
+
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Manage)?;
+			let fee =
+				if RenameCount::<T>::get(id) == 0 { Zero::zero() } else { T::RenameFee::get() };
+			if !fee.is_zero() {
+				let imbalance = T::Currency::withdraw(
+					&sender,
+					fee,
+					WithdrawReasons::FEE,
+					ExistenceRequirement::KeepAlive,
+				)?;
+				T::OnRenameFee::on_unbalanced(imbalance);
+			}
+
+			PetsInfo::<T>::mutate(&sender, |pet| {
+				if let Some((_, info)) = pet {
+					info.name = name.clone();
+				}
+			});
+			RenameCount::<T>::mutate(id, |count| count.saturating_inc());
+
+			Self::deposit_event(Event::PetRenamed { owner: sender, pet_id: id, name, fee });
+
+			Ok(().into())
+		}
+
+		/// Pause or unpause the game. While it's paused every call of the players fails, so
+		/// operators can freeze the game during an incident.
+		///
+		/// - paused: Whether the game is paused
+		#[pallet::call_index(14)]
+		#[pallet::weight(0)]
+		pub fn set_paused(origin: OriginFor<T>, paused: bool) -> DispatchResultWithPostInfo {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			Paused::<T>::put(paused);
+
+			Self::deposit_event(if paused { Event::GamePaused } else { Event::GameUnpaused });
+
+			Ok(().into())
+		}
+
+		/// Set how many pets of a species can exist. Lowering the cap under the current
+		/// population only stops new pets of that species.
+		///
+		/// - species: The capped species
+		/// - cap: The new cap, `None` makes the species unlimited
+		#[pallet::call_index(15)]
+		#[pallet::weight(0)]
+		pub fn set_species_cap(
+			origin: OriginFor<T>,
+			species: Species,
+			cap: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+
+			SpeciesCaps::<T>::set(&species, cap);
+
+			Self::deposit_event(Event::SpeciesCapSet { species, cap });
+
+			Ok(().into())
+		}
+
+		/// Move a pet left unfed for `AbandonAfter` blocks to the adoption pool. Anyone can
+		/// report a neglected pet, its owner loses it and can mint or buy another one.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(16)]
+		#[pallet::weight(0)]
+		pub fn abandon(origin: OriginFor<T>, id: PetId) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let owner = PetOwners::<T>::get(id).ok_or(Error::<T>::PetNotFound)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			Self::abandon_pet(id, owner, now)?;
+
+			Ok(().into())
+		}
+
+		/// Take back an abandoned pet, only its previous owner can during `ReclaimWindow`.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(17)]
+		#[pallet::weight(0)]
+		pub fn reclaim(origin: OriginFor<T>, id: PetId) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let abandoned = AdoptionPool::<T>::get(id).ok_or(Error::<T>::PetNotAbandoned)?;
+			ensure!(abandoned.owner == sender, Error::<T>::NotPreviousOwner);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now.saturating_sub(abandoned.since) < T::ReclaimWindow::get(),
+				Error::<T>::ReclaimWindowClosed
+			);
+			Self::ensure_can_own(&sender)?;
+
+			Self::take_from_pool(&sender, id, abandoned.pet);
+
+			Self::deposit_event(Event::PetReclaimed { owner: sender, pet_id: id });
+			Self::deposit_stats(id);
+
+			Ok(().into())
+		}
+
+		/// Adopt a pet from the adoption pool once its `ReclaimWindow` is over, paying the
+		/// `AdoptionFee`.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(18)]
+		#[pallet::weight(0)]
+		pub fn adopt(origin: OriginFor<T>, id: PetId) -> DispatchResultWithPostInfo {
+			let adopter = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let abandoned = AdoptionPool::<T>::get(id).ok_or(Error::<T>::PetNotAbandoned)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now.saturating_sub(abandoned.since) >= T::ReclaimWindow::get(),
+				Error::<T>::ReclaimWindowOpen
+			);
+			Self::ensure_can_own(&adopter)?;
+
+			let imbalance = T::Currency::withdraw(
+				&adopter,
+				T::AdoptionFee::get(),
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::OnMarketFee::on_unbalanced(imbalance);
+
+			Self::take_from_pool(&adopter, id, abandoned.pet);
+
+			Self::deposit_event(Event::PetAdopted {
+				adopter,
+				previous_owner: abandoned.owner,
+				pet_id: id,
+			});
+			Self::deposit_stats(id);
+
+			Ok(().into())
+		}
+
+		/// Hatch the egg once its hatch block is reached, the pet counts as fed from then on.
+		/// Can be called by the owner or one of its game keys.
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::hatch())]
+		pub fn hatch(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+
+			let (owner, id, _) = Self::ensure_can(origin, None, Action::Care)?;
+			let hatch_at = Eggs::<T>::get(id).ok_or(Error::<T>::NotAnEgg)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now >= hatch_at, Error::<T>::EggNotReady);
+
+			Eggs::<T>::remove(id);
+			PetVitals::<T>::mutate(id, |vitals| {
+				vitals.last_fed = now;
+				vitals.fed_at = Self::moment();
+			});
+
+			Self::deposit_event(Event::PetHatched { owner, pet_id: id });
+			Self::deposit_stats(id);
+
+			Ok(().into())
+		}
+
+		/// Offer to buy a pet, whether it's listed or not. The price and `OfferDeposit` are
+		/// reserved until the owner accepts or rejects the offer, or it expires after
+		/// `OfferDuration` blocks.
+		///
+		/// - pet_id: The id of the pet
+		/// - price: The price offered
+		#[pallet::call_index(20)]
+		#[pallet::weight(0)]
+		pub fn make_offer(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let buyer = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			let owner = PetOwners::<T>::get(pet_id).ok_or(Error::<T>::PetNotFound)?;
+			ensure!(owner != buyer, Error::<T>::CannotOfferOnOwnPet);
+			Self::ensure_can_own(&buyer)?;
+
+			let mut offers = Offers::<T>::get(pet_id);
+			ensure!(!offers.iter().any(|offer| offer.buyer == buyer), Error::<T>::OfferAlreadyMade);
+			let expires_at =
+				frame_system::Pallet::<T>::block_number().saturating_add(T::OfferDuration::get());
+			offers
+				.try_push(Offer { buyer: buyer.clone(), price, expires_at })
+				.map_err(|_| Error::<T>::TooManyOffers)?;
+			OfferExpiries::<T>::try_mutate(expires_at, |expiring| {
+				expiring.try_push((pet_id, buyer.clone()))
+			})
+			.map_err(|_| Error::<T>::TooManyOffers)?;
+			let deposit = T::OfferDeposit::get();
+			T::Currency::reserve(&buyer, price.saturating_add(deposit))?;
+			if !deposit.is_zero() {
+				OfferDeposits::<T>::insert(pet_id, &buyer, deposit);
+			}
+			Offers::<T>::insert(pet_id, offers);
+
+			Self::deposit_event(Event::OfferMade { buyer, pet_id, price, expires_at });
+
+			Ok(().into())
+		}
+
+		/// Take back an offer and its reserved price.
+		///
+		/// - pet_id: The id of the pet the offer was made on
+		#[pallet::call_index(21)]
+		#[pallet::weight(0)]
+		pub fn withdraw_offer(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let buyer = ensure_signed(origin)?;
+
+			Self::take_offer(pet_id, &buyer).ok_or(Error::<T>::OfferNotFound)?;
+
+			Self::deposit_event(Event::OfferWithdrawn { buyer, pet_id });
+
+			Ok(().into())
+		}
+
+		/// Sell the pet to the maker of an offer. The market fee and the royalty are taken from
+		/// the price like for a listed sale.
+		///
+		/// - buyer: The account that made the offer
+		#[pallet::call_index(22)]
+		#[pallet::weight(0)]
+		pub fn accept_offer(
+			origin: OriginFor<T>,
+			buyer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+
+			let (seller, id, pet) = Self::ensure_can(origin, None, Action::Trade)?;
+			Self::ensure_unlocked(id)?;
+			Self::ensure_can_own(&buyer)?;
+			let offer = Self::take_offer(id, &buyer).ok_or(Error::<T>::OfferNotFound)?;
+
+			let (fee, royalty) = Self::pay_for_pet(&buyer, &seller, id, offer.price)?;
+
+			Self::move_pet(&seller, &buyer, id, pet);
+
+			Self::deposit_event(Event::OfferAccepted {
+				seller,
+				buyer,
+				pet_id: id,
+				price: offer.price,
+				fee,
+				royalty,
+			});
+
+			Ok(().into())
+		}
+
+		/// Turn down an offer on the sender's pet, refunding the buyer.
+		///
+		/// - buyer: The account that made the offer
+		#[pallet::call_index(23)]
+		#[pallet::weight(0)]
+		pub fn reject_offer(
+			origin: OriginFor<T>,
+			buyer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let (owner, id, _) = Self::ensure_can(origin, None, Action::Decline)?;
+			Self::take_offer(id, &buyer).ok_or(Error::<T>::OfferNotFound)?;
+
+			Self::deposit_event(Event::OfferRejected { owner, buyer, pet_id: id });
+
+			Ok(().into())
+		}
+
+		/// List the pet for sale together with items of your inventory. The items stay in
+		/// the inventory until the pet is sold, the sale fails if some are gone by then.
+		///
+		/// - price: The price asked for the pet and the items
+		/// - items: The items sold with the pet, and how many of each
+		#[pallet::call_index(24)]
+		#[pallet::weight(0)]
+		pub fn list_bundle(
+			origin: OriginFor<T>,
+			price: BalanceOf<T>,
+			items: BoundedVec<(T::BundleItem, u32), T::MaxBundleItems>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let signer = ensure_signed(origin.clone())?;
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Trade)?;
+			Self::ensure_unlocked(id)?;
+			ensure!(
+				!items.is_empty() && items.iter().all(|(_, amount)| *amount > 0),
+				Error::<T>::EmptyBundle
+			);
+			Self::ensure_bundle_owned(&sender, &items)?;
+
+			Listings::<T>::insert(id, price);
+			ListingBundles::<T>::insert(id, items.clone());
+			ListedBy::<T>::insert(id, signer);
+
+			Self::deposit_event(Event::BundleListed { seller: sender, pet_id: id, price, items });
+
+			Ok(().into())
+		}
+
+		/// Set the royalty you receive when a pet you bred is resold. Only the breeder can
+		/// set it, and only while they still own the pet.
+		///
+		/// - royalty: The share of each resale paid to the breeder
+		#[pallet::call_index(25)]
+		#[pallet::weight(0)]
+		pub fn set_royalty(origin: OriginFor<T>, royalty: Permill) -> DispatchResultWithPostInfo {
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Manage)?;
+			ensure!(royalty <= MaxRoyalty::<T>::get(), Error::<T>::RoyaltyTooHigh);
+
+			Provenance::<T>::try_mutate(id, |provenance| {
+				let provenance = provenance
+					.as_mut()
+					.filter(|provenance| provenance.breeder.as_ref() == Some(&sender))
+					.ok_or(Error::<T>::NotBreeder)?;
+				provenance.royalty = royalty;
+				Ok::<_, Error<T>>(())
+			})?;
+
+			Self::deposit_event(Event::RoyaltySet { breeder: sender, pet_id: id, royalty });
+
+			Ok(().into())
+		}
+
+		/// Set how fast the stats of pets drop and when pets get hungry or tired. Stats and
+		/// moods of all pets follow the new rules at once.
+		///
+		/// - rules: The new care rules
+		#[pallet::call_index(27)]
+		#[pallet::weight(0)]
+		pub fn set_care_rules(
+			origin: OriginFor<T>,
+			rules: CareParameters,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+			ensure!(pet_core::Rules::from(rules).is_valid(), Error::<T>::InvalidCareRules);
+
+			CareRules::<T>::put(rules);
+
+			Self::deposit_event(Event::CareRulesSet { rules });
+
+			Ok(().into())
+		}
+
+		/// Schedule a seasonal event, during which every pet can claim its cosmetic once.
+		/// Scheduling the event of a cosmetic again replaces its dates.
+		///
+		/// - cosmetic: The cosmetic given during the event
+		/// - starts: The first block of the event
+		/// - ends: The block the event is over at
+		#[pallet::call_index(28)]
+		#[pallet::weight(0)]
+		pub fn schedule_seasonal_event(
+			origin: OriginFor<T>,
+			cosmetic: Cosmetic,
+			starts: T::BlockNumber,
+			ends: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+			ensure!(starts < ends, Error::<T>::InvalidSeasonalEvent);
+
+			SeasonalEvents::<T>::insert(cosmetic, SeasonalEvent { starts, ends });
+
+			Self::deposit_event(Event::SeasonalEventScheduled { cosmetic, starts, ends });
+
+			Ok(().into())
+		}
+
+		/// Cancel a seasonal event, the pets that claimed its cosmetic keep it.
+		///
+		/// - cosmetic: The cosmetic of the event
+		#[pallet::call_index(29)]
+		#[pallet::weight(0)]
+		pub fn cancel_seasonal_event(
+			origin: OriginFor<T>,
+			cosmetic: Cosmetic,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				SeasonalEvents::<T>::take(cosmetic).is_some(),
+				Error::<T>::SeasonalEventNotFound
+			);
+
+			Self::deposit_event(Event::SeasonalEventCancelled { cosmetic });
+
+			Ok(().into())
+		}
+
+		/// Claim the cosmetic of a seasonal event for your pet, while the event runs. The
+		/// cosmetic is bound to the pet and can't be traded on its own.
+		///
+		/// - cosmetic: The cosmetic of the event
+		#[pallet::call_index(30)]
+		#[pallet::weight(0)]
+		pub fn claim_seasonal_cosmetic(
+			origin: OriginFor<T>,
+			cosmetic: Cosmetic,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Manage)?;
+			let event =
+				SeasonalEvents::<T>::get(cosmetic).ok_or(Error::<T>::SeasonalEventNotFound)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(event.starts <= now && now < event.ends, Error::<T>::SeasonalEventNotActive);
+			ensure!(
+				!PetCosmetics::<T>::contains_key(id, cosmetic),
+				Error::<T>::CosmeticAlreadyClaimed
+			);
+
+			PetCosmetics::<T>::insert(id, cosmetic, now);
+
+			Self::deposit_event(Event::CosmeticClaimed { owner: sender, pet_id: id, cosmetic });
+
+			Ok(().into())
+		}
+
+		/// Dress your pet with one of its cosmetics, the one it wore is taken off.
+		///
+		/// - cosmetic: The cosmetic to wear, `None` takes it off
+		#[pallet::call_index(31)]
+		#[pallet::weight(0)]
+		pub fn equip_cosmetic(
+			origin: OriginFor<T>,
+			cosmetic: Option<Cosmetic>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+
+			let (sender, id, _) = Self::ensure_can(origin, None, Action::Manage)?;
+			if let Some(cosmetic) = cosmetic {
+				ensure!(
+					PetCosmetics::<T>::contains_key(id, cosmetic),
+					Error::<T>::CosmeticNotOwned
+				);
+			}
+
+			EquippedCosmetic::<T>::set(id, cosmetic);
+
+			Self::deposit_event(Event::CosmeticEquipped { owner: sender, pet_id: id, cosmetic });
+
+			Ok(().into())
+		}
+
+		/// Mint a new pet as a gift: the sender mints it and `receiver` owns it. The receiver
+		/// must be able to own one more pet, as when minting for themselves.
+		///
+		/// - receiver: The owner of the new pet
+		/// - name: The name of the pet
+		/// - species: The species of the pet
+		/// - id: The id of the pet
+		#[pallet::call_index(32)]
+		#[pallet::weight(0)]
+		pub fn mint_for(
+			origin: OriginFor<T>,
+			receiver: T::AccountId,
+			name: Vec<u8>,
+			species: Species,
+			id: u32,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+
+			Self::do_mint(&sender, &receiver, name, species, id)?;
+
+			Ok(().into())
+		}
+
+		/// Send a message about a pet that is listed or has open offers, to negotiate its
+		/// sale. Messages go between its owner and anyone else, they cost `MessageFee` and
+		/// each account can send `MaxMessagesPerEra` of them in a `MessageEra`.
+		///
+		/// - pet_id: The id of the pet
+		/// - to: The owner of the pet, or the account its owner answers
+		/// - text: The message
+		#[pallet::call_index(33)]
+		#[pallet::weight(0)]
+		pub fn send_trade_message(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			to: T::AccountId,
+			text: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+			ensure!(!text.is_empty(), Error::<T>::EmptyMessage);
+			let text: BoundedVec<u8, T::MaxMessageLen> =
+				text.try_into().map_err(|_| Error::<T>::MessageTooLong)?;
+
+			let owner = PetOwners::<T>::get(pet_id).ok_or(Error::<T>::PetNotFound)?;
+			ensure!(
+				Listings::<T>::contains_key(pet_id) || !Offers::<T>::get(pet_id).is_empty(),
+				Error::<T>::PetNotOnMarket
+			);
+			ensure!(sender != to && (sender == owner || to == owner), Error::<T>::NotTradeParty);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let era: u32 = (now / T::MessageEra::get().max(1u32.into())).saturated_into();
+			let (last_era, sent) = MessagesSent::<T>::get(&sender);
+			let sent = if last_era == era { sent } else { 0 };
+			ensure!(sent < T::MaxMessagesPerEra::get(), Error::<T>::TooManyMessages);
+
+			let fee = T::MessageFee::get();
+			if !fee.is_zero() {
+				let imbalance = T::Currency::withdraw(
+					&sender,
+					fee,
+					WithdrawReasons::FEE,
+					ExistenceRequirement::KeepAlive,
+				)?;
+				T::OnMessageFee::on_unbalanced(imbalance);
+			}
+			MessagesSent::<T>::insert(&sender, (era, sent + 1));
+
+			Self::deposit_event(Event::TradeMessageSent { pet_id, from: sender, to, text, fee });
+
+			Ok(().into())
+		}
+
+		/// Let a pet go for good instead of reviving it. Its name, level and its owner's badges
+		/// are kept in the owner's `Memorials`, everything else about it is removed and the
+		/// offers on it are refunded. The pet must be neglected, or abandoned by the sender and
+		/// still in the adoption pool. Co-owners can let a neglected pet go like they trade it.
+		///
+		/// - id: The id of the pet
+		#[pallet::call_index(34)]
+		#[pallet::weight(0)]
+		pub fn memorialize(origin: OriginFor<T>, id: PetId) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin.clone())?;
+			Self::ensure_running()?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let (owner, pet) = match AdoptionPool::<T>::get(id) {
+				Some(abandoned) => {
+					ensure!(abandoned.owner == sender, Error::<T>::NotPreviousOwner);
+					AdoptionPool::<T>::remove(id);
+					(abandoned.owner, abandoned.pet)
+				},
+				// A pet still owned frees the slot of its owner, who may not be the sender
+				None => {
+					let (owner, ..) = Self::ensure_can(origin, Some(id), Action::Trade)?;
+					Self::ensure_hatched(id)?;
+					Self::ensure_unlocked(id)?;
+					let unfed = now.saturating_sub(PetVitals::<T>::get(id).last_fed);
+					ensure!(unfed >= T::AbandonAfter::get(), Error::<T>::PetNotNeglected);
+					let (_, pet) = PetsInfo::<T>::take(&owner).ok_or(Error::<T>::PetNotFound)?;
+					PetOwners::<T>::remove(id);
+					(owner, pet)
+				},
+			};
+
+			let experience = Experience::<T>::get(id);
+			let mut achievements = T::Items::achievements(&owner);
+			achievements.truncate(T::MaxMemorialBadges::get() as usize);
+			let memorial = Memorial {
+				dna: PetDna::<T>::get(id),
+				level: pet_core::level(experience),
+				experience,
+				achievements: BoundedVec::truncate_from(achievements),
+				since: now,
+				pet,
+			};
+			SpeciesPopulation::<T>::mutate(&memorial.pet.species, |population| {
+				*population = population.saturating_sub(1)
+			});
+			Self::remove_pet(&owner, id);
+			let level = memorial.level;
+			Memorials::<T>::insert(&owner, id, memorial);
+
+			Self::deposit_event(Event::PetMemorialized { owner, pet_id: id, level });
+
+			Ok(().into())
+		}
+
+		/// Feed the pet or put it to sleep at a later block, and every `every` blocks after
+		/// that if any, even while its owner is away. Scheduling an action again replaces its
+		/// schedule.
+		///
+		/// - action: What to do
+		/// - when: The first block to do it at
+		/// - every: The blocks between two runs, at least `MinCareInterval`
+		#[pallet::call_index(35)]
+		#[pallet::weight(0)]
+		pub fn schedule_care(
+			origin: OriginFor<T>,
+			action: CareAction,
+			when: T::BlockNumber,
+			every: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (owner, _, _) = Self::ensure_can(origin, None, Action::Manage)?;
+			ensure!(when > frame_system::Pallet::<T>::block_number(), Error::<T>::CareInThePast);
+			if let Some(every) = every {
+				ensure!(every >= T::MinCareInterval::get(), Error::<T>::CareTooFrequent);
+			}
+
+			if ScheduledCare::<T>::contains_key(&owner, action) {
+				T::CareScheduler::cancel(&owner, action)?;
+			}
+			T::CareScheduler::schedule(&owner, action, when, every)?;
+			ScheduledCare::<T>::insert(&owner, action, CareSchedule { next: when, every });
+
+			Self::deposit_event(Event::CareScheduled { owner, action, when, every });
+
+			Ok(().into())
+		}
+
+		/// Cancel a care schedule.
+		///
+		/// - action: The action scheduled
+		#[pallet::call_index(36)]
+		#[pallet::weight(0)]
+		pub fn cancel_care(origin: OriginFor<T>, action: CareAction) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			ensure!(ScheduledCare::<T>::contains_key(&owner, action), Error::<T>::CareNotScheduled);
+
+			T::CareScheduler::cancel(&owner, action)?;
+			ScheduledCare::<T>::remove(&owner, action);
+
+			Self::deposit_event(Event::CareCancelled { owner, action });
+
+			Ok(().into())
+		}
+
+		/// Run scheduled care, dispatched by the `CareScheduler` at the blocks it was
+		/// scheduled at. Only `CareSchedulerOrigin` can call it, owners manage their schedule
+		/// with `schedule_care` and `cancel_care`.
+		///
+		/// - owner: The owner who scheduled the action
+		/// - action: The action scheduled
+		#[pallet::call_index(37)]
+		#[pallet::weight(T::WeightInfo::run_scheduled_care())]
+		pub fn run_scheduled_care(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			action: CareAction,
+		) -> DispatchResultWithPostInfo {
+			T::CareSchedulerOrigin::ensure_origin(origin)?;
+			let schedule =
+				ScheduledCare::<T>::get(&owner, action).ok_or(Error::<T>::CareNotScheduled)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(schedule.next <= now, Error::<T>::CareNotDue);
+
+			// The scheduler counts the next run from the block this one was due at
+			match schedule.every {
+				Some(every) => ScheduledCare::<T>::insert(
+					&owner,
+					action,
+					CareSchedule { next: schedule.next.saturating_add(every), every: Some(every) },
+				),
+				None => ScheduledCare::<T>::remove(&owner, action),
+			}
+			let done = Self::ensure_running().and_then(|_| match action {
+				CareAction::Feed => Self::feed_pet(&owner).map(|_| ()),
+				CareAction::Sleep => Self::sleep_pet(&owner).map(|_| ()),
+			});
+
+			Self::deposit_event(Event::ScheduledCareRan { owner, action, done: done.is_ok() });
+
+			Ok(().into())
+		}
+
+		/// Refund the offers its owner made on a pet before getting it some other way, they
+		/// can't be accepted anymore. Anyone can call it, and is paid the deposits of the
+		/// offers, so storage doesn't wait for their expiry. The price is refunded in full.
+		///
+		/// Offers are all a pet leaves behind when it changes hands. Its listing, trade
+		/// consents and co-owners are dropped right away, while game keys and care schedules
+		/// belong to the account of the previous owner, not to the pet, and stay with it.
+		///
+		/// - pet_id: The id of the pet whose offers are checked
+		#[pallet::call_index(38)]
+		#[pallet::weight(0)]
+		pub fn cleanup(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			// Expired offers are refunded as their block starts, so only the owner's are left
+			let owner = PetOwners::<T>::get(pet_id);
+			let stale: Vec<T::AccountId> = Offers::<T>::get(pet_id)
+				.into_iter()
+				.filter(|offer| owner.as_ref() == Some(&offer.buyer))
+				.map(|offer| offer.buyer)
+				.collect();
+			ensure!(!stale.is_empty(), Error::<T>::NothingToCleanUp);
+
+			let mut reward: BalanceOf<T> = Zero::zero();
+			for buyer in &stale {
+				// Cleaning up one's own offers pays nothing, `take_offer` returns the deposit
+				if buyer != &sender {
+					if let Some(deposit) = OfferDeposits::<T>::take(pet_id, buyer) {
+						let unpaid = T::Currency::repatriate_reserved(
+							buyer,
+							&sender,
+							deposit,
+							BalanceStatus::Free,
+						)
+						.unwrap_or(deposit);
+						T::Currency::unreserve(buyer, unpaid);
+						reward = reward.saturating_add(deposit.saturating_sub(unpaid));
+					}
+				}
+				if Self::take_offer(pet_id, buyer).is_some() {
+					Self::deposit_event(Event::OfferExpired { buyer: buyer.clone(), pet_id });
+				}
+			}
+
+			Self::deposit_event(Event::PetCleanedUp {
+				by: sender,
+				pet_id,
+				offers: stale.len() as u32,
+				reward,
+			});
+
+			Ok(().into())
+		}
+
+		/// Share the pet with another player, who can care for it and battle with it as the
+		/// owner does and takes part in trading it, see `CoOwnerConsent`. The co-owner can't
+		/// own a pet meanwhile, and can leave with `remove_co_owner`. Co-owners don't follow
+		/// the pet when it changes hands.
+		///
+		/// - pet_id: The id of the pet
+		/// - co_owner: The account of the new co-owner
+		#[pallet::call_index(39)]
+		#[pallet::weight(0)]
+		pub fn add_co_owner(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			co_owner: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_running()?;
+			let (owner, id, _) = Self::ensure_can(origin, Some(pet_id), Action::Manage)?;
+			ensure!(!PetsInfo::<T>::contains_key(&co_owner), Error::<T>::CoOwnerInUse);
+			ensure!(!GameKeys::<T>::contains_key(&co_owner), Error::<T>::CoOwnerInUse);
+			ensure!(!CoOwnedPets::<T>::contains_key(&co_owner), Error::<T>::CoOwnerInUse);
+
+			CoOwners::<T>::try_mutate(id, |co_owners| co_owners.try_push(co_owner.clone()))
+				.map_err(|_| Error::<T>::TooManyCoOwners)?;
+			CoOwnedPets::<T>::insert(&co_owner, id);
+
+			Self::deposit_event(Event::CoOwnerAdded { owner, pet_id: id, co_owner });
+
+			Ok(().into())
+		}
+
+		/// Remove a co-owner of the pet, as its owner or as the co-owner leaving.
+		///
+		/// - pet_id: The id of the pet
+		/// - co_owner: The account of the co-owner
+		#[pallet::call_index(40)]
+		#[pallet::weight(0)]
+		pub fn remove_co_owner(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			co_owner: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin.clone())?;
+			Self::ensure_running()?;
+			if sender != co_owner {
+				Self::ensure_can(origin, Some(pet_id), Action::Manage)?;
+			}
+			ensure!(CoOwnedPets::<T>::get(&co_owner) == Some(pet_id), Error::<T>::CoOwnerNotFound);
+			let owner = PetOwners::<T>::get(pet_id).ok_or(Error::<T>::PetNotFound)?;
+
+			Self::drop_co_owner(pet_id, &co_owner);
+
+			Self::deposit_event(Event::CoOwnerRemoved { owner, pet_id, co_owner });
+
+			Ok(().into())
+		}
+
+		/// Consent to trade a pet one owns or co-owns, or take the consent back. Under
+		/// `CoOwnerConsent::All`, the pet is only sold, given away or let go once all of its
+		/// owner and co-owners but the one doing it consent.
+		///
+		/// - pet_id: The id of the pet
+		/// - consent: Whether the sender consents
+		#[pallet::call_index(41)]
+		#[pallet::weight(0)]
+		pub fn consent_to_trade(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			consent: bool,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+			let owner = PetOwners::<T>::get(pet_id).ok_or(Error::<T>::PetNotFound)?;
+			ensure!(
+				owner == sender || CoOwnedPets::<T>::get(&sender) == Some(pet_id),
+				Error::<T>::NotOwner
+			);
+
+			if consent {
+				TradeConsents::<T>::insert(pet_id, &sender, ());
+			} else {
+				TradeConsents::<T>::remove(pet_id, &sender);
+			}
+
+			Self::deposit_event(Event::TradeConsentSet { account: sender, pet_id, consent });
+
+			Ok(().into())
+		}
+
+		/// Register an entry of the stats block registry, or change it. Every pet referring to
+		/// it battles with the new stats at once.
+		///
+		/// - block_id: The id of the entry
+		/// - block: The combat stats
+		#[pallet::call_index(42)]
+		#[pallet::weight(0)]
+		pub fn set_stats_block(
+			origin: OriginFor<T>,
+			block_id: StatsBlockId,
+			block: StatsBlock,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+			ensure!(pet_core::StatsBlock::from(block).is_valid(), Error::<T>::InvalidStatsBlock);
+
+			StatsBlocks::<T>::insert(block_id, block);
+
+			Self::deposit_event(Event::StatsBlockSet { block_id, block });
+
+			Ok(().into())
+		}
+
+		/// Set the stats block new pets of a species get. Pets of the species that exist keep
+		/// theirs.
+		///
+		/// - species: The species
+		/// - block_id: A registered entry of the stats block registry
+		#[pallet::call_index(43)]
+		#[pallet::weight(0)]
+		pub fn set_species_stats_block(
+			origin: OriginFor<T>,
+			species: Species,
+			block_id: StatsBlockId,
+		) -> DispatchResultWithPostInfo {
+			T::GameAdminOrigin::ensure_origin(origin)?;
+			ensure!(StatsBlocks::<T>::contains_key(block_id), Error::<T>::StatsBlockNotFound);
+
+			SpeciesStatsBlocks::<T>::insert(&species, block_id);
+
+			Self::deposit_event(Event::SpeciesStatsBlockSet { species, block_id });
+
+			Ok(().into())
+		}
+
+		/// Accept being the game key of `owner`, who proposed it with `propose_game_key`. The
+		/// other proposals to the sender are dropped.
+		///
+		/// - owner: The account that proposed the sender as its game key
+		#[pallet::call_index(44)]
+		#[pallet::weight(0)]
+		pub fn accept_game_key(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_running()?;
+			ensure!(
+				GameKeyProposals::<T>::contains_key(&sender, &owner),
+				Error::<T>::GameKeyNotProposed
+			);
+
+			let _ = GameKeyProposals::<T>::clear_prefix(&sender, u32::MAX, None);
+			Self::grant_game_key(&owner, sender)?;
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Move a pet between accounts, dropping any listing it had.
+		fn move_pet(from: &T::AccountId, to: &T::AccountId, id: PetId, pet: PetInfo<T>) {
+			PetsInfo::<T>::insert(to, (id, pet));
+			PetsInfo::<T>::remove(from);
+			PetOwners::<T>::insert(id, to);
+			Listings::<T>::remove(id);
+			ListingBundles::<T>::remove(id);
+			ListedBy::<T>::remove(id);
+			Self::drop_co_owners(id);
+		}
+
+		/// Check that `seller` holds every item of a bundle, adding up items listed in more
+		/// than one entry.
+		fn ensure_bundle_owned(
+			seller: &T::AccountId,
+			items: &[(T::BundleItem, u32)],
+		) -> DispatchResult {
+			for (item, _) in items {
+				let listed = items
+					.iter()
+					.filter(|(other, _)| other == item)
+					.fold(0u32, |total, (_, amount)| total.saturating_add(*amount));
+				ensure!(T::Items::count(seller, *item) >= listed, Error::<T>::BundleItemsMissing);
+			}
+			Ok(())
+		}
+
+		/// Pay the price of a pet from `buyer`: the market fee to `OnMarketFee`, the royalty
+		/// to the breeder unless they are the seller, the rest to the seller. Returns the fee
+		/// and the royalty.
+		fn pay_for_pet(
+			buyer: &T::AccountId,
+			seller: &T::AccountId,
+			id: PetId,
+			price: BalanceOf<T>,
+		) -> Result<(BalanceOf<T>, BalanceOf<T>), DispatchError> {
+			let fee = MarketFee::<T>::get() * price;
+			let royalty = match Provenance::<T>::get(id) {
+				Some(PetProvenance { breeder: Some(breeder), royalty }) if &breeder != seller => {
+					let royalty = royalty.min(MaxRoyalty::<T>::get()) * price;
+					T::Currency::transfer(
+						buyer,
+						&breeder,
+						royalty,
+						ExistenceRequirement::KeepAlive,
+					)?;
+					royalty
+				},
+				_ => Zero::zero(),
+			};
+			T::Currency::transfer(
+				buyer,
+				seller,
+				price.saturating_sub(fee).saturating_sub(royalty),
+				ExistenceRequirement::KeepAlive,
+			)?;
+			let imbalance = T::Currency::withdraw(
+				buyer,
+				fee,
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::OnMarketFee::on_unbalanced(imbalance);
+			Ok((fee, royalty))
+		}
+
+		/// Remove the offer of `buyer` on a pet and unreserve its price.
+		fn take_offer(pet_id: PetId, buyer: &T::AccountId) -> Option<Offer<T>> {
+			let offer = Offers::<T>::mutate_exists(pet_id, |offers| {
+				let list = offers.as_mut()?;
+				let index = list.iter().position(|offer| &offer.buyer == buyer)?;
+				let offer = list.remove(index);
+				if list.is_empty() {
+					*offers = None;
+				}
+				Some(offer)
+			})?;
+			OfferExpiries::<T>::mutate_exists(offer.expires_at, |expiring| {
+				if let Some(list) = expiring {
+					list.retain(|(id, who)| *id != pet_id || who != buyer);
+					if list.is_empty() {
+						*expiring = None;
+					}
+				}
+			});
+			let deposit = OfferDeposits::<T>::take(pet_id, buyer).unwrap_or_default();
+			T::Currency::unreserve(buyer, offer.price.saturating_add(deposit));
+			Some(offer)
+		}
+
+		/// Remove everything kept about a pet that's gone, refunding the offers on it. Its
+		/// owner and adoption pool entries are already removed.
+		fn remove_pet(owner: &T::AccountId, id: PetId) {
+			for offer in Offers::<T>::get(id) {
+				if Self::take_offer(id, &offer.buyer).is_some() {
+					Self::deposit_event(Event::OfferRejected {
+						owner: owner.clone(),
+						buyer: offer.buyer,
+						pet_id: id,
+					});
+				}
+			}
+			Listings::<T>::remove(id);
+			ListingBundles::<T>::remove(id);
+			ListedBy::<T>::remove(id);
+			PetDna::<T>::remove(id);
+			PetStatsBlocks::<T>::remove(id);
+			PetVitals::<T>::remove(id);
+			Experience::<T>::remove(id);
+			ScoreNonce::<T>::remove(id);
+			Provenance::<T>::remove(id);
+			DailyActivity::<T>::remove(id);
+			CareStreaks::<T>::remove(id);
+			RenameCount::<T>::remove(id);
+			Eggs::<T>::remove(id);
+			EquippedCosmetic::<T>::remove(id);
+			let _ = PetCosmetics::<T>::clear_prefix(id, u32::MAX, None);
+			PetHistory::<T>::remove(id);
+			Self::drop_co_owners(id);
+		}
+
+		/// Move a neglected pet to the adoption pool, unless it's an egg or locked.
+		fn abandon_pet(id: PetId, owner: T::AccountId, now: T::BlockNumber) -> DispatchResult {
+			Self::ensure_hatched(id)?;
+			Self::ensure_unlocked(id)?;
+			ensure!(
+				now.saturating_sub(PetVitals::<T>::get(id).last_fed) >= T::AbandonAfter::get(),
+				Error::<T>::PetNotNeglected
+			);
+			let (_, pet) = PetsInfo::<T>::take(&owner).ok_or(Error::<T>::PetNotFound)?;
+
+			PetOwners::<T>::remove(id);
+			Listings::<T>::remove(id);
+			ListingBundles::<T>::remove(id);
+			ListedBy::<T>::remove(id);
+			Self::drop_co_owners(id);
+			AdoptionPool::<T>::insert(id, AbandonedPet { owner: owner.clone(), pet, since: now });
+
+			Self::deposit_event(Event::PetAbandoned { owner: owner.clone(), pet_id: id });
+			T::OnAbandon::on_pet_abandoned(&owner, id);
+			Ok(())
+		}
+
+		/// Queue a deferred task, unless it's already waiting or the queue is full.
+		fn defer(task: DeferredTask) {
+			DeferredTasks::<T>::mutate(|tasks| {
+				if !tasks.contains(&task) {
+					let _ = tasks.try_push(task);
+				}
+			});
+		}
+
+		/// Go on with the adoption sweep from `TaskCursor`, as far as `limit` allows. Returns
+		/// whether every pet was checked, `used` already counts storing the cursor.
+		fn sweep_neglected(now: T::BlockNumber, limit: Weight, used: &mut Weight) -> bool {
+			// Checking the next pet and abandoning it, with what `Locks` and `OnAbandon` do
+			let per_pet =
+				T::WeightInfo::adoption_sweep(1).saturating_sub(T::WeightInfo::adoption_sweep(0));
+			let mut owners = match TaskCursor::<T>::get() {
+				Some(cursor) => PetOwners::<T>::iter_from(cursor.into_inner()),
+				None => PetOwners::<T>::iter(),
+			};
+			loop {
+				if limit.any_lt(used.saturating_add(per_pet)) {
+					// The key fits, it's the prefix of the map and a hashed pet id
+					if let Ok(cursor) = BoundedVec::try_from(owners.last_raw_key().to_vec()) {
+						TaskCursor::<T>::set(Some(cursor));
+					}
+					return false;
+				}
+				used.saturating_accrue(per_pet);
+				let Some((id, owner)) = owners.next() else { return true };
+				// Pets that can't be abandoned yet are left alone
+				let _ = Self::abandon_pet(id, owner, now);
+			}
+		}
+
+		/// Give a pet of the adoption pool to its new owner, it counts as fed from now on.
+		fn take_from_pool(to: &T::AccountId, id: PetId, pet: PetInfo<T>) {
+			AdoptionPool::<T>::remove(id);
+			PetsInfo::<T>::insert(to, (id, pet));
+			PetOwners::<T>::insert(id, to);
+			PetVitals::<T>::mutate(id, |vitals| {
+				vitals.last_fed = frame_system::Pallet::<T>::block_number();
+				vitals.fed_at = Self::moment();
+			});
+		}
+
+		/// Check that the payload is signed by the game server or a game key of the pet owner.
+		fn is_score_signer(payload: &ScorePayload, signature: &T::OffchainSignature) -> bool {
+			let message = payload.encode();
+			let by_server =
+				GameServerKey::<T>::get().map_or(false, |key| signature.verify(&message[..], &key));
+			by_server ||
+				PetOwners::<T>::get(payload.pet_id).map_or(false, |owner| {
+					OwnerGameKeys::<T>::get(owner)
+						.iter()
+						.any(|key| signature.verify(&message[..], key))
+				})
+		}
+
+		/// Check that the signer of `origin` may take `action` with a pet, and return the
+		/// owner of the pet, its id and the pet. The signer acts on its own pet, on the pet of
+		/// the account that registered it as a game key, or on the pet it co-owns. `pet_id` is
+		/// the pet the call is about when it names one, which must be that pet.
+		///
+		/// Every call acting on a pet checks its origin here rather than on its own, so
+		/// `Action::allows` is the only place saying who may do what.
+		pub fn ensure_can(
+			origin: OriginFor<T>,
+			pet_id: Option<PetId>,
+			action: Action,
+		) -> Result<(T::AccountId, PetId, PetInfo<T>), DispatchError> {
+			let signer = ensure_signed(origin)?;
+			let (owner, role) = if let Some(owner) = GameKeys::<T>::get(&signer) {
+				(owner, Role::GameKey)
+			} else if let Some(id) = CoOwnedPets::<T>::get(&signer) {
+				(PetOwners::<T>::get(id).ok_or(Error::<T>::PetNotFound)?, Role::CoOwner)
+			} else {
+				(signer.clone(), Role::Owner)
+			};
+			ensure!(action.allows(role), Error::<T>::NotAuthorized);
+
+			let (id, pet) = PetsInfo::<T>::get(&owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			ensure!(pet_id.map_or(true, |pet_id| pet_id == id), Error::<T>::NotOwner);
+			if action == Action::Trade {
+				Self::ensure_trade_consent(id, &owner, &signer)?;
+			}
+			Ok((owner, id, pet))
+		}
+
+		/// Check that everyone who has to sign for the pet to be traded did, see
+		/// `CoOwnerConsent`. `signer` signs with the call trading it.
+		fn ensure_trade_consent(
+			id: PetId,
+			owner: &T::AccountId,
+			signer: &T::AccountId,
+		) -> DispatchResult {
+			let consented = match T::CoOwnerConsent::get() {
+				CoOwnerConsent::Either => true,
+				CoOwnerConsent::All => sp_std::iter::once(owner.clone())
+					.chain(CoOwners::<T>::get(id))
+					.filter(|account| account != signer)
+					.all(|account| TradeConsents::<T>::contains_key(id, account)),
+			};
+			ensure!(consented, Error::<T>::TradeNotConsented);
+			Ok(())
+		}
+
+		/// Remove a co-owner of a pet, with its consent to trade the pet.
+		fn drop_co_owner(id: PetId, co_owner: &T::AccountId) {
+			CoOwners::<T>::mutate(id, |co_owners| co_owners.retain(|account| account != co_owner));
+			CoOwnedPets::<T>::remove(co_owner);
+			TradeConsents::<T>::remove(id, co_owner);
+		}
+
+		/// Remove every co-owner of a pet leaving its owner, co-owners don't follow the pet.
+		fn drop_co_owners(id: PetId) {
+			for co_owner in CoOwners::<T>::take(id) {
+				CoOwnedPets::<T>::remove(co_owner);
+			}
+			let _ = TradeConsents::<T>::clear_prefix(id, u32::MAX, None);
+		}
+
+		/// Resolve a battle between the pets of two accounts, the winner earns
+		/// `BattleExperience` cut by its household's jealousy. Returns the winning pet. Used by
+		/// challenges and tournaments.
+		pub fn battle(
+			challenger: T::AccountId,
+			opponent: T::AccountId,
+		) -> Result<PetId, DispatchError> {
+			ensure!(challenger != opponent, Error::<T>::CannotBattleOwnPet);
+
+			let (challenger_pet, _) =
+				PetsInfo::<T>::get(&challenger).ok_or(Error::<T>::AccountHasNoPet)?;
+			let (opponent_pet, _) =
+				PetsInfo::<T>::get(&opponent).ok_or(Error::<T>::OpponentHasNoPet)?;
+			Self::ensure_hatched(challenger_pet)?;
+			Self::ensure_hatched(opponent_pet)?;
+			ensure!(
+				!T::Locks::is_benched(challenger_pet) && !T::Locks::is_benched(opponent_pet),
+				Error::<T>::PetBenched
+			);
+
+			let log = Self::roll_battle(challenger_pet, opponent_pet);
+			let (winner, loser) = if pet_core::BattleLog::from(log).challenger_wins() {
+				(challenger_pet, opponent_pet)
+			} else {
+				(opponent_pet, challenger_pet)
+			};
+			let experience =
+				Self::experience_gain(winner, T::BattleExperience::get() as u64) as u32;
+			Experience::<T>::mutate(winner, |xp| *xp = xp.saturating_add(experience as u64));
+			Self::record_activity(winner, |activity| activity.battles_won.saturating_inc());
+
+			Self::deposit_event(Event::BattleResolved {
+				challenger,
+				opponent,
+				winner,
+				loser,
+				experience,
+				log,
+			});
+			Self::deposit_stats(winner);
+
+			Ok(winner)
+		}
+
+		/// Roll a battle between two pets, each one wins with a chance proportional to its
+		/// `pet_core` battle weight, scaled by its stats block.
+		///
+		/// The roll is seeded from `T::Randomness`, see there how far it can be trusted.
+		fn roll_battle(challenger: PetId, opponent: PetId) -> BattleLog {
+			let now =
+				pet_core::at_block(frame_system::Pallet::<T>::block_number().saturated_into());
+			let rules: pet_core::Rules = CareRules::<T>::get().into();
+			let weight = |id| {
+				let block = pet_core::StatsBlock::from(Self::stats_block(id));
+				block.battle_weight(Self::care(id).battle_weight(&rules, now))
+			};
+			let challenger_weight = weight(challenger);
+			let opponent_weight = weight(opponent);
+
+			let seed = Self::random(&(b"battle", challenger, opponent));
+			let mut bytes = [0u8; 8];
+			bytes.copy_from_slice(&seed.as_ref()[..8]);
+
+			BattleLog { seed: u64::from_le_bytes(bytes), challenger_weight, opponent_weight }
+		}
+
+		/// Mint a new pet `id` owned by `owner`, paid for by `payer`, who is its breeder.
+		fn do_mint(
+			payer: &T::AccountId,
+			owner: &T::AccountId,
+			name: Vec<u8>,
+			species: Species,
+			id: PetId,
+		) -> DispatchResult {
+			let name = Self::bounded_name(name)?;
+
+			Self::ensure_can_own(owner)?;
+			ensure!(!PetOwners::<T>::contains_key(id), Error::<T>::PetIdAlreadyUsed);
+			ensure!(!AdoptionPool::<T>::contains_key(id), Error::<T>::PetIdAlreadyUsed);
+			Self::add_to_population(&species)?;
+
+			let pet = PetInfo { name, species: species.clone() };
+
+			PetsInfo::<T>::insert(owner, (id, pet));
+			PetOwners::<T>::insert(id, owner);
+			PetDna::<T>::insert(id, Self::new_dna(id));
+			Self::assign_stats_block(id, &species);
+			// A new pet counts as fed, so it isn't abandoned right away
+			let now = frame_system::Pallet::<T>::block_number();
+			let vitals = Vitals { last_fed: now, fed_at: Self::moment(), ..Default::default() };
+			PetVitals::<T>::insert(id, vitals);
+			Provenance::<T>::insert(
+				id,
+				PetProvenance { breeder: Some(payer.clone()), royalty: Permill::zero() },
+			);
+
+			Self::deposit_event(Event::PetMinted {
+				owner: owner.clone(),
+				pet_id: id,
+				species,
+				payer: payer.clone(),
+			});
+			if !T::IncubationPeriod::get().is_zero() {
+				let hatch_at = now.saturating_add(T::IncubationPeriod::get());
+				Eggs::<T>::insert(id, hatch_at);
+				Self::deposit_event(Event::EggLaid { owner: owner.clone(), pet_id: id, hatch_at });
+			}
+			T::OnMint::on_pet_minted(owner, id);
+
+			Ok(())
+		}
+
+		/// DNA of a newly minted pet, seeded like battle rolls.
+		fn new_dna(id: PetId) -> Dna {
+			let seed = Self::random(&(b"dna", id));
+			pet_gene::dna_from_seed(seed.as_ref())
+		}
+
+		/// A random hash for the roll about `subject`, which is different for each roll of
+		/// a block so they don't all come out the same.
+		fn random(subject: &impl Encode) -> T::Hash {
+			let (seed, _) = T::Randomness::random(&subject.encode());
+			seed
+		}
+
+		/// Check that the game isn't paused, also used when an item is used from the inventory.
+		pub fn ensure_running() -> DispatchResult {
+			ensure!(!Paused::<T>::get(), Error::<T>::GamePaused);
+			Ok(())
+		}
+
+		/// Count a new pet of the species, failing if the species is sold out.
+		fn add_to_population(species: &Species) -> DispatchResult {
+			SpeciesPopulation::<T>::try_mutate(species, |population| {
+				let cap = SpeciesCaps::<T>::get(species).unwrap_or(u32::MAX);
+				ensure!(*population < cap, Error::<T>::SpeciesSoldOut);
+				*population += 1;
+				Ok(())
+			})
+		}
+
+		/// Everything about a pet for `PetApi`, `None` if nobody owns it.
+		pub fn pet_profile(id: PetId) -> Option<PetProfile<T>> {
+			let owner = PetOwners::<T>::get(id)?;
+			let (_, info) = PetsInfo::<T>::get(&owner)?;
+			let now =
+				pet_core::at_block(frame_system::Pallet::<T>::block_number().saturated_into());
+			let rules: pet_core::Rules = CareRules::<T>::get().into();
+			let vitals = PetVitals::<T>::get(id);
+			let experience = Experience::<T>::get(id);
+			let stats = vitals.care(experience).stats(&rules, now);
+			Some(PetProfile {
+				pet_id: id,
+				info,
+				dna: PetDna::<T>::get(id),
+				stats: ProfileStats {
+					fullness: stats.fullness,
+					energy: stats.energy,
+					level: stats.level,
+					hungry: stats.mood.is_hungry(),
+					tired: stats.mood.is_tired(),
+				},
+				experience,
+				last_feed: vitals.last_fed,
+				last_sleep: vitals.last_slept,
+				hatch_at: Eggs::<T>::get(id),
+				listing: Listings::<T>::get(id),
+				breeder: Provenance::<T>::get(id).and_then(|provenance| provenance.breeder),
+				cosmetics: PetCosmetics::<T>::iter_key_prefix(id).collect(),
+				equipped_cosmetic: EquippedCosmetic::<T>::get(id),
+				equipment: T::Items::equipment(&owner),
+				achievements: T::Items::achievements(&owner),
+				today: Self::activity_today(id),
+				history: PetHistory::<T>::get(id).into_inner(),
+				fed_at: vitals.fed_at,
+				slept_at: vitals.slept_at,
+				co_owners: CoOwners::<T>::get(id).into_inner(),
+				stats_block: Self::stats_block(id),
+				owner,
+			})
+		}
+
+		/// Population and cap of every species, for `PetApi`.
+		pub fn species_supply() -> Vec<SpeciesSupply> {
+			Species::ALL
+				.into_iter()
+				.map(|species| SpeciesSupply {
+					population: SpeciesPopulation::<T>::get(&species),
+					cap: SpeciesCaps::<T>::get(&species),
+					species,
+				})
+				.collect()
+		}
+
+		/// Register `key` as a game key of `owner`, once the key accepted it.
+		fn grant_game_key(owner: &T::AccountId, key: T::AccountId) -> DispatchResult {
+			// Game keys act for their owner, not for keys of their own
+			ensure!(!GameKeys::<T>::contains_key(owner), Error::<T>::NotAuthorized);
+			ensure!(&key != owner, Error::<T>::GameKeyInUse);
+			ensure!(!GameKeys::<T>::contains_key(&key), Error::<T>::GameKeyInUse);
+			ensure!(OwnerGameKeys::<T>::get(&key).is_empty(), Error::<T>::GameKeyInUse);
+			ensure!(!CoOwnedPets::<T>::contains_key(&key), Error::<T>::GameKeyInUse);
+			ensure!(!PetsInfo::<T>::contains_key(&key), Error::<T>::GameKeyCannotOwnPet);
+
+			OwnerGameKeys::<T>::try_mutate(owner, |keys| keys.try_push(key.clone()))
+				.map_err(|_| Error::<T>::TooManyGameKeys)?;
+			GameKeys::<T>::insert(&key, owner);
+
+			Self::deposit_event(Event::GameKeyAdded { owner: owner.clone(), key });
+
+			Ok(())
+		}
+
+		/// Remove the game key `key` of `owner`.
+		fn revoke_game_key(owner: &T::AccountId, key: T::AccountId) -> DispatchResult {
+			let key_owner = GameKeys::<T>::get(&key).ok_or(Error::<T>::GameKeyNotFound)?;
+			ensure!(&key_owner == owner, Error::<T>::NotOwner);
+
+			OwnerGameKeys::<T>::mutate(owner, |keys| keys.retain(|k| k != &key));
+			GameKeys::<T>::remove(&key);
+
+			Self::deposit_event(Event::GameKeyRemoved { owner: owner.clone(), key });
+
+			Ok(())
+		}
+
+		/// Give an abandoned pet back to its previous owner, with `payer` paying the
+		/// `AdoptionFee`. Unlike `reclaim`, it works after the `ReclaimWindow` too.
+		pub fn revive(payer: &T::AccountId, id: PetId) -> DispatchResult {
+			let abandoned = AdoptionPool::<T>::get(id).ok_or(Error::<T>::PetNotAbandoned)?;
+			Self::ensure_can_own(&abandoned.owner)?;
+
+			let imbalance = T::Currency::withdraw(
+				payer,
+				T::AdoptionFee::get(),
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::OnMarketFee::on_unbalanced(imbalance);
+
+			Self::take_from_pool(&abandoned.owner, id, abandoned.pet);
+
+			Self::deposit_event(Event::PetRevived {
+				owner: abandoned.owner,
+				payer: payer.clone(),
+				pet_id: id,
+			});
+			Self::deposit_stats(id);
+
+			Ok(())
+		}
+
+		/// Deposit a pet event with the accounts and pets it is about as topics, so clients can
+		/// look up the events of an account or a pet in `frame_system::EventTopics` instead of
+		/// decoding every block.
+		pub(super) fn deposit_event(event: Event<T>) {
+			let now = frame_system::Pallet::<T>::block_number();
+			for (id, entry) in event.history() {
+				PetHistory::<T>::mutate(id, |history| {
+					if history.is_full() {
+						history.remove(0);
+					}
+					let _ = history.try_push((now, entry));
+				});
+			}
+			let (accounts, pets) = event.subjects();
+			let topics: Vec<T::Hash> = accounts
+				.into_iter()
+				.map(Self::account_topic)
+				.chain(pets.into_iter().map(Self::pet_topic))
+				.collect();
+			let event = <T as Config>::RuntimeEvent::from(event).into();
+			frame_system::Pallet::<T>::deposit_event_indexed(&topics, event);
+		}
+
+		/// Topic of the events about `who`: the hash of the encoded account.
+		pub fn account_topic(who: &T::AccountId) -> T::Hash {
+			T::Hashing::hash_of(who)
+		}
+
+		/// Topic of the events about the pet `id`: the hash of the encoded id.
+		pub fn pet_topic(id: PetId) -> T::Hash {
+			T::Hashing::hash_of(&id)
+		}
+
+		/// Check that a pet name is UTF-8 and fits in `StringLimit`, with `pet_core::check_name`
+		/// like clients do.
+		fn bounded_name(name: Vec<u8>) -> Result<BoundedVec<u8, T::StringLimit>, Error<T>> {
+			pet_core::check_name(&name, T::StringLimit::get()).map_err(|e| match e {
+				pet_core::NameError::NotUtf8 => Error::<T>::NameNotUtf8,
+				pet_core::NameError::TooLong => Error::<T>::NameTooLong,
+			})?;
+			name.try_into().map_err(|_| Error::<T>::NameTooLong)
+		}
+
+		/// Check that the pet can change hands.
+		fn ensure_unlocked(id: PetId) -> DispatchResult {
+			ensure!(!T::Locks::is_locked(id), Error::<T>::PetLocked);
+			Ok(())
+		}
+
+		/// Check that the pet hatched, eggs can't be fed, put to sleep or battle.
+		fn ensure_hatched(id: PetId) -> DispatchResult {
+			ensure!(!Eggs::<T>::contains_key(id), Error::<T>::PetIsEgg);
+			Ok(())
+		}
+
+		/// Bring the hatch block of the egg of `owner` closer by `IncubationBoost`, used when
+		/// an incubator item is used from the inventory.
+		pub fn warm_egg(owner: &T::AccountId) -> DispatchResult {
+			let (id, _) = PetsInfo::<T>::get(owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			let hatch_at = Eggs::<T>::try_mutate(id, |egg| {
+				let hatch_at = egg.as_mut().ok_or(Error::<T>::NotAnEgg)?;
+				*hatch_at = hatch_at.saturating_sub(T::IncubationBoost::get());
+				Ok::<_, Error<T>>(*hatch_at)
+			})?;
+
+			Self::deposit_event(Event::EggWarmed { owner: owner.clone(), pet_id: id, hatch_at });
+
+			Ok(())
+		}
+
+		/// Check that the account is allowed to receive a pet.
+		fn ensure_can_own(who: &T::AccountId) -> DispatchResult {
+			Self::can_own(who).map_err(|reason| Error::<T>::from(reason).into())
+		}
+
+		/// Whether the account can receive a pet, minted, bought, adopted or given, for
+		/// `PetApi`. The calls check it the same way.
+		pub fn can_own(who: &T::AccountId) -> Result<(), MintBlockReason> {
+			ensure!(!PetsInfo::<T>::contains_key(who), MintBlockReason::MaxPetsReached);
+			ensure!(!GameKeys::<T>::contains_key(who), MintBlockReason::GameKey);
+			ensure!(!CoOwnedPets::<T>::contains_key(who), MintBlockReason::CoOwner);
+			Ok(())
+		}
+
+		/// Whether the account can mint a pet of `species` now, for `PetApi`. Only the name
+		/// and the id of the pet are left to check.
+		pub fn can_mint(who: &T::AccountId, species: &Species) -> Result<(), MintBlockReason> {
+			ensure!(!Paused::<T>::get(), MintBlockReason::GamePaused);
+			Self::can_own(who)?;
+			let cap = SpeciesCaps::<T>::get(species).unwrap_or(u32::MAX);
+			ensure!(SpeciesPopulation::<T>::get(species) < cap, MintBlockReason::SpeciesSoldOut);
+			Ok(())
+		}
+
+		/// Put the pet of `owner` to sleep.
+		pub fn sleep_pet(owner: &T::AccountId) -> Result<PetId, DispatchError> {
+			let (id, _) = PetsInfo::<T>::get(owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			Self::ensure_hatched(id)?;
+
+			PetVitals::<T>::mutate(id, |vitals| {
+				vitals.last_slept = Some(frame_system::Pallet::<T>::block_number());
+				vitals.slept_at = Self::moment();
+				vitals.sleeps.saturating_inc();
+			});
+			Self::record_activity(id, |activity| activity.sleeps.saturating_inc());
+
+			Self::deposit_event(Event::PetSleeped { owner: owner.clone(), pet_id: id });
+			Self::deposit_stats(id);
+
+			Ok(id)
+		}
+
+		/// Feed the pet of `owner`, also used when food is given from the inventory.
+		pub fn feed_pet(owner: &T::AccountId) -> Result<PetId, DispatchError> {
+			let (id, _) = PetsInfo::<T>::get(owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			Self::ensure_hatched(id)?;
+
+			PetVitals::<T>::mutate(id, |vitals| {
+				vitals.last_fed = frame_system::Pallet::<T>::block_number();
+				vitals.fed_at = Self::moment();
+				vitals.feeds.saturating_inc();
+			});
+			Self::record_activity(id, |activity| activity.feeds.saturating_inc());
+
+			Self::deposit_event(Event::PetFeeded { owner: owner.clone(), pet_id: id });
+			Self::deposit_stats(id);
+
+			Ok(id)
+		}
+
+		/// The current day, counted in `BlocksPerDay` since genesis.
+		pub fn current_day() -> u32 {
+			let day =
+				frame_system::Pallet::<T>::block_number() / T::BlocksPerDay::get().max(1u32.into());
+			day.saturated_into()
+		}
+
+		/// What the pet did today.
+		pub fn activity_today(id: PetId) -> Activity {
+			let day = Self::current_day();
+			let activity = DailyActivity::<T>::get(id);
+			if activity.day == day {
+				activity
+			} else {
+				Activity { day, ..Default::default() }
+			}
+		}
+
+		fn record_activity(id: PetId, count: impl FnOnce(&mut Activity)) {
+			let mut activity = Self::activity_today(id);
+			count(&mut activity);
+			// A day counts toward the streak once the pet was both fed and put to sleep
+			let cared = activity.feeds > 0 && activity.sleeps > 0;
+			let day = activity.day;
+			DailyActivity::<T>::insert(id, activity);
+			if cared {
+				Self::extend_streak(id, day);
+			}
+		}
+
+		/// Count `day` in the care streak of a pet, rewarding its owner at the milestones of
+		/// `StreakRewards`. A day that isn't the one after the last starts a new streak.
+		fn extend_streak(id: PetId, day: u32) {
+			let streak = CareStreaks::<T>::get(id);
+			if streak.days > 0 && streak.last_day == day {
+				return;
+			}
+			let days = if streak.days > 0 && streak.last_day.saturating_add(1) == day {
+				streak.days.saturating_add(1)
+			} else {
+				1
+			};
+			CareStreaks::<T>::insert(id, CareStreak { days, last_day: day });
+
+			let Some(owner) = PetOwners::<T>::get(id) else { return };
+			for (milestone, item, amount) in T::StreakRewards::get() {
+				if milestone == days {
+					T::Items::give(&owner, item, amount);
+					Self::deposit_event(Event::StreakRewarded {
+						owner: owner.clone(),
+						pet_id: id,
+						days,
+						item,
+						amount,
+					});
+				}
+			}
+		}
+
+		/// The days in a row a pet was cared for until yesterday or today, zero once it
+		/// missed a day.
+		pub fn care_streak(id: PetId) -> u32 {
+			let streak = CareStreaks::<T>::get(id);
+			if streak.last_day.saturating_add(1) >= Self::current_day() {
+				streak.days
+			} else {
+				0
+			}
+		}
+
+		/// The care times and experience of a pet, its stats are derived from them with
+		/// `pet_core`.
+		pub fn care(id: PetId) -> pet_core::Care {
+			PetVitals::<T>::get(id).care(Experience::<T>::get(id))
+		}
+
+		/// The combat stats of a pet, from the entry of the registry it refers to. Pets without
+		/// one, or referring to an entry that isn't registered, battle with neutral stats.
+		pub fn stats_block(id: PetId) -> StatsBlock {
+			PetStatsBlocks::<T>::get(id)
+				.and_then(StatsBlocks::<T>::get)
+				.unwrap_or(StatsBlock::NEUTRAL)
+		}
+
+		/// Give a new pet the stats block of its species, if the species has one.
+		pub(crate) fn assign_stats_block(id: PetId, species: &Species) {
+			if let Some(block_id) = SpeciesStatsBlocks::<T>::get(species) {
+				PetStatsBlocks::<T>::insert(id, block_id);
+			}
+		}
+
+		/// Register a neutral stats block for each species, numbered like `Species::ALL`, and
+		/// give it to the species. Returns the number of species.
+		pub(crate) fn register_species_stats_blocks() -> u32 {
+			for (block_id, species) in Species::ALL.iter().enumerate() {
+				StatsBlocks::<T>::insert(block_id as StatsBlockId, StatsBlock::NEUTRAL);
+				SpeciesStatsBlocks::<T>::insert(species, block_id as StatsBlockId);
+			}
+			Species::ALL.len() as u32
+		}
+
+		/// The `pet_core` jealousy of the household of `head`, from the last feeds of its pet
+		/// and the pets of its `Household` members.
+		pub fn household_jealousy(head: &T::AccountId) -> u16 {
+			let last_feeds: Vec<_> = sp_std::iter::once(head.clone())
+				.chain(T::Household::members(head))
+				.filter_map(|account| PetsInfo::<T>::get(&account))
+				.map(|(id, _)| Some(PetVitals::<T>::get(id).last_fed.saturated_into()))
+				.collect();
+			pet_core::household_jealousy(&last_feeds, &CareRules::<T>::get().into())
+		}
+
+		/// The part of `experience` a pet earns, cut by the jealousy of its owner's household.
+		fn experience_gain(id: PetId, experience: u64) -> u64 {
+			let jealousy =
+				PetOwners::<T>::get(id).map_or(0, |owner| Self::household_jealousy(&owner));
+			pet_core::experience_gain(experience, jealousy)
+		}
+
+		/// The level of a pet, from its experience.
+		pub fn level(id: PetId) -> u32 {
+			pet_core::level(Experience::<T>::get(id))
+		}
+
+		/// Tell the pet's current stats, so clients don't have to read them back from storage.
+		fn deposit_stats(id: PetId) {
+			let vitals = PetVitals::<T>::get(id);
+			Self::deposit_event(Event::StatsChanged {
+				pet_id: id,
+				last_feed: vitals.last_fed,
+				last_sleep: vitals.last_slept,
+				experience: Experience::<T>::get(id),
+				fed_at: vitals.fed_at,
+				slept_at: vitals.slept_at,
+			});
+		}
+
+		/// The wall-clock time of the current block, `None` at genesis which has no time. In
+		/// `on_initialize` it's still the time of the previous block.
+		pub fn moment() -> Option<Moment> {
+			let now: Moment = T::UnixTime::now().as_millis().saturated_into();
+			(now > 0).then_some(now)
+		}
+
+		/// The wall-clock time of a past or future block for `PetApi`, estimated from the time
+		/// of the current block with `BlocksPerDay` blocks a day. Blocks before a pause of
+		/// block production are estimated too late, care times are stored as they happened.
+		pub fn block_moment(block: T::BlockNumber) -> Option<Moment> {
+			let now = Self::moment()?;
+			let current = frame_system::Pallet::<T>::block_number();
+			let blocks_per_day: Moment = T::BlocksPerDay::get().saturated_into();
+			let block_time = 24 * 60 * 60 * 1000 / blocks_per_day.max(1);
+			if block <= current {
+				let blocks: Moment = current.saturating_sub(block).saturated_into();
+				Some(now.saturating_sub(blocks.saturating_mul(block_time)))
+			} else {
+				let blocks: Moment = block.saturating_sub(current).saturated_into();
+				Some(now.saturating_add(blocks.saturating_mul(block_time)))
+			}
+		}
 	}
-}
\ No newline at end of file
+}
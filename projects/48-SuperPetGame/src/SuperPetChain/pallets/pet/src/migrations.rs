@@ -0,0 +1,172 @@
+//! Storage migrations of the pet pallet, to be listed in the `Executive` of the runtime.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::*,
+	storage_alias,
+	traits::{GetStorageVersion, OnRuntimeUpgrade},
+};
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+/// Version 1 keeps the care times of a pet in `PetVitals`, instead of the `LastFeedTime` and
+/// `LastSleepTime` maps.
+pub mod v1 {
+	use super::*;
+
+	#[storage_alias]
+	pub type LastFeedTime<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		PetId,
+		<T as frame_system::Config>::BlockNumber,
+		ValueQuery,
+	>;
+
+	#[storage_alias]
+	pub type LastSleepTime<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, PetId, <T as frame_system::Config>::BlockNumber>;
+
+	/// Move the care times of every pet into its vitals. The counters start at zero, feeds and
+	/// sleeps weren't counted before.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1);
+			}
+			let mut moved = 0u64;
+			for (id, last_fed) in LastFeedTime::<T>::drain() {
+				let last_slept = LastSleepTime::<T>::take(id);
+				PetVitals::<T>::insert(id, Vitals { last_fed, last_slept, ..Default::default() });
+				moved += 1;
+			}
+			// Every pet has a feed time, so no sleep time should be left
+			let stray = LastSleepTime::<T>::clear(u32::MAX, None).unique as u64;
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(1 + 2 * moved + stray, 1 + 3 * moved + stray)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((LastFeedTime::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pets = u32::decode(&mut &state[..]).map_err(|_| "Can't decode the pet count")?;
+			ensure!(PetVitals::<T>::iter_keys().count() as u32 == pets, "Not every pet has vitals");
+			ensure!(LastFeedTime::<T>::iter_keys().next().is_none(), "Feed times are left");
+			ensure!(LastSleepTime::<T>::iter_keys().next().is_none(), "Sleep times are left");
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "Wrong storage version");
+			Ok(())
+		}
+	}
+}
+
+/// Version 2 adds the wall-clock times of the care to `Vitals`.
+pub mod v2 {
+	use super::*;
+
+	/// `Vitals` in storage version 1.
+	#[derive(Decode)]
+	pub struct VitalsV1<BlockNumber> {
+		pub last_fed: BlockNumber,
+		pub last_slept: Option<BlockNumber>,
+		pub feeds: u32,
+		pub sleeps: u32,
+	}
+
+	/// Add empty wall-clock times to the vitals of every pet, the times of past care are lost.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return T::DbWeight::get().reads(1);
+			}
+			let mut translated = 0u64;
+			PetVitals::<T>::translate::<VitalsV1<T::BlockNumber>, _>(|_, old| {
+				translated += 1;
+				Some(Vitals {
+					last_fed: old.last_fed,
+					last_slept: old.last_slept,
+					feeds: old.feeds,
+					sleeps: old.sleeps,
+					fed_at: None,
+					slept_at: None,
+				})
+			});
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(1 + translated, 1 + translated)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((PetVitals::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pets = u32::decode(&mut &state[..]).map_err(|_| "Can't decode the pet count")?;
+			ensure!(PetVitals::<T>::iter_values().count() as u32 == pets, "Vitals were lost");
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "Wrong storage version");
+			Ok(())
+		}
+	}
+}
+
+/// Version 3 moves the combat stats of pets out of their species, into the stats block registry.
+pub mod v3 {
+	use super::*;
+
+	/// Register a neutral stats block for each species, and give every pet, owned or in the
+	/// adoption pool, the block of its species. Battles go on as before, until the blocks are
+	/// changed.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				return T::DbWeight::get().reads(1);
+			}
+			let species = Pallet::<T>::register_species_stats_blocks() as u64;
+			let mut assigned = 0u64;
+			for (_, (id, pet)) in PetsInfo::<T>::iter() {
+				Pallet::<T>::assign_stats_block(id, &pet.species);
+				assigned += 1;
+			}
+			for (id, abandoned) in AdoptionPool::<T>::iter() {
+				Pallet::<T>::assign_stats_block(id, &abandoned.pet.species);
+				assigned += 1;
+			}
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(1 + 2 * assigned, 1 + 2 * species + assigned)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let pets = PetsInfo::<T>::iter_keys().count() + AdoptionPool::<T>::iter_keys().count();
+			Ok((pets as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pets = u32::decode(&mut &state[..]).map_err(|_| "Can't decode the pet count")?;
+			ensure!(
+				PetStatsBlocks::<T>::iter_keys().count() as u32 == pets,
+				"Not every pet has a stats block"
+			);
+			ensure!(
+				SpeciesStatsBlocks::<T>::iter_keys().count() == Species::ALL.len(),
+				"Not every species has a stats block"
+			);
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "Wrong storage version");
+			Ok(())
+		}
+	}
+}
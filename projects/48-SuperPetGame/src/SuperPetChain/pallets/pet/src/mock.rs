@@ -0,0 +1,187 @@
+use crate as pallet_pet;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, Randomness},
+	weights::{
+		constants::{RocksDbWeight, WEIGHT_REF_TIME_PER_SECOND},
+		Weight,
+	},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::{Header, TestSignature, UintAuthorityId},
+	traits::{BlakeTwo256, Hash, IdentityLookup},
+	Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// Blocks in a day, with 6 second blocks.
+pub const DAY: u64 = 14_400;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		Timestamp: pallet_timestamp,
+		Utility: pallet_utility,
+		PetModule: pallet_pet,
+	}
+);
+
+parameter_types! {
+	/// The same block weight as the runtime, 2 seconds of compute.
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::simple_max(
+			Weight::from_parts(2 * WEIGHT_REF_TIME_PER_SECOND, u64::MAX),
+		);
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = RocksDbWeight;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const DefaultMarketFee: Permill = Permill::from_percent(2);
+	pub const DefaultMaxRoyalty: Permill = Permill::from_percent(10);
+	pub const DefaultCareRules: pallet_pet::CareParameters = pallet_pet::CareParameters {
+		fullness_lasts: 1200,
+		energy_lasts: 2400,
+		hungry_below: 500,
+		tired_below: 500,
+	};
+	/// The same share of the block as the runtime.
+	pub MaxIdleWeight: Weight = Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 4, u64::MAX);
+	pub StreakRewards: Vec<(u32, u32, u32)> = vec![(7, 0, 3), (30, 1, 1)];
+	/// Like the runtime, tests can switch to `Either`.
+	pub static CoOwnerConsentRule: pallet_pet::CoOwnerConsent = pallet_pet::CoOwnerConsent::All;
+}
+
+impl pallet_pet::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type StringLimit = ConstU32<30>;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+	type Currency = Balances;
+	type OnMarketFee = ();
+	type DefaultMarketFee = DefaultMarketFee;
+	type DefaultMaxRoyalty = DefaultMaxRoyalty;
+	type MarketFeeOrigin = EnsureRoot<u64>;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type GameAdminOrigin = EnsureRoot<u64>;
+	type MaxScore = ConstU32<1000>;
+	type MaxGameKeys = ConstU32<4>;
+	type BattleExperience = ConstU32<10>;
+	type PauseOrigin = EnsureRoot<u64>;
+	type BlocksPerDay = ConstU64<DAY>;
+	type OnMint = ();
+	type AbandonAfter = ConstU64<{ 30 * DAY }>;
+	type ReclaimWindow = ConstU64<{ 7 * DAY }>;
+	type AdoptionFee = ConstU64<2>;
+	type IncubationPeriod = ConstU64<0>;
+	type IncubationBoost = ConstU64<0>;
+	type RenameFee = ConstU64<1>;
+	type OnRenameFee = ();
+	type OfferDuration = ConstU64<{ 3 * DAY }>;
+	type MaxOffersPerPet = ConstU32<20>;
+	type MaxOffersPerBlock = ConstU32<50>;
+	type OfferDeposit = ConstU64<1>;
+	type BundleItem = u32;
+	type Items = ();
+	type MaxBundleItems = ConstU32<8>;
+	type Locks = ();
+	type OnAbandon = ();
+	type DefaultCareRules = DefaultCareRules;
+	type MaxIdleWeight = MaxIdleWeight;
+	type MessageFee = ConstU64<1>;
+	type OnMessageFee = ();
+	type MaxMessageLen = ConstU32<280>;
+	type MessageEra = ConstU64<DAY>;
+	type MaxMessagesPerEra = ConstU32<50>;
+	type MaxMemorialBadges = ConstU32<16>;
+	type CareScheduler = ();
+	type CareSchedulerOrigin = EnsureRoot<u64>;
+	type MinCareInterval = ConstU64<{ DAY / 24 }>;
+	type UnixTime = Timestamp;
+	type StreakRewards = StreakRewards;
+	type Randomness = TestRandomness;
+	type Household = ();
+	type MaxCoOwners = ConstU32<2>;
+	type CoOwnerConsent = CoOwnerConsentRule;
+}
+
+/// Rolls from the parent block hash, predictable but enough for tests.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		(BlakeTwo256::hash_of(&(System::parent_hash(), subject)), System::block_number())
+	}
+}
+
+/// Batches let tests care for the pets of derivative accounts at once, like players do.
+impl pallet_utility::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = ();
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
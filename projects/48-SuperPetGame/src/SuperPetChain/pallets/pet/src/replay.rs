@@ -0,0 +1,140 @@
+//! Replay of recorded pet calls against the mock runtime, to turn what happened on a devnet,
+//! like the calls around an incident, into a regression test. A capture is transcribed into
+//! `Recorded` calls, in the order the chain included them and with the blocks they landed in.
+//! Accounts are numbered like the mock's, and balances given in its units. `replay` runs them
+//! with the hooks of every block in between, checks each call went the way it did on the chain,
+//! and `PetState` compares the pets left with the chain's storage after the capture.
+
+use crate::{mock::*, *};
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{Currency, Hooks, UnfilteredDispatchable},
+};
+
+/// Milliseconds between two blocks, like on the devnet.
+const MILLIS_PER_BLOCK: u64 = 6_000;
+
+/// A call of a capture and how it went on the chain.
+pub struct Recorded {
+	/// The block the call was included in.
+	pub block: u64,
+	/// The account that signed the call, `None` for root, like through sudo.
+	pub signer: Option<u64>,
+	pub call: Call<Test>,
+	/// Whether the call went through, or the error it failed with.
+	pub result: DispatchResult,
+	/// The pet events the call deposited, in order, `None` to leave them unchecked.
+	pub events: Option<Vec<Event<Test>>>,
+}
+
+impl Recorded {
+	/// A call `signer` made at `block` that went through.
+	pub fn ok(block: u64, signer: u64, call: Call<Test>) -> Self {
+		Self { block, signer: Some(signer), call, result: Ok(()), events: None }
+	}
+
+	/// A call `signer` made at `block` that failed with `error`.
+	pub fn failed(
+		block: u64,
+		signer: u64,
+		call: Call<Test>,
+		error: impl Into<DispatchError>,
+	) -> Self {
+		Self { block, signer: Some(signer), call, result: Err(error.into()), events: None }
+	}
+
+	/// Check the call deposited exactly these pet events.
+	pub fn with_events(mut self, events: Vec<Event<Test>>) -> Self {
+		self.events = Some(events);
+		self
+	}
+}
+
+/// What the storage keeps about a pet, to compare with the chain's after a capture.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PetState {
+	pub id: PetId,
+	pub owner: u64,
+	pub name: Vec<u8>,
+	pub experience: u64,
+	pub last_fed: u64,
+	/// The price the pet is listed at.
+	pub listing: Option<u64>,
+}
+
+impl PetState {
+	/// Every pet, by id.
+	pub fn all() -> Vec<PetState> {
+		let mut pets: Vec<PetState> = PetsInfo::<Test>::iter()
+			.map(|(owner, (id, pet))| PetState {
+				id,
+				owner,
+				name: pet.name.into_inner(),
+				experience: Experience::<Test>::get(id),
+				last_fed: PetVitals::<Test>::get(id).last_fed,
+				listing: Listings::<Test>::get(id),
+			})
+			.collect();
+		pets.sort_by_key(|pet| pet.id);
+		pets
+	}
+}
+
+/// Move to `block`, running the pet hooks of the blocks on the way and setting their time.
+fn run_to_block(block: u64) {
+	let max_block = BlockWeights::get().max_block;
+	while System::block_number() < block {
+		PetModule::on_idle(System::block_number(), max_block);
+		System::set_block_number(System::block_number() + 1);
+		Timestamp::set_timestamp(System::block_number() * MILLIS_PER_BLOCK);
+		PetModule::on_initialize(System::block_number());
+	}
+}
+
+/// The pet events deposited since the events were last reset.
+fn pet_events() -> Vec<Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			RuntimeEvent::PetModule(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Fund `balances`, then replay `capture` from the current block, panicking at the first call
+/// that didn't go the way it did on the chain. Run it inside `new_test_ext().execute_with`.
+pub fn replay(balances: &[(u64, u64)], capture: &[Recorded]) {
+	for (who, amount) in balances {
+		let _ = Balances::deposit_creating(who, *amount);
+	}
+	for (step, recorded) in capture.iter().enumerate() {
+		assert!(
+			recorded.block >= System::block_number(),
+			"call {step} at block {} is before block {}",
+			recorded.block,
+			System::block_number()
+		);
+		run_to_block(recorded.block);
+		System::reset_events();
+
+		let origin = match recorded.signer {
+			Some(signer) => RuntimeOrigin::signed(signer),
+			None => RuntimeOrigin::root(),
+		};
+		let result = recorded
+			.call
+			.clone()
+			.dispatch_bypass_filter(origin)
+			.map(|_| ())
+			.map_err(|e| e.error);
+		assert_eq!(
+			result, recorded.result,
+			"call {step} at block {}: {:?}",
+			recorded.block, recorded.call
+		);
+		if let Some(events) = &recorded.events {
+			assert_eq!(&pet_events(), events, "events of call {step} at block {}", recorded.block);
+		}
+	}
+}
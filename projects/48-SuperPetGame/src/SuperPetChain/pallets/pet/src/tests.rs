@@ -0,0 +1,1200 @@
+use crate::{
+	mock::*,
+	replay::{replay, PetState, Recorded},
+	*,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::GetDispatchInfo,
+	storage::storage_prefix,
+	traits::{Currency, Get, Hooks},
+	weights::Weight,
+	BoundedVec,
+};
+use sp_runtime::{traits::Dispatchable, DispatchError, Perbill, Permill};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The share of a block background work may take, the rest is left to transactions.
+const BACKGROUND_SHARE: Perbill = Perbill::from_percent(25);
+
+/// The share of a block caring for a whole household in one batch may take, so players
+/// batching their care leave room for everyone else.
+const BATCH_CARE_SHARE: Perbill = Perbill::from_percent(5);
+
+/// The derivative accounts a player batch cares for, see `DerivativeHousehold` in the runtime.
+const HOUSEHOLD_SLOTS: u16 = 8;
+
+/// `share` of the block's weight. Proof size isn't limited on a solo chain.
+fn share_of_block(share: Perbill) -> Weight {
+	let max_block = BlockWeights::get().max_block;
+	Weight::from_parts(share * max_block.ref_time(), max_block.proof_size())
+}
+
+/// The most weight the hooks may use in a block.
+fn background_limit() -> Weight {
+	share_of_block(BACKGROUND_SHARE)
+}
+
+/// Give pets `0..count` to accounts `100..`, never fed, and move to the block they can be
+/// abandoned at.
+fn neglected_pets(count: u32) -> u64 {
+	for id in 0..count {
+		let owner = 100 + id as u64;
+		let pet = PetInfo { name: Default::default(), species: Species::Turtle };
+		PetsInfo::<Test>::insert(owner, (id, pet));
+		PetOwners::<Test>::insert(id, owner);
+	}
+	let now = <Test as Config>::AbandonAfter::get();
+	System::set_block_number(now);
+	now
+}
+
+fn queue_sweep() {
+	DeferredTasks::<Test>::put(BoundedVec::truncate_from(vec![DeferredTask::AdoptionSweep]));
+}
+
+/// Every storage key and its value but the events, which weights don't count.
+fn storage_snapshot() -> BTreeMap<Vec<u8>, Vec<u8>> {
+	let events = [storage_prefix(b"System", b"Events"), storage_prefix(b"System", b"EventCount")];
+	let mut snapshot = BTreeMap::new();
+	let mut key = Vec::new();
+	while let Some(next) = sp_io::storage::next_key(&key) {
+		if !events.iter().any(|prefix| next.starts_with(prefix)) {
+			let value = sp_io::storage::get(&next).map(|value| value.to_vec()).unwrap_or_default();
+			snapshot.insert(next.clone(), value);
+		}
+		key = next;
+	}
+	snapshot
+}
+
+/// The weight of writing each key `f` inserted, changed or removed. The weight `f` takes must
+/// at least cover it, whatever it counts for reads.
+fn weight_of_writes(f: impl FnOnce()) -> Weight {
+	let before = storage_snapshot();
+	f();
+	let after = storage_snapshot();
+	let written = before
+		.keys()
+		.chain(after.keys())
+		.filter(|key| before.get(*key) != after.get(*key))
+		.collect::<BTreeSet<_>>()
+		.len();
+	<Test as frame_system::Config>::DbWeight::get().writes(written as u64)
+}
+
+#[test]
+fn max_idle_weight_leaves_most_of_the_block() {
+	assert!(MaxIdleWeight::get().all_lte(background_limit()));
+}
+
+#[test]
+fn adoption_sweep_is_queued_once_a_day() {
+	new_test_ext().execute_with(|| {
+		PetModule::on_initialize(DAY - 1);
+		assert!(DeferredTasks::<Test>::get().is_empty());
+
+		PetModule::on_initialize(DAY);
+		PetModule::on_initialize(2 * DAY);
+		assert_eq!(DeferredTasks::<Test>::get().into_inner(), vec![DeferredTask::AdoptionSweep]);
+	});
+}
+
+#[test]
+fn sweep_stops_at_max_idle_weight_and_resumes() {
+	new_test_ext().execute_with(|| {
+		let now = neglected_pets(500);
+		queue_sweep();
+
+		let used = PetModule::on_idle(now, BlockWeights::get().max_block);
+		assert!(used.all_lte(MaxIdleWeight::get()));
+		let abandoned = AdoptionPool::<Test>::iter().count();
+		assert!(abandoned > 0 && abandoned < 500);
+		assert!(TaskCursor::<Test>::exists());
+
+		let mut blocks = 1;
+		while !DeferredTasks::<Test>::get().is_empty() {
+			let used = PetModule::on_idle(now + blocks, BlockWeights::get().max_block);
+			assert!(used.all_lte(MaxIdleWeight::get()));
+			blocks += 1;
+			assert!(blocks < 10, "the sweep doesn't move on");
+		}
+		assert_eq!(AdoptionPool::<Test>::iter().count(), 500);
+		assert!(!TaskCursor::<Test>::exists());
+		System::assert_last_event(
+			Event::DeferredTaskDone { task: DeferredTask::AdoptionSweep }.into(),
+		);
+	});
+}
+
+#[test]
+fn sweep_never_uses_more_than_the_block_left() {
+	new_test_ext().execute_with(|| {
+		let now = neglected_pets(100);
+		queue_sweep();
+
+		assert_eq!(PetModule::on_idle(now, Weight::zero()), Weight::zero());
+		assert_eq!(AdoptionPool::<Test>::iter().count(), 0);
+
+		// Room for ten pets and a bit, not for eleven
+		let remaining = <() as WeightInfo>::adoption_sweep(10)
+			.saturating_add(<Test as frame_system::Config>::DbWeight::get().reads(1));
+		let used = PetModule::on_idle(now, remaining);
+		assert_eq!(used, <() as WeightInfo>::adoption_sweep(10));
+		assert_eq!(AdoptionPool::<Test>::iter().count(), 10);
+	});
+}
+
+#[test]
+fn sweep_weight_covers_what_it_writes() {
+	new_test_ext().execute_with(|| {
+		let now = neglected_pets(20);
+		// Abandoning drops the listings, co-owners and consents of the pets too
+		for id in 0..20 {
+			let owner = 100 + id as u64;
+			Listings::<Test>::insert(id, 10);
+			ListingBundles::<Test>::insert(id, BoundedVec::default());
+			ListedBy::<Test>::insert(id, owner);
+			let co_owners = vec![200 + id as u64, 300 + id as u64];
+			for co_owner in &co_owners {
+				CoOwnedPets::<Test>::insert(co_owner, id);
+				TradeConsents::<Test>::insert(id, co_owner, ());
+			}
+			TradeConsents::<Test>::insert(id, owner, ());
+			CoOwners::<Test>::insert(id, BoundedVec::truncate_from(co_owners));
+		}
+		queue_sweep();
+
+		let mut used = Weight::zero();
+		let written = weight_of_writes(|| {
+			used = PetModule::on_idle(now, BlockWeights::get().max_block);
+		});
+		assert_eq!(AdoptionPool::<Test>::iter().count(), 20);
+		assert!(written.all_lte(used));
+	});
+}
+
+#[test]
+fn sweep_waits_while_paused() {
+	new_test_ext().execute_with(|| {
+		let now = neglected_pets(10);
+		queue_sweep();
+		Paused::<Test>::put(true);
+
+		PetModule::on_idle(now, BlockWeights::get().max_block);
+		assert_eq!(AdoptionPool::<Test>::iter().count(), 0);
+		assert_eq!(DeferredTasks::<Test>::get().len(), 1);
+	});
+}
+
+#[test]
+fn sweep_leaves_eggs_and_fed_pets() {
+	new_test_ext().execute_with(|| {
+		let now = neglected_pets(3);
+		Eggs::<Test>::insert(0, now);
+		PetVitals::<Test>::mutate(1, |vitals| vitals.last_fed = now - 1);
+		queue_sweep();
+
+		PetModule::on_idle(now, BlockWeights::get().max_block);
+		assert_eq!(AdoptionPool::<Test>::iter_keys().collect::<Vec<_>>(), vec![2]);
+		assert!(DeferredTasks::<Test>::get().is_empty());
+	});
+}
+
+#[test]
+fn offer_expiries_fit_in_the_background_share() {
+	new_test_ext().execute_with(|| {
+		let max_offers: u32 = <Test as Config>::MaxOffersPerBlock::get();
+		let expires_at = 1 + <Test as Config>::OfferDuration::get();
+		let pets = max_offers / <Test as Config>::MaxOffersPerPet::get() + 1;
+		neglected_pets(pets);
+		System::set_block_number(1);
+
+		// Fill the block's expiries, spreading the offers over enough pets
+		for buyer in 0..max_offers as u64 {
+			let _ = Balances::deposit_creating(&buyer, 100);
+			let pet_id = buyer as u32 % pets;
+			assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(buyer), pet_id, 10));
+		}
+		let _ = Balances::deposit_creating(&max_offers.into(), 100);
+		assert_noop!(
+			PetModule::make_offer(RuntimeOrigin::signed(max_offers.into()), 0, 10),
+			Error::<Test>::TooManyOffers
+		);
+
+		let mut used = Weight::zero();
+		let written = weight_of_writes(|| used = PetModule::on_initialize(expires_at));
+		assert!(written.all_lte(used));
+		assert!(used.all_lte(background_limit()));
+		assert!(Offers::<Test>::iter().next().is_none());
+		assert_eq!(Balances::reserved_balance(0), 0);
+	});
+}
+
+#[test]
+fn care_weights_cover_what_care_writes() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		Eggs::<Test>::insert(0, 1);
+		assert_ok!(PetModule::schedule_care(
+			RuntimeOrigin::signed(1),
+			CareAction::Feed,
+			2,
+			Some(DAY)
+		));
+		let check = |origin: RuntimeOrigin, call: Call<Test>| {
+			let call = RuntimeCall::from(call);
+			let weight = call.get_dispatch_info().weight;
+			assert!(weight.ref_time() > 0);
+			let written = weight_of_writes(|| assert_ok!(call.dispatch(origin)));
+			assert!(written.all_lte(weight));
+		};
+
+		// The co-owner goes through every role lookup, and completing the day extends the
+		// care streak
+		check(RuntimeOrigin::signed(5), Call::hatch {});
+		check(RuntimeOrigin::signed(5), Call::sleep {});
+		check(RuntimeOrigin::signed(5), Call::feed {});
+		System::set_block_number(2);
+		check(
+			RuntimeOrigin::root(),
+			Call::run_scheduled_care { owner: 1, action: CareAction::Feed },
+		);
+		assert_eq!(CareStreaks::<Test>::get(0).days, 1);
+	});
+}
+
+#[test]
+fn household_batch_care_fits_its_share_of_the_block() {
+	new_test_ext().execute_with(|| {
+		for index in 0..HOUSEHOLD_SLOTS {
+			let member = Utility::derivative_account_id(1, index);
+			let name = b"Kit".to_vec();
+			assert_ok!(PetModule::mint(
+				RuntimeOrigin::signed(member),
+				name,
+				Species::Rabbit,
+				index.into()
+			));
+		}
+		let care = |index| {
+			[Call::<Test>::feed {}, Call::sleep {}].map(|call| {
+				RuntimeCall::Utility(pallet_utility::Call::as_derivative {
+					index,
+					call: Box::new(call.into()),
+				})
+			})
+		};
+		let calls: Vec<RuntimeCall> = (0..HOUSEHOLD_SLOTS).flat_map(care).collect();
+		assert!(calls.len() as u32 <= Utility::batched_calls_limit());
+		let batch = RuntimeCall::Utility(pallet_utility::Call::batch_all { calls });
+
+		// The care of each pet is paid for, and a whole household still leaves room for others
+		let weight = batch.get_dispatch_info().weight;
+		let care_weight = <() as WeightInfo>::feed().saturating_add(<() as WeightInfo>::sleep());
+		assert!(weight.all_gte(care_weight.saturating_mul(HOUSEHOLD_SLOTS.into())));
+		assert!(weight.all_lte(share_of_block(BATCH_CARE_SHARE)));
+
+		assert_ok!(batch.dispatch(RuntimeOrigin::signed(1)));
+		for id in 0..HOUSEHOLD_SLOTS.into() {
+			assert_eq!(PetVitals::<Test>::get(id).last_slept, Some(1));
+			assert_eq!(DailyActivity::<Test>::get(id).feeds, 1);
+		}
+	});
+}
+
+#[test]
+fn cleanup_pays_the_deposit_of_offers_left_by_the_owner() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Sly".to_vec(), Species::Snake, 0));
+		let _ = Balances::deposit_creating(&2, 100);
+		let _ = Balances::deposit_creating(&3, 100);
+		assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(2), 0, 10));
+		assert_eq!(Balances::reserved_balance(2), 11);
+		assert_noop!(
+			PetModule::cleanup(RuntimeOrigin::signed(3), 0),
+			Error::<Test>::NothingToCleanUp
+		);
+
+		// The buyer got the pet without its offer
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(1), 2));
+		assert_ok!(PetModule::cleanup(RuntimeOrigin::signed(3), 0));
+		System::assert_has_event(
+			Event::PetCleanedUp { by: 3, pet_id: 0, offers: 1, reward: 1 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 99);
+		assert_eq!(Balances::free_balance(3), 101);
+		assert!(OfferDeposits::<Test>::iter().next().is_none());
+		assert_noop!(
+			PetModule::cleanup(RuntimeOrigin::signed(3), 0),
+			Error::<Test>::NothingToCleanUp
+		);
+	});
+}
+
+#[test]
+fn migration_moves_care_times_into_vitals() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+	use migrations::v1::{LastFeedTime, LastSleepTime, MigrateToV1};
+
+	new_test_ext().execute_with(|| {
+		LastFeedTime::<Test>::insert(0, 5);
+		LastFeedTime::<Test>::insert(1, 7);
+		LastSleepTime::<Test>::insert(1, 6);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(PetVitals::<Test>::get(0), Vitals { last_fed: 5, ..Default::default() });
+		assert_eq!(
+			PetVitals::<Test>::get(1),
+			Vitals { last_fed: 7, last_slept: Some(6), ..Default::default() }
+		);
+		assert!(LastFeedTime::<Test>::iter().next().is_none());
+		assert!(LastSleepTime::<Test>::iter().next().is_none());
+		assert_eq!(PetModule::on_chain_storage_version(), StorageVersion::new(1));
+
+		// Running it again leaves the vitals alone
+		LastFeedTime::<Test>::insert(0, 9);
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(PetVitals::<Test>::get(0).last_fed, 5);
+	});
+}
+
+#[test]
+fn migration_adds_empty_care_moments() {
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+	use migrations::v2::MigrateToV2;
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<PetModule>();
+		// Vitals of version 1: last fed, last slept, feeds and sleeps
+		unhashed::put(&PetVitals::<Test>::hashed_key_for(0), &(5u64, Some(6u64), 2u32, 1u32));
+
+		MigrateToV2::<Test>::on_runtime_upgrade();
+		assert_eq!(
+			PetVitals::<Test>::get(0),
+			Vitals { last_fed: 5, last_slept: Some(6), feeds: 2, sleeps: 1, ..Default::default() }
+		);
+		assert_eq!(PetModule::on_chain_storage_version(), StorageVersion::new(2));
+	});
+}
+
+#[test]
+fn migration_gives_pets_the_stats_block_of_their_species() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+	use migrations::v3::MigrateToV3;
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(2).put::<PetModule>();
+		let turtle = PetInfo { name: Default::default(), species: Species::Turtle };
+		let snake = PetInfo { name: Default::default(), species: Species::Snake };
+		PetsInfo::<Test>::insert(1, (0, turtle));
+		PetsInfo::<Test>::insert(2, (1, snake.clone()));
+		AdoptionPool::<Test>::insert(2, AbandonedPet { owner: 3, pet: snake, since: 1 });
+
+		MigrateToV3::<Test>::on_runtime_upgrade();
+		// Blocks are numbered like `Species::ALL`, and neutral
+		assert_eq!(SpeciesStatsBlocks::<Test>::get(Species::Rabbit), Some(2));
+		assert_eq!(StatsBlocks::<Test>::get(2), Some(StatsBlock::NEUTRAL));
+		assert_eq!(PetStatsBlocks::<Test>::get(0), Some(0));
+		assert_eq!(PetStatsBlocks::<Test>::get(1), Some(1));
+		assert_eq!(PetStatsBlocks::<Test>::get(2), Some(1));
+		assert_eq!(PetModule::on_chain_storage_version(), StorageVersion::new(3));
+
+		// Running it again leaves the registry alone
+		StatsBlocks::<Test>::insert(0, StatsBlock { power: 500 });
+		MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(PetModule::stats_block(0), StatsBlock { power: 500 });
+	});
+}
+
+#[test]
+fn stats_blocks_rebalance_pets_without_touching_them() {
+	new_test_ext().execute_with(|| {
+		let strong = StatsBlock { power: 2_000 };
+		assert_noop!(
+			PetModule::set_stats_block(RuntimeOrigin::signed(1), 7, strong),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			PetModule::set_stats_block(RuntimeOrigin::root(), 7, StatsBlock { power: 0 }),
+			Error::<Test>::InvalidStatsBlock
+		);
+		assert_noop!(
+			PetModule::set_species_stats_block(RuntimeOrigin::root(), Species::Snake, 7),
+			Error::<Test>::StatsBlockNotFound
+		);
+		assert_ok!(PetModule::set_stats_block(RuntimeOrigin::root(), 7, strong));
+		assert_ok!(PetModule::set_species_stats_block(RuntimeOrigin::root(), Species::Snake, 7));
+
+		// Pets of species without a block battle with neutral stats
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Sly".to_vec(), Species::Snake, 0));
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(2), b"Bo".to_vec(), Species::Turtle, 1));
+		assert_eq!(PetStatsBlocks::<Test>::get(0), Some(7));
+		assert_eq!(PetModule::stats_block(1), StatsBlock::NEUTRAL);
+		assert_eq!(PetModule::pet_profile(0).unwrap().stats_block, strong);
+
+		// Both are tired, never having slept: 10 halved, doubled by the snake's block
+		Experience::<Test>::insert(0, 9);
+		Experience::<Test>::insert(1, 9);
+		assert_ok!(PetModule::battle(1, 2));
+		let log = System::events()
+			.into_iter()
+			.find_map(|record| match record.event {
+				RuntimeEvent::PetModule(Event::BattleResolved { log, .. }) => Some(log),
+				_ => None,
+			})
+			.unwrap();
+		assert_eq!((log.challenger_weight, log.opponent_weight), (10, 5));
+
+		// Changing the entry changes every pet referring to it, `PetInfo` stays as it was
+		let info = PetsInfo::<Test>::get(1);
+		assert_ok!(PetModule::set_stats_block(RuntimeOrigin::root(), 7, StatsBlock::NEUTRAL));
+		assert_eq!(PetModule::stats_block(0), StatsBlock::NEUTRAL);
+		assert_eq!(PetsInfo::<Test>::get(1), info);
+		System::assert_last_event(
+			Event::StatsBlockSet { block_id: 7, block: StatsBlock::NEUTRAL }.into(),
+		);
+	});
+}
+
+#[test]
+fn names_are_limited_in_bytes_not_characters() {
+	new_test_ext().execute_with(|| {
+		// 7 turtles are 28 bytes, one more goes past the 30 of `StringLimit`
+		let turtles = "🐢".repeat(7).into_bytes();
+		let too_many = "🐢".repeat(8).into_bytes();
+		assert_noop!(
+			PetModule::mint(RuntimeOrigin::signed(1), too_many, Species::Turtle, 0),
+			Error::<Test>::NameTooLong
+		);
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), turtles.clone(), Species::Turtle, 0));
+		assert_eq!(PetsInfo::<Test>::get(1).unwrap().1.name.into_inner(), turtles);
+
+		// 15 two byte letters fill it exactly
+		let accented = "é".repeat(15).into_bytes();
+		assert_ok!(PetModule::rename(RuntimeOrigin::signed(1), accented.clone()));
+		assert_eq!(PetsInfo::<Test>::get(1).unwrap().1.name.into_inner(), accented);
+		assert_noop!(
+			PetModule::rename(RuntimeOrigin::signed(1), "é".repeat(16).into_bytes()),
+			Error::<Test>::NameTooLong
+		);
+	});
+}
+
+#[test]
+fn names_must_be_utf8() {
+	new_test_ext().execute_with(|| {
+		// A turtle cut short, and a byte that never starts a character
+		let shelly = b"Shelly".to_vec();
+		let cut = "Shelly🐢".as_bytes()[..8].to_vec();
+		assert_noop!(
+			PetModule::mint(RuntimeOrigin::signed(1), cut, Species::Turtle, 0),
+			Error::<Test>::NameNotUtf8
+		);
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), shelly.clone(), Species::Turtle, 0));
+		assert_noop!(
+			PetModule::rename(RuntimeOrigin::signed(1), vec![b'S', 0xff]),
+			Error::<Test>::NameNotUtf8
+		);
+		assert_eq!(PetsInfo::<Test>::get(1).unwrap().1.name.into_inner(), shelly);
+	});
+}
+
+const ACTIONS: [Action; 5] =
+	[Action::Care, Action::Battle, Action::Trade, Action::Decline, Action::Manage];
+
+/// Register `key` as a game key of `owner`, proposed then accepted.
+fn add_game_key(owner: u64, key: u64) {
+	assert_ok!(PetModule::propose_game_key(RuntimeOrigin::signed(owner), key));
+	assert_ok!(PetModule::accept_game_key(RuntimeOrigin::signed(key), owner));
+}
+
+/// Mint pet 0 for account 1, and register account 2 as its game key.
+fn pet_with_game_key() {
+	assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Shelly".to_vec(), Species::Turtle, 0));
+	add_game_key(1, 2);
+}
+
+/// The owner and the pet `who` may take `action` with.
+fn can(who: u64, pet_id: Option<PetId>, action: Action) -> Result<(u64, PetId), DispatchError> {
+	PetModule::ensure_can(RuntimeOrigin::signed(who), pet_id, action)
+		.map(|(owner, id, _)| (owner, id))
+}
+
+#[test]
+fn game_keys_play_and_owners_do_everything() {
+	let game_key_allowed = [
+		(Action::Care, true),
+		(Action::Battle, true),
+		(Action::Trade, false),
+		(Action::Decline, false),
+		(Action::Manage, false),
+	];
+	assert_eq!(game_key_allowed.map(|(action, _)| action), ACTIONS);
+	for (action, allowed) in game_key_allowed {
+		assert!(action.allows(Role::Owner));
+		assert_eq!(action.allows(Role::GameKey), allowed);
+	}
+}
+
+#[test]
+fn owners_act_on_their_pet() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		for action in ACTIONS {
+			assert_eq!(can(1, None, action), Ok((1, 0)));
+			assert_eq!(can(1, Some(0), action), Ok((1, 0)));
+		}
+	});
+}
+
+#[test]
+fn game_keys_act_for_their_owner() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		for action in [Action::Care, Action::Battle] {
+			assert_eq!(can(2, None, action), Ok((1, 0)));
+			assert_eq!(can(2, Some(0), action), Ok((1, 0)));
+		}
+		for action in [Action::Trade, Action::Decline, Action::Manage] {
+			assert_noop!(can(2, None, action), Error::<Test>::NotAuthorized);
+			assert_noop!(can(2, Some(0), action), Error::<Test>::NotAuthorized);
+		}
+	});
+}
+
+#[test]
+fn only_the_pet_of_the_signer_can_be_named() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(3), b"Rex".to_vec(), Species::Turtle, 1));
+		for action in ACTIONS {
+			assert_noop!(can(1, Some(1), action), Error::<Test>::NotOwner);
+			assert_noop!(can(3, Some(0), action), Error::<Test>::NotOwner);
+		}
+		assert_noop!(can(2, Some(1), Action::Care), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn game_keys_are_accepted_by_the_key_and_can_leave() {
+	new_test_ext().execute_with(|| {
+		// A proposal alone doesn't stop the account from owning a pet
+		assert_ok!(PetModule::propose_game_key(RuntimeOrigin::signed(1), 2));
+		assert_ok!(PetModule::propose_game_key(RuntimeOrigin::signed(3), 2));
+		assert_eq!(GameKeys::<Test>::get(2), None);
+		assert_ok!(PetModule::can_own(&2));
+		assert_noop!(
+			PetModule::accept_game_key(RuntimeOrigin::signed(2), 4),
+			Error::<Test>::GameKeyNotProposed
+		);
+		assert_noop!(
+			PetModule::propose_game_key(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::GameKeyInUse
+		);
+
+		assert_ok!(PetModule::accept_game_key(RuntimeOrigin::signed(2), 1));
+		assert_eq!(GameKeys::<Test>::get(2), Some(1));
+		assert!(GameKeyProposals::<Test>::iter_prefix(2).next().is_none());
+		// Keys don't propose keys of their own
+		assert_noop!(
+			PetModule::propose_game_key(RuntimeOrigin::signed(2), 5),
+			Error::<Test>::NotAuthorized
+		);
+
+		// The key leaves on its own, and can own a pet again
+		assert_ok!(PetModule::remove_game_key(RuntimeOrigin::signed(2), 2));
+		System::assert_last_event(Event::GameKeyRemoved { owner: 1, key: 2 }.into());
+		assert_eq!(OwnerGameKeys::<Test>::get(1).len(), 0);
+		assert_ok!(PetModule::can_own(&2));
+	});
+}
+
+#[test]
+fn accounts_without_a_pet_cannot_act() {
+	new_test_ext().execute_with(|| {
+		for action in ACTIONS {
+			assert_noop!(can(1, None, action), Error::<Test>::AccountHasNoPet);
+			assert_noop!(can(1, Some(0), action), Error::<Test>::AccountHasNoPet);
+		}
+		// A key of an account without a pet is refused what keys can't do first
+		add_game_key(1, 2);
+		assert_noop!(can(2, None, Action::Care), Error::<Test>::AccountHasNoPet);
+		assert_noop!(can(2, None, Action::Trade), Error::<Test>::NotAuthorized);
+	});
+}
+
+#[test]
+fn only_signed_origins_act() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		for action in ACTIONS {
+			for origin in [RuntimeOrigin::root(), RuntimeOrigin::none()] {
+				assert_noop!(
+					PetModule::ensure_can(origin.clone(), None, action).map(|_| ()),
+					DispatchError::BadOrigin
+				);
+			}
+		}
+	});
+}
+
+#[test]
+fn calls_check_the_action_they_take() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(3), b"Rex".to_vec(), Species::Turtle, 1));
+
+		assert_ok!(PetModule::feed(RuntimeOrigin::signed(2)));
+		assert_ok!(PetModule::sleep(RuntimeOrigin::signed(2)));
+		assert_ok!(PetModule::challenge(RuntimeOrigin::signed(2), 3));
+		assert_noop!(
+			PetModule::transfer(RuntimeOrigin::signed(2), 4),
+			Error::<Test>::NotAuthorized
+		);
+		assert_noop!(
+			PetModule::list_for_sale(RuntimeOrigin::signed(2), 10),
+			Error::<Test>::NotAuthorized
+		);
+		assert_noop!(
+			PetModule::rename(RuntimeOrigin::signed(2), b"Rex".to_vec()),
+			Error::<Test>::NotAuthorized
+		);
+		assert_noop!(
+			PetModule::schedule_care(RuntimeOrigin::signed(2), CareAction::Feed, 10, None),
+			Error::<Test>::NotAuthorized
+		);
+
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(1), 4));
+		assert_eq!(PetOwners::<Test>::get(0), Some(4));
+	});
+}
+
+#[test]
+fn only_the_scheduler_runs_scheduled_care() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		let feed = CareAction::Feed;
+		assert_ok!(PetModule::schedule_care(RuntimeOrigin::signed(1), feed, 10, Some(DAY)));
+
+		System::set_block_number(10);
+		// The owner can't run its schedule ahead or skip a run
+		assert_noop!(
+			PetModule::run_scheduled_care(RuntimeOrigin::signed(1), 1, feed),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			PetModule::run_scheduled_care(RuntimeOrigin::root(), 1, CareAction::Sleep),
+			Error::<Test>::CareNotScheduled
+		);
+
+		assert_ok!(PetModule::run_scheduled_care(RuntimeOrigin::root(), 1, feed));
+		System::assert_last_event(
+			Event::ScheduledCareRan { owner: 1, action: feed, done: true }.into(),
+		);
+		assert_eq!(PetVitals::<Test>::get(0).last_fed, 10);
+		assert_eq!(
+			ScheduledCare::<Test>::get(1, feed),
+			Some(CareSchedule { next: 10 + DAY, every: Some(DAY) })
+		);
+		assert_noop!(
+			PetModule::run_scheduled_care(RuntimeOrigin::root(), 1, feed),
+			Error::<Test>::CareNotDue
+		);
+	});
+}
+
+/// Mint pet 0 for account 1, and share it with account 5.
+fn pet_with_co_owner() {
+	assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Shelly".to_vec(), Species::Turtle, 0));
+	assert_ok!(PetModule::add_co_owner(RuntimeOrigin::signed(1), 0, 5));
+}
+
+#[test]
+fn co_owners_play_and_trade_but_only_owners_manage() {
+	for action in ACTIONS {
+		assert_eq!(action.allows(Role::CoOwner), action != Action::Manage);
+	}
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		assert_ok!(PetModule::feed(RuntimeOrigin::signed(5)));
+		assert_eq!(can(5, Some(0), Action::Battle), Ok((1, 0)));
+		assert_noop!(can(5, None, Action::Manage), Error::<Test>::NotAuthorized);
+		assert_noop!(
+			PetModule::add_co_owner(RuntimeOrigin::signed(5), 0, 6),
+			Error::<Test>::NotAuthorized
+		);
+		assert_noop!(
+			PetModule::mint(RuntimeOrigin::signed(5), b"Rex".to_vec(), Species::Turtle, 1),
+			Error::<Test>::CoOwnerCannotOwnPet
+		);
+
+		// Co-owners can leave
+		assert_ok!(PetModule::remove_co_owner(RuntimeOrigin::signed(5), 0, 5));
+		assert_noop!(can(5, None, Action::Care), Error::<Test>::AccountHasNoPet);
+	});
+}
+
+#[test]
+fn co_owners_memorialize_the_pet_for_its_owner() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		System::set_block_number(1 + <Test as Config>::AbandonAfter::get());
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(1), 0, true));
+
+		assert_ok!(PetModule::memorialize(RuntimeOrigin::signed(5), 0));
+		System::assert_last_event(Event::PetMemorialized { owner: 1, pet_id: 0, level: 1 }.into());
+		assert!(Memorials::<Test>::contains_key(1, 0));
+		assert!(!Memorials::<Test>::contains_key(5, 0));
+		assert_eq!(PetsInfo::<Test>::get(1), None);
+		assert_eq!(PetOwners::<Test>::get(0), None);
+		assert_eq!(CoOwnedPets::<Test>::get(5), None);
+	});
+}
+
+#[test]
+fn trades_wait_for_the_consent_of_the_others() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		assert_noop!(
+			PetModule::transfer(RuntimeOrigin::signed(1), 4),
+			Error::<Test>::TradeNotConsented
+		);
+		assert_noop!(
+			PetModule::transfer(RuntimeOrigin::signed(5), 4),
+			Error::<Test>::TradeNotConsented
+		);
+
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(5), 0, true));
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(1), 4));
+		assert_eq!(PetOwners::<Test>::get(0), Some(4));
+		// Co-owners don't follow the pet
+		assert!(CoOwners::<Test>::get(0).is_empty());
+		assert_eq!(CoOwnedPets::<Test>::get(5), None);
+		assert_eq!(TradeConsents::<Test>::iter_prefix(0).count(), 0);
+	});
+}
+
+#[test]
+fn sales_check_the_consent_of_the_others_again() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		let _ = Balances::deposit_creating(&3, 100);
+		let _ = Balances::deposit_creating(&4, 100);
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(5), 0, true));
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(1), 10));
+		assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(4), 0, 10));
+
+		// The co-owner changed its mind after the pet was listed
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(5), 0, false));
+		assert_noop!(PetModule::buy(RuntimeOrigin::signed(3), 0), Error::<Test>::TradeNotConsented);
+		assert_noop!(
+			PetModule::accept_offer(RuntimeOrigin::signed(1), 4),
+			Error::<Test>::TradeNotConsented
+		);
+
+		// Turning a trade down needs no consent
+		assert_ok!(PetModule::reject_offer(RuntimeOrigin::signed(5), 4));
+		assert_eq!(Balances::reserved_balance(4), 0);
+		assert_ok!(PetModule::cancel_listing(RuntimeOrigin::signed(5)));
+		assert_eq!(ListedBy::<Test>::get(0), None);
+	});
+}
+
+#[test]
+fn the_co_owner_listing_a_pet_consents_to_its_sale() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		let _ = Balances::deposit_creating(&3, 100);
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(1), 0, true));
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(5), 10));
+		assert_eq!(ListedBy::<Test>::get(0), Some(5));
+
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		assert_eq!(PetOwners::<Test>::get(0), Some(3));
+		assert_eq!(ListedBy::<Test>::get(0), None);
+	});
+}
+
+#[test]
+fn pets_leave_no_consents_or_co_owners_behind() {
+	new_test_ext().execute_with(|| {
+		pet_with_game_key();
+		assert_ok!(PetModule::add_co_owner(RuntimeOrigin::signed(1), 0, 5));
+		let _ = Balances::deposit_creating(&4, 100);
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(5), 0, true));
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(1), 20));
+		assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(4), 0, 10));
+
+		assert_ok!(PetModule::accept_offer(RuntimeOrigin::signed(1), 4));
+		assert_eq!(PetOwners::<Test>::get(0), Some(4));
+		assert_eq!(Listings::<Test>::get(0), None);
+		assert_eq!(ListedBy::<Test>::get(0), None);
+		assert_eq!(TradeConsents::<Test>::iter_prefix(0).count(), 0);
+		assert!(CoOwners::<Test>::get(0).is_empty());
+		assert_eq!(CoOwnedPets::<Test>::get(5), None);
+		assert_noop!(
+			PetModule::cleanup(RuntimeOrigin::signed(3), 0),
+			Error::<Test>::NothingToCleanUp
+		);
+
+		// Game keys act for the account, which has no pet until it gets another one
+		assert_eq!(GameKeys::<Test>::get(2), Some(1));
+		assert_noop!(can(2, None, Action::Care), Error::<Test>::AccountHasNoPet);
+		assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Rex".to_vec(), Species::Turtle, 1));
+		assert_eq!(can(2, None, Action::Care), Ok((1, 1)));
+	});
+}
+
+#[test]
+fn abandoned_pets_leave_no_consents_or_co_owners_behind() {
+	new_test_ext().execute_with(|| {
+		pet_with_co_owner();
+		assert_ok!(PetModule::consent_to_trade(RuntimeOrigin::signed(5), 0, true));
+		System::set_block_number(1 + <Test as Config>::AbandonAfter::get());
+
+		assert_ok!(PetModule::abandon(RuntimeOrigin::signed(3), 0));
+		assert!(AdoptionPool::<Test>::contains_key(0));
+		assert_eq!(TradeConsents::<Test>::iter_prefix(0).count(), 0);
+		assert!(CoOwners::<Test>::get(0).is_empty());
+		assert_eq!(CoOwnedPets::<Test>::get(5), None);
+	});
+}
+
+#[test]
+fn anyone_sharing_the_pet_trades_it_alone_under_either() {
+	new_test_ext().execute_with(|| {
+		CoOwnerConsentRule::set(CoOwnerConsent::Either);
+		pet_with_co_owner();
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(5), 4));
+		assert_eq!(PetOwners::<Test>::get(0), Some(4));
+	});
+}
+
+/// A day on the market: account 1 lists its pet, account 2 can't buy it while it owns one, and
+/// account 3 buys it and feeds it.
+fn market_capture() -> Vec<Recorded> {
+	let mint = |name: &[u8], id| Call::mint { name: name.to_vec(), species: Species::Turtle, id };
+	vec![
+		Recorded::ok(2, 1, mint(b"Shelly", 0)).with_events(vec![Event::PetMinted {
+			owner: 1,
+			pet_id: 0,
+			species: Species::Turtle,
+			payer: 1,
+		}]),
+		Recorded::ok(3, 2, mint(b"Rex", 1)),
+		Recorded::ok(10, 1, Call::list_for_sale { price: 50 })
+			.with_events(vec![Event::PetListed { seller: 1, pet_id: 0, price: 50 }]),
+		Recorded::failed(11, 2, Call::buy { id: 0 }, Error::<Test>::AccountAlreadyHasPet),
+		Recorded::ok(12, 3, Call::buy { id: 0 }),
+		Recorded::ok(20, 3, Call::feed {}),
+	]
+}
+
+#[test]
+fn replayed_market_capture_ends_with_the_chain_storage() {
+	new_test_ext().execute_with(|| {
+		replay(&[(2, 100), (3, 100)], &market_capture());
+		let pet = |id, owner, name: &[u8], last_fed| PetState {
+			id,
+			owner,
+			name: name.to_vec(),
+			experience: 0,
+			last_fed,
+			listing: None,
+		};
+		assert_eq!(PetState::all(), vec![pet(0, 3, b"Shelly", 20), pet(1, 2, b"Rex", 3)]);
+		// The seller got the price less the 2% market fee
+		assert_eq!(Balances::free_balance(1), 49);
+		assert_eq!(Balances::free_balance(3), 50);
+	});
+}
+
+#[test]
+#[should_panic(expected = "call 3 at block 11")]
+fn replay_stops_at_a_call_going_another_way() {
+	new_test_ext().execute_with(|| {
+		let mut capture = market_capture();
+		// Account 2 sold its pet on the chain, so it could buy
+		capture[3].result = Ok(());
+		replay(&[(2, 100), (3, 100)], &capture);
+	});
+}
+
+/// Mint pet 0 for account 1, its breeder.
+fn bred_pet() {
+	assert_ok!(PetModule::mint(RuntimeOrigin::signed(1), b"Shelly".to_vec(), Species::Turtle, 0));
+}
+
+/// Mint pet 0 for account 1, its breeder, and list it for `price`.
+fn listed_pet(price: u64) {
+	bred_pet();
+	assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(1), price));
+}
+
+#[test]
+fn sales_pay_the_market_fee_out_of_the_price() {
+	new_test_ext().execute_with(|| {
+		listed_pet(50);
+		let _ = Balances::deposit_creating(&3, 100);
+		let issuance = Balances::total_issuance();
+
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 1, buyer: 3, pet_id: 0, price: 50, fee: 1, royalty: 0 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 49);
+		assert_eq!(Balances::free_balance(3), 50);
+		// The mock hands the fee to `()`, which burns it
+		assert_eq!(Balances::total_issuance(), issuance - 1);
+	});
+}
+
+#[test]
+fn market_fee_rounds_to_the_nearest_unit() {
+	new_test_ext().execute_with(|| {
+		// 2% of 24 is 0.48
+		listed_pet(24);
+		let _ = Balances::deposit_creating(&3, 100);
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 1, buyer: 3, pet_id: 0, price: 24, fee: 0, royalty: 0 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 24);
+
+		// 2% of 26 is 0.52, the seller gets the rest of the price
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(3), 26));
+		let _ = Balances::deposit_creating(&4, 100);
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(4), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 3, buyer: 4, pet_id: 0, price: 26, fee: 1, royalty: 0 }.into(),
+		);
+		assert_eq!(Balances::free_balance(3), 76 + 25);
+		assert_eq!(Balances::free_balance(4), 74);
+	});
+}
+
+#[test]
+fn buyers_keep_their_account_alive() {
+	new_test_ext().execute_with(|| {
+		listed_pet(50);
+		// Paying the fee would leave nothing
+		let _ = Balances::deposit_creating(&3, 50);
+		assert_noop!(
+			PetModule::buy(RuntimeOrigin::signed(3), 0),
+			pallet_balances::Error::<Test>::KeepAlive
+		);
+		assert_eq!(Balances::free_balance(1), 0);
+
+		let _ = Balances::deposit_creating(&3, 1);
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		assert_eq!(Balances::free_balance(3), 1);
+	});
+}
+
+#[test]
+fn governance_sets_the_market_fee() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PetModule::set_market_fee(RuntimeOrigin::signed(1), Permill::from_percent(10)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(PetModule::set_market_fee(RuntimeOrigin::root(), Permill::from_percent(10)));
+		System::assert_last_event(Event::MarketFeeSet { fee: Permill::from_percent(10) }.into());
+
+		listed_pet(50);
+		let _ = Balances::deposit_creating(&3, 100);
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		assert_eq!(Balances::free_balance(1), 45);
+	});
+}
+
+#[test]
+fn offers_hold_the_price_and_deposit_until_accepted() {
+	new_test_ext().execute_with(|| {
+		bred_pet();
+		let _ = Balances::deposit_creating(&4, 100);
+		assert_noop!(
+			PetModule::make_offer(RuntimeOrigin::signed(1), 0, 50),
+			Error::<Test>::CannotOfferOnOwnPet
+		);
+		assert_noop!(
+			PetModule::make_offer(RuntimeOrigin::signed(4), 1, 50),
+			Error::<Test>::PetNotFound
+		);
+
+		assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(4), 0, 50));
+		let expires_at = 1 + <Test as Config>::OfferDuration::get();
+		System::assert_last_event(
+			Event::OfferMade { buyer: 4, pet_id: 0, price: 50, expires_at }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(4), 51);
+		assert_noop!(
+			PetModule::make_offer(RuntimeOrigin::signed(4), 0, 60),
+			Error::<Test>::OfferAlreadyMade
+		);
+		assert_noop!(
+			PetModule::accept_offer(RuntimeOrigin::signed(1), 5),
+			Error::<Test>::OfferNotFound
+		);
+
+		assert_ok!(PetModule::accept_offer(RuntimeOrigin::signed(1), 4));
+		System::assert_last_event(
+			Event::OfferAccepted { seller: 1, buyer: 4, pet_id: 0, price: 50, fee: 1, royalty: 0 }
+				.into(),
+		);
+		assert_eq!(PetOwners::<Test>::get(0), Some(4));
+		assert_eq!(Balances::free_balance(1), 49);
+		// The deposit is back with the buyer
+		assert_eq!(Balances::reserved_balance(4), 0);
+		assert_eq!(Balances::free_balance(4), 50);
+		assert!(!Offers::<Test>::contains_key(0));
+		assert!(!OfferExpiries::<Test>::contains_key(expires_at));
+	});
+}
+
+#[test]
+fn offers_are_only_made_with_the_funds_to_hold() {
+	new_test_ext().execute_with(|| {
+		bred_pet();
+		// The price is there, the deposit isn't
+		let _ = Balances::deposit_creating(&4, 50);
+		assert_noop!(
+			PetModule::make_offer(RuntimeOrigin::signed(4), 0, 50),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert!(OfferExpiries::<Test>::iter().next().is_none());
+	});
+}
+
+#[test]
+fn rejected_withdrawn_and_expired_offers_are_refunded() {
+	new_test_ext().execute_with(|| {
+		bred_pet();
+		for buyer in 2..=4 {
+			let _ = Balances::deposit_creating(&buyer, 100);
+			assert_ok!(PetModule::make_offer(RuntimeOrigin::signed(buyer), 0, 10));
+		}
+
+		assert_noop!(
+			PetModule::reject_offer(RuntimeOrigin::signed(5), 2),
+			Error::<Test>::AccountHasNoPet
+		);
+		assert_ok!(PetModule::reject_offer(RuntimeOrigin::signed(1), 2));
+		System::assert_last_event(Event::OfferRejected { owner: 1, buyer: 2, pet_id: 0 }.into());
+		assert_noop!(
+			PetModule::reject_offer(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::OfferNotFound
+		);
+
+		assert_ok!(PetModule::withdraw_offer(RuntimeOrigin::signed(3), 0));
+		System::assert_last_event(Event::OfferWithdrawn { buyer: 3, pet_id: 0 }.into());
+		assert_noop!(
+			PetModule::withdraw_offer(RuntimeOrigin::signed(3), 0),
+			Error::<Test>::OfferNotFound
+		);
+
+		let expires_at = 1 + <Test as Config>::OfferDuration::get();
+		PetModule::on_initialize(expires_at - 1);
+		assert_eq!(Balances::reserved_balance(4), 11);
+		PetModule::on_initialize(expires_at);
+		System::assert_last_event(Event::OfferExpired { buyer: 4, pet_id: 0 }.into());
+
+		for buyer in 2..=4 {
+			assert_eq!(Balances::reserved_balance(buyer), 0);
+			assert_eq!(Balances::free_balance(buyer), 100);
+		}
+		assert!(!Offers::<Test>::contains_key(0));
+		assert!(OfferDeposits::<Test>::iter().next().is_none());
+		// The pet never moved
+		assert_eq!(PetOwners::<Test>::get(0), Some(1));
+	});
+}
+
+#[test]
+fn breeders_set_their_royalty_up_to_the_cap() {
+	new_test_ext().execute_with(|| {
+		bred_pet();
+		assert_noop!(
+			PetModule::set_royalty(RuntimeOrigin::signed(1), Permill::from_percent(11)),
+			Error::<Test>::RoyaltyTooHigh
+		);
+		assert_noop!(
+			PetModule::set_royalty(RuntimeOrigin::signed(5), Permill::from_percent(5)),
+			Error::<Test>::AccountHasNoPet
+		);
+
+		assert_ok!(PetModule::set_royalty(RuntimeOrigin::signed(1), Permill::from_percent(5)));
+		System::assert_last_event(
+			Event::RoyaltySet { breeder: 1, pet_id: 0, royalty: Permill::from_percent(5) }.into(),
+		);
+		assert_eq!(
+			Provenance::<Test>::get(0),
+			Some(PetProvenance { breeder: Some(1), royalty: Permill::from_percent(5) })
+		);
+
+		// Owning the pet doesn't make one its breeder
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(1), 2));
+		assert_noop!(
+			PetModule::set_royalty(RuntimeOrigin::signed(2), Permill::from_percent(1)),
+			Error::<Test>::NotBreeder
+		);
+	});
+}
+
+#[test]
+fn resales_pay_the_breeder_its_royalty() {
+	new_test_ext().execute_with(|| {
+		listed_pet(100);
+		assert_ok!(PetModule::set_royalty(RuntimeOrigin::signed(1), Permill::from_percent(5)));
+		let _ = Balances::deposit_creating(&2, 200);
+		let _ = Balances::deposit_creating(&3, 200);
+
+		// The breeder selling its own pet owes itself nothing
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(2), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 1, buyer: 2, pet_id: 0, price: 100, fee: 2, royalty: 0 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 98);
+
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(2), 100));
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 2, buyer: 3, pet_id: 0, price: 100, fee: 2, royalty: 5 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 98 + 5);
+		assert_eq!(Balances::free_balance(2), 100 + 93);
+		assert_eq!(Balances::free_balance(3), 100);
+	});
+}
+
+#[test]
+fn governance_caps_royalties_already_set() {
+	new_test_ext().execute_with(|| {
+		bred_pet();
+		assert_ok!(PetModule::set_royalty(RuntimeOrigin::signed(1), Permill::from_percent(5)));
+		assert_noop!(
+			PetModule::set_max_royalty(RuntimeOrigin::signed(1), Permill::from_percent(1)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(PetModule::set_max_royalty(RuntimeOrigin::root(), Permill::from_percent(1)));
+		System::assert_last_event(
+			Event::MaxRoyaltySet { royalty: Permill::from_percent(1) }.into(),
+		);
+
+		assert_ok!(PetModule::transfer(RuntimeOrigin::signed(1), 2));
+		assert_ok!(PetModule::list_for_sale(RuntimeOrigin::signed(2), 100));
+		let _ = Balances::deposit_creating(&3, 200);
+		assert_ok!(PetModule::buy(RuntimeOrigin::signed(3), 0));
+		System::assert_last_event(
+			Event::Sold { seller: 2, buyer: 3, pet_id: 0, price: 100, fee: 2, royalty: 1 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 1);
+		assert_eq!(Balances::free_balance(2), 97);
+	});
+}
@@ -0,0 +1,176 @@
+//! Weights of the care calls and of the work the pallet does in its hooks, laid out like
+//! `benchmark pallet` output for the benchmarks in `benchmarking.rs`.
+//!
+//! They aren't generated yet: the storage accesses below are counted by hand from the
+//! benchmarked paths, and their execution time is missing. Run `node-template benchmark
+//! pallet --chain dev --pallet pallet_pet --extrinsic '*' --steps 50 --repeat 20` on the
+//! reference machine and replace this file with its output.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_pet.
+pub trait WeightInfo {
+	fn adoption_sweep(n: u32) -> Weight;
+	fn expire_offers(n: u32) -> Weight;
+	fn feed() -> Weight;
+	fn hatch() -> Weight;
+	fn run_scheduled_care() -> Weight;
+	fn sleep() -> Weight;
+}
+
+/// Weights for pallet_pet using the node template runtime and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: PetModule Paused (r:1 w:0)
+	/// Storage: PetModule DeferredTasks (r:1 w:1)
+	/// Storage: PetModule TaskCursor (r:1 w:1)
+	/// Storage: PetModule PetOwners (r:n w:n)
+	/// Storage: PetModule Eggs (r:n w:0)
+	/// Storage: RentalModule Rentals (r:n w:0)
+	/// Storage: StakingModule Stakes (r:n w:0)
+	/// Storage: TournamentModule EnteredPets (r:n w:0)
+	/// Storage: PetModule PetVitals (r:n w:n)
+	/// Storage: PetModule PetsInfo (r:n w:n)
+	/// Storage: PetModule Listings (r:0 w:n)
+	/// Storage: PetModule ListingBundles (r:0 w:n)
+	/// Storage: PetModule ListedBy (r:0 w:n)
+	/// Storage: PetModule CoOwners (r:n w:n)
+	/// Storage: PetModule CoOwnedPets (r:n w:3n)
+	/// Storage: PetModule TradeConsents (r:n w:4n)
+	/// Storage: PetModule AdoptionPool (r:n w:n)
+	/// Storage: PetModule GameKeys (r:n w:0)
+	/// Storage: PetModule Experience (r:n w:0)
+	/// Storage: InsuranceModule Policies (r:n w:n)
+	/// Storage: InsuranceModule Policyholders (r:n w:n)
+	/// Storage: System Account (r:2n w:2n)
+	/// Storage: Timestamp Now (r:n w:0)
+	/// The range of component `n` is `[0, 100]`.
+	fn adoption_sweep(n: u32) -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((18_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((19_u64).saturating_mul(n.into())))
+	}
+	/// Storage: PetModule OfferExpiries (r:1 w:1)
+	/// Storage: PetModule Offers (r:n w:n)
+	/// Storage: PetModule OfferDeposits (r:n w:n)
+	/// Storage: System Account (r:n w:n)
+	/// The range of component `n` is `[0, 50]`.
+	fn expire_offers(n: u32) -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(n.into())))
+	}
+	/// Storage: PetModule Paused (r:1 w:0)
+	/// Storage: PetModule GameKeys (r:1 w:0)
+	/// Storage: PetModule CoOwnedPets (r:1 w:0)
+	/// Storage: PetModule PetOwners (r:1 w:0)
+	/// Storage: PetModule PetsInfo (r:1 w:0)
+	/// Storage: PetModule Eggs (r:1 w:0)
+	/// Storage: PetModule PetVitals (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: PetModule DailyActivity (r:1 w:1)
+	/// Storage: PetModule CareStreaks (r:1 w:1)
+	/// Storage: ItemsModule Inventory (r:1 w:1)
+	/// Storage: PetModule Experience (r:1 w:0)
+	fn feed() -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: PetModule Paused (r:1 w:0)
+	/// Storage: PetModule GameKeys (r:1 w:0)
+	/// Storage: PetModule CoOwnedPets (r:1 w:0)
+	/// Storage: PetModule PetOwners (r:1 w:0)
+	/// Storage: PetModule PetsInfo (r:1 w:0)
+	/// Storage: PetModule Eggs (r:1 w:1)
+	/// Storage: PetModule PetVitals (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: PetModule Experience (r:1 w:0)
+	fn hatch() -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: PetModule ScheduledCare (r:1 w:1)
+	/// Storage: PetModule Paused (r:1 w:0)
+	/// Storage: PetModule PetsInfo (r:1 w:0)
+	/// Storage: PetModule Eggs (r:1 w:0)
+	/// Storage: PetModule PetVitals (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: PetModule DailyActivity (r:1 w:1)
+	/// Storage: PetModule CareStreaks (r:1 w:1)
+	/// Storage: PetModule PetOwners (r:1 w:0)
+	/// Storage: ItemsModule Inventory (r:1 w:1)
+	/// Storage: PetModule Experience (r:1 w:0)
+	fn run_scheduled_care() -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: PetModule Paused (r:1 w:0)
+	/// Storage: PetModule GameKeys (r:1 w:0)
+	/// Storage: PetModule CoOwnedPets (r:1 w:0)
+	/// Storage: PetModule PetOwners (r:1 w:0)
+	/// Storage: PetModule PetsInfo (r:1 w:0)
+	/// Storage: PetModule Eggs (r:1 w:0)
+	/// Storage: PetModule PetVitals (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: PetModule DailyActivity (r:1 w:1)
+	/// Storage: PetModule CareStreaks (r:1 w:1)
+	/// Storage: ItemsModule Inventory (r:1 w:1)
+	/// Storage: PetModule Experience (r:1 w:0)
+	fn sleep() -> Weight {
+		Weight::zero()
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn adoption_sweep(n: u32) -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((18_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((19_u64).saturating_mul(n.into())))
+	}
+	fn expire_offers(n: u32) -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(n.into())))
+	}
+	fn feed() -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	fn hatch() -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn run_scheduled_care() -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	fn sleep() -> Weight {
+		Weight::zero()
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+}
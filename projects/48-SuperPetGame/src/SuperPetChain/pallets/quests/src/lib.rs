@@ -0,0 +1,194 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::Currency, RuntimeDebug};
+use scale_info::TypeInfo;
+
+type PetId = u32;
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// What the pet of an account did today.
+#[derive(Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Progress {
+	pub feeds: u32,
+	pub sleeps: u32,
+	pub battles_won: u32,
+}
+
+/// The pets quests are about, and the days they are counted in.
+pub trait PetActivity<AccountId> {
+	/// The current day, quests rotate when it changes.
+	fn current_day() -> u32;
+	/// The account's pet and what it did today, `None` if it has no pet.
+	fn today(who: &AccountId) -> Option<(PetId, Progress)>;
+}
+
+/// Hands out reward items.
+pub trait GiveItem<AccountId, Item> {
+	fn give_item(who: &AccountId, item: Item);
+}
+
+impl<AccountId, Item> GiveItem<AccountId, Item> for () {
+	fn give_item(_who: &AccountId, _item: Item) {}
+}
+
+/// What a pet has to do during the day to complete a quest.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum Objective {
+	Feed(u32),
+	Sleep(u32),
+	WinBattles(u32),
+}
+
+impl Objective {
+	pub fn is_met(&self, progress: &Progress) -> bool {
+		match *self {
+			Objective::Feed(times) => progress.feeds >= times,
+			Objective::Sleep(times) => progress.sleeps >= times,
+			Objective::WinBattles(wins) => progress.battles_won >= wins,
+		}
+	}
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum Reward<Item, Balance> {
+	Item(Item),
+	Tokens(Balance),
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Quest<Item, Balance> {
+	pub objective: Objective,
+	pub reward: Reward<Item, Balance>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency token rewards are minted in.
+		type Currency: Currency<Self::AccountId>;
+
+		/// The items that can be given as rewards.
+		type Item: Parameter + MaxEncodedLen + Copy;
+
+		/// Reads what the players' pets did today.
+		type Pets: PetActivity<Self::AccountId>;
+
+		/// Gives reward items to the players.
+		type Items: GiveItem<Self::AccountId, Self::Item>;
+
+		/// All the quests, a few of them are active each day.
+		#[pallet::constant]
+		type QuestPool: Get<Vec<Quest<Self::Item, BalanceOf<Self>>>>;
+
+		/// The number of quests active each day, taken in turn from `QuestPool`.
+		#[pallet::constant]
+		type QuestsPerDay: Get<u32>;
+	}
+
+	/// The day a reward was last claimed for each pet, and the quests claimed for it that day
+	/// as a bit set of their index. Claims follow the pet, whose progress they're paid for, so
+	/// passing it to another account doesn't pay its quests again.
+	#[pallet::storage]
+	pub type Claimed<T: Config> = StorageMap<_, Twox64Concat, PetId, (u32, u32), ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A quest is completed and its reward paid.
+		QuestRewardClaimed {
+			who: T::AccountId,
+			pet_id: PetId,
+			day: u32,
+			quest: u32,
+			reward: Reward<T::Item, BalanceOf<T>>,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		UnknownQuest,
+		QuestAlreadyClaimed,
+		QuestNotComplete,
+		AccountHasNoPet,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Claim the reward of one of today's quests, once the pet completed it. Each quest is
+		/// paid once a day per pet.
+		///
+		/// - quest: The index of the quest among today's, see `active_quests`
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn claim_quest_reward(origin: OriginFor<T>, quest: u32) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let day = T::Pets::current_day();
+			let active = Self::active_quests(day);
+			let Quest { objective, reward } =
+				*active.get(quest as usize).ok_or(Error::<T>::UnknownQuest)?;
+
+			let (pet_id, progress) = T::Pets::today(&sender).ok_or(Error::<T>::AccountHasNoPet)?;
+			let (claimed_day, claimed) = Claimed::<T>::get(pet_id);
+			let claimed = if claimed_day == day { claimed } else { 0 };
+			ensure!(claimed & (1 << quest) == 0, Error::<T>::QuestAlreadyClaimed);
+			ensure!(objective.is_met(&progress), Error::<T>::QuestNotComplete);
+
+			Claimed::<T>::insert(pet_id, (day, claimed | (1 << quest)));
+			match reward {
+				Reward::Item(item) => T::Items::give_item(&sender, item),
+				Reward::Tokens(amount) => {
+					let _ = T::Currency::deposit_creating(&sender, amount);
+				},
+			}
+
+			Self::deposit_event(Event::QuestRewardClaimed {
+				who: sender,
+				pet_id,
+				day,
+				quest,
+				reward,
+			});
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The quests of the day. The pool is walked through `QuestsPerDay` quests at a time,
+		/// so every quest comes back after a few days.
+		pub fn active_quests(day: u32) -> Vec<Quest<T::Item, BalanceOf<T>>> {
+			let pool = T::QuestPool::get();
+			if pool.is_empty() {
+				return Vec::new();
+			}
+			// Quests are claimed as bits of a u32
+			let per_day = T::QuestsPerDay::get().min(pool.len() as u32).min(32) as usize;
+			let first = (day as usize).wrapping_mul(per_day);
+			(0..per_day).map(|i| pool[first.wrapping_add(i) % pool.len()]).collect()
+		}
+	}
+}
@@ -0,0 +1,128 @@
+use crate::{self as pallet_quests, GiveItem, Objective, PetActivity, Progress, Quest, Reward};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		QuestsModule: pallet_quests,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub static Today: u32 = 0;
+	/// The pet of each account and what it did today. Accounts 1 to 3 own pets 1 to 3, account
+	/// 4 owns none.
+	pub static PetsToday: Vec<(u64, u32, Progress)> =
+		(1..=3).map(|who| (who, who as u32, Progress::default())).collect();
+	pub static GivenItems: Vec<(u64, u32)> = vec![];
+	/// Two quests a day, the pool comes back every other day.
+	pub static QuestPool: Vec<Quest<u32, u64>> = vec![
+		Quest { objective: Objective::Feed(2), reward: Reward::Item(10) },
+		Quest { objective: Objective::Sleep(1), reward: Reward::Tokens(50) },
+		Quest { objective: Objective::WinBattles(1), reward: Reward::Item(20) },
+		Quest { objective: Objective::Feed(1), reward: Reward::Item(30) },
+	];
+	pub static QuestsPerDay: u32 = 2;
+}
+
+/// Pets doing what `PetsToday` says, and reward items recorded in `GivenItems`.
+pub struct MockPets;
+
+impl PetActivity<u64> for MockPets {
+	fn current_day() -> u32 {
+		Today::get()
+	}
+
+	fn today(who: &u64) -> Option<(u32, Progress)> {
+		PetsToday::get()
+			.into_iter()
+			.find(|(owner, _, _)| owner == who)
+			.map(|(_, pet_id, progress)| (pet_id, progress))
+	}
+}
+
+impl GiveItem<u64, u32> for MockPets {
+	fn give_item(who: &u64, item: u32) {
+		let mut given = GivenItems::get();
+		given.push((*who, item));
+		GivenItems::set(given);
+	}
+}
+
+impl pallet_quests::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Item = u32;
+	type Pets = MockPets;
+	type Items = MockPets;
+	type QuestPool = QuestPool;
+	type QuestsPerDay = QuestsPerDay;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+// Every account has a balance of 100.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=5).map(|who| (who, 100)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
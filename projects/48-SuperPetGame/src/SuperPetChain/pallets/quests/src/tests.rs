@@ -0,0 +1,109 @@
+use crate::{mock::*, *};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
+
+/// Record what the pet of `who` did today.
+fn did_today(who: u64, feeds: u32, sleeps: u32, battles_won: u32) {
+	let mut pets = PetsToday::get();
+	for (_, _, progress) in pets.iter_mut().filter(|(owner, _, _)| *owner == who) {
+		*progress = Progress { feeds, sleeps, battles_won };
+	}
+	PetsToday::set(pets);
+}
+
+#[test]
+fn quests_of_the_day_rotate_through_the_pool() {
+	new_test_ext().execute_with(|| {
+		let pool = QuestPool::get();
+		assert_eq!(QuestsModule::active_quests(0), pool[0..2].to_vec());
+		assert_eq!(QuestsModule::active_quests(1), pool[2..4].to_vec());
+		assert_eq!(QuestsModule::active_quests(2), QuestsModule::active_quests(0));
+
+		// A day never has the same quest twice
+		QuestsPerDay::set(5);
+		assert_eq!(QuestsModule::active_quests(1).len(), 4);
+
+		QuestPool::set(vec![]);
+		assert!(QuestsModule::active_quests(0).is_empty());
+	});
+}
+
+#[test]
+fn completed_quests_pay_their_reward() {
+	new_test_ext().execute_with(|| {
+		did_today(1, 2, 1, 0);
+		let issuance = Balances::total_issuance();
+
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0));
+		assert_eq!(GivenItems::get(), vec![(1, 10)]);
+		System::assert_last_event(
+			Event::QuestRewardClaimed {
+				who: 1,
+				pet_id: 1,
+				day: 0,
+				quest: 0,
+				reward: Reward::Item(10),
+			}
+			.into(),
+		);
+
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 1));
+		assert_eq!(Balances::free_balance(1), 150);
+		assert_eq!(Balances::total_issuance(), issuance + 50);
+		assert_eq!(Claimed::<Test>::get(1), (0, 0b11));
+	});
+}
+
+#[test]
+fn claims_check_the_quest_and_the_pet() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::UnknownQuest
+		);
+		assert_noop!(
+			QuestsModule::claim_quest_reward(RuntimeOrigin::signed(4), 0),
+			Error::<Test>::AccountHasNoPet
+		);
+
+		did_today(1, 1, 0, 0);
+		assert_noop!(
+			QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0),
+			Error::<Test>::QuestNotComplete
+		);
+
+		did_today(1, 2, 0, 0);
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0));
+		assert_noop!(
+			QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0),
+			Error::<Test>::QuestAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn quests_are_paid_again_when_they_come_back() {
+	new_test_ext().execute_with(|| {
+		did_today(1, 2, 0, 0);
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0));
+
+		Today::set(2);
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0));
+		assert_eq!(Claimed::<Test>::get(1), (2, 0b1));
+		assert_eq!(GivenItems::get(), vec![(1, 10), (1, 10)]);
+	});
+}
+
+#[test]
+fn claims_follow_the_pet_to_its_new_owner() {
+	new_test_ext().execute_with(|| {
+		did_today(1, 2, 0, 0);
+		assert_ok!(QuestsModule::claim_quest_reward(RuntimeOrigin::signed(1), 0));
+
+		// Account 4 got pet 1 later that day
+		PetsToday::set(vec![(4, 1, Progress { feeds: 2, sleeps: 0, battles_won: 0 })]);
+		assert_noop!(
+			QuestsModule::claim_quest_reward(RuntimeOrigin::signed(4), 0),
+			Error::<Test>::QuestAlreadyClaimed
+		);
+	});
+}
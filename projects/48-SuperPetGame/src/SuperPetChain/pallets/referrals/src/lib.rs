@@ -0,0 +1,167 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::RuntimeDebug;
+use scale_info::TypeInfo;
+
+/// Tells which accounts own a pet.
+pub trait PetOwnership<AccountId> {
+	fn has_pet(who: &AccountId) -> bool;
+}
+
+/// Hands out the starter items.
+pub trait GiveItem<AccountId, Item> {
+	fn give_item(who: &AccountId, item: Item, amount: u32);
+}
+
+impl<AccountId, Item> GiveItem<AccountId, Item> for () {
+	fn give_item(_who: &AccountId, _item: Item, _amount: u32) {}
+}
+
+/// The account that brought a newcomer, and whether their bonus was paid.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Referral<AccountId> {
+	pub referrer: AccountId,
+	pub rewarded: bool,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::SaturatedConversion;
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The items given as a bonus.
+		type Item: Parameter + MaxEncodedLen + Copy;
+
+		/// Tells newcomers from players who already have a pet.
+		type Pets: PetOwnership<Self::AccountId>;
+
+		/// Gives the starter items to the players.
+		type Items: GiveItem<Self::AccountId, Self::Item>;
+
+		/// The items both the referrer and the newcomer get after the newcomer's first mint.
+		#[pallet::constant]
+		type StarterItems: Get<Vec<(Self::Item, u32)>>;
+
+		/// The length of an era, `MaxReferralsPerEra` starts over each era.
+		#[pallet::constant]
+		type EraLength: Get<Self::BlockNumber>;
+
+		/// The number of newcomers an account can refer during an era.
+		#[pallet::constant]
+		type MaxReferralsPerEra: Get<u32>;
+	}
+
+	/// The referral of each newcomer. An account is only ever referred once.
+	#[pallet::storage]
+	pub type Referrals<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Referral<T::AccountId>>;
+
+	/// The era each referrer last referred someone, and how many newcomers it referred then.
+	#[pallet::storage]
+	pub type ReferralCount<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (u32, u32), ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A newcomer registered with a referrer.
+		Referred { newcomer: T::AccountId, referrer: T::AccountId },
+		/// The newcomer minted its first pet, both accounts got the starter items.
+		ReferralRewarded { newcomer: T::AccountId, referrer: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		CannotReferSelf,
+		AlreadyReferred,
+		AlreadyHasPet,
+		ReferrerHasNoPet,
+		ReferralLimitReached,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register as a newcomer brought by `referrer`, before minting a first pet.
+		/// Both get the starter items once the pet is minted.
+		///
+		/// - referrer: The account of the player who referred the sender
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn register_with_referral(
+			origin: OriginFor<T>,
+			referrer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let newcomer = ensure_signed(origin)?;
+
+			ensure!(newcomer != referrer, Error::<T>::CannotReferSelf);
+			ensure!(!Referrals::<T>::contains_key(&newcomer), Error::<T>::AlreadyReferred);
+			ensure!(!T::Pets::has_pet(&newcomer), Error::<T>::AlreadyHasPet);
+			ensure!(T::Pets::has_pet(&referrer), Error::<T>::ReferrerHasNoPet);
+
+			let era = Self::current_era();
+			let (count_era, count) = ReferralCount::<T>::get(&referrer);
+			let count = if count_era == era { count } else { 0 };
+			ensure!(count < T::MaxReferralsPerEra::get(), Error::<T>::ReferralLimitReached);
+
+			ReferralCount::<T>::insert(&referrer, (era, count + 1));
+			Referrals::<T>::insert(
+				&newcomer,
+				Referral { referrer: referrer.clone(), rewarded: false },
+			);
+
+			Self::deposit_event(Event::Referred { newcomer, referrer });
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The current era, block number divided by `EraLength`.
+		pub fn current_era() -> u32 {
+			let now = frame_system::Pallet::<T>::block_number().saturated_into::<u32>();
+			now / T::EraLength::get().saturated_into::<u32>().max(1)
+		}
+
+		/// Give the starter items to a newcomer who minted a pet and to its referrer. The bonus
+		/// is only paid once, later mints of the account are ignored.
+		pub fn reward_mint(newcomer: &T::AccountId) {
+			let referral = match Referrals::<T>::get(newcomer) {
+				Some(referral) if !referral.rewarded => referral,
+				_ => return,
+			};
+			for (item, amount) in T::StarterItems::get() {
+				T::Items::give_item(newcomer, item, amount);
+				T::Items::give_item(&referral.referrer, item, amount);
+			}
+			Referrals::<T>::insert(
+				newcomer,
+				Referral { referrer: referral.referrer.clone(), rewarded: true },
+			);
+
+			Self::deposit_event(Event::ReferralRewarded {
+				newcomer: newcomer.clone(),
+				referrer: referral.referrer,
+			});
+		}
+	}
+}
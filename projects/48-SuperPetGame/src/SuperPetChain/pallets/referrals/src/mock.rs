@@ -0,0 +1,94 @@
+use crate::{self as pallet_referrals, GiveItem, PetOwnership};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		ReferralsModule: pallet_referrals,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	/// The accounts owning a pet, the others are newcomers.
+	pub static PetOwners: Vec<u64> = vec![1, 2];
+	pub static GivenItems: Vec<(u64, u32, u32)> = vec![];
+	pub StarterItems: Vec<(u32, u32)> = vec![(10, 3), (20, 2)];
+}
+
+/// Pets owned by the accounts in `PetOwners`, and starter items recorded in `GivenItems`.
+pub struct MockPets;
+
+impl PetOwnership<u64> for MockPets {
+	fn has_pet(who: &u64) -> bool {
+		PetOwners::get().contains(who)
+	}
+}
+
+impl GiveItem<u64, u32> for MockPets {
+	fn give_item(who: &u64, item: u32, amount: u32) {
+		let mut given = GivenItems::get();
+		given.push((*who, item, amount));
+		GivenItems::set(given);
+	}
+}
+
+impl pallet_referrals::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Item = u32;
+	type Pets = MockPets;
+	type Items = MockPets;
+	type StarterItems = StarterItems;
+	type EraLength = ConstU64<10>;
+	type MaxReferralsPerEra = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
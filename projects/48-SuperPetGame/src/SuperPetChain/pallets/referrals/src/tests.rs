@@ -0,0 +1,83 @@
+use crate::{mock::*, *};
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn newcomers_register_with_their_referrer() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 1));
+
+		assert_eq!(Referrals::<Test>::get(3), Some(Referral { referrer: 1, rewarded: false }));
+		assert_eq!(ReferralCount::<Test>::get(1), (0, 1));
+		System::assert_last_event(Event::Referred { newcomer: 3, referrer: 1 }.into());
+	});
+}
+
+#[test]
+fn registration_checks_both_accounts() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 3),
+			Error::<Test>::CannotReferSelf
+		);
+		assert_noop!(
+			ReferralsModule::register_with_referral(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::AlreadyHasPet
+		);
+		assert_noop!(
+			ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 4),
+			Error::<Test>::ReferrerHasNoPet
+		);
+
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 1));
+		assert_noop!(
+			ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 2),
+			Error::<Test>::AlreadyReferred
+		);
+	});
+}
+
+#[test]
+fn referrals_are_limited_per_era() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 1));
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(4), 1));
+		assert_noop!(
+			ReferralsModule::register_with_referral(RuntimeOrigin::signed(5), 1),
+			Error::<Test>::ReferralLimitReached
+		);
+		// Other referrers have their own limit
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(5), 2));
+
+		System::set_block_number(10);
+		assert_eq!(ReferralsModule::current_era(), 1);
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(6), 1));
+		assert_eq!(ReferralCount::<Test>::get(1), (1, 1));
+	});
+}
+
+#[test]
+fn first_mint_gives_both_accounts_the_starter_items_once() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ReferralsModule::register_with_referral(RuntimeOrigin::signed(3), 1));
+		PetOwners::set(vec![1, 2, 3]);
+
+		ReferralsModule::reward_mint(&3);
+		assert_eq!(GivenItems::get(), vec![(3, 10, 3), (1, 10, 3), (3, 20, 2), (1, 20, 2)]);
+		assert_eq!(Referrals::<Test>::get(3), Some(Referral { referrer: 1, rewarded: true }));
+		System::assert_last_event(Event::ReferralRewarded { newcomer: 3, referrer: 1 }.into());
+
+		// Later mints of the account pay nothing
+		ReferralsModule::reward_mint(&3);
+		assert_eq!(GivenItems::get().len(), 4);
+	});
+}
+
+#[test]
+fn mints_without_a_referral_pay_nothing() {
+	new_test_ext().execute_with(|| {
+		ReferralsModule::reward_mint(&4);
+
+		assert!(GivenItems::get().is_empty());
+		assert!(System::events().is_empty());
+	});
+}
@@ -0,0 +1,340 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{dispatch::DispatchResult, RuntimeDebug};
+use scale_info::TypeInfo;
+
+type PetId = u32;
+
+/// Finds pets and their owners, and cares for and battles with the pets of owners on behalf
+/// of their renters.
+pub trait PetRights<AccountId> {
+	fn pet_of(owner: &AccountId) -> Option<PetId>;
+	fn owner_of(pet_id: PetId) -> Option<AccountId>;
+	fn feed(owner: &AccountId) -> DispatchResult;
+	fn sleep(owner: &AccountId) -> DispatchResult;
+	/// Battle the pet of `opponent` with the pet of `owner`.
+	fn challenge(owner: &AccountId, opponent: &AccountId) -> DispatchResult;
+}
+
+/// The price and length of a rental, as offered by the owner.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct RentalTerms<AccountId, Balance, BlockNumber> {
+	pub owner: AccountId,
+	pub price: Balance,
+	pub duration: BlockNumber,
+}
+
+/// A running rental. The price is reserved from the renter until the rental ends, then it
+/// goes to the owner.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Rental<AccountId, Balance, BlockNumber> {
+	pub owner: AccountId,
+	pub renter: AccountId,
+	pub price: Balance,
+	pub ends_at: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, ReservableCurrency},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Saturating, Zero};
+
+	type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	type TermsOf<T> =
+		RentalTerms<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>;
+	type RentalOf<T> =
+		Rental<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency rentals are paid in, held from the renter until the rental ends.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Lends the rented pets to their renters.
+		type Pets: PetRights<Self::AccountId>;
+
+		/// The longest rental an owner can offer.
+		#[pallet::constant]
+		type MaxRentalDuration: Get<Self::BlockNumber>;
+
+		/// The maximum number of rentals ending at the same block.
+		#[pallet::constant]
+		type MaxRentalsPerBlock: Get<u32>;
+	}
+
+	/// Pets offered for rent and their terms.
+	#[pallet::storage]
+	pub type RentalListings<T: Config> = StorageMap<_, Blake2_128Concat, PetId, TermsOf<T>>;
+
+	/// Pets rented right now.
+	#[pallet::storage]
+	pub type Rentals<T: Config> = StorageMap<_, Blake2_128Concat, PetId, RentalOf<T>>;
+
+	/// The rentals ending at each block, the pets go back to their owners when it starts.
+	#[pallet::storage]
+	pub type RentalExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<PetId, T::MaxRentalsPerBlock>,
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The owner offered the pet for rent.
+		RentalListed {
+			owner: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			duration: T::BlockNumber,
+		},
+		/// The owner stopped offering the pet for rent.
+		RentalListingCancelled { owner: T::AccountId, pet_id: PetId },
+		/// The pet is rented until `ends_at`, its price is held from the renter.
+		PetRented {
+			owner: T::AccountId,
+			renter: T::AccountId,
+			pet_id: PetId,
+			price: BalanceOf<T>,
+			ends_at: T::BlockNumber,
+		},
+		/// The pet went back to its owner, who received the price of the rental.
+		RentalEnded { owner: T::AccountId, renter: T::AccountId, pet_id: PetId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		AccountHasNoPet,
+		PetNotForRent,
+		PetAlreadyRented,
+		CannotRentOwnPet,
+		InvalidDuration,
+		TooManyRentals,
+		NotRenter,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Return the pets whose rental ends at this block.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let ending = RentalExpiries::<T>::take(now);
+			let count = ending.len() as u64;
+			for pet_id in ending {
+				// Rentals returned early are gone, or may have been rented again since
+				if let Some(rental) = Rentals::<T>::get(pet_id).filter(|r| r.ends_at == now) {
+					Self::end_rental(pet_id, rental);
+				}
+			}
+			T::DbWeight::get().reads_writes(1 + count, 1 + 4 * count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Offer your pet for rent. The renter can feed it, put it to sleep and battle with it
+		/// through the calls of this pallet until the rental ends, but not transfer or sell it.
+		///
+		/// - price: The price of the whole rental
+		/// - duration: How many blocks the rental lasts
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn list_for_rent(
+			origin: OriginFor<T>,
+			price: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let pet_id = T::Pets::pet_of(&owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			ensure!(
+				!duration.is_zero() && duration <= T::MaxRentalDuration::get(),
+				Error::<T>::InvalidDuration
+			);
+			ensure!(!Rentals::<T>::contains_key(pet_id), Error::<T>::PetAlreadyRented);
+
+			RentalListings::<T>::insert(
+				pet_id,
+				RentalTerms { owner: owner.clone(), price, duration },
+			);
+
+			Self::deposit_event(Event::RentalListed { owner, pet_id, price, duration });
+
+			Ok(().into())
+		}
+
+		/// Stop offering your pet for rent, a running rental goes on.
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn cancel_rental_listing(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let pet_id = T::Pets::pet_of(&owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			ensure!(RentalListings::<T>::contains_key(pet_id), Error::<T>::PetNotForRent);
+
+			RentalListings::<T>::remove(pet_id);
+
+			Self::deposit_event(Event::RentalListingCancelled { owner, pet_id });
+
+			Ok(().into())
+		}
+
+		/// Rent a pet offered for rent. Its price is held from the sender and paid to the
+		/// owner when the rental ends. The sender can own pets and rent others at the same
+		/// time.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(2)]
+		#[pallet::weight(0)]
+		pub fn rent(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let renter = ensure_signed(origin)?;
+			let terms = RentalListings::<T>::get(pet_id).ok_or(Error::<T>::PetNotForRent)?;
+			// The listing is left behind when the pet changes hands
+			ensure!(
+				T::Pets::owner_of(pet_id).as_ref() == Some(&terms.owner),
+				Error::<T>::PetNotForRent
+			);
+			ensure!(renter != terms.owner, Error::<T>::CannotRentOwnPet);
+			ensure!(!Rentals::<T>::contains_key(pet_id), Error::<T>::PetAlreadyRented);
+
+			let ends_at = frame_system::Pallet::<T>::block_number().saturating_add(terms.duration);
+			RentalExpiries::<T>::try_mutate(ends_at, |ending| ending.try_push(pet_id))
+				.map_err(|_| Error::<T>::TooManyRentals)?;
+			T::Currency::reserve(&renter, terms.price)?;
+
+			RentalListings::<T>::remove(pet_id);
+			Rentals::<T>::insert(
+				pet_id,
+				Rental {
+					owner: terms.owner.clone(),
+					renter: renter.clone(),
+					price: terms.price,
+					ends_at,
+				},
+			);
+
+			Self::deposit_event(Event::PetRented {
+				owner: terms.owner,
+				renter,
+				pet_id,
+				price: terms.price,
+				ends_at,
+			});
+
+			Ok(().into())
+		}
+
+		/// Give a rented pet back before the rental ends. The owner still receives the whole
+		/// price.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(3)]
+		#[pallet::weight(0)]
+		pub fn return_pet(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let rental = Self::ensure_renter(origin, pet_id)?;
+
+			Self::end_rental(pet_id, rental);
+
+			Ok(().into())
+		}
+
+		/// Feed a pet you rent.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(4)]
+		#[pallet::weight(0)]
+		pub fn feed_rented(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let rental = Self::ensure_renter(origin, pet_id)?;
+			T::Pets::feed(&rental.owner)?;
+
+			Ok(().into())
+		}
+
+		/// Put a pet you rent to sleep.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(5)]
+		#[pallet::weight(0)]
+		pub fn sleep_rented(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let rental = Self::ensure_renter(origin, pet_id)?;
+			T::Pets::sleep(&rental.owner)?;
+
+			Ok(().into())
+		}
+
+		/// Challenge the pet of another account with a pet you rent. The experience goes to
+		/// the rented pet.
+		///
+		/// - pet_id: The id of the rented pet
+		/// - opponent: The owner of the challenged pet
+		#[pallet::call_index(6)]
+		#[pallet::weight(0)]
+		pub fn challenge_with_rented(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+			opponent: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let rental = Self::ensure_renter(origin, pet_id)?;
+			T::Pets::challenge(&rental.owner, &opponent)?;
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether the pet is rented right now, rented pets can't change hands.
+		pub fn is_rented(pet_id: PetId) -> bool {
+			Rentals::<T>::contains_key(pet_id)
+		}
+
+		/// Check that the signer rents the pet right now.
+		fn ensure_renter(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+		) -> Result<RentalOf<T>, DispatchError> {
+			let renter = ensure_signed(origin)?;
+			let rental = Rentals::<T>::get(pet_id)
+				.filter(|rental| rental.renter == renter)
+				.ok_or(Error::<T>::NotRenter)?;
+			Ok(rental)
+		}
+
+		/// Take the pet back from the renter and pay the owner.
+		fn end_rental(pet_id: PetId, rental: RentalOf<T>) {
+			Rentals::<T>::remove(pet_id);
+			// The whole price was reserved when renting, nothing can be left unpaid
+			let _ = T::Currency::repatriate_reserved(
+				&rental.renter,
+				&rental.owner,
+				rental.price,
+				BalanceStatus::Free,
+			);
+
+			Self::deposit_event(Event::RentalEnded {
+				owner: rental.owner,
+				renter: rental.renter,
+				pet_id,
+			});
+		}
+	}
+}
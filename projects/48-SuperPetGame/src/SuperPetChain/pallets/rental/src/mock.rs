@@ -0,0 +1,147 @@
+use crate::{self as pallet_rental, PetRights};
+use frame_support::{
+	dispatch::DispatchResult,
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		RentalModule: pallet_rental,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// What was done with a pet through `PetRights`, by the owner of the pet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Played {
+	Fed(u64),
+	Slept(u64),
+	Challenged(u64, u64),
+}
+
+parameter_types! {
+	/// The owner of each pet. Accounts 1 to 3 own pets 1 to 3, account 4 owns none.
+	pub static PetOwners: Vec<(u64, u32)> = vec![(1, 1), (2, 2), (3, 3)];
+	pub static PlayedWith: Vec<Played> = vec![];
+}
+
+/// Pets owned as listed in `PetOwners`, recording what renters do with them.
+pub struct MockPets;
+
+impl MockPets {
+	fn play(owner: &u64, played: Played) -> DispatchResult {
+		Self::ensure_has_pet(owner)?;
+		let mut all = PlayedWith::get();
+		all.push(played);
+		PlayedWith::set(all);
+		Ok(())
+	}
+
+	fn ensure_has_pet(who: &u64) -> DispatchResult {
+		Self::pet_of(who).map(|_| ()).ok_or(DispatchError::Other("no pet"))
+	}
+}
+
+impl PetRights<u64> for MockPets {
+	fn pet_of(owner: &u64) -> Option<u32> {
+		PetOwners::get()
+			.into_iter()
+			.find(|(account, _)| account == owner)
+			.map(|(_, id)| id)
+	}
+
+	fn owner_of(pet_id: u32) -> Option<u64> {
+		PetOwners::get()
+			.into_iter()
+			.find(|(_, id)| *id == pet_id)
+			.map(|(account, _)| account)
+	}
+
+	fn feed(owner: &u64) -> DispatchResult {
+		Self::play(owner, Played::Fed(*owner))
+	}
+
+	fn sleep(owner: &u64) -> DispatchResult {
+		Self::play(owner, Played::Slept(*owner))
+	}
+
+	fn challenge(owner: &u64, opponent: &u64) -> DispatchResult {
+		Self::ensure_has_pet(opponent)?;
+		Self::play(owner, Played::Challenged(*owner, *opponent))
+	}
+}
+
+impl pallet_rental::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = MockPets;
+	type MaxRentalDuration = ConstU64<100>;
+	type MaxRentalsPerBlock = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+// Every account has a balance of 100.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=5).map(|who| (who, 100)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
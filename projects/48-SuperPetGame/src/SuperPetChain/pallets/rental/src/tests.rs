@@ -0,0 +1,233 @@
+use crate::{mock::*, *};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, Hooks, ReservableCurrency},
+};
+
+/// Account 1 offers its pet for 10 over 20 blocks, and account 2, which owns a pet itself,
+/// rents it at block 1.
+fn rented_pet() {
+	assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20));
+	assert_ok!(RentalModule::rent(RuntimeOrigin::signed(2), 1));
+}
+
+#[test]
+fn list_for_rent_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20));
+
+		assert_eq!(
+			RentalListings::<Test>::get(1),
+			Some(RentalTerms { owner: 1, price: 10, duration: 20 })
+		);
+		System::assert_last_event(
+			Event::RentalListed { owner: 1, pet_id: 1, price: 10, duration: 20 }.into(),
+		);
+	});
+}
+
+#[test]
+fn list_for_rent_checks_the_pet_and_duration() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RentalModule::list_for_rent(RuntimeOrigin::signed(4), 10, 20),
+			Error::<Test>::AccountHasNoPet
+		);
+		assert_noop!(
+			RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 0),
+			Error::<Test>::InvalidDuration
+		);
+		assert_noop!(
+			RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 101),
+			Error::<Test>::InvalidDuration
+		);
+
+		rented_pet();
+		assert_noop!(
+			RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20),
+			Error::<Test>::PetAlreadyRented
+		);
+	});
+}
+
+#[test]
+fn cancel_rental_listing_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RentalModule::cancel_rental_listing(RuntimeOrigin::signed(1)),
+			Error::<Test>::PetNotForRent
+		);
+
+		assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20));
+		assert_ok!(RentalModule::cancel_rental_listing(RuntimeOrigin::signed(1)));
+
+		assert!(!RentalListings::<Test>::contains_key(1));
+		System::assert_last_event(Event::RentalListingCancelled { owner: 1, pet_id: 1 }.into());
+		assert_noop!(RentalModule::rent(RuntimeOrigin::signed(2), 1), Error::<Test>::PetNotForRent);
+	});
+}
+
+#[test]
+fn owners_of_pets_can_rent_others() {
+	new_test_ext().execute_with(|| {
+		rented_pet();
+
+		assert_eq!(
+			Rentals::<Test>::get(1),
+			Some(Rental { owner: 1, renter: 2, price: 10, ends_at: 21 })
+		);
+		assert!(RentalModule::is_rented(1));
+		assert!(!RentalListings::<Test>::contains_key(1));
+		assert_eq!(RentalExpiries::<Test>::get(21).into_inner(), vec![1]);
+		assert_eq!(Balances::reserved_balance(2), 10);
+		System::assert_last_event(
+			Event::PetRented { owner: 1, renter: 2, pet_id: 1, price: 10, ends_at: 21 }.into(),
+		);
+	});
+}
+
+#[test]
+fn rent_checks_the_listing() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(RentalModule::rent(RuntimeOrigin::signed(2), 1), Error::<Test>::PetNotForRent);
+
+		assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20));
+		assert_noop!(
+			RentalModule::rent(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::CannotRentOwnPet
+		);
+
+		// The listing is left behind when the pet changes hands
+		PetOwners::set(vec![(4, 1), (2, 2), (3, 3)]);
+		assert_noop!(RentalModule::rent(RuntimeOrigin::signed(2), 1), Error::<Test>::PetNotForRent);
+	});
+}
+
+#[test]
+fn rent_holds_the_price() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 101, 20));
+
+		assert_noop!(
+			RentalModule::rent(RuntimeOrigin::signed(2), 1),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert!(!RentalExpiries::<Test>::contains_key(21));
+	});
+}
+
+#[test]
+fn rentals_ending_at_a_block_are_bounded() {
+	new_test_ext().execute_with(|| {
+		for owner in 1..=3 {
+			assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(owner), 10, 20));
+		}
+		assert_ok!(RentalModule::rent(RuntimeOrigin::signed(4), 1));
+		assert_ok!(RentalModule::rent(RuntimeOrigin::signed(5), 2));
+
+		assert_noop!(
+			RentalModule::rent(RuntimeOrigin::signed(1), 3),
+			Error::<Test>::TooManyRentals
+		);
+	});
+}
+
+#[test]
+fn renters_care_for_and_battle_with_the_rented_pet() {
+	new_test_ext().execute_with(|| {
+		rented_pet();
+
+		assert_ok!(RentalModule::feed_rented(RuntimeOrigin::signed(2), 1));
+		assert_ok!(RentalModule::sleep_rented(RuntimeOrigin::signed(2), 1));
+		assert_ok!(RentalModule::challenge_with_rented(RuntimeOrigin::signed(2), 1, 3));
+
+		assert_eq!(
+			PlayedWith::get(),
+			vec![Played::Fed(1), Played::Slept(1), Played::Challenged(1, 3)]
+		);
+	});
+}
+
+#[test]
+fn only_the_renter_plays_with_the_rented_pet() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RentalModule::feed_rented(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::NotRenter
+		);
+
+		rented_pet();
+		assert_noop!(
+			RentalModule::feed_rented(RuntimeOrigin::signed(3), 1),
+			Error::<Test>::NotRenter
+		);
+		assert_noop!(
+			RentalModule::sleep_rented(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NotRenter
+		);
+		assert_noop!(
+			RentalModule::challenge_with_rented(RuntimeOrigin::signed(3), 1, 2),
+			Error::<Test>::NotRenter
+		);
+		assert_noop!(
+			RentalModule::return_pet(RuntimeOrigin::signed(3), 1),
+			Error::<Test>::NotRenter
+		);
+		assert!(PlayedWith::get().is_empty());
+	});
+}
+
+#[test]
+fn rentals_end_at_their_block_and_pay_the_owner() {
+	new_test_ext().execute_with(|| {
+		rented_pet();
+
+		RentalModule::on_initialize(20);
+		assert!(RentalModule::is_rented(1));
+
+		RentalModule::on_initialize(21);
+		assert!(!RentalModule::is_rented(1));
+		assert!(!RentalExpiries::<Test>::contains_key(21));
+		assert_eq!(Balances::free_balance(1), 110);
+		assert_eq!(Balances::total_balance(&2), 90);
+		System::assert_last_event(Event::RentalEnded { owner: 1, renter: 2, pet_id: 1 }.into());
+		assert_noop!(
+			RentalModule::feed_rented(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::NotRenter
+		);
+	});
+}
+
+#[test]
+fn return_pet_pays_the_whole_price() {
+	new_test_ext().execute_with(|| {
+		rented_pet();
+
+		assert_ok!(RentalModule::return_pet(RuntimeOrigin::signed(2), 1));
+
+		assert!(!RentalModule::is_rented(1));
+		assert_eq!(Balances::free_balance(1), 110);
+		assert_eq!(Balances::total_balance(&2), 90);
+		System::assert_last_event(Event::RentalEnded { owner: 1, renter: 2, pet_id: 1 }.into());
+	});
+}
+
+#[test]
+fn expiry_of_a_returned_rental_leaves_the_next_one() {
+	new_test_ext().execute_with(|| {
+		rented_pet();
+		assert_ok!(RentalModule::return_pet(RuntimeOrigin::signed(2), 1));
+
+		System::set_block_number(11);
+		assert_ok!(RentalModule::list_for_rent(RuntimeOrigin::signed(1), 10, 20));
+		assert_ok!(RentalModule::rent(RuntimeOrigin::signed(3), 1));
+
+		RentalModule::on_initialize(21);
+		assert_eq!(Rentals::<Test>::get(1).map(|rental| rental.renter), Some(3));
+		assert_eq!(Balances::reserved_balance(3), 10);
+
+		RentalModule::on_initialize(31);
+		assert!(!RentalModule::is_rented(1));
+		assert_eq!(Balances::free_balance(1), 120);
+	});
+}
@@ -0,0 +1,268 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::RuntimeDebug;
+use scale_info::TypeInfo;
+
+type PetId = u32;
+
+/// Finds the pets to stake and their level, and hands out reward items.
+pub trait StakedPets<AccountId, Item> {
+	fn owner_of(pet_id: PetId) -> Option<AccountId>;
+	/// Whether the pet can be staked now, e.g. it's hatched and not rented.
+	fn can_stake(pet_id: PetId) -> bool;
+	fn level(pet_id: PetId) -> u32;
+	fn give_item(who: &AccountId, item: Item, amount: u32);
+}
+
+/// A staked pet. Rewards are paid for each whole `RewardPeriod` since `paid_until`, until the
+/// owner unstakes it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Stake<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	pub paid_until: BlockNumber,
+	/// When the pet is free again, once the owner unstaked it.
+	pub unlocks_at: Option<BlockNumber>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{pallet_prelude::*, traits::Currency};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{SaturatedConversion, Saturating, Zero};
+
+	type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	type StakeOf<T> = Stake<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency token rewards are minted in.
+		type Currency: Currency<Self::AccountId>;
+
+		/// The items that can be given as rewards.
+		type Item: Parameter + MaxEncodedLen + Copy;
+
+		/// Finds the staked pets and gives the reward items.
+		type Pets: StakedPets<Self::AccountId, Self::Item>;
+
+		/// How often rewards are earned.
+		#[pallet::constant]
+		type RewardPeriod: Get<Self::BlockNumber>;
+
+		/// The tokens earned each period for each level of the pet.
+		#[pallet::constant]
+		type RewardPerLevel: Get<BalanceOf<Self>>;
+
+		/// The item earned each period, once for every `LevelsPerItem` levels of the pet.
+		#[pallet::constant]
+		type RewardItem: Get<Self::Item>;
+
+		/// The levels a pet needs for each `RewardItem` it earns.
+		#[pallet::constant]
+		type LevelsPerItem: Get<u32>;
+
+		/// How long an unstaked pet stays locked before it's free again.
+		#[pallet::constant]
+		type UnstakeCooldown: Get<Self::BlockNumber>;
+
+		/// The maximum number of pets unlocking at the same block.
+		#[pallet::constant]
+		type MaxUnlocksPerBlock: Get<u32>;
+	}
+
+	/// The staked pets, and the pets unstaked but still cooling down.
+	#[pallet::storage]
+	pub type Stakes<T: Config> = StorageMap<_, Blake2_128Concat, PetId, StakeOf<T>>;
+
+	/// The pets unlocking at each block, their stake is removed when it starts.
+	#[pallet::storage]
+	pub type StakeUnlocks<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<PetId, T::MaxUnlocksPerBlock>,
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The owner staked the pet, it can't change hands or battle until it's unstaked.
+		PetStaked { owner: T::AccountId, pet_id: PetId },
+		/// The rewards of `periods` whole periods are paid.
+		StakingRewardsClaimed {
+			owner: T::AccountId,
+			pet_id: PetId,
+			periods: u32,
+			tokens: BalanceOf<T>,
+			item: T::Item,
+			items: u32,
+		},
+		/// The owner unstaked the pet, it earns nothing more and is free again at `unlocks_at`.
+		PetUnstaked { owner: T::AccountId, pet_id: PetId, unlocks_at: T::BlockNumber },
+		/// The cooldown of an unstaked pet is over.
+		PetUnlocked { owner: T::AccountId, pet_id: PetId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		NotOwner,
+		PetAlreadyStaked,
+		PetCannotBeStaked,
+		NotStaked,
+		AlreadyUnstaked,
+		NoRewardsYet,
+		TooManyUnlocks,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Free the pets whose cooldown ends at this block.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let unlocking = StakeUnlocks::<T>::take(now);
+			let count = unlocking.len() as u64;
+			for pet_id in unlocking {
+				if let Some(stake) = Stakes::<T>::take(pet_id) {
+					Self::deposit_event(Event::PetUnlocked { owner: stake.owner, pet_id });
+				}
+			}
+			T::DbWeight::get().reads_writes(1 + count, 1 + count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Stake your pet. It earns `RewardPerLevel` tokens for each of its levels every
+		/// `RewardPeriod`, and a `RewardItem` for every `LevelsPerItem` levels. Until it's
+		/// unstaked it can't be transferred, sold or abandoned, and it doesn't battle.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn stake_pet(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			ensure!(T::Pets::owner_of(pet_id).as_ref() == Some(&owner), Error::<T>::NotOwner);
+			ensure!(!Stakes::<T>::contains_key(pet_id), Error::<T>::PetAlreadyStaked);
+			ensure!(T::Pets::can_stake(pet_id), Error::<T>::PetCannotBeStaked);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			Stakes::<T>::insert(
+				pet_id,
+				Stake { owner: owner.clone(), paid_until: now, unlocks_at: None },
+			);
+
+			Self::deposit_event(Event::PetStaked { owner, pet_id });
+
+			Ok(().into())
+		}
+
+		/// Claim the rewards your staked pet earned since they were last claimed, at its
+		/// current level.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn claim_staking_rewards(
+			origin: OriginFor<T>,
+			pet_id: PetId,
+		) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let mut stake = Self::stake_of(&owner, pet_id)?;
+
+			ensure!(Self::pay_rewards(pet_id, &mut stake) > 0, Error::<T>::NoRewardsYet);
+			Stakes::<T>::insert(pet_id, stake);
+
+			Ok(().into())
+		}
+
+		/// Unstake your pet, the rewards it earned are paid. It stays locked for
+		/// `UnstakeCooldown` blocks, without earning anything.
+		///
+		/// - pet_id: The id of the pet
+		#[pallet::call_index(2)]
+		#[pallet::weight(0)]
+		pub fn unstake_pet(origin: OriginFor<T>, pet_id: PetId) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let mut stake = Self::stake_of(&owner, pet_id)?;
+
+			let unlocks_at = frame_system::Pallet::<T>::block_number()
+				.saturating_add(T::UnstakeCooldown::get().max(1u32.into()));
+			StakeUnlocks::<T>::try_mutate(unlocks_at, |unlocking| unlocking.try_push(pet_id))
+				.map_err(|_| Error::<T>::TooManyUnlocks)?;
+			Self::pay_rewards(pet_id, &mut stake);
+			stake.unlocks_at = Some(unlocks_at);
+			Stakes::<T>::insert(pet_id, stake);
+
+			Self::deposit_event(Event::PetUnstaked { owner, pet_id, unlocks_at });
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether the pet is staked or cooling down, it can't change hands or battle then.
+		pub fn is_staked(pet_id: PetId) -> bool {
+			Stakes::<T>::contains_key(pet_id)
+		}
+
+		/// The stake of `owner` on the pet, as long as it earns rewards.
+		fn stake_of(owner: &T::AccountId, pet_id: PetId) -> Result<StakeOf<T>, DispatchError> {
+			let stake = Stakes::<T>::get(pet_id)
+				.filter(|stake| &stake.owner == owner)
+				.ok_or(Error::<T>::NotStaked)?;
+			ensure!(stake.unlocks_at.is_none(), Error::<T>::AlreadyUnstaked);
+			Ok(stake)
+		}
+
+		/// Pay the rewards of the whole periods since the last payment, and return how many
+		/// were paid. The rest of the current period is paid with the next one.
+		fn pay_rewards(pet_id: PetId, stake: &mut StakeOf<T>) -> u32 {
+			let period = T::RewardPeriod::get().max(1u32.into());
+			let staked = frame_system::Pallet::<T>::block_number().saturating_sub(stake.paid_until);
+			let periods: u32 = (staked / period).saturated_into();
+			if periods.is_zero() {
+				return 0;
+			}
+			stake.paid_until =
+				stake.paid_until.saturating_add(period.saturating_mul(periods.into()));
+
+			let level = T::Pets::level(pet_id);
+			let tokens =
+				T::RewardPerLevel::get().saturating_mul(level.saturating_mul(periods).into());
+			let item = T::RewardItem::get();
+			let items = (level / T::LevelsPerItem::get().max(1)).saturating_mul(periods);
+			if !tokens.is_zero() {
+				let _ = T::Currency::deposit_creating(&stake.owner, tokens);
+			}
+			if items > 0 {
+				T::Pets::give_item(&stake.owner, item, items);
+			}
+
+			Self::deposit_event(Event::StakingRewardsClaimed {
+				owner: stake.owner.clone(),
+				pet_id,
+				periods,
+				tokens,
+				item,
+				items,
+			});
+			periods
+		}
+	}
+}
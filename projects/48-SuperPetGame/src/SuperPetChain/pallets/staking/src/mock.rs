@@ -0,0 +1,130 @@
+use crate::{self as pallet_pet_staking, PetId, StakedPets};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		StakingModule: pallet_pet_staking,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// The item staked pets earn.
+pub const REWARD_ITEM: u32 = 42;
+
+parameter_types! {
+	/// The owner and level of each pet. Accounts 1 to 3 own pets 1 to 3, account 4 owns none.
+	pub static Pets: Vec<(u64, PetId, u32)> = vec![(1, 1, 7), (2, 2, 3), (3, 3, 1)];
+	/// The pets busy elsewhere, e.g. rented out.
+	pub static Busy: Vec<PetId> = vec![3];
+	pub static GivenItems: Vec<(u64, u32, u32)> = vec![];
+}
+
+/// Pets owned as listed in `Pets`, and reward items recorded in `GivenItems`.
+pub struct MockPets;
+
+impl MockPets {
+	fn pet(pet_id: PetId) -> Option<(u64, PetId, u32)> {
+		Pets::get().into_iter().find(|(_, id, _)| *id == pet_id)
+	}
+}
+
+impl StakedPets<u64, u32> for MockPets {
+	fn owner_of(pet_id: PetId) -> Option<u64> {
+		Self::pet(pet_id).map(|(owner, _, _)| owner)
+	}
+
+	fn can_stake(pet_id: PetId) -> bool {
+		!Busy::get().contains(&pet_id)
+	}
+
+	fn level(pet_id: PetId) -> u32 {
+		Self::pet(pet_id).map(|(_, _, level)| level).unwrap_or_default()
+	}
+
+	fn give_item(who: &u64, item: u32, amount: u32) {
+		let mut given = GivenItems::get();
+		given.push((*who, item, amount));
+		GivenItems::set(given);
+	}
+}
+
+impl pallet_pet_staking::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Item = u32;
+	type Pets = MockPets;
+	type RewardPeriod = ConstU64<10>;
+	type RewardPerLevel = ConstU64<2>;
+	type RewardItem = ConstU32<REWARD_ITEM>;
+	type LevelsPerItem = ConstU32<5>;
+	type UnstakeCooldown = ConstU64<20>;
+	type MaxUnlocksPerBlock = ConstU32<1>;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+// Every account has a balance of 100.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=5).map(|who| (who, 100)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
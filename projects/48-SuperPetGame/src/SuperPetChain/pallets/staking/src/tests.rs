@@ -0,0 +1,169 @@
+use crate::{mock::*, *};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, Hooks},
+};
+
+#[test]
+fn stake_pet_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+
+		assert_eq!(
+			Stakes::<Test>::get(1),
+			Some(Stake { owner: 1, paid_until: 1, unlocks_at: None })
+		);
+		assert!(StakingModule::is_staked(1));
+		System::assert_last_event(Event::PetStaked { owner: 1, pet_id: 1 }.into());
+	});
+}
+
+#[test]
+fn stake_pet_checks_the_pet() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			StakingModule::stake_pet(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			StakingModule::stake_pet(RuntimeOrigin::signed(4), 4),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			StakingModule::stake_pet(RuntimeOrigin::signed(3), 3),
+			Error::<Test>::PetCannotBeStaked
+		);
+
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+		assert_noop!(
+			StakingModule::stake_pet(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::PetAlreadyStaked
+		);
+	});
+}
+
+#[test]
+fn rewards_are_paid_for_whole_periods_at_the_pet_level() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+		System::set_block_number(10);
+		assert_noop!(
+			StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NoRewardsYet
+		);
+
+		// Two periods for a level 7 pet, one item for its first 5 levels each period
+		System::set_block_number(25);
+		assert_ok!(StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1));
+		assert_eq!(Balances::free_balance(1), 100 + 2 * 7 * 2);
+		assert_eq!(GivenItems::get(), vec![(1, REWARD_ITEM, 2)]);
+		System::assert_last_event(
+			Event::StakingRewardsClaimed {
+				owner: 1,
+				pet_id: 1,
+				periods: 2,
+				tokens: 28,
+				item: REWARD_ITEM,
+				items: 2,
+			}
+			.into(),
+		);
+
+		// The rest of the period is paid with the next one
+		assert_eq!(Stakes::<Test>::get(1).map(|stake| stake.paid_until), Some(21));
+		System::set_block_number(30);
+		assert_noop!(
+			StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NoRewardsYet
+		);
+		System::set_block_number(31);
+		assert_ok!(StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1));
+		assert_eq!(Balances::free_balance(1), 128 + 14);
+	});
+}
+
+#[test]
+fn pets_below_an_item_earn_tokens_only() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(2), 2));
+		System::set_block_number(11);
+
+		assert_ok!(StakingModule::claim_staking_rewards(RuntimeOrigin::signed(2), 2));
+		assert_eq!(Balances::free_balance(2), 106);
+		assert!(GivenItems::get().is_empty());
+	});
+}
+
+#[test]
+fn only_the_owner_claims_while_the_pet_is_staked() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NotStaked
+		);
+
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+		System::set_block_number(11);
+		assert_noop!(
+			StakingModule::claim_staking_rewards(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::NotStaked
+		);
+		assert_noop!(
+			StakingModule::unstake_pet(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::NotStaked
+		);
+	});
+}
+
+#[test]
+fn unstaked_pets_are_paid_and_unlock_after_the_cooldown() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+		System::set_block_number(15);
+
+		assert_ok!(StakingModule::unstake_pet(RuntimeOrigin::signed(1), 1));
+		assert_eq!(Balances::free_balance(1), 114);
+		System::assert_last_event(
+			Event::PetUnstaked { owner: 1, pet_id: 1, unlocks_at: 35 }.into(),
+		);
+		assert_eq!(StakeUnlocks::<Test>::get(35).into_inner(), vec![1]);
+
+		// Still locked, but earning nothing
+		assert!(StakingModule::is_staked(1));
+		System::set_block_number(34);
+		assert_noop!(
+			StakingModule::claim_staking_rewards(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::AlreadyUnstaked
+		);
+		assert_noop!(
+			StakingModule::unstake_pet(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::AlreadyUnstaked
+		);
+		assert_noop!(
+			StakingModule::stake_pet(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::PetAlreadyStaked
+		);
+		StakingModule::on_initialize(34);
+		assert!(StakingModule::is_staked(1));
+
+		StakingModule::on_initialize(35);
+		assert!(!StakingModule::is_staked(1));
+		assert!(!StakeUnlocks::<Test>::contains_key(35));
+		System::assert_last_event(Event::PetUnlocked { owner: 1, pet_id: 1 }.into());
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+	});
+}
+
+#[test]
+fn pets_unlocking_at_a_block_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(1), 1));
+		assert_ok!(StakingModule::stake_pet(RuntimeOrigin::signed(2), 2));
+
+		assert_ok!(StakingModule::unstake_pet(RuntimeOrigin::signed(1), 1));
+		assert_noop!(
+			StakingModule::unstake_pet(RuntimeOrigin::signed(2), 2),
+			Error::<Test>::TooManyUnlocks
+		);
+	});
+}
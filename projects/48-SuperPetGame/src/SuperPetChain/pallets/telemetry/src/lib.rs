@@ -0,0 +1,248 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	dispatch::{DispatchInfo, DispatchResult, GetCallMetadata, PostDispatchInfo},
+	pallet_prelude::{ConstU32, Weight},
+	BoundedVec, RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::TransactionValidityError,
+};
+use sp_std::marker::PhantomData;
+
+/// The name of a pallet or of one of its calls, as in the metadata.
+pub type CallName = BoundedVec<u8, ConstU32<48>>;
+
+/// Computes the fee charged for a dispatched call, before the tip.
+pub trait ActualFee<Balance> {
+	fn actual_fee(len: u32, info: &DispatchInfo, post_info: &PostDispatchInfo) -> Balance;
+}
+
+/// The calls of one kind dispatched during an era.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct CallUsage<Balance> {
+	pub count: u32,
+	/// The calls that returned an error, they are also in `count`.
+	pub failed: u32,
+	/// The sum of their actual weights, after refunds.
+	pub weight: Weight,
+	pub fees: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{AtLeast32BitUnsigned, SaturatedConversion, Saturating, Zero};
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The balance fees are paid in.
+		type Balance: Parameter + MaxEncodedLen + AtLeast32BitUnsigned + Default + Copy;
+
+		/// Computes the fee of each call, e.g. with the transaction payment pallet.
+		type Fees: ActualFee<Self::Balance>;
+
+		/// The origin allowed to switch telemetry on and off.
+		type TelemetryOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// How long an era lasts. The usage of each era is emitted when the next one starts.
+		#[pallet::constant]
+		type EraLength: Get<Self::BlockNumber>;
+
+		/// The maximum number of kinds of calls recorded in an era.
+		#[pallet::constant]
+		type MaxTrackedCalls: Get<u32>;
+	}
+
+	/// Whether the calls are recorded. Off by default, recording costs two storage accesses
+	/// per transaction which aren't part of its weight.
+	#[pallet::storage]
+	pub type Enabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The usage of each kind of call during the current era, by pallet and call names.
+	#[pallet::storage]
+	pub type Usage<T: Config> = CountedStorageMap<
+		_,
+		Blake2_128Concat,
+		(CallName, CallName),
+		CallUsage<T::Balance>,
+		ValueQuery,
+	>;
+
+	/// The calls of the current era not recorded, because `MaxTrackedCalls` kinds already were.
+	#[pallet::storage]
+	pub type Untracked<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Telemetry is switched on or off.
+		TelemetrySet { enabled: bool },
+		/// The calls of `function` in `pallet` dispatched during the era.
+		CallUsageRecorded {
+			era: u32,
+			pallet: CallName,
+			function: CallName,
+			usage: CallUsage<T::Balance>,
+		},
+		/// The era is over and the usage of its `calls` kinds of calls emitted. `untracked`
+		/// calls weren't recorded.
+		EraUsageClosed { era: u32, calls: u32, untracked: u32 },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Emit the usage of the era that just ended, and start the next one afresh.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let length = T::EraLength::get().max(1u32.into());
+			if !(now % length).is_zero() {
+				return Weight::zero();
+			}
+			let era = (now / length).saturated_into::<u32>().saturating_sub(1);
+			let mut calls = 0;
+			for ((pallet, function), usage) in Usage::<T>::drain() {
+				calls += 1;
+				Self::deposit_event(Event::CallUsageRecorded { era, pallet, function, usage });
+			}
+			let untracked = Untracked::<T>::take();
+			if calls > 0 || untracked > 0 {
+				Self::deposit_event(Event::EraUsageClosed { era, calls, untracked });
+			}
+			T::DbWeight::get().reads_writes(2 + calls as u64, 2 + calls as u64)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Switch the recording of calls on or off. What was recorded during the era is still
+		/// emitted when it ends.
+		///
+		/// - enabled: Whether calls are recorded
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn set_telemetry(origin: OriginFor<T>, enabled: bool) -> DispatchResultWithPostInfo {
+			T::TelemetryOrigin::ensure_origin(origin)?;
+			Enabled::<T>::put(enabled);
+
+			Self::deposit_event(Event::TelemetrySet { enabled });
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Add a dispatched call to the usage of its kind, unless too many kinds are recorded.
+		fn record(
+			pallet: CallName,
+			function: CallName,
+			info: &DispatchInfo,
+			post_info: &PostDispatchInfo,
+			len: u32,
+			failed: bool,
+		) {
+			let key = (pallet, function);
+			if !Usage::<T>::contains_key(&key) && Usage::<T>::count() >= T::MaxTrackedCalls::get() {
+				Untracked::<T>::mutate(|untracked| *untracked = untracked.saturating_add(1));
+				return;
+			}
+			let fee = T::Fees::actual_fee(len, info, post_info);
+			Usage::<T>::mutate(key, |usage| {
+				usage.count = usage.count.saturating_add(1);
+				usage.failed = usage.failed.saturating_add(u32::from(failed));
+				usage.weight = usage.weight.saturating_add(post_info.calc_actual_weight(info));
+				usage.fees = usage.fees.saturating_add(fee);
+			});
+		}
+	}
+}
+
+/// Records the actual weight and fee of each signed transaction while telemetry is on. It adds
+/// nothing to the transactions, so wallets can sign them as before.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct TrackCallUsage<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> TrackCallUsage<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for TrackCallUsage<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for TrackCallUsage<T> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "TrackCallUsage")
+	}
+}
+
+fn call_name(name: &str) -> CallName {
+	BoundedVec::truncate_from(name.as_bytes().to_vec())
+}
+
+impl<T: Config + Send + Sync> SignedExtension for TrackCallUsage<T>
+where
+	T::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + GetCallMetadata,
+{
+	const IDENTIFIER: &'static str = "TrackCallUsage";
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = ();
+	/// The pallet and call names and the length of the transaction, when telemetry is on.
+	type Pre = Option<(CallName, CallName, u32)>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if !Enabled::<T>::get() {
+			return Ok(None);
+		}
+		let metadata = call.get_call_metadata();
+		Ok(Some((call_name(metadata.pallet_name), call_name(metadata.function_name), len as u32)))
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some(Some((pallet, function, len))) = pre {
+			Pallet::<T>::record(pallet, function, info, post_info, len, result.is_err());
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,138 @@
+use crate::{mock::*, *};
+use frame_support::{assert_noop, assert_ok, dispatch::Pays, traits::Hooks};
+use sp_runtime::DispatchError;
+
+/// A remark, recorded as `System::remark`.
+fn remark() -> RuntimeCall {
+	RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+}
+
+/// Pass a transaction of `len` bytes through `TrackCallUsage`, weighing 100 and using
+/// `actual` of it.
+fn submit(call: RuntimeCall, len: usize, actual: u64, result: DispatchResult) {
+	let info = DispatchInfo { weight: Weight::from_parts(100, 0), ..Default::default() };
+	let post_info = PostDispatchInfo {
+		actual_weight: Some(Weight::from_parts(actual, 0)),
+		pays_fee: Pays::Yes,
+	};
+	let pre = TrackCallUsage::<Test>::new().pre_dispatch(&1, &call, &info, len).unwrap();
+	assert_ok!(TrackCallUsage::<Test>::post_dispatch(Some(pre), &info, &post_info, len, &result));
+}
+
+/// Submit three kinds of calls, one more than recorded: a remark first, using 40 of its weight.
+fn one_kind_too_many() {
+	submit(remark(), 10, 40, Ok(()));
+	submit(
+		RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![] }),
+		10,
+		40,
+		Ok(()),
+	);
+	submit(RuntimeCall::TelemetryModule(Call::set_telemetry { enabled: true }), 10, 40, Ok(()));
+}
+
+fn name(text: &str) -> CallName {
+	CallName::truncate_from(text.as_bytes().to_vec())
+}
+
+fn usage_of(pallet: &str, function: &str) -> CallUsage<u64> {
+	Usage::<Test>::get((name(pallet), name(function)))
+}
+
+#[test]
+fn set_telemetry_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TelemetryModule::set_telemetry(RuntimeOrigin::signed(1), true),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), true));
+		assert!(Enabled::<Test>::get());
+		System::assert_last_event(Event::TelemetrySet { enabled: true }.into());
+	});
+}
+
+#[test]
+fn calls_are_only_recorded_while_telemetry_is_on() {
+	new_test_ext().execute_with(|| {
+		submit(remark(), 10, 40, Ok(()));
+		assert_eq!(Usage::<Test>::count(), 0);
+
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), true));
+		submit(remark(), 10, 40, Ok(()));
+		assert_eq!(
+			usage_of("System", "remark"),
+			CallUsage { count: 1, failed: 0, weight: Weight::from_parts(40, 0), fees: 50 }
+		);
+
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), false));
+		submit(remark(), 10, 40, Ok(()));
+		assert_eq!(usage_of("System", "remark").count, 1);
+	});
+}
+
+#[test]
+fn usage_adds_up_actual_weights_fees_and_failures() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), true));
+
+		submit(remark(), 10, 40, Ok(()));
+		// Actual weights above the weight of the call don't count
+		submit(remark(), 20, 150, Err(DispatchError::Other("failed")));
+
+		assert_eq!(
+			usage_of("System", "remark"),
+			CallUsage { count: 2, failed: 1, weight: Weight::from_parts(140, 0), fees: 170 }
+		);
+	});
+}
+
+#[test]
+fn kinds_of_calls_beyond_the_limit_are_untracked() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), true));
+		one_kind_too_many();
+		assert_eq!(Untracked::<Test>::get(), 1);
+		assert_eq!(Usage::<Test>::count(), 2);
+
+		// Kinds already recorded still are
+		submit(remark(), 10, 40, Ok(()));
+		assert_eq!(usage_of("System", "remark").count, 2);
+	});
+}
+
+#[test]
+fn the_usage_of_an_era_is_emitted_when_the_next_starts() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TelemetryModule::set_telemetry(RuntimeOrigin::root(), true));
+		one_kind_too_many();
+
+		TelemetryModule::on_initialize(9);
+		assert_eq!(Usage::<Test>::count(), 2);
+
+		TelemetryModule::on_initialize(10);
+		System::assert_has_event(
+			Event::CallUsageRecorded {
+				era: 0,
+				pallet: name("System"),
+				function: name("remark"),
+				usage: CallUsage {
+					count: 1,
+					failed: 0,
+					weight: Weight::from_parts(40, 0),
+					fees: 50,
+				},
+			}
+			.into(),
+		);
+		System::assert_last_event(Event::EraUsageClosed { era: 0, calls: 2, untracked: 1 }.into());
+		assert_eq!(Usage::<Test>::count(), 0);
+		assert_eq!(Untracked::<Test>::get(), 0);
+
+		// Nothing is emitted for an era without calls
+		let events = System::events().len();
+		TelemetryModule::on_initialize(20);
+		assert_eq!(System::events().len(), events);
+	});
+}
@@ -0,0 +1,18 @@
+//! Runtime API of pet tournaments, for the client's tournament screen.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// `Bracket` is the runtime's `pallet_tournament::BracketOf`.
+	pub trait TournamentApi<Bracket> where Bracket: Codec {
+		/// The tournaments signing up or running.
+		fn active_tournaments() -> Vec<u32>;
+
+		/// A tournament with its entrants and every round drawn so far, `None` if it doesn't
+		/// exist.
+		fn bracket(tournament_id: u32) -> Option<Bracket>;
+	}
+}
@@ -0,0 +1,554 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::RuntimeDebug;
+use scale_info::TypeInfo;
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+type PetId = u32;
+pub type TournamentId = u32;
+
+/// Finds the pets entering tournaments and makes them battle.
+pub trait TournamentPets<AccountId> {
+	fn pet_of(owner: &AccountId) -> Option<PetId>;
+	/// Whether the pet can enter a tournament now, e.g. it's hatched and not staked.
+	fn can_enter(pet_id: PetId) -> bool;
+	/// Resolve a battle between the pets of two accounts and return the winning pet.
+	fn battle(first: &AccountId, second: &AccountId) -> Result<PetId, DispatchError>;
+}
+
+/// Where a tournament is at.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum Status {
+	/// Pets can enter until `signup_ends`.
+	SignUp,
+	/// The bracket is drawn, `round` is the round being played.
+	Running { round: u32 },
+	/// The last pet standing won the prize pool.
+	Finished { winner: PetId },
+	/// Too few pets entered, or governance cancelled it. Entry fees were refunded.
+	Cancelled,
+}
+
+/// A scheduled tournament. Rounds are fought every `round_interval` blocks once sign-up ends,
+/// and the entry fees pooled in `pool` go to the winner and the runner-up.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Tournament<Balance, BlockNumber> {
+	pub entry_fee: Balance,
+	pub signup_ends: BlockNumber,
+	pub round_interval: BlockNumber,
+	/// When the next step is taken: the bracket is drawn, or a round is fought.
+	pub next_step_at: BlockNumber,
+	pub pool: Balance,
+	pub status: Status,
+}
+
+/// A match of a round. A pet without opponent gets a bye and goes through.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Match {
+	pub first: PetId,
+	pub second: Option<PetId>,
+	pub winner: Option<PetId>,
+}
+
+/// A tournament with its entrants and bracket, returned by `TournamentApi`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Bracket<AccountId, Balance, BlockNumber> {
+	pub tournament: Tournament<Balance, BlockNumber>,
+	/// The pets that entered and their owners, in the order they entered.
+	pub entrants: Vec<(PetId, AccountId)>,
+	/// The matches of each round drawn so far, the first round first.
+	pub rounds: Vec<Vec<Match>>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{AccountIdConversion, Hash, Saturating, Zero},
+		Permill,
+	};
+	use sp_std::prelude::*;
+
+	type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	type TournamentOf<T> = Tournament<BalanceOf<T>, BlockNumberFor<T>>;
+	pub type BracketOf<T> =
+		Bracket<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency entry fees and prizes are paid in.
+		type Currency: Currency<Self::AccountId>;
+
+		/// Finds the entering pets and makes them battle.
+		type Pets: TournamentPets<Self::AccountId>;
+
+		/// The account holding the prize pools.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The origin allowed to schedule and cancel tournaments.
+		type TournamentOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The share of the prize pool paid to the runner-up, the winner gets the rest.
+		#[pallet::constant]
+		type RunnerUpShare: Get<Permill>;
+
+		/// The maximum number of pets in a tournament.
+		#[pallet::constant]
+		type MaxEntrants: Get<u32>;
+
+		/// The maximum number of tournaments signing up or running at the same time.
+		#[pallet::constant]
+		type MaxActiveTournaments: Get<u32>;
+	}
+
+	/// The id of the next scheduled tournament.
+	#[pallet::storage]
+	pub type NextTournamentId<T: Config> = StorageValue<_, TournamentId, ValueQuery>;
+
+	/// Every tournament scheduled.
+	#[pallet::storage]
+	pub type Tournaments<T: Config> = StorageMap<_, Twox64Concat, TournamentId, TournamentOf<T>>;
+
+	/// The tournaments signing up or running, their next step is taken when its block starts.
+	#[pallet::storage]
+	pub type ActiveTournaments<T: Config> =
+		StorageValue<_, BoundedVec<TournamentId, T::MaxActiveTournaments>, ValueQuery>;
+
+	/// The pets that entered each tournament and their owners.
+	#[pallet::storage]
+	pub type Entrants<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		TournamentId,
+		BoundedVec<(PetId, T::AccountId), T::MaxEntrants>,
+		ValueQuery,
+	>;
+
+	/// The matches of each round of each tournament.
+	#[pallet::storage]
+	pub type Rounds<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		TournamentId,
+		Twox64Concat,
+		u32,
+		BoundedVec<Match, T::MaxEntrants>,
+		ValueQuery,
+	>;
+
+	/// The tournament each pet is in, pets can't change hands until it's over.
+	#[pallet::storage]
+	pub type EnteredPets<T: Config> = StorageMap<_, Blake2_128Concat, PetId, TournamentId>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A tournament is scheduled, pets can enter until `signup_ends`.
+		TournamentScheduled {
+			tournament_id: TournamentId,
+			entry_fee: BalanceOf<T>,
+			signup_ends: T::BlockNumber,
+		},
+		/// A pet entered a tournament, its owner paid the entry fee.
+		TournamentEntered { tournament_id: TournamentId, owner: T::AccountId, pet_id: PetId },
+		/// Sign-up is over and the first round is drawn, it's fought at `next_round_at`.
+		BracketDrawn { tournament_id: TournamentId, entrants: u32, next_round_at: T::BlockNumber },
+		/// A round is fought, the next one is drawn from its winners.
+		RoundResolved { tournament_id: TournamentId, round: u32 },
+		/// The tournament is over and the prize pool paid.
+		TournamentWon {
+			tournament_id: TournamentId,
+			winner: PetId,
+			prize: BalanceOf<T>,
+			runner_up: Option<PetId>,
+			runner_up_prize: BalanceOf<T>,
+		},
+		/// The tournament is cancelled and the entry fees refunded.
+		TournamentCancelled { tournament_id: TournamentId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		InvalidSchedule,
+		TooManyTournaments,
+		TournamentNotFound,
+		SignUpClosed,
+		TournamentFull,
+		AccountHasNoPet,
+		PetAlreadyEntered,
+		PetCannotEnter,
+		TournamentOver,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Draw the brackets of the tournaments whose sign-up ends, and fight the rounds due.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let active = ActiveTournaments::<T>::get();
+			let mut weight = T::DbWeight::get().reads(1 + active.len() as u64);
+			for tournament_id in active {
+				let Some(mut tournament) = Tournaments::<T>::get(tournament_id) else {
+					continue;
+				};
+				if now < tournament.next_step_at {
+					continue;
+				}
+				let entrants = Entrants::<T>::get(tournament_id);
+				let count = entrants.len() as u64;
+				match tournament.status {
+					Status::SignUp => Self::draw_bracket(tournament_id, &mut tournament, entrants),
+					Status::Running { round } =>
+						Self::fight_round(tournament_id, &mut tournament, round, &entrants),
+					Status::Finished { .. } | Status::Cancelled => {},
+				}
+				Tournaments::<T>::insert(tournament_id, tournament);
+				// Battles read and write the care and experience of both pets
+				weight.saturating_accrue(
+					T::DbWeight::get().reads_writes(2 + 6 * count, 2 + 4 * count),
+				);
+			}
+			weight
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Schedule a tournament. Pets can enter until `signup_ends`, then a round is fought
+		/// every `round_interval` blocks until one pet is left.
+		///
+		/// - entry_fee: What each owner pays into the prize pool
+		/// - signup_ends: The block sign-up closes and the bracket is drawn at
+		/// - round_interval: The blocks between two rounds
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn schedule_tournament(
+			origin: OriginFor<T>,
+			entry_fee: BalanceOf<T>,
+			signup_ends: T::BlockNumber,
+			round_interval: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+			T::TournamentOrigin::ensure_origin(origin)?;
+			ensure!(
+				signup_ends > frame_system::Pallet::<T>::block_number() &&
+					!round_interval.is_zero(),
+				Error::<T>::InvalidSchedule
+			);
+
+			let tournament_id = NextTournamentId::<T>::get();
+			ActiveTournaments::<T>::try_mutate(|active| active.try_push(tournament_id))
+				.map_err(|_| Error::<T>::TooManyTournaments)?;
+			NextTournamentId::<T>::put(tournament_id.saturating_add(1));
+			Tournaments::<T>::insert(
+				tournament_id,
+				Tournament {
+					entry_fee,
+					signup_ends,
+					round_interval,
+					next_step_at: signup_ends,
+					pool: Zero::zero(),
+					status: Status::SignUp,
+				},
+			);
+
+			Self::deposit_event(Event::TournamentScheduled {
+				tournament_id,
+				entry_fee,
+				signup_ends,
+			});
+
+			Ok(().into())
+		}
+
+		/// Enter your pet in a tournament, paying its entry fee into the prize pool. The pet
+		/// can't change hands until the tournament is over.
+		///
+		/// - tournament_id: The id of the tournament
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn enter_tournament(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+		) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			let mut tournament =
+				Tournaments::<T>::get(tournament_id).ok_or(Error::<T>::TournamentNotFound)?;
+			ensure!(
+				tournament.status == Status::SignUp &&
+					frame_system::Pallet::<T>::block_number() < tournament.signup_ends,
+				Error::<T>::SignUpClosed
+			);
+			let pet_id = T::Pets::pet_of(&owner).ok_or(Error::<T>::AccountHasNoPet)?;
+			ensure!(!EnteredPets::<T>::contains_key(pet_id), Error::<T>::PetAlreadyEntered);
+			ensure!(T::Pets::can_enter(pet_id), Error::<T>::PetCannotEnter);
+
+			Entrants::<T>::try_mutate(tournament_id, |entrants| {
+				entrants.try_push((pet_id, owner.clone()))
+			})
+			.map_err(|_| Error::<T>::TournamentFull)?;
+			T::Currency::transfer(
+				&owner,
+				&Self::pool_account(),
+				tournament.entry_fee,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			tournament.pool = tournament.pool.saturating_add(tournament.entry_fee);
+			Tournaments::<T>::insert(tournament_id, tournament);
+			EnteredPets::<T>::insert(pet_id, tournament_id);
+
+			Self::deposit_event(Event::TournamentEntered { tournament_id, owner, pet_id });
+
+			Ok(().into())
+		}
+
+		/// Cancel a tournament that isn't over, refunding the entry fees.
+		///
+		/// - tournament_id: The id of the tournament
+		#[pallet::call_index(2)]
+		#[pallet::weight(0)]
+		pub fn cancel_tournament(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+		) -> DispatchResultWithPostInfo {
+			T::TournamentOrigin::ensure_origin(origin)?;
+			let mut tournament =
+				Tournaments::<T>::get(tournament_id).ok_or(Error::<T>::TournamentNotFound)?;
+			ensure!(
+				matches!(tournament.status, Status::SignUp | Status::Running { .. }),
+				Error::<T>::TournamentOver
+			);
+
+			Self::cancel(tournament_id, &mut tournament, &Entrants::<T>::get(tournament_id));
+			Tournaments::<T>::insert(tournament_id, tournament);
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account holding the prize pools.
+		pub fn pool_account() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Whether the pet is in a tournament that isn't over.
+		pub fn is_entered(pet_id: PetId) -> bool {
+			EnteredPets::<T>::contains_key(pet_id)
+		}
+
+		/// The tournaments signing up or running.
+		pub fn active_tournaments() -> Vec<TournamentId> {
+			ActiveTournaments::<T>::get().into_inner()
+		}
+
+		/// A tournament with its entrants and the rounds drawn so far.
+		pub fn bracket(tournament_id: TournamentId) -> Option<BracketOf<T>> {
+			let tournament = Tournaments::<T>::get(tournament_id)?;
+			let rounds = match tournament.status {
+				Status::SignUp | Status::Cancelled => 0,
+				Status::Running { round } => round + 1,
+				// The rounds up to the final
+				Status::Finished { .. } =>
+					Rounds::<T>::iter_key_prefix(tournament_id).count() as u32,
+			};
+			Some(Bracket {
+				tournament,
+				entrants: Entrants::<T>::get(tournament_id).into_inner(),
+				rounds: (0..rounds)
+					.map(|round| Rounds::<T>::get(tournament_id, round).into_inner())
+					.collect(),
+			})
+		}
+
+		/// Shuffle the entrants and pair them for the first round, or cancel the tournament
+		/// when fewer than two pets entered.
+		fn draw_bracket(
+			tournament_id: TournamentId,
+			tournament: &mut TournamentOf<T>,
+			entrants: BoundedVec<(PetId, T::AccountId), T::MaxEntrants>,
+		) {
+			if entrants.len() < 2 {
+				Self::cancel(tournament_id, tournament, &entrants);
+				return;
+			}
+			let mut pets: Vec<PetId> = entrants.iter().map(|(pet_id, _)| *pet_id).collect();
+			// Fisher-Yates, seeded from the parent block hash like battles
+			let seed =
+				T::Hashing::hash_of(&(frame_system::Pallet::<T>::parent_hash(), tournament_id));
+			for i in (1..pets.len()).rev() {
+				let roll = T::Hashing::hash_of(&(seed, i as u32));
+				let mut bytes = [0u8; 8];
+				bytes.copy_from_slice(&roll.as_ref()[..8]);
+				pets.swap(i, (u64::from_le_bytes(bytes) % (i as u64 + 1)) as usize);
+			}
+			Self::draw_round(tournament_id, 0, &pets);
+			tournament.status = Status::Running { round: 0 };
+			tournament.next_step_at =
+				tournament.next_step_at.saturating_add(tournament.round_interval);
+
+			Self::deposit_event(Event::BracketDrawn {
+				tournament_id,
+				entrants: pets.len() as u32,
+				next_round_at: tournament.next_step_at,
+			});
+		}
+
+		/// Pair the pets in order, the last one gets a bye when they are odd.
+		fn draw_round(tournament_id: TournamentId, round: u32, pets: &[PetId]) {
+			let matches: Vec<Match> = pets
+				.chunks(2)
+				.map(|pair| Match {
+					first: pair[0],
+					second: pair.get(1).copied(),
+					winner: if pair.len() == 1 { Some(pair[0]) } else { None },
+				})
+				.collect();
+			// As many matches as half the entrants, rounded up
+			Rounds::<T>::insert(tournament_id, round, BoundedVec::truncate_from(matches));
+		}
+
+		/// Fight the matches of the round with the battle engine, then draw the next round
+		/// from the winners, or pay the prizes after the final. A match that can't be fought,
+		/// e.g. while the game is paused, is tried again at the next block.
+		fn fight_round(
+			tournament_id: TournamentId,
+			tournament: &mut TournamentOf<T>,
+			round: u32,
+			entrants: &[(PetId, T::AccountId)],
+		) {
+			let owner = |pet_id: PetId| {
+				entrants.iter().find(|(entrant, _)| *entrant == pet_id).map(|(_, owner)| owner)
+			};
+			let mut matches = Rounds::<T>::get(tournament_id, round);
+			let mut blocked = false;
+			for fight in matches.iter_mut().filter(|fight| fight.winner.is_none()) {
+				let Some(second) = fight.second else {
+					continue;
+				};
+				let (Some(first_owner), Some(second_owner)) = (owner(fight.first), owner(second))
+				else {
+					continue;
+				};
+				match T::Pets::battle(first_owner, second_owner) {
+					Ok(winner) => fight.winner = Some(winner),
+					Err(_) => {
+						blocked = true;
+						break;
+					},
+				}
+			}
+			Rounds::<T>::insert(tournament_id, round, &matches);
+			if blocked {
+				tournament.next_step_at =
+					frame_system::Pallet::<T>::block_number().saturating_add(1u32.into());
+				return;
+			}
+
+			Self::deposit_event(Event::RoundResolved { tournament_id, round });
+
+			let winners: Vec<PetId> = matches.iter().filter_map(|fight| fight.winner).collect();
+			if let [winner] = winners[..] {
+				let runner_up = matches
+					.first()
+					.and_then(|fight| fight.second.map(|second| (fight.first, second)))
+					.map(|(first, second)| if first == winner { second } else { first });
+				Self::pay_prizes(tournament_id, tournament, winner, runner_up, entrants);
+				return;
+			}
+			Self::draw_round(tournament_id, round + 1, &winners);
+			tournament.status = Status::Running { round: round + 1 };
+			tournament.next_step_at =
+				tournament.next_step_at.saturating_add(tournament.round_interval);
+		}
+
+		/// Pay the winner and the runner-up from the prize pool, and free the pets.
+		fn pay_prizes(
+			tournament_id: TournamentId,
+			tournament: &mut TournamentOf<T>,
+			winner: PetId,
+			runner_up: Option<PetId>,
+			entrants: &[(PetId, T::AccountId)],
+		) {
+			let owner = |pet_id: PetId| {
+				entrants.iter().find(|(entrant, _)| *entrant == pet_id).map(|(_, owner)| owner)
+			};
+			let runner_up_prize = match runner_up {
+				Some(_) => T::RunnerUpShare::get() * tournament.pool,
+				None => Zero::zero(),
+			};
+			let prize = tournament.pool.saturating_sub(runner_up_prize);
+			for (pet_id, amount) in [(Some(winner), prize), (runner_up, runner_up_prize)] {
+				if let Some(owner) = pet_id.and_then(owner) {
+					// The pool holds every entry fee paid
+					let _ = T::Currency::transfer(
+						&Self::pool_account(),
+						owner,
+						amount,
+						ExistenceRequirement::AllowDeath,
+					);
+				}
+			}
+			tournament.status = Status::Finished { winner };
+			Self::close(tournament_id, entrants);
+
+			Self::deposit_event(Event::TournamentWon {
+				tournament_id,
+				winner,
+				prize,
+				runner_up,
+				runner_up_prize,
+			});
+		}
+
+		/// Refund the entry fees and free the pets.
+		fn cancel(
+			tournament_id: TournamentId,
+			tournament: &mut TournamentOf<T>,
+			entrants: &[(PetId, T::AccountId)],
+		) {
+			for (_, owner) in entrants {
+				let _ = T::Currency::transfer(
+					&Self::pool_account(),
+					owner,
+					tournament.entry_fee,
+					ExistenceRequirement::AllowDeath,
+				);
+			}
+			tournament.pool = Zero::zero();
+			tournament.status = Status::Cancelled;
+			Self::close(tournament_id, entrants);
+
+			Self::deposit_event(Event::TournamentCancelled { tournament_id });
+		}
+
+		fn close(tournament_id: TournamentId, entrants: &[(PetId, T::AccountId)]) {
+			for (pet_id, _) in entrants {
+				EnteredPets::<T>::remove(pet_id);
+			}
+			ActiveTournaments::<T>::mutate(|active| active.retain(|id| *id != tournament_id));
+		}
+	}
+}
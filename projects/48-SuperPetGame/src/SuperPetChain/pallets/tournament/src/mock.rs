@@ -0,0 +1,124 @@
+use crate::{self as pallet_tournament, PetId, TournamentPets};
+use frame_support::{
+	ensure, parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+	PalletId,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		TournamentModule: pallet_tournament,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const TournamentPalletId: PalletId = PalletId(*b"py/tourn");
+	pub const RunnerUpShare: Permill = Permill::from_percent(30);
+	/// The owner of each pet. Accounts 1 to 5 own pets 1 to 5, account 6 owns none.
+	pub static PetOwners: Vec<(u64, PetId)> = (1..=5).map(|who| (who, who as PetId)).collect();
+	/// The pets busy elsewhere, e.g. staked.
+	pub static Busy: Vec<PetId> = vec![5];
+	/// Whether battles can't be fought, like while the game is paused.
+	pub static BattlesFail: bool = false;
+}
+
+/// Pets owned as listed in `PetOwners`. The pet with the lowest id wins every battle.
+pub struct MockPets;
+
+impl TournamentPets<u64> for MockPets {
+	fn pet_of(owner: &u64) -> Option<PetId> {
+		PetOwners::get()
+			.into_iter()
+			.find(|(account, _)| account == owner)
+			.map(|(_, id)| id)
+	}
+
+	fn can_enter(pet_id: PetId) -> bool {
+		!Busy::get().contains(&pet_id)
+	}
+
+	fn battle(first: &u64, second: &u64) -> Result<PetId, DispatchError> {
+		ensure!(!BattlesFail::get(), DispatchError::Other("paused"));
+		let pet = |owner: &u64| Self::pet_of(owner).ok_or(DispatchError::Other("no pet"));
+		Ok(pet(first)?.min(pet(second)?))
+	}
+}
+
+impl pallet_tournament::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = MockPets;
+	type PalletId = TournamentPalletId;
+	type TournamentOrigin = EnsureRoot<u64>;
+	type RunnerUpShare = RunnerUpShare;
+	type MaxEntrants = ConstU32<4>;
+	type MaxActiveTournaments = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime, at block 1 so events are kept.
+// Every account has a balance of 100.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=6).map(|who| (who, 100)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,256 @@
+use crate::{mock::*, *};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, Hooks},
+};
+use sp_runtime::DispatchError;
+
+/// Schedule tournament 0 with an entry fee of 10, sign-up ending at block 5 and a round every
+/// 3 blocks, and enter the pets of `owners`.
+fn tournament_with(owners: &[u64]) {
+	assert_ok!(TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 5, 3));
+	for owner in owners {
+		assert_ok!(TournamentModule::enter_tournament(RuntimeOrigin::signed(*owner), 0));
+	}
+}
+
+/// Start every block up to `n`.
+fn run_to(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		TournamentModule::on_initialize(System::block_number());
+	}
+}
+
+fn status() -> Option<Status> {
+	Tournaments::<Test>::get(0).map(|tournament| tournament.status)
+}
+
+fn pool_balance() -> u64 {
+	Balances::free_balance(TournamentModule::pool_account())
+}
+
+#[test]
+fn schedule_tournament_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TournamentModule::schedule_tournament(RuntimeOrigin::signed(1), 10, 5, 3),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 1, 3),
+			Error::<Test>::InvalidSchedule
+		);
+		assert_noop!(
+			TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 5, 0),
+			Error::<Test>::InvalidSchedule
+		);
+
+		assert_ok!(TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 5, 3));
+		assert_eq!(
+			Tournaments::<Test>::get(0),
+			Some(Tournament {
+				entry_fee: 10,
+				signup_ends: 5,
+				round_interval: 3,
+				next_step_at: 5,
+				pool: 0,
+				status: Status::SignUp,
+			})
+		);
+		assert_eq!(NextTournamentId::<Test>::get(), 1);
+		System::assert_last_event(
+			Event::TournamentScheduled { tournament_id: 0, entry_fee: 10, signup_ends: 5 }.into(),
+		);
+
+		assert_ok!(TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 5, 3));
+		assert_eq!(TournamentModule::active_tournaments(), vec![0, 1]);
+		assert_noop!(
+			TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 5, 3),
+			Error::<Test>::TooManyTournaments
+		);
+	});
+}
+
+#[test]
+fn entry_fees_go_to_the_prize_pool() {
+	new_test_ext().execute_with(|| {
+		tournament_with(&[1]);
+
+		assert_eq!(Tournaments::<Test>::get(0).map(|tournament| tournament.pool), Some(10));
+		assert_eq!(pool_balance(), 10);
+		assert_eq!(Balances::free_balance(1), 90);
+		assert_eq!(Entrants::<Test>::get(0).into_inner(), vec![(1, 1)]);
+		assert!(TournamentModule::is_entered(1));
+		System::assert_last_event(
+			Event::TournamentEntered { tournament_id: 0, owner: 1, pet_id: 1 }.into(),
+		);
+	});
+}
+
+#[test]
+fn enter_tournament_checks_the_tournament_and_the_pet() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(1), 0),
+			Error::<Test>::TournamentNotFound
+		);
+		tournament_with(&[1]);
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(6), 0),
+			Error::<Test>::AccountHasNoPet
+		);
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(1), 0),
+			Error::<Test>::PetAlreadyEntered
+		);
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(5), 0),
+			Error::<Test>::PetCannotEnter
+		);
+
+		for owner in 2..=4 {
+			assert_ok!(TournamentModule::enter_tournament(RuntimeOrigin::signed(owner), 0));
+		}
+		Busy::set(vec![]);
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(5), 0),
+			Error::<Test>::TournamentFull
+		);
+
+		System::set_block_number(5);
+		assert_ok!(TournamentModule::schedule_tournament(RuntimeOrigin::root(), 10, 6, 3));
+		System::set_block_number(6);
+		assert_noop!(
+			TournamentModule::enter_tournament(RuntimeOrigin::signed(5), 1),
+			Error::<Test>::SignUpClosed
+		);
+	});
+}
+
+#[test]
+fn tournaments_with_one_pet_are_cancelled_and_refunded() {
+	new_test_ext().execute_with(|| {
+		tournament_with(&[1]);
+
+		run_to(5);
+		assert_eq!(status(), Some(Status::Cancelled));
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(pool_balance(), 0);
+		assert!(!TournamentModule::is_entered(1));
+		assert!(TournamentModule::active_tournaments().is_empty());
+		System::assert_last_event(Event::TournamentCancelled { tournament_id: 0 }.into());
+	});
+}
+
+#[test]
+fn the_winner_and_the_runner_up_share_the_prize_pool() {
+	new_test_ext().execute_with(|| {
+		tournament_with(&[1, 2]);
+
+		run_to(4);
+		assert_eq!(status(), Some(Status::SignUp));
+		run_to(5);
+		assert_eq!(status(), Some(Status::Running { round: 0 }));
+		System::assert_last_event(
+			Event::BracketDrawn { tournament_id: 0, entrants: 2, next_round_at: 8 }.into(),
+		);
+
+		run_to(8);
+		assert_eq!(status(), Some(Status::Finished { winner: 1 }));
+		// The runner-up gets 30% of the pool of 20
+		System::assert_last_event(
+			Event::TournamentWon {
+				tournament_id: 0,
+				winner: 1,
+				prize: 14,
+				runner_up: Some(2),
+				runner_up_prize: 6,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 90 + 14);
+		assert_eq!(Balances::free_balance(2), 90 + 6);
+		assert_eq!(pool_balance(), 0);
+		assert!(!TournamentModule::is_entered(1));
+		assert!(!TournamentModule::is_entered(2));
+		assert!(TournamentModule::active_tournaments().is_empty());
+	});
+}
+
+#[test]
+fn rounds_are_fought_until_one_pet_is_left() {
+	new_test_ext().execute_with(|| {
+		tournament_with(&[1, 2, 3]);
+
+		// The odd pet gets a bye
+		run_to(5);
+		let first_round = Rounds::<Test>::get(0, 0);
+		assert_eq!(first_round.len(), 2);
+		let bye = first_round[1].clone();
+		assert_eq!((bye.second, bye.winner), (None, Some(bye.first)));
+
+		run_to(8);
+		assert_eq!(status(), Some(Status::Running { round: 1 }));
+		System::assert_has_event(Event::RoundResolved { tournament_id: 0, round: 0 }.into());
+		assert_eq!(Rounds::<Test>::get(0, 1).len(), 1);
+
+		run_to(11);
+		assert_eq!(status(), Some(Status::Finished { winner: 1 }));
+		let bracket = TournamentModule::bracket(0).unwrap();
+		assert_eq!(bracket.rounds.len(), 2);
+		let last = &bracket.rounds[1][0];
+		let runner_up = if last.first == 1 { last.second.unwrap() } else { last.first };
+		// 30% of the pool of 30
+		assert_eq!(Balances::free_balance(1), 90 + 21);
+		assert_eq!(Balances::free_balance(runner_up), 90 + 9);
+		assert_eq!(pool_balance(), 0);
+	});
+}
+
+#[test]
+fn battles_that_cannot_be_fought_are_tried_the_next_block() {
+	new_test_ext().execute_with(|| {
+		tournament_with(&[1, 2]);
+		run_to(5);
+
+		BattlesFail::set(true);
+		run_to(8);
+		assert_eq!(status(), Some(Status::Running { round: 0 }));
+		assert_eq!(Rounds::<Test>::get(0, 0)[0].winner, None);
+		assert_eq!(Tournaments::<Test>::get(0).map(|tournament| tournament.next_step_at), Some(9));
+
+		BattlesFail::set(false);
+		run_to(9);
+		assert_eq!(status(), Some(Status::Finished { winner: 1 }));
+	});
+}
+
+#[test]
+fn cancel_tournament_refunds_the_entry_fees() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TournamentModule::cancel_tournament(RuntimeOrigin::root(), 0),
+			Error::<Test>::TournamentNotFound
+		);
+		tournament_with(&[1, 2]);
+		run_to(5);
+		assert_noop!(
+			TournamentModule::cancel_tournament(RuntimeOrigin::signed(1), 0),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(TournamentModule::cancel_tournament(RuntimeOrigin::root(), 0));
+		assert_eq!(status(), Some(Status::Cancelled));
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(pool_balance(), 0);
+		assert!(!TournamentModule::is_entered(1));
+		System::assert_last_event(Event::TournamentCancelled { tournament_id: 0 }.into());
+
+		assert_noop!(
+			TournamentModule::cancel_tournament(RuntimeOrigin::root(), 0),
+			Error::<Test>::TournamentOver
+		);
+	});
+}
@@ -0,0 +1,406 @@
+//! Care stats of a pet: how full and rested it is, its level and its mood.
+//!
+//! The pallet resolves battles with these stats and the client draws them, moving them on
+//! between two blocks. Both go through this crate, with the `Rules` the chain stores, so what
+//! the client shows at a block is exactly what the chain computes at that block. Everything
+//! here is plain integer math, with no floats or platform dependent behaviour.
+
+#![no_std]
+
+#[cfg(test)]
+mod tests;
+
+pub type BlockNumber = u32;
+
+/// Time in thousandths of a block. The chain only looks at the start of blocks, the client
+/// counts the ticks since the last block to animate the stats until the next one.
+pub type Ticks = u64;
+
+pub const TICKS_PER_BLOCK: Ticks = 1000;
+
+/// The value of a full stat, stats drop steadily from it.
+pub const FULL: u16 = 1000;
+
+/// Level `n` starts at `LEVEL_STEP * (n - 1)²` experience.
+pub const LEVEL_STEP: u64 = 25;
+
+/// Version of the rules in this crate. It goes up with every change to what they compute, so
+/// a client built with other rules than the runtime's can tell it would show other stats than
+/// the chain.
+pub const GAME_RULES_VERSION: u32 = 3;
+
+/// The start of `block`.
+pub fn at_block(block: BlockNumber) -> Ticks {
+	block as Ticks * TICKS_PER_BLOCK
+}
+
+/// How fast stats drop and when the pet minds it. Governance can change them on chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+	/// Blocks a full stomach lasts.
+	pub fullness_lasts: BlockNumber,
+	/// Blocks a full night of sleep lasts.
+	pub energy_lasts: BlockNumber,
+	/// The pet is hungry when its fullness is below this, out of `FULL`.
+	pub hungry_below: u16,
+	/// The pet is tired when its energy is below this, out of `FULL`.
+	pub tired_below: u16,
+}
+
+impl Rules {
+	/// Hungry after 1 hour without food and tired after 2 hours without sleep, with 6 second
+	/// blocks. Stats are half full then, and empty twice as late.
+	pub const DEFAULT: Rules =
+		Rules { fullness_lasts: 1200, energy_lasts: 2400, hungry_below: 500, tired_below: 500 };
+
+	/// Whether the stats can drop below the thresholds: both last at least a block and the
+	/// thresholds are above zero and at most `FULL`.
+	pub fn is_valid(&self) -> bool {
+		self.fullness_lasts > 0 &&
+			self.energy_lasts > 0 &&
+			(1..=FULL).contains(&self.hungry_below) &&
+			(1..=FULL).contains(&self.tired_below)
+	}
+
+	/// Blocks without food after which a pet is hungry.
+	pub fn hungry_after(&self) -> BlockNumber {
+		threshold_after(self.fullness_lasts, self.hungry_below)
+	}
+
+	/// Blocks without sleep after which a pet is tired.
+	pub fn tired_after(&self) -> BlockNumber {
+		threshold_after(self.energy_lasts, self.tired_below)
+	}
+}
+
+impl Default for Rules {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// The blocks a stat lasting `span` blocks takes to drop below `below`. `remaining` is below
+/// it exactly when more than `span * (FULL - below) / FULL` blocks passed.
+fn threshold_after(span: BlockNumber, below: u16) -> BlockNumber {
+	let drop = FULL.saturating_sub(below) as u64;
+	(span as u64 * drop / FULL as u64) as BlockNumber
+}
+
+/// How a pet feels, from its stats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mood {
+	Happy,
+	Hungry,
+	Tired,
+	/// Both hungry and tired.
+	Miserable,
+}
+
+impl Mood {
+	pub fn is_hungry(self) -> bool {
+		matches!(self, Mood::Hungry | Mood::Miserable)
+	}
+
+	pub fn is_tired(self) -> bool {
+		matches!(self, Mood::Tired | Mood::Miserable)
+	}
+
+	/// The odds in a battle of a pet with `experience` in this mood: its experience plus one,
+	/// halved when it's hungry or tired and quartered when it's both.
+	pub fn battle_weight(self, experience: u64) -> u64 {
+		let weight = experience.saturating_add(1);
+		let weight = match self {
+			Mood::Happy => weight,
+			Mood::Hungry | Mood::Tired => weight / 2,
+			Mood::Miserable => weight / 4,
+		};
+		weight.max(1)
+	}
+}
+
+/// What the chain stores about the care of a pet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Care {
+	/// `None` when the pet was never fed.
+	pub last_feed: Option<BlockNumber>,
+	/// `None` when the pet never slept.
+	pub last_sleep: Option<BlockNumber>,
+	pub experience: u64,
+}
+
+/// The stats of a pet at a given time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+	/// How full the pet is, out of `FULL`.
+	pub fullness: u16,
+	/// How rested the pet is, out of `FULL`.
+	pub energy: u16,
+	pub level: u32,
+	pub mood: Mood,
+}
+
+impl Care {
+	/// The stats at `now` under `rules`. `now` can be a little before the care times when
+	/// the client reads them ahead of the chain head, the pet is then just fed or rested.
+	pub fn stats(&self, rules: &Rules, now: Ticks) -> Stats {
+		let fullness = remaining(self.last_feed, now, rules.fullness_lasts);
+		let energy = remaining(self.last_sleep, now, rules.energy_lasts);
+		let mood = match (fullness < rules.hungry_below, energy < rules.tired_below) {
+			(false, false) => Mood::Happy,
+			(true, false) => Mood::Hungry,
+			(false, true) => Mood::Tired,
+			(true, true) => Mood::Miserable,
+		};
+		Stats { fullness, energy, level: level(self.experience), mood }
+	}
+
+	/// The odds of the pet in a battle at `now`, see `Mood::battle_weight`.
+	pub fn battle_weight(&self, rules: &Rules, now: Ticks) -> u64 {
+		self.stats(rules, now).mood.battle_weight(self.experience)
+	}
+}
+
+/// Whether a pet with battle weight `weight` beats one with `opponent_weight`, given a random
+/// `roll`. Out of every `weight + opponent_weight` consecutive rolls, it wins `weight`.
+pub fn wins_battle(roll: u64, weight: u64, opponent_weight: u64) -> bool {
+	roll % weight.saturating_add(opponent_weight).max(1) < weight
+}
+
+/// The chance to win a battle with `weight` against `opponent_weight`, as the rolls won out of
+/// every `rolls` consecutive ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Odds {
+	pub wins: u64,
+	pub rolls: u64,
+}
+
+impl Odds {
+	pub fn new(weight: u64, opponent_weight: u64) -> Odds {
+		let rolls = weight.saturating_add(opponent_weight).max(1);
+		Odds { wins: weight.min(rolls), rolls }
+	}
+
+	/// The chance in percent, rounded down.
+	pub fn percent(&self) -> u64 {
+		(self.wins as u128 * 100 / self.rolls as u128) as u64
+	}
+}
+
+/// The odds of a pet with `experience` against an opponent of `opponent_weight`, in each mood
+/// it can be in when the chain rolls. The roll comes from randomness no one knows before the
+/// battle is on chain, so clients show this table instead of a result.
+pub fn battle_odds(experience: u64, opponent_weight: u64) -> [(Mood, Odds); 4] {
+	[Mood::Happy, Mood::Hungry, Mood::Tired, Mood::Miserable]
+		.map(|mood| (mood, Odds::new(mood.battle_weight(experience), opponent_weight)))
+}
+
+/// The combat stats a pet battles with, kept apart from what it looks like so they can be
+/// rebalanced without touching the pets. The chain keeps them in a registry, pets refer to an
+/// entry of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatsBlock {
+	/// How strong the pet is in battles, out of `FULL`: its battle weight is scaled by it.
+	pub power: u16,
+}
+
+impl StatsBlock {
+	/// Battle weights are left as they are.
+	pub const NEUTRAL: StatsBlock = StatsBlock { power: FULL };
+
+	/// The highest power, four times the neutral one.
+	pub const MAX_POWER: u16 = 4 * FULL;
+
+	/// Whether the power is above zero and at most `MAX_POWER`.
+	pub fn is_valid(&self) -> bool {
+		(1..=Self::MAX_POWER).contains(&self.power)
+	}
+
+	/// `weight` scaled by the power, at least 1 like every battle weight.
+	pub fn battle_weight(&self, weight: u64) -> u64 {
+		let scaled = weight as u128 * self.power as u128 / FULL as u128;
+		(scaled.min(u64::MAX as u128) as u64).max(1)
+	}
+}
+
+impl Default for StatsBlock {
+	fn default() -> Self {
+		Self::NEUTRAL
+	}
+}
+
+/// How jealous a pet last fed at `last_feed` is of the pet of its household fed last, at
+/// `favourite`, out of `FULL`. Being fed a little before the others is fine: jealousy starts
+/// when the pet was already hungry as the other one was fed, and is full when its stomach was
+/// empty by then. A pet never fed is fully jealous of any pet fed.
+pub fn jealousy(
+	last_feed: Option<BlockNumber>,
+	favourite: Option<BlockNumber>,
+	rules: &Rules,
+) -> u16 {
+	let Some(favourite) = favourite else {
+		return 0;
+	};
+	let Some(last_feed) = last_feed else {
+		return FULL;
+	};
+	let hungry_after = rules.hungry_after();
+	let neglected = favourite.saturating_sub(last_feed).saturating_sub(hungry_after) as u64;
+	let span = rules.fullness_lasts.saturating_sub(hungry_after).max(1) as u64;
+	(neglected * FULL as u64 / span).min(FULL as u64) as u16
+}
+
+/// The jealousy of a household, from the last feeds of its pets: that of its most jealous
+/// pet, so neglecting one pet costs all of them, see `experience_gain`.
+pub fn household_jealousy(last_feeds: &[Option<BlockNumber>], rules: &Rules) -> u16 {
+	let favourite = last_feeds.iter().flatten().max().copied();
+	last_feeds
+		.iter()
+		.map(|last_feed| jealousy(*last_feed, favourite, rules))
+		.max()
+		.unwrap_or(0)
+}
+
+/// The experience a pet of a household with `jealousy` earns out of `experience`: all of it
+/// without jealousy, down to half of it at full jealousy.
+pub fn experience_gain(experience: u64, jealousy: u16) -> u64 {
+	let cut = experience as u128 * jealousy.min(FULL) as u128 / (2 * FULL as u128);
+	experience - cut as u64
+}
+
+/// Health of a pet at the start of a replayed battle.
+pub const REPLAY_HEALTH: u16 = 1000;
+
+/// What the chain records of a battle, enough to replay it: the roll that settled it and the
+/// battle weights of both pets then.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BattleLog {
+	pub seed: u64,
+	pub challenger_weight: u64,
+	pub opponent_weight: u64,
+}
+
+impl BattleLog {
+	pub fn challenger_wins(&self) -> bool {
+		wins_battle(self.seed, self.challenger_weight, self.opponent_weight)
+	}
+
+	/// The blows of the battle, for clients to animate. They follow from the seed alone, so
+	/// every client replays the same battle, and the winner is always the one the roll chose.
+	pub fn blows(&self) -> Blows {
+		Blows {
+			state: self.seed,
+			health: [REPLAY_HEALTH; 2],
+			weights: [self.challenger_weight.max(1), self.opponent_weight.max(1)],
+			winner: if self.challenger_wins() { 0 } else { 1 },
+			attacker: 0,
+		}
+	}
+}
+
+/// A blow of a replayed battle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Blow {
+	pub by_challenger: bool,
+	pub damage: u16,
+	/// The health left to the pet hit, out of `REPLAY_HEALTH`.
+	pub health_left: u16,
+}
+
+/// The blows of a replayed battle, taking turns from the challenger on until the loser is
+/// down. Heavier pets hit harder, and the loser never downs the winner.
+pub struct Blows {
+	state: u64,
+	/// Health of the challenger and of the opponent.
+	health: [u16; 2],
+	weights: [u64; 2],
+	winner: usize,
+	attacker: usize,
+}
+
+impl Iterator for Blows {
+	type Item = Blow;
+
+	fn next(&mut self) -> Option<Blow> {
+		let loser = 1 - self.winner;
+		if self.health[loser] == 0 {
+			return None;
+		}
+		let (attacker, defender) = (self.attacker, 1 - self.attacker);
+		self.attacker = defender;
+
+		// Splitmix64, a fine spread of blows from consecutive seeds
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^= z >> 31;
+
+		// 100 to 250, doubled against an equal pet and scaled by the share of the weights
+		let base = 100 + (z % 151) as u128;
+		let (weight, other) = (self.weights[attacker] as u128, self.weights[defender] as u128);
+		let damage = (base * 2 * weight / (weight + other)).clamp(40, 400) as u16;
+		let damage =
+			if attacker == loser { damage.min(self.health[self.winner] - 1) } else { damage };
+		self.health[defender] = self.health[defender].saturating_sub(damage);
+		Some(Blow { by_challenger: attacker == 0, damage, health_left: self.health[defender] })
+	}
+}
+
+/// What is left of a stat `span` blocks long, `since` the last time it was full. It drops by
+/// at least one as soon as a tick passes, so it is below half exactly when more than half of
+/// `span` passed, and likewise for any threshold.
+fn remaining(since: Option<BlockNumber>, now: Ticks, span: BlockNumber) -> u16 {
+	let Some(since) = since else {
+		return 0;
+	};
+	let elapsed = now.saturating_sub(at_block(since));
+	let span = at_block(span).max(1);
+	let used = elapsed.saturating_mul(FULL as Ticks).saturating_add(span - 1) / span;
+	FULL.saturating_sub(used.min(FULL as Ticks) as u16)
+}
+
+/// The level of a pet with `experience`, starting at 1.
+pub fn level(experience: u64) -> u32 {
+	let level = sqrt(experience / LEVEL_STEP).saturating_add(1);
+	level.min(u32::MAX as u64) as u32
+}
+
+/// The experience a pet needs to reach `level`.
+pub fn experience_for(level: u32) -> u64 {
+	let steps = level.saturating_sub(1) as u64;
+	steps.saturating_mul(steps).saturating_mul(LEVEL_STEP)
+}
+
+/// Why a pet name is refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameError {
+	NotUtf8,
+	/// Longer than the limit in bytes, not in characters.
+	TooLong,
+}
+
+/// Check a pet name against the chain's `StringLimit`, as the pallet does on mint and rename,
+/// so a client can tell the player before submitting. Names are kept as UTF-8 bytes, so a
+/// character outside ASCII takes up to 4 of the `limit`.
+pub fn check_name(name: &[u8], limit: u32) -> Result<&str, NameError> {
+	let name = core::str::from_utf8(name).map_err(|_| NameError::NotUtf8)?;
+	if name.len() > limit as usize {
+		return Err(NameError::TooLong);
+	}
+	Ok(name)
+}
+
+/// Integer square root, rounded down.
+fn sqrt(n: u64) -> u64 {
+	if n < 2 {
+		return n;
+	}
+	// Newton's method, starting above the root and going down to it
+	let mut root = n;
+	let mut next = n / 2 + n % 2;
+	while next < root {
+		root = next;
+		next = (root + n / root) / 2;
+	}
+	root
+}
@@ -0,0 +1,303 @@
+use crate::*;
+use proptest::prelude::*;
+
+const RULES: Rules = Rules::DEFAULT;
+
+fn rules() -> impl Strategy<Value = Rules> {
+	(1..=4 * RULES.energy_lasts, 1..=4 * RULES.energy_lasts, 1..=FULL, 1..=FULL).prop_map(
+		|(fullness_lasts, energy_lasts, hungry_below, tired_below)| Rules {
+			fullness_lasts,
+			energy_lasts,
+			hungry_below,
+			tired_below,
+		},
+	)
+}
+
+fn care(last_feed: Option<BlockNumber>, last_sleep: Option<BlockNumber>, experience: u64) -> Care {
+	Care { last_feed, last_sleep, experience }
+}
+
+proptest! {
+	#[test]
+	fn stats_never_go_up_without_care(
+		feed in any::<Option<BlockNumber>>(),
+		sleep in any::<Option<BlockNumber>>(),
+		experience in any::<u64>(),
+		now in any::<Ticks>(),
+		later in any::<Ticks>(),
+		rules in rules(),
+	) {
+		let care = care(feed, sleep, experience);
+		let before = care.stats(&rules, now.min(later));
+		let after = care.stats(&rules, now.max(later));
+		prop_assert!(after.fullness <= before.fullness);
+		prop_assert!(after.energy <= before.energy);
+		prop_assert_eq!(after.level, before.level);
+	}
+
+	#[test]
+	fn stats_stay_in_range_at_any_time(
+		feed in any::<Option<BlockNumber>>(),
+		sleep in any::<Option<BlockNumber>>(),
+		experience in any::<u64>(),
+		now in any::<Ticks>(),
+		rules in rules(),
+	) {
+		// Overflows panic in test builds, so reaching the checks means there were none
+		let care = care(feed, sleep, experience);
+		let stats = care.stats(&rules, now);
+		prop_assert!(stats.fullness <= FULL);
+		prop_assert!(stats.energy <= FULL);
+		prop_assert!(stats.level >= 1);
+		let weight = care.battle_weight(&rules, now);
+		prop_assert!(weight >= 1);
+		prop_assert!(weight <= experience.saturating_add(1));
+	}
+
+	#[test]
+	fn hungry_and_tired_exactly_after_their_limits(
+		since in any::<BlockNumber>(),
+		blocks in 0..4 * RULES.energy_lasts,
+		rules in rules(),
+	) {
+		let now = at_block(since.saturating_add(blocks));
+		let blocks = (now - at_block(since)) / TICKS_PER_BLOCK;
+		let mood = care(Some(since), Some(since), 0).stats(&rules, now).mood;
+		prop_assert_eq!(mood.is_hungry(), blocks > rules.hungry_after() as Ticks);
+		prop_assert_eq!(mood.is_tired(), blocks > rules.tired_after() as Ticks);
+	}
+
+	#[test]
+	fn stats_between_blocks_stay_between_the_chain_values(
+		since in any::<BlockNumber>(),
+		block in any::<BlockNumber>(),
+		tick in 0..TICKS_PER_BLOCK,
+	) {
+		let care = care(Some(since), Some(since), 0);
+		let block = block.min(BlockNumber::MAX - 1);
+		let start = care.stats(&RULES, at_block(block));
+		let end = care.stats(&RULES, at_block(block + 1));
+		let between = care.stats(&RULES, at_block(block) + tick);
+		prop_assert!(start.fullness >= between.fullness && between.fullness >= end.fullness);
+		prop_assert!(start.energy >= between.energy && between.energy >= end.energy);
+	}
+
+	#[test]
+	fn level_grows_with_experience(experience in any::<u64>(), more in any::<u64>()) {
+		prop_assert!(level(experience.saturating_add(more)) >= level(experience));
+	}
+
+	#[test]
+	fn level_starts_at_its_experience(experience in 0..u64::MAX / 2) {
+		let level = level(experience);
+		prop_assert!(experience_for(level) <= experience);
+		prop_assert!(experience < experience_for(level + 1));
+	}
+
+	#[test]
+	fn experience_for_a_level_reaches_it(level in 1..858_993_460u32) {
+		prop_assert_eq!(crate::level(experience_for(level)), level);
+		prop_assert_eq!(crate::level(experience_for(level) - 1), level - 1);
+	}
+
+	#[test]
+	fn battles_are_won_in_proportion_to_the_weights(
+		start in any::<u64>(),
+		weight in 1..500u64,
+		opponent_weight in 1..500u64,
+	) {
+		let rolls = (0..weight + opponent_weight).map(|roll| start.wrapping_add(roll));
+		let won = rolls.filter(|roll| wins_battle(*roll, weight, opponent_weight)).count();
+		prop_assert_eq!(won as u64, weight);
+	}
+
+	#[test]
+	fn odds_tables_count_the_rolls_won(
+		start in 0..u64::MAX / 2,
+		experience in 0..500u64,
+		opponent_weight in 1..500u64,
+	) {
+		for (mood, odds) in battle_odds(experience, opponent_weight) {
+			let weight = mood.battle_weight(experience);
+			let won = (start..start + odds.rolls)
+				.filter(|roll| wins_battle(*roll, weight, opponent_weight))
+				.count();
+			prop_assert_eq!(won as u64, odds.wins);
+		}
+	}
+
+	#[test]
+	fn stats_blocks_scale_weights_in_proportion_to_their_power(
+		weight in any::<u64>(),
+		power in 1..=StatsBlock::MAX_POWER,
+		more in 0..=StatsBlock::MAX_POWER,
+	) {
+		prop_assert_eq!(StatsBlock::NEUTRAL.battle_weight(weight), weight.max(1));
+		let block = StatsBlock { power };
+		let stronger = StatsBlock { power: power.saturating_add(more).min(StatsBlock::MAX_POWER) };
+		prop_assert!(block.is_valid() && stronger.is_valid());
+		let scaled = block.battle_weight(weight);
+		prop_assert!(scaled >= 1);
+		prop_assert!(stronger.battle_weight(weight) >= scaled);
+	}
+
+	#[test]
+	fn jealousy_cuts_at_most_half_the_experience(
+		last_feeds in prop::collection::vec(prop::option::of(any::<BlockNumber>()), 0..8),
+		rules in rules(),
+		experience in any::<u64>(),
+	) {
+		let jealousy = household_jealousy(&last_feeds, &rules);
+		prop_assert!(jealousy <= FULL);
+		let gain = experience_gain(experience, jealousy);
+		prop_assert!(gain <= experience && gain >= experience - experience / 2);
+	}
+
+	#[test]
+	fn replays_end_with_the_winner_of_the_roll_standing(
+		seed in any::<u64>(),
+		challenger_weight in 1..u64::MAX,
+		opponent_weight in 1..u64::MAX,
+	) {
+		let log = BattleLog { seed, challenger_weight, opponent_weight };
+		// A winner's blow takes at least 40 of the 1000 health
+		prop_assert!(log.blows().count() <= 2 * 25);
+		let last = log.blows().last().unwrap();
+		prop_assert_eq!(last.by_challenger, log.challenger_wins());
+		prop_assert_eq!(last.health_left, 0);
+		prop_assert_eq!(log.blows().filter(|blow| blow.health_left == 0).count(), 1);
+		prop_assert!(log.blows().eq(log.blows()));
+	}
+}
+
+#[test]
+fn levels_start_at_one() {
+	assert_eq!(level(0), 1);
+	assert_eq!(level(LEVEL_STEP - 1), 1);
+	assert_eq!(level(LEVEL_STEP), 2);
+	assert_eq!(level(4 * LEVEL_STEP), 3);
+	assert_eq!(experience_for(0), 0);
+	assert_eq!(experience_for(1), 0);
+	assert_eq!(level(u64::MAX), 858_993_460);
+}
+
+#[test]
+fn default_rules_are_an_hour_to_hunger_and_two_to_tiredness() {
+	assert!(RULES.is_valid());
+	assert_eq!((RULES.hungry_after(), RULES.tired_after()), (600, 1200));
+}
+
+#[test]
+fn neglecting_one_pet_makes_the_household_jealous() {
+	// Fed together, or one fed before the other got hungry
+	assert_eq!(household_jealousy(&[Some(100), Some(100)], &RULES), 0);
+	assert_eq!(household_jealousy(&[Some(100), Some(700)], &RULES), 0);
+	// The first one was hungry as the second one was fed, then starving
+	assert_eq!(household_jealousy(&[Some(100), Some(1000)], &RULES), 500);
+	assert_eq!(household_jealousy(&[Some(100), Some(5000)], &RULES), FULL);
+	assert_eq!(household_jealousy(&[None, Some(5000)], &RULES), FULL);
+	// A lone pet has nobody to be jealous of
+	assert_eq!(household_jealousy(&[Some(100)], &RULES), 0);
+	assert_eq!(household_jealousy(&[], &RULES), 0);
+	assert_eq!(experience_gain(100, 0), 100);
+	assert_eq!(experience_gain(100, 500), 75);
+	assert_eq!(experience_gain(100, FULL), 50);
+}
+
+#[test]
+fn stats_blocks_are_out_of_full_power() {
+	assert_eq!(StatsBlock::default(), StatsBlock::NEUTRAL);
+	assert_eq!(StatsBlock { power: FULL / 2 }.battle_weight(11), 5);
+	assert_eq!(StatsBlock { power: 2 * FULL }.battle_weight(11), 22);
+	assert_eq!(StatsBlock { power: 1 }.battle_weight(11), 1);
+	assert_eq!(StatsBlock { power: StatsBlock::MAX_POWER }.battle_weight(u64::MAX), u64::MAX);
+	assert!(!StatsBlock { power: 0 }.is_valid());
+	assert!(!StatsBlock { power: StatsBlock::MAX_POWER + 1 }.is_valid());
+}
+
+#[test]
+fn never_cared_for_pets_are_miserable() {
+	let stats = Care::default().stats(&RULES, 0);
+	assert_eq!((stats.fullness, stats.energy, stats.mood), (0, 0, Mood::Miserable));
+	assert_eq!(Care::default().battle_weight(&RULES, 0), 1);
+}
+
+#[test]
+fn care_times_ahead_of_the_client_count_as_just_done() {
+	let stats = care(Some(10), Some(10), 0).stats(&RULES, at_block(5));
+	assert_eq!((stats.fullness, stats.energy, stats.mood), (FULL, FULL, Mood::Happy));
+}
+
+// A pet's life as told by its `StatsChanged` events, with the stats expected at some blocks.
+// Each step is the block, then the care times and experience from the last event before it.
+struct Step {
+	block: BlockNumber,
+	care: Care,
+	fullness: u16,
+	energy: u16,
+	level: u32,
+	mood: Mood,
+	weight: u64,
+}
+
+fn replay(steps: &[Step]) {
+	for step in steps {
+		let now = at_block(step.block);
+		let stats = step.care.stats(&RULES, now);
+		assert_eq!(
+			(stats.fullness, stats.energy, stats.level, stats.mood),
+			(step.fullness, step.energy, step.level, step.mood),
+			"stats at block {}",
+			step.block,
+		);
+		let weight = step.care.battle_weight(&RULES, now);
+		assert_eq!(weight, step.weight, "weight at block {}", step.block);
+	}
+}
+
+#[test]
+fn minted_pet_gets_hungry_and_recovers() {
+	// Minted at block 1024 (a new pet counts as fed), sleeps at 1400, wins a battle at 1700
+	// (10 experience in the runtime) and is fed at 1800
+	let minted = care(Some(1024), None, 0);
+	let slept = care(Some(1024), Some(1400), 0);
+	let won = care(Some(1024), Some(1400), 10);
+	let fed = care(Some(1800), Some(1400), 10);
+	#[rustfmt::skip]
+	replay(&[
+		Step { block: 1024, care: minted, fullness: 1000, energy: 0, level: 1, mood: Mood::Tired, weight: 1 },
+		Step { block: 1324, care: minted, fullness: 750, energy: 0, level: 1, mood: Mood::Tired, weight: 1 },
+		Step { block: 1400, care: slept, fullness: 686, energy: 1000, level: 1, mood: Mood::Happy, weight: 1 },
+		Step { block: 1624, care: slept, fullness: 500, energy: 906, level: 1, mood: Mood::Happy, weight: 1 },
+		Step { block: 1625, care: slept, fullness: 499, energy: 906, level: 1, mood: Mood::Hungry, weight: 1 },
+		Step { block: 1700, care: won, fullness: 436, energy: 875, level: 1, mood: Mood::Hungry, weight: 5 },
+		Step { block: 1800, care: fed, fullness: 1000, energy: 833, level: 1, mood: Mood::Happy, weight: 11 },
+		Step { block: 2600, care: fed, fullness: 333, energy: 500, level: 1, mood: Mood::Hungry, weight: 5 },
+		Step { block: 2601, care: fed, fullness: 332, energy: 499, level: 1, mood: Mood::Miserable, weight: 2 },
+	]);
+}
+
+#[test]
+fn veteran_pet_left_alone_for_years() {
+	// Thousands of won battles and mini-game scores, then nobody plays until the block number
+	// runs out
+	let veteran = care(Some(100), Some(100), 2_500);
+	#[rustfmt::skip]
+	replay(&[
+		Step { block: 100, care: veteran, fullness: 1000, energy: 1000, level: 11, mood: Mood::Happy, weight: 2_501 },
+		Step { block: 1300, care: veteran, fullness: 0, energy: 500, level: 11, mood: Mood::Hungry, weight: 1_250 },
+		Step { block: BlockNumber::MAX, care: veteran, fullness: 0, energy: 0, level: 11, mood: Mood::Miserable, weight: 625 },
+	]);
+}
+
+#[test]
+fn names_are_checked_in_bytes() {
+	assert_eq!(check_name("Shelly".as_bytes(), 6), Ok("Shelly"));
+	assert_eq!(check_name("Shelly".as_bytes(), 5), Err(NameError::TooLong));
+	// 3 characters, but 4 bytes each
+	assert_eq!(check_name("🐢🐇🐍".as_bytes(), 12), Ok("🐢🐇🐍"));
+	assert_eq!(check_name("🐢🐇🐍".as_bytes(), 11), Err(NameError::TooLong));
+	assert_eq!(check_name(&"🐢".as_bytes()[..2], 30), Err(NameError::NotUtf8));
+	assert_eq!(check_name(&[b'a', 0xc0, b'b'], 30), Err(NameError::NotUtf8));
+}
@@ -0,0 +1,68 @@
+//! Visual traits of a pet, derived from its DNA.
+//!
+//! The pallet stores the DNA and the client draws the traits. Both go through this crate, so
+//! what the client shows is exactly what the chain produced. Everything here is plain integer
+//! math, with no floats or platform dependent behaviour.
+
+#![no_std]
+
+/// Number of bytes in a pet's DNA.
+pub const DNA_LENGTH: usize = 16;
+
+pub type Dna = [u8; DNA_LENGTH];
+
+/// Pattern drawn over the pet sprite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+	Plain,
+	Spots,
+	Stripes,
+}
+
+/// What a pet looks like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Traits {
+	/// Tint of the sprite as RGB, every channel is at least 128 so the sprite stays readable.
+	pub tint: [u8; 3],
+	pub pattern: Pattern,
+	/// Size relative to the species sprite, in percent from 80 to 120.
+	pub size: u8,
+}
+
+/// The traits encoded in `dna`. Bytes 0 to 4 are used, the others are kept for future
+/// traits.
+pub fn traits(dna: &Dna) -> Traits {
+	let pattern = match dna[3] % 4 {
+		0 | 1 => Pattern::Plain,
+		2 => Pattern::Spots,
+		_ => Pattern::Stripes,
+	};
+	Traits {
+		tint: [128 | (dna[0] >> 1), 128 | (dna[1] >> 1), 128 | (dna[2] >> 1)],
+		pattern,
+		size: 80 + dna[4] % 41,
+	}
+}
+
+/// DNA of a new pet, taken from the first bytes of `seed` (zero padded when shorter).
+pub fn dna_from_seed(seed: &[u8]) -> Dna {
+	let mut dna = [0; DNA_LENGTH];
+	for (gene, byte) in dna.iter_mut().zip(seed) {
+		*gene = *byte;
+	}
+	dna
+}
+
+/// DNA of the child of two pets. Each byte comes from one of the parents, picked by the
+/// lowest bit of the matching `seed` byte, and mutates when that seed byte is 250 or more
+/// (about 2% of the bytes).
+pub fn inherit(mother: &Dna, father: &Dna, seed: &Dna) -> Dna {
+	let mut child = [0; DNA_LENGTH];
+	for (i, gene) in child.iter_mut().enumerate() {
+		*gene = if seed[i] & 1 == 0 { mother[i] } else { father[i] };
+		if seed[i] >= 250 {
+			*gene ^= mother[i] ^ father[i] ^ seed[i];
+		}
+	}
+	child
+}
@@ -6,6 +6,17 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
+use frame_support::{
+	dispatch::{DispatchError, DispatchInfo, DispatchResult, PostDispatchInfo},
+	traits::{
+		schedule::{
+			v3::{Named as ScheduleNamed, TaskName},
+			DispatchTime, LOWEST_PRIORITY,
+		},
+		EqualPrivilegeOnly, StorePreimage,
+	},
+};
 use pallet_grandpa::AuthorityId as GrandpaId;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -13,7 +24,8 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
@@ -27,7 +39,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ConstU128, ConstU32, ConstU64, ConstU8, Currency, KeyOwnerProofSystem, OnUnbalanced,
+		Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -35,10 +48,14 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::EnsureRoot;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_items::Item as PetItem;
+pub use pallet_pet::Species as PetSpecies;
+use pallet_quests::{Objective, Quest, Reward};
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
 #[cfg(any(feature = "std", test))]
@@ -100,10 +117,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 107,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -217,6 +234,8 @@ impl pallet_grandpa::Config for Runtime {
 	type EquivocationReportSystem = ();
 }
 
+impl pallet_insecure_randomness_collective_flip::Config for Runtime {}
+
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
@@ -260,9 +279,532 @@ impl pallet_sudo::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+/// Batches let a player care for all the pets of their derivative accounts at once.
+impl pallet_utility::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// Scheduled care takes at most half of the block, like the calls it replaces would
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(50) * BlockWeights::get().max_block;
+	pub const PreimageBaseDeposit: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const PreimageByteDeposit: Balance = EXISTENTIAL_DEPOSIT / 100;
+}
+
+/// Runs the care players schedule for their pets, see `PetCareScheduler`.
+impl pallet_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type Preimages = Preimage;
+}
+
+/// Holds the calls too large to be scheduled inline, care calls never are.
+impl pallet_preimage::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type Currency = Balances;
+	type ManagerOrigin = EnsureRoot<AccountId>;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub const IdentityBasicDeposit: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const IdentityFieldDeposit: Balance = 2 * EXISTENTIAL_DEPOSIT;
+}
+
+/// Display names players give their accounts, shown by the game instead of their addresses.
+impl pallet_identity::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type BasicDeposit = IdentityBasicDeposit;
+	type FieldDeposit = IdentityFieldDeposit;
+	type SubAccountDeposit = IdentityBasicDeposit;
+	type MaxSubAccounts = ConstU32<10>;
+	type MaxAdditionalFields = ConstU32<10>;
+	type MaxRegistrars = ConstU32<5>;
+	type Slashed = ToPetTreasury;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type RegistrarOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const PetTreasuryPalletId: PalletId = PalletId(*b"py/pettr");
+	pub const PetInsurancePalletId: PalletId = PalletId(*b"py/petin");
+	pub const TournamentPalletId: PalletId = PalletId(*b"py/tourn");
+	pub const DefaultMarketFee: Permill = Permill::from_percent(2);
+	pub const DefaultMaxRoyalty: Permill = Permill::from_percent(10);
+	pub const ItemPrice: Balance = 1_000_000_000_000;
+	pub const AdoptionFee: Balance = 2_000_000_000_000;
+	pub const RenameFee: Balance = 500_000_000_000;
+	pub const MessageFee: Balance = 10_000_000_000;
+	pub const OfferDeposit: Balance = 1_000_000_000;
+	pub const InsurancePremium: Balance = ItemPrice::get() / 10;
+	pub const InsurancePayout: Balance = 5 * ItemPrice::get();
+	pub const MinCoverRatio: Permill = Permill::from_percent(20);
+	pub const StakingRewardPerLevel: Balance = ItemPrice::get() / 100;
+	pub const StakingRewardItem: PetItem = PetItem::Apple;
+	pub const RunnerUpShare: Permill = Permill::from_percent(30);
+	// A co-owned pet only changes hands when everyone sharing it agrees
+	pub const CoOwnerConsent: pallet_pet::CoOwnerConsent = pallet_pet::CoOwnerConsent::All;
+	// Hungry after an hour without food and tired after two hours without sleep
+	pub const DefaultCareRules: pallet_pet::CareParameters = pallet_pet::CareParameters {
+		fullness_lasts: 2 * HOURS,
+		energy_lasts: 4 * HOURS,
+		hungry_below: 500,
+		tired_below: 500,
+	};
+	// Deferred pet tasks take at most an eighth of the block time
+	pub MaxIdleWeight: Weight = Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 4, u64::MAX);
+	// A week, a month and a hundred days of care in a row, the last one earns a badge
+	pub StreakRewards: Vec<(u32, PetItem, u32)> = vec![
+		(7, PetItem::Fish, 3),
+		(30, PetItem::Scarf, 1),
+		(100, PetItem::CaretakerBadge, 1),
+	];
+}
+
+/// Sends the pet marketplace fee to the pet treasury pot account.
+pub struct ToPetTreasury;
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for ToPetTreasury {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+		let pot: AccountId = PetTreasuryPalletId::get().into_account_truncating();
+		Balances::resolve_creating(&pot, amount);
+	}
+}
+
 impl pallet_pet::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type StringLimit = ConstU32<30>;
+	type WeightInfo = pallet_pet::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = PetInsurance;
+	type Currency = Balances;
+	type OnMarketFee = ToPetTreasury;
+	type DefaultMarketFee = DefaultMarketFee;
+	type DefaultMaxRoyalty = DefaultMaxRoyalty;
+	type MarketFeeOrigin = EnsureRoot<AccountId>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type GameAdminOrigin = EnsureRoot<AccountId>;
+	type MaxScore = ConstU32<1000>;
+	type MaxGameKeys = ConstU32<4>;
+	type BattleExperience = ConstU32<10>;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type BlocksPerDay = ConstU32<DAYS>;
+	type OnMint = PetReferrals;
+	type AbandonAfter = ConstU32<{ 30 * DAYS }>;
+	type ReclaimWindow = ConstU32<{ 7 * DAYS }>;
+	type IncubationPeriod = ConstU32<{ 2 * HOURS }>;
+	type IncubationBoost = ConstU32<{ 30 * MINUTES }>;
+	type AdoptionFee = AdoptionFee;
+	type RenameFee = RenameFee;
+	// Rename fees are burned
+	type OnRenameFee = ();
+	type OfferDuration = ConstU32<{ 3 * DAYS }>;
+	type MaxOffersPerPet = ConstU32<20>;
+	type MaxOffersPerBlock = ConstU32<50>;
+	type OfferDeposit = OfferDeposit;
+	type BundleItem = PetItem;
+	type Items = PetBundleItems;
+	type MaxBundleItems = ConstU32<8>;
+	type Locks = LockedPets;
+	type OnAbandon = PetInsurance;
+	type DefaultCareRules = DefaultCareRules;
+	type MaxIdleWeight = MaxIdleWeight;
+	type MessageFee = MessageFee;
+	// Message fees are burned, like rename fees
+	type OnMessageFee = ();
+	type MaxMessageLen = ConstU32<280>;
+	type MessageEra = ConstU32<DAYS>;
+	type MaxMessagesPerEra = ConstU32<50>;
+	type MaxMemorialBadges = ConstU32<32>;
+	type CareScheduler = PetCareScheduler;
+	type CareSchedulerOrigin = EnsureRoot<AccountId>;
+	type MinCareInterval = ConstU32<HOURS>;
+	type UnixTime = Timestamp;
+	type StreakRewards = StreakRewards;
+	// Fine for a development chain, see the trade-off on the pallet's `Randomness`. Running
+	// BABE, production would use `pallet_babe::RandomnessFromOneEpochAgo<Runtime>` instead.
+	type Randomness = RandomnessCollectiveFlip;
+	type Household = DerivativeHousehold;
+	type MaxCoOwners = ConstU32<3>;
+	type CoOwnerConsent = CoOwnerConsent;
+}
+
+/// Schedules the care players ask for with the scheduler, dispatched as root, which players
+/// can't sign. Each account has one named task per care action, so scheduling it again finds
+/// the old one.
+pub struct PetCareScheduler;
+impl PetCareScheduler {
+	fn task(owner: &AccountId, action: pallet_pet::CareAction) -> TaskName {
+		(b"pet/care", owner, action).using_encoded(sp_core::hashing::blake2_256)
+	}
+}
+impl pallet_pet::CareScheduler<AccountId, BlockNumber> for PetCareScheduler {
+	fn schedule(
+		owner: &AccountId,
+		action: pallet_pet::CareAction,
+		when: BlockNumber,
+		every: Option<BlockNumber>,
+	) -> DispatchResult {
+		let call = RuntimeCall::PetModule(pallet_pet::Call::run_scheduled_care {
+			owner: owner.clone(),
+			action,
+		});
+		// The trait, the scheduler has calls of the same names
+		<Scheduler as ScheduleNamed<_, _, _>>::schedule_named(
+			Self::task(owner, action),
+			DispatchTime::At(when),
+			every.map(|every| (every, u32::MAX)),
+			LOWEST_PRIORITY,
+			frame_system::RawOrigin::Root.into(),
+			Preimage::bound(call)?,
+		)?;
+		Ok(())
+	}
+
+	fn cancel(owner: &AccountId, action: pallet_pet::CareAction) -> DispatchResult {
+		<Scheduler as ScheduleNamed<_, _, _>>::cancel_named(Self::task(owner, action))
+	}
+}
+
+/// A household is the account of a player and the derivative accounts they batch care for,
+/// the first slots the game looks for pets in.
+pub struct DerivativeHousehold;
+impl DerivativeHousehold {
+	const SLOTS: u16 = 8;
+}
+impl pallet_pet::HouseholdMembers<AccountId> for DerivativeHousehold {
+	fn members(head: &AccountId) -> Vec<AccountId> {
+		(0..Self::SLOTS)
+			.map(|index| Utility::derivative_account_id(head.clone(), index))
+			.collect()
+	}
+}
+
+/// Lets pets be sold together with items of the seller's inventory.
+pub struct PetBundleItems;
+impl pallet_pet::BundleItems<AccountId, PetItem> for PetBundleItems {
+	fn count(who: &AccountId, item: PetItem) -> u32 {
+		// Badges stay with the account, there are never any to sell
+		if item.is_soulbound() {
+			return 0
+		}
+		pallet_items::Inventory::<Runtime>::get(who, item)
+	}
+
+	fn transfer(from: &AccountId, to: &AccountId, item: PetItem, amount: u32) -> DispatchResult {
+		ItemsModule::transfer(from, to, item, amount)
+	}
+
+	fn equipment(who: &AccountId) -> Vec<(PetItem, u32)> {
+		ItemsModule::held(who, pallet_items::ItemCategory::Accessory)
+	}
+
+	fn achievements(who: &AccountId) -> Vec<(PetItem, u32)> {
+		ItemsModule::badges(who)
+	}
+
+	fn give(who: &AccountId, item: PetItem, amount: u32) {
+		ItemsModule::give(who, item, amount);
+	}
+}
+
+/// Applies items from the inventory to the owner's pet.
+pub struct PetItemUser;
+impl pallet_items::UseItem<AccountId> for PetItemUser {
+	fn use_item(who: &AccountId, item: PetItem) -> DispatchResult {
+		PetModule::ensure_running()?;
+		match item.category() {
+			pallet_items::ItemCategory::Food => PetModule::feed_pet(who).map(|_| ()),
+			pallet_items::ItemCategory::Incubator => PetModule::warm_egg(who),
+			_ => {
+				frame_support::ensure!(
+					pallet_pet::PetsInfo::<Runtime>::contains_key(who),
+					pallet_pet::Error::<Runtime>::AccountHasNoPet
+				);
+				Ok(())
+			},
+		}
+	}
+}
+
+impl pallet_items::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ItemPrice = ItemPrice;
+	type OnItemSale = ToPetTreasury;
+	type ItemUser = PetItemUser;
+}
+
+parameter_types! {
+	pub QuestPool: Vec<Quest<PetItem, Balance>> = vec![
+		Quest { objective: Objective::Feed(3), reward: Reward::Item(PetItem::Apple) },
+		Quest { objective: Objective::WinBattles(1), reward: Reward::Tokens(5 * ItemPrice::get()) },
+		Quest { objective: Objective::Sleep(2), reward: Reward::Item(PetItem::Ball) },
+		Quest { objective: Objective::Feed(5), reward: Reward::Item(PetItem::Fish) },
+		Quest { objective: Objective::WinBattles(3), reward: Reward::Item(PetItem::Hat) },
+		Quest { objective: Objective::Sleep(1), reward: Reward::Tokens(ItemPrice::get()) },
+		// Achievements, rewarded with a badge that stays with the account
+		Quest { objective: Objective::Feed(10), reward: Reward::Item(PetItem::CaretakerBadge) },
+		Quest { objective: Objective::WinBattles(5), reward: Reward::Item(PetItem::ChampionBadge) },
+		Quest { objective: Objective::Sleep(5), reward: Reward::Item(PetItem::DreamerBadge) },
+	];
+}
+
+/// Lets quests read the daily activity of pets and reward items to the inventory.
+pub struct PetQuests;
+impl pallet_quests::PetActivity<AccountId> for PetQuests {
+	fn current_day() -> u32 {
+		PetModule::current_day()
+	}
+
+	fn today(who: &AccountId) -> Option<(u32, pallet_quests::Progress)> {
+		let (id, _) = pallet_pet::PetsInfo::<Runtime>::get(who)?;
+		let activity = PetModule::activity_today(id);
+		let progress = pallet_quests::Progress {
+			feeds: activity.feeds,
+			sleeps: activity.sleeps,
+			battles_won: activity.battles_won,
+		};
+		Some((id, progress))
+	}
+}
+impl pallet_quests::GiveItem<AccountId, PetItem> for PetQuests {
+	fn give_item(who: &AccountId, item: PetItem) {
+		ItemsModule::give(who, item, 1);
+	}
+}
+
+impl pallet_quests::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Item = PetItem;
+	type Pets = PetQuests;
+	type Items = PetQuests;
+	type QuestPool = QuestPool;
+	type QuestsPerDay = ConstU32<3>;
+}
+
+parameter_types! {
+	pub StarterItems: Vec<(PetItem, u32)> = vec![(PetItem::Apple, 3), (PetItem::Fish, 2)];
+}
+
+/// Lets referrals check pet ownership, and pays the referral bonus when a newcomer mints.
+pub struct PetReferrals;
+impl pallet_referrals::PetOwnership<AccountId> for PetReferrals {
+	fn has_pet(who: &AccountId) -> bool {
+		pallet_pet::PetsInfo::<Runtime>::contains_key(who)
+	}
+}
+impl pallet_referrals::GiveItem<AccountId, PetItem> for PetReferrals {
+	fn give_item(who: &AccountId, item: PetItem, amount: u32) {
+		ItemsModule::give(who, item, amount);
+	}
+}
+impl pallet_pet::OnPetMinted<AccountId> for PetReferrals {
+	fn on_pet_minted(owner: &AccountId, _pet_id: u32) {
+		ReferralsModule::reward_mint(owner);
+	}
+}
+
+impl pallet_referrals::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Item = PetItem;
+	type Pets = PetReferrals;
+	type Items = PetReferrals;
+	type StarterItems = StarterItems;
+	type EraLength = ConstU32<{ 7 * DAYS }>;
+	type MaxReferralsPerEra = ConstU32<5>;
+}
+
+/// Lets renters feed the pets they rent, put them to sleep and battle with them.
+pub struct PetRentals;
+impl pallet_rental::PetRights<AccountId> for PetRentals {
+	fn pet_of(owner: &AccountId) -> Option<u32> {
+		pallet_pet::PetsInfo::<Runtime>::get(owner).map(|(id, _)| id)
+	}
+
+	fn owner_of(pet_id: u32) -> Option<AccountId> {
+		pallet_pet::PetOwners::<Runtime>::get(pet_id)
+	}
+
+	fn feed(owner: &AccountId) -> DispatchResult {
+		PetModule::ensure_running()?;
+		PetModule::feed_pet(owner).map(|_| ())
+	}
+
+	fn sleep(owner: &AccountId) -> DispatchResult {
+		PetModule::ensure_running()?;
+		PetModule::sleep_pet(owner).map(|_| ())
+	}
+
+	fn challenge(owner: &AccountId, opponent: &AccountId) -> DispatchResult {
+		PetModule::ensure_running()?;
+		PetModule::battle(owner.clone(), opponent.clone()).map(|_| ())
+	}
+}
+
+impl pallet_rental::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = PetRentals;
+	type MaxRentalDuration = ConstU32<{ 30 * DAYS }>;
+	type MaxRentalsPerBlock = ConstU32<50>;
+}
+
+/// Lets the insurance find and revive pets, and files a claim when an insured pet is abandoned.
+/// Insures the pets of the pet benchmarks.
+pub struct PetInsurance;
+impl pallet_insurance::InsuredPets<AccountId, Balance> for PetInsurance {
+	fn pet_of(owner: &AccountId) -> Option<u32> {
+		pallet_pet::PetsInfo::<Runtime>::get(owner).map(|(id, _)| id)
+	}
+
+	fn revival_cost() -> Balance {
+		AdoptionFee::get()
+	}
+
+	fn revive(payer: &AccountId, pet_id: u32) -> DispatchResult {
+		PetModule::revive(payer, pet_id)
+	}
+}
+impl pallet_pet::OnPetAbandoned<AccountId> for PetInsurance {
+	fn on_pet_abandoned(owner: &AccountId, pet_id: u32) {
+		InsuranceModule::claim(owner, pet_id);
+	}
+}
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_pet::BenchmarkHelper<AccountId> for PetInsurance {
+	/// Insure the pet for a revival the pool can pay, the claim then gives it back.
+	fn worst_case_abandon(owner: &AccountId, pet_id: u32) {
+		let pool = InsuranceModule::pool_account();
+		let _ = Balances::deposit_creating(&pool, 2 * AdoptionFee::get());
+		let policy = pallet_insurance::Policy { pet_id, cover: pallet_insurance::Cover::Revival };
+		pallet_insurance::Policies::<Runtime>::insert(owner, policy);
+		pallet_insurance::Policyholders::<Runtime>::mutate(|holders| {
+			let _ = holders.try_push(owner.clone());
+		});
+	}
+}
+
+impl pallet_insurance::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = PetInsurance;
+	type PalletId = PetInsurancePalletId;
+	type Premium = InsurancePremium;
+	type Payout = InsurancePayout;
+	type EraLength = ConstU32<{ 7 * DAYS }>;
+	type MinCoverRatio = MinCoverRatio;
+	type MaxPolicies = ConstU32<1000>;
+}
+
+/// Finds the pets to stake and their level, and gives the reward items.
+pub struct PetStakes;
+impl pallet_pet_staking::StakedPets<AccountId, PetItem> for PetStakes {
+	fn owner_of(pet_id: u32) -> Option<AccountId> {
+		pallet_pet::PetOwners::<Runtime>::get(pet_id)
+	}
+
+	fn can_stake(pet_id: u32) -> bool {
+		!RentalModule::is_rented(pet_id) &&
+			!TournamentModule::is_entered(pet_id) &&
+			!pallet_pet::Eggs::<Runtime>::contains_key(pet_id)
+	}
+
+	fn level(pet_id: u32) -> u32 {
+		PetModule::level(pet_id)
+	}
+
+	fn give_item(who: &AccountId, item: PetItem, amount: u32) {
+		ItemsModule::give(who, item, amount);
+	}
+}
+
+impl pallet_pet_staking::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Item = PetItem;
+	type Pets = PetStakes;
+	type RewardPeriod = ConstU32<DAYS>;
+	type RewardPerLevel = StakingRewardPerLevel;
+	type RewardItem = StakingRewardItem;
+	type LevelsPerItem = ConstU32<5>;
+	type UnstakeCooldown = ConstU32<{ 2 * DAYS }>;
+	type MaxUnlocksPerBlock = ConstU32<50>;
+}
+
+/// Finds the pets entering tournaments, and fights their matches with the pet battles.
+pub struct TournamentPets;
+impl pallet_tournament::TournamentPets<AccountId> for TournamentPets {
+	fn pet_of(owner: &AccountId) -> Option<u32> {
+		pallet_pet::PetsInfo::<Runtime>::get(owner).map(|(id, _)| id)
+	}
+
+	fn can_enter(pet_id: u32) -> bool {
+		!StakingModule::is_staked(pet_id) && !pallet_pet::Eggs::<Runtime>::contains_key(pet_id)
+	}
+
+	fn battle(first: &AccountId, second: &AccountId) -> Result<u32, DispatchError> {
+		PetModule::ensure_running()?;
+		PetModule::battle(first.clone(), second.clone())
+	}
+}
+
+/// Fees of the calls recorded by the telemetry, as charged by the transaction payment.
+pub struct TransactionFees;
+impl pallet_game_telemetry::ActualFee<Balance> for TransactionFees {
+	fn actual_fee(len: u32, info: &DispatchInfo, post_info: &PostDispatchInfo) -> Balance {
+		TransactionPayment::compute_actual_fee(len, info, post_info, 0)
+	}
+}
+
+impl pallet_game_telemetry::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type Fees = TransactionFees;
+	type TelemetryOrigin = EnsureRoot<AccountId>;
+	type EraLength = ConstU32<{ 7 * DAYS }>;
+	type MaxTrackedCalls = ConstU32<200>;
+}
+
+impl pallet_tournament::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Pets = TournamentPets;
+	type PalletId = TournamentPalletId;
+	type TournamentOrigin = EnsureRoot<AccountId>;
+	type RunnerUpShare = RunnerUpShare;
+	type MaxEntrants = ConstU32<64>;
+	type MaxActiveTournaments = ConstU32<4>;
+}
+
+/// Keeps rented, staked and tournament pets from changing hands, and staked pets out of
+/// battles.
+pub struct LockedPets;
+impl pallet_pet::PetLocks for LockedPets {
+	fn is_locked(pet_id: u32) -> bool {
+		RentalModule::is_rented(pet_id) ||
+			StakingModule::is_staked(pet_id) ||
+			TournamentModule::is_entered(pet_id)
+	}
+
+	fn is_benched(pet_id: u32) -> bool {
+		StakingModule::is_staked(pet_id)
+	}
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -275,13 +817,26 @@ construct_runtime!(
 	{
 		System: frame_system,
 		Timestamp: pallet_timestamp,
+		RandomnessCollectiveFlip: pallet_insecure_randomness_collective_flip,
 		Aura: pallet_aura,
 		Grandpa: pallet_grandpa,
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
+		Utility: pallet_utility,
+		Identity: pallet_identity,
+		Scheduler: pallet_scheduler,
+		Preimage: pallet_preimage,
 		// Include the custom palllets
 		PetModule: pallet_pet,
+		ItemsModule: pallet_items,
+		QuestsModule: pallet_quests,
+		ReferralsModule: pallet_referrals,
+		RentalModule: pallet_rental,
+		InsuranceModule: pallet_insurance,
+		StakingModule: pallet_pet_staking,
+		TournamentModule: pallet_tournament,
+		TelemetryModule: pallet_game_telemetry,
 	}
 );
 
@@ -301,6 +856,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_game_telemetry::TrackCallUsage<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -308,6 +864,13 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run on the next runtime upgrade.
+pub type Migrations = (
+	pallet_pet::migrations::v1::MigrateToV1<Runtime>,
+	pallet_pet::migrations::v2::MigrateToV2<Runtime>,
+	pallet_pet::migrations::v3::MigrateToV3<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -315,6 +878,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -328,6 +892,7 @@ mod benches {
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
+		[pallet_pet, PetModule]
 	);
 }
 
@@ -399,6 +964,51 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_pet_runtime_api::PetApi<Block, pallet_pet::PetProfile<Runtime>, AccountId>
+		for Runtime
+	{
+		fn species_supply() -> Vec<pallet_pet::SpeciesSupply> {
+			PetModule::species_supply()
+		}
+
+		fn badges(pet_id: u32) -> Vec<(PetItem, u32)> {
+			pallet_pet::PetOwners::<Runtime>::get(pet_id)
+				.map(|owner| ItemsModule::badges(&owner))
+				.unwrap_or_default()
+		}
+
+		fn pet_profile(pet_id: u32) -> Option<pallet_pet::PetProfile<Runtime>> {
+			PetModule::pet_profile(pet_id)
+		}
+
+		fn block_moment(block: BlockNumber) -> Option<pallet_pet::Moment> {
+			PetModule::block_moment(block)
+		}
+
+		fn can_mint(
+			account: AccountId,
+			species: pallet_pet::Species,
+		) -> Result<(), pallet_pet::MintBlockReason> {
+			PetModule::can_mint(&account, &species)
+		}
+
+		fn can_own(account: AccountId) -> Result<(), pallet_pet::MintBlockReason> {
+			PetModule::can_own(&account)
+		}
+	}
+
+	impl pallet_tournament_runtime_api::TournamentApi<Block, pallet_tournament::BracketOf<Runtime>>
+		for Runtime
+	{
+		fn active_tournaments() -> Vec<u32> {
+			TournamentModule::active_tournaments()
+		}
+
+		fn bracket(tournament_id: u32) -> Option<pallet_tournament::BracketOf<Runtime>> {
+			TournamentModule::bracket(tournament_id)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			opaque::SessionKeys::generate(seed)
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use super::{
+    client::{AccountChanged, ChainClient},
+    despawn_screen,
+    toast::Toast,
+    GameState, TEXT_COLOR,
+};
+
+// This plugin adds an account dropdown to the menus, to play as another account of the
+// keystore without restarting the game
+pub struct AccountsPlugin;
+
+impl Plugin for AccountsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            account_switcher_setup.in_schedule(OnEnter(GameState::Menu)),
+            despawn_screen::<OnAccountSwitcher>.in_schedule(OnExit(GameState::Menu)),
+        ))
+        .add_systems((toggle_account_list, select_account).in_set(OnUpdate(GameState::Menu)))
+        .add_system(show_active_account);
+    }
+}
+
+// Tag component used to tag the dropdown entities
+#[derive(Component)]
+struct OnAccountSwitcher;
+
+// Button showing the active account, opens the list
+#[derive(Component)]
+struct AccountListToggle;
+
+#[derive(Component)]
+struct ActiveAccountText;
+
+#[derive(Component)]
+struct AccountList;
+
+#[derive(Component)]
+struct SelectAccount(usize);
+
+fn account_switcher_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    client: Res<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(140.0), Val::Px(30.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.0),
+                        top: Val::Px(10.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            OnAccountSwitcher,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    AccountListToggle,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("{} v", client.account().name),
+                            text_style.clone(),
+                        ),
+                        ActiveAccountText,
+                    ));
+                });
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            display: Display::None,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    AccountList,
+                ))
+                .with_children(|parent| {
+                    for (index, account) in client.accounts().iter().enumerate() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                                    ..default()
+                                },
+                                SelectAccount(index),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    account.name.clone(),
+                                    text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn toggle_account_list(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<AccountListToggle>)>,
+    mut list: Query<&mut Style, With<AccountList>>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            for mut style in &mut list {
+                style.display = match style.display {
+                    Display::None => Display::Flex,
+                    _ => Display::None,
+                };
+            }
+        }
+    }
+}
+
+fn select_account(
+    interaction_query: Query<(&Interaction, &SelectAccount), Changed<Interaction>>,
+    mut client: ResMut<ChainClient>,
+    mut list: Query<&mut Style, With<AccountList>>,
+    mut account_changed: EventWriter<AccountChanged>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (interaction, select) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        for mut style in &mut list {
+            style.display = Display::None;
+        }
+        if !client.switch_account(select.0) {
+            continue;
+        }
+        account_changed.send(AccountChanged);
+        toasts.send(Toast(format!("Playing as {}", client.account().name)));
+    }
+}
+
+// The account can also change from a gamepad, keep the dropdown in sync
+fn show_active_account(
+    mut events: EventReader<AccountChanged>,
+    client: Res<ChainClient>,
+    mut active_text: Query<&mut Text, With<ActiveAccountText>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    for mut text in &mut active_text {
+        text.sections[0].value = format!("{} v", client.account().name);
+    }
+}
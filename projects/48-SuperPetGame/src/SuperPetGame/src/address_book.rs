@@ -0,0 +1,496 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use subxt::utils::AccountId32;
+
+use super::{despawn_screen, settings::Palette, GameState, TEXT_COLOR};
+
+// Contacts shown in the hint under an address field
+const HINTS_SHOWN: usize = 3;
+
+// This plugin keeps the player's contacts, labels for the addresses they play with, saved
+// with the settings. They are added, edited and deleted on the address book screen, and
+// complete the fields marked `AddressField`: typing the start of a label or address and
+// pressing Tab fills in the address.
+pub struct AddressBookPlugin;
+
+impl Plugin for AddressBookPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContactForm>()
+            .add_event::<FillAddress>()
+            .add_systems((type_address, show_hints).chain())
+            .add_systems((
+                address_book_setup.in_schedule(OnEnter(GameState::AddressBook)),
+                despawn_screen::<OnAddressBookScreen>.in_schedule(OnExit(GameState::AddressBook)),
+            ))
+            .add_systems(
+                (
+                    type_in_form,
+                    show_form.after(type_in_form),
+                    show_contacts,
+                    book_action,
+                    button_colors,
+                )
+                    .in_set(OnUpdate(GameState::AddressBook)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Contact {
+    pub label: String,
+    // SS58 address
+    pub address: String,
+}
+
+// Contacts sorted by label, loaded and saved by the settings plugin
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct AddressBook(pub Vec<Contact>);
+
+impl AddressBook {
+    // Contacts whose label or address starts with what was typed
+    pub fn matches<'a>(&'a self, typed: &str) -> impl Iterator<Item = &'a Contact> + 'a {
+        let (typed, typed_label) = (typed.to_string(), typed.to_lowercase());
+        self.0.iter().filter(move |contact| {
+            !typed.is_empty()
+                && (contact.label.to_lowercase().starts_with(&typed_label)
+                    || contact.address.starts_with(&typed))
+        })
+    }
+
+    // The address of the contact labelled `typed`, else what was typed
+    pub fn resolve(&self, typed: &str) -> String {
+        self.0
+            .iter()
+            .find(|contact| contact.label.eq_ignore_ascii_case(typed))
+            .map_or(typed.to_string(), |contact| contact.address.clone())
+    }
+}
+
+// Text typed as an address, completed from the address book with Tab. Only the visible
+// fields are typed in.
+#[derive(Component)]
+pub struct AddressField;
+
+// Address to put in the visible address field, e.g. read from a QR code
+pub struct FillAddress(pub String);
+
+// Text listing the contacts matching the visible address field
+#[derive(Component)]
+pub struct ContactHint;
+
+fn short(address: &str) -> String {
+    format!("{}…", address.get(..8).unwrap_or(address))
+}
+
+// Type in the visible address field, complete it with the first matching contact, or fill it
+// with an address from elsewhere
+fn type_address(
+    mut events: EventReader<ReceivedCharacter>,
+    mut fills: EventReader<FillAddress>,
+    kbd: Res<Input<KeyCode>>,
+    book: Res<AddressBook>,
+    mut fields: Query<(&mut Text, &ComputedVisibility), With<AddressField>>,
+) {
+    let typed: Vec<char> = events.iter().map(|event| event.char).collect();
+    let filled = fills.iter().last().map(|fill| fill.0.clone());
+    for (mut text, visibility) in &mut fields {
+        if !visibility.is_visible() {
+            continue;
+        }
+        let value = &mut text.sections[0].value;
+        if let Some(address) = &filled {
+            *value = address.clone();
+            continue;
+        }
+        if kbd.just_pressed(KeyCode::Back) {
+            value.pop();
+        }
+        if kbd.just_pressed(KeyCode::Tab) {
+            if let Some(contact) = book.matches(value).next() {
+                *value = contact.address.clone();
+            }
+        }
+        value.extend(typed.iter().filter(|c| c.is_ascii_alphanumeric()));
+    }
+}
+
+fn show_hints(
+    book: Res<AddressBook>,
+    fields: Query<(&Text, &ComputedVisibility), (With<AddressField>, Changed<Text>)>,
+    mut hints: Query<&mut Text, (With<ContactHint>, Without<AddressField>)>,
+) {
+    let Some((field, _)) = fields
+        .iter()
+        .find(|(_, visibility)| visibility.is_visible())
+    else {
+        return;
+    };
+    let matches: Vec<String> = book
+        .matches(&field.sections[0].value)
+        .take(HINTS_SHOWN)
+        .map(|contact| format!("{} ({})", contact.label, short(&contact.address)))
+        .collect();
+    let hint = if matches.is_empty() {
+        String::new()
+    } else {
+        format!("Tab: {}", matches.join(", "))
+    };
+    for mut text in &mut hints {
+        if text.sections[0].value != hint {
+            text.sections[0].value = hint.clone();
+        }
+    }
+}
+
+// Tag component used to tag entities added on the address book screen
+#[derive(Component)]
+struct OnAddressBookScreen;
+
+// Column the contacts are listed in
+#[derive(Component)]
+struct ContactList;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default)]
+enum FormField {
+    #[default]
+    Label,
+    Address,
+}
+
+#[derive(Component)]
+struct FormStatus;
+
+// Contact being added, or edited when `editing` is its index
+#[derive(Resource, Default)]
+struct ContactForm {
+    label: String,
+    address: String,
+    focus: FormField,
+    editing: Option<usize>,
+    status: String,
+}
+
+#[derive(Component)]
+enum BookAction {
+    Focus(FormField),
+    Edit(usize),
+    Delete(usize),
+    Save,
+    Clear,
+    Back,
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    width: f32,
+    text_style: &TextStyle,
+    bundle: impl Bundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(width), Val::Px(40.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..default()
+            },
+            bundle,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn address_book_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut form: ResMut<ContactForm>,
+) {
+    *form = ContactForm::default();
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+    let row_style = Style {
+        align_items: AlignItems::Center,
+        gap: Size::all(Val::Px(10.0)),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnAddressBookScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Address book", text_style.clone()));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        gap: Size::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ContactList,
+            ));
+            for (field, name, width) in [
+                (FormField::Label, "Label", 240.0),
+                (FormField::Address, "Address", 560.0),
+            ] {
+                parent
+                    .spawn(NodeBundle {
+                        style: row_style.clone(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(name, text_style.clone()));
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(width), Val::Px(40.0)),
+                                        align_items: AlignItems::Center,
+                                        padding: UiRect::horizontal(Val::Px(8.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..default()
+                                },
+                                BookAction::Focus(field),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(String::new(), text_style.clone()),
+                                    field,
+                                ));
+                            });
+                    });
+            }
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                FormStatus,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, "Save", 120.0, &text_style, BookAction::Save);
+                    spawn_button(parent, "Clear", 120.0, &text_style, BookAction::Clear);
+                    spawn_button(parent, "Back", 120.0, &text_style, BookAction::Back);
+                });
+        });
+}
+
+// Type in the focused field of the form, Tab moves to the other one
+fn type_in_form(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    mut form: ResMut<ContactForm>,
+) {
+    let typed: Vec<char> = events.iter().map(|event| event.char).collect();
+    if kbd.just_pressed(KeyCode::Tab) {
+        form.focus = match form.focus {
+            FormField::Label => FormField::Address,
+            FormField::Address => FormField::Label,
+        };
+        return;
+    }
+    let focus = form.focus;
+    let value = match focus {
+        FormField::Label => &mut form.label,
+        FormField::Address => &mut form.address,
+    };
+    if kbd.just_pressed(KeyCode::Back) {
+        value.pop();
+    }
+    for c in typed {
+        match focus {
+            FormField::Label if !c.is_control() => value.push(c),
+            FormField::Address if c.is_ascii_alphanumeric() => value.push(c),
+            _ => {}
+        }
+    }
+}
+
+fn show_form(
+    form: Res<ContactForm>,
+    mut texts: Query<(&mut Text, Option<&FormField>), Or<(With<FormField>, With<FormStatus>)>>,
+) {
+    if !form.is_changed() {
+        return;
+    }
+    for (mut text, field) in &mut texts {
+        text.sections[0].value = match field {
+            Some(field) => {
+                let value = match field {
+                    FormField::Label => &form.label,
+                    FormField::Address => &form.address,
+                };
+                let cursor = if *field == form.focus { "|" } else { "" };
+                format!("{value}{cursor}")
+            }
+            None => form.status.clone(),
+        };
+    }
+}
+
+// List the contacts, again whenever the book changes
+fn show_contacts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    book: Res<AddressBook>,
+    list: Query<(Entity, Option<&Children>), With<ContactList>>,
+) {
+    let Ok((list, children)) = list.get_single() else {
+        return;
+    };
+    if !book.is_changed() && children.is_some() {
+        return;
+    }
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+    commands
+        .entity(list)
+        .despawn_descendants()
+        .with_children(|parent| {
+            if book.0.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No contacts yet",
+                    text_style.clone(),
+                ));
+            }
+            for (index, contact) in book.0.iter().enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{} {}", contact.label, short(&contact.address)),
+                            text_style.clone(),
+                        ));
+                        spawn_button(parent, "Edit", 80.0, &text_style, BookAction::Edit(index));
+                        spawn_button(
+                            parent,
+                            "Delete",
+                            80.0,
+                            &text_style,
+                            BookAction::Delete(index),
+                        );
+                    });
+            }
+        });
+}
+
+fn book_action(
+    interaction_query: Query<(&Interaction, &BookAction), (Changed<Interaction>, With<Button>)>,
+    mut book: ResMut<AddressBook>,
+    mut form: ResMut<ContactForm>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            BookAction::Focus(field) => form.focus = *field,
+            BookAction::Edit(index) => {
+                let Some(contact) = book.0.get(*index) else {
+                    continue;
+                };
+                *form = ContactForm {
+                    label: contact.label.clone(),
+                    address: contact.address.clone(),
+                    editing: Some(*index),
+                    ..default()
+                };
+            }
+            BookAction::Delete(index) => {
+                if *index < book.0.len() {
+                    book.0.remove(*index);
+                    *form = ContactForm::default();
+                }
+            }
+            BookAction::Save => match save_contact(&mut book, &form) {
+                Ok(()) => *form = ContactForm::default(),
+                Err(e) => form.status = e,
+            },
+            BookAction::Clear => *form = ContactForm::default(),
+            BookAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// Add the contact of the form, or replace the one edited
+fn save_contact(book: &mut AddressBook, form: &ContactForm) -> Result<(), String> {
+    let label = form.label.trim();
+    if label.is_empty() {
+        return Err("Give the contact a label".to_string());
+    }
+    let address = form
+        .address
+        .parse::<AccountId32>()
+        .map_err(|_| "Invalid address".to_string())?
+        .to_string();
+    let taken = book.0.iter().enumerate().any(|(index, contact)| {
+        Some(index) != form.editing && contact.label.eq_ignore_ascii_case(label)
+    });
+    if taken {
+        return Err(format!("There's already a contact labelled {label}"));
+    }
+    let contact = Contact {
+        label: label.to_string(),
+        address,
+    };
+    match form.editing.and_then(|index| book.0.get_mut(index)) {
+        Some(edited) => *edited = contact,
+        None => book.0.push(contact),
+    }
+    book.0
+        .sort_by(|a, b| a.label.to_lowercase().cmp(&b.label.to_lowercase()));
+    Ok(())
+}
+
+// Color the buttons, the field typed in shown selected
+fn button_colors(
+    mut buttons: Query<(Ref<Interaction>, &mut BackgroundColor, &BookAction)>,
+    form: Res<ContactForm>,
+    palette: Res<Palette>,
+) {
+    for (interaction, mut color, action) in &mut buttons {
+        if !interaction.is_changed() && !form.is_changed() {
+            continue;
+        }
+        let selected = matches!(action, BookAction::Focus(field) if *field == form.focus);
+        *color = palette.button(*interaction, selected).into();
+    }
+}
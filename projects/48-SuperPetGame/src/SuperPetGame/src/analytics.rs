@@ -0,0 +1,410 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sp_core::hashing::blake2_128;
+use subxt::ext::codec::{Decode, Encode};
+use subxt::storage::Storage;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{pet_storage_prefix, polkadot, ChainClient, PetId, PetVitals},
+    despawn_screen,
+    indexer::IndexDb,
+    settings::Palette,
+    GameState, TEXT_COLOR,
+};
+
+// File caching the care times already read from past blocks
+const CACHE_FILE: &str = "care-history.json";
+
+// The history is sampled every hour (6 second blocks) over the last week
+const BLOCKS_PER_HOUR: u32 = 600;
+const BLOCKS_PER_DAY: u32 = 24 * BLOCKS_PER_HOUR;
+const DAYS: u32 = 7;
+
+// This plugin charts how often the player fed their pet and put it to sleep over the last week.
+// The care events come from the event index when it is up to date. Otherwise the care times
+// are read from the state of past blocks, which needs an archive node for blocks older than
+// the pruning window.
+pub struct AnalyticsPlugin;
+
+impl Plugin for AnalyticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            analytics_setup.in_schedule(OnEnter(GameState::Analytics)),
+            despawn_screen::<OnAnalyticsScreen>.in_schedule(OnExit(GameState::Analytics)),
+        ))
+        .add_systems((show_history, analytics_action).in_set(OnUpdate(GameState::Analytics)));
+    }
+}
+
+// Tag component used to tag entities added on the analytics screen
+#[derive(Component)]
+struct OnAnalyticsScreen;
+
+// Node the chart is drawn in once the history is loaded
+#[derive(Component)]
+struct ChartArea;
+
+#[derive(Component)]
+struct BackToMenu;
+
+// Care times of a pet as seen at sampled blocks, saved between runs
+#[derive(Serialize, Deserialize, Default)]
+struct CareHistory {
+    pet_id: PetId,
+    // Sample block -> (last feed block, last sleep block)
+    samples: BTreeMap<u32, (Option<u32>, Option<u32>)>,
+}
+
+// Feeds and sleeps of each day, today first
+struct CareChart {
+    feeds: Vec<usize>,
+    sleeps: Vec<usize>,
+    skipped: usize,
+}
+
+// Result of the background history scan
+#[derive(Resource)]
+struct HistoryLoad(Mutex<mpsc::Receiver<Result<CareChart, String>>>);
+
+impl CareHistory {
+    fn load(pet_id: PetId) -> Self {
+        std::fs::read(CACHE_FILE)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|history| history.pet_id == pet_id)
+            .unwrap_or(Self {
+                pet_id,
+                ..default()
+            })
+    }
+
+    fn save(&self) {
+        let saved = serde_json::to_vec(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(CACHE_FILE, json));
+        if let Err(e) = saved {
+            error!("error caching care history: {e:?}");
+        }
+    }
+
+    // Count the distinct care times seen in the samples
+    fn chart(&self, now: u32, skipped: usize) -> CareChart {
+        let feeds: BTreeSet<_> = self.samples.values().filter_map(|s| s.0).collect();
+        let sleeps: BTreeSet<_> = self.samples.values().filter_map(|s| s.1).collect();
+        CareChart {
+            feeds: per_day(now, feeds),
+            sleeps: per_day(now, sleeps),
+            skipped,
+        }
+    }
+}
+
+// Count the care times of each day before `now`
+fn per_day(now: u32, times: impl IntoIterator<Item = u32>) -> Vec<usize> {
+    let mut days = vec![0; DAYS as usize];
+    for time in times {
+        if let Some(day) = days.get_mut((now.saturating_sub(time) / BLOCKS_PER_DAY) as usize) {
+            *day += 1;
+        }
+    }
+    days
+}
+
+// Count the care events of the pet in the event index, if it is indexed up to the last hour
+fn indexed_chart(pet_id: PetId, now: u32) -> Option<CareChart> {
+    let db = IndexDb::open().ok()?;
+    if db.last_block().ok()?? + BLOCKS_PER_HOUR < now {
+        return None;
+    }
+    let since = now.saturating_sub(DAYS * BLOCKS_PER_DAY);
+    Some(CareChart {
+        feeds: per_day(now, db.pet_event_blocks(pet_id, "PetFeeded", since).ok()?),
+        sleeps: per_day(now, db.pet_event_blocks(pet_id, "PetSleeped", since).ok()?),
+        skipped: 0,
+    })
+}
+
+// Raw key of the entry of a pet in a care time map the pallet had before `PetVitals`. They
+// aren't in the node's metadata anymore, so their keys are built by hand.
+fn legacy_care_key(map: &str, pet_id: PetId) -> Vec<u8> {
+    let id = pet_id.encode();
+    [&pet_storage_prefix(map)[..], &blake2_128(&id), &id].concat()
+}
+
+// Care times of the pet at a block, from its vitals or, for blocks before the storage
+// migration, from the separate feed and sleep maps
+async fn care_at(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    pet_id: PetId,
+) -> Result<(Option<u32>, Option<u32>), subxt::Error> {
+    if let Some(value) = storage.fetch(&PetVitals::address(pet_id)).await? {
+        let vitals = PetVitals::decode(&mut value.encoded())?;
+        return Ok((Some(vitals.last_fed), vitals.last_slept));
+    }
+    let feed_key = legacy_care_key("LastFeedTime", pet_id);
+    let sleep_key = legacy_care_key("LastSleepTime", pet_id);
+    let block = |value: Option<Vec<u8>>| value.and_then(|bytes| u32::decode(&mut &bytes[..]).ok());
+    Ok((
+        block(storage.fetch_raw(&feed_key).await?),
+        block(storage.fetch_raw(&sleep_key).await?),
+    ))
+}
+
+// Read the care times at every sample block that isn't cached yet, unless the index has them
+async fn scan_history(
+    api: OnlineClient<PolkadotConfig>,
+    mut history: CareHistory,
+) -> Result<CareChart, subxt::Error> {
+    let now = api.blocks().at_latest().await?.number();
+    if let Some(chart) = indexed_chart(history.pet_id, now) {
+        return Ok(chart);
+    }
+    let first = now.saturating_sub(DAYS * BLOCKS_PER_DAY) / BLOCKS_PER_HOUR * BLOCKS_PER_HOUR;
+    history.samples.retain(|block, _| *block >= first);
+
+    let mut skipped = 0;
+    for block in (first..=now).step_by(BLOCKS_PER_HOUR as usize) {
+        if history.samples.contains_key(&block) {
+            continue;
+        }
+        let Some(hash) = api.rpc().block_hash(Some(block.into())).await? else {
+            continue;
+        };
+        let storage = api.storage().at(hash);
+        // Pruned nodes only keep the state of recent blocks
+        match care_at(&storage, history.pet_id).await {
+            Ok(care) => {
+                history.samples.insert(block, care);
+            }
+            Err(_) => skipped += 1,
+        }
+    }
+    history.save();
+    Ok(history.chart(now, skipped))
+}
+
+fn analytics_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let account = client.account_id();
+    let pet = client.api().and_then(|api| {
+        let pet = client.block_on(async {
+            api.storage()
+                .at_latest()
+                .await?
+                .fetch(&polkadot::storage().pet_module().pets_info(&account))
+                .await
+        })?;
+        Ok((api, pet))
+    });
+    match pet {
+        Ok((api, Some((pet_id, _)))) => {
+            let history = CareHistory::load(pet_id);
+            client.spawn(async move {
+                let chart = scan_history(api, history).await;
+                let _ = sender.send(chart.map_err(|e| e.to_string()));
+            });
+        }
+        Ok((_, None)) => {
+            let _ = sender.send(Err("You don't have a pet yet".to_string()));
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(HistoryLoad(Mutex::new(receiver)));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(15.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnAnalyticsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Care over the last week",
+                text_style.clone(),
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ChartArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading past blocks...",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    BackToMenu,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+// Draw the chart once the scan is done: a feed bar and a sleep bar for each day
+fn show_history(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<HistoryLoad>>,
+    area: Query<Entity, With<ChartArea>>,
+    palette: Res<Palette>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<HistoryLoad>();
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            let chart = match result {
+                Ok(chart) => chart,
+                Err(e) => {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Can't read the history: {e}"),
+                        text_style.clone(),
+                    ));
+                    return;
+                }
+            };
+            let (feed_color, sleep_color, legend) = palette.stat_bars();
+            let bar = |count: usize, color: Color| NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(14.0), Val::Px(4.0 + 16.0 * count.min(12) as f32)),
+                    ..default()
+                },
+                background_color: color.into(),
+                ..default()
+            };
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::FlexEnd,
+                        gap: Size::all(Val::Px(18.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Oldest day on the left
+                    for day in (0..DAYS as usize).rev() {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    gap: Size::all(Val::Px(4.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            align_items: AlignItems::FlexEnd,
+                                            gap: Size::all(Val::Px(2.0)),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn(bar(chart.feeds[day], feed_color));
+                                        parent.spawn(bar(chart.sleeps[day], sleep_color));
+                                    });
+                                let label = match day {
+                                    0 => "today".to_string(),
+                                    day => format!("-{day}d"),
+                                };
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Fed {} times, slept {} times ({legend})",
+                    chart.feeds.iter().sum::<usize>(),
+                    chart.sleeps.iter().sum::<usize>()
+                ),
+                text_style.clone(),
+            ));
+            if chart.skipped > 0 {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{} hours skipped, the node doesn't keep their state",
+                        chart.skipped
+                    ),
+                    text_style.clone(),
+                ));
+            }
+        });
+}
+
+fn analytics_action(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackToMenu>)>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            game_state.set(GameState::Menu);
+        }
+    }
+}
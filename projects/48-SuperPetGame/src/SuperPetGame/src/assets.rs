@@ -0,0 +1,227 @@
+use bevy::asset::{HandleId, LoadState};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+// Pet sprites, one per species, packed in one atlas once they're loaded
+pub const PET_SPECIES: [&str; 3] = ["turtle", "snake", "rabbit"];
+
+// Drawn for the species without a sprite of their own
+const FALLBACK_SPECIES: &str = "turtle";
+
+// This plugin loads the font and textures used across screens while the splash screen shows,
+// and keeps them loaded so switching screens never waits on the disk. Pet sprites are packed
+// in one texture atlas, so all the pets on screen are drawn from the same texture. The atlas
+// is packed again when a skin changes.
+pub struct AssetsPlugin;
+
+impl Plugin for AssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_assets)
+            .add_system(build_pet_atlas);
+    }
+}
+
+// What a sprite draws a pet as
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stage {
+    Egg,
+    Hatched,
+}
+
+// Icons of the menu buttons and the HUD
+pub struct Icons {
+    pub right: Handle<Image>,
+    pub wrench: Handle<Image>,
+    pub flame: Handle<Image>,
+}
+
+#[derive(Resource)]
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    pub icons: Icons,
+    // UI images can't be drawn from an atlas in this Bevy version, so the sprites are kept
+    // on their own for them
+    pet_images: HashMap<&'static str, Handle<Image>>,
+    // The atlas and the index of each species with a sprite in it, once the sprites loaded
+    pet_atlas: Option<(Handle<TextureAtlas>, HashMap<&'static str, usize>)>,
+    // Sprites from the skins directory, drawn instead of the bundled ones. A skin keeps its
+    // handle when it changes, so what's drawn with it follows.
+    skins: HashMap<(&'static str, Stage), Handle<Image>>,
+    // Whether a skin of a hatched pet changed since the atlas was packed
+    repack: bool,
+}
+
+// The species the game has sprites for named `species`, in any case
+pub fn known_species(species: &str) -> Option<&'static str> {
+    let species = species.to_lowercase();
+    PET_SPECIES.into_iter().find(|known| *known == species)
+}
+
+impl GameAssets {
+    // Whether everything finished loading, missing files included
+    pub fn ready(&self, asset_server: &AssetServer) -> bool {
+        let settled = |handle: HandleId| {
+            !matches!(
+                asset_server.get_load_state(handle),
+                LoadState::NotLoaded | LoadState::Loading
+            )
+        };
+        self.pet_atlas.is_some()
+            && settled(self.font.id())
+            && settled(self.icons.right.id())
+            && settled(self.icons.wrench.id())
+            && settled(self.icons.flame.id())
+    }
+
+    // The species drawn for `species`, the fallback one when it has no sprite
+    fn drawn_species(&self, species: &str) -> &'static str {
+        let indices = self.pet_atlas.as_ref().map(|(_, indices)| indices);
+        known_species(species)
+            .filter(|known| indices.map_or(false, |i| i.contains_key(known)))
+            .unwrap_or(FALLBACK_SPECIES)
+    }
+
+    // Sprite of a hatched `species` pet, its skin if it has one
+    fn image_of(&self, species: &'static str) -> Option<&Handle<Image>> {
+        self.skins
+            .get(&(species, Stage::Hatched))
+            .or_else(|| self.pet_images.get(species))
+    }
+
+    // Sprite of a pet for UI nodes
+    pub fn pet_image(&self, species: &str) -> Handle<Image> {
+        self.image_of(self.drawn_species(species))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Sprite of a pet still in its egg, only skins draw eggs
+    pub fn egg_image(&self, species: &str) -> Option<Handle<Image>> {
+        let species = known_species(species)?;
+        self.skins.get(&(species, Stage::Egg)).cloned()
+    }
+
+    // Draw `image` for the `species` pets at `stage`, or the bundled sprite again with `None`
+    pub fn set_skin(
+        &mut self,
+        species: &'static str,
+        stage: Stage,
+        image: Option<Image>,
+        images: &mut Assets<Image>,
+    ) {
+        let key = (species, stage);
+        match image {
+            Some(image) => match self.skins.get(&key) {
+                Some(handle) => images.set_untracked(handle, image),
+                None => {
+                    self.skins.insert(key, images.add(image));
+                }
+            },
+            None => {
+                self.skins.remove(&key);
+            }
+        }
+        self.repack |= stage == Stage::Hatched;
+    }
+
+    // Sprite of a pet drawn in the world, from the atlas
+    pub fn pet_sprite(&self, species: &str) -> SpriteSheetBundle {
+        let Some((atlas, indices)) = &self.pet_atlas else {
+            return SpriteSheetBundle::default();
+        };
+        let index = indices
+            .get(self.drawn_species(species))
+            .copied()
+            .unwrap_or_default();
+        SpriteSheetBundle {
+            texture_atlas: atlas.clone(),
+            sprite: TextureAtlasSprite::new(index),
+            ..default()
+        }
+    }
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameAssets {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        icons: Icons {
+            right: asset_server.load("textures/Game Icons/right.png"),
+            wrench: asset_server.load("textures/Game Icons/wrench.png"),
+            flame: asset_server.load("textures/Game Icons/flame.png"),
+        },
+        pet_images: PET_SPECIES
+            .into_iter()
+            .map(|species| {
+                let path = format!("textures/{species}-front2.png");
+                (species, asset_server.load(path))
+            })
+            .collect(),
+        pet_atlas: None,
+        skins: HashMap::default(),
+        repack: false,
+    });
+}
+
+// Pack the pet sprites that loaded into the atlas, once none is still loading, and again when
+// a skin changes
+fn build_pet_atlas(
+    asset_server: Res<AssetServer>,
+    mut assets: ResMut<GameAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut pets: Query<(&Handle<TextureAtlas>, &mut TextureAtlasSprite)>,
+) {
+    if assets.pet_atlas.is_some() && !assets.repack {
+        return;
+    }
+    // Skins are added as they are, only the bundled sprites load
+    let loading = assets.pet_images.values().any(|handle| {
+        matches!(
+            asset_server.get_load_state(handle),
+            LoadState::NotLoaded | LoadState::Loading
+        )
+    });
+    if loading {
+        return;
+    }
+
+    let sprites: Vec<_> = PET_SPECIES
+        .into_iter()
+        .filter_map(|species| Some((species, assets.image_of(species)?.clone_weak())))
+        .collect();
+    let mut builder = TextureAtlasBuilder::default();
+    for (_, handle) in &sprites {
+        if let Some(image) = images.get(handle) {
+            builder.add_texture(handle.clone_weak(), image);
+        }
+    }
+    let atlas = builder.finish(&mut images).unwrap_or_else(|e| {
+        error!("error packing the pet sprites: {e:?}");
+        TextureAtlas::new_empty(Handle::default(), Vec2::ZERO)
+    });
+    let indices: HashMap<_, _> = sprites
+        .iter()
+        .filter_map(|(species, handle)| Some((*species, atlas.get_texture_index(handle)?)))
+        .collect();
+    assets.repack = false;
+
+    let Some((handle, previous)) = assets.pet_atlas.take() else {
+        assets.pet_atlas = Some((atlases.add(atlas), indices));
+        return;
+    };
+    // Packed again for a skin, the pets on screen move to the place of their species in the
+    // new atlas. Those drawn as the fallback species stay so until they're drawn again.
+    atlases.set_untracked(&handle, atlas);
+    let moved: HashMap<usize, usize> = previous
+        .iter()
+        .filter_map(|(species, from)| Some((*from, *indices.get(species)?)))
+        .collect();
+    for (atlas, mut sprite) in &mut pets {
+        if *atlas == handle {
+            if let Some(to) = moved.get(&sprite.index) {
+                sprite.index = *to;
+            }
+        }
+    }
+    assets.pet_atlas = Some((handle, indices));
+}
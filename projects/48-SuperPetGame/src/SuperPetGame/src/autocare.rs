@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use chrono::{Local, NaiveDate, NaiveTime};
+use serde::Deserialize;
+use sp_core::Pair as _;
+use sp_keyring::sr25519::sr25519::Pair;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::tx::PairSigner;
+use subxt::utils::AccountId32;
+use subxt::PolkadotConfig;
+
+use super::{
+    client::{AccountChanged, ChainClient},
+    despawn_screen,
+    game::Spectating,
+    pet_watch::PlayerPet,
+    poll::{PollScheduler, Priority},
+    toast::Toast,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Auto-care settings, read at startup. Without the file, or with `enabled` off, nothing is
+// sent.
+const SETTINGS_FILE: &str = "autocare.json";
+
+// How often the pet is checked on
+const CHECK_SECONDS: f32 = 30.0;
+
+// Blocks in a minute, with 6 second blocks
+const BLOCKS_PER_MINUTE: u32 = 10;
+
+// This plugin takes care of the player's pet while the game runs: it feeds the pet when it
+// was last fed `feed_every_minutes` ago, and puts it to sleep at `bedtime` every day. The
+// calls are signed by a game key, which can only feed the pet and put it to sleep, so the
+// player's own key is never used unattended. The first time, the player's account proposes
+// the game key through the transaction queue and the game key accepts, paying its own fees
+// like for the care. Each action is announced with a toast and shown on the game screen.
+pub struct AutoCarePlugin;
+
+impl Plugin for AutoCarePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = AutoCareSettings::load();
+        app.insert_resource(AutoCare::new(&settings))
+            .insert_resource(settings)
+            .add_systems((forget_registration, auto_care, auto_care_text).chain())
+            .add_system(auto_care_setup.in_schedule(OnEnter(GameState::Game)))
+            .add_system(despawn_screen::<OnAutoCareText>.in_schedule(OnExit(GameState::Game)));
+    }
+}
+
+#[derive(Resource, Deserialize)]
+#[serde(default)]
+pub struct AutoCareSettings {
+    pub enabled: bool,
+    // Secret URI of the game key, like "//Alice//game" or a mnemonic phrase
+    pub game_key: Option<String>,
+    pub feed_every_minutes: u32,
+    // Local time, "HH:MM"
+    pub bedtime: String,
+}
+
+impl Default for AutoCareSettings {
+    fn default() -> Self {
+        // The pet gets hungry after an hour
+        Self {
+            enabled: false,
+            game_key: None,
+            feed_every_minutes: 50,
+            bedtime: "22:00".to_string(),
+        }
+    }
+}
+
+impl AutoCareSettings {
+    fn load() -> Self {
+        let Ok(json) = std::fs::read(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&json).unwrap_or_else(|e| {
+            warn!("ignoring invalid {SETTINGS_FILE}: {e}");
+            Self::default()
+        })
+    }
+}
+
+#[derive(Resource)]
+struct AutoCare {
+    // `None` when auto-care is off or its settings are invalid
+    key: Option<Pair>,
+    bedtime: NaiveTime,
+    // Whether the proposal of the game key was queued for the current account
+    registering: bool,
+    // Whether the game key accepted the proposal of the current account
+    accepted: bool,
+    // Day the pet was last put to sleep at bedtime
+    slept_on: Option<NaiveDate>,
+    // Last thing auto-care did, shown on the game screen
+    last_action: Option<String>,
+}
+
+impl AutoCare {
+    fn new(settings: &AutoCareSettings) -> Self {
+        let key = settings
+            .game_key
+            .as_deref()
+            .filter(|_| settings.enabled)
+            .and_then(|suri| match Pair::from_string(suri, None) {
+                Ok(pair) => Some(pair),
+                Err(e) => {
+                    warn!("auto-care is off, invalid game key: {e:?}");
+                    None
+                }
+            });
+        let bedtime = NaiveTime::parse_from_str(&settings.bedtime, "%H:%M").unwrap_or_else(|e| {
+            warn!("invalid auto-care bedtime {}: {e}", settings.bedtime);
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap_or_default()
+        });
+        Self {
+            key,
+            bedtime,
+            registering: false,
+            accepted: false,
+            slept_on: None,
+            last_action: None,
+        }
+    }
+}
+
+// Text telling the player what auto-care did
+#[derive(Component)]
+struct OnAutoCareText;
+
+// The game key has to be registered again for another account
+fn forget_registration(mut events: EventReader<AccountChanged>, mut auto_care: ResMut<AutoCare>) {
+    if events.iter().count() > 0 {
+        auto_care.registering = false;
+        auto_care.accepted = false;
+    }
+}
+
+fn auto_care(
+    settings: Res<AutoCareSettings>,
+    mut auto_care: ResMut<AutoCare>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    mut scheduler: ResMut<PollScheduler>,
+    mut queue: ResMut<TxQueue>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(key) = auto_care.key.clone() else {
+        return;
+    };
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    if !scheduler.ready("auto-care", Priority::Background, CHECK_SECONDS) {
+        return;
+    }
+
+    let owner = client.account_id();
+    let key_account = AccountId32::from(key.public().0);
+    let state = client.api().and_then(|api| {
+        client.block_on(async {
+            let block = api.blocks().at_latest().await?;
+            let registered = api
+                .storage()
+                .at(block.hash())
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "GameKeys",
+                    vec![Value::from_bytes(key_account.0)],
+                ))
+                .await?
+                .map(|value| AccountId32::decode(&mut value.encoded()))
+                .transpose()?;
+            let proposed = api
+                .storage()
+                .at(block.hash())
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "GameKeyProposals",
+                    vec![Value::from_bytes(key_account.0), Value::from_bytes(owner.0)],
+                ))
+                .await?
+                .is_some();
+            Ok::<_, subxt::Error>((api, block.number(), registered, proposed))
+        })
+    });
+    let (api, head, registered, proposed) = match state {
+        Ok(state) => state,
+        Err(e) => {
+            error!("error reading the auto-care state: {e:?}");
+            return;
+        }
+    };
+    let signer = PairSigner::<PolkadotConfig, Pair>::new(key);
+    if registered.as_ref() != Some(&owner) {
+        if proposed && !auto_care.accepted {
+            // The game key can't own a pet once it accepts, so it only accepts when asked to
+            let tx = subxt::dynamic::tx(
+                "PetModule",
+                "accept_game_key",
+                vec![Value::from_bytes(owner.0)],
+            );
+            match client.block_on(api.tx().sign_and_submit_default(&tx, &signer)) {
+                Ok(hash) => {
+                    info!(?hash, "auto-care key accepted");
+                    auto_care.accepted = true;
+                }
+                Err(e) => error!("error accepting the auto-care key: {e:?}"),
+            }
+        } else if !proposed && !auto_care.registering {
+            auto_care.registering = true;
+            queue.push(
+                "Register the auto-care key",
+                subxt::dynamic::tx(
+                    "PetModule",
+                    "propose_game_key",
+                    vec![Value::from_bytes(key_account.0)],
+                ),
+            );
+        }
+        return;
+    }
+
+    let feed_blocks = settings.feed_every_minutes * BLOCKS_PER_MINUTE;
+    let feed_due = player_pet
+        .last_feed_block
+        .map_or(true, |fed| head.saturating_sub(fed) >= feed_blocks);
+    let now = Local::now();
+    let bedtime_due =
+        now.time() >= auto_care.bedtime && auto_care.slept_on != Some(now.date_naive());
+    let (call, done) = match (feed_due, bedtime_due) {
+        (true, _) => ("feed", format!("fed {}", pet.name)),
+        (false, true) => ("sleep", format!("put {} to sleep", pet.name)),
+        (false, false) => return,
+    };
+
+    let _span = info_span!("auto-care", call).entered();
+    let tx = subxt::dynamic::tx("PetModule", call, Vec::<Value>::new());
+    match client.block_on(api.tx().sign_and_submit_default(&tx, &signer)) {
+        Ok(hash) => {
+            info!(?hash, "submitted");
+            if call == "sleep" {
+                auto_care.slept_on = Some(now.date_naive());
+            }
+            let action = format!("Auto-care {done} at {}", now.format("%H:%M"));
+            toasts.send(Toast(action.clone()));
+            auto_care.last_action = Some(action);
+        }
+        Err(e) => error!("error sending the auto-care {call}: {e:?}"),
+    }
+}
+
+fn auto_care_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    auto_care: Res<AutoCare>,
+    spectating: Res<Spectating>,
+) {
+    if auto_care.key.is_none() || spectating.0.is_some() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "Auto-care on",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 16.0,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
+        OnAutoCareText,
+    ));
+}
+
+fn auto_care_text(auto_care: Res<AutoCare>, mut texts: Query<&mut Text, With<OnAutoCareText>>) {
+    let Some(action) = &auto_care.last_action else {
+        return;
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != *action {
+            text.sections[0].value = action.clone();
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use bevy::prelude::*;
+use pet_core::Mood;
+use subxt::{dynamic::Value, utils::AccountId32};
+
+use super::{
+    address_book::{AddressBook, AddressField, ContactHint},
+    assets::GameAssets,
+    client::{pet_events::BattleResolved, AnyCall, ChainClient, ChainEvent},
+    despawn_screen,
+    fee::FeeText,
+    pet_watch::{CareRules, PlayerPet},
+    settings::Palette,
+    snapshot::PetSnapshot,
+    tooltip::CallHelp,
+    txqueue::Locked,
+    GameState, TEXT_COLOR,
+};
+
+// This plugin lets the player challenge the pet of another account. The battle is resolved
+// by the chain, so the screen just waits for the `BattleResolved` event of our challenge.
+pub struct BattlePlugin;
+
+impl Plugin for BattlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<BattlePhase>()
+            .init_resource::<Battle>()
+            .add_systems((
+                battle_enter.in_schedule(OnEnter(GameState::Battle)),
+                battle_exit.in_schedule(OnExit(GameState::Battle)),
+            ))
+            .add_systems((
+                challenge_setup.in_schedule(OnEnter(BattlePhase::Challenge)),
+                despawn_screen::<OnChallengeScreen>.in_schedule(OnExit(BattlePhase::Challenge)),
+                fight_setup.in_schedule(OnEnter(BattlePhase::Fighting)),
+                despawn_screen::<OnFightScreen>.in_schedule(OnExit(BattlePhase::Fighting)),
+                fight_animation.in_set(OnUpdate(BattlePhase::Fighting)),
+                battle_resolution.in_set(OnUpdate(BattlePhase::Fighting)),
+                result_setup.in_schedule(OnEnter(BattlePhase::Result)),
+                despawn_screen::<OnResultScreen>.in_schedule(OnExit(BattlePhase::Result)),
+            ))
+            .add_systems((battle_action, button_system).in_set(OnUpdate(GameState::Battle)));
+    }
+}
+
+// Step of the battle currently displayed
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+enum BattlePhase {
+    Challenge,
+    Fighting,
+    Result,
+    #[default]
+    Disabled,
+}
+
+// The battle in progress
+#[derive(Resource, Default)]
+struct Battle {
+    player: Option<PetSnapshot>,
+    opponent: Option<(AccountId32, PetSnapshot)>,
+    outcome: Option<BattleResolved>,
+}
+
+// Tag component used to tag entities added on the challenge screen
+#[derive(Component)]
+struct OnChallengeScreen;
+
+// Tag component used to tag entities added on the fight screen
+#[derive(Component)]
+struct OnFightScreen;
+
+// Tag component used to tag entities added on the result screen
+#[derive(Component)]
+struct OnResultScreen;
+
+#[derive(Component)]
+struct OnOpponentInputText;
+
+#[derive(Component)]
+struct OnBattleStatusText;
+
+// Pet sprite lunging at the other one, `direction` is 1 for the left pet and -1 for the right
+#[derive(Component)]
+struct Fighter {
+    direction: f32,
+}
+
+#[derive(Component)]
+enum BattleButtonAction {
+    FindOpponent,
+    Fight,
+    Practice,
+    Replays,
+    BackToGame,
+}
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    palette: Res<Palette>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        *color = palette.button(*interaction, false).into();
+    }
+}
+
+fn battle_enter(mut battle: ResMut<Battle>, mut phase: ResMut<NextState<BattlePhase>>) {
+    *battle = Battle::default();
+    phase.set(BattlePhase::Challenge);
+}
+
+fn battle_exit(mut phase: ResMut<NextState<BattlePhase>>) {
+    phase.set(BattlePhase::Disabled);
+}
+
+// Full screen column used by every battle screen
+fn screen_node() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            gap: Size {
+                width: Val::Px(15.0),
+                height: Val::Px(15.0),
+            },
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    action: impl Bundle,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+// The chances of the player's pet against `opponent` in each mood it can be in when the chain
+// rolls. There's no result to preview, the roll comes from randomness drawn once the challenge
+// is in a block.
+fn odds_breakdown(
+    player_pet: &PlayerPet,
+    rules: &pet_core::Rules,
+    opponent: &PetSnapshot,
+) -> Option<String> {
+    player_pet.pet.as_ref()?;
+    let care = player_pet.care();
+    let current = care
+        .stats(rules, pet_core::at_block(opponent.snapshot_block))
+        .mood;
+    let odds = pet_core::battle_odds(care.experience, opponent.battle_weight(rules));
+    let breakdown: Vec<String> = odds
+        .iter()
+        .map(|(mood, odds)| {
+            let name = match mood {
+                Mood::Happy => "happy",
+                Mood::Hungry => "hungry",
+                Mood::Tired => "tired",
+                Mood::Miserable => "hungry and tired",
+            };
+            let now = if *mood == current { " (now)" } else { "" };
+            format!("{}% {name}{now}", odds.percent())
+        })
+        .collect();
+    Some(format!(
+        "Odds to win: {}\nThe chain rolls once the challenge is in a block, no one can tell \
+         the result before",
+        breakdown.join(", ")
+    ))
+}
+
+// The roll and weights the chain settled a battle with, anyone can check the result from them
+// with `pet_core::wins_battle`
+pub fn roll_tag(log: &pet_core::BattleLog) -> String {
+    format!(
+        "seed {:016x}, weights {} vs {}",
+        log.seed, log.challenger_weight, log.opponent_weight
+    )
+}
+
+// Asks for the address of the account to challenge
+fn challenge_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::MAROON.into(),
+                ..screen_node()
+            },
+            OnChallengeScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Type the address or contact of the owner to challenge",
+                text_style.clone(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size {
+                            width: Val::Px(700.),
+                            height: Val::Px(50.),
+                        },
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            String::new(),
+                            TextStyle {
+                                font_size: 18.0,
+                                ..text_style.clone()
+                            },
+                        ),
+                        OnOpponentInputText,
+                        AddressField,
+                    ));
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                ContactHint,
+            ));
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                OnBattleStatusText,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size {
+                            width: Val::Px(15.0),
+                            height: Val::Px(15.0),
+                        },
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(
+                        parent,
+                        "Find",
+                        BattleButtonAction::FindOpponent,
+                        &text_style,
+                    );
+                    spawn_button(
+                        parent,
+                        "Fight!",
+                        (BattleButtonAction::Fight, CallHelp::pet("challenge")),
+                        &text_style,
+                    );
+                    spawn_button(
+                        parent,
+                        "Practice",
+                        BattleButtonAction::Practice,
+                        &text_style,
+                    );
+                    spawn_button(parent, "Replays", BattleButtonAction::Replays, &text_style);
+                    spawn_button(parent, "Back", BattleButtonAction::BackToGame, &text_style);
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                FeeText(AnyCall::new(subxt::dynamic::tx(
+                    "PetModule",
+                    "challenge",
+                    vec![Value::from_bytes([0u8; 32])],
+                ))),
+            ));
+        });
+}
+
+fn fighter_sprite(
+    assets: &GameAssets,
+    pet: &PetSnapshot,
+    direction: f32,
+) -> (SpriteSheetBundle, Fighter) {
+    let mut sprite = assets.pet_sprite(&pet.species);
+    // Both pets face each other
+    sprite.sprite.flip_x = direction < 0.0;
+    sprite.transform = Transform::from_xyz(-200.0 * direction, 0.0, 0.0);
+    (sprite, Fighter { direction })
+}
+
+// Both pets fight until the chain tells who won
+fn fight_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    battle: Res<Battle>,
+) {
+    let (Some(player), Some((_, opponent))) = (&battle.player, &battle.opponent) else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    commands.spawn((fighter_sprite(&assets, player, 1.0), OnFightScreen));
+    commands.spawn((fighter_sprite(&assets, opponent, -1.0), OnFightScreen));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..screen_node().style
+                },
+                ..screen_node()
+            },
+            OnFightScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("{} VS {}", player.name, opponent.name),
+                text_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Waiting for the battle to be finalized...",
+                text_style.clone(),
+            ));
+            spawn_button(parent, "Back", BattleButtonAction::BackToGame, &text_style);
+        });
+}
+
+fn fight_animation(time: Res<Time>, mut fighters: Query<(&Fighter, &mut Transform)>) {
+    for (fighter, mut transform) in &mut fighters {
+        let lunge = (time.elapsed_seconds() * 6.0).sin().max(0.0) * 60.0;
+        transform.translation.x = (lunge - 200.0) * fighter.direction;
+    }
+}
+
+// Wait for the event resolving our challenge
+fn battle_resolution(
+    mut events: EventReader<ChainEvent>,
+    client: Res<ChainClient>,
+    mut battle: ResMut<Battle>,
+    mut phase: ResMut<NextState<BattlePhase>>,
+) {
+    let account = client.account_id();
+    for ChainEvent(event) in events.iter() {
+        match event.as_event::<BattleResolved>() {
+            Ok(Some(resolved)) if resolved.challenger == account => {
+                battle.outcome = Some(resolved);
+                phase.set(BattlePhase::Result);
+            }
+            Ok(_) => {}
+            Err(e) => error!("error decoding battle event: {e:?}"),
+        }
+    }
+}
+
+fn result_setup(mut commands: Commands, asset_server: Res<AssetServer>, battle: Res<Battle>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 40.0,
+        color: TEXT_COLOR,
+    };
+    let (Some(player), Some(outcome)) = (&battle.player, &battle.outcome) else {
+        return;
+    };
+    let (title, result) = if outcome.winner == player.pet_id {
+        (
+            "You won!",
+            format!("{} gained {} XP", player.name, outcome.experience),
+        )
+    } else {
+        (
+            "You lost",
+            format!("{} will do better next time", player.name),
+        )
+    };
+    let detail = format!("{result}\n{}", roll_tag(&outcome.log.into()));
+
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::MAROON.into(),
+                ..screen_node()
+            },
+            OnResultScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, text_style.clone()));
+            parent.spawn(TextBundle::from_section(
+                detail,
+                TextStyle {
+                    font_size: 24.0,
+                    ..text_style.clone()
+                },
+            ));
+            let button_style = TextStyle {
+                font_size: 24.0,
+                ..text_style
+            };
+            spawn_button(parent, "Replay", BattleButtonAction::Replays, &button_style);
+            spawn_button(
+                parent,
+                "Back",
+                BattleButtonAction::BackToGame,
+                &button_style,
+            );
+        });
+}
+
+fn battle_action(
+    interaction_query: Query<
+        (&Interaction, &BattleButtonAction, Option<&Locked>),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut client: ResMut<ChainClient>,
+    mut battle: ResMut<Battle>,
+    mut phase: ResMut<NextState<BattlePhase>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    address_book: Res<AddressBook>,
+    player_pet: Res<PlayerPet>,
+    care_rules: Res<CareRules>,
+    address_text: Query<&Text, With<OnOpponentInputText>>,
+    mut status_text: Query<&mut Text, (With<OnBattleStatusText>, Without<OnOpponentInputText>)>,
+) {
+    for (interaction, action, locked) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        if let Some(locked) = locked {
+            status_text.single_mut().sections[0].value = locked.0.clone();
+            continue;
+        }
+        match action {
+            //Look up the pet of the typed address or contact
+            BattleButtonAction::FindOpponent => {
+                let address = address_book.resolve(&address_text.single().sections[0].value);
+                let opponent = address
+                    .parse::<AccountId32>()
+                    .map_err(|_| "Invalid address".to_string())
+                    .and_then(|account| {
+                        PetSnapshot::fetch(&mut client, &account)
+                            .map(|pet| (account, pet))
+                            .map_err(|e| e.to_string())
+                    });
+                status_text.single_mut().sections[0].value = match &opponent {
+                    Ok((_, pet)) => {
+                        let found =
+                            format!("{} the {}, {} XP", pet.name, pet.species, pet.experience);
+                        match odds_breakdown(&player_pet, &care_rules.rules, pet) {
+                            Some(odds) => format!("{found}\n{odds}"),
+                            None => found,
+                        }
+                    }
+                    Err(e) => e.clone(),
+                };
+                battle.opponent = opponent.ok();
+            }
+            //Submit the challenge, the result comes with the finalized block
+            BattleButtonAction::Fight => {
+                let Some((opponent, _)) = &battle.opponent else {
+                    status_text.single_mut().sections[0].value = "Find an opponent first".into();
+                    continue;
+                };
+                let account = client.account_id();
+                if *opponent == account {
+                    status_text.single_mut().sections[0].value =
+                        "You can't battle your own pet".into();
+                    continue;
+                }
+                let _span = info_span!("challenge").entered();
+                let challenge =
+                    subxt::dynamic::tx("PetModule", "challenge", vec![Value::from_bytes(opponent)]);
+                let result = PetSnapshot::fetch(&mut client, &account)
+                    .map_err(|e| e.to_string())
+                    .and_then(|player| {
+                        client.submit(&challenge).map_err(|e| e.to_string())?;
+                        Ok(player)
+                    });
+                match result {
+                    Ok(player) => {
+                        battle.player = Some(player);
+                        phase.set(BattlePhase::Fighting);
+                    }
+                    Err(e) => status_text.single_mut().sections[0].value = e,
+                }
+            }
+            BattleButtonAction::Practice => game_state.set(GameState::Practice),
+            BattleButtonAction::Replays => game_state.set(GameState::Replay),
+            BattleButtonAction::BackToGame => game_state.set(GameState::Game),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::Path;
+use std::process::Command;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::client::DEFAULT_NODE_URL;
+use super::profile::{self, Profiles};
+
+// The chains the profile plays on, edited by hand to add test networks
+const CHAINS_FILE: &str = "chains.json";
+
+// Chains other than the default one, a directory each
+const CHAINS_DIR: &str = "chains";
+
+// The chain used last, connected to again when none is chosen
+const LAST_CHAIN_FILE: &str = "chains/last.json";
+
+// The local development node, the only chain before chains could be chosen
+pub const DEFAULT_CHAIN: &str = "local";
+
+// This plugin lets a profile play on several chains, like parallel test networks running
+// different runtimes. Each chain has its own directory inside the profile, which `select`
+// moves to, so accounts, caches and the event database of one chain never mix with another's.
+// A chain is chosen at launch with `--chain <name>` or `SUPERPET_CHAIN` among the ones listed
+// in `chains.json`. Choosing another one on the settings screen starts the game again with it.
+pub struct ChainsPlugin(pub Chains);
+
+impl Plugin for ChainsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone()).add_system(switch_chain);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Chain {
+    pub name: String,
+    // Websocket address of a node of the chain
+    pub url: String,
+}
+
+#[derive(Resource, Clone)]
+pub struct Chains {
+    pub current: Chain,
+    // Every chain, the current one included
+    pub all: Vec<Chain>,
+    // Problems that didn't stop the launch, logged once logging is up
+    pub warnings: Vec<String>,
+}
+
+// Button starting the game again on a chain
+#[derive(Component)]
+pub struct ChainButton(pub String);
+
+#[derive(Serialize, Deserialize)]
+struct LastChain {
+    name: String,
+}
+
+fn default_chain() -> Chain {
+    Chain {
+        name: DEFAULT_CHAIN.to_string(),
+        url: DEFAULT_NODE_URL.to_string(),
+    }
+}
+
+// The name is a directory name, like a profile's
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// The chain chosen at launch, else the last one used
+fn chosen() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--chain")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| std::env::var("SUPERPET_CHAIN").ok())
+        .or_else(|| {
+            let json = std::fs::read(LAST_CHAIN_FILE).ok()?;
+            serde_json::from_slice::<LastChain>(&json)
+                .ok()
+                .map(|last| last.name)
+        })
+}
+
+// The chains of `chains.json`, written with the default chain the first time so there's an
+// example to follow. The default chain is always there.
+fn list(warnings: &mut Vec<String>) -> Result<Vec<Chain>, String> {
+    let mut all = match std::fs::read(CHAINS_FILE) {
+        Ok(json) => serde_json::from_slice::<Vec<Chain>>(&json)
+            .map_err(|e| format!("can't read {CHAINS_FILE}: {e}"))?,
+        Err(_) => {
+            let json = serde_json::to_vec_pretty(&[default_chain()]).map_err(|e| e.to_string())?;
+            if let Err(e) = std::fs::write(CHAINS_FILE, json) {
+                warnings.push(format!("can't save {CHAINS_FILE}: {e}"));
+            }
+            Vec::new()
+        }
+    };
+    if let Some(chain) = all.iter().find(|chain| !valid_name(&chain.name)) {
+        return Err(format!(
+            "invalid chain name {} in {CHAINS_FILE}, use letters, digits, - or _",
+            chain.name
+        ));
+    }
+    if !all.iter().any(|chain| chain.name == DEFAULT_CHAIN) {
+        all.insert(0, default_chain());
+    }
+    Ok(all)
+}
+
+// Move to the directory of the chosen chain, creating it the first time. Call it right after
+// `profile::select`, the chains are the profile's. Nothing logs yet, so errors and warnings are
+// returned to be logged once it does.
+pub fn select() -> Result<Chains, String> {
+    let mut warnings = Vec::new();
+    let all = list(&mut warnings)?;
+    let name = chosen().unwrap_or_else(|| DEFAULT_CHAIN.to_string());
+    let Some(current) = all.iter().find(|chain| chain.name == name).cloned() else {
+        let names: Vec<&str> = all.iter().map(|chain| chain.name.as_str()).collect();
+        return Err(format!(
+            "unknown chain {name}, add it to {CHAINS_FILE} or choose one of {}",
+            names.join(", ")
+        ));
+    };
+
+    let last = serde_json::to_vec_pretty(&LastChain { name: name.clone() })
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            std::fs::create_dir_all(CHAINS_DIR)
+                .and_then(|()| std::fs::write(LAST_CHAIN_FILE, json))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = last {
+        warnings.push(format!("can't save {LAST_CHAIN_FILE}: {e}"));
+    }
+
+    // The default chain keeps the files of the profile from before chains existed
+    if name != DEFAULT_CHAIN {
+        let dir = Path::new(CHAINS_DIR).join(&name);
+        std::fs::create_dir_all(&dir)
+            .and_then(|()| std::env::set_current_dir(&dir))
+            .map_err(|e| format!("can't open the chain {name}: {e}"))?;
+    }
+    Ok(Chains {
+        current,
+        all,
+        warnings,
+    })
+}
+
+// Start the game again on the chain of the clicked button, with the same profile
+fn switch_chain(
+    chains: Res<Chains>,
+    profiles: Res<Profiles>,
+    buttons: Query<(&Interaction, &ChainButton), Changed<Interaction>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked || button.0 == chains.current.name {
+            continue;
+        }
+        let args = profile::restart_args(&["--profile", "--chain"]);
+        let started = std::env::current_exe().and_then(|exe| {
+            Command::new(exe)
+                .args(args)
+                .args(["--profile", &profiles.current, "--chain", &button.0])
+                .current_dir(profiles.root())
+                .spawn()
+        });
+        match started {
+            Ok(_) => {
+                info!("switching to the chain {}", button.0);
+                exit.send(AppExit);
+            }
+            Err(e) => error!("can't start the game on the chain {}: {e}", button.0),
+        }
+    }
+}
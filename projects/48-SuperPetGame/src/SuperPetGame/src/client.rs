@@ -0,0 +1,990 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sp_core::{hashing::twox_128, Pair as _};
+use sp_keyring::sr25519::sr25519::Pair;
+use sp_keyring::AccountKeyring;
+use subxt::events::EventDetails;
+use subxt::rpc::{
+    rpc_params,
+    types::{StorageChangeSet, StorageKey},
+};
+use subxt::storage::StorageAddress;
+use subxt::utils::{AccountId32, H256};
+use subxt::{
+    config::polkadot::{Era, PlainTip, PolkadotExtrinsicParamsBuilder},
+    ext::codec::{self, Compact, Decode, Encode, Input},
+    tx::{
+        DryRunResult, PairSigner, PartialExtrinsic, SubmittableExtrinsic, TxPayload, TxProgress,
+        ValidationDetails,
+    },
+    Config, Metadata, OnlineClient, PolkadotConfig,
+};
+use tokio::task::JoinHandle;
+
+#[subxt::subxt(runtime_metadata_path = "./metadata.scale")]
+//#[subxt::subxt(runtime_metadata_path = "/mnt/hddisk1/github/SuperPetGame-RST/metadata.scale")]
+pub mod polkadot {}
+pub type PetId = u32;
+pub type PetSpecies = polkadot::runtime_types::pallet_pet::pallet::Species;
+pub type PetInfo = polkadot::runtime_types::pallet_pet::pallet::PetInfo;
+//type Error = polkadot::runtime_types::pallet_pet::pallet::Error;
+pub type PetName = polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec<u8>;
+pub type Nonce = <PolkadotConfig as Config>::Index;
+
+// Events added or changed in the pallet after `metadata.scale` was generated. They mirror
+// the pallet definitions so they can be decoded with `EventDetails::as_event`.
+pub mod pet_events {
+    use subxt::{
+        events::StaticEvent,
+        ext::{codec::Decode, scale_decode::DecodeAsType},
+        utils::AccountId32,
+    };
+
+    use super::{PetId, PetName, PetSpecies};
+
+    #[derive(Debug, Clone, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct PetMinted {
+        pub owner: AccountId32,
+        pub pet_id: PetId,
+        pub species: PetSpecies,
+        // Who minted the pet, someone else than the owner when it was a gift
+        pub payer: AccountId32,
+    }
+
+    impl StaticEvent for PetMinted {
+        const PALLET: &'static str = "PetModule";
+        const EVENT: &'static str = "PetMinted";
+    }
+
+    #[derive(Debug, Clone, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct BattleResolved {
+        pub challenger: AccountId32,
+        pub opponent: AccountId32,
+        pub winner: PetId,
+        pub loser: PetId,
+        pub experience: u32,
+        pub log: BattleLog,
+    }
+
+    // Roll and weights of a battle, `pet_core` replays it from them
+    #[derive(Debug, Clone, Copy, Decode, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct BattleLog {
+        pub seed: u64,
+        pub challenger_weight: u64,
+        pub opponent_weight: u64,
+    }
+
+    impl From<BattleLog> for pet_core::BattleLog {
+        fn from(log: BattleLog) -> Self {
+            pet_core::BattleLog {
+                seed: log.seed,
+                challenger_weight: log.challenger_weight,
+                opponent_weight: log.opponent_weight,
+            }
+        }
+    }
+
+    impl StaticEvent for BattleResolved {
+        const PALLET: &'static str = "PetModule";
+        const EVENT: &'static str = "BattleResolved";
+    }
+
+    #[derive(Debug, Clone, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct PetRenamed {
+        pub owner: AccountId32,
+        pub pet_id: PetId,
+        pub name: PetName,
+        // Zero for the first rename of the pet
+        pub fee: u128,
+    }
+
+    impl StaticEvent for PetRenamed {
+        const PALLET: &'static str = "PetModule";
+        const EVENT: &'static str = "PetRenamed";
+    }
+
+    // New care times and experience of a pet, sent with each event changing them
+    #[derive(Debug, Clone, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct StatsChanged {
+        pub pet_id: PetId,
+        pub last_feed: u32,
+        pub last_sleep: Option<u32>,
+        pub experience: u64,
+    }
+
+    impl StaticEvent for StatsChanged {
+        const PALLET: &'static str = "PetModule";
+        const EVENT: &'static str = "StatsChanged";
+    }
+
+    // A care streak reached a milestone, the item given is left out, the inventory shows it
+    #[derive(Debug, Clone, DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub struct StreakRewarded {
+        pub owner: AccountId32,
+        pub pet_id: PetId,
+        pub days: u32,
+        pub amount: u32,
+    }
+
+    impl StaticEvent for StreakRewarded {
+        const PALLET: &'static str = "PetModule";
+        const EVENT: &'static str = "StreakRewarded";
+    }
+}
+
+// Raw key prefix of a storage entry of the pet pallet, to read entries by hand when they
+// aren't in the node's metadata or only the first part of their key is known
+pub fn pet_storage_prefix(entry: &str) -> Vec<u8> {
+    [twox_128(b"PetModule"), twox_128(entry.as_bytes())].concat()
+}
+
+// Mirror of `Vitals` of the pet pallet, kept in `PetVitals` which `metadata.scale` predates
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PetVitals {
+    pub last_fed: u32,
+    pub last_slept: Option<u32>,
+    pub feeds: u32,
+    pub sleeps: u32,
+    // Wall-clock times of the care in milliseconds since the Unix epoch, `None` when the chain
+    // doesn't know them
+    pub fed_at: Option<u64>,
+    pub slept_at: Option<u64>,
+}
+
+// By hand, so vitals from runtimes before the wall-clock times decode too
+impl Decode for PetVitals {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let last_fed = Decode::decode(input)?;
+        let last_slept = Decode::decode(input)?;
+        let feeds = Decode::decode(input)?;
+        let sleeps = Decode::decode(input)?;
+        let fed_at = Option::<u64>::decode(input).unwrap_or_default();
+        let slept_at = Option::<u64>::decode(input).unwrap_or_default();
+        Ok(Self {
+            last_fed,
+            last_slept,
+            feeds,
+            sleeps,
+            fed_at,
+            slept_at,
+        })
+    }
+}
+
+impl PetVitals {
+    // Storage address of the vitals of a pet
+    pub fn address(pet_id: PetId) -> subxt::storage::DynamicAddress<subxt::dynamic::Value> {
+        subxt::dynamic::storage(
+            "PetModule",
+            "PetVitals",
+            vec![subxt::dynamic::Value::u128(pet_id.into())],
+        )
+    }
+
+    // Vitals from the raw value of a storage entry, `None` when there's none
+    pub fn from_raw(value: &Option<Vec<u8>>) -> Option<Self> {
+        value
+            .as_ref()
+            .and_then(|bytes| Self::decode(&mut &bytes[..]).ok())
+    }
+}
+
+// Mirror of `MintBlockReason` of the pet pallet, why an account can't get a pet right now
+#[derive(Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MintBlockReason {
+    GamePaused,
+    MaxPetsReached,
+    GameKey,
+    SpeciesSoldOut,
+    CoOwner,
+}
+
+impl MintBlockReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            MintBlockReason::GamePaused => "game paused",
+            MintBlockReason::MaxPetsReached => "max pets reached",
+            MintBlockReason::GameKey => "game keys can't own pets",
+            MintBlockReason::SpeciesSoldOut => "sold out",
+            MintBlockReason::CoOwner => "co-owners can't own pets",
+        }
+    }
+}
+
+// Websocket endpoint of the local dev node
+pub const DEFAULT_NODE_URL: &str = "ws://127.0.0.1:9944";
+
+// Balances have 12 decimals
+const UNIT: u128 = 1_000_000_000_000;
+
+// Show a balance in whole tokens
+pub fn format_balance(amount: u128) -> String {
+    format!(
+        "{}.{:04} PET",
+        amount / UNIT,
+        amount % UNIT / (UNIT / 10_000)
+    )
+}
+
+// Any call payload, boxed so calls of different types can be stored together
+pub struct AnyCall(Box<dyn TxPayload + Send + Sync>);
+
+impl AnyCall {
+    pub fn new(call: impl TxPayload + Send + Sync + 'static) -> Self {
+        Self(Box::new(call))
+    }
+}
+
+impl TxPayload for AnyCall {
+    fn encode_call_data_to(
+        &self,
+        metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        self.0.encode_call_data_to(metadata, out)
+    }
+
+    fn validation_details(&self) -> Option<ValidationDetails<'_>> {
+        self.0.validation_details()
+    }
+}
+
+// A call whose check against the node's metadata can be skipped. Unchecked, it's encoded by
+// pallet and call name with the types the node describes, like a dynamic call.
+struct CheckedCall<'a, Call> {
+    call: &'a Call,
+    validate: bool,
+}
+
+impl<Call: TxPayload> TxPayload for CheckedCall<'_, Call> {
+    fn encode_call_data_to(
+        &self,
+        metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        self.call.encode_call_data_to(metadata, out)
+    }
+
+    fn validation_details(&self) -> Option<ValidationDetails<'_>> {
+        self.validate
+            .then(|| self.call.validation_details())
+            .flatten()
+    }
+}
+
+// How the player's transactions are built
+#[derive(Clone, Copy, Debug)]
+pub struct TxParams {
+    // Number of blocks after which a transaction that wasn't included is dropped from the
+    // pool, or `None` to keep it valid forever
+    pub mortal_period: Option<u64>,
+    // Paid to the block author on top of the fee, to be included first when blocks are full
+    pub tip: u128,
+}
+
+impl Default for TxParams {
+    fn default() -> Self {
+        // About 6 minutes with 6 second blocks
+        Self {
+            mortal_period: Some(64),
+            tip: 0,
+        }
+    }
+}
+
+impl TxParams {
+    // Defaults overridden by `SUPERPET_TX_MORTAL_PERIOD` (0 for immortal transactions) and
+    // `SUPERPET_TX_TIP`
+    pub fn from_env() -> Self {
+        let var = |name| -> Option<u128> { std::env::var(name).ok()?.parse().ok() };
+        let mut params = Self::default();
+        if let Some(period) = var("SUPERPET_TX_MORTAL_PERIOD") {
+            params.mortal_period = Some(period as u64).filter(|period| *period > 0);
+        }
+        if let Some(tip) = var("SUPERPET_TX_TIP") {
+            params.tip = tip;
+        }
+        params
+    }
+}
+
+// Accounts the player can switch between, read from this file at startup. Without it the
+// dev accounts are used.
+const KEYSTORE_FILE: &str = "keystore.json";
+
+// Keystore entry, the secret is a secret URI like "//Alice" or a mnemonic phrase
+#[derive(Serialize, Deserialize)]
+struct KeystoreEntry {
+    name: String,
+    suri: String,
+}
+
+// An account of the keystore
+pub struct Account {
+    pub name: String,
+    pair: Pair,
+}
+
+impl Account {
+    fn read_keystore() -> Vec<KeystoreEntry> {
+        match std::fs::read(KEYSTORE_FILE) {
+            Ok(json) => serde_json::from_slice(&json).unwrap_or_else(|e| {
+                warn!("ignoring invalid {KEYSTORE_FILE}: {e}");
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn load_keystore() -> Vec<Self> {
+        let entries = Self::read_keystore();
+        let accounts: Vec<_> = entries
+            .into_iter()
+            .filter_map(|entry| match Pair::from_string(&entry.suri, None) {
+                Ok(pair) => Some(Self {
+                    name: entry.name,
+                    pair,
+                }),
+                Err(e) => {
+                    warn!("ignoring account {}: {e:?}", entry.name);
+                    None
+                }
+            })
+            .collect();
+        if !accounts.is_empty() {
+            return accounts;
+        }
+        [
+            AccountKeyring::Alice,
+            AccountKeyring::Bob,
+            AccountKeyring::Charlie,
+            AccountKeyring::Dave,
+            AccountKeyring::Eve,
+        ]
+        .into_iter()
+        .map(|keyring| Self {
+            name: <&str>::from(keyring).to_string(),
+            pair: keyring.pair(),
+        })
+        .collect()
+    }
+}
+
+// Sent when the player switches to another account, so state tied to the previous one is
+// dropped and loaded again
+pub struct AccountChanged;
+
+// Fee of a transaction, tip included, and the free balance of the player paying it
+pub struct FeeEstimate {
+    pub fee: u128,
+    pub free: u128,
+    // Execution time the runtime charges for the transaction, in picoseconds
+    pub weight: u64,
+}
+
+impl FeeEstimate {
+    pub fn affordable(&self) -> bool {
+        self.free >= self.fee
+    }
+}
+
+// This plugin owns the connection to the chain, shared by all screens
+pub struct ClientPlugin(pub String);
+
+impl Plugin for ClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChainClient::new(&self.0))
+            .add_event::<ChainEvent>()
+            .add_event::<StorageChanged>()
+            .add_event::<AccountChanged>()
+            .add_startup_system(subscribe_events)
+            .add_systems((read_chain_events, read_storage_changes));
+    }
+}
+
+// Event of the pet or tournament pallet emitted in a finalized block
+#[derive(Clone)]
+pub struct ChainEvent(pub EventDetails<PolkadotConfig>);
+
+// New value of a storage entry watched with `ChainClient::watch_storage`. The first values
+// are sent as soon as the watch starts.
+#[derive(Clone)]
+pub struct StorageChanged {
+    pub watch: &'static str,
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+// Receiving end of the event subscription running on the client runtime
+#[derive(Resource)]
+struct ChainEventReceiver(Mutex<mpsc::Receiver<ChainEvent>>);
+
+// Follow finalized blocks in the background, so screens can react to what happened on chain
+fn subscribe_events(mut commands: Commands, mut client: ResMut<ChainClient>) {
+    let api = match client.api() {
+        Ok(api) => api,
+        Err(e) => {
+            warn!("not following chain events of {}: {e:?}", client.url());
+            return;
+        }
+    };
+    let (sender, receiver) = mpsc::channel();
+    client.spawn(async move {
+        if let Err(e) = forward_game_events(api, sender).await {
+            warn!("chain event subscription ended: {e:?}");
+        }
+    });
+    commands.insert_resource(ChainEventReceiver(Mutex::new(receiver)));
+}
+
+async fn forward_game_events(
+    api: OnlineClient<PolkadotConfig>,
+    sender: mpsc::Sender<ChainEvent>,
+) -> Result<(), subxt::Error> {
+    let mut blocks = api.blocks().subscribe_finalized().await?;
+    while let Some(block) = blocks.next().await {
+        let events = block?.events().await?;
+        for event in events.iter() {
+            let event = event?;
+            // The game is gone once nobody listens anymore
+            let game = matches!(event.pallet_name(), "PetModule" | "TournamentModule");
+            if game && sender.send(ChainEvent(event)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Turn the events received since the last frame into bevy events
+fn read_chain_events(
+    receiver: Option<Res<ChainEventReceiver>>,
+    mut events: EventWriter<ChainEvent>,
+) {
+    if let Some(receiver) = receiver {
+        events.send_batch(receiver.0.lock().unwrap().try_iter());
+    }
+}
+
+fn read_storage_changes(client: Res<ChainClient>, mut events: EventWriter<StorageChanged>) {
+    events.send_batch(client.storage_changes.1.lock().unwrap().try_iter());
+}
+
+// An action of the player on one of their pets, from when it's queued until it's finalized
+// or fails
+#[derive(Clone, Debug)]
+pub struct PendingAction {
+    pub pet: PetId,
+    // Name of the pallet call, to tell which other actions it conflicts with
+    pub call: &'static str,
+    pub label: String,
+}
+
+#[derive(Default)]
+struct PendingActions {
+    next_id: u64,
+    actions: Vec<(u64, PendingAction)>,
+}
+
+// Held while an action is pending, dropping it frees the pet for the actions it blocked. It
+// can be moved to the task waiting for the transaction to be finalized.
+pub struct PetLock {
+    id: u64,
+    actions: Arc<Mutex<PendingActions>>,
+}
+
+impl Drop for PetLock {
+    fn drop(&mut self) {
+        self.actions
+            .lock()
+            .unwrap()
+            .actions
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+// Connection to the chain node. The connection is opened lazily on first use, so the
+// game still starts when no node is running.
+#[derive(Resource)]
+pub struct ChainClient {
+    runtime: tokio::runtime::Runtime,
+    url: String,
+    api: Option<OnlineClient<PolkadotConfig>>,
+    // Nonce of the next transaction of the player, tracked locally so several transactions
+    // can wait in the pool at once
+    next_nonce: Option<Nonce>,
+    tx_params: TxParams,
+    accounts: Vec<Account>,
+    active_account: usize,
+    // Whether the interface generated from `metadata.scale` still matches the node's runtime.
+    // After an upgrade changing it, calls are sent without checking them.
+    static_interface: bool,
+    // Whether the node answers `system_dryRun`, an unsafe RPC method public nodes usually
+    // refuse. Calls are simulated before they're sent until it doesn't.
+    dry_run: bool,
+    // Running storage watches by name
+    watches: HashMap<&'static str, JoinHandle<()>>,
+    storage_changes: (
+        mpsc::Sender<StorageChanged>,
+        Mutex<mpsc::Receiver<StorageChanged>>,
+    ),
+    // Actions of the player not finalized yet, shared with the tasks waiting for them
+    pending_actions: Arc<Mutex<PendingActions>>,
+    // Nanoseconds systems spent waiting for the node in `block_on` since it was last read
+    blocked: AtomicU64,
+}
+
+impl ChainClient {
+    pub fn new(url: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            runtime: tokio::runtime::Runtime::new().expect("failed to start tokio runtime"),
+            url: url.to_string(),
+            api: None,
+            next_nonce: None,
+            tx_params: TxParams::from_env(),
+            accounts: Account::load_keystore(),
+            active_account: 0,
+            static_interface: true,
+            dry_run: true,
+            watches: HashMap::new(),
+            storage_changes: (sender, Mutex::new(receiver)),
+            pending_actions: Arc::default(),
+            blocked: AtomicU64::new(0),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // The account playing the game
+    pub fn account_id(&self) -> AccountId32 {
+        self.account().pair.public().0.into()
+    }
+
+    // Signer of the account playing the game
+    pub fn signer(&self) -> PairSigner<PolkadotConfig, Pair> {
+        PairSigner::new(self.account().pair.clone())
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.accounts[self.active_account]
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    // Index of the playing account in `accounts`
+    pub fn active_account(&self) -> usize {
+        self.active_account
+    }
+
+    // Play as another account of the keystore, returns whether the account changed.
+    // Send `AccountChanged` when it did.
+    pub fn switch_account(&mut self, index: usize) -> bool {
+        if index == self.active_account || index >= self.accounts.len() {
+            return false;
+        }
+        self.active_account = index;
+        self.next_nonce = None;
+        true
+    }
+
+    // Add an account to the keystore file and play as it. `suri` is a secret URI or a
+    // mnemonic phrase, like in the keystore.
+    pub fn add_account(&mut self, name: &str, suri: &str) -> Result<(), String> {
+        let pair = Pair::from_string(suri, None).map_err(|e| format!("invalid secret: {e:?}"))?;
+        let mut entries = Account::read_keystore();
+        entries.push(KeystoreEntry {
+            name: name.to_string(),
+            suri: suri.to_string(),
+        });
+        let json = serde_json::to_vec_pretty(&entries).map_err(|e| e.to_string())?;
+        std::fs::write(KEYSTORE_FILE, json)
+            .map_err(|e| format!("can't write {KEYSTORE_FILE}: {e}"))?;
+        self.accounts.push(Account {
+            name: name.to_string(),
+            pair,
+        });
+        self.switch_account(self.accounts.len() - 1);
+        Ok(())
+    }
+
+    // Add an account kept in memory only, like the throwaway accounts of the demo, unless it's
+    // there already. Returns its index in `accounts`, to `switch_account` to.
+    pub fn add_session_account(&mut self, name: &str, pair: Pair) -> usize {
+        let public = pair.public();
+        if let Some(index) = self
+            .accounts
+            .iter()
+            .position(|account| account.pair.public() == public)
+        {
+            return index;
+        }
+        self.accounts.push(Account {
+            name: name.to_string(),
+            pair,
+        });
+        self.accounts.len() - 1
+    }
+
+    // Whether the player's accounts come from the keystore file rather than the dev accounts
+    pub fn has_keystore(&self) -> bool {
+        !Account::read_keystore().is_empty()
+    }
+
+    pub fn static_interface(&self) -> bool {
+        self.static_interface
+    }
+
+    // Set after a runtime upgrade, see `upgrade.rs`
+    pub fn set_static_interface(&mut self, matches: bool) {
+        self.static_interface = matches;
+    }
+
+    // `call` checked against the node's metadata only while the static interface matches it
+    fn checked<'a, Call>(&self, call: &'a Call) -> CheckedCall<'a, Call> {
+        CheckedCall {
+            call,
+            validate: self.static_interface,
+        }
+    }
+
+    // `call` ready to be signed with `nonce`. `sign` signs it with the player's keys right away,
+    // the keys of cold accounts sign its payload outside the game, see `cold_sign.rs`.
+    pub fn unsigned<Call: TxPayload>(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        call: &Call,
+        nonce: Nonce,
+        params: PolkadotExtrinsicParamsBuilder<PolkadotConfig>,
+    ) -> Result<PartialExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>, subxt::Error> {
+        api.tx()
+            .create_partial_signed_with_nonce(&self.checked(call), nonce, params)
+    }
+
+    // Paid on top of the fee of every call, see `TxParams`
+    pub fn tip(&self) -> u128 {
+        self.tx_params.tip
+    }
+
+    // Extrinsic params for `tx_params`, a mortal era starts at the latest block
+    async fn extrinsic_params(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+    ) -> Result<PolkadotExtrinsicParamsBuilder<PolkadotConfig>, subxt::Error> {
+        let params = PolkadotExtrinsicParamsBuilder::new().tip(PlainTip::new(self.tx_params.tip));
+        let Some(period) = self.tx_params.mortal_period else {
+            return Ok(params);
+        };
+        let block = api.blocks().at_latest().await?;
+        Ok(params.era(Era::mortal(period, block.number().into()), block.hash()))
+    }
+
+    // Mark `action` as pending on its pet until the returned lock is dropped
+    pub fn lock_pet(&self, action: PendingAction) -> PetLock {
+        let mut pending = self.pending_actions.lock().unwrap();
+        pending.next_id += 1;
+        let id = pending.next_id;
+        pending.actions.push((id, action));
+        PetLock {
+            id,
+            actions: self.pending_actions.clone(),
+        }
+    }
+
+    // The pending actions on `pet`, oldest first
+    pub fn pending_actions(&self, pet: PetId) -> Vec<PendingAction> {
+        self.pending_actions
+            .lock()
+            .unwrap()
+            .actions
+            .iter()
+            .filter(|(_, action)| action.pet == pet)
+            .map(|(_, action)| action.clone())
+            .collect()
+    }
+
+    // Run a future on the client runtime, blocking the current system until it completes
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let started = Instant::now();
+        let output = self.runtime.block_on(future);
+        self.blocked
+            .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        output
+    }
+
+    // Time systems spent blocked on the node since the last call, the frame stalled that long
+    pub fn take_blocked_time(&self) -> Duration {
+        Duration::from_nanos(self.blocked.swap(0, Ordering::Relaxed))
+    }
+
+    // Run a future in the background on the client runtime
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.runtime.spawn(future)
+    }
+
+    // The api if the node was reached already, without connecting
+    pub fn connected_api(&self) -> Option<OnlineClient<PolkadotConfig>> {
+        self.api.clone()
+    }
+
+    // Get the api, connecting to the node if we are not connected yet
+    pub fn api(&mut self) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
+        if let Some(api) = &self.api {
+            return Ok(api.clone());
+        }
+        let api = self
+            .runtime
+            .block_on(OnlineClient::<PolkadotConfig>::from_url(&self.url))?;
+        // Another chain may run a runtime other than the one `metadata.scale` was taken from
+        self.static_interface = polkadot::validate_codegen(&api).is_ok();
+        if !self.static_interface {
+            let url = &self.url;
+            warn!("{url} runs another runtime, calls are sent without checking them");
+        }
+        self.api = Some(api.clone());
+        Ok(api)
+    }
+
+    // Sign and submit a call from the player's account without waiting for it to be included.
+    // The outcome arrives later as a `ChainEvent`.
+    pub fn submit<Call: TxPayload>(&mut self, call: &Call) -> Result<H256, subxt::Error> {
+        let (tx, nonce) = self.sign(call)?;
+        let _span = info_span!("submit", nonce).entered();
+        let result = self.block_on(tx.submit());
+        // A rejected transaction may mean our nonce is off, so ask the node again next time
+        self.next_nonce = result.is_ok().then_some(nonce + 1);
+        result
+    }
+
+    // Like `submit`, but follow the transaction to read its own events once it's in a block
+    pub fn submit_and_watch<Call: TxPayload>(
+        &mut self,
+        call: &Call,
+    ) -> Result<TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>, subxt::Error> {
+        let (tx, nonce) = self.sign(call)?;
+        let _span = info_span!("submit", nonce).entered();
+        let result = self.block_on(tx.submit_and_watch());
+        self.next_nonce = result.is_ok().then_some(nonce + 1);
+        result
+    }
+
+    // Sign a call with the player's next nonce
+    fn sign<Call: TxPayload>(
+        &mut self,
+        call: &Call,
+    ) -> Result<
+        (
+            SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+            Nonce,
+        ),
+        subxt::Error,
+    > {
+        let _span = info_span!("build").entered();
+        let api = self.api()?;
+        let signer = self.signer();
+        let nonce = match self.next_nonce {
+            Some(nonce) => nonce,
+            None => self.block_on(api.rpc().system_account_next_index(&self.account_id()))?,
+        };
+        // The node would drop it silently, so tell the player instead
+        let estimate = self.estimate_fee(call)?;
+        if !estimate.affordable() {
+            return Err(subxt::Error::Other(format!(
+                "the fee of {} is more than your {}",
+                format_balance(estimate.fee),
+                format_balance(estimate.free)
+            )));
+        }
+        let tx = self.block_on(async {
+            let params = self.extrinsic_params(&api).await?;
+            Ok::<_, subxt::Error>(self.unsigned(&api, call, nonce, params)?.sign(&signer))
+        })?;
+        self.simulate(&api, &tx, nonce)?;
+        Ok((tx, nonce))
+    }
+
+    // Run a signed transaction on top of the latest block without submitting it, so a call
+    // bound to fail costs the player nothing. Only the player's next transaction can be
+    // simulated, the ones after it would run without the pending ones before them.
+    fn simulate(
+        &mut self,
+        api: &OnlineClient<PolkadotConfig>,
+        tx: &SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        nonce: Nonce,
+    ) -> Result<(), subxt::Error> {
+        if !self.dry_run {
+            return Ok(());
+        }
+        let _span = info_span!("dry run").entered();
+        let account = self.account_id();
+        let result = self.block_on(async {
+            let block = api.blocks().at_latest().await?;
+            let account_nonce = api
+                .storage()
+                .at(block.hash())
+                .fetch(&polkadot::storage().system().account(&account))
+                .await?
+                .map_or(0, |info| info.nonce);
+            if account_nonce != nonce {
+                return Ok(None);
+            }
+            Ok::<_, subxt::Error>(Some(tx.dry_run(Some(block.hash())).await))
+        });
+        match result {
+            Ok(Some(Ok(DryRunResult::DispatchError(e)))) => Err(subxt::Error::Other(format!(
+                "not sent, it would fail with {e}"
+            ))),
+            // The transaction pool has the last word on the validity of transactions
+            Ok(Some(Ok(_))) | Ok(None) => Ok(()),
+            Ok(Some(Err(subxt::Error::Rpc(e)))) => {
+                warn!("sending calls without a dry run, the node refused it: {e}");
+                self.dry_run = false;
+                Ok(())
+            }
+            Ok(Some(Err(e))) | Err(e) => {
+                warn!("sending the call without a dry run: {e}");
+                Ok(())
+            }
+        }
+    }
+
+    // Ask the transaction payment runtime api what submitting `call` would cost the player
+    pub fn estimate_fee<Call: TxPayload>(
+        &mut self,
+        call: &Call,
+    ) -> Result<FeeEstimate, subxt::Error> {
+        let api = self.api()?;
+        let signer = self.signer();
+        let account = self.account_id();
+        // The nonce doesn't change the fee, the extrinsic is never submitted
+        let nonce = self.next_nonce.unwrap_or_default();
+        self.block_on(async {
+            let params = self.extrinsic_params(&api).await?;
+            let tx =
+                api.tx()
+                    .create_signed_with_nonce(&self.checked(call), &signer, nonce, params)?;
+            // What `partial_fee_estimate` asks for, keeping the weight as well
+            let mut query = tx.encoded().to_vec();
+            (tx.encoded().len() as u32).encode_to(&mut query);
+            let (weight, _proof_size, _class, partial_fee) = api
+                .rpc()
+                .state_call::<(Compact<u64>, Compact<u64>, u8, u128)>(
+                    "TransactionPaymentApi_query_info",
+                    Some(&query),
+                    None,
+                )
+                .await?;
+            let fee = partial_fee + self.tx_params.tip;
+            let free = api
+                .storage()
+                .at_latest()
+                .await?
+                .fetch(&polkadot::storage().system().account(&account))
+                .await?
+                .map_or(0, |info| info.data.free);
+            Ok(FeeEstimate {
+                fee,
+                free,
+                weight: weight.0,
+            })
+        })
+    }
+
+    // Population and cap (`None` when unlimited) of every species, from the pet runtime api
+    pub fn species_supply(&mut self) -> Result<Vec<(PetSpecies, u32, Option<u32>)>, subxt::Error> {
+        let api = self.api()?;
+        self.block_on(api.rpc().state_call("PetApi_species_supply", None, None))
+    }
+
+    // Whether the player's account can mint a pet of `species` now, or why not, from the pet
+    // runtime api. Runtimes from before the check fail the call.
+    pub fn can_mint(
+        &mut self,
+        species: &PetSpecies,
+    ) -> Result<Result<(), MintBlockReason>, subxt::Error> {
+        let api = self.api()?;
+        let args = (self.account_id(), species).encode();
+        self.block_on(api.rpc().state_call("PetApi_can_mint", Some(&args), None))
+    }
+
+    // Watch storage entries by their raw keys (see `storage_key`), replacing the previous watch
+    // with the same name. Their values are sent as `StorageChanged` events each time a block
+    // changes them.
+    pub fn watch_storage(
+        &mut self,
+        watch: &'static str,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<(), subxt::Error> {
+        let api = self.api()?;
+        let keys: Vec<_> = keys.into_iter().map(StorageKey).collect();
+        let sender = self.storage_changes.0.clone();
+        let task = self.runtime.spawn(async move {
+            let changes = api
+                .rpc()
+                .subscribe::<StorageChangeSet<H256>>(
+                    "state_subscribeStorage",
+                    rpc_params![keys],
+                    "state_unsubscribeStorage",
+                )
+                .await;
+            let mut changes = match changes {
+                Ok(changes) => changes,
+                Err(e) => {
+                    error!("error watching {watch}: {e:?}");
+                    return;
+                }
+            };
+            while let Some(Ok(set)) = changes.next().await {
+                for (key, value) in set.changes {
+                    let change = StorageChanged {
+                        watch,
+                        key: key.0,
+                        value: value.map(|value| value.0),
+                    };
+                    if sender.send(change).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        if let Some(previous) = self.watches.insert(watch, task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    pub fn unwatch_storage(&mut self, watch: &'static str) {
+        if let Some(task) = self.watches.remove(watch) {
+            task.abort();
+        }
+    }
+
+    // Raw key of a storage entry, to match it with `StorageChanged::key`
+    pub fn storage_key<Address: StorageAddress>(
+        &mut self,
+        address: &Address,
+    ) -> Result<Vec<u8>, subxt::Error> {
+        self.api()?.storage().address_bytes(address)
+    }
+}
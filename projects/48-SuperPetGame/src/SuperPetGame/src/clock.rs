@@ -0,0 +1,155 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use chrono::{Local, TimeZone};
+
+use super::client::{polkadot, ChainClient};
+
+// Time between two reads of the chain head and its time
+const CLOCK_SECONDS: f32 = 6.0;
+
+// Blocks are 6 seconds apart while block production doesn't pause
+const MILLIS_PER_BLOCK: i64 = 6_000;
+
+// This plugin tells the wall-clock time of blocks, so screens show dates and times instead of
+// block numbers. The time of the chain head is read from the timestamp pallet now and then,
+// other blocks are counted 6 seconds apart from it, which is off for blocks before a pause of
+// block production. Care comes with the time the chain recorded, which is always right. F4 or
+// `--raw-blocks` shows the block numbers instead, to debug.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChainClock {
+            raw_blocks: std::env::args().any(|arg| arg == "--raw-blocks"),
+            ..default()
+        })
+        .add_systems((read_clock, toggle_raw_blocks));
+    }
+}
+
+#[derive(Resource)]
+pub struct ChainClock {
+    // The latest head read and its time in milliseconds since the Unix epoch
+    head: Option<(u32, u64)>,
+    // Show block numbers rather than times
+    pub raw_blocks: bool,
+    timer: Timer,
+    // Set by the background read when it's done, the head and its time or why it couldn't be
+    // read
+    result: Arc<Mutex<Option<Result<(u32, u64), String>>>>,
+    in_flight: bool,
+}
+
+impl Default for ChainClock {
+    fn default() -> Self {
+        Self {
+            head: None,
+            raw_blocks: false,
+            // Read right away
+            timer: Timer::from_seconds(0.0, TimerMode::Once),
+            result: Arc::default(),
+            in_flight: false,
+        }
+    }
+}
+
+impl ChainClock {
+    // Number of the latest head read, `None` until it's read
+    pub fn head(&self) -> Option<u32> {
+        self.head.map(|(head, _)| head)
+    }
+
+    // Time of a past or future block, `None` until the head is read
+    pub fn moment(&self, block: u32) -> Option<u64> {
+        let (head, at) = self.head?;
+        let blocks = i64::from(block) - i64::from(head);
+        u64::try_from(at as i64 + blocks * MILLIS_PER_BLOCK).ok()
+    }
+
+    // When a block happened or will, like "today 14:05 (2 h ago)"
+    pub fn block(&self, block: u32) -> String {
+        self.at(block, None)
+    }
+
+    // When something happened at a block, at `moment` if the chain recorded it. Without the
+    // time of the block it's the block number, like when block numbers are shown to debug.
+    pub fn at(&self, block: u32, moment: Option<u64>) -> String {
+        match moment.or_else(|| self.moment(block)) {
+            Some(moment) if !self.raw_blocks => human_time(moment),
+            _ => format!("block #{block}"),
+        }
+    }
+}
+
+// Local date and time of a moment, with how long ago it is or how long until it
+fn human_time(moment: u64) -> String {
+    let Some(at) = Local.timestamp_millis_opt(moment as i64).single() else {
+        return "unknown time".to_string();
+    };
+    let now = Local::now();
+    let date = if at.date_naive() == now.date_naive() {
+        at.format("today %H:%M")
+    } else {
+        at.format("%a %d %b %Y %H:%M")
+    };
+    let minutes = (at - now).num_minutes();
+    let relative = match minutes.unsigned_abs() {
+        0 => return format!("{date} (now)"),
+        m if m < 60 => format!("{m} min"),
+        m if m < 48 * 60 => format!("{} h", m / 60),
+        m => format!("{} days", m / (24 * 60)),
+    };
+    if minutes < 0 {
+        format!("{date} ({relative} ago)")
+    } else {
+        format!("{date} (in {relative})")
+    }
+}
+
+// Read the head and its time in the background now and then
+fn read_clock(time: Res<Time>, client: Res<ChainClient>, mut clock: ResMut<ChainClock>) {
+    if clock.in_flight {
+        let Some(result) = clock.result.lock().unwrap().take() else {
+            return;
+        };
+        match result {
+            Ok(head) => clock.head = Some(head),
+            Err(e) => debug!("can't read the time of the chain: {e}"),
+        }
+        clock.in_flight = false;
+        clock.timer = Timer::from_seconds(CLOCK_SECONDS, TimerMode::Once);
+    }
+    if !clock.timer.tick(time.delta()).finished() {
+        return;
+    }
+    // Connecting would block the frame, the other systems connect when they need the node
+    let Some(api) = client.connected_api() else {
+        return;
+    };
+    let result = clock.result.clone();
+    clock.in_flight = true;
+    client.spawn(async move {
+        let head = async {
+            let block = api.blocks().at_latest().await?;
+            let now = api
+                .storage()
+                .at(block.hash())
+                .fetch_or_default(&polkadot::storage().timestamp().now())
+                .await?;
+            Ok::<_, subxt::Error>((block.number(), now))
+        };
+        let head = match head.await {
+            Ok((_, 0)) => Err("genesis has no time".to_string()),
+            Ok(head) => Ok(head),
+            Err(e) => Err(e.to_string()),
+        };
+        *result.lock().unwrap() = Some(head);
+    });
+}
+
+fn toggle_raw_blocks(keys: Res<Input<KeyCode>>, mut clock: ResMut<ChainClock>) {
+    if keys.just_pressed(KeyCode::F4) {
+        clock.raw_blocks = !clock.raw_blocks;
+    }
+}
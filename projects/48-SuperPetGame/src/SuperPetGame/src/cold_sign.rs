@@ -0,0 +1,617 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sp_core::bytes::{from_hex, to_hex};
+use sp_core::{sr25519, Pair as _};
+use subxt::config::polkadot::{PlainTip, PolkadotExtrinsicParamsBuilder};
+use subxt::tx::TxPayload;
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature};
+use subxt::{Metadata, PolkadotConfig};
+
+use super::{
+    address_book::{AddressBook, AddressField, ContactHint},
+    client::{polkadot, ChainClient, Nonce},
+    despawn_screen,
+    menu::species_from_name,
+    naming::{self, DEFAULT_NAME},
+    settings::Palette,
+    toast::Toast,
+    GameState, TEXT_COLOR,
+};
+
+// Calls waiting to be signed, `<id>.json` each, with their signature in `<id>.sig` once it's
+// brought back. Sent calls are moved to `SENT_DIR`.
+const COLD_SIGN_DIR: &str = "cold-sign";
+const SENT_DIR: &str = "cold-sign/sent";
+
+const SPECIES: [&str; 3] = ["Turtle", "Snake", "Rabbit"];
+
+// This plugin lets cold accounts, whose keys never enter the game, mint and transfer pets.
+// A call is exported to a file holding the payload to sign, built like the game builds its
+// own calls, and is signed on another computer, e.g. with
+// `subkey sign --hex --suri <secret> --message <payload>`. The signature is brought back in a
+// `.sig` file next to the export, or dropped on the window, and the call is broadcast once
+// the signature is checked. Exported calls never expire, so they can take their time.
+pub struct ColdSignPlugin;
+
+impl Plugin for ColdSignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            cold_sign_setup.in_schedule(OnEnter(GameState::ColdSign)),
+            despawn_screen::<OnColdSignScreen>.in_schedule(OnExit(GameState::ColdSign)),
+        ))
+        .add_systems(
+            (
+                cold_sign_action,
+                focus_field.after(cold_sign_action),
+                type_name,
+                drop_signature,
+                show_exports.after(cold_sign_action).after(drop_signature),
+                color_choices.after(cold_sign_action),
+            )
+                .in_set(OnUpdate(GameState::ColdSign)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the cold signing screen
+#[derive(Component)]
+struct OnColdSignScreen;
+
+#[derive(Component)]
+struct ExportList;
+
+// The text of a field of the screen. Only the focused address field is an `AddressField`, so
+// typing goes to one field at a time.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum ColdField {
+    Account,
+    Recipient,
+    Name,
+}
+
+#[derive(Resource)]
+struct ColdForm {
+    focus: ColdField,
+    species: &'static str,
+}
+
+impl Default for ColdForm {
+    fn default() -> Self {
+        Self {
+            focus: ColdField::Account,
+            species: SPECIES[0],
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+enum ColdAction {
+    Focus(ColdField),
+    Species(&'static str),
+    ExportMint,
+    ExportTransfer,
+    Broadcast(String),
+    Back,
+}
+
+// A call exported for a cold account to sign
+#[derive(Serialize, Deserialize)]
+struct Export {
+    // SS58 address of the cold account
+    account: String,
+    // What the call does, to check before signing it
+    description: String,
+    nonce: Nonce,
+    tip: u128,
+    // The payload is only valid for this runtime
+    spec_version: u32,
+    // Hex of the encoded call
+    call_data: String,
+    // Hex of the bytes to sign with the sr25519 key of the account
+    payload: String,
+}
+
+// The exports read from `COLD_SIGN_DIR`, by id, and whether their signature is there
+#[derive(Resource, Default)]
+struct Exports(Vec<(String, Export, bool)>);
+
+// A call already encoded, to build an exported call again from its file
+struct EncodedCall(Vec<u8>);
+
+impl TxPayload for EncodedCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+fn export_path(id: &str) -> String {
+    format!("{COLD_SIGN_DIR}/{id}.json")
+}
+
+fn signature_path(id: &str) -> String {
+    format!("{COLD_SIGN_DIR}/{id}.sig")
+}
+
+fn read_exports() -> Exports {
+    let mut exports: Vec<(String, Export, bool)> = std::fs::read_dir(COLD_SIGN_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "json")
+            {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            let export = serde_json::from_slice(&std::fs::read(&path).ok()?)
+                .map_err(|e| warn!("ignoring invalid {}: {e}", path.display()))
+                .ok()?;
+            let signed = Path::new(&signature_path(&id)).exists();
+            Some((id, export, signed))
+        })
+        .collect();
+    exports.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    Exports(exports)
+}
+
+fn parse_account(address: &str) -> Result<AccountId32, String> {
+    address
+        .trim()
+        .parse::<AccountId32>()
+        .map_err(|_| format!("invalid address {address:?}"))
+}
+
+// Params of exported calls, immortal so they're still valid whenever they come back signed
+fn export_params(tip: u128) -> PolkadotExtrinsicParamsBuilder<PolkadotConfig> {
+    PolkadotExtrinsicParamsBuilder::new().tip(PlainTip::new(tip))
+}
+
+// Write `call` of `account` to a file for its keys to sign, returns the file
+fn export<Call: TxPayload>(
+    client: &mut ChainClient,
+    address: &str,
+    description: String,
+    call: &Call,
+) -> Result<String, String> {
+    let account = parse_account(address)?;
+    let api = client.api().map_err(|e| e.to_string())?;
+    let next = client
+        .block_on(api.rpc().system_account_next_index(&account))
+        .map_err(|e| e.to_string())?;
+    // After the calls of the account exported and not sent yet
+    let nonce = read_exports()
+        .0
+        .iter()
+        .filter(|(_, export, _)| export.account == account.to_string())
+        .map(|(_, export, _)| export.nonce + 1)
+        .fold(next, Nonce::max);
+    let tip = client.tip();
+    let unsigned = client
+        .unsigned(&api, call, nonce, export_params(tip))
+        .map_err(|e| e.to_string())?;
+    let address = account.to_string();
+    let id = format!("{}-{nonce}", &address[..8]);
+    let export = Export {
+        account: address,
+        description,
+        nonce,
+        tip,
+        spec_version: api.runtime_version().spec_version,
+        call_data: to_hex(unsigned.call_data(), false),
+        payload: to_hex(&unsigned.signer_payload(), false),
+    };
+    let json = serde_json::to_vec_pretty(&export).map_err(|e| e.to_string())?;
+    let path = export_path(&id);
+    std::fs::create_dir_all(COLD_SIGN_DIR)
+        .and_then(|()| std::fs::write(&path, json))
+        .map_err(|e| format!("can't write {path}: {e}"))?;
+    Ok(path)
+}
+
+// Check the signature brought back for the export `id` and send the signed call
+fn broadcast(client: &mut ChainClient, id: &str) -> Result<(), String> {
+    let json = std::fs::read(export_path(id)).map_err(|e| e.to_string())?;
+    let export: Export = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    let signature = std::fs::read_to_string(signature_path(id)).map_err(|e| e.to_string())?;
+    let signature: [u8; 64] = from_hex(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("the signature isn't 64 bytes of hex")?;
+    let account = parse_account(&export.account)?;
+    let call_data = from_hex(&export.call_data).map_err(|e| e.to_string())?;
+
+    let api = client.api().map_err(|e| e.to_string())?;
+    if api.runtime_version().spec_version != export.spec_version {
+        return Err("the chain was upgraded since the export, export it again".to_string());
+    }
+    let unsigned = client
+        .unsigned(
+            &api,
+            &EncodedCall(call_data),
+            export.nonce,
+            export_params(export.tip),
+        )
+        .map_err(|e| e.to_string())?;
+    let payload = unsigned.signer_payload();
+    if to_hex(&payload, false) != export.payload {
+        return Err("the chain changed since the export, export it again".to_string());
+    }
+    let valid = sr25519::Pair::verify(
+        &sr25519::Signature::from_raw(signature),
+        &payload,
+        &sr25519::Public::from_raw(account.0),
+    );
+    if !valid {
+        return Err(format!("the signature isn't {}'s", export.account));
+    }
+
+    let tx = unsigned.sign_with_address_and_signature(
+        &MultiAddress::Id(account),
+        &MultiSignature::Sr25519(signature),
+    );
+    let hash = client.block_on(tx.submit()).map_err(|e| e.to_string())?;
+    info!("sent the cold signed call {id}: {hash:?}");
+
+    let sent = std::fs::create_dir_all(SENT_DIR).and_then(|()| {
+        std::fs::rename(export_path(id), format!("{SENT_DIR}/{id}.json"))?;
+        std::fs::rename(signature_path(id), format!("{SENT_DIR}/{id}.sig"))
+    });
+    if let Err(e) = sent {
+        warn!("can't move {id} to {SENT_DIR}: {e}");
+    }
+    Ok(())
+}
+
+fn button(parent: &mut ChildBuilder, text_style: &TextStyle, label: &str, action: ColdAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn field(parent: &mut ChildBuilder, text_style: &TextStyle, label: &str, field: ColdField) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                gap: Size::all(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            button(parent, text_style, label, ColdAction::Focus(field));
+            let value = match field {
+                ColdField::Name => DEFAULT_NAME,
+                _ => "",
+            };
+            parent.spawn((TextBundle::from_section(value, text_style.clone()), field));
+        });
+}
+
+fn cold_sign_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+    let row = Style {
+        align_items: AlignItems::Center,
+        gap: Size::all(Val::Px(10.0)),
+        ..default()
+    };
+
+    commands.insert_resource(ColdForm::default());
+    commands.insert_resource(read_exports());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnColdSignScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Cold signing, for accounts whose keys stay off this computer",
+                text_style.clone(),
+            ));
+            field(parent, &text_style, "Cold account", ColdField::Account);
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                ContactHint,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: row.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for species in SPECIES {
+                        button(parent, &text_style, species, ColdAction::Species(species));
+                    }
+                });
+            field(parent, &text_style, "Name", ColdField::Name);
+            button(parent, &text_style, "Export mint", ColdAction::ExportMint);
+
+            field(parent, &text_style, "Transfer to", ColdField::Recipient);
+            button(
+                parent,
+                &text_style,
+                "Export transfer",
+                ColdAction::ExportTransfer,
+            );
+
+            parent.spawn(TextBundle::from_section(
+                format!("Exported to {COLD_SIGN_DIR}, drop the .sig files here"),
+                text_style.clone(),
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ExportList,
+            ));
+            button(parent, &text_style, "Back to Main", ColdAction::Back);
+        });
+}
+
+fn cold_sign_action(
+    interaction_query: Query<(&Interaction, &ColdAction), Changed<Interaction>>,
+    fields: Query<(&Text, &ColdField)>,
+    book: Res<AddressBook>,
+    mut form: ResMut<ColdForm>,
+    mut exports: ResMut<Exports>,
+    mut client: ResMut<ChainClient>,
+    mut toasts: EventWriter<Toast>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let value = |field: ColdField| {
+        fields
+            .iter()
+            .find(|(_, text_field)| **text_field == field)
+            .map_or(String::new(), |(text, _)| text.sections[0].value.clone())
+    };
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let account = book.resolve(&value(ColdField::Account));
+        let exported = match action {
+            ColdAction::Focus(field) => {
+                form.focus = *field;
+                continue;
+            }
+            ColdAction::Species(species) => {
+                form.species = *species;
+                continue;
+            }
+            ColdAction::ExportMint => {
+                let name = value(ColdField::Name);
+                let call = polkadot::tx().pet_module().mint(
+                    polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec(
+                        name.clone().into_bytes(),
+                    ),
+                    species_from_name(form.species),
+                    1,
+                );
+                let description = format!("Mint {name} the {}", form.species);
+                export(&mut client, &account, description, &call)
+            }
+            ColdAction::ExportTransfer => {
+                let recipient = book.resolve(&value(ColdField::Recipient));
+                parse_account(&recipient).and_then(|to| {
+                    let call = polkadot::tx().pet_module().transfer(to);
+                    let description = format!("Transfer the pet to {recipient}");
+                    export(&mut client, &account, description, &call)
+                })
+            }
+            ColdAction::Broadcast(id) => {
+                let result = broadcast(&mut client, id);
+                match result {
+                    Ok(()) => toasts.send(Toast(format!("Sent {id}"))),
+                    Err(e) => toasts.send(Toast(format!("Can't send {id}: {e}"))),
+                }
+                *exports = read_exports();
+                continue;
+            }
+            ColdAction::Back => {
+                game_state.set(GameState::Menu);
+                continue;
+            }
+        };
+        match exported {
+            Ok(path) => {
+                toasts.send(Toast(format!("Exported to {path}, sign its payload")));
+                *exports = read_exports();
+            }
+            Err(e) => toasts.send(Toast(format!("Can't export: {e}"))),
+        }
+    }
+}
+
+// Make the focused address field the one typed in
+fn focus_field(mut commands: Commands, form: Res<ColdForm>, fields: Query<(Entity, &ColdField)>) {
+    if !form.is_changed() {
+        return;
+    }
+    for (entity, field) in &fields {
+        if *field == form.focus && *field != ColdField::Name {
+            commands.entity(entity).insert(AddressField);
+        } else {
+            commands.entity(entity).remove::<AddressField>();
+        }
+    }
+}
+
+fn type_name(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    form: Res<ColdForm>,
+    mut fields: Query<(&mut Text, &ColdField)>,
+) {
+    let typed: Vec<char> = events.iter().map(|event| event.char).collect();
+    if form.focus != ColdField::Name {
+        return;
+    }
+    for (mut text, field) in &mut fields {
+        if *field != ColdField::Name {
+            continue;
+        }
+        let name = &mut text.sections[0].value;
+        if kbd.just_pressed(KeyCode::Back) {
+            naming::pop_character(name);
+        }
+        name.extend(typed.iter().filter(|c| !c.is_control()));
+    }
+}
+
+// Bring back the signature of an export, dropped as a file named after it
+fn drop_signature(
+    mut events: EventReader<FileDragAndDrop>,
+    mut exports: ResMut<Exports>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for event in events.iter() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if path_buf
+            .extension()
+            .map_or(true, |extension| extension != "sig")
+        {
+            continue;
+        }
+        let Some(id) = path_buf.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !exports.0.iter().any(|(export, _, _)| export.as_str() == id) {
+            toasts.send(Toast(format!("No call was exported as {id}")));
+            continue;
+        }
+        match std::fs::copy(path_buf, signature_path(id)) {
+            Ok(_) => {
+                toasts.send(Toast(format!("Signature of {id} added")));
+                *exports = read_exports();
+            }
+            Err(e) => toasts.send(Toast(format!("Can't add the signature of {id}: {e}"))),
+        }
+    }
+}
+
+// One line per export, with a button to send it once it's signed
+fn show_exports(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    exports: Res<Exports>,
+    list: Query<Entity, With<ExportList>>,
+) {
+    if !exports.is_changed() {
+        return;
+    }
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .entity(list)
+        .despawn_descendants()
+        .with_children(|parent| {
+            if exports.0.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "Nothing exported",
+                    text_style.clone(),
+                ));
+            }
+            for (id, export, signed) in &exports.0 {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{id}: {}", export.description),
+                            text_style.clone(),
+                        ));
+                        if *signed {
+                            button(
+                                parent,
+                                &text_style,
+                                "Broadcast",
+                                ColdAction::Broadcast(id.clone()),
+                            );
+                        } else {
+                            parent.spawn(TextBundle::from_section(
+                                "waiting for its signature",
+                                text_style.clone(),
+                            ));
+                        }
+                    });
+            }
+        });
+}
+
+// Highlight the focused field and the chosen species
+fn color_choices(
+    form: Res<ColdForm>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &ColdAction, &mut BackgroundColor)>,
+) {
+    for (interaction, action, mut color) in &mut buttons {
+        if !form.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = match action {
+            ColdAction::Focus(field) => *field == form.focus,
+            ColdAction::Species(species) => *species == form.species,
+            _ => false,
+        };
+        *color = palette.button(*interaction, selected).into();
+    }
+}
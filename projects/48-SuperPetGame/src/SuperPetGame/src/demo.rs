@@ -0,0 +1,419 @@
+use bevy::prelude::*;
+use sp_core::Pair as _;
+use sp_keyring::sr25519::sr25519::Pair;
+use sp_keyring::AccountKeyring;
+use subxt::dynamic::Value;
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{AccountChanged, ChainClient, PetSpecies},
+    clock::ChainClock,
+    onboarding::{mint_call, new_pet_id, FAUCET_AMOUNT},
+    pet_watch::{self, PlayerPet},
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Longest a step may take, until its calls are finalized, before the round starts over
+const STEP_SECONDS: f64 = 90.0;
+
+// Shortest time a caption stays up, so visitors have time to read it
+const CAPTION_SECONDS: f64 = 4.0;
+
+// Pause between two rounds, and before starting over after a failure
+const REST_SECONDS: f64 = 10.0;
+
+// This plugin runs the game as an unattended booth demo when started with `--demo`. Each
+// round, Alice funds a throwaway visitor account which mints a pet, then whoever of Alice and
+// Bob holds the booth pet feeds it, puts it to sleep and gives it to the other one. New pets
+// start as eggs, so the booth pet is the one cared for, and Alice mints it the first time.
+// A caption tells visitors what's going on. The calls go through the transaction queue like
+// the player's, and a step that fails or doesn't show up on chain in time starts the round
+// over, so the demo gets going again by itself after the node restarts. It needs a
+// development chain, where Alice and Bob have tokens.
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        if !std::env::args().any(|arg| arg == "--demo") {
+            return;
+        }
+        app.add_startup_systems((demo_setup, caption_setup))
+            .add_systems((
+                stay_in_game,
+                // Once the pet of the account switched to is forgotten, not to take it for
+                // the new account's
+                run_demo.after(pet_watch::rebind_player_pet),
+                show_caption.after(run_demo),
+            ));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Role {
+    Alice,
+    Bob,
+    Visitor,
+}
+
+impl Role {
+    fn name(self) -> &'static str {
+        match self {
+            Role::Alice => "Alice",
+            Role::Bob => "Bob",
+            Role::Visitor => "The visitor",
+        }
+    }
+
+    // The one the booth pet goes to next
+    fn other(self) -> Role {
+        match self {
+            Role::Alice => Role::Bob,
+            _ => Role::Alice,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Step {
+    // Alice sends a new visitor account tokens for its fees
+    Fund,
+    // The visitor mints a pet
+    Mint,
+    // Look for the booth pet at Alice's, then Bob's
+    FindHolder(Role),
+    // Alice mints the booth pet, when nobody holds it
+    MintBoothPet,
+    // The holder feeds the booth pet, or hatches it once its egg is ready
+    Feed,
+    Sleep,
+    // The holder gives the booth pet to the other one
+    Give,
+    Rest,
+}
+
+// What a step waits for once its call is queued
+enum Await {
+    // The queued calls are finalized
+    Finalized,
+    // The player's pet is there, or gone
+    Pet(bool),
+    // The care time moved from the one before the call
+    Fed(Option<u32>),
+    Slept(Option<u32>),
+    Hatched,
+    // Only the caption, no call was made
+    Caption,
+}
+
+impl Await {
+    fn done(&self, player_pet: &PlayerPet, queue: &TxQueue) -> bool {
+        match self {
+            Await::Finalized => queue.pending_count() == (0, 0),
+            Await::Pet(owned) => player_pet.pet.is_some() == *owned,
+            Await::Fed(before) => player_pet.last_feed_block != *before,
+            Await::Slept(before) => player_pet.last_sleep_block != *before,
+            Await::Hatched => player_pet.hatch_block.is_none(),
+            Await::Caption => true,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Demo {
+    step: Step,
+    // What the step waits for and the step after, once its call is queued
+    awaiting: Option<(Await, Step)>,
+    started_at: f64,
+    caption: String,
+    // Indexes in the accounts of the client
+    alice: usize,
+    bob: usize,
+    visitor: Option<usize>,
+    // Who holds the booth pet
+    holder: Role,
+    round: u32,
+}
+
+impl Demo {
+    fn start(&mut self, step: Step, now: f64, caption: impl Into<String>) {
+        self.step = step;
+        self.awaiting = None;
+        self.started_at = now;
+        self.caption = caption.into();
+    }
+
+    fn start_over(&mut self, now: f64, reason: &str) {
+        warn!("demo round {} failed: {reason}", self.round);
+        self.start(
+            Step::Rest,
+            now,
+            format!("Something went wrong, starting over ({reason})"),
+        );
+    }
+
+    // The account signing the calls of the step
+    fn signer(&self) -> Option<usize> {
+        let role = match self.step {
+            Step::Fund | Step::MintBoothPet => Role::Alice,
+            Step::Mint => Role::Visitor,
+            Step::FindHolder(role) => role,
+            Step::Feed | Step::Sleep | Step::Give => self.holder,
+            Step::Rest => return None,
+        };
+        match role {
+            Role::Alice => Some(self.alice),
+            Role::Bob => Some(self.bob),
+            Role::Visitor => self.visitor,
+        }
+    }
+}
+
+// Caption shown over every screen
+#[derive(Component)]
+struct DemoCaption;
+
+// Play as Alice and Bob whatever the keystore holds, in memory only
+fn demo_setup(mut commands: Commands, mut client: ResMut<ChainClient>) {
+    let alice = client.add_session_account("Alice", AccountKeyring::Alice.pair());
+    let bob = client.add_session_account("Bob", AccountKeyring::Bob.pair());
+    info!("running the booth demo");
+    commands.insert_resource(Demo {
+        step: Step::Rest,
+        awaiting: None,
+        started_at: 0.0,
+        caption: "Welcome to Super Pet Game!".to_string(),
+        alice,
+        bob,
+        visitor: None,
+        holder: Role::Alice,
+        round: 0,
+    });
+}
+
+fn caption_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_section(
+            String::new(),
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 28.0,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(60.0),
+                left: Val::Px(20.0),
+                right: Val::Px(20.0),
+                ..default()
+            },
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        DemoCaption,
+    ));
+}
+
+fn show_caption(demo: Res<Demo>, mut captions: Query<&mut Text, With<DemoCaption>>) {
+    if !demo.is_changed() {
+        return;
+    }
+    for mut text in &mut captions {
+        text.sections[0].value = demo.caption.clone();
+    }
+}
+
+// The booth shows the pet, wherever visitors click to
+fn stay_in_game(state: Res<State<GameState>>, mut game_state: ResMut<NextState<GameState>>) {
+    if !matches!(state.0, GameState::Splash | GameState::Game) {
+        game_state.set(GameState::Game);
+    }
+}
+
+// Move the script along: play as the account of the step, queue its call once the account's
+// pet is read, and go on once the call shows up on chain and the caption was read
+fn run_demo(
+    time: Res<Time>,
+    mut demo: ResMut<Demo>,
+    mut client: ResMut<ChainClient>,
+    mut queue: ResMut<TxQueue>,
+    player_pet: Res<PlayerPet>,
+    clock: Res<ChainClock>,
+    mut account_changed: EventWriter<AccountChanged>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let elapsed = now - demo.started_at;
+    if let Some(error) = queue.last_error().map(str::to_string) {
+        queue.clear_error();
+        demo.start_over(now, &error);
+        return;
+    }
+    if demo.step != Step::Rest && elapsed > STEP_SECONDS {
+        demo.start_over(now, "the chain didn't answer in time");
+        return;
+    }
+    let Some(signer) = demo.signer() else {
+        if elapsed >= REST_SECONDS {
+            demo.round += 1;
+            let caption = caption(Step::Fund, &demo, &player_pet);
+            demo.start(Step::Fund, now, caption);
+        }
+        return;
+    };
+    // The calls of the previous account are finalized first, switching drops the queued ones
+    let idle = queue.pending_count() == (0, 0);
+    if client.active_account() != signer {
+        if idle && client.switch_account(signer) {
+            account_changed.send(AccountChanged);
+        }
+        return;
+    }
+    if !player_pet.loaded() {
+        return;
+    }
+
+    if let Some((awaiting, next)) = &demo.awaiting {
+        if elapsed >= CAPTION_SECONDS && awaiting.done(&player_pet, &queue) {
+            let next = *next;
+            if demo.step == Step::Give {
+                demo.holder = demo.holder.other();
+            }
+            let caption = caption(next, &demo, &player_pet);
+            demo.start(next, now, caption);
+        }
+        return;
+    }
+    let pet = player_pet.pet.as_ref();
+    let pet_name = pet.map_or("the pet".to_string(), |pet| pet.name.clone());
+    let awaiting = match demo.step {
+        Step::Fund => {
+            if !idle {
+                return;
+            }
+            let (pair, _) = Pair::generate();
+            let account: AccountId32 = pair.public().0.into();
+            let name = format!("Visitor {}", demo.round);
+            demo.visitor = Some(client.add_session_account(&name, pair));
+            queue.push(
+                format!("Fund {name}"),
+                subxt::dynamic::tx(
+                    "Balances",
+                    "transfer",
+                    vec![
+                        Value::unnamed_variant("Id", [Value::from_bytes(&account)]),
+                        Value::u128(FAUCET_AMOUNT),
+                    ],
+                ),
+            );
+            (Await::Finalized, Step::Mint)
+        }
+        Step::Mint | Step::MintBoothPet => {
+            let name = match demo.step {
+                Step::Mint => format!("Demo {}", demo.round),
+                _ => "Booth".to_string(),
+            };
+            let id = new_pet_id(&client.account_id());
+            queue.push(
+                format!("Mint {name}"),
+                mint_call(&name, PetSpecies::Turtle, id),
+            );
+            let next = match demo.step {
+                Step::Mint => Step::FindHolder(Role::Alice),
+                _ => Step::Feed,
+            };
+            (Await::Pet(true), next)
+        }
+        Step::FindHolder(role) => {
+            let next = match (pet, role) {
+                (Some(_), _) => Step::Feed,
+                (None, Role::Alice) => Step::FindHolder(Role::Bob),
+                (None, _) => Step::MintBoothPet,
+            };
+            let role = if pet.is_some() { role } else { Role::Alice };
+            demo.holder = role;
+            let caption = caption(next, &demo, &player_pet);
+            demo.start(next, now, caption);
+            return;
+        }
+        Step::Feed => {
+            let id = pet.map(|pet| pet.id);
+            match player_pet.hatch_block {
+                Some(hatch) if clock.head().map_or(true, |head| head < hatch) => {
+                    demo.caption = format!(
+                        "{pet_name} is still an egg, it hatches {}",
+                        clock.block(hatch)
+                    );
+                    (Await::Caption, Step::Give)
+                }
+                Some(_) => {
+                    let Some(_) = queue.push_pet_call(&client, id, "Hatch", "hatch", Vec::new())
+                    else {
+                        return;
+                    };
+                    demo.caption = format!("{} hatches {pet_name}", demo.holder.name());
+                    (Await::Hatched, Step::Sleep)
+                }
+                None => {
+                    let Some(_) = queue.push_pet_call(&client, id, "Feed", "feed", Vec::new())
+                    else {
+                        return;
+                    };
+                    (Await::Fed(player_pet.last_feed_block), Step::Sleep)
+                }
+            }
+        }
+        Step::Sleep => {
+            let id = pet.map(|pet| pet.id);
+            let Some(_) = queue.push_pet_call(&client, id, "Sleep", "sleep", Vec::new()) else {
+                return;
+            };
+            (Await::Slept(player_pet.last_sleep_block), Step::Give)
+        }
+        Step::Give => {
+            let keyring = match demo.holder.other() {
+                Role::Bob => AccountKeyring::Bob,
+                _ => AccountKeyring::Alice,
+            };
+            let to: AccountId32 = keyring.public().0.into();
+            let id = pet.map(|pet| pet.id);
+            let args = vec![Value::from_bytes(to)];
+            let Some(_) = queue.push_pet_call(&client, id, "Give", "transfer", args) else {
+                return;
+            };
+            (Await::Pet(false), Step::Rest)
+        }
+        Step::Rest => return,
+    };
+    demo.awaiting = Some(awaiting);
+}
+
+// What a step is about to do, told to the visitors
+fn caption(step: Step, demo: &Demo, player_pet: &PlayerPet) -> String {
+    let holder = demo.holder.name();
+    let booth_pet = player_pet
+        .pet
+        .as_ref()
+        .map_or("the booth pet".to_string(), |pet| pet.name.clone());
+    match step {
+        Step::Fund => "Alice sends tokens to a new visitor, to pay for its fees".to_string(),
+        Step::Mint => format!(
+            "{} mints Demo {}, a new pet starts as an egg",
+            Role::Visitor.name(),
+            demo.round
+        ),
+        Step::FindHolder(_) => "Looking for the booth pet".to_string(),
+        Step::MintBoothPet => "Nobody holds the booth pet yet, Alice mints it".to_string(),
+        Step::Feed => format!("{holder} feeds {booth_pet}"),
+        Step::Sleep => format!("{holder} puts {booth_pet} to sleep"),
+        Step::Give => format!(
+            "{holder} gives {booth_pet} to {}",
+            demo.holder.other().name()
+        ),
+        Step::Rest => "That's it! Another round starts in a few seconds".to_string(),
+    }
+}
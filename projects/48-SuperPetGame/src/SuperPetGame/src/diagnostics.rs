@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use super::{client::ChainClient, txqueue::TxQueue};
+
+// Time between two reads of the chain head, measuring the round trip to the node
+const PROBE_SECONDS: f32 = 2.0;
+
+// Time systems spent blocked on the node in the last frame, in milliseconds
+const CHAIN_BLOCKED: DiagnosticId =
+    DiagnosticId::from_u128(0x7c1e_2b8a_53d4_4f0e_9a61_0d2c_8e35_b147);
+// Round trip of a request to the node, in milliseconds
+const CHAIN_ROUND_TRIP: DiagnosticId =
+    DiagnosticId::from_u128(0x7c1e_2b8a_53d4_4f0e_9a61_0d2c_8e35_b148);
+
+// This plugin measures the chain I/O next to the frame rate, and shows both in an overlay
+// toggled with F3, or shown from the start with `--diagnostics`. A stutter with a high
+// blocked time comes from a system waiting for the node, one without from rendering. The
+// chain head is read in the background, so measuring doesn't stall the frame itself.
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugin(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<ChainProbe>()
+            .add_startup_system(setup_diagnostics)
+            .add_systems((measure_chain, toggle_overlay, overlay_text).chain());
+    }
+}
+
+#[derive(Resource)]
+struct ChainProbe {
+    timer: Timer,
+    // Set by the background read when it's done, the chain head or why it couldn't be read
+    result: Arc<Mutex<Option<Result<u32, String>>>>,
+    in_flight: Option<Instant>,
+    last_block: Option<u32>,
+    last_error: Option<String>,
+}
+
+impl Default for ChainProbe {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(PROBE_SECONDS, TimerMode::Repeating),
+            result: Arc::default(),
+            in_flight: None,
+            last_block: None,
+            last_error: None,
+        }
+    }
+}
+
+// Tag component used to tag the overlay
+#[derive(Component)]
+struct OnDiagnosticsOverlay;
+
+fn setup_diagnostics(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    diagnostics.add(Diagnostic::new(CHAIN_BLOCKED, "chain_blocked", 120).with_suffix("ms"));
+    diagnostics.add(Diagnostic::new(CHAIN_ROUND_TRIP, "chain_round_trip", 10).with_suffix("ms"));
+    if std::env::args().any(|arg| arg == "--diagnostics") {
+        spawn_overlay(&mut commands, &asset_server);
+    }
+}
+
+// Record the time blocked on the node every frame, and read the chain head now and then
+fn measure_chain(
+    time: Res<Time>,
+    client: Res<ChainClient>,
+    mut probe: ResMut<ChainProbe>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    let blocked = client.take_blocked_time();
+    diagnostics.add_measurement(CHAIN_BLOCKED, || blocked.as_secs_f64() * 1000.0);
+
+    if let Some(started) = probe.in_flight {
+        let Some(result) = probe.result.lock().unwrap().take() else {
+            return;
+        };
+        probe.in_flight = None;
+        match result {
+            Ok(block) => {
+                let round_trip = started.elapsed().as_secs_f64() * 1000.0;
+                diagnostics.add_measurement(CHAIN_ROUND_TRIP, || round_trip);
+                probe.last_block = Some(block);
+                probe.last_error = None;
+            }
+            Err(e) => probe.last_error = Some(e),
+        }
+    }
+    if !probe.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    // Connecting would block the frame, the other systems connect when they need the node
+    let Some(api) = client.connected_api() else {
+        probe.last_error = Some("not connected".to_string());
+        return;
+    };
+    let result = probe.result.clone();
+    probe.in_flight = Some(Instant::now());
+    client.spawn(async move {
+        let head = match api.rpc().header(None).await {
+            Ok(Some(header)) => Ok(header.number),
+            Ok(None) => Err("no chain head".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        *result.lock().unwrap() = Some(head);
+    });
+}
+
+fn spawn_overlay(commands: &mut Commands, asset_server: &AssetServer) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 14.0,
+                color: Color::rgb(0.8, 1.0, 0.8),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                top: Val::Px(10.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        ZIndex::Global(20),
+        OnDiagnosticsOverlay,
+    ));
+}
+
+fn toggle_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    keys: Res<Input<KeyCode>>,
+    overlays: Query<Entity, With<OnDiagnosticsOverlay>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+    match overlays.get_single() {
+        Ok(overlay) => commands.entity(overlay).despawn_recursive(),
+        Err(_) => spawn_overlay(&mut commands, &asset_server),
+    }
+}
+
+fn millis(diagnostics: &Diagnostics, id: DiagnosticId) -> String {
+    diagnostics
+        .get(id)
+        .and_then(Diagnostic::smoothed)
+        .map_or_else(|| "-".to_string(), |ms| format!("{ms:.1} ms"))
+}
+
+fn overlay_text(
+    diagnostics: Res<Diagnostics>,
+    probe: Res<ChainProbe>,
+    queue: Res<TxQueue>,
+    mut texts: Query<&mut Text, With<OnDiagnosticsOverlay>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(Diagnostic::smoothed)
+        .map_or_else(|| "-".to_string(), |fps| format!("{fps:.0}"));
+    let block = match (probe.last_block, &probe.last_error) {
+        (_, Some(e)) => format!("unknown ({e})"),
+        (Some(block), None) => format!("#{block}"),
+        (None, None) => "-".to_string(),
+    };
+    // A read taking longer than the probe interval is a round trip in itself
+    let round_trip = match probe.in_flight.map(|started| started.elapsed()) {
+        Some(waiting) if waiting > Duration::from_secs_f32(PROBE_SECONDS) => {
+            format!("> {} ms", waiting.as_millis())
+        }
+        _ => millis(&diagnostics, CHAIN_ROUND_TRIP),
+    };
+    let (queued, in_flight) = queue.pending_count();
+    let value = format!(
+        "Diagnostics (F3)\nFPS: {fps}\nBlocked on the node: {}\n\
+         Last block: {block}\nRPC round trip: {round_trip}\n\
+         Pending tx: {queued} queued, {in_flight} not finalized",
+        millis(&diagnostics, CHAIN_BLOCKED),
+    );
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
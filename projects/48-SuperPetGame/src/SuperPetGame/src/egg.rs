@@ -0,0 +1,250 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use super::{
+    assets::GameAssets,
+    client::ChainClient,
+    despawn_screen,
+    game::{PlayerSprite, Spectating},
+    pet_watch::PlayerPet,
+    poll::{PollScheduler, Priority},
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Block time of the chain, the countdown runs on between two reads of the chain head
+const BLOCK_SECONDS: f32 = 6.0;
+
+// This plugin shows the player's pet as an egg until it hatches, with a countdown to its
+// hatch block and a button to hatch it once the block is reached
+pub struct EggPlugin;
+
+impl Plugin for EggPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            egg_setup.in_schedule(OnEnter(GameState::Game)),
+            despawn_screen::<OnEggScreen>.in_schedule(OnExit(GameState::Game)),
+        ))
+        .add_systems((show_egg, egg_countdown, hatch_action).in_set(OnUpdate(GameState::Game)));
+    }
+}
+
+// Tag component used to tag the egg entities of the game screen
+#[derive(Component)]
+struct OnEggScreen;
+
+#[derive(Component)]
+struct EggSprite;
+
+#[derive(Component)]
+struct EggPanel;
+
+#[derive(Component)]
+struct EggCountdown;
+
+#[derive(Component)]
+struct HatchButton;
+
+fn egg_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    player_pet: Res<PlayerPet>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    spectating: Res<Spectating>,
+) {
+    if spectating.0.is_some() {
+        return;
+    }
+    // An egg is a stretched circle, unless a skin draws the eggs of the species
+    let skin = player_pet
+        .pet
+        .as_ref()
+        .and_then(|pet| assets.egg_image(&pet.species));
+    match skin {
+        Some(texture) => commands.spawn((
+            SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(100.0)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            EggSprite,
+            OnEggScreen,
+        )),
+        None => commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(50.0).into()).into(),
+                material: materials.add(ColorMaterial::from(Color::rgb(0.96, 0.92, 0.8))),
+                transform: Transform::from_scale(Vec3::new(0.8, 1.0, 1.0)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            EggSprite,
+            OnEggScreen,
+        )),
+    };
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 22.0,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(80.0),
+                        left: Val::Px(0.0),
+                        right: Val::Px(0.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    gap: Size::all(Val::Px(8.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            EggPanel,
+            OnEggScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                EggCountdown,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(6.0)),
+                            display: Display::None,
+                            ..default()
+                        },
+                        background_color: Color::DARK_GREEN.into(),
+                        ..default()
+                    },
+                    HatchButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Hatch", text_style));
+                });
+        });
+}
+
+// Swap the pet sprite for the egg while the pet hasn't hatched, and rock the egg
+fn show_egg(
+    time: Res<Time>,
+    player_pet: Res<PlayerPet>,
+    mut eggs: Query<(&mut Visibility, &mut Transform), (With<EggSprite>, Without<PlayerSprite>)>,
+    mut pets: Query<&mut Visibility, (With<PlayerSprite>, Without<EggSprite>)>,
+    mut panels: Query<&mut Style, With<EggPanel>>,
+) {
+    let is_egg = player_pet.hatch_block.is_some();
+    let (egg_visibility, pet_visibility, display) = if is_egg {
+        (Visibility::Inherited, Visibility::Hidden, Display::Flex)
+    } else {
+        (Visibility::Hidden, Visibility::Inherited, Display::None)
+    };
+    for (mut visibility, mut transform) in &mut eggs {
+        if *visibility != egg_visibility {
+            *visibility = egg_visibility;
+        }
+        if is_egg {
+            transform.rotation = Quat::from_rotation_z((time.elapsed_seconds() * 2.0).sin() * 0.1);
+        }
+    }
+    for mut visibility in &mut pets {
+        if *visibility != pet_visibility {
+            *visibility = pet_visibility;
+        }
+    }
+    for mut style in &mut panels {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+// Time left before the hatch block, from the chain head read every block and the time
+// since it was read
+fn egg_countdown(
+    time: Res<Time>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    mut scheduler: ResMut<PollScheduler>,
+    mut head: Local<Option<(u32, Timer)>>,
+    mut texts: Query<&mut Text, With<EggCountdown>>,
+    mut buttons: Query<&mut Style, With<HatchButton>>,
+) {
+    let Some(hatch_block) = player_pet.hatch_block else {
+        *head = None;
+        scheduler.forget("egg countdown");
+        return;
+    };
+    if let Some((_, timer)) = head.as_mut() {
+        timer.tick(time.delta());
+    }
+    if scheduler.ready("egg countdown", Priority::High, BLOCK_SECONDS) {
+        let block = client.api().and_then(|api| {
+            client
+                .block_on(async { Ok::<_, subxt::Error>(api.blocks().at_latest().await?.number()) })
+        });
+        match block {
+            Ok(block) => *head = Some((block, Timer::from_seconds(BLOCK_SECONDS, TimerMode::Once))),
+            Err(e) => {
+                error!("error reading the chain head: {e:?}");
+                return;
+            }
+        }
+    }
+    let Some((block, timer)) = head.as_ref() else {
+        return;
+    };
+
+    let seconds = hatch_block.saturating_sub(*block) as f32 * BLOCK_SECONDS - timer.elapsed_secs();
+    let ready = *block >= hatch_block;
+    let value = if ready {
+        "Ready to hatch!".to_string()
+    } else {
+        let seconds = seconds.max(1.0) as u32;
+        format!(
+            "Hatches in {}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+    let display = if ready { Display::Flex } else { Display::None };
+    for mut style in &mut buttons {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+fn hatch_action(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<HatchButton>)>,
+    mut queue: ResMut<TxQueue>,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let pet = player_pet.pet.as_ref().map(|pet| pet.id);
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            queue.push_pet_call(&client, pet, "Hatch", "hatch", Vec::new());
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use super::{
+    despawn_screen,
+    game::{PlayerSprite, Spectating},
+    recap, GameState, TEXT_COLOR,
+};
+
+// How quickly the pet turns to the cursor, follows the toy and notices the cursor speed, the
+// higher the snappier
+const LOOK_RATE: f32 = 6.0;
+const FOLLOW_RATE: f32 = 2.0;
+const SPEED_RATE: f32 = 10.0;
+
+// Widest lean toward the cursor, in radians, reached with the cursor this far to the side
+const MAX_LEAN: f32 = 0.3;
+const LEAN_DISTANCE: f32 = 250.0;
+
+// A cursor faster than this (pixels per second), this close to the pet, startles it
+const STARTLE_SPEED: f32 = 2500.0;
+const STARTLE_RADIUS: f32 = 200.0;
+const STARTLE_SECONDS: f32 = 0.6;
+
+// Distance the pet keeps from the toy, it never quite catches it
+const TOY_REACH: f32 = 40.0;
+
+// This plugin makes the player's pet react to the cursor on the game screen: it leans toward
+// it, jumps when the cursor rushes past, and with T it chases a toy dangled from the cursor
+pub struct EmotesPlugin;
+
+impl Plugin for EmotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Emote>()
+            .add_systems((emotes_setup, reset_emote).in_schedule(OnEnter(GameState::Game)))
+            .add_systems(
+                (despawn_screen::<OnEmotes>, drop_toy).in_schedule(OnExit(GameState::Game)),
+            )
+            .add_systems(
+                (toggle_toy, track_cursor, pet_emotes)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Game))
+                    .distributive_run_if(recap::recap_finished),
+            );
+    }
+}
+
+// Tag component used to tag entities added by this plugin on the game screen
+#[derive(Component)]
+struct OnEmotes;
+
+#[derive(Component)]
+struct Toy;
+
+#[derive(Resource, Default)]
+struct Emote {
+    // Cursor in world coordinates, and its smoothed speed
+    cursor: Option<Vec2>,
+    cursor_speed: f32,
+    lean: f32,
+    startled: Option<Timer>,
+    toy: bool,
+    // Where the pet stands, before jumps
+    position: Vec2,
+}
+
+// Fraction of the way to a target covered in `dt`, at `rate`, the same whatever the frame rate
+fn smoothing(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
+
+fn emotes_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    spectating: Res<Spectating>,
+) {
+    if spectating.0.is_some() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "T: dangle a toy",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 16.0,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
+        OnEmotes,
+    ));
+}
+
+fn reset_emote(mut emote: ResMut<Emote>) {
+    *emote = Emote::default();
+}
+
+fn drop_toy(mut emote: ResMut<Emote>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    emote.toy = false;
+    for mut window in &mut windows {
+        window.cursor.visible = true;
+    }
+}
+
+// The toy replaces the cursor while it's out
+fn toggle_toy(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    spectating: Res<Spectating>,
+    mut emote: ResMut<Emote>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    toys: Query<Entity, With<Toy>>,
+) {
+    if spectating.0.is_some() || !keys.just_pressed(KeyCode::T) {
+        return;
+    }
+    emote.toy = !emote.toy;
+    for mut window in &mut windows {
+        window.cursor.visible = !emote.toy;
+    }
+    if !emote.toy {
+        for toy in &toys {
+            commands.entity(toy).despawn_recursive();
+        }
+        return;
+    }
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::ORANGE,
+                    custom_size: Some(Vec2::splat(16.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..default()
+            },
+            Toy,
+            OnEmotes,
+        ))
+        .with_children(|parent| {
+            // The string the toy hangs from
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::WHITE,
+                    custom_size: Some(Vec2::new(2.0, 60.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 38.0, 0.0),
+                ..default()
+            });
+        });
+}
+
+// Read where the cursor is in the world, and how fast it goes
+fn track_cursor(
+    time: Res<Time>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut emote: ResMut<Emote>,
+) {
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .and_then(|position| {
+            let (camera, transform) = cameras.get_single().ok()?;
+            camera.viewport_to_world_2d(transform, position)
+        });
+    let dt = time.delta_seconds();
+    match (emote.cursor, cursor) {
+        (Some(from), Some(to)) if dt > 0.0 => {
+            let speed = from.distance(to) / dt;
+            emote.cursor_speed += (speed - emote.cursor_speed) * smoothing(SPEED_RATE, dt);
+        }
+        (Some(_), Some(_)) => {}
+        _ => emote.cursor_speed = 0.0,
+    }
+    emote.cursor = cursor;
+}
+
+fn pet_emotes(
+    time: Res<Time>,
+    mut emote: ResMut<Emote>,
+    mut pets: Query<(&mut Transform, &mut TextureAtlasSprite), With<PlayerSprite>>,
+    mut toys: Query<&mut Transform, (With<Toy>, Without<PlayerSprite>)>,
+) {
+    let Ok((mut transform, mut sprite)) = pets.get_single_mut() else {
+        return;
+    };
+    let emote = &mut *emote;
+    let dt = time.delta_seconds();
+
+    match &mut emote.startled {
+        Some(timer) => {
+            if timer.tick(time.delta()).finished() {
+                emote.startled = None;
+            }
+        }
+        None => {
+            let close = emote.cursor.map_or(false, |cursor| {
+                cursor.distance(emote.position) < STARTLE_RADIUS
+            });
+            if close && emote.cursor_speed > STARTLE_SPEED {
+                emote.startled = Some(Timer::from_seconds(STARTLE_SECONDS, TimerMode::Once));
+            }
+        }
+    }
+
+    // Chase the toy, or walk back to the middle once it's put away
+    let target = match (emote.toy, emote.cursor) {
+        (true, Some(cursor)) => {
+            let offset = cursor - emote.position;
+            (offset.length() > TOY_REACH).then(|| cursor - offset.normalize() * TOY_REACH)
+        }
+        (true, None) => None,
+        (false, _) => Some(Vec2::ZERO),
+    };
+    if let Some(target) = target {
+        emote.position = emote.position.lerp(target, smoothing(FOLLOW_RATE, dt));
+    }
+    if let (true, Some(cursor)) = (emote.toy, emote.cursor) {
+        for mut toy in &mut toys {
+            toy.translation = cursor.extend(toy.translation.z);
+        }
+    }
+
+    if let Some(cursor) = emote.cursor {
+        let side = ((cursor.x - emote.position.x) / LEAN_DISTANCE).clamp(-1.0, 1.0);
+        emote.lean += (side * MAX_LEAN - emote.lean) * smoothing(LOOK_RATE, dt);
+        sprite.flip_x = side < 0.0;
+    }
+
+    // A startled pet jumps and shakes, the shake fading as it lands
+    let (hop, shake) = match &emote.startled {
+        Some(timer) => (
+            (timer.percent() * PI).sin() * 30.0,
+            (timer.elapsed_secs() * 60.0).sin() * 0.2 * timer.percent_left(),
+        ),
+        None => (0.0, 0.0),
+    };
+    transform.translation.x = emote.position.x;
+    transform.translation.y = emote.position.y + hop;
+    transform.rotation = Quat::from_rotation_z(-emote.lean + shake);
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use super::client::{format_balance, AnyCall, ChainClient};
+
+// This plugin fills in the estimated fee of a transaction next to the button submitting it,
+// with a warning when the player can't pay it.
+pub struct FeePlugin;
+
+impl Plugin for FeePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fee_text_system);
+    }
+}
+
+// Text showing the fee of the given call, estimated when the text is spawned
+#[derive(Component)]
+pub struct FeeText(pub AnyCall);
+
+const WARNING_COLOR: Color = Color::ORANGE_RED;
+
+fn fee_text_system(
+    mut client: ResMut<ChainClient>,
+    mut fee_texts: Query<(&FeeText, &mut Text), Added<FeeText>>,
+) {
+    for (FeeText(call), mut text) in &mut fee_texts {
+        let section = &mut text.sections[0];
+        match client.estimate_fee(call) {
+            Ok(estimate) if estimate.affordable() => {
+                section.value = format!("Fee ~{}", format_balance(estimate.fee));
+            }
+            Ok(estimate) => {
+                section.value = format!(
+                    "Fee ~{}, but you only have {}",
+                    format_balance(estimate.fee),
+                    format_balance(estimate.free)
+                );
+                section.style.color = WARNING_COLOR;
+            }
+            Err(e) => {
+                error!("error estimating fee: {e:?}");
+                section.value = "Fee unknown".to_string();
+            }
+        }
+    }
+}
@@ -1,5 +1,20 @@
 
-use super::{despawn_screen, GameState, PetOwned,TEXT_COLOR};
+use super::{
+    assets::GameAssets,
+    client::{ChainClient, PetId},
+    clock::ChainClock,
+    despawn_screen,
+    pet_watch::{CareRules, PlayerPet},
+    poll::{PollScheduler, Priority},
+    recap,
+    settings::Palette,
+    snapshot::PetSnapshot,
+    sounds::{Cry, PetCry},
+    tooltip::CallHelp,
+    txqueue::TxQueue,
+    upgrade::GameRules,
+    GameState, PetOwned, TEXT_COLOR,
+};
 // #[cfg(target_os = "macos")]
 use bevy::prelude::*;
 // This plugin will contain the game. In this case, it's just be a screen that will
@@ -18,13 +33,20 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<PlayMenuState>()
+            .init_resource::<Spectating>()
             .add_systems((
                 game_setup.in_schedule(OnEnter(GameState::Game)),
                 //.run_if(in_state(PetOwned::Enable)),
                 despawn_screen::<OnGameScreen>.in_schedule(OnExit(GameState::Game)),
+                pet_animation.in_set(OnUpdate(GameState::Game)),
+                pet_hud.in_set(OnUpdate(GameState::Game)),
+                streak_hud.in_set(OnUpdate(GameState::Game)),
             ))
             .add_systems((
-                play_menu_show.run_if(in_state(GameState::Game)),
+                // The player gets control back once the recap of their absence is over
+                play_menu_show
+                    .run_if(in_state(GameState::Game))
+                    .run_if(recap::recap_finished),
                 play_menu_setup.in_schedule(OnEnter(PlayMenuState::Show)),
                 despawn_screen::<OnPlayMenuScreen>.in_schedule(OnExit(PlayMenuState::Show)),
             ))
@@ -42,10 +64,52 @@ struct OnPlayMenuScreen;
 #[derive(Resource, Deref, DerefMut)]
 struct GameTimer(Timer);
 
+// Text showing the state of the player's pet
+#[derive(Component)]
+struct PetHud;
+
+// Flame and days of the care streak of the player's pet, hidden without a streak
+#[derive(Component)]
+struct StreakHud;
+
+#[derive(Component)]
+struct StreakText;
+
+// Sprite of the player's pet
+#[derive(Component)]
+pub struct PlayerSprite;
+
+// Pet of another account watched in spectator mode, shown read-only without keys
+#[derive(Resource, Default)]
+pub struct Spectating(pub Option<PetSnapshot>);
+
+// Block time of the chain, the HUD moves the stats on between two reads of the chain head
+const BLOCK_SECONDS: f32 = 6.0;
+
+// Mood of the displayed pet, derived from its on-chain care times
+#[derive(Component)]
+struct PetMood {
+    hungry: bool,
+    tired: bool,
+    // Sprite color from the pet's DNA
+    tint: Color,
+}
+
+impl PetMood {
+    fn from_snapshot(snapshot: &PetSnapshot, rules: &pet_core::Rules) -> Self {
+        let mood = snapshot.stats(rules).mood;
+        Self {
+            hungry: mood.is_hungry(),
+            tired: mood.is_tired(),
+            tint: snapshot.traits().map_or(Color::WHITE, |traits| {
+                let [r, g, b] = traits.tint;
+                Color::rgb_u8(r, g, b)
+            }),
+        }
+    }
+}
+
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.25, 0.65, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
 #[derive(Component)]
 enum PlayMenuButtonAction {
@@ -54,15 +118,126 @@ enum PlayMenuButtonAction {
     //WakeUpPet, //Wake up pet
     //SleepPet,//Make pet sleep
     //IdlePet, //Make pet into idle situation
+    Feed,
+    Sleep,
+    Battle,
+    Inventory,
     BackToMain,
 }
 
-fn game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn game_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    player_pet: Res<PlayerPet>,
+    care_rules: Res<CareRules>,
+    spectating: Res<Spectating>,
+) {
+    let Some(snapshot) = &spectating.0 else {
+        let species = player_pet
+            .pet
+            .as_ref()
+            .map_or("Turtle", |pet| pet.species.as_str());
+        commands.spawn((assets.pet_sprite(species), PlayerSprite, OnGameScreen));
+        commands.spawn((
+            TextBundle::from_section(
+                String::new(),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+            PetHud,
+            OnGameScreen,
+        ));
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Px(10.0),
+                            top: Val::Px(10.0),
+                            ..default()
+                        },
+                        align_items: AlignItems::Center,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StreakHud,
+                OnGameScreen,
+            ))
+            .with_children(|parent| {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(24.0), Val::Auto),
+                        margin: UiRect::right(Val::Px(6.0)),
+                        ..default()
+                    },
+                    image: UiImage::new(assets.icons.flame.clone()),
+                    background_color: Color::ORANGE.into(),
+                    ..default()
+                });
+                parent.spawn((
+                    TextBundle::from_section(
+                        String::new(),
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 20.0,
+                            color: TEXT_COLOR,
+                        },
+                    ),
+                    StreakText,
+                ));
+            });
+        // Spawn a 5 seconds timer to trigger going back to the menu
+        commands.insert_resource(GameTimer(Timer::from_seconds(5.0, TimerMode::Once)));
+        return;
+    };
+
+    // Spectator mode, the sprite follows the watched pet's species, DNA and mood
+    let size = snapshot.traits().map_or(100, |traits| traits.size);
     commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("../assets/textures/turtle-front2.png"),
-            ..default()
+        SpriteSheetBundle {
+            transform: Transform::from_scale(Vec3::splat(size as f32 / 100.0)),
+            ..assets.pet_sprite(&snapshot.species)
         },
+        PetMood::from_snapshot(snapshot, &care_rules.rules),
+        OnGameScreen,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            format!(
+                "Watching {} (read-only), right click to leave",
+                snapshot.name
+            ),
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 20.0,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                top: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
         OnGameScreen,
     ));
 
@@ -80,13 +255,156 @@ fn button_system(
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
         (Changed<Interaction>, With<Button>),
     >,
+    palette: Res<Palette>,
 ) {
     for (interaction, mut color, selected) in &mut interaction_query {
-        *color = match (*interaction, selected) {
-            (Interaction::Clicked, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON.into(),
-            (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON.into(),
-            (Interaction::Hovered, None) => HOVERED_BUTTON.into(),
-            (Interaction::None, None) => NORMAL_BUTTON.into(),
+        *color = palette.button(*interaction, selected.is_some()).into();
+    }
+}
+
+// Bob the pet up and down, slower when it's tired, and grey it out when it's hungry
+fn pet_animation(
+    time: Res<Time>,
+    mut pets: Query<(&PetMood, &mut Transform, &mut TextureAtlasSprite)>,
+) {
+    for (mood, mut transform, mut sprite) in &mut pets {
+        let speed = if mood.tired { 1.0 } else { 3.0 };
+        transform.translation.y = (time.elapsed_seconds() * speed).sin() * 6.0;
+        sprite.color = if mood.hungry {
+            mood.tint * 0.6
+        } else {
+            mood.tint
+        };
+    }
+}
+
+// Show the days in a row the player's pet was both fed and put to sleep, once it has a streak
+fn streak_hud(
+    player_pet: Res<PlayerPet>,
+    clock: Res<ChainClock>,
+    mut huds: Query<&mut Style, With<StreakHud>>,
+    mut texts: Query<&mut Text, With<StreakText>>,
+) {
+    let days = match (&player_pet.pet, clock.head()) {
+        (Some(_), Some(head)) => player_pet.care_streak(head),
+        _ => 0,
+    };
+    for mut style in &mut huds {
+        let display = if days == 0 {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+    let value = if days == 1 {
+        "1 day".to_string()
+    } else {
+        format!("{days} days")
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+// Keep the HUD in sync with the watched state of the player's pet. Its stats move on
+// between blocks with the `pet_core` math the chain uses, so they match the chain at each
+// block. The pet cries when its mood changes. Stats computed with other rules than the chain's
+// would be wrong, so they aren't shown then.
+fn pet_hud(
+    time: Res<Time>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    care_rules: Res<CareRules>,
+    game_rules: Res<GameRules>,
+    clock: Res<ChainClock>,
+    mut scheduler: ResMut<PollScheduler>,
+    mut head: Local<Option<(u32, Timer)>>,
+    mut mood: Local<Option<(PetId, pet_core::Mood)>>,
+    mut cries: EventWriter<PetCry>,
+    mut huds: Query<&mut Text, With<PetHud>>,
+) {
+    // Spectators have no HUD
+    if huds.is_empty() {
+        *head = None;
+        *mood = None;
+        scheduler.forget("pet hud");
+        return;
+    }
+    let value = match &player_pet.pet {
+        Some(pet) if !game_rules.matches() => format!(
+            "{} the {}\nUpdate the game to see its stats",
+            pet.name, pet.species
+        ),
+        Some(pet) => {
+            if let Some((_, timer)) = head.as_mut() {
+                timer.tick(time.delta());
+            }
+            if scheduler.ready("pet hud", Priority::High, BLOCK_SECONDS) {
+                let block = client.api().and_then(|api| {
+                    client.block_on(async {
+                        Ok::<_, subxt::Error>(api.blocks().at_latest().await?.number())
+                    })
+                });
+                match block {
+                    Ok(block) => {
+                        *head = Some((block, Timer::from_seconds(BLOCK_SECONDS, TimerMode::Once)))
+                    }
+                    Err(e) => error!("error reading the chain head: {e:?}"),
+                }
+            }
+            let Some((block, timer)) = head.as_ref() else {
+                return;
+            };
+            // Never past the next block, the chain has the last word on it
+            let ticks = pet_core::at_block(*block)
+                + (timer.percent() * pet_core::TICKS_PER_BLOCK as f32) as u64;
+            let stats = player_pet.care().stats(&care_rules.rules, ticks);
+            // Eggs don't cry, and opening the screen isn't a change of mood
+            if player_pet.hatch_block.is_none() {
+                if let Some((id, last)) = *mood {
+                    if id == pet.id && last != stats.mood {
+                        let troubles = |mood: pet_core::Mood| {
+                            u8::from(mood.is_hungry()) + u8::from(mood.is_tired())
+                        };
+                        let cry = if troubles(stats.mood) < troubles(last) {
+                            Cry::Happy
+                        } else {
+                            Cry::Unhappy
+                        };
+                        cries.send(PetCry {
+                            species: pet.species.clone(),
+                            cry,
+                        });
+                    }
+                }
+                *mood = Some((pet.id, stats.mood));
+            }
+            let percent = |stat: u16| format!("{}.{}%", stat / 10, stat % 10);
+            let care = |block: Option<u32>, moment| {
+                block.map_or("never".to_string(), |block| clock.at(block, moment))
+            };
+            format!(
+                "{} the {}, level {}\n{:?}\nFullness {}\nEnergy {}\nFed {}\nSlept {}",
+                pet.name,
+                pet.species,
+                stats.level,
+                stats.mood,
+                percent(stats.fullness),
+                percent(stats.energy),
+                care(player_pet.last_feed_block, player_pet.fed_at),
+                care(player_pet.last_sleep_block, player_pet.slept_at)
+            )
+        }
+        None => "You don't have a pet yet".to_string(),
+    };
+    for mut text in &mut huds {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
         }
     }
 }
@@ -94,8 +412,17 @@ fn button_system(
 fn play_menu_show(
     mouse_button_input: Res<Input<MouseButton>>,
     mut menu_state: ResMut<NextState<PlayMenuState>>,
-    //game_state: ResMut<State<GameState>>,
+    mut spectating: ResMut<Spectating>,
+    mut game_state: ResMut<NextState<GameState>>,
 ) {
+    // Spectators can't act on the pet, right click just leaves
+    if spectating.0.is_some() {
+        if mouse_button_input.just_pressed(MouseButton::Right) {
+            spectating.0 = None;
+            game_state.set(GameState::Menu);
+        }
+        return;
+    }
     if mouse_button_input.pressed(MouseButton::Right) {
         menu_state.set(PlayMenuState::Show);
     }
@@ -165,7 +492,7 @@ fn play_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ..default()
                         },
                         size: Size {
-                            width: Val::Px(500.),
+                            width: Val::Px(700.),
                             height: Val::Px(56.),
                         },
                         ..default()
@@ -182,7 +509,8 @@ fn play_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            PlayMenuButtonAction::BackToMain,
+                            PlayMenuButtonAction::Feed,
+                            CallHelp::pet("feed"),
                         ))
                         .with_children(|parent| {
                             parent
@@ -210,7 +538,8 @@ fn play_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            PlayMenuButtonAction::BackToMain,
+                            PlayMenuButtonAction::Sleep,
+                            CallHelp::pet("sleep"),
                         ))
                         .with_children(|parent| {
                             parent.spawn(TextBundle::from_section(
@@ -231,6 +560,37 @@ fn play_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             parent
                                 .spawn(TextBundle::from_section("Idle", button_text_style.clone()));
                         });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            PlayMenuButtonAction::Battle,
+                            CallHelp::pet("challenge"),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Battle",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            PlayMenuButtonAction::Inventory,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Items",
+                                button_text_style.clone(),
+                            ));
+                        });
                     parent
                         .spawn((
                             ButtonBundle {
@@ -255,7 +615,21 @@ fn play_menu_action(
     >,
     mut play_menu_state: ResMut<NextState<PlayMenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut queue: ResMut<TxQueue>,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    mut cries: EventWriter<PetCry>,
 ) {
+    let pet = player_pet.pet.as_ref().map(|pet| pet.id);
+    // The pet answers the care it's given
+    let mut care = |queued: Option<u64>| {
+        if let (Some(_), Some(pet)) = (queued, &player_pet.pet) {
+            cries.send(PetCry {
+                species: pet.species.clone(),
+                cry: Cry::Care,
+            });
+        }
+    };
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Clicked {
             match menu_button_action {
@@ -264,6 +638,21 @@ fn play_menu_action(
                 //PlayMenuButtonAction::WakeUpPet => play_menu_state.set(PlayMenuState::FeedMenu),
                 //PlayMenuButtonAction::SleepPet => play_menu_state.set(PlayMenuState::FeedMenu),
                 //PlayMenuButtonAction::IdlePet => play_menu_state.set(PlayMenuState::FeedMenu),
+                //Care actions are queued, so several can be sent in a row
+                PlayMenuButtonAction::Feed => {
+                    care(queue.push_pet_call(&client, pet, "Feed", "feed", Vec::new()));
+                }
+                PlayMenuButtonAction::Sleep => {
+                    care(queue.push_pet_call(&client, pet, "Sleep", "sleep", Vec::new()));
+                }
+                PlayMenuButtonAction::Battle => {
+                    play_menu_state.set(PlayMenuState::Disable);
+                    game_state.set(GameState::Battle);
+                }
+                PlayMenuButtonAction::Inventory => {
+                    play_menu_state.set(PlayMenuState::Disable);
+                    game_state.set(GameState::Inventory);
+                }
                 PlayMenuButtonAction::BackToMain => {
                     //exit play menu
                     play_menu_state.set(PlayMenuState::Disable);
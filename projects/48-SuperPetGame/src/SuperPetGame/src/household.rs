@@ -0,0 +1,351 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use sp_core::hashing::blake2_256;
+use subxt::dynamic::Value;
+use subxt::error::DispatchError;
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{format_balance, polkadot, AnyCall, ChainClient, PetVitals},
+    clock::ChainClock,
+    despawn_screen,
+    game::Spectating,
+    pet_watch::CareRules,
+    toast::Toast,
+    GameState, TEXT_COLOR,
+};
+
+// Derivative accounts (`Utility::as_derivative`) checked for pets. An account can only own
+// one pet, so a household keeps the other pets in accounts derived from the player's.
+const HOUSEHOLD_SLOTS: u16 = 8;
+
+// This plugin adds a "Care for all" button to the game screen when the player's household has
+// several pets, feeding all of them with a single batch transaction. Above it, the pets left
+// hungry while another one was fed show how jealous they are, which cuts the experience the
+// player's pet earns.
+pub struct HouseholdPlugin;
+
+impl Plugin for HouseholdPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            household_setup.in_schedule(OnEnter(GameState::Game)),
+            despawn_screen::<OnHouseholdPanel>.in_schedule(OnExit(GameState::Game)),
+        ))
+        .add_systems((care_for_all, care_results, jealousy_text).in_set(OnUpdate(GameState::Game)));
+    }
+}
+
+// Tag component used to tag the household panel
+#[derive(Component)]
+struct OnHouseholdPanel;
+
+#[derive(Component)]
+struct CareForAll;
+
+#[derive(Component)]
+struct JealousyText;
+
+// A pet of the household, owned by the player's account (`slot` is `None`) or by one of its
+// derivative accounts
+struct HouseholdPet {
+    slot: Option<u16>,
+    name: String,
+    // Block it was fed last, zero without vitals like on chain
+    last_feed: u32,
+}
+
+// Outcome of each batch item in order, `None` when it succeeded
+type CareResults = Result<Vec<Option<String>>, String>;
+
+#[derive(Resource)]
+struct Household {
+    pets: Vec<HouseholdPet>,
+    results: Option<Mutex<mpsc::Receiver<CareResults>>>,
+}
+
+// Account `owner` acts as with `Utility::as_derivative(index, ..)`
+fn derivative_account(owner: &AccountId32, index: u16) -> AccountId32 {
+    AccountId32(blake2_256(&(b"modlpy/utilisuba", owner, index).encode()))
+}
+
+// One batch feeding every pet. `force_batch` goes on after a failed item, so each pet gets
+// its own outcome.
+fn care_call(pets: &[HouseholdPet]) -> AnyCall {
+    let calls = pets.iter().map(|pet| {
+        let feed = Value::unnamed_variant(
+            "PetModule",
+            [Value::named_variant("feed", Vec::<(&str, Value)>::new())],
+        );
+        match pet.slot {
+            None => feed,
+            Some(index) => Value::unnamed_variant(
+                "Utility",
+                [Value::named_variant(
+                    "as_derivative",
+                    [("index", Value::u128(index.into())), ("call", feed)],
+                )],
+            ),
+        }
+    });
+    AnyCall::new(subxt::dynamic::tx(
+        "Utility",
+        "force_batch",
+        vec![("calls", Value::unnamed_composite(calls))],
+    ))
+}
+
+fn household_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    spectating: Res<Spectating>,
+) {
+    if spectating.0.is_some() {
+        return;
+    }
+    let owner = client.account_id();
+    let accounts: Vec<_> = std::iter::once((None, owner.clone()))
+        .chain((0..HOUSEHOLD_SLOTS).map(|index| (Some(index), derivative_account(&owner, index))))
+        .collect();
+    let pets = client.api().and_then(|api| {
+        client.block_on(async {
+            let storage = api.storage().at_latest().await?;
+            let mut pets = Vec::new();
+            for (slot, account) in accounts {
+                let pet = storage
+                    .fetch(&polkadot::storage().pet_module().pets_info(&account))
+                    .await?;
+                if let Some((pet_id, info)) = pet {
+                    let vitals = storage
+                        .fetch(&PetVitals::address(pet_id))
+                        .await?
+                        .and_then(|value| PetVitals::decode(&mut value.encoded()).ok());
+                    pets.push(HouseholdPet {
+                        slot,
+                        name: String::from_utf8_lossy(&info.name.0).into_owned(),
+                        last_feed: vitals.map_or(0, |vitals| vitals.last_fed),
+                    });
+                }
+            }
+            Ok::<_, subxt::Error>(pets)
+        })
+    });
+    let pets = match pets {
+        Ok(pets) if pets.len() > 1 => pets,
+        Ok(_) => return,
+        Err(e) => {
+            error!("error reading the household: {e:?}");
+            return;
+        }
+    };
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    // Preview what the batch costs before the player sends it
+    let preview = match client.estimate_fee(&care_call(&pets)) {
+        Ok(estimate) => TextBundle::from_section(
+            format!(
+                "{} pets, fee ~{}, {:.2} ms of block time",
+                pets.len(),
+                format_balance(estimate.fee),
+                estimate.weight as f64 / 1e9
+            ),
+            TextStyle {
+                color: if estimate.affordable() {
+                    TEXT_COLOR
+                } else {
+                    Color::ORANGE_RED
+                },
+                ..text_style.clone()
+            },
+        ),
+        Err(e) => TextBundle::from_section(
+            format!("{} pets, fee unknown: {e}", pets.len()),
+            text_style.clone(),
+        ),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.0),
+                        bottom: Val::Px(10.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    gap: Size::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            OnHouseholdPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(preview);
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        color: Color::ORANGE,
+                        ..text_style.clone()
+                    },
+                ),
+                JealousyText,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(140.0), Val::Px(36.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    CareForAll,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Care for all", text_style.clone()));
+                });
+        });
+    commands.insert_resource(Household {
+        pets,
+        results: None,
+    });
+}
+
+// Send the batch and follow it until it's finalized, to read the outcome of each item
+fn care_for_all(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<CareForAll>)>,
+    mut client: ResMut<ChainClient>,
+    household: Option<ResMut<Household>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(mut household) = household else {
+        return;
+    };
+    for interaction in &interaction_query {
+        if *interaction != Interaction::Clicked || household.results.is_some() {
+            continue;
+        }
+        let sent = client.api().and_then(|api| {
+            let progress = client.submit_and_watch(&care_call(&household.pets))?;
+            Ok((api, progress))
+        });
+        let (api, progress) = match sent {
+            Ok(sent) => sent,
+            Err(e) => {
+                toasts.send(Toast(format!("Care for all failed: {e}")));
+                continue;
+            }
+        };
+        let (sender, receiver) = mpsc::channel();
+        client.spawn(async move {
+            let results = async {
+                let events = progress.wait_for_finalized().await?.fetch_events().await?;
+                let mut results = Vec::new();
+                for event in events.iter() {
+                    let event = event?;
+                    match (event.pallet_name(), event.variant_name()) {
+                        ("Utility", "ItemCompleted") => results.push(None),
+                        ("Utility", "ItemFailed") => {
+                            let error =
+                                DispatchError::decode_from(event.field_bytes(), api.metadata())?;
+                            results.push(Some(error.to_string()));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok::<_, subxt::Error>(results)
+            };
+            let _ = sender.send(results.await.map_err(|e| e.to_string()));
+        });
+        household.results = Some(Mutex::new(receiver));
+        toasts.send(Toast(format!(
+            "Caring for {} pets...",
+            household.pets.len()
+        )));
+    }
+}
+
+// Report the outcome of the batch pet by pet
+fn care_results(
+    household: Option<ResMut<Household>>,
+    clock: Res<ChainClock>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(mut household) = household else {
+        return;
+    };
+    let Some(results) = household
+        .results
+        .as_ref()
+        .and_then(|receiver| receiver.lock().unwrap().try_recv().ok())
+    else {
+        return;
+    };
+    household.results = None;
+    match results {
+        Ok(results) => {
+            for (pet, result) in household.pets.iter_mut().zip(results) {
+                toasts.send(Toast(match result {
+                    None => {
+                        // Fed at about the head, the batch was just finalized
+                        pet.last_feed = clock.head().unwrap_or(pet.last_feed);
+                        format!("{} was fed", pet.name)
+                    }
+                    Some(error) => format!("{} wasn't fed: {error}", pet.name),
+                }));
+            }
+        }
+        Err(e) => toasts.send(Toast(format!("Care for all failed: {e}"))),
+    }
+}
+
+// The pets of the household jealous of the one fed last and the experience it costs, with the
+// same `pet_core` rules the chain cuts experience with
+fn jealousy_summary(pets: &[HouseholdPet], rules: &pet_core::Rules) -> String {
+    let last_feeds: Vec<_> = pets.iter().map(|pet| Some(pet.last_feed)).collect();
+    let favourite = last_feeds.iter().flatten().max().copied();
+    let jealous: Vec<String> = pets
+        .iter()
+        .filter_map(|pet| {
+            let jealousy = pet_core::jealousy(Some(pet.last_feed), favourite, rules);
+            let percent = u32::from(jealousy) * 100 / u32::from(pet_core::FULL);
+            (jealousy > 0).then(|| format!("{} {percent}%", pet.name))
+        })
+        .collect();
+    if jealous.is_empty() {
+        return String::new();
+    }
+    let jealousy = pet_core::household_jealousy(&last_feeds, rules);
+    let cut = 100 - pet_core::experience_gain(100, jealousy);
+    format!("Jealous: {}, -{cut}% experience", jealous.join(", "))
+}
+
+fn jealousy_text(
+    household: Option<Res<Household>>,
+    care_rules: Res<CareRules>,
+    mut texts: Query<&mut Text, With<JealousyText>>,
+) {
+    let Some(household) = household else {
+        return;
+    };
+    if !household.is_changed() && !care_rules.is_changed() {
+        return;
+    }
+    let summary = jealousy_summary(&household.pets, &care_rules.rules);
+    for mut text in &mut texts {
+        text.sections[0].value = summary.clone();
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use subxt::dynamic::Value;
+use subxt::ext::codec::{Decode, Input};
+use subxt::utils::AccountId32;
+
+use super::client::ChainClient;
+
+// How long a read display name is used before it's read again
+const NAME_TTL: Duration = Duration::from_secs(600);
+
+// Cells on a side of an avatar, and pixels per cell
+const AVATAR_CELLS: usize = 5;
+const AVATAR_CELL_PIXELS: usize = 8;
+
+// This plugin reads the display names players set with the identity pallet, for the screens
+// showing accounts. Texts tagged with `AccountNames` are written with `short` addresses at
+// first, and each address is replaced by its display name once it's read.
+pub struct IdentityPlugin;
+
+impl Plugin for IdentityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Names>()
+            .add_systems((ask_names, receive_names).chain());
+    }
+}
+
+// Accounts whose `short` address a text shows, to be replaced by their display name
+#[derive(Component)]
+pub struct AccountNames(pub Vec<AccountId32>);
+
+// Truncated SS58 address, shown for accounts without a display name
+pub fn short(account: &AccountId32) -> String {
+    format!("{}…", &account.to_string()[..8])
+}
+
+// Avatar of an account, a pattern of cells mirrored left to right in a color, both taken from
+// its address, so players tell accounts apart at a glance
+pub fn avatar(account: &AccountId32) -> Image {
+    let bytes = &account.0;
+    let color = Color::hsl(f32::from(bytes[0]) / 255.0 * 360.0, 0.6, 0.5).as_rgba_u8();
+    let half = (AVATAR_CELLS + 1) / 2;
+    let size = AVATAR_CELLS * AVATAR_CELL_PIXELS;
+    let mut data = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let (column, row) = (x / AVATAR_CELL_PIXELS, y / AVATAR_CELL_PIXELS);
+            let cell = row * half + column.min(AVATAR_CELLS - 1 - column);
+            let on = (bytes[1 + cell / 8] >> (cell % 8)) & 1 == 1;
+            data.extend(if on { color } else { [240, 240, 240, 255] });
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size as u32,
+            height: size as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+// Display names read so far, `None` for accounts without one, and when they were read
+#[derive(Resource)]
+pub struct Names {
+    read: HashMap<AccountId32, (Option<String>, Instant)>,
+    asked: Vec<AccountId32>,
+    sender: Mutex<mpsc::Sender<(AccountId32, Option<String>)>>,
+    receiver: Mutex<mpsc::Receiver<(AccountId32, Option<String>)>>,
+}
+
+impl Default for Names {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            read: HashMap::new(),
+            asked: Vec::new(),
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl Names {
+    // Display name of `account` if it was read, else its `short` address
+    pub fn display(&self, account: &AccountId32) -> String {
+        match self.read.get(account) {
+            Some((Some(name), _)) => name.clone(),
+            _ => short(account),
+        }
+    }
+
+    // Whether the name of `account` is to be read, because it wasn't or not recently
+    fn wants(&self, account: &AccountId32) -> bool {
+        let fresh = self
+            .read
+            .get(account)
+            .map_or(false, |(_, read_at)| read_at.elapsed() < NAME_TTL);
+        !fresh && !self.asked.contains(account)
+    }
+}
+
+// Mirrors of the start of the identity pallet's `Registration`, up to the display name
+#[derive(Decode)]
+struct Registration {
+    _judgements: Vec<(u32, Judgement)>,
+    _deposit: u128,
+    _additional: Vec<(Data, Data)>,
+    display: Data,
+}
+
+#[derive(Decode)]
+enum Judgement {
+    Unknown,
+    FeePaid { _fee: u128 },
+    Reasonable,
+    KnownGood,
+    OutOfDate,
+    LowQuality,
+    Erroneous,
+}
+
+// Identity field, only kept when it's raw UTF-8 text
+struct Data(Option<String>);
+
+impl Decode for Data {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, subxt::ext::codec::Error> {
+        match input.read_byte()? {
+            0 => Ok(Data(None)),
+            // Raw bytes, as many as the variant index minus one
+            tag @ 1..=33 => {
+                let mut raw = vec![0; tag as usize - 1];
+                input.read(&mut raw)?;
+                Ok(Data(String::from_utf8(raw).ok()))
+            }
+            // One of the hashes
+            34..=37 => {
+                let mut hash = [0; 32];
+                input.read(&mut hash)?;
+                Ok(Data(None))
+            }
+            _ => Err("invalid identity data".into()),
+        }
+    }
+}
+
+// Read the display names of the accounts of new texts, unless they were read recently
+fn ask_names(
+    mut client: ResMut<ChainClient>,
+    mut names: ResMut<Names>,
+    texts: Query<&AccountNames, Added<AccountNames>>,
+) {
+    let mut accounts: Vec<AccountId32> = Vec::new();
+    for account in texts.iter().flat_map(|text| &text.0) {
+        if names.wants(account) && !accounts.contains(account) {
+            accounts.push(account.clone());
+        }
+    }
+    if accounts.is_empty() {
+        return;
+    }
+    let api = match client.api() {
+        Ok(api) => api,
+        Err(e) => {
+            warn!("can't read display names: {e}");
+            return;
+        }
+    };
+    names.asked.extend(accounts.iter().cloned());
+    let sender = names.sender.lock().unwrap().clone();
+    client.spawn(async move {
+        for account in accounts {
+            let name = match api.storage().at_latest().await {
+                Ok(storage) => storage
+                    .fetch(&subxt::dynamic::storage(
+                        "Identity",
+                        "IdentityOf",
+                        vec![Value::from_bytes(&account)],
+                    ))
+                    .await
+                    .map(|value| {
+                        value
+                            .and_then(|value| Registration::decode(&mut value.encoded()).ok())
+                            .and_then(|registration| registration.display.0)
+                    }),
+                Err(e) => Err(e),
+            };
+            let name = name.unwrap_or_else(|e| {
+                warn!("can't read the display name of {account}: {e}");
+                None
+            });
+            let _ = sender.send((account, name));
+        }
+    });
+}
+
+// Keep the names read, and write the names known in the new texts, or in all of them when
+// names were just read
+fn receive_names(mut names: ResMut<Names>, mut texts: Query<(Ref<AccountNames>, &mut Text)>) {
+    let read: Vec<_> = names.receiver.lock().unwrap().try_iter().collect();
+    let renamed = !read.is_empty();
+    for (account, name) in read {
+        names.asked.retain(|asked| asked != &account);
+        names.read.insert(account, (name, Instant::now()));
+    }
+    for (accounts, mut text) in &mut texts {
+        if !renamed && !accounts.is_added() {
+            continue;
+        }
+        for account in &accounts.0 {
+            let (address, name) = (short(account), names.display(account));
+            if address == name {
+                continue;
+            }
+            for section in &mut text.sections {
+                if section.value.contains(&address) {
+                    section.value = section.value.replace(&address, &name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,720 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use futures::StreamExt;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::json;
+use subxt::events::EventDetails;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use thiserror::Error as ThisError;
+
+use super::client::{pet_events::BattleLog, ChainClient, PetId, PetSpecies};
+use super::push::{self, EventFeed};
+
+// Database written by the indexer, can be moved with `SUPERPET_INDEX_DB`
+const DEFAULT_INDEX_DB: &str = "superpet-index.db";
+
+const BLOCKS_PER_HOUR: u32 = 600;
+const HOURS_PER_DAY: u32 = 24;
+
+// Blocks of activity the rate checks look at, a week
+pub const RATE_WINDOW: u32 = 7 * HOURS_PER_DAY * BLOCKS_PER_HOUR;
+
+// Most feeds and sleeps an account can plausibly send in an hour. Auto-care feeds every few
+// minutes at most.
+const MAX_CARE_PER_HOUR: u32 = 60;
+
+// Most battles an account can plausibly start by hand in an hour
+const MAX_BATTLES_PER_HOUR: u32 = 30;
+
+// Most hours of a day an account can plausibly battle in. Care isn't counted, auto-care sends
+// it around the clock while the game runs.
+const MAX_BATTLE_HOURS_PER_DAY: u32 = 20;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        block INTEGER NOT NULL,
+        idx INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        account TEXT,
+        other TEXT,
+        pet_id INTEGER,
+        amount TEXT,
+        PRIMARY KEY (block, idx)
+    );
+    CREATE INDEX IF NOT EXISTS events_by_pet ON events (pet_id, name, block);
+    CREATE INDEX IF NOT EXISTS events_by_account ON events (account, block);
+    CREATE TABLE IF NOT EXISTS battle_logs (
+        block INTEGER NOT NULL,
+        idx INTEGER NOT NULL,
+        winner INTEGER NOT NULL,
+        loser INTEGER NOT NULL,
+        seed TEXT NOT NULL,
+        challenger_weight TEXT NOT NULL,
+        opponent_weight TEXT NOT NULL,
+        PRIMARY KEY (block, idx)
+    );
+    CREATE INDEX IF NOT EXISTS battle_logs_by_winner ON battle_logs (winner, block);
+    CREATE INDEX IF NOT EXISTS battle_logs_by_loser ON battle_logs (loser, block);
+    CREATE TABLE IF NOT EXISTS message_texts (
+        block INTEGER NOT NULL,
+        idx INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        PRIMARY KEY (block, idx)
+    );
+    CREATE TABLE IF NOT EXISTS progress (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        last_block INTEGER NOT NULL
+    );
+";
+
+// This plugin runs the indexer next to the game, so the screens reading the database get
+// the latest events even when no standalone indexer is running (`--indexer`)
+pub struct IndexerPlugin;
+
+impl Plugin for IndexerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_indexer);
+    }
+}
+
+#[derive(Debug, ThisError)]
+pub enum IndexerError {
+    #[error("chain error: {0}")]
+    Chain(#[from] subxt::Error),
+    #[error("database error: {0}")]
+    Db(#[from] rusqlite::Error),
+}
+
+// Pet pallet event as stored in the database
+pub struct IndexedEvent {
+    pub name: String,
+    // Account the event is about, and the other party of transfers, sales, battles and gifts
+    pub account: Option<AccountId32>,
+    pub other: Option<AccountId32>,
+    pub pet_id: Option<PetId>,
+    // Price, score, experience or fee, depending on the event
+    pub amount: Option<u128>,
+    // Loser and log of a battle, battles resolved before logs were recorded have none
+    pub battle: Option<(PetId, BattleLog)>,
+    // Text of a trade message
+    pub text: Option<String>,
+}
+
+// A trade message of the index
+pub struct TradeMessage {
+    pub from: AccountId32,
+    pub text: String,
+}
+
+// A battle of the index, enough to replay it
+#[derive(Clone)]
+pub struct RecordedBattle {
+    pub block: u32,
+    pub winner: PetId,
+    pub loser: PetId,
+    pub log: pet_core::BattleLog,
+}
+
+impl RecordedBattle {
+    // The pets of the challenger and of the opponent
+    pub fn pets(&self) -> (PetId, PetId) {
+        if self.log.challenger_wins() {
+            (self.winner, self.loser)
+        } else {
+            (self.loser, self.winner)
+        }
+    }
+}
+
+impl IndexedEvent {
+    // Pick the indexed fields out of a pet pallet event, `None` for other events
+    pub fn decode(event: &EventDetails<PolkadotConfig>) -> Option<Self> {
+        if event.pallet_name() != "PetModule" {
+            return None;
+        }
+        let bytes = &mut event.field_bytes();
+        let mut battle = None;
+        let mut text = None;
+        let (account, other, pet_id, amount) = match event.variant_name() {
+            "PetMinted" => {
+                let (owner, pet_id) = <(AccountId32, PetId)>::decode(bytes).ok()?;
+                // Minted before gifts were recorded when there's no payer, else a gift when
+                // someone else paid
+                let payer = <(PetSpecies, AccountId32)>::decode(bytes)
+                    .ok()
+                    .map(|(_, payer)| payer)
+                    .filter(|payer| payer != &owner);
+                (Some(owner), payer, Some(pet_id), None)
+            }
+            "PetFeeded" | "PetSleeped" | "ListingCancelled" | "PetRenamed" => {
+                let (owner, pet_id) = <(AccountId32, PetId)>::decode(bytes).ok()?;
+                (Some(owner), None, Some(pet_id), None)
+            }
+            "PetTransfered" => {
+                let (from, to, pet_id) = <(AccountId32, AccountId32, PetId)>::decode(bytes).ok()?;
+                (Some(from), Some(to), Some(pet_id), None)
+            }
+            "PetListed" => {
+                let (owner, pet_id, price) = <(AccountId32, PetId, u128)>::decode(bytes).ok()?;
+                (Some(owner), None, Some(pet_id), Some(price))
+            }
+            "Sold" | "OfferAccepted" => {
+                let (seller, buyer, pet_id, price, _fee) =
+                    <(AccountId32, AccountId32, PetId, u128, u128)>::decode(bytes).ok()?;
+                (Some(seller), Some(buyer), Some(pet_id), Some(price))
+            }
+            "ScoreSubmitted" => {
+                let (pet_id, score, _experience) = <(PetId, u32, u64)>::decode(bytes).ok()?;
+                (None, None, Some(pet_id), Some(score.into()))
+            }
+            "BattleResolved" => {
+                let (challenger, opponent, winner, loser, experience) =
+                    <(AccountId32, AccountId32, PetId, PetId, u32)>::decode(bytes).ok()?;
+                battle = BattleLog::decode(bytes).ok().map(|log| (loser, log));
+                (
+                    Some(challenger),
+                    Some(opponent),
+                    Some(winner),
+                    Some(experience.into()),
+                )
+            }
+            "TradeMessageSent" => {
+                let (pet_id, from, to, message, fee) =
+                    <(PetId, AccountId32, AccountId32, Vec<u8>, u128)>::decode(bytes).ok()?;
+                text = Some(String::from_utf8_lossy(&message).into_owned());
+                (Some(from), Some(to), Some(pet_id), Some(fee))
+            }
+            _ => return None,
+        };
+        Some(Self {
+            name: event.variant_name().to_string(),
+            account,
+            other,
+            pet_id,
+            amount,
+            battle,
+            text,
+        })
+    }
+}
+
+// A rate check an account fails, its activity is unlikely to be sent by hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RateFlag {
+    CareRate,
+    BattleRate,
+    RoundTheClockBattles,
+}
+
+// Peak activity of an account over the rate window, and the checks it fails
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountRates {
+    pub account: AccountId32,
+    pub care_per_hour: u32,
+    pub battles_per_hour: u32,
+    pub battle_hours_per_day: u32,
+    pub flags: Vec<RateFlag>,
+}
+
+impl AccountRates {
+    fn new(account: AccountId32) -> Self {
+        Self {
+            account,
+            care_per_hour: 0,
+            battles_per_hour: 0,
+            battle_hours_per_day: 0,
+            flags: Vec::new(),
+        }
+    }
+
+    fn check(mut self) -> Self {
+        let checks = [
+            (self.care_per_hour > MAX_CARE_PER_HOUR, RateFlag::CareRate),
+            (
+                self.battles_per_hour > MAX_BATTLES_PER_HOUR,
+                RateFlag::BattleRate,
+            ),
+            (
+                self.battle_hours_per_day > MAX_BATTLE_HOURS_PER_DAY,
+                RateFlag::RoundTheClockBattles,
+            ),
+        ];
+        self.flags = checks
+            .into_iter()
+            .filter_map(|(failed, flag)| failed.then_some(flag))
+            .collect();
+        self
+    }
+}
+
+// Connection to the event database
+pub struct IndexDb(Connection);
+
+impl IndexDb {
+    pub fn open() -> Result<Self, IndexerError> {
+        let path = std::env::var("SUPERPET_INDEX_DB").unwrap_or(DEFAULT_INDEX_DB.to_string());
+        let connection = Connection::open(path)?;
+        // The game and a standalone indexer may write at the same time
+        connection.busy_timeout(std::time::Duration::from_secs(5))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self(connection))
+    }
+
+    // Last block whose events are stored
+    pub fn last_block(&self) -> Result<Option<u32>, IndexerError> {
+        Ok(self
+            .0
+            .query_row("SELECT last_block FROM progress WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    // Store the events of a block and move the progress past it
+    pub fn insert_block(
+        &mut self,
+        block: u32,
+        events: &[(u32, IndexedEvent)],
+    ) -> Result<(), IndexerError> {
+        let tx = self.0.transaction()?;
+        for (idx, event) in events {
+            tx.execute(
+                "INSERT OR IGNORE INTO events (block, idx, name, account, other, pet_id, amount)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    block,
+                    idx,
+                    event.name,
+                    event.account.as_ref().map(|a| a.to_string()),
+                    event.other.as_ref().map(|a| a.to_string()),
+                    event.pet_id,
+                    event.amount.map(|a| a.to_string()),
+                ],
+            )?;
+            if let (Some(winner), Some((loser, log))) = (event.pet_id, &event.battle) {
+                tx.execute(
+                    "INSERT OR IGNORE INTO battle_logs
+                       (block, idx, winner, loser, seed, challenger_weight, opponent_weight)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        block,
+                        idx,
+                        winner,
+                        loser,
+                        log.seed.to_string(),
+                        log.challenger_weight.to_string(),
+                        log.opponent_weight.to_string(),
+                    ],
+                )?;
+            }
+            if let Some(text) = &event.text {
+                tx.execute(
+                    "INSERT OR IGNORE INTO message_texts (block, idx, text) VALUES (?1, ?2, ?3)",
+                    params![block, idx, text],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO progress (id, last_block) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET last_block = max(last_block, ?1)",
+            params![block],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Blocks in which `name` events happened to the pet since `since`
+    pub fn pet_event_blocks(
+        &self,
+        pet_id: PetId,
+        name: &str,
+        since: u32,
+    ) -> Result<Vec<u32>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT block FROM events WHERE pet_id = ?1 AND name = ?2 AND block >= ?3
+             ORDER BY block",
+        )?;
+        let blocks = query
+            .query_map(params![pet_id, name, since], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(blocks)
+    }
+
+    // Block and name of every event of the pet between `since` and `until`, oldest first
+    pub fn pet_events(
+        &self,
+        pet_id: PetId,
+        since: u32,
+        until: u32,
+    ) -> Result<Vec<(u32, String)>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT block, name FROM events WHERE pet_id = ?1 AND block >= ?2 AND block <= ?3
+             ORDER BY block, idx",
+        )?;
+        let events = query
+            .query_map(params![pet_id, since, until], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(events)
+    }
+
+    // Block, pet and price of every sale since `since`, oldest first
+    pub fn sales(&self, since: u32) -> Result<Vec<(u32, PetId, u128)>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT block, pet_id, amount FROM events
+             WHERE name IN ('Sold', 'OfferAccepted') AND block >= ?1
+             ORDER BY block, idx",
+        )?;
+        let sales = query
+            .query_map(params![since], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sales
+            .into_iter()
+            .filter_map(|(block, pet_id, price)| Some((block, pet_id, price.parse().ok()?)))
+            .collect())
+    }
+
+    // Peak care and battle rates of every account acting since `since`, with the checks they
+    // fail. Battles count for the challenger, who started them.
+    pub fn account_rates(&self, since: u32) -> Result<Vec<AccountRates>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT account, block / ?2 AS hour,
+                    SUM(name IN ('PetFeeded', 'PetSleeped')), SUM(name = 'BattleResolved')
+             FROM events WHERE account IS NOT NULL AND block >= ?1
+             GROUP BY account, hour",
+        )?;
+        let hours = query
+            .query_map(params![since, BLOCKS_PER_HOUR], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, u32>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut rates: BTreeMap<String, AccountRates> = BTreeMap::new();
+        let mut battle_hours: BTreeMap<(String, u32), u32> = BTreeMap::new();
+        for (account, hour, care, battles) in hours {
+            let Ok(id) = account.parse() else {
+                continue;
+            };
+            let account_rates = rates
+                .entry(account.clone())
+                .or_insert_with(|| AccountRates::new(id));
+            account_rates.care_per_hour = account_rates.care_per_hour.max(care);
+            account_rates.battles_per_hour = account_rates.battles_per_hour.max(battles);
+            if battles > 0 {
+                *battle_hours
+                    .entry((account, hour / HOURS_PER_DAY))
+                    .or_default() += 1;
+            }
+        }
+        for ((account, _), hours) in battle_hours {
+            if let Some(account_rates) = rates.get_mut(&account) {
+                account_rates.battle_hours_per_day = account_rates.battle_hours_per_day.max(hours);
+            }
+        }
+        Ok(rates.into_values().map(AccountRates::check).collect())
+    }
+
+    // Accounts failing a rate check over the last `RATE_WINDOW` indexed blocks
+    pub fn flagged_accounts(&self) -> Result<Vec<AccountRates>, IndexerError> {
+        let since = self.last_block()?.unwrap_or(0).saturating_sub(RATE_WINDOW);
+        Ok(self
+            .account_rates(since)?
+            .into_iter()
+            .filter(|rates| !rates.flags.is_empty())
+            .collect())
+    }
+
+    // Pets that won the most battles since `since`, with their wins, the most first
+    pub fn battle_winners(
+        &self,
+        since: u32,
+        limit: u32,
+    ) -> Result<Vec<(PetId, u32)>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT pet_id, COUNT(*) AS wins FROM events
+             WHERE name = 'BattleResolved' AND block >= ?1
+             GROUP BY pet_id ORDER BY wins DESC, pet_id LIMIT ?2",
+        )?;
+        let winners = query
+            .query_map(params![since, limit], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(winners)
+    }
+
+    // The latest `limit` battles of the pet with a log, the latest first
+    pub fn pet_battles(
+        &self,
+        pet_id: PetId,
+        limit: u32,
+    ) -> Result<Vec<RecordedBattle>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT block, winner, loser, seed, challenger_weight, opponent_weight
+             FROM battle_logs WHERE winner = ?1 OR loser = ?1
+             ORDER BY block DESC, idx DESC LIMIT ?2",
+        )?;
+        let battles = query
+            .query_map(params![pet_id, limit], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(battles
+            .into_iter()
+            .filter_map(|(block, winner, loser, seed, challenger, opponent)| {
+                let log = pet_core::BattleLog {
+                    seed: seed.parse().ok()?,
+                    challenger_weight: challenger.parse().ok()?,
+                    opponent_weight: opponent.parse().ok()?,
+                };
+                Some(RecordedBattle {
+                    block,
+                    winner,
+                    loser,
+                    log,
+                })
+            })
+            .collect())
+    }
+
+    // Trade messages about the pet between two accounts, oldest first
+    pub fn trade_messages(
+        &self,
+        pet_id: PetId,
+        one: &AccountId32,
+        other: &AccountId32,
+    ) -> Result<Vec<TradeMessage>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT e.account, m.text FROM events e JOIN message_texts m USING (block, idx)
+             WHERE e.name = 'TradeMessageSent' AND e.pet_id = ?1
+               AND ((e.account = ?2 AND e.other = ?3) OR (e.account = ?3 AND e.other = ?2))
+             ORDER BY e.block, e.idx",
+        )?;
+        let messages = query
+            .query_map(params![pet_id, one.to_string(), other.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(messages
+            .into_iter()
+            .filter_map(|(from, text)| {
+                Some(TradeMessage {
+                    from: from.parse().ok()?,
+                    text,
+                })
+            })
+            .collect())
+    }
+
+    // Pets whose latest market event is a listing, their seller and their asking price
+    pub fn listings(&self) -> Result<Vec<(PetId, AccountId32, u128)>, IndexerError> {
+        let mut query = self.0.prepare_cached(
+            "SELECT pet_id, account, amount FROM events e
+             WHERE name = 'PetListed'
+               AND (block, idx) = (
+                 SELECT block, idx FROM events
+                 WHERE pet_id = e.pet_id AND name IN
+                   ('PetListed', 'ListingCancelled', 'PetTransfered', 'Sold', 'OfferAccepted')
+                 ORDER BY block DESC, idx DESC LIMIT 1
+               )",
+        )?;
+        let listings = query
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(listings
+            .into_iter()
+            .filter_map(|(pet_id, seller, price)| {
+                Some((pet_id, seller.parse().ok()?, price.parse().ok()?))
+            })
+            .collect())
+    }
+}
+
+fn start_indexer(mut client: ResMut<ChainClient>) {
+    // A gateway pushing the events it indexes is lighter than following the chain here
+    if let Ok(url) = std::env::var("SUPERPET_GATEWAY") {
+        match IndexDb::open() {
+            Ok(db) => {
+                std::thread::spawn(move || {
+                    if let Err(e) = push::follow_gateway(&url, db) {
+                        warn!("stopped storing the events of {url}: {e}");
+                    }
+                });
+            }
+            Err(e) => warn!("not indexing events: {e}"),
+        }
+        return;
+    }
+    let started = client
+        .api()
+        .map_err(IndexerError::from)
+        .and_then(|api| Ok((api, IndexDb::open()?)));
+    let (api, db) = match started {
+        Ok(started) => started,
+        Err(e) => {
+            warn!("not indexing events: {e}");
+            return;
+        }
+    };
+    client.spawn(async move {
+        if let Err(e) = follow_chain(api, db, EventFeed::default()).await {
+            warn!("indexer stopped: {e}");
+        }
+    });
+}
+
+// Index every finalized block, starting after the last indexed one, and push its events to
+// the subscribers of `feed`
+async fn follow_chain(
+    api: OnlineClient<PolkadotConfig>,
+    mut db: IndexDb,
+    feed: EventFeed,
+) -> Result<(), IndexerError> {
+    let mut blocks = api.blocks().subscribe_finalized().await?;
+    while let Some(block) = blocks.next().await {
+        let head = block?.number();
+        let next = db.last_block()?.map_or(0, |last| last + 1);
+        for number in next..=head {
+            let Some(hash) = api.rpc().block_hash(Some(number.into())).await? else {
+                continue;
+            };
+            // Pruned nodes don't keep the events of old blocks
+            let events = match api.events().at(hash).await {
+                Ok(events) => events,
+                Err(e) if number < head => {
+                    warn!("skipping events of block {number}: {e}");
+                    db.insert_block(number, &[])?;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let indexed: Vec<_> = events
+                .iter()
+                .filter_map(Result::ok)
+                .filter_map(|event| Some((event.index(), IndexedEvent::decode(&event)?)))
+                .collect();
+            db.insert_block(number, &indexed)?;
+            feed.publish(number, &indexed);
+        }
+    }
+    Ok(())
+}
+
+// Answer a request to the rate check api
+fn api_response(db: &IndexDb, url: &str) -> (u16, String) {
+    let path: Vec<&str> = url
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let body = match path[..] {
+        ["flagged"] => db.flagged_accounts().map(|flagged| json!(flagged)),
+        ["verified", address] => {
+            let Ok(account) = address.parse::<AccountId32>() else {
+                return (400, json!({ "error": "invalid address" }).to_string());
+            };
+            let since = db
+                .last_block()
+                .map(|last| last.unwrap_or(0).saturating_sub(RATE_WINDOW));
+            since
+                .and_then(|since| db.account_rates(since))
+                .map(|rates| {
+                    let flags = rates
+                        .into_iter()
+                        .find(|rates| rates.account == account)
+                        .map_or_else(Vec::new, |rates| rates.flags);
+                    json!({ "account": account, "verified": flags.is_empty(), "flags": flags })
+                })
+        }
+        _ => return (404, json!({ "error": "not found" }).to_string()),
+    };
+    match body {
+        Ok(body) => (200, body.to_string()),
+        Err(e) => (500, json!({ "error": e.to_string() }).to_string()),
+    }
+}
+
+// Serve the rate checks over HTTP, for leaderboards and rewards run elsewhere:
+// `GET /flagged` lists the accounts failing a check with their peak rates, and
+// `GET /verified/<address>` tells whether an account passes them all
+fn serve_api(address: &str) {
+    let server = match tiny_http::Server::http(address) {
+        Ok(server) => server,
+        Err(e) => {
+            warn!("not serving the rate check api on {address}: {e}");
+            return;
+        }
+    };
+    info!("serving the rate check api on {address}");
+    std::thread::spawn(move || {
+        let db = match IndexDb::open() {
+            Ok(db) => db,
+            Err(e) => {
+                warn!("rate check api stopped: {e}");
+                return;
+            }
+        };
+        let content_type =
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                .expect("valid header");
+        for request in server.incoming_requests() {
+            let (status, body) = api_response(&db, request.url());
+            let response = tiny_http::Response::from_string(body)
+                .with_status_code(status)
+                .with_header(content_type.clone());
+            if let Err(e) = request.respond(response) {
+                warn!("error answering a rate check request: {e}");
+            }
+        }
+    });
+}
+
+// Run the indexer without the game, until the node connection ends. The rate check api is
+// served at `SUPERPET_INDEX_API`, like "127.0.0.1:8787", and the events are pushed over
+// websocket at `SUPERPET_INDEX_PUSH`, like "127.0.0.1:8788", when they're set.
+// `SUPERPET_NODE_URL` indexes another node than the chain's.
+pub fn run_standalone(url: &str) {
+    let url = std::env::var("SUPERPET_NODE_URL").unwrap_or(url.to_string());
+    if let Ok(address) = std::env::var("SUPERPET_INDEX_API") {
+        serve_api(&address);
+    }
+    let feed = EventFeed::default();
+    if let Ok(address) = std::env::var("SUPERPET_INDEX_PUSH") {
+        push::serve(&address, feed.clone());
+    }
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    let result = runtime.block_on(async {
+        let api = OnlineClient::<PolkadotConfig>::from_url(&url).await?;
+        let db = IndexDb::open()?;
+        info!("indexing pet events of {url}");
+        follow_chain(api, db, feed).await
+    });
+    if let Err(e) = result {
+        warn!("indexer stopped: {e}");
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+
+// Longest the replay waits for a check to hold before the script fails
+const CHECK_SECONDS: f64 = 30.0;
+
+// The keys scripts record, the ones the game reacts to besides typed characters
+const KEYS: &[KeyCode] = &[
+    KeyCode::Back,
+    KeyCode::Return,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Space,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LWin,
+    KeyCode::RWin,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::C,
+    KeyCode::T,
+    KeyCode::V,
+];
+
+// This plugin records the player's input to a script with `--record-input <file>`, and plays
+// a script back with `--play-input <file>`, the base of automated tests of the screens. A
+// script has one JSON entry per line, with the seconds since launch it happened at: keys,
+// typed characters, mouse buttons and cursor moves, and the screens reached, which the replay
+// checks. Checks can be added by hand, like `{"at":12.0,"input":"text","text":"Shelly"}` for
+// a text that must be on screen, and lines starting with `#` are comments. The replay waits
+// for each check to hold, shifting the inputs after it, and exits once the script is over, or
+// with an error when a check doesn't hold in time. It runs in a hidden window on the chain
+// chosen with `--chain`, there's no mock of the node in the game, so tests start a fresh
+// `--dev --tmp` node and a new `--profile` for the chain state and accounts to match the
+// recording.
+pub struct InputScriptPlugin(pub PathBuf);
+
+impl Plugin for InputScriptPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = arg_value("--record-input") {
+            let path = self.0.join(path);
+            match File::create(&path) {
+                Ok(file) => {
+                    info!("recording the input to {}", path.display());
+                    app.insert_resource(Recorder { file, screen: None })
+                        .add_system(record_input);
+                }
+                Err(e) => error!("can't record the input to {}: {e}", path.display()),
+            }
+        }
+        if let Some(path) = arg_value("--play-input") {
+            let path = self.0.join(path);
+            match Player::load(&path) {
+                Ok(player) => {
+                    info!("playing the input of {}", path.display());
+                    app.insert_resource(player)
+                        .add_system(play_input.in_base_set(CoreSet::First));
+                }
+                Err(e) => {
+                    error!("can't play the input of {}: {e}", path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+// Whether a script is played, the window is hidden then
+pub fn playing() -> bool {
+    arg_value("--play-input").is_some()
+}
+
+fn arg_value(option: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == option)
+        .and_then(|i| args.get(i + 1).cloned())
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Entry {
+    // Seconds since launch
+    at: f64,
+    #[serde(flatten)]
+    input: ScriptInput,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "input", rename_all = "snake_case")]
+enum ScriptInput {
+    Key { key: String, pressed: bool },
+    Char { char: char },
+    Mouse { button: String, pressed: bool },
+    // Cursor position in the window, from the bottom left corner
+    Cursor { x: f32, y: f32 },
+    // Checks, the screen shown and a text visible on it
+    Screen { screen: String },
+    Text { text: String },
+}
+
+fn key_name(key: KeyCode) -> Option<String> {
+    KEYS.contains(&key).then(|| format!("{key:?}"))
+}
+
+fn key_code(name: &str) -> Option<KeyCode> {
+    KEYS.iter().copied().find(|key| format!("{key:?}") == name)
+}
+
+fn button_name(button: MouseButton) -> Option<&'static str> {
+    match button {
+        MouseButton::Left => Some("Left"),
+        MouseButton::Right => Some("Right"),
+        MouseButton::Middle => Some("Middle"),
+        MouseButton::Other(_) => None,
+    }
+}
+
+fn mouse_button(name: &str) -> Option<MouseButton> {
+    match name {
+        "Left" => Some(MouseButton::Left),
+        "Right" => Some(MouseButton::Right),
+        "Middle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+fn button_state(pressed: bool) -> ButtonState {
+    if pressed {
+        ButtonState::Pressed
+    } else {
+        ButtonState::Released
+    }
+}
+
+#[derive(Resource)]
+struct Recorder {
+    file: File,
+    // The screen last recorded
+    screen: Option<GameState>,
+}
+
+impl Recorder {
+    fn write(&mut self, entry: &Entry) {
+        let written = serde_json::to_string(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(self.file, "{line}").map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            warn!("can't record {entry:?}: {e}");
+        }
+    }
+}
+
+// Write the input of the frame to the script, the cursor only where it ended up
+fn record_input(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut recorder: ResMut<Recorder>,
+    mut keys: EventReader<KeyboardInput>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut buttons: EventReader<MouseButtonInput>,
+    mut cursor: EventReader<CursorMoved>,
+) {
+    let mut inputs = Vec::new();
+    if recorder.screen != Some(state.0) {
+        recorder.screen = Some(state.0);
+        inputs.push(ScriptInput::Screen {
+            screen: format!("{:?}", state.0),
+        });
+    }
+    inputs.extend(keys.iter().filter_map(|event| {
+        Some(ScriptInput::Key {
+            key: key_name(event.key_code?)?,
+            pressed: event.state.is_pressed(),
+        })
+    }));
+    inputs.extend(
+        chars
+            .iter()
+            .map(|event| ScriptInput::Char { char: event.char }),
+    );
+    if let Some(moved) = cursor.iter().last() {
+        inputs.push(ScriptInput::Cursor {
+            x: moved.position.x,
+            y: moved.position.y,
+        });
+    }
+    inputs.extend(buttons.iter().filter_map(|event| {
+        Some(ScriptInput::Mouse {
+            button: button_name(event.button)?.to_string(),
+            pressed: event.state.is_pressed(),
+        })
+    }));
+    let at = time.elapsed_seconds_f64();
+    for input in inputs {
+        recorder.write(&Entry { at, input });
+    }
+}
+
+#[derive(Resource)]
+struct Player {
+    entries: VecDeque<Entry>,
+    // Seconds spent waiting for checks, the entries after them are played that much later
+    waited: f64,
+    // Since when the next check is waited for
+    waiting_since: Option<f64>,
+}
+
+impl Player {
+    fn load(path: &Path) -> Result<Self, String> {
+        let script = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let entries = script
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            entries,
+            waited: 0.0,
+            waiting_since: None,
+        })
+    }
+}
+
+// Send the input of the entries whose time came as if the player gave it, in `First` so the
+// game sees it this frame, and stop at a check until it holds
+fn play_input(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut player: ResMut<Player>,
+    texts: Query<(&Text, &ComputedVisibility)>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut keys: EventWriter<KeyboardInput>,
+    mut chars: EventWriter<ReceivedCharacter>,
+    mut buttons: EventWriter<MouseButtonInput>,
+    mut cursor: EventWriter<CursorMoved>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Ok((window, mut primary)) = windows.get_single_mut() else {
+        return;
+    };
+    let now = time.elapsed_seconds_f64();
+    while let Some(entry) = player.entries.front() {
+        if entry.at + player.waited > now {
+            return;
+        }
+        let holds = match &entry.input {
+            ScriptInput::Screen { screen } => format!("{:?}", state.0) == *screen,
+            ScriptInput::Text { text } => texts.iter().any(|(shown, visibility)| {
+                let shown: String = shown.sections.iter().map(|s| s.value.as_str()).collect();
+                visibility.is_visible_in_hierarchy() && shown.contains(text.as_str())
+            }),
+            _ => true,
+        };
+        if !holds {
+            let check = format!("{:?} at {}", entry.input, entry.at);
+            let since = *player.waiting_since.get_or_insert(now);
+            if now - since > CHECK_SECONDS {
+                error!("input script failed, {check} didn't hold");
+                std::process::exit(1);
+            }
+            return;
+        }
+        if let Some(since) = player.waiting_since.take() {
+            player.waited += now - since;
+        }
+        let Some(entry) = player.entries.pop_front() else {
+            break;
+        };
+        match entry.input {
+            ScriptInput::Key { key, pressed } => match key_code(&key) {
+                Some(key) => keys.send(KeyboardInput {
+                    scan_code: 0,
+                    key_code: Some(key),
+                    state: button_state(pressed),
+                }),
+                None => warn!("skipping the unknown key {key}"),
+            },
+            ScriptInput::Char { char } => chars.send(ReceivedCharacter { window, char }),
+            ScriptInput::Mouse { button, pressed } => match mouse_button(&button) {
+                Some(button) => buttons.send(MouseButtonInput {
+                    button,
+                    state: button_state(pressed),
+                }),
+                None => warn!("skipping the unknown mouse button {button}"),
+            },
+            ScriptInput::Cursor { x, y } => {
+                let position = Vec2::new(x, y);
+                primary.set_cursor_position(Some(position));
+                cursor.send(CursorMoved { window, position });
+            }
+            ScriptInput::Screen { .. } | ScriptInput::Text { .. } => {}
+        }
+    }
+    info!("input script played to the end");
+    exit.send(AppExit);
+}
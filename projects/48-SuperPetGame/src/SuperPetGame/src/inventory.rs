@@ -0,0 +1,409 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition, window::PrimaryWindow};
+use subxt::{dynamic::Value, ext::codec::Decode, storage::DynamicAddress};
+
+use super::{
+    assets::GameAssets,
+    client::{ChainClient, StorageChanged},
+    despawn_screen,
+    settings::Palette,
+    snapshot::PetSnapshot,
+    tooltip::CallHelp,
+    GameState, TEXT_COLOR,
+};
+
+// Items of the items pallet with their category, in the order of its `Item` enum
+pub const ITEMS: [(&str, &str); 11] = [
+    ("Apple", "Food"),
+    ("Fish", "Food"),
+    ("Carrot", "Food"),
+    ("Ball", "Toy"),
+    ("Rope", "Toy"),
+    ("Hat", "Accessory"),
+    ("Scarf", "Accessory"),
+    ("Heater", "Incubator"),
+    ("CaretakerBadge", "Badge"),
+    ("ChampionBadge", "Badge"),
+    ("DreamerBadge", "Badge"),
+];
+
+// Name of the storage watch keeping the counts up to date
+const INVENTORY_WATCH: &str = "inventory";
+
+// This plugin shows the items held by the player. Items are dragged onto the pet to use them,
+// and the counts follow the chain through a storage watch. Badges are only shown, they are
+// earned with quests and can't be bought or used.
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            inventory_setup.in_schedule(OnEnter(GameState::Inventory)),
+            inventory_exit.in_schedule(OnExit(GameState::Inventory)),
+            despawn_screen::<OnInventoryScreen>.in_schedule(OnExit(GameState::Inventory)),
+        ))
+        .add_systems(
+            (inventory_update, inventory_action, item_drag, button_system)
+                .in_set(OnUpdate(GameState::Inventory)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the inventory screen
+#[derive(Component)]
+struct OnInventoryScreen;
+
+#[derive(Component)]
+struct OnInventoryStatusText;
+
+// Text showing how many of an item the player holds, with the storage key of that count
+#[derive(Component)]
+struct ItemCount {
+    key: Vec<u8>,
+}
+
+// Area showing the pet, items dropped there are used on it
+#[derive(Component)]
+struct PetDropZone;
+
+// Item following the cursor while it's dragged onto the pet
+#[derive(Component)]
+struct DraggedItem(usize);
+
+#[derive(Component)]
+enum InventoryButtonAction {
+    Drag(usize),
+    Buy(usize),
+    BackToGame,
+}
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    palette: Res<Palette>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        *color = palette.button(*interaction, false).into();
+    }
+}
+
+fn inventory_address(account: &[u8], item: usize) -> DynamicAddress<Value> {
+    subxt::dynamic::storage(
+        "ItemsModule",
+        "Inventory",
+        vec![
+            Value::from_bytes(account),
+            Value::unnamed_variant(ITEMS[item].0, []),
+        ],
+    )
+}
+
+fn inventory_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 22.0,
+        color: TEXT_COLOR,
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(90.0), Val::Px(36.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let account = client.account_id();
+    let keys = (0..ITEMS.len())
+        .map(|item| client.storage_key(&inventory_address(&account.0, item)))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|keys| {
+            client.watch_storage(INVENTORY_WATCH, keys.clone())?;
+            Ok(keys)
+        });
+    let (keys, status) = match keys {
+        Ok(keys) => (keys, "Drag an item onto your pet to use it".to_string()),
+        Err(e) => (vec![vec![]; ITEMS.len()], format!("Can't load items: {e}")),
+    };
+    let pet = PetSnapshot::fetch(&mut client, &account).ok();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnInventoryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(status, text_style.clone()),
+                OnInventoryStatusText,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::all(Val::Px(20.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // One row per item: name, count and buy button
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                gap: Size::all(Val::Px(6.0)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (item, key) in keys.into_iter().enumerate() {
+                                let (name, category) = ITEMS[item];
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            gap: Size::all(Val::Px(10.0)),
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        let badge = category == "Badge";
+                                        let mut name_button = parent.spawn(ButtonBundle {
+                                            style: Style {
+                                                size: Size::new(Val::Px(200.0), Val::Px(36.0)),
+                                                ..button_style.clone()
+                                            },
+                                            background_color: NORMAL_BUTTON.into(),
+                                            ..default()
+                                        });
+                                        if !badge {
+                                            name_button.insert(InventoryButtonAction::Drag(item));
+                                        }
+                                        name_button.with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(
+                                                format!("{name} ({category})"),
+                                                text_style.clone(),
+                                            ));
+                                        });
+                                        parent.spawn((
+                                            TextBundle::from_section("x0", text_style.clone()),
+                                            ItemCount { key },
+                                        ));
+                                        if badge {
+                                            return;
+                                        }
+                                        parent
+                                            .spawn((
+                                                ButtonBundle {
+                                                    style: button_style.clone(),
+                                                    background_color: NORMAL_BUTTON.into(),
+                                                    ..default()
+                                                },
+                                                InventoryButtonAction::Buy(item),
+                                                CallHelp {
+                                                    pallet: "ItemsModule",
+                                                    call: "buy",
+                                                },
+                                            ))
+                                            .with_children(|parent| {
+                                                parent.spawn(TextBundle::from_section(
+                                                    "Buy",
+                                                    text_style.clone(),
+                                                ));
+                                            });
+                                    });
+                            }
+                        });
+                    parent
+                        .spawn((
+                            ImageBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(200.0), Val::Px(200.0)),
+                                    ..default()
+                                },
+                                image: pet
+                                    .as_ref()
+                                    .map(|pet| assets.pet_image(&pet.species))
+                                    .unwrap_or_default()
+                                    .into(),
+                                background_color: Color::rgba(1.0, 1.0, 1.0, 0.9).into(),
+                                ..default()
+                            },
+                            PetDropZone,
+                            RelativeCursorPosition::default(),
+                            CallHelp {
+                                pallet: "ItemsModule",
+                                call: "use_item",
+                            },
+                        ))
+                        .with_children(|parent| {
+                            if pet.is_none() {
+                                parent.spawn(TextBundle::from_section(
+                                    "No pet",
+                                    TextStyle {
+                                        color: Color::BLACK,
+                                        ..text_style.clone()
+                                    },
+                                ));
+                            }
+                        });
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                            ..button_style.clone()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    InventoryButtonAction::BackToGame,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style.clone()));
+                });
+        });
+}
+
+fn inventory_exit(mut client: ResMut<ChainClient>) {
+    client.unwatch_storage(INVENTORY_WATCH);
+}
+
+// Show the counts sent by the storage watch
+fn inventory_update(
+    mut events: EventReader<StorageChanged>,
+    mut counts: Query<(&ItemCount, &mut Text)>,
+) {
+    for change in events
+        .iter()
+        .filter(|change| change.watch == INVENTORY_WATCH)
+    {
+        let count = change
+            .value
+            .as_ref()
+            .and_then(|value| u32::decode(&mut &value[..]).ok())
+            .unwrap_or_default();
+        for (_, mut text) in counts.iter_mut().filter(|(c, _)| c.key == change.key) {
+            text.sections[0].value = format!("x{count}");
+        }
+    }
+}
+
+fn inventory_action(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    interaction_query: Query<
+        (&Interaction, &InventoryButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut client: ResMut<ChainClient>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut status_text: Query<&mut Text, With<OnInventoryStatusText>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            //Pick the item up, it's used when released over the pet
+            InventoryButtonAction::Drag(item) => {
+                commands.spawn((
+                    TextBundle::from_section(
+                        ITEMS[*item].0,
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 28.0,
+                            color: Color::YELLOW,
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    }),
+                    DraggedItem(*item),
+                    OnInventoryScreen,
+                ));
+            }
+            //The new count comes back through the storage watch
+            InventoryButtonAction::Buy(item) => {
+                let _span = info_span!("buy item", item = ITEMS[*item].0).entered();
+                let buy = subxt::dynamic::tx(
+                    "ItemsModule",
+                    "buy",
+                    vec![Value::unnamed_variant(ITEMS[*item].0, []), Value::u128(1)],
+                );
+                status_text.single_mut().sections[0].value = match client.submit(&buy) {
+                    Ok(_) => format!("Buying one {}", ITEMS[*item].0),
+                    Err(e) => format!("Can't buy: {e}"),
+                };
+            }
+            InventoryButtonAction::BackToGame => game_state.set(GameState::Game),
+        }
+    }
+}
+
+// Move the dragged item with the cursor and use it when it's dropped on the pet
+fn item_drag(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut dragged: Query<(Entity, &DraggedItem, &mut Style)>,
+    drop_zone: Query<&RelativeCursorPosition, With<PetDropZone>>,
+    mut client: ResMut<ChainClient>,
+    mut status_text: Query<&mut Text, With<OnInventoryStatusText>>,
+) {
+    let Ok((entity, item, mut style)) = dragged.get_single_mut() else {
+        return;
+    };
+    if let Some(cursor) = windows.get_single().ok().and_then(|window| {
+        // The cursor position starts from the bottom left of the window
+        window
+            .cursor_position()
+            .map(|cursor| Vec2::new(cursor.x, window.height() - cursor.y))
+    }) {
+        style.position = UiRect {
+            left: Val::Px(cursor.x + 8.0),
+            top: Val::Px(cursor.y + 8.0),
+            ..default()
+        };
+    }
+    if !mouse_button_input.just_released(MouseButton::Left) {
+        return;
+    }
+
+    commands.entity(entity).despawn_recursive();
+    if !drop_zone.iter().any(RelativeCursorPosition::mouse_over) {
+        return;
+    }
+    let _span = info_span!("use item", item = ITEMS[item.0].0).entered();
+    let use_item = subxt::dynamic::tx(
+        "ItemsModule",
+        "use_item",
+        vec![Value::unnamed_variant(ITEMS[item.0].0, [])],
+    );
+    status_text.single_mut().sections[0].value = match client.submit(&use_item) {
+        Ok(_) => format!("Giving the {} to your pet", ITEMS[item.0].0),
+        Err(e) => format!("Can't use item: {e}"),
+    };
+}
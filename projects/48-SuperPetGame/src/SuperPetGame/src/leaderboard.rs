@@ -0,0 +1,385 @@
+use std::collections::BTreeSet;
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{ChainClient, PetId},
+    despawn_screen,
+    identity::{AccountNames, Names},
+    indexer::{IndexDb, IndexerError, RATE_WINDOW},
+    pet_profile::ProfileLink,
+    settings::Palette,
+    GameState, TEXT_COLOR,
+};
+
+// Pets ranked on the board
+const RANKED: usize = 10;
+
+// Winners read from the index, more than ranked so the verified board is still full
+const WINNERS_READ: u32 = 50;
+
+// This plugin ranks the pets that won the most battles over the last week, from the event
+// index. The verified board leaves out the pets of accounts failing the indexer's rate checks,
+// whose battles are unlikely to be started by hand.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VerifiedOnly>()
+            .add_systems((
+                leaderboard_setup.in_schedule(OnEnter(GameState::Leaderboard)),
+                despawn_screen::<OnLeaderboardScreen>.in_schedule(OnExit(GameState::Leaderboard)),
+            ))
+            .add_systems(
+                (
+                    receive_leaderboard,
+                    leaderboard_action,
+                    color_choices.after(leaderboard_action),
+                    show_board
+                        .after(receive_leaderboard)
+                        .after(leaderboard_action),
+                )
+                    .in_set(OnUpdate(GameState::Leaderboard)),
+            );
+    }
+}
+
+// Tag component used to tag entities added on the leaderboard screen
+#[derive(Component)]
+struct OnLeaderboardScreen;
+
+#[derive(Component)]
+struct BoardArea;
+
+#[derive(Component)]
+enum LeaderboardAction {
+    Filter(bool),
+    Back,
+}
+
+// Whether only verified accounts are ranked, kept between visits
+#[derive(Resource)]
+struct VerifiedOnly(bool);
+
+impl Default for VerifiedOnly {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+struct Entry {
+    pet_id: PetId,
+    owner: AccountId32,
+    wins: u32,
+    verified: bool,
+}
+
+#[derive(Resource)]
+struct Leaderboard(Vec<Entry>);
+
+// Result of the background leaderboard read
+#[derive(Resource)]
+struct LeaderboardLoad(Mutex<mpsc::Receiver<Result<Leaderboard, String>>>);
+
+// Current owner of a pet, `None` when nobody owns it anymore
+async fn pet_owner(
+    api: &OnlineClient<PolkadotConfig>,
+    pet_id: PetId,
+) -> Result<Option<AccountId32>, subxt::Error> {
+    let owner = api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&subxt::dynamic::storage(
+            "PetModule",
+            "PetOwners",
+            vec![Value::u128(pet_id.into())],
+        ))
+        .await?
+        .map(|value| AccountId32::decode(&mut value.encoded()))
+        .transpose()?;
+    Ok(owner)
+}
+
+// Read the week's battle winners from the event index and check their owners' rates
+async fn load_leaderboard(api: OnlineClient<PolkadotConfig>) -> Result<Leaderboard, IndexerError> {
+    let db = IndexDb::open()?;
+    let since = db.last_block()?.unwrap_or(0).saturating_sub(RATE_WINDOW);
+    let flagged: BTreeSet<AccountId32> = db
+        .flagged_accounts()?
+        .into_iter()
+        .map(|rates| rates.account)
+        .collect();
+    let mut entries = Vec::new();
+    // Pets nobody owns anymore, e.g. abandoned ones, are left out
+    for (pet_id, wins) in db.battle_winners(since, WINNERS_READ)? {
+        if let Some(owner) = pet_owner(&api, pet_id).await? {
+            entries.push(Entry {
+                pet_id,
+                verified: !flagged.contains(&owner),
+                owner,
+                wins,
+            });
+        }
+    }
+    Ok(Leaderboard(entries))
+}
+
+fn leaderboard_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let choice_style = Style {
+        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+        ..default()
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    match client.api() {
+        Ok(api) => {
+            client.spawn(async move {
+                let leaderboard = load_leaderboard(api).await;
+                let _ = sender.send(leaderboard.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(LeaderboardLoad(Mutex::new(receiver)));
+    commands.remove_resource::<Leaderboard>();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(15.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnLeaderboardScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Most battles won this week",
+                text_style.clone(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, verified) in [("Verified", true), ("All", false)] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: choice_style.clone(),
+                                    ..default()
+                                },
+                                LeaderboardAction::Filter(verified),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(6.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    BoardArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the battles...",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    LeaderboardAction::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+fn receive_leaderboard(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<LeaderboardLoad>>,
+    area: Query<Entity, With<BoardArea>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<LeaderboardLoad>();
+    match result {
+        Ok(leaderboard) => commands.insert_resource(leaderboard),
+        Err(e) => {
+            let text_style = TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 24.0,
+                color: TEXT_COLOR,
+            };
+            for area in &area {
+                commands
+                    .entity(area)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Can't read the leaderboard: {e}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+        }
+    }
+}
+
+// Rank the pets of the chosen board, each opening its profile when clicked
+fn show_board(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    leaderboard: Option<Res<Leaderboard>>,
+    verified_only: Res<VerifiedOnly>,
+    names: Res<Names>,
+    area: Query<Entity, With<BoardArea>>,
+) {
+    let Some(leaderboard) = leaderboard else {
+        return;
+    };
+    if !leaderboard.is_changed() && !verified_only.is_changed() {
+        return;
+    }
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+
+    let ranked: Vec<_> = leaderboard
+        .0
+        .iter()
+        .filter(|entry| entry.verified || !verified_only.0)
+        .take(RANKED)
+        .collect();
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            if ranked.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No battles won this week",
+                    text_style.clone(),
+                ));
+            }
+            for (rank, entry) in ranked.iter().enumerate() {
+                // Shown on the full board only, the verified one has none
+                let flagged = if entry.verified { "" } else { " (unverified)" };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        ProfileLink(entry.pet_id),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                format!(
+                                    "{}. Pet #{} of {}, {} wins{flagged}",
+                                    rank + 1,
+                                    entry.pet_id,
+                                    names.display(&entry.owner),
+                                    entry.wins
+                                ),
+                                text_style.clone(),
+                            ),
+                            AccountNames(vec![entry.owner.clone()]),
+                        ));
+                    });
+            }
+        });
+}
+
+fn leaderboard_action(
+    interaction_query: Query<(&Interaction, &LeaderboardAction), Changed<Interaction>>,
+    mut verified_only: ResMut<VerifiedOnly>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            LeaderboardAction::Filter(verified) => verified_only.0 = *verified,
+            LeaderboardAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// Highlight the board shown
+fn color_choices(
+    verified_only: Res<VerifiedOnly>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &LeaderboardAction, &mut BackgroundColor)>,
+) {
+    for (interaction, action, mut color) in &mut buttons {
+        if !verified_only.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected =
+            matches!(action, LeaderboardAction::Filter(verified) if *verified == verified_only.0);
+        *color = palette.button(*interaction, selected).into();
+    }
+}
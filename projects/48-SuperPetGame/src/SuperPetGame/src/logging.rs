@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use bevy::prelude::*;
+use bevy::utils::tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, EnvFilter, Layer};
+
+use super::telemetry;
+
+// Log of the last run, written next to the other files the game keeps, to attach to bug
+// reports
+pub const LOG_FILE: &str = "superpet.log";
+
+// Log levels when `RUST_LOG` isn't set, the same as the engine's default
+const DEFAULT_FILTER: &str = "info,wgpu=error";
+
+// Lines kept for the debug console, and the ones it shows
+const KEPT_LINES: usize = 500;
+const SHOWN_LINES: usize = 30;
+
+// Install the log subscriber, before anything logs. Logs go to the terminal, to `LOG_FILE`
+// and to the debug console, each line with the spans it happened in, e.g. the transaction
+// being built, submitted or finalized for the button that queued it.
+pub fn init() -> ConsoleLines {
+    let lines = ConsoleLines::default();
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let file = match File::create(LOG_FILE) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("can't write {LOG_FILE}: {e}");
+            None
+        }
+    };
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(Mutex::new(file))
+    });
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .with(ConsoleLayer(lines.clone()))
+        .with(telemetry::ReportLayer)
+        .init();
+    lines
+}
+
+// This plugin shows the recent log lines and closed spans in a debug console over the game,
+// toggled with F12. The engine's own log plugin must be disabled, `init` replaces it.
+pub struct LoggingPlugin(pub ConsoleLines);
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .add_systems((toggle_console, console_text).chain());
+    }
+}
+
+// The recent lines, shared with the log subscriber
+#[derive(Resource, Clone, Default)]
+pub struct ConsoleLines(Arc<Mutex<Lines>>);
+
+#[derive(Default)]
+struct Lines {
+    recent: VecDeque<String>,
+    // Lines ever pushed, to know when the console is out of date
+    count: u64,
+}
+
+impl ConsoleLines {
+    fn push(&self, line: String) {
+        let Ok(mut lines) = self.0.lock() else {
+            return;
+        };
+        if lines.recent.len() == KEPT_LINES {
+            lines.recent.pop_front();
+        }
+        lines.recent.push_back(line);
+        lines.count += 1;
+    }
+}
+
+// What the console keeps about an open span
+struct SpanInfo {
+    fields: String,
+    opened: Instant,
+}
+
+// Collects the fields of an event or span as `name=value` pairs, the message first
+#[derive(Default)]
+pub struct Fields(pub String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, "{}={value:?}", field.name());
+        }
+    }
+}
+
+struct ConsoleLayer(ConsoleLines);
+
+impl<S> Layer<S> for ConsoleLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanInfo {
+            fields: fields.0,
+            opened: Instant::now(),
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut line = format!("{} ", event.metadata().level());
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let _ = write!(line, "{}: ", span.name());
+            }
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        line.push_str(&fields.0);
+        self.0.push(line);
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(info) = extensions.get::<SpanInfo>() else {
+            return;
+        };
+        self.0.push(format!(
+            "SPAN {} {} took {} ms",
+            span.name(),
+            info.fields,
+            info.opened.elapsed().as_millis()
+        ));
+    }
+}
+
+// Tag component used to tag the debug console
+#[derive(Component)]
+struct OnDebugConsole;
+
+fn toggle_console(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    keys: Res<Input<KeyCode>>,
+    consoles: Query<Entity, With<OnDebugConsole>>,
+) {
+    if !keys.just_pressed(KeyCode::F12) {
+        return;
+    }
+    if let Ok(console) = consoles.get_single() {
+        commands.entity(console).despawn_recursive();
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 12.0,
+                color: Color::rgb(0.8, 1.0, 0.8),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.85)),
+        ZIndex::Global(20),
+        OnDebugConsole,
+    ));
+}
+
+// Show the last lines while the console is open
+fn console_text(
+    lines: Res<ConsoleLines>,
+    mut texts: Query<&mut Text, With<OnDebugConsole>>,
+    mut shown: Local<Option<u64>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        *shown = None;
+        return;
+    };
+    let Ok(lines) = lines.0.lock() else {
+        return;
+    };
+    if *shown == Some(lines.count) {
+        return;
+    }
+    *shown = Some(lines.count);
+    let skipped = lines.recent.len().saturating_sub(SHOWN_LINES);
+    let mut value = format!("Debug console (F12), full log in {LOG_FILE}\n");
+    for line in lines.recent.iter().skip(skipped) {
+        value.push_str(line);
+        value.push('\n');
+    }
+    text.sections[0].value = value;
+}
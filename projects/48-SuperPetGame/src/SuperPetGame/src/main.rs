@@ -1,11 +1,65 @@
 //#[cfg(target_os = "macos")]
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy::window::{Window, WindowPlugin, WindowResolution};
 
+mod accounts;
+mod address_book;
+mod analytics;
+mod assets;
+mod autocare;
+mod battle;
+mod chains;
+mod client;
+mod clock;
+mod cold_sign;
+mod demo;
+mod diagnostics;
+mod egg;
+mod emotes;
+mod fee;
 mod game;
+mod household;
+mod identity;
+mod indexer;
+mod input_script;
+mod inventory;
+mod leaderboard;
+mod logging;
+mod market;
+mod memorial;
 mod menu;
+mod naming;
+mod navigation;
+mod notifier;
+mod offers;
+mod onboarding;
+mod pet_profile;
+mod pet_watch;
+mod platform;
+mod poll;
+mod practice;
+mod profile;
+mod push;
+mod qr;
+mod quests;
+mod recap;
+mod replay;
+mod schedule;
+mod seasonal;
+mod settings;
+mod skins;
+mod snapshot;
+mod sounds;
 mod splash;
-//mod client;
+mod telemetry;
+mod toast;
+mod tooltip;
+mod tournament;
+mod trade_chat;
+mod txqueue;
+mod upgrade;
+mod widget;
 
 const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 
@@ -14,8 +68,23 @@ const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 enum GameState {
     #[default]
     Splash,
+    Onboarding,
     Menu,
     Game,
+    Battle,
+    Inventory,
+    Analytics,
+    Market,
+    PetProfile,
+    AddressBook,
+    Tournament,
+    Leaderboard,
+    Practice,
+    Replay,
+    TradeChat,
+    Memorial,
+    Schedule,
+    ColdSign,
 }
 
 // Enum that will be used as a global state for the game
@@ -26,21 +95,68 @@ enum PetOwned {
     Enable,
 }
 
+// Log why the game can't start, in the directory it got to, and quit
+fn quit(error: &str, warnings: &[String]) -> ! {
+    logging::init();
+    for warning in warnings {
+        warn!("{warning}");
+    }
+    error!("{error}");
+    std::process::exit(1);
+}
+
 fn main() {
+    let profiles = match profile::select() {
+        Ok(profiles) => profiles,
+        Err(e) => quit(&e, &[]),
+    };
+    let chains = match chains::select() {
+        Ok(chains) => chains,
+        Err(e) => quit(&e, &profiles.warnings),
+    };
+    let console = logging::init();
+    for warning in profiles.warnings.iter().chain(&chains.warnings) {
+        warn!("{warning}");
+    }
+    telemetry::init();
+
+    // Only follow the chain into the event database, without opening the game window
+    if std::env::args().any(|arg| arg == "--indexer") {
+        indexer::run_standalone(&chains.current.url);
+        return;
+    }
+
+    // The log subscriber is already installed by `logging::init`
+    let plugins = DefaultPlugins.build().disable::<LogPlugin>();
+    let desktop = platform::Desktop::detect();
+    let transparent = desktop.transparent();
+    info!("running on {}, transparent: {transparent}", desktop.name);
+    let mut window = Window {
+        title: match (profiles.current.as_str(), chains.current.name.as_str()) {
+            (profile::DEFAULT_PROFILE, chains::DEFAULT_CHAIN) => "Super Pet Game".to_string(),
+            (name, chains::DEFAULT_CHAIN) | (profile::DEFAULT_PROFILE, name) => {
+                format!("Super Pet Game ({name})")
+            }
+            (profile, chain) => format!("Super Pet Game ({profile} on {chain})"),
+        },
+        resolution: WindowResolution::new(800., 600.).with_scale_factor_override(1.0),
+        // Disabling window decorations to make it feel more like a widget than a window
+        decorations: true,
+        ..default()
+    };
+    // Transparent where the desktop allows it, so the `ClearColor`'s alpha value takes effect
+    desktop.configure(&mut window);
+    // Input scripts play in a hidden window, see `input_script.rs`
+    if input_script::playing() {
+        window.visible = false;
+    }
+    // Input scripts are given relative to the directory the game was started in
+    let started_in = profiles.root().to_path_buf();
     App::new()
-        .insert_resource(ClearColor(Color::NONE))
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: ("Super Pet Game").to_string(),
-                resolution: WindowResolution::new(800., 600.).with_scale_factor_override(1.0),
-                // Setting `transparent` allows the `ClearColor`'s alpha value to take effect
-                transparent: true,
-                // Disabling window decorations to make it feel more like a widget than a window
-                decorations: true,
-                //#[cfg(target_os = "macos")]
-                //composite_alpha_mode: CompositeAlphaMode::PostMultiplied,
-                ..default()
-            }),
+        .insert_resource(desktop.clear_color())
+        .insert_resource(desktop)
+        .add_plugins(plugins.set(WindowPlugin {
+            primary_window: Some(window),
             ..default()
         }))
         .add_startup_system(setup)
@@ -48,10 +164,59 @@ fn main() {
         .add_state::<GameState>()
         .add_state::<PetOwned>()
         // Adds the plugins for each state
+        .add_plugin(assets::AssetsPlugin)
         .add_plugin(splash::SplashPlugin)
+        .add_plugin(onboarding::OnboardingPlugin)
         .add_plugin(menu::MenuPlugin)
         .add_plugin(game::GamePlugin)
-        //.add_plugin(client::ClientPlugin)
+        .add_plugin(battle::BattlePlugin)
+        .add_plugin(inventory::InventoryPlugin)
+        .add_plugin(analytics::AnalyticsPlugin)
+        .add_plugin(market::MarketPlugin)
+        .add_plugin(client::ClientPlugin(chains.current.url.clone()))
+        .add_plugin(chains::ChainsPlugin(chains))
+        .add_plugin(clock::ClockPlugin)
+        .add_plugin(cold_sign::ColdSignPlugin)
+        .add_plugin(demo::DemoPlugin)
+        .add_plugin(accounts::AccountsPlugin)
+        .add_plugin(address_book::AddressBookPlugin)
+        .add_plugin(autocare::AutoCarePlugin)
+        .add_plugin(diagnostics::DiagnosticsOverlayPlugin)
+        .add_plugin(egg::EggPlugin)
+        .add_plugin(emotes::EmotesPlugin)
+        .add_plugin(fee::FeePlugin)
+        .add_plugin(household::HouseholdPlugin)
+        .add_plugin(identity::IdentityPlugin)
+        .add_plugin(indexer::IndexerPlugin)
+        .add_plugin(input_script::InputScriptPlugin(started_in))
+        .add_plugin(leaderboard::LeaderboardPlugin)
+        .add_plugin(logging::LoggingPlugin(console))
+        .add_plugin(memorial::MemorialPlugin)
+        .add_plugin(schedule::SchedulePlugin)
+        .add_plugin(navigation::NavigationPlugin)
+        .add_plugin(notifier::NotifierPlugin)
+        .add_plugin(offers::OffersPlugin)
+        .add_plugin(pet_profile::PetProfilePlugin)
+        .add_plugin(pet_watch::PetWatchPlugin)
+        .add_plugin(poll::PollPlugin)
+        .add_plugin(practice::PracticePlugin)
+        .add_plugin(profile::ProfilePlugin(profiles))
+        .add_plugin(qr::QrPlugin)
+        .add_plugin(quests::QuestsPlugin)
+        .add_plugin(recap::RecapPlugin)
+        .add_plugin(replay::ReplayPlugin)
+        .add_plugin(seasonal::SeasonalPlugin)
+        .add_plugin(settings::SettingsPlugin)
+        .add_plugin(skins::SkinsPlugin)
+        .add_plugin(sounds::SoundsPlugin)
+        .add_plugin(telemetry::TelemetryPlugin)
+        .add_plugin(toast::ToastPlugin)
+        .add_plugin(tooltip::TooltipPlugin)
+        .add_plugin(tournament::TournamentPlugin)
+        .add_plugin(trade_chat::TradeChatPlugin)
+        .add_plugin(txqueue::TxQueuePlugin)
+        .add_plugin(upgrade::UpgradePlugin)
+        .add_plugin(widget::WidgetPlugin)
         .run();
 }
 
@@ -65,6 +230,32 @@ fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands
         commands.entity(entity).despawn_recursive();
     }
 }
+
+// Generic systems for screens spawned once and kept between visits: `hide_screen` hides the
+// entities with that component when leaving the screen, `show_screen` shows them again
+fn hide_screen<T: Component>(mut screens: Query<(&mut Visibility, Option<&mut Style>), With<T>>) {
+    for (mut visibility, style) in &mut screens {
+        *visibility = Visibility::Hidden;
+        // Hidden root nodes still take room in the layout, next to the shown screen
+        if let Some(mut style) = style {
+            style.display = Display::None;
+        }
+    }
+}
+
+fn show_screen<T: Component>(mut screens: Query<(&mut Visibility, Option<&mut Style>), With<T>>) {
+    for (mut visibility, style) in &mut screens {
+        *visibility = Visibility::Inherited;
+        if let Some(mut style) = style {
+            style.display = Display::Flex;
+        }
+    }
+}
+
+// Run condition spawning a kept screen on the first visit only
+fn screen_missing<T: Component>(screens: Query<(), With<T>>) -> bool {
+    screens.is_empty()
+}
 /*
 // Tick the timer, and change state when finished
 fn game(
@@ -0,0 +1,612 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{format_balance, polkadot, ChainClient, PetId, PetSpecies},
+    despawn_screen,
+    identity::{AccountNames, Names},
+    indexer::{IndexDb, IndexerError},
+    pet_profile::ProfileLink,
+    settings::Palette,
+    GameState, TEXT_COLOR,
+};
+
+// File caching the species of the pets seen on the market, a pet never changes species
+const SPECIES_CACHE_FILE: &str = "pet-species.json";
+
+const BLOCKS_PER_HOUR: u32 = 600;
+const BLOCKS_PER_DAY: u32 = 24 * BLOCKS_PER_HOUR;
+
+// Ranges of the price chart: label, length and blocks per bar
+const RANGES: [(&str, u32, u32); 3] = [
+    ("Day", BLOCKS_PER_DAY, BLOCKS_PER_HOUR),
+    ("Week", 7 * BLOCKS_PER_DAY, BLOCKS_PER_DAY),
+    ("Month", 30 * BLOCKS_PER_DAY, BLOCKS_PER_DAY),
+];
+
+// Height of the bar of the highest average price
+const CHART_HEIGHT: f32 = 160.0;
+
+// Cheapest listings of the chosen species shown under the chart
+const LISTINGS_SHOWN: usize = 5;
+
+// This plugin charts the prices pets of each species sold for, from the sales in the event
+// index, and shows the cheapest pets of the species listed right now with their sellers,
+// whose profile opens when clicked
+pub struct MarketPlugin;
+
+impl Plugin for MarketPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MarketView>()
+            .add_systems((
+                market_setup.in_schedule(OnEnter(GameState::Market)),
+                despawn_screen::<OnMarketScreen>.in_schedule(OnExit(GameState::Market)),
+            ))
+            .add_systems(
+                (
+                    receive_market,
+                    market_choice,
+                    color_choices.after(market_choice),
+                    show_chart.after(receive_market).after(market_choice),
+                    market_action,
+                )
+                    .in_set(OnUpdate(GameState::Market)),
+            );
+    }
+}
+
+// Tag component used to tag entities added on the market screen
+#[derive(Component)]
+struct OnMarketScreen;
+
+// Row the species buttons are added to once the market is read
+#[derive(Component)]
+struct SpeciesRow;
+
+#[derive(Component)]
+struct ChartArea;
+
+#[derive(Component)]
+enum MarketChoice {
+    Species(String),
+    Range(usize),
+}
+
+#[derive(Component)]
+struct BackToMenu;
+
+// Species and range shown on the chart
+#[derive(Resource, Default)]
+struct MarketView {
+    species: Option<String>,
+    range: usize,
+}
+
+struct Sale {
+    block: u32,
+    species: String,
+    price: u128,
+}
+
+struct Listing {
+    pet_id: PetId,
+    species: String,
+    seller: AccountId32,
+    price: u128,
+}
+
+// Sales of the longest range and current listings, with the species of their pets
+#[derive(Resource)]
+struct MarketData {
+    now: u32,
+    species: Vec<String>,
+    sales: Vec<Sale>,
+    listings: Vec<Listing>,
+}
+
+// Result of the background market read
+#[derive(Resource)]
+struct MarketLoad(Mutex<mpsc::Receiver<Result<MarketData, String>>>);
+
+fn load_species_cache() -> BTreeMap<PetId, String> {
+    std::fs::read(SPECIES_CACHE_FILE)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_species_cache(cache: &BTreeMap<PetId, String>) {
+    let saved = serde_json::to_vec(cache)
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(SPECIES_CACHE_FILE, json));
+    if let Err(e) = saved {
+        error!("error caching pet species: {e:?}");
+    }
+}
+
+// Species of a pet from its current owner, `None` when nobody owns it anymore
+async fn pet_species(
+    api: &OnlineClient<PolkadotConfig>,
+    pet_id: PetId,
+) -> Result<Option<String>, subxt::Error> {
+    let storage = api.storage().at_latest().await?;
+    let owner = storage
+        .fetch(&subxt::dynamic::storage(
+            "PetModule",
+            "PetOwners",
+            vec![Value::u128(pet_id.into())],
+        ))
+        .await?
+        .map(|value| AccountId32::decode(&mut value.encoded()))
+        .transpose()?;
+    let Some(owner) = owner else {
+        return Ok(None);
+    };
+    let pet = storage
+        .fetch(&polkadot::storage().pet_module().pets_info(&owner))
+        .await?;
+    Ok(pet.map(|(_, pet)| format!("{:?}", pet.species)))
+}
+
+// Read the sales of the last month and the listings from the event index, and the species
+// of their pets from the chain unless they are cached
+async fn load_market(api: OnlineClient<PolkadotConfig>) -> Result<MarketData, IndexerError> {
+    let now = api.blocks().at_latest().await?.number();
+    let supply: Vec<(PetSpecies, u32, Option<u32>)> = api
+        .rpc()
+        .state_call("PetApi_species_supply", None, None)
+        .await?;
+    let db = IndexDb::open()?;
+    let (_, longest, _) = RANGES[RANGES.len() - 1];
+    let sales = db.sales(now.saturating_sub(longest))?;
+    let listings = db.listings()?;
+
+    let mut cache = load_species_cache();
+    let pets: BTreeSet<_> = sales
+        .iter()
+        .map(|(_, pet_id, _)| *pet_id)
+        .chain(listings.iter().map(|(pet_id, _, _)| *pet_id))
+        .collect();
+    // Pets nobody owns anymore, e.g. abandoned ones, are left out
+    for pet_id in pets {
+        if cache.contains_key(&pet_id) {
+            continue;
+        }
+        if let Some(species) = pet_species(&api, pet_id).await? {
+            cache.insert(pet_id, species);
+        }
+    }
+    save_species_cache(&cache);
+
+    Ok(MarketData {
+        now,
+        species: supply
+            .iter()
+            .map(|(species, _, _)| format!("{species:?}"))
+            .collect(),
+        sales: sales
+            .into_iter()
+            .filter_map(|(block, pet_id, price)| {
+                Some(Sale {
+                    block,
+                    species: cache.get(&pet_id)?.clone(),
+                    price,
+                })
+            })
+            .collect(),
+        listings: listings
+            .into_iter()
+            .filter_map(|(pet_id, seller, price)| {
+                Some(Listing {
+                    pet_id,
+                    species: cache.get(&pet_id)?.clone(),
+                    seller,
+                    price,
+                })
+            })
+            .collect(),
+    })
+}
+
+fn market_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let choice_style = Style {
+        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+        ..default()
+    };
+    let row_style = Style {
+        gap: Size::all(Val::Px(8.0)),
+        ..default()
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    match client.api() {
+        Ok(api) => {
+            client.spawn(async move {
+                let market = load_market(api).await;
+                let _ = sender.send(market.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(MarketLoad(Mutex::new(receiver)));
+    commands.remove_resource::<MarketData>();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(15.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnMarketScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Market prices",
+                text_style.clone(),
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                },
+                SpeciesRow,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: row_style,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (index, (label, _, _)) in RANGES.iter().enumerate() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: choice_style.clone(),
+                                    ..default()
+                                },
+                                MarketChoice::Range(index),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(*label, text_style.clone()));
+                            });
+                    }
+                });
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ChartArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the market...",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    BackToMenu,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+// Keep the market once it is read, and add a button for each species
+fn receive_market(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<MarketLoad>>,
+    mut view: ResMut<MarketView>,
+    rows: Query<Entity, With<SpeciesRow>>,
+    area: Query<Entity, With<ChartArea>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<MarketLoad>();
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    let market = match result {
+        Ok(market) => market,
+        Err(e) => {
+            for area in &area {
+                commands
+                    .entity(area)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Can't read the market: {e}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+            return;
+        }
+    };
+    // Keep the species chosen on the last visit
+    if !view
+        .species
+        .as_ref()
+        .map_or(false, |species| market.species.contains(species))
+    {
+        view.species = market.species.first().cloned();
+    }
+    for row in &rows {
+        commands.entity(row).with_children(|parent| {
+            for species in &market.species {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        MarketChoice::Species(species.clone()),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(species, text_style.clone()));
+                    });
+            }
+        });
+    }
+    commands.insert_resource(market);
+}
+
+fn market_choice(
+    interaction_query: Query<(&Interaction, &MarketChoice), Changed<Interaction>>,
+    mut view: ResMut<MarketView>,
+) {
+    for (interaction, choice) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match choice {
+            MarketChoice::Species(species) => view.species = Some(species.clone()),
+            MarketChoice::Range(range) => view.range = *range,
+        }
+    }
+}
+
+// Highlight the chosen species and range
+fn color_choices(
+    view: Res<MarketView>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &MarketChoice, &mut BackgroundColor)>,
+) {
+    for (interaction, choice, mut color) in &mut buttons {
+        if !view.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = match choice {
+            MarketChoice::Species(species) => view.species.as_ref() == Some(species),
+            MarketChoice::Range(range) => view.range == *range,
+        };
+        *color = palette.button(*interaction, selected).into();
+    }
+}
+
+// Draw the average price of each bar of the chosen range, and the floor price under it
+fn show_chart(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    market: Option<Res<MarketData>>,
+    view: Res<MarketView>,
+    area: Query<Entity, With<ChartArea>>,
+    palette: Res<Palette>,
+    names: Res<Names>,
+) {
+    let Some(market) = market else {
+        return;
+    };
+    if !market.is_changed() && !view.is_changed() {
+        return;
+    }
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let Some(species) = &view.species else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+
+    let (label, length, bar_blocks) = RANGES[view.range];
+    let since = market.now.saturating_sub(length);
+    let bars = (length / bar_blocks) as usize;
+    // Sales and total price of each bar, the oldest on the left
+    let mut totals = vec![(0u32, 0u128); bars];
+    let mut highest = 0;
+    for sale in &market.sales {
+        if &sale.species != species || sale.block < since {
+            continue;
+        }
+        let age = (market.now.saturating_sub(sale.block) / bar_blocks) as usize;
+        let bar = &mut totals[bars - 1 - age.min(bars - 1)];
+        bar.0 += 1;
+        bar.1 = bar.1.saturating_add(sale.price);
+        highest = highest.max(sale.price);
+    }
+    let averages: Vec<_> = totals
+        .iter()
+        .map(|(count, total)| (*count > 0).then(|| total / u128::from(*count)))
+        .collect();
+    let top = averages.iter().flatten().copied().max().unwrap_or(0);
+    let (sales, total) = totals.iter().fold((0, 0u128), |(count, sum), bar| {
+        (count + bar.0, sum.saturating_add(bar.1))
+    });
+    let mut listed: Vec<_> = market
+        .listings
+        .iter()
+        .filter(|listing| &listing.species == species)
+        .collect();
+    listed.sort_by_key(|listing| listing.price);
+    let floor = listed.first();
+    let (bar_color, _, _) = palette.stat_bars();
+
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::height(Val::Px(CHART_HEIGHT)),
+                        align_items: AlignItems::FlexEnd,
+                        gap: Size::all(Val::Px(3.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let width = (480.0 / bars as f32 - 3.0).max(4.0);
+                    for average in &averages {
+                        let (height, color) = match average {
+                            Some(average) => (
+                                4.0 + (CHART_HEIGHT - 4.0) * (*average as f32 / top as f32),
+                                bar_color,
+                            ),
+                            None => (2.0, Color::GRAY),
+                        };
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(width), Val::Px(height)),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        });
+                    }
+                });
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Average {species} price over the last {}",
+                    label.to_lowercase()
+                ),
+                text_style.clone(),
+            ));
+            let summary = if sales == 0 {
+                format!("No {species} sold in this range")
+            } else {
+                format!(
+                    "{sales} sold, average {}, highest {}",
+                    format_balance(total / u128::from(sales)),
+                    format_balance(highest)
+                )
+            };
+            parent.spawn(TextBundle::from_section(summary, text_style.clone()));
+            let floor = match floor {
+                Some(listing) => format!(
+                    "Floor price {} (pet #{})",
+                    format_balance(listing.price),
+                    listing.pet_id
+                ),
+                None => format!("No {species} listed right now"),
+            };
+            parent.spawn(TextBundle::from_section(floor, text_style.clone()));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for listing in listed.iter().take(LISTINGS_SHOWN) {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..default()
+                                },
+                                ProfileLink(listing.pet_id),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        format!(
+                                            "#{} {} by {}",
+                                            listing.pet_id,
+                                            format_balance(listing.price),
+                                            names.display(&listing.seller)
+                                        ),
+                                        text_style.clone(),
+                                    ),
+                                    AccountNames(vec![listing.seller.clone()]),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn market_action(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackToMenu>)>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            game_state.set(GameState::Menu);
+        }
+    }
+}
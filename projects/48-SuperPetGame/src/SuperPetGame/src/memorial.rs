@@ -0,0 +1,493 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use pet_gene::Dna;
+use sp_core::hashing::blake2_128;
+use subxt::dynamic::Value;
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::{AccountId32, H256};
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{pet_storage_prefix, ChainClient, ChainEvent, PetId, PetInfo},
+    clock::ChainClock,
+    despawn_screen,
+    inventory::ITEMS,
+    pet_watch::PlayerPet,
+    settings::Palette,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Storage keys read per request when listing memorials and the adoption pool
+const PAGE_SIZE: u32 = 100;
+
+// This plugin shows the pets the player let go, kept by the pet pallet in `Memorials` with
+// their name, level and the player's badges at the time. Instead of reviving an abandoned
+// pet, or when their own pet is neglected, the player can let it go from here too. It's
+// permanent, so the button asks to be clicked twice.
+pub struct MemorialPlugin;
+
+impl Plugin for MemorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            memorial_setup.in_schedule(OnEnter(GameState::Memorial)),
+            despawn_screen::<OnMemorialScreen>.in_schedule(OnExit(GameState::Memorial)),
+        ))
+        .add_systems(
+            (
+                memorial_events,
+                receive_memorials.after(memorial_events),
+                show_memorials.after(receive_memorials),
+                memorial_action,
+                color_choices.after(memorial_action),
+            )
+                .in_set(OnUpdate(GameState::Memorial)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the memorial screen
+#[derive(Component)]
+struct OnMemorialScreen;
+
+#[derive(Component)]
+struct MemorialArea;
+
+#[derive(Component)]
+enum MemorialAction {
+    LetGo(PetId),
+    Back,
+}
+
+// Mirror of `Memorial` of the pet pallet, items are their index in `ITEMS`
+#[derive(Decode)]
+struct Memorial {
+    pet: PetInfo,
+    dna: Option<Dna>,
+    level: u32,
+    _experience: u64,
+    achievements: Vec<(u8, u32)>,
+    since: u32,
+}
+
+// Mirror of `AbandonedPet` of the pet pallet
+#[derive(Decode)]
+struct AbandonedPet {
+    owner: AccountId32,
+    pet: PetInfo,
+    _since: u32,
+}
+
+// A pet of the player that can be let go
+struct Departing {
+    pet_id: PetId,
+    name: String,
+    abandoned: bool,
+}
+
+#[derive(Resource)]
+struct Memorials {
+    // Latest first
+    memorials: Vec<(PetId, Memorial)>,
+    departing: Vec<Departing>,
+}
+
+// Result of the background memorials read
+#[derive(Resource)]
+struct MemorialLoad(Mutex<mpsc::Receiver<Result<Memorials, String>>>);
+
+// The pet whose "Let go" button was clicked once, the next click sends the call
+#[derive(Resource, Default)]
+struct Armed(Option<PetId>);
+
+fn pet_name(pet: &PetInfo) -> String {
+    String::from_utf8_lossy(&pet.name.0).into_owned()
+}
+
+// Values of the map entries whose raw keys start with `prefix`, with the pet id ending them
+async fn pet_entries<V: Decode>(
+    api: &OnlineClient<PolkadotConfig>,
+    prefix: &[u8],
+    at: H256,
+) -> Result<Vec<(PetId, V)>, subxt::Error> {
+    let storage = api.storage().at(at);
+    let mut entries = Vec::new();
+    let mut start: Option<Vec<u8>> = None;
+    loop {
+        let keys = api
+            .rpc()
+            .storage_keys_paged(prefix, PAGE_SIZE, start.as_deref(), Some(at))
+            .await?;
+        for key in &keys {
+            // Pet ids are hashed with `Blake2_128Concat`, so they end the key in the clear
+            let id = &key.0[key.0.len().saturating_sub(4)..];
+            let (Ok(pet_id), Some(value)) = (
+                PetId::decode(&mut &id[..]),
+                storage.fetch_raw(&key.0).await?,
+            ) else {
+                continue;
+            };
+            entries.push((pet_id, V::decode(&mut &value[..])?));
+        }
+        if keys.len() < PAGE_SIZE as usize {
+            return Ok(entries);
+        }
+        start = keys.last().map(|key| key.0.clone());
+    }
+}
+
+// Read the memorials of the account, and its pets that can be let go
+async fn load_memorials(
+    api: OnlineClient<PolkadotConfig>,
+    account: AccountId32,
+    own_pet: Option<(PetId, String, u32)>,
+) -> Result<Memorials, subxt::Error> {
+    let block = api.blocks().at_latest().await?;
+    let owner = account.encode();
+    let prefix = [
+        &pet_storage_prefix("Memorials")[..],
+        &blake2_128(&owner),
+        &owner,
+    ]
+    .concat();
+    let mut memorials: Vec<(PetId, Memorial)> = pet_entries(&api, &prefix, block.hash()).await?;
+    memorials.sort_by_key(|(_, memorial)| std::cmp::Reverse(memorial.since));
+
+    let pool: Vec<(PetId, AbandonedPet)> =
+        pet_entries(&api, &pet_storage_prefix("AdoptionPool"), block.hash()).await?;
+    let mut departing: Vec<Departing> = pool
+        .into_iter()
+        .filter(|(_, abandoned)| abandoned.owner == account)
+        .map(|(pet_id, abandoned)| Departing {
+            pet_id,
+            name: pet_name(&abandoned.pet),
+            abandoned: true,
+        })
+        .collect();
+    // The pallet lets a pet go once it could be abandoned
+    if let Some((pet_id, name, last_feed)) = own_pet {
+        let abandon_after = api
+            .constants()
+            .at(&subxt::dynamic::constant("PetModule", "AbandonAfter"))?;
+        let abandon_after = u32::decode(&mut abandon_after.encoded())?;
+        if block.number().saturating_sub(last_feed) >= abandon_after {
+            departing.push(Departing {
+                pet_id,
+                name,
+                abandoned: false,
+            });
+        }
+    }
+    Ok(Memorials {
+        memorials,
+        departing,
+    })
+}
+
+fn start_load(commands: &mut Commands, client: &mut ChainClient, player_pet: &PlayerPet) {
+    let (sender, receiver) = mpsc::channel();
+    let own_pet = player_pet
+        .pet
+        .as_ref()
+        .zip(player_pet.last_feed_block)
+        .map(|(pet, last_feed)| (pet.id, pet.name.clone(), last_feed));
+    match client.api() {
+        Ok(api) => {
+            let account = client.account_id();
+            client.spawn(async move {
+                let memorials = load_memorials(api, account, own_pet).await;
+                let _ = sender.send(memorials.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(MemorialLoad(Mutex::new(receiver)));
+}
+
+fn memorial_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    start_load(&mut commands, &mut client, &player_pet);
+    commands.remove_resource::<Memorials>();
+    commands.insert_resource(Armed::default());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(15.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnMemorialScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "In memory of past companions",
+                text_style.clone(),
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    MemorialArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the memorials...",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    MemorialAction::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+// Read the memorials again once a pet of the player is let go
+fn memorial_events(
+    mut commands: Commands,
+    mut events: EventReader<ChainEvent>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let account = client.account_id();
+    let ours = events.iter().any(|ChainEvent(event)| {
+        event.pallet_name() == "PetModule"
+            && event.variant_name() == "PetMemorialized"
+            && AccountId32::decode(&mut event.field_bytes()).map_or(false, |owner| owner == account)
+    });
+    if ours {
+        start_load(&mut commands, &mut client, &player_pet);
+    }
+}
+
+fn receive_memorials(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<MemorialLoad>>,
+    area: Query<Entity, With<MemorialArea>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<MemorialLoad>();
+    match result {
+        Ok(memorials) => commands.insert_resource(memorials),
+        Err(e) => {
+            let text_style = TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 24.0,
+                color: TEXT_COLOR,
+            };
+            for area in &area {
+                commands
+                    .entity(area)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Can't read the memorials: {e}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+        }
+    }
+}
+
+fn badge_list(achievements: &[(u8, u32)]) -> String {
+    let badges: Vec<String> = achievements
+        .iter()
+        .filter_map(|(item, count)| {
+            let (name, _) = ITEMS.get(*item as usize)?;
+            Some(if *count > 1 {
+                format!("{name} x{count}")
+            } else {
+                name.to_string()
+            })
+        })
+        .collect();
+    if badges.is_empty() {
+        "no badges".to_string()
+    } else {
+        badges.join(", ")
+    }
+}
+
+// One line per pet let go, in the tint of its DNA, then the pets that can be let go
+fn show_memorials(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    memorials: Option<Res<Memorials>>,
+    armed: Res<Armed>,
+    clock: Res<ChainClock>,
+    area: Query<Entity, With<MemorialArea>>,
+) {
+    let Some(memorials) = memorials else {
+        return;
+    };
+    if !memorials.is_changed() && !armed.is_changed() {
+        return;
+    }
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            if memorials.memorials.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No pet was let go yet",
+                    text_style.clone(),
+                ));
+            }
+            for (pet_id, memorial) in &memorials.memorials {
+                let tint = memorial.dna.as_ref().map_or(Color::GRAY, |dna| {
+                    let [r, g, b] = pet_gene::traits(dna).tint;
+                    Color::rgb_u8(r, g, b)
+                });
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::all(Val::Px(24.0)),
+                                ..default()
+                            },
+                            background_color: tint.into(),
+                            ..default()
+                        });
+                        parent.spawn(TextBundle::from_section(
+                            format!(
+                                "{} (pet #{pet_id}), {:?} of level {}, let go {}: {}",
+                                pet_name(&memorial.pet),
+                                memorial.pet.species,
+                                memorial.level,
+                                clock.block(memorial.since),
+                                badge_list(&memorial.achievements)
+                            ),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+            for departing in &memorials.departing {
+                let label = match (armed.0 == Some(departing.pet_id), departing.abandoned) {
+                    (true, _) => format!("Let {} go for good? Click again", departing.name),
+                    (false, true) => format!("Let {} go instead of reviving it", departing.name),
+                    (false, false) => format!("{} is neglected, let it go", departing.name),
+                };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        MemorialAction::LetGo(departing.pet_id),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn memorial_action(
+    interaction_query: Query<(&Interaction, &MemorialAction), Changed<Interaction>>,
+    mut armed: ResMut<Armed>,
+    mut queue: ResMut<TxQueue>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            MemorialAction::LetGo(pet_id) if armed.0 == Some(*pet_id) => {
+                armed.0 = None;
+                queue.push(
+                    format!("Let pet #{pet_id} go"),
+                    subxt::dynamic::tx(
+                        "PetModule",
+                        "memorialize",
+                        vec![Value::u128((*pet_id).into())],
+                    ),
+                );
+            }
+            MemorialAction::LetGo(pet_id) => armed.0 = Some(*pet_id),
+            MemorialAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// Highlight the pet about to be let go
+fn color_choices(
+    armed: Res<Armed>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &MemorialAction, &mut BackgroundColor)>,
+) {
+    for (interaction, action, mut color) in &mut buttons {
+        if !armed.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = matches!(action, MemorialAction::LetGo(pet_id) if armed.0 == Some(*pet_id));
+        *color = palette.button(*interaction, selected).into();
+    }
+}
@@ -1,10 +1,31 @@
-use bevy::{app::AppExit, prelude::*};
-
-use super::{despawn_screen, GameState, PetOwned, TEXT_COLOR};
+use bevy::{app::AppExit, prelude::*, ui::RelativeCursorPosition};
+
+use super::{
+    address_book::{AddressBook, AddressField, ContactHint},
+    assets::GameAssets,
+    chains::{ChainButton, Chains},
+    client::{pet_events, polkadot, AnyCall, ChainClient, MintBlockReason, PetId, PetSpecies},
+    despawn_screen,
+    fee::FeeText,
+    game::Spectating,
+    hide_screen,
+    naming::{self, NameRules},
+    profile::{ProfileButton, Profiles},
+    qr, screen_missing,
+    settings::{LargeTargets, Palette, MAX_UI_SCALE, MIN_UI_SCALE},
+    show_screen,
+    snapshot::{PetSnapshot, SNAPSHOT_FILE},
+    sounds::Muted,
+    telemetry::ErrorReports,
+    toast::Toast,
+    tooltip::CallHelp,
+    GameState, PetOwned, TEXT_COLOR,
+};
 
 use futures::StreamExt;
 use sp_keyring::sr25519::sr25519::Pair;
 use sp_keyring::AccountKeyring;
+use subxt::dynamic::Value;
 use subxt::utils::AccountId32;
 use subxt::{tx::PairSigner, tx::TxStatus, OnlineClient, PolkadotConfig};
 use thiserror::Error as ThisError;
@@ -13,6 +34,8 @@ use thiserror::Error as ThisError;
 // - a main menu with "New Game", "Settings", "Quit"
 // - a settings menu with two submenus and a back button
 // - two settings screen with a setting that can be set and a back button
+// Screens that don't depend on the chain are spawned on their first visit, then hidden and
+// shown again rather than rebuilt, so switching between them doesn't hitch
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -25,20 +48,67 @@ impl Plugin for MenuPlugin {
             .add_system(menu_setup.in_schedule(OnEnter(GameState::Menu)))
             // Systems to handle the main menu screen
             .add_systems((
-                main_menu_setup.in_schedule(OnEnter(MenuState::Main)),
-                despawn_screen::<OnMainMenuScreen>.in_schedule(OnExit(MenuState::Main)),
+                main_menu_setup
+                    .run_if(screen_missing::<OnMainMenuScreen>)
+                    .in_schedule(OnEnter(MenuState::Main)),
+                show_screen::<OnMainMenuScreen>.in_schedule(OnEnter(MenuState::Main)),
+                hide_screen::<OnMainMenuScreen>.in_schedule(OnExit(MenuState::Main)),
             ))
             // Systems to handle the new game menu screen
             .add_systems((
                 new_game_setup.in_schedule(OnEnter(MenuState::NewGame)),
                 despawn_screen::<OnNewGameScreen>.in_schedule(OnExit(MenuState::NewGame)),
+                (
+                    choose_species,
+                    focus_field,
+                    pet_name_input.after(focus_field),
+                    referrer_input.after(focus_field),
+                    naming::receive_taken_names,
+                    show_pet_name
+                        .after(choose_species)
+                        .after(pet_name_input)
+                        .after(naming::receive_taken_names),
+                )
+                    .in_set(OnUpdate(MenuState::NewGame)),
+            ))
+            // Systems to handle the settings screen
+            .add_systems((
+                settings_setup
+                    .run_if(screen_missing::<OnSettingsMenuScreen>)
+                    .in_schedule(OnEnter(MenuState::Settings)),
+                show_screen::<OnSettingsMenuScreen>.in_schedule(OnEnter(MenuState::Settings)),
+                hide_screen::<OnSettingsMenuScreen>.in_schedule(OnExit(MenuState::Settings)),
+                setting_button::<Palette>.in_set(OnUpdate(MenuState::Settings)),
+                setting_button::<LargeTargets>.in_set(OnUpdate(MenuState::Settings)),
+                setting_button::<ErrorReports>.in_set(OnUpdate(MenuState::Settings)),
+                setting_button::<Muted>.in_set(OnUpdate(MenuState::Settings)),
+                ui_scale_slider.in_set(OnUpdate(MenuState::Settings)),
+            ))
+            .add_systems((
+                transaction_setup
+                    .run_if(screen_missing::<OnTransactionScreen>)
+                    .in_schedule(OnEnter(MenuState::Transaction)),
+                show_screen::<OnTransactionScreen>.in_schedule(OnEnter(MenuState::Transaction)),
+                hide_screen::<OnTransactionScreen>.in_schedule(OnExit(MenuState::Transaction)),
+            ))
+            // Systems to handle the pet file viewer screen
+            .init_resource::<ViewedPet>()
+            .add_systems((
+                pet_viewer_setup.in_schedule(OnEnter(MenuState::PetViewer)),
+                despawn_screen::<OnPetViewerScreen>.in_schedule(OnExit(MenuState::PetViewer)),
             ))
+            // Systems to handle the spectator screen
             .add_systems((
-                transaction_setup.in_schedule(OnEnter(MenuState::Transaction)),
-                despawn_screen::<OnTransactionScreen>.in_schedule(OnExit(MenuState::Transaction)),
+                spectate_setup
+                    .run_if(screen_missing::<OnSpectateScreen>)
+                    .in_schedule(OnEnter(MenuState::Spectate)),
+                show_screen::<OnSpectateScreen>.in_schedule(OnEnter(MenuState::Spectate)),
+                hide_screen::<OnSpectateScreen>.in_schedule(OnExit(MenuState::Spectate)),
             ))
             // Common systems to all screens that handles buttons behaviour
-            .add_systems((menu_action, button_system).in_set(OnUpdate(GameState::Menu)));
+            .add_systems(
+                (menu_action, button_system, pet_file_drop).in_set(OnUpdate(GameState::Menu)),
+            );
     }
 }
 
@@ -52,6 +122,8 @@ enum MenuState {
     Settings,
     Update,
     Transaction,
+    PetViewer,
+    Spectate,
     #[default]
     Disabled,
 }
@@ -76,10 +148,44 @@ struct OnUpdateScreen;
 #[derive(Component)]
 struct OnTransactionScreen;
 
+// Tag component used to tag entities added on the pet file viewer screen
+#[derive(Component)]
+struct OnPetViewerScreen;
+
+// Tag component used to tag entities added on the spectator screen
+#[derive(Component)]
+struct OnSpectateScreen;
+
+// Track of the UI scale slider on the settings screen, clicked to set the scale
+#[derive(Component)]
+struct UiScaleSlider;
+
+// Filled part of the slider track, up to the current scale
+#[derive(Component)]
+struct UiScaleFill;
+
+#[derive(Component)]
+struct UiScaleText;
+
+#[derive(Component)]
+struct OnAddressInputText;
+
+#[derive(Component)]
+struct OnSpectateStatusText;
+
+// Pet shown on the viewer screen, or the message explaining why there is none
+#[derive(Resource)]
+struct ViewedPet(Result<PetSnapshot, String>);
+
+impl Default for ViewedPet {
+    fn default() -> Self {
+        Self(Err(format!(
+            "Drop an exported pet file here, or export yours to {SNAPSHOT_FILE}"
+        )))
+    }
+}
+
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.25, 0.65, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
 // Tag component used to mark which setting is currently selected
 #[derive(Component)]
@@ -94,6 +200,40 @@ struct OnPetIdInputText;
 #[derive(Component)]
 struct OnPetSpeciesInputText;
 
+// Address of the player who referred this one, optional
+#[derive(Component)]
+struct OnReferrerInputText;
+
+// Under the pet name, how long it is and what's wrong with it
+#[derive(Component)]
+struct OnPetNameCheckText;
+
+// Sprite of the chosen species, with the typed name over it
+#[derive(Component)]
+struct OnPetPreview;
+
+#[derive(Component)]
+struct OnPetPreviewName;
+
+// Label of the Mint Pet button, dimmed while the name can't be minted
+#[derive(Component)]
+struct OnMintLabel;
+
+// Field of the new game screen typing goes to, Tab or a click on the other one moves it
+#[derive(Component, Resource, Clone, Copy, PartialEq, Eq)]
+enum InputField {
+    PetName,
+    Referrer,
+}
+
+// Species button of the new game screen, only spawned for species that aren't sold out
+#[derive(Component)]
+struct SpeciesChoice(String);
+
+// Species the new pet will be minted as
+#[derive(Resource)]
+struct ChosenSpecies(String);
+
 #[derive(Component)]
 struct SelectedOption;
 
@@ -102,10 +242,22 @@ struct SelectedOption;
 enum MenuButtonAction {
     NewGame,      //Create a new Game
     ContinueGame, //Continue the Game
-    //Settings,//Game settings
+    Settings,     //Game settings
     MintPet(String, String),
     //Update, //Pet state update
     Transaction, //Buy or Sell pet
+    PetViewer,   //Export or view a pet file
+    ExportPet,
+    CareStats,    //Chart the pet's care over the last week
+    CareSchedule, //Schedule care on chain for while the player is away
+    Leaderboard,  //Rank the pets that won the most battles this week
+    Memorials,    //Browse the pets the player let go
+    Spectate,     //Watch the pet of any account
+    MarketPrices, //Chart the prices pets sold for
+    AddressBook,  //Edit the labelled addresses of the address book
+    ColdSign,     //Export calls of accounts whose keys aren't in the game, and send them signed
+    Tournaments,  //Follow the brackets of the tournaments and enter them
+    WatchPet,
     BackToMainMenu,
     Quit,
 }
@@ -116,30 +268,45 @@ fn button_system(
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
         (Changed<Interaction>, With<Button>),
     >,
+    palette: Res<Palette>,
 ) {
     for (interaction, mut color, selected) in &mut interaction_query {
-        *color = match (*interaction, selected) {
-            (Interaction::Clicked, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON.into(),
-            (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON.into(),
-            (Interaction::Hovered, None) => HOVERED_BUTTON.into(),
-            (Interaction::None, None) => NORMAL_BUTTON.into(),
-        }
+        *color = palette.button(*interaction, selected.is_some()).into();
     }
 }
 
 // This system updates the settings when a new value for a setting is selected, and marks
 // the button as the one currently selected
+fn setting_button<T: Resource + Component + PartialEq + Copy>(
+    interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
+    mut selected_query: Query<(Entity, &mut BackgroundColor), (With<SelectedOption>, With<T>)>,
+    mut commands: Commands,
+    mut setting: ResMut<T>,
+    palette: Res<Palette>,
+) {
+    for (interaction, button_setting, entity) in &interaction_query {
+        if *interaction == Interaction::Clicked && *setting != *button_setting {
+            for (previous_button, mut previous_color) in &mut selected_query {
+                *previous_color = palette.button(Interaction::None, false).into();
+                commands.entity(previous_button).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            *setting = *button_setting;
+        }
+    }
+}
 
 fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::Main);
 }
 
-fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
-    // Common style for all buttons on the screen
+fn main_menu_setup(mut commands: Commands, assets: Res<GameAssets>) {
+    let font = assets.font.clone();
+    // Common style for all buttons on the screen. Heights are relative to the window, so the
+    // menu still fits when the UI is scaled up.
     let button_style = Style {
-        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
-        margin: UiRect::all(Val::Px(20.0)),
+        size: Size::new(Val::Px(250.0), Val::Percent(10.0)),
+        margin: UiRect::vertical(Val::Percent(1.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -180,8 +347,11 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             parent
                 .spawn(NodeBundle {
                     style: Style {
+                        size: Size::height(Val::Percent(100.0)),
                         flex_direction: FlexDirection::Column,
                         align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::horizontal(Val::Px(10.0)),
                         ..default()
                     },
                     background_color: Color::DARK_GREEN.into(),
@@ -218,7 +388,7 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             MenuButtonAction::NewGame,
                         ))
                         .with_children(|parent| {
-                            let icon = asset_server.load("textures/Game Icons/right.png");
+                            let icon = assets.icons.right.clone();
                             parent.spawn(ImageBundle {
                                 style: button_icon_style.clone(),
                                 image: UiImage::new(icon),
@@ -239,7 +409,7 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             MenuButtonAction::ContinueGame,
                         ))
                         .with_children(|parent| {
-                            let icon = asset_server.load("textures/Game Icons/right.png");
+                            let icon = assets.icons.right.clone();
                             parent.spawn(ImageBundle {
                                 style: button_icon_style.clone(),
                                 image: UiImage::new(icon),
@@ -257,10 +427,10 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            //MenuButtonAction::Settings,
+                            MenuButtonAction::Settings,
                         ))
                         .with_children(|parent| {
-                            let icon = asset_server.load("textures/Game Icons/wrench.png");
+                            let icon = assets.icons.wrench.clone();
                             parent.spawn(ImageBundle {
                                 style: button_icon_style.clone(),
                                 image: UiImage::new(icon),
@@ -278,17 +448,59 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            //MenuButtonAction::Update,
+                            MenuButtonAction::PetViewer,
+                        ))
+                        .with_children(|parent| {
+                            let icon = assets.icons.wrench.clone();
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: UiImage::new(icon),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Pet File",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Spectate,
                         ))
                         .with_children(|parent| {
-                            let icon = asset_server.load("textures/Game Icons/wrench.png");
+                            let icon = assets.icons.right.clone();
                             parent.spawn(ImageBundle {
                                 style: button_icon_style.clone(),
                                 image: UiImage::new(icon),
                                 ..default()
                             });
                             parent.spawn(TextBundle::from_section(
-                                "Update",
+                                "Watch Pet",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Tournaments,
+                        ))
+                        .with_children(|parent| {
+                            let icon = assets.icons.right.clone();
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: UiImage::new(icon),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Tournaments",
                                 button_text_style.clone(),
                             ));
                         });
@@ -302,7 +514,7 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             MenuButtonAction::Transaction,
                         ))
                         .with_children(|parent| {
-                            let icon = asset_server.load("textures/Game Icons/wrench.png");
+                            let icon = assets.icons.wrench.clone();
                             parent.spawn(ImageBundle {
                                 style: button_icon_style,
                                 image: UiImage::new(icon),
@@ -315,7 +527,34 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 //New game menu setup, enter a webpage to mint a pet if the user don't have one.
-fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn new_game_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    mut client: ResMut<ChainClient>,
+) {
+    // Species the player can't mint are greyed out with the reason
+    let supply = client.species_supply().unwrap_or_else(|e| {
+        error!("error reading the species supply: {e:?}");
+        Vec::new()
+    });
+    // The chain's own checks, runtimes without them only tell which species are sold out
+    let blocked: Vec<Option<MintBlockReason>> = supply
+        .iter()
+        .map(|(species, population, cap)| {
+            let sold_out = cap.map_or(false, |cap| *population >= cap);
+            match client.can_mint(species) {
+                Ok(result) => result.err(),
+                Err(_) => sold_out.then_some(MintBlockReason::SpeciesSoldOut),
+            }
+        })
+        .collect();
+    commands.insert_resource(ChosenSpecies("Turtle".to_string()));
+    let name_rules = NameRules::load(&mut commands, &mut client);
+    let name_check = name_rules.describe(naming::DEFAULT_NAME);
+    commands.insert_resource(name_rules);
+    commands.insert_resource(InputField::PetName);
+
     let button_style = Style {
         size: Size::new(Val::Px(150.0), Val::Px(50.0)),
         //margin: UiRect::all(Val::Px(20.0)),
@@ -438,6 +677,58 @@ fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ));
                         });
                 });
+            parent
+                .spawn(NodeBundle {
+                    style: node_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for ((species, population, cap), blocked) in supply.iter().zip(&blocked) {
+                        let name = format!("{species:?}");
+                        let count = match cap {
+                            Some(cap) => format!("{population}/{cap}"),
+                            None => population.to_string(),
+                        };
+                        let choice_style = Style {
+                            padding: UiRect::all(Val::Px(8.0)),
+                            ..default()
+                        };
+                        if let Some(reason) = blocked {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: choice_style,
+                                    background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        format!("{name}: {}", reason.describe()),
+                                        TextStyle {
+                                            color: Color::GRAY,
+                                            ..text_style.clone()
+                                        },
+                                    ));
+                                });
+                        } else {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: choice_style,
+                                        background_color: NORMAL_BUTTON.into(),
+                                        ..default()
+                                    },
+                                    SpeciesChoice(name.clone()),
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        format!("{name} {count}"),
+                                        text_style.clone(),
+                                    ));
+                                });
+                        }
+                    }
+                });
             parent
                 .spawn(NodeBundle {
                     style: node_style.clone(),
@@ -480,16 +771,20 @@ fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     });
 
                     parent
-                        .spawn(NodeBundle {
-                            style: text_node_bundle_style.clone(),
-                            background_color: Color::DARK_GRAY.into(),
-                            ..default()
-                        })
+                        .spawn((
+                            NodeBundle {
+                                style: text_node_bundle_style.clone(),
+                                background_color: Color::GRAY.into(),
+                                ..default()
+                            },
+                            Interaction::default(),
+                            InputField::PetName,
+                        ))
                         .with_children(|parent| {
                             parent.spawn((
                                 TextBundle {
                                     text: Text::from_section(
-                                        "Annatle".to_string(),
+                                        naming::DEFAULT_NAME.to_string(),
                                         text_style.clone(),
                                     ),
                                     ..default()
@@ -498,6 +793,80 @@ fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ));
                         });
                 });
+            parent.spawn((
+                TextBundle::from_section(
+                    name_check,
+                    TextStyle {
+                        font_size: 16.0,
+                        ..text_style.clone()
+                    },
+                ),
+                OnPetNameCheckText,
+            ));
+            parent
+                .spawn((
+                    ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(120.0), Val::Px(120.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        image: assets.pet_image("Turtle").into(),
+                        ..default()
+                    },
+                    OnPetPreview,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            naming::DEFAULT_NAME,
+                            TextStyle {
+                                font_size: 18.0,
+                                ..text_style.clone()
+                            },
+                        ),
+                        OnPetPreviewName,
+                    ));
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: node_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Referred by ".to_string(), text_style.clone()),
+                        ..default()
+                    });
+
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(600.0), Val::Px(50.0)),
+                                    ..text_node_bundle_style.clone()
+                                },
+                                background_color: Color::DARK_GRAY.into(),
+                                ..default()
+                            },
+                            Interaction::default(),
+                            InputField::Referrer,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    String::new(),
+                                    TextStyle {
+                                        font_size: 16.0,
+                                        ..text_style.clone()
+                                    },
+                                ),
+                                OnReferrerInputText,
+                            ));
+                        });
+                });
 
             parent
                 .spawn((
@@ -507,13 +876,27 @@ fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         ..default()
                     },
                     MenuButtonAction::MintPet("test-name".to_string(), "test-species".to_string()),
+                    CallHelp::pet("mint"),
                 ))
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Mint Pet".to_string(), text_style.clone()),
-                        ..default()
-                    });
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section("Mint Pet".to_string(), text_style.clone()),
+                            ..default()
+                        },
+                        OnMintLabel,
+                    ));
                 });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                FeeText(AnyCall::new(polkadot::tx().pet_module().mint(
+                    polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec(
+                        b"Annatle".to_vec(),
+                    ),
+                    PetSpecies::Turtle,
+                    1,
+                ))),
+            ));
             parent
                 .spawn((
                     ButtonBundle {
@@ -532,10 +915,24 @@ fn new_game_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
-fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+// Position of `scale` along the UI scale slider, in percent
+fn ui_scale_percent(scale: f64) -> f32 {
+    ((scale - MIN_UI_SCALE) / (MAX_UI_SCALE - MIN_UI_SCALE) * 100.0) as f32
+}
+
+fn settings_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ui_scale: Res<UiScale>,
+    palette: Res<Palette>,
+    large_targets: Res<LargeTargets>,
+    error_reports: Res<ErrorReports>,
+    muted: Res<Muted>,
+    profiles: Res<Profiles>,
+    chains: Res<Chains>,
+) {
     let button_style = Style {
-        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
-        //margin: UiRect::all(Val::Px(20.0)),
+        size: Size::new(Val::Px(170.0), Val::Px(50.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -545,25 +942,9 @@ fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         font_size: 24.0,
         color: TEXT_COLOR,
     };
-
-    let node_style = Style {
-        flex_direction: FlexDirection::Row,
-        align_items: AlignItems::Center,
-        align_content: AlignContent::Center,
-        gap: Size {
-            width: Val::Px(15.0),
-            height: Val::Px(15.0),
-        },
-        ..default()
-    };
-
-    let text_node_bundle_style = Style {
-        size: Size {
-            width: Val::Px(200.),
-            height: Val::Px(50.),
-        },
+    let row_style = Style {
         align_items: AlignItems::Center,
-        align_content: AlignContent::Center,
+        gap: Size::all(Val::Px(10.0)),
         ..default()
     };
 
@@ -575,102 +956,438 @@ fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
-                    gap: Size {
-                        width: Val::Px(15.0),
-                        height: Val::Px(15.0),
-                    },
+                    gap: Size::all(Val::Px(20.0)),
                     ..default()
                 },
                 background_color: Color::DARK_GREEN.into(),
                 ..default()
             },
-            OnTransactionScreen,
+            OnSettingsMenuScreen,
         ))
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
-                    style: node_style.clone(),
-                    background_color: Color::NONE.into(),
+                    style: row_style.clone(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Sender Id   ".to_string(), text_style.clone()),
-                        ..default()
-                    });
-
+                    parent.spawn(TextBundle::from_section("UI scale", text_style.clone()));
                     parent
-                        .spawn(
+                        .spawn((
                             NodeBundle {
-                                style: text_node_bundle_style.clone(),
+                                style: Style {
+                                    size: Size::new(Val::Px(300.0), Val::Px(24.0)),
+                                    ..default()
+                                },
                                 background_color: Color::DARK_GRAY.into(),
-
                                 ..default()
                             },
-                            //OnIdInputText,
-                        )
+                            UiScaleSlider,
+                            RelativeCursorPosition::default(),
+                        ))
                         .with_children(|parent| {
                             parent.spawn((
-                                TextBundle {
-                                    text: Text::from_section(
-                                        "Alice".to_string(),
-                                        text_style.clone(),
-                                    ),
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::new(
+                                            Val::Percent(ui_scale_percent(ui_scale.scale)),
+                                            Val::Percent(100.0),
+                                        ),
+                                        ..default()
+                                    },
+                                    background_color: palette
+                                        .button(Interaction::None, true)
+                                        .into(),
                                     ..default()
                                 },
-                                OnPlayerIdInputText,
+                                UiScaleFill,
                             ));
                         });
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("{:.0}%", ui_scale.scale * 100.0),
+                            text_style.clone(),
+                        ),
+                        UiScaleText,
+                    ));
                 });
 
             parent
                 .spawn(NodeBundle {
-                    style: node_style.clone(),
-                    background_color: Color::NONE.into(),
+                    style: row_style.clone(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Receiver Id ".to_string(), text_style.clone()),
-                        ..default()
-                    });
-
-                    parent
-                        .spawn(NodeBundle {
-                            style: text_node_bundle_style.clone(),
-                            background_color: Color::DARK_GRAY.into(),
-
-                            ..default()
-                        })
-                        .with_children(|parent| {
-                            parent.spawn((
-                                TextBundle {
-                                    text: Text::from_section("Bob".to_string(), text_style.clone()),
-                                    ..default()
-                                },
-                                OnPetSpeciesInputText,
-                            ));
+                    parent.spawn(TextBundle::from_section("Colors", text_style.clone()));
+                    for choice in Palette::ALL {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            choice,
+                        ));
+                        entity.with_children(|parent| {
+                            parent
+                                .spawn(TextBundle::from_section(choice.name(), text_style.clone()));
                         });
+                        if *palette == choice {
+                            entity.insert(SelectedOption);
+                        }
+                    }
                 });
+
             parent
                 .spawn(NodeBundle {
-                    style: node_style.clone(),
-                    background_color: Color::NONE.into(),
+                    style: row_style.clone(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Pet Id      ".to_string(), text_style.clone()),
-                        ..default()
-                    });
+                    parent.spawn(TextBundle::from_section(
+                        "Large touch targets",
+                        text_style.clone(),
+                    ));
+                    for (choice, label) in
+                        [(LargeTargets(false), "Off"), (LargeTargets(true), "On")]
+                    {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            choice,
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                        if *large_targets == choice {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                });
 
-                    parent
-                        .spawn(NodeBundle {
-                            style: text_node_bundle_style.clone(),
-                            background_color: Color::DARK_GRAY.into(),
-                            ..default()
-                        })
-                        .with_children(|parent| {
+            // Consent to crash and error reports, see the telemetry plugin
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Error reports",
+                        text_style.clone(),
+                    ));
+                    for (choice, label) in
+                        [(ErrorReports(false), "Off"), (ErrorReports(true), "On")]
+                    {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            choice,
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                        if *error_reports == choice {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Pet sounds", text_style.clone()));
+                    for (choice, label) in [(Muted(true), "Off"), (Muted(false), "On")] {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            choice,
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                        if *muted == choice {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                });
+
+            // Another profile starts the game again, see the profile plugin
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Profile", text_style.clone()));
+                    for name in &profiles.all {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            ProfileButton(name.clone()),
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(name, text_style.clone()));
+                        });
+                        if *name == profiles.current {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                });
+
+            // So does another chain, see the chains plugin
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Chain", text_style.clone()));
+                    for chain in &chains.all {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            ChainButton(chain.name.clone()),
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(&chain.name, text_style.clone()));
+                        });
+                        if chain.name == chains.current.name {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                });
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::AddressBook,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Address book", text_style.clone()));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::ColdSign,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Cold signing", text_style.clone()));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::BackToMainMenu,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style.clone()));
+                });
+        });
+}
+
+// Set the UI scale where the slider track is clicked
+fn ui_scale_slider(
+    mouse: Res<Input<MouseButton>>,
+    slider: Query<&RelativeCursorPosition, With<UiScaleSlider>>,
+    mut fill: Query<&mut Style, With<UiScaleFill>>,
+    mut value_text: Query<&mut Text, With<UiScaleText>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(position) = slider
+        .iter()
+        .find(|cursor| cursor.mouse_over())
+        .and_then(|cursor| cursor.normalized)
+    else {
+        return;
+    };
+    // Steps of 5%
+    let scale = MIN_UI_SCALE + position.x as f64 * (MAX_UI_SCALE - MIN_UI_SCALE);
+    ui_scale.scale = ((scale * 20.0).round() / 20.0).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+    for mut style in &mut fill {
+        style.size.width = Val::Percent(ui_scale_percent(ui_scale.scale));
+    }
+    for mut text in &mut value_text {
+        text.sections[0].value = format!("{:.0}%", ui_scale.scale * 100.0);
+    }
+}
+
+fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let button_style = Style {
+        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+        //margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    let node_style = Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        align_content: AlignContent::Center,
+        gap: Size {
+            width: Val::Px(15.0),
+            height: Val::Px(15.0),
+        },
+        ..default()
+    };
+
+    let text_node_bundle_style = Style {
+        size: Size {
+            width: Val::Px(200.),
+            height: Val::Px(50.),
+        },
+        align_items: AlignItems::Center,
+        align_content: AlignContent::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size {
+                        width: Val::Px(15.0),
+                        height: Val::Px(15.0),
+                    },
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnTransactionScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: node_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Sender Id   ".to_string(), text_style.clone()),
+                        ..default()
+                    });
+
+                    parent
+                        .spawn(
+                            NodeBundle {
+                                style: text_node_bundle_style.clone(),
+                                background_color: Color::DARK_GRAY.into(),
+
+                                ..default()
+                            },
+                            //OnIdInputText,
+                        )
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle {
+                                    text: Text::from_section(
+                                        "Alice".to_string(),
+                                        text_style.clone(),
+                                    ),
+                                    ..default()
+                                },
+                                OnPlayerIdInputText,
+                            ));
+                        });
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: node_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Receiver Id ".to_string(), text_style.clone()),
+                        ..default()
+                    });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: text_node_bundle_style.clone(),
+                            background_color: Color::DARK_GRAY.into(),
+
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle {
+                                    text: Text::from_section("Bob".to_string(), text_style.clone()),
+                                    ..default()
+                                },
+                                OnPetSpeciesInputText,
+                                AddressField,
+                            ));
+                        });
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                ContactHint,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: node_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Pet Id      ".to_string(), text_style.clone()),
+                        ..default()
+                    });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: text_node_bundle_style.clone(),
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
                             parent.spawn((
                                 TextBundle {
                                     text: Text::from_section("1".to_string(), text_style.clone()),
@@ -682,16 +1399,271 @@ fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 });
 
             parent
-                .spawn(ButtonBundle {
-                    style: button_style.clone(),
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    CallHelp::pet("transfer"),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Submit".to_string(), text_style.clone()),
+                        ..default()
+                    });
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                FeeText(AnyCall::new(
+                    polkadot::tx()
+                        .pet_module()
+                        .transfer(AccountKeyring::Bob.public().0.into()),
+                )),
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::MarketPrices,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Prices".to_string(), text_style.clone()),
+                        ..default()
+                    });
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::BackToMainMenu,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section("Back to Main".to_string(), text_style.clone()),
+                        ..default()
+                    });
+                });
+        });
+}
+
+// Pet file viewer, shows an exported pet without needing chain access
+fn pet_viewer_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    viewed: Res<ViewedPet>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    let lines = match &viewed.0 {
+        Ok(snapshot) => snapshot.describe(),
+        Err(message) => vec![message.clone()],
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size {
+                        width: Val::Px(15.0),
+                        height: Val::Px(15.0),
+                    },
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnPetViewerScreen,
+        ))
+        .with_children(|parent| {
+            for line in lines {
+                parent.spawn(TextBundle::from_section(line, text_style.clone()));
+            }
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::ExportPet,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Export My Pet",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::CareStats,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Care Stats", text_style.clone()));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::CareSchedule,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Care Schedule",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::Leaderboard,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Leaderboard", text_style.clone()));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::Memorials,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Memorials", text_style.clone()));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::BackToMainMenu,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+// Spectator screen, asks for the address whose pet should be watched
+fn spectate_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let button_style = Style {
+        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size {
+                        width: Val::Px(15.0),
+                        height: Val::Px(15.0),
+                    },
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnSpectateScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Type the address of the pet owner".to_string(),
+                text_style.clone(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size {
+                            width: Val::Px(700.),
+                            height: Val::Px(50.),
+                        },
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
                     background_color: Color::DARK_GRAY.into(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Submit".to_string(), text_style.clone()),
+                    parent.spawn((
+                        TextBundle::from_section(
+                            String::new(),
+                            TextStyle {
+                                font_size: 18.0,
+                                ..text_style.clone()
+                            },
+                        ),
+                        OnAddressInputText,
+                        AddressField,
+                    ));
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                ContactHint,
+            ));
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                OnSpectateStatusText,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: Color::DARK_GRAY.into(),
                         ..default()
-                    });
+                    },
+                    MenuButtonAction::WatchPet,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Watch", text_style.clone()));
                 });
             parent
                 .spawn((
@@ -703,14 +1675,30 @@ fn transaction_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     MenuButtonAction::BackToMainMenu,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section("Back to Main".to_string(), text_style.clone()),
-                        ..default()
-                    });
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
                 });
         });
 }
 
+// Open a pet file dropped on the window in the viewer
+fn pet_file_drop(
+    mut events: EventReader<FileDragAndDrop>,
+    mut viewed: ResMut<ViewedPet>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    for event in events.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            // Images are QR codes of addresses, see the QR plugin
+            if qr::is_qr_image(path_buf) {
+                continue;
+            }
+            viewed.0 = PetSnapshot::load(path_buf)
+                .map_err(|e| format!("Can't open {}: {e}", path_buf.display()));
+            menu_state.set(MenuState::PetViewer);
+        }
+    }
+}
+
 fn menu_action(
     interaction_query: Query<
         (&Interaction, &MenuButtonAction),
@@ -720,6 +1708,24 @@ fn menu_action(
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut pet_owned: ResMut<NextState<PetOwned>>,
+    mut client: ResMut<ChainClient>,
+    mut viewed: ResMut<ViewedPet>,
+    mut spectating: ResMut<Spectating>,
+    address_text: Query<&Text, With<OnAddressInputText>>,
+    mut status_text: Query<
+        &mut Text,
+        (
+            With<OnSpectateStatusText>,
+            Without<OnAddressInputText>,
+            Without<OnReferrerInputText>,
+            Without<OnPetNameInputText>,
+        ),
+    >,
+    referrer_text: Query<&Text, With<OnReferrerInputText>>,
+    pet_name_text: Query<&Text, With<OnPetNameInputText>>,
+    name_rules: Option<Res<NameRules>>,
+    chosen_species: Option<Res<ChosenSpecies>>,
+    address_book: Res<AddressBook>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Clicked {
@@ -734,25 +1740,108 @@ fn menu_action(
                     menu_state.set(MenuState::Disabled);
                 }
 
-                //MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
+                MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
                 //MenuButtonAction::Update => menu_state.set(MenuState::Update),
                 MenuButtonAction::Transaction => menu_state.set(MenuState::Transaction),
+                MenuButtonAction::PetViewer => menu_state.set(MenuState::PetViewer),
+                //Export the player's pet and show it in the viewer
+                MenuButtonAction::ExportPet => {
+                    let account = client.account_id();
+                    let snapshot = PetSnapshot::fetch(&mut client, &account).and_then(|snapshot| {
+                        snapshot.save(SNAPSHOT_FILE)?;
+                        Ok(snapshot)
+                    });
+                    viewed.0 = snapshot.map_err(|e| format!("Export failed: {e}"));
+                    menu_state.set(MenuState::PetViewer);
+                }
+                MenuButtonAction::CareStats => {
+                    game_state.set(GameState::Analytics);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Leaderboard => {
+                    game_state.set(GameState::Leaderboard);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Memorials => {
+                    game_state.set(GameState::Memorial);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::CareSchedule => {
+                    game_state.set(GameState::Schedule);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Spectate => menu_state.set(MenuState::Spectate),
+                MenuButtonAction::MarketPrices => {
+                    game_state.set(GameState::Market);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::AddressBook => {
+                    game_state.set(GameState::AddressBook);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::ColdSign => {
+                    game_state.set(GameState::ColdSign);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Tournaments => {
+                    game_state.set(GameState::Tournament);
+                    menu_state.set(MenuState::Disabled);
+                }
+                //Watch the pet of the typed address or contact, no keys are needed to read it
+                MenuButtonAction::WatchPet => {
+                    let address = address_book.resolve(&address_text.single().sections[0].value);
+                    let snapshot = address
+                        .parse::<AccountId32>()
+                        .map_err(|_| "Invalid address".to_string())
+                        .and_then(|account| {
+                            PetSnapshot::fetch(&mut client, &account).map_err(|e| e.to_string())
+                        });
+                    match snapshot {
+                        Ok(snapshot) => {
+                            spectating.0 = Some(snapshot);
+                            game_state.set(GameState::Game);
+                            menu_state.set(MenuState::Disabled);
+                        }
+                        Err(e) => status_text.single_mut().sections[0].value = e,
+                    }
+                }
                 //Return to Main menu
                 MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
                 //Submit mint_pet information
                 MenuButtonAction::MintPet(name, species) => {
-                    println!("mint pet, {}, {}", name, species);
-                    let result = tokio::runtime::Runtime::new().unwrap().block_on(mint(1, PetSpecies::Rabbit, name.clone()));
+                    let name = pet_name_text
+                        .get_single()
+                        .map_or_else(|_| name.clone(), |text| text.sections[0].value.clone());
+                    // The label is dimmed meanwhile, the check under the name tells why
+                    if let Some(check) = name_rules.as_ref().map(|rules| rules.check(&name)) {
+                        if !check.allows_mint() {
+                            warn!("not minting a pet named {name:?}: {check:?}");
+                            continue;
+                        }
+                    }
+                    let species = chosen_species.as_ref().map_or(species, |chosen| &chosen.0);
+                    let _span = info_span!("mint pet", %name, %species).entered();
+                    info!("mint pet, {}, {}", name, species);
+                    // The referral has to be registered before the first mint to pay the bonus
+                    if let Ok(referrer) = referrer_text.get_single() {
+                        let referrer = referrer.sections[0].value.trim();
+                        if !referrer.is_empty() {
+                            if let Err(e) = register_referral(&mut client, referrer) {
+                                error!("error registering the referral: {e}");
+                            }
+                        }
+                    }
+                    let result = tokio::runtime::Runtime::new().unwrap().block_on(mint(client.signer(), 1, species_from_name(species), name.clone()));
                     match result {
                         Ok(_) => {
-                            println!("minted pet");
+                            info!("minted pet");
                             pet_owned.set(PetOwned::Enable);
                             game_state.set(GameState::Game);
                             menu_state.set(MenuState::Disabled);
                             
                         },
                         Err(e) => {
-                            println!("error minting pet: {:?}", e);
+                            error!("error minting pet: {:?}", e);
                             menu_state.set(MenuState::Main)
                         },
                     }
@@ -772,7 +1861,7 @@ fn listen_received_character_events_player_id_input(
     for event in events.iter() {
         if kbd.just_pressed(KeyCode::Return) {
             let userid = &edit_text.single_mut().sections[0].value;
-            println!("{userid:?}");
+            info!("{userid:?}");
         } else if kbd.just_pressed(KeyCode::Back) {
             edit_text.single_mut().sections[0].value.pop();
         } else {
@@ -781,36 +1870,205 @@ fn listen_received_character_events_player_id_input(
     }
 }
 
-#[subxt::subxt(runtime_metadata_path = "./metadata.scale")]
-//#[subxt::subxt(runtime_metadata_path = "/mnt/hddisk1/github/SuperPetGame-RST/metadata.scale")]
-pub mod polkadot {}
-type PetId = u32;
-type PetSpecies = polkadot::runtime_types::pallet_pet::pallet::Species;
-type PetInfo = polkadot::runtime_types::pallet_pet::pallet::PetInfo;
-//type Error = polkadot::runtime_types::pallet_pet::pallet::Error;
-type PetName = polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec<u8>;
+// Type the address of the player who referred this one
+fn referrer_input(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    focus: Res<InputField>,
+    mut edit_text: Query<&mut Text, With<OnReferrerInputText>>,
+) {
+    let Ok(mut text) = edit_text.get_single_mut() else {
+        return;
+    };
+    if *focus != InputField::Referrer {
+        events.clear();
+        return;
+    }
+    if kbd.just_pressed(KeyCode::Back) {
+        text.sections[0].value.pop();
+    }
+    for event in events.iter() {
+        if event.char.is_ascii_alphanumeric() {
+            text.sections[0].value.push(event.char);
+        }
+    }
+}
+
+// Move typing to the field clicked, or to the other one on Tab, and light up the focused one
+fn focus_field(
+    kbd: Res<Input<KeyCode>>,
+    mut focus: ResMut<InputField>,
+    mut fields: Query<(&Interaction, &InputField, &mut BackgroundColor)>,
+) {
+    if kbd.just_pressed(KeyCode::Tab) {
+        *focus = match *focus {
+            InputField::PetName => InputField::Referrer,
+            InputField::Referrer => InputField::PetName,
+        };
+    }
+    for (interaction, field, _) in &fields {
+        if *interaction == Interaction::Clicked {
+            *focus = *field;
+        }
+    }
+    if focus.is_changed() {
+        for (_, field, mut color) in &mut fields {
+            *color = if *field == *focus {
+                Color::GRAY
+            } else {
+                Color::DARK_GRAY
+            }
+            .into();
+        }
+    }
+}
+
+// Type the name of the new pet. Backspace removes what the player sees as one character,
+// and Ctrl+V pastes, for emoji the keyboard can't type.
+fn pet_name_input(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    focus: Res<InputField>,
+    mut edit_text: Query<&mut Text, With<OnPetNameInputText>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Ok(mut text) = edit_text.get_single_mut() else {
+        return;
+    };
+    if *focus != InputField::PetName {
+        events.clear();
+        return;
+    }
+    let name = &mut text.sections[0].value;
+    if kbd.just_pressed(KeyCode::Back) {
+        naming::pop_character(name);
+    }
+    let modifier = [
+        KeyCode::LControl,
+        KeyCode::RControl,
+        KeyCode::LWin,
+        KeyCode::RWin,
+    ];
+    if kbd.just_pressed(KeyCode::V) && kbd.any_pressed(modifier) {
+        match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(pasted) => name.extend(pasted.chars().filter(|c| !c.is_control())),
+            Err(e) => toasts.send(Toast(format!("Can't paste a name: {e}"))),
+        }
+    }
+    // Backspace, Tab and the Ctrl+V itself come as control characters too
+    let typed = events.iter().map(|event| event.char);
+    name.extend(typed.filter(|c| !c.is_control()));
+}
+
+// Show the length of the name and what's wrong with it, the name over the sprite of the
+// chosen species, and dim the Mint button while it can't be minted
+fn show_pet_name(
+    rules: Res<NameRules>,
+    chosen: Res<ChosenSpecies>,
+    assets: Res<GameAssets>,
+    name_text: Query<Ref<Text>, With<OnPetNameInputText>>,
+    mut texts: Query<
+        (
+            &mut Text,
+            Option<&OnPetNameCheckText>,
+            Option<&OnPetPreviewName>,
+        ),
+        (
+            Or<(
+                With<OnPetNameCheckText>,
+                With<OnPetPreviewName>,
+                With<OnMintLabel>,
+            )>,
+            Without<OnPetNameInputText>,
+        ),
+    >,
+    mut preview: Query<&mut UiImage, With<OnPetPreview>>,
+) {
+    if chosen.is_changed() {
+        for mut image in &mut preview {
+            *image = assets.pet_image(&chosen.0).into();
+        }
+    }
+    let Ok(name_text) = name_text.get_single() else {
+        return;
+    };
+    if !name_text.is_changed() && !rules.is_changed() {
+        return;
+    }
+    let name = &name_text.sections[0].value;
+    let allowed = rules.check(name).allows_mint();
+    for (mut text, check, preview) in &mut texts {
+        if check.is_some() {
+            text.sections[0].value = rules.describe(name);
+            text.sections[0].style.color = if allowed { TEXT_COLOR } else { Color::ORANGE };
+        } else if preview.is_some() {
+            text.sections[0].value = name.clone();
+        } else {
+            text.sections[0].style.color = if allowed { TEXT_COLOR } else { Color::GRAY };
+        }
+    }
+}
+
+// Register the player as a newcomer brought by `referrer`, both get starter items once the
+// pet is minted
+fn register_referral(client: &mut ChainClient, referrer: &str) -> Result<(), String> {
+    let referrer = referrer
+        .parse::<AccountId32>()
+        .map_err(|_| "invalid referrer address".to_string())?;
+    let call = subxt::dynamic::tx(
+        "ReferralsModule",
+        "register_with_referral",
+        vec![Value::from_bytes(referrer)],
+    );
+    let progress = client.submit_and_watch(&call).map_err(|e| e.to_string())?;
+    client
+        .block_on(progress.wait_for_finalized_success())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
 
 #[derive(Debug)]
 pub struct PetError;
 
+// Pick a species on the new game screen, the species field shows the choice
+fn choose_species(
+    interaction_query: Query<(&Interaction, &SpeciesChoice), Changed<Interaction>>,
+    mut chosen: ResMut<ChosenSpecies>,
+    mut species_text: Query<&mut Text, With<OnPetSpeciesInputText>>,
+) {
+    for (interaction, choice) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            chosen.0 = choice.0.clone();
+            for mut text in &mut species_text {
+                text.sections[0].value = choice.0.clone();
+            }
+        }
+    }
+}
+
+pub fn species_from_name(name: &str) -> PetSpecies {
+    match name {
+        "Snake" => PetSpecies::Snake,
+        "Rabbit" => PetSpecies::Rabbit,
+        _ => PetSpecies::Turtle,
+    }
+}
+
 
 async fn mint(
+    from: PairSigner<PolkadotConfig, Pair>,
     petid: PetId,
     species: PetSpecies,
     name: String,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("start to mint!");
+    info!("start to mint!");
 
     let api = OnlineClient::<PolkadotConfig>::new().await?;
 
     //Some pet information, include petname, species, petid
     let petid: PetId = 1;
-    let species = polkadot::runtime_types::pallet_pet::pallet::Species::Turtle;
     let petname = polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec(name.into_bytes());
 
-    //Mint a pet for account Alice.
-    let from = PairSigner::new(AccountKeyring::Alice.pair());
-
     // Build a pet mint extrinsic.
     let balance_transfer_tx = polkadot::tx().pet_module().mint(petname, species, petid);
     // Submit the balance transfer extrinsic from Alice, and wait for it to be successful
@@ -827,7 +2085,7 @@ async fn mint(
         match status? {
             // It's finalized in a block!
             TxStatus::Finalized(in_block) => {
-                println!(
+                info!(
                     "Transaction is finalized in block ",
                     //in_block.extrinsic_hash(),
                     //in_block.block_hash()
@@ -839,20 +2097,19 @@ async fn mint(
                 //println!("Event:{events:?}");
                 // We can look for events (this uses the static interface; we can also iterate
                 //over them and dynamically decode them):
-                let transfer_event =
-                    events.find_first::<polkadot::pet_module::events::PetMinted>()?;
+                let transfer_event = events.find_first::<pet_events::PetMinted>()?;
 
                 if let Some(_event) = transfer_event {
-                    println!("Yeah! You have your own pet!");
+                    info!("Yeah! You have your own pet!");
                 } else {
-                    println!("Error::AlreadyHavePet");
+                    info!("Error::AlreadyHavePet");
                 }
             }
             TxStatus::Ready => {}
             TxStatus::InBlock(_) => {}
             // Just log any other status we encounter:
             other => {
-                println!("Status: {other:?}");
+                info!("Status: {other:?}");
             }
         }
     }
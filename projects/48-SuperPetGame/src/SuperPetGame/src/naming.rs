@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::ext::codec::Decode;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::client::{polkadot, ChainClient};
+
+// Name the new game screen starts with
+pub const DEFAULT_NAME: &str = "Annatle";
+
+// Pets read per request when gathering the names already given
+const PAGE_SIZE: u32 = 100;
+
+// What the chain accepts as a pet name, read when the new game screen opens. The limit is the
+// pallet's `StringLimit`, in bytes. Names don't have to be unique on chain, but the screen
+// won't mint a pet under a name another pet already has.
+#[derive(Resource, Default)]
+pub struct NameRules {
+    limit: Option<u32>,
+    // Lowercase names of the pets on chain, once they're read
+    taken: Option<HashSet<String>>,
+}
+
+// Result of the background read of the names already given
+#[derive(Resource)]
+pub struct TakenNamesLoad(Mutex<mpsc::Receiver<Result<HashSet<String>, String>>>);
+
+// What's wrong with a name, if anything
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NameCheck {
+    Empty,
+    TooLong { over: usize },
+    Taken,
+    Valid,
+}
+
+impl NameCheck {
+    pub fn allows_mint(self) -> bool {
+        self == NameCheck::Valid
+    }
+}
+
+async fn load_taken_names(
+    api: subxt::OnlineClient<subxt::PolkadotConfig>,
+) -> Result<HashSet<String>, subxt::Error> {
+    let storage = api.storage().at_latest().await?;
+    let mut pets = storage
+        .iter(polkadot::storage().pet_module().pets_info_root(), PAGE_SIZE)
+        .await?;
+    let mut taken = HashSet::new();
+    while let Some((_, (_, info))) = pets.next().await? {
+        taken.insert(String::from_utf8_lossy(&info.name.0).to_lowercase());
+    }
+    Ok(taken)
+}
+
+impl NameRules {
+    // Read the limit, and start reading the names already given
+    pub fn load(commands: &mut Commands, client: &mut ChainClient) -> Self {
+        let limit = client.api().and_then(|api| {
+            let value = api
+                .constants()
+                .at(&subxt::dynamic::constant("PetModule", "StringLimit"))?;
+            Ok::<_, subxt::Error>(u32::decode(&mut value.encoded())?)
+        });
+        let limit = limit
+            .map_err(|e| warn!("not checking pet name lengths: {e:?}"))
+            .ok();
+
+        let (sender, receiver) = mpsc::channel();
+        match client.api() {
+            Ok(api) => {
+                client.spawn(async move {
+                    let taken = load_taken_names(api).await;
+                    let _ = sender.send(taken.map_err(|e| e.to_string()));
+                });
+            }
+            Err(e) => {
+                let _ = sender.send(Err(e.to_string()));
+            }
+        }
+        commands.insert_resource(TakenNamesLoad(Mutex::new(receiver)));
+
+        NameRules { limit, taken: None }
+    }
+
+    pub fn check(&self, name: &str) -> NameCheck {
+        if name.trim().is_empty() {
+            return NameCheck::Empty;
+        }
+        // A `String` is always UTF-8, so only its length can be refused
+        if let Some(limit) = self.limit {
+            if pet_core::check_name(name.as_bytes(), limit).is_err() {
+                return NameCheck::TooLong {
+                    over: name.len() - limit as usize,
+                };
+            }
+        }
+        let taken = self.taken.as_ref();
+        if taken.map_or(false, |taken| taken.contains(&name.to_lowercase())) {
+            return NameCheck::Taken;
+        }
+        NameCheck::Valid
+    }
+
+    // What the name input shows under the name: its length as the player sees it, in
+    // characters, and as the chain counts it, in bytes
+    pub fn describe(&self, name: &str) -> String {
+        let characters = name.graphemes(true).count();
+        match (self.check(name), self.limit) {
+            (NameCheck::Empty, _) => "Type a name for the pet".to_string(),
+            (NameCheck::TooLong { over }, _) => {
+                format!("{characters} characters, {over} bytes too long")
+            }
+            (NameCheck::Taken, _) => format!("Another pet is already named {name}"),
+            (NameCheck::Valid, Some(limit)) => format!(
+                "{characters} characters, {} of {limit} bytes left",
+                limit as usize - name.len()
+            ),
+            (NameCheck::Valid, None) => format!("{characters} characters"),
+        }
+    }
+}
+
+// Keep the names already given once they're read, names are only checked by length until then
+pub fn receive_taken_names(
+    mut commands: Commands,
+    load: Option<Res<TakenNamesLoad>>,
+    rules: Option<ResMut<NameRules>>,
+) {
+    let (Some(load), Some(mut rules)) = (load, rules) else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<TakenNamesLoad>();
+    match result {
+        Ok(taken) => rules.taken = Some(taken),
+        Err(e) => warn!("not checking whether pet names are taken: {e}"),
+    }
+}
+
+// Remove the last character of a name as the player sees it, so an emoji made of several
+// code points, or a letter with its accent, goes at once
+pub fn pop_character(name: &mut String) {
+    if let Some((start, _)) = name.grapheme_indices(true).next_back() {
+        name.truncate(start);
+    }
+}
@@ -0,0 +1,334 @@
+use bevy::input::gamepad::{GamepadButton, GamepadButtonType, Gamepads};
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, UiSystem};
+
+use super::{
+    client::{AccountChanged, ChainClient},
+    toast::Toast,
+    GameState, TEXT_COLOR,
+};
+
+// Hints shown for each control scheme, nothing is shown with the mouse
+const KEYBOARD_HINT: &str = "Arrows: move   Enter: select";
+const GAMEPAD_HINT: &str = "D-pad: move   A: select   LB/RB: switch pet";
+
+// This plugin lets the menus and the game be played with the keyboard or a gamepad: the
+// arrows or the D-pad move the focus between buttons, Enter or A presses the focused button
+// and the shoulder buttons switch to the pet of the next account of the keystore
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .init_resource::<ControlScheme>()
+            .add_startup_system(navigation_setup)
+            // Presses have to land after bevy computed the interactions of the frame
+            .add_system(
+                press_focused
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(UiSystem::Focus),
+            )
+            .add_systems((
+                detect_control_scheme,
+                move_focus.after(detect_control_scheme),
+                show_focus.after(move_focus),
+                show_hint.after(detect_control_scheme),
+                cycle_pets.run_if(in_state(GameState::Menu).or_else(in_state(GameState::Game))),
+            ));
+    }
+}
+
+// The button pressed by Enter or A
+#[derive(Resource, Default)]
+pub struct Focus(pub Option<Entity>);
+
+// The kind of input used last, drives the hints and the focus frame
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ControlScheme {
+    #[default]
+    Mouse,
+    Keyboard,
+    Gamepad,
+}
+
+// Frame drawn over the focused button
+#[derive(Component)]
+struct FocusFrame;
+
+#[derive(Component)]
+struct ControlHint;
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn vector(self) -> Vec2 {
+        // UI coordinates go down from the top of the window
+        match self {
+            Direction::Up => Vec2::NEG_Y,
+            Direction::Down => Vec2::Y,
+            Direction::Left => Vec2::NEG_X,
+            Direction::Right => Vec2::X,
+        }
+    }
+}
+
+fn navigation_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.25).into(),
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(40),
+            ..default()
+        },
+        FocusFrame,
+    ));
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(4.0),
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(20),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 16.0,
+                            color: TEXT_COLOR,
+                        },
+                    ),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                },
+                ControlHint,
+            ));
+        });
+}
+
+// Switch to the scheme of the last input: the mouse when it moves or clicks, the keyboard
+// or a gamepad when one of their buttons is pressed
+fn detect_control_scheme(
+    mut scheme: ResMut<ControlScheme>,
+    mut cursor: EventReader<CursorMoved>,
+    mouse: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let used = if gamepad_buttons.get_just_pressed().next().is_some() {
+        ControlScheme::Gamepad
+    } else if keys.get_just_pressed().next().is_some() {
+        ControlScheme::Keyboard
+    } else if cursor.iter().count() > 0 || mouse.get_just_pressed().next().is_some() {
+        ControlScheme::Mouse
+    } else {
+        return;
+    };
+    if *scheme != used {
+        *scheme = used;
+    }
+}
+
+fn pressed_direction(
+    keys: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<Direction> {
+    let pad = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    if keys.just_pressed(KeyCode::Up) || pad(GamepadButtonType::DPadUp) {
+        Some(Direction::Up)
+    } else if keys.just_pressed(KeyCode::Down) || pad(GamepadButtonType::DPadDown) {
+        Some(Direction::Down)
+    } else if keys.just_pressed(KeyCode::Left) || pad(GamepadButtonType::DPadLeft) {
+        Some(Direction::Left)
+    } else if keys.just_pressed(KeyCode::Right) || pad(GamepadButtonType::DPadRight) {
+        Some(Direction::Right)
+    } else {
+        None
+    }
+}
+
+// Move the focus to the closest visible button in the pressed direction. Buttons further
+// off the axis count as further away, so moving down a column stays in the column.
+fn move_focus(
+    mut focus: ResMut<Focus>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    buttons: Query<(Entity, &Node, &GlobalTransform, &ComputedVisibility), With<Button>>,
+) {
+    // Buttons under a `Display::None` node are still visible, but have no size
+    let shown = buttons
+        .iter()
+        .filter(|(_, node, _, visibility)| visibility.is_visible() && node.size() != Vec2::ZERO)
+        .map(|(entity, _, transform, _)| (entity, transform.translation().truncate()))
+        .collect::<Vec<_>>();
+    // Screens despawn or hide their buttons, start over from the first one then
+    let current = shown
+        .iter()
+        .find(|(entity, _)| Some(*entity) == focus.0)
+        .map(|(_, position)| *position);
+    if current.is_none() && focus.0.is_some() {
+        focus.0 = None;
+    }
+    let Some(direction) = pressed_direction(&keys, &gamepads, &gamepad_buttons) else {
+        return;
+    };
+    let Some(from) = current else {
+        // Nothing focused yet, start from the top left button
+        focus.0 = shown
+            .into_iter()
+            .min_by(|(_, a), (_, b)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap())
+            .map(|(entity, _)| entity);
+        return;
+    };
+
+    let axis = direction.vector();
+    let next = shown
+        .into_iter()
+        .filter(|(entity, _)| Some(*entity) != focus.0)
+        .filter_map(|(entity, position)| {
+            let offset = position - from;
+            let along = offset.dot(axis);
+            let across = offset.perp_dot(axis).abs();
+            (along > 1.0).then_some((entity, along + 2.0 * across))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(entity, _)| entity);
+    if next.is_some() {
+        focus.0 = next;
+    }
+}
+
+// Press the focused button on Enter or A, like a mouse click would, and release it on the
+// next frame
+fn press_focused(
+    focus: Res<Focus>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut interactions: Query<&mut Interaction, With<Button>>,
+    mut pressed: Local<Option<Entity>>,
+) {
+    if let Some(entity) = pressed.take() {
+        if let Ok(mut interaction) = interactions.get_mut(entity) {
+            if *interaction == Interaction::Clicked {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+    let confirm = keys.just_pressed(KeyCode::Return)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+        });
+    if !confirm {
+        return;
+    }
+    if let Some(mut interaction) = focus.0.and_then(|entity| interactions.get_mut(entity).ok()) {
+        *interaction = Interaction::Clicked;
+        *pressed = focus.0;
+    }
+}
+
+// Keep the frame over the focused button. The frame is placed in UI pixels, which
+// `UiScale` enlarges, so the layout positions are scaled back down.
+fn show_focus(
+    focus: Res<Focus>,
+    scheme: Res<ControlScheme>,
+    ui_scale: Res<UiScale>,
+    buttons: Query<(&Node, &GlobalTransform), With<Button>>,
+    mut frame: Query<&mut Style, With<FocusFrame>>,
+) {
+    let Ok(mut style) = frame.get_single_mut() else {
+        return;
+    };
+    let focused = focus
+        .0
+        .filter(|_| *scheme != ControlScheme::Mouse)
+        .and_then(|entity| buttons.get(entity).ok());
+    let Some((node, transform)) = focused else {
+        if style.display != Display::None {
+            style.display = Display::None;
+        }
+        return;
+    };
+    let scale = ui_scale.scale as f32;
+    let size = node.size();
+    let top_left = transform.translation().truncate() - size / 2.0;
+    style.display = Display::Flex;
+    style.position = UiRect {
+        left: Val::Px(top_left.x / scale),
+        top: Val::Px(top_left.y / scale),
+        ..default()
+    };
+    style.size = Size::new(Val::Px(size.x / scale), Val::Px(size.y / scale));
+}
+
+fn show_hint(scheme: Res<ControlScheme>, mut hint: Query<&mut Text, With<ControlHint>>) {
+    if !scheme.is_changed() {
+        return;
+    }
+    let value = match *scheme {
+        ControlScheme::Mouse => "",
+        ControlScheme::Keyboard => KEYBOARD_HINT,
+        ControlScheme::Gamepad => GAMEPAD_HINT,
+    };
+    for mut text in &mut hint {
+        text.sections[0].value = value.to_string();
+    }
+}
+
+// Play as the next or previous account of the keystore, each with its own pet
+fn cycle_pets(
+    mut client: ResMut<ChainClient>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut account_changed: EventWriter<AccountChanged>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let pad = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let step = if pad(GamepadButtonType::RightTrigger) {
+        1
+    } else if pad(GamepadButtonType::LeftTrigger) {
+        client.accounts().len().saturating_sub(1)
+    } else {
+        return;
+    };
+    let next = (client.active_account() + step) % client.accounts().len();
+    if !client.switch_account(next) {
+        return;
+    }
+    account_changed.send(AccountChanged);
+    toasts.send(Toast(format!("Playing as {}", client.account().name)));
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    client::{format_balance, ChainClient, ChainEvent},
+    indexer::IndexedEvent,
+    pet_watch::{CareRules, PlayerPet},
+    poll::{PollScheduler, Priority},
+    upgrade,
+};
+
+// Notification settings, read at startup. Without a webhook url nothing is posted.
+const SETTINGS_FILE: &str = "notify-settings.json";
+
+// How often the pet's hunger is checked
+const HUNGER_CHECK_SECONDS: f32 = 60.0;
+
+// This plugin posts what happens to the player's pet to a webhook (Discord, Slack or anything
+// accepting `{"content": "..."}`), so the player hears about it with the game closed
+pub struct NotifierPlugin;
+
+impl Plugin for NotifierPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NotifySettings::load())
+            .add_systems((notify_events, notify_hunger.run_if(upgrade::rules_match)));
+    }
+}
+
+// Which events are posted, each can be turned off in the settings file
+#[derive(Resource, Deserialize)]
+#[serde(default)]
+pub struct NotifySettings {
+    pub webhook_url: Option<String>,
+    // The player's pet was transferred away, or a pet was transferred to the player
+    pub transferred: bool,
+    pub sold: bool,
+    // Someone challenged the player's pet
+    pub challenged: bool,
+    pub hungry: bool,
+}
+
+impl Default for NotifySettings {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            transferred: true,
+            sold: true,
+            challenged: true,
+            hungry: true,
+        }
+    }
+}
+
+impl NotifySettings {
+    fn load() -> Self {
+        let Ok(json) = std::fs::read(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&json).unwrap_or_else(|e| {
+            warn!("ignoring invalid {SETTINGS_FILE}: {e}");
+            Self::default()
+        })
+    }
+
+    // Post the message in the background if a webhook is set
+    fn post(&self, client: &ChainClient, message: String) {
+        let Some(url) = self.webhook_url.clone() else {
+            return;
+        };
+        client.spawn(async move {
+            let body = serde_json::json!({ "content": message });
+            let sent = reqwest::Client::new()
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = sent {
+                error!("webhook error: {e}");
+            }
+        });
+    }
+}
+
+fn notify_events(
+    settings: Res<NotifySettings>,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    mut events: EventReader<ChainEvent>,
+) {
+    if settings.webhook_url.is_none() {
+        events.clear();
+        return;
+    }
+    let me = Some(client.account_id());
+    let my_pet = player_pet.pet.as_ref().map(|pet| pet.id);
+    for ChainEvent(event) in events.iter() {
+        let Some(event) = IndexedEvent::decode(event) else {
+            continue;
+        };
+        let pet_id = event.pet_id.unwrap_or_default();
+        let message = match event.name.as_str() {
+            "PetTransfered" if settings.transferred && event.account == me => {
+                format!("Your pet #{pet_id} was transferred away")
+            }
+            "PetTransfered" if settings.transferred && event.other == me => {
+                format!("Pet #{pet_id} was transferred to you")
+            }
+            "PetMinted" if settings.transferred && event.other.is_some() && event.account == me => {
+                format!("Pet #{pet_id} was minted for you as a gift")
+            }
+            "Sold" if settings.sold && event.account == me => format!(
+                "Your pet #{pet_id} was sold for {}",
+                format_balance(event.amount.unwrap_or_default())
+            ),
+            "BattleResolved" if settings.challenged && event.other == me => {
+                let outcome = if event.pet_id == my_pet {
+                    "won"
+                } else {
+                    "lost"
+                };
+                format!("Your pet was challenged and {outcome}")
+            }
+            _ => continue,
+        };
+        settings.post(&client, message);
+    }
+}
+
+// Post once when the pet gets hungry, and again after it was fed and got hungry again
+fn notify_hunger(
+    settings: Res<NotifySettings>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    care_rules: Res<CareRules>,
+    mut scheduler: ResMut<PollScheduler>,
+    // Feed time the last notice was posted for
+    mut notified_feed: Local<Option<Option<u32>>>,
+) {
+    if !settings.hungry || settings.webhook_url.is_none() {
+        return;
+    }
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    if *notified_feed == Some(player_pet.last_feed_block) {
+        return;
+    }
+    if !scheduler.ready("hunger notice", Priority::Background, HUNGER_CHECK_SECONDS) {
+        return;
+    }
+    let head = client.api().and_then(|api| {
+        client.block_on(async { Ok::<_, subxt::Error>(api.blocks().at_latest().await?.number()) })
+    });
+    let Ok(head) = head else {
+        return;
+    };
+    let stats = player_pet
+        .care()
+        .stats(&care_rules.rules, pet_core::at_block(head));
+    if stats.mood.is_hungry() {
+        settings.post(&client, format!("{} is hungry", pet.name));
+        *notified_feed = Some(player_pet.last_feed_block);
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{format_balance, ChainClient, PetId},
+    despawn_screen,
+    game::Spectating,
+    pet_watch::PlayerPet,
+    poll::{PollScheduler, Priority},
+    trade_chat::ChatLink,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// How often the offers are read again while playing
+const REFRESH_SECONDS: f32 = 15.0;
+
+// Offers expire on block numbers, shown in hours of 6 second blocks
+const BLOCKS_PER_HOUR: u32 = 600;
+
+// This plugin lists the offers other players made on the player's pet, on the game screen,
+// with buttons to accept or reject each of them
+pub struct OffersPlugin;
+
+impl Plugin for OffersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            offers_setup.in_schedule(OnEnter(GameState::Game)),
+            despawn_screen::<OnOffersPanel>.in_schedule(OnExit(GameState::Game)),
+        ))
+        .add_systems((refresh_offers, offer_action).in_set(OnUpdate(GameState::Game)));
+    }
+}
+
+// Mirror of `Offer` of the pet pallet
+#[derive(Decode)]
+struct Offer {
+    buyer: AccountId32,
+    price: u128,
+    expires_at: u32,
+}
+
+// Tag component used to tag the offers panel
+#[derive(Component)]
+struct OnOffersPanel;
+
+#[derive(Component)]
+enum OfferAction {
+    Accept(AccountId32),
+    Reject(AccountId32),
+}
+
+// Open offers on the pet, and the head of the chain to tell when they expire
+fn load_offers(client: &mut ChainClient, pet_id: PetId) -> Result<(Vec<Offer>, u32), subxt::Error> {
+    let api = client.api()?;
+    client.block_on(async {
+        let block = api.blocks().at_latest().await?;
+        let offers = api
+            .storage()
+            .at(block.hash())
+            .fetch(&subxt::dynamic::storage(
+                "PetModule",
+                "Offers",
+                vec![Value::u128(pet_id.into())],
+            ))
+            .await?
+            .map(|value| Vec::<Offer>::decode(&mut value.encoded()))
+            .transpose()?
+            .unwrap_or_default();
+        Ok((offers, block.number()))
+    })
+}
+
+fn offers_setup(mut commands: Commands, spectating: Res<Spectating>) {
+    if spectating.0.is_some() {
+        return;
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                gap: Size::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        },
+        OnOffersPanel,
+    ));
+}
+
+// Rebuild the panel when the game screen opens, then every `REFRESH_SECONDS`
+fn refresh_offers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    panel: Query<Entity, With<OnOffersPanel>>,
+    mut scheduler: ResMut<PollScheduler>,
+) {
+    let Ok(panel) = panel.get_single() else {
+        scheduler.forget("offers");
+        return;
+    };
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    if !scheduler.ready("offers", Priority::Normal, REFRESH_SECONDS) {
+        return;
+    }
+
+    let (offers, now) = match load_offers(&mut client, pet.id) {
+        Ok(offers) => offers,
+        Err(e) => {
+            error!("error reading offers: {e:?}");
+            return;
+        }
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    commands.entity(panel).despawn_descendants();
+    if offers.is_empty() {
+        return;
+    }
+    commands.entity(panel).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("Offers on {}", pet.name),
+            text_style.clone(),
+        ));
+        for offer in offers {
+            let buyer = offer.buyer.to_string();
+            let hours = offer.expires_at.saturating_sub(now) / BLOCKS_PER_HOUR;
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!(
+                            "{}...  {}  ({hours}h left)",
+                            &buyer[..8],
+                            format_balance(offer.price)
+                        ),
+                        text_style.clone(),
+                    ));
+                    // Negotiate with the buyer before answering
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(4.0)),
+                                    ..default()
+                                },
+                                background_color: Color::DARK_GRAY.into(),
+                                ..default()
+                            },
+                            ChatLink {
+                                pet_id: pet.id,
+                                with: offer.buyer.clone(),
+                            },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Message", text_style.clone()));
+                        });
+                    for (label, color, action) in [
+                        (
+                            "Accept",
+                            Color::DARK_GREEN,
+                            OfferAction::Accept(offer.buyer.clone()),
+                        ),
+                        ("Reject", Color::MAROON, OfferAction::Reject(offer.buyer)),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::all(Val::Px(4.0)),
+                                        ..default()
+                                    },
+                                    background_color: color.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+        }
+    });
+}
+
+fn offer_action(
+    interaction_query: Query<(&Interaction, &OfferAction), Changed<Interaction>>,
+    mut queue: ResMut<TxQueue>,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let pet = player_pet.pet.as_ref().map(|pet| pet.id);
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let (label, call, buyer) = match action {
+            OfferAction::Accept(buyer) => ("Accept offer", "accept_offer", buyer),
+            OfferAction::Reject(buyer) => ("Reject offer", "reject_offer", buyer),
+        };
+        queue.push_pet_call(&client, pet, label, call, vec![Value::from_bytes(buyer)]);
+    }
+}
@@ -0,0 +1,532 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sp_keyring::sr25519::sr25519::Pair;
+use sp_keyring::AccountKeyring;
+use subxt::dynamic::Value;
+use subxt::tx::PairSigner;
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{format_balance, polkadot, ChainClient, PetId, PetSpecies},
+    despawn_screen, GameState, PetOwned, TEXT_COLOR,
+};
+
+// Where the wizard remembers how far the player got, so it resumes after a restart
+const PROGRESS_FILE: &str = "onboarding.json";
+
+// Sent by the dev faucet, 100 tokens
+pub const FAUCET_AMOUNT: u128 = 100_000_000_000_000;
+
+// This plugin walks new players through connecting to a node, creating or importing an
+// account, funding it and minting their first pet, before they reach the menu
+pub struct OnboardingPlugin;
+
+impl Plugin for OnboardingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Onboarding::load())
+            .add_systems((
+                onboarding_setup.in_schedule(OnEnter(GameState::Onboarding)),
+                despawn_screen::<OnOnboardingScreen>.in_schedule(OnExit(GameState::Onboarding)),
+            ))
+            .add_systems(
+                (text_input, wizard_action, show_step)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Onboarding)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
+enum Step {
+    #[default]
+    Connect,
+    Account,
+    Fund,
+    Mint,
+    Done,
+}
+
+#[derive(Resource)]
+pub struct Onboarding {
+    step: Step,
+    // Outcome of the last action, shown under the step
+    status: String,
+    // What the player typed: the secret to import, then the name of the pet
+    input: String,
+    species: PetSpecies,
+}
+
+impl Onboarding {
+    fn load() -> Self {
+        let step = std::fs::read(PROGRESS_FILE)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default();
+        Self {
+            step,
+            status: String::new(),
+            input: String::new(),
+            species: PetSpecies::Turtle,
+        }
+    }
+
+    fn advance(&mut self, step: Step) {
+        self.step = step;
+        self.input.clear();
+        if let Err(e) = serde_json::to_vec(&step)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(PROGRESS_FILE, json).map_err(|e| e.to_string()))
+        {
+            error!("can't save the onboarding progress: {e}");
+        }
+    }
+
+    // Whether the wizard was completed, the game then starts on the menu
+    pub fn finished(&self) -> bool {
+        self.step == Step::Done
+    }
+}
+
+// Tag component used to tag entities added on the onboarding screen
+#[derive(Component)]
+struct OnOnboardingScreen;
+
+// Content of the current step, rebuilt when it changes
+#[derive(Component)]
+struct StepPanel;
+
+#[derive(Component)]
+struct InputText;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum WizardAction {
+    Connect,
+    CreateAccount,
+    ImportAccount,
+    KeepAccount,
+    RequestFunds,
+    CheckBalance,
+    NextSpecies,
+    Mint,
+}
+
+// Pet ids are chosen by the minter, derive one from the account so new players don't collide
+pub fn new_pet_id(account: &AccountId32) -> PetId {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&account.0[..4]);
+    PetId::from_le_bytes(bytes)
+}
+
+pub fn mint_call(name: &str, species: PetSpecies, id: PetId) -> impl subxt::tx::TxPayload {
+    polkadot::tx().pet_module().mint(
+        polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec(
+            name.as_bytes().to_vec(),
+        ),
+        species,
+        id,
+    )
+}
+
+fn has_pet(client: &mut ChainClient) -> bool {
+    let account = client.account_id();
+    let pet = client.api().and_then(|api| {
+        client.block_on(async {
+            api.storage()
+                .at_latest()
+                .await?
+                .fetch(&polkadot::storage().pet_module().pets_info(&account))
+                .await
+        })
+    });
+    matches!(pet, Ok(Some(_)))
+}
+
+// Whether the player can pay for minting a pet
+fn is_funded(client: &mut ChainClient) -> bool {
+    let call = mint_call("", PetSpecies::Turtle, new_pet_id(&client.account_id()));
+    client
+        .estimate_fee(&call)
+        .map_or(false, |estimate| estimate.free > 0 && estimate.affordable())
+}
+
+// Skip the steps the player already went through, e.g. someone who played before the wizard
+// existed, or who funded the account from elsewhere before coming back
+fn catch_up(onboarding: &mut Onboarding, client: &mut ChainClient) {
+    if onboarding.step == Step::Connect && client.api().is_ok() {
+        onboarding.advance(Step::Account);
+    }
+    if onboarding.step == Step::Connect {
+        return;
+    }
+    if has_pet(client) {
+        onboarding.advance(Step::Done);
+        return;
+    }
+    if onboarding.step == Step::Account && client.has_keystore() {
+        onboarding.advance(Step::Fund);
+    }
+    if onboarding.step == Step::Fund && is_funded(client) {
+        onboarding.advance(Step::Mint);
+    }
+}
+
+// Fund the player from Alice on a dev chain, or ask the faucet at `SUPERPET_FAUCET_URL`
+fn request_funds(client: &mut ChainClient) -> Result<String, String> {
+    let api = client.api().map_err(|e| e.to_string())?;
+    let account = client.account_id();
+    if let Ok(url) = std::env::var("SUPERPET_FAUCET_URL") {
+        client
+            .block_on(async {
+                let body = serde_json::json!({ "address": account.to_string() });
+                reqwest::Client::new()
+                    .post(&url)
+                    .json(&body)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+            })
+            .map_err(|e| format!("faucet error: {e}"))?;
+        return Ok("Tokens requested from the faucet, they can take a minute to arrive".into());
+    }
+    let chain = client
+        .block_on(api.rpc().system_chain())
+        .map_err(|e| e.to_string())?;
+    if !chain.contains("Development") && !chain.contains("Local") {
+        return Err(format!(
+            "{chain} has no faucet, ask another player to send tokens to {account}"
+        ));
+    }
+    let transfer = subxt::dynamic::tx(
+        "Balances",
+        "transfer",
+        vec![
+            Value::unnamed_variant("Id", [Value::from_bytes(&account)]),
+            Value::u128(FAUCET_AMOUNT),
+        ],
+    );
+    let faucet = PairSigner::<_, Pair>::new(AccountKeyring::Alice.pair());
+    client
+        .block_on(async {
+            api.tx()
+                .sign_and_submit_then_watch_default(&transfer, &faucet)
+                .await?
+                .wait_for_finalized_success()
+                .await
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(format!(
+        "Received {} from Alice",
+        format_balance(FAUCET_AMOUNT)
+    ))
+}
+
+fn onboarding_setup(
+    mut commands: Commands,
+    mut onboarding: ResMut<Onboarding>,
+    mut client: ResMut<ChainClient>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    catch_up(&mut onboarding, &mut client);
+    if onboarding.finished() {
+        game_state.set(GameState::Menu);
+        return;
+    }
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnOnboardingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Px(700.0)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                StepPanel,
+            ));
+        });
+}
+
+// Type the secret to import or the name of the pet
+fn text_input(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    mut onboarding: ResMut<Onboarding>,
+    mut input_text: Query<&mut Text, With<InputText>>,
+) {
+    if !matches!(onboarding.step, Step::Account | Step::Mint) {
+        events.clear();
+        return;
+    }
+    let mut input = onboarding.input.clone();
+    if kbd.just_pressed(KeyCode::Back) {
+        input.pop();
+    }
+    for event in events.iter() {
+        if !event.char.is_control() {
+            input.push(event.char);
+        }
+    }
+    if input != onboarding.input {
+        for mut text in &mut input_text {
+            text.sections[0].value = input.clone();
+        }
+        // Bypass change detection, typing doesn't need the step rebuilt
+        onboarding.bypass_change_detection().input = input;
+    }
+}
+
+fn wizard_action(
+    interaction_query: Query<(&Interaction, &WizardAction), Changed<Interaction>>,
+    mut onboarding: ResMut<Onboarding>,
+    mut client: ResMut<ChainClient>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut pet_owned: ResMut<NextState<PetOwned>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let result = match action {
+            WizardAction::Connect => client
+                .api()
+                .map(|_| String::new())
+                .map_err(|e| format!("Can't reach {}: {e}", client.url())),
+            WizardAction::CreateAccount => {
+                let (_, phrase, _) = <Pair as sp_core::Pair>::generate_with_phrase(None);
+                client.add_account("Player", &phrase).map(|_| {
+                    format!("Write down your secret phrase, it's the only way to recover the account: {phrase}")
+                })
+            }
+            WizardAction::ImportAccount => {
+                let secret = onboarding.input.trim().to_string();
+                client
+                    .add_account("Imported", &secret)
+                    .map(|_| String::new())
+            }
+            WizardAction::KeepAccount => Ok(String::new()),
+            WizardAction::RequestFunds => request_funds(&mut client),
+            WizardAction::CheckBalance => {
+                if is_funded(&mut client) {
+                    Ok(String::new())
+                } else {
+                    Err("Not enough tokens yet to mint a pet".to_string())
+                }
+            }
+            WizardAction::NextSpecies => {
+                onboarding.species = match onboarding.species {
+                    PetSpecies::Turtle => PetSpecies::Snake,
+                    PetSpecies::Snake => PetSpecies::Rabbit,
+                    _ => PetSpecies::Turtle,
+                };
+                continue;
+            }
+            WizardAction::Mint => {
+                let name = onboarding.input.trim().to_string();
+                let species = onboarding.species.clone();
+                let call = mint_call(&name, species, new_pet_id(&client.account_id()));
+                client
+                    .submit_and_watch(&call)
+                    .and_then(|progress| client.block_on(progress.wait_for_finalized_success()))
+                    .map(|_| String::new())
+                    .map_err(|e| format!("Minting failed: {e}"))
+            }
+        };
+        let status = match result {
+            Ok(status) => status,
+            Err(e) => {
+                onboarding.status = e;
+                continue;
+            }
+        };
+        let next = match action {
+            WizardAction::Connect => Step::Account,
+            WizardAction::CreateAccount
+            | WizardAction::ImportAccount
+            | WizardAction::KeepAccount => Step::Fund,
+            // Faucet tokens may still be on the way, the player checks the balance after
+            WizardAction::RequestFunds => onboarding.step,
+            _ => Step::Mint,
+        };
+        let next = if *action == WizardAction::Mint {
+            Step::Done
+        } else {
+            next
+        };
+        if next != onboarding.step {
+            onboarding.advance(next);
+            catch_up(&mut onboarding, &mut client);
+        }
+        onboarding.status = status;
+        if onboarding.finished() {
+            pet_owned.set(PetOwned::Enable);
+            game_state.set(if *action == WizardAction::Mint {
+                GameState::Game
+            } else {
+                GameState::Menu
+            });
+        }
+    }
+}
+
+// Rebuild the panel for the current step whenever the wizard moves on
+fn show_step(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    onboarding: Res<Onboarding>,
+    client: Res<ChainClient>,
+    panel: Query<Entity, With<StepPanel>>,
+) {
+    if !onboarding.is_changed() {
+        return;
+    }
+    let Ok(panel) = panel.get_single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 36.0,
+        color: TEXT_COLOR,
+    };
+    let text_style = TextStyle {
+        font,
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(220.0), Val::Px(50.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let (title, explanation) = match onboarding.step {
+        Step::Connect => (
+            "Connect to a node",
+            format!(
+                "The game plays on the Super Pet chain through a node at {}. Start one with \
+                 `node-template --dev` to play locally.",
+                client.url()
+            ),
+        ),
+        Step::Account => (
+            "Your account",
+            format!(
+                "Create a new account, import one by typing its secret phrase, or keep \
+                 playing as {}.",
+                client.account().name
+            ),
+        ),
+        Step::Fund => (
+            "Fund your account",
+            format!(
+                "Minting a pet needs a few tokens on {}.",
+                client.account_id()
+            ),
+        ),
+        Step::Mint => (
+            "Mint your first pet",
+            "Type its name and pick a species.".to_string(),
+        ),
+        Step::Done => ("Welcome!", String::new()),
+    };
+    let actions: &[(WizardAction, String)] = &match onboarding.step {
+        Step::Connect => vec![(WizardAction::Connect, "Connect".to_string())],
+        Step::Account => vec![
+            (WizardAction::CreateAccount, "Create account".to_string()),
+            (WizardAction::ImportAccount, "Import".to_string()),
+            (
+                WizardAction::KeepAccount,
+                format!("Keep {}", client.account().name),
+            ),
+        ],
+        Step::Fund => vec![
+            (WizardAction::RequestFunds, "Get tokens".to_string()),
+            (WizardAction::CheckBalance, "Check balance".to_string()),
+        ],
+        Step::Mint => vec![
+            (
+                WizardAction::NextSpecies,
+                format!("Species: {:?}", onboarding.species),
+            ),
+            (WizardAction::Mint, "Mint".to_string()),
+        ],
+        Step::Done => Vec::new(),
+    };
+
+    commands.entity(panel).despawn_descendants();
+    commands.entity(panel).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("Step {} of 4: {title}", onboarding.step as usize + 1),
+            title_style,
+        ));
+        parent.spawn(TextBundle::from_section(explanation, text_style.clone()));
+        if matches!(onboarding.step, Step::Account | Step::Mint) {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Px(40.0)),
+                        align_items: AlignItems::Center,
+                        padding: UiRect::horizontal(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(onboarding.input.clone(), text_style.clone()),
+                        InputText,
+                    ));
+                });
+        }
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    gap: Size::all(Val::Px(12.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for (action, label) in actions {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: Color::DARK_GRAY.into(),
+                                ..default()
+                            },
+                            *action,
+                        ))
+                        .with_children(|parent| {
+                            parent
+                                .spawn(TextBundle::from_section(label.clone(), text_style.clone()));
+                        });
+                }
+            });
+        parent.spawn(TextBundle::from_section(
+            onboarding.status.clone(),
+            TextStyle {
+                color: Color::ORANGE,
+                ..text_style.clone()
+            },
+        ));
+    });
+}
@@ -0,0 +1,756 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::{mpsc, Mutex};
+
+use bevy::render::{mesh::Indices, render_resource::PrimitiveTopology};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, window::PrimaryWindow};
+use pet_gene::Dna;
+use subxt::dynamic::Value;
+use subxt::ext::codec::{self, Decode, Encode, Input};
+use subxt::utils::AccountId32;
+
+use super::{
+    assets::GameAssets,
+    client::{format_balance, ChainClient, PetId, PetSpecies},
+    clock::ChainClock,
+    despawn_screen,
+    game::Spectating,
+    identity::{self, short, AccountNames},
+    inventory::ITEMS,
+    qr,
+    seasonal::COSMETICS,
+    settings::Palette,
+    snapshot::PetSnapshot,
+    tooltip::CallHelp,
+    trade_chat::ChatLink,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Radius of the stats radar chart
+const RADAR_RADIUS: f32 = 110.0;
+
+// Level drawn at the edge of the radar chart
+const RADAR_LEVEL: f32 = 10.0;
+
+// Side of the QR code of the player's address, on the profile of their own pets
+const QR_SIZE: f32 = 160.0;
+
+// Side of the avatars of the co-owners
+const AVATAR_SIZE: f32 = 40.0;
+
+// This plugin shows the profile of any pet, read in one call of the pet runtime api: a
+// preview of its sprite from its DNA, a radar chart of its stats, its lineage, owner and sale
+// status, who shares it, and what the player can do with it. The profile of the player's own pet has the
+// QR code of their address, to be scanned by whoever sends them something. Buttons with a `ProfileLink` open it from any
+// screen, and it goes back to that screen when closed.
+pub struct PetProfilePlugin;
+
+impl Plugin for PetProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewedProfile>()
+            .add_systems((
+                profile_setup.in_schedule(OnEnter(GameState::PetProfile)),
+                despawn_screen::<OnPetProfileScreen>.in_schedule(OnExit(GameState::PetProfile)),
+            ))
+            .add_system(open_profile)
+            .add_systems((receive_profile, profile_action).in_set(OnUpdate(GameState::PetProfile)));
+    }
+}
+
+// Button opening the profile of a pet
+#[derive(Component)]
+pub struct ProfileLink(pub PetId);
+
+// Pet whose profile is shown, and the screen it was opened from
+#[derive(Resource)]
+struct ViewedProfile {
+    pet_id: PetId,
+    back: GameState,
+}
+
+impl Default for ViewedProfile {
+    fn default() -> Self {
+        Self {
+            pet_id: 0,
+            back: GameState::Menu,
+        }
+    }
+}
+
+// Tag component used to tag entities added on the pet profile screen
+#[derive(Component)]
+struct OnPetProfileScreen;
+
+// Column the profile is written in once it's read
+#[derive(Component)]
+struct ProfileColumn;
+
+#[derive(Component)]
+enum ProfileAction {
+    Care,
+    Buy(u128),
+    Watch(AccountId32),
+    Back,
+}
+
+// Result of the background profile read, `None` when nobody owns the pet
+#[derive(Resource)]
+struct ProfileLoad(Mutex<mpsc::Receiver<Result<Option<PetProfile>, String>>>);
+
+// Mirrors of the `PetProfile` returned by the pet runtime api and of its parts
+#[derive(Decode)]
+struct ProfileInfo {
+    name: Vec<u8>,
+    species: PetSpecies,
+}
+
+#[derive(Decode)]
+struct ProfileStats {
+    fullness: u16,
+    energy: u16,
+    level: u32,
+    hungry: bool,
+    tired: bool,
+}
+
+#[derive(Decode)]
+struct Activity {
+    _day: u32,
+    feeds: u32,
+    sleeps: u32,
+    battles_won: u32,
+}
+
+#[derive(Decode)]
+enum HistoryEntry {
+    Minted,
+    Hatched,
+    Renamed,
+    ChangedHands {
+        from: AccountId32,
+        to: AccountId32,
+        price: Option<u128>,
+    },
+    Adopted {
+        adopter: AccountId32,
+    },
+    Battled {
+        opponent: PetId,
+        won: bool,
+    },
+    CosmeticClaimed {
+        cosmetic: u8,
+    },
+}
+
+#[derive(Decode)]
+struct PetProfile {
+    pet_id: PetId,
+    owner: AccountId32,
+    info: ProfileInfo,
+    dna: Option<Dna>,
+    stats: ProfileStats,
+    experience: u64,
+    last_feed: u32,
+    last_sleep: Option<u32>,
+    hatch_at: Option<u32>,
+    listing: Option<u128>,
+    breeder: Option<AccountId32>,
+    cosmetics: Vec<u8>,
+    equipped_cosmetic: Option<u8>,
+    // Items are their index in `ITEMS`
+    equipment: Vec<(u8, u32)>,
+    achievements: Vec<(u8, u32)>,
+    today: Activity,
+    history: Vec<(u32, HistoryEntry)>,
+    moments: CareMoments,
+    co_owners: CoOwners,
+}
+
+// Wall-clock times of the care in milliseconds, at the end of the profile of newer runtimes
+#[derive(Default)]
+struct CareMoments {
+    fed_at: Option<u64>,
+    slept_at: Option<u64>,
+}
+
+// By hand, so profiles without them decode too
+impl Decode for CareMoments {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Ok(Self {
+            fed_at: Option::<u64>::decode(input).unwrap_or_default(),
+            slept_at: Option::<u64>::decode(input).unwrap_or_default(),
+        })
+    }
+}
+
+// Accounts the owner shares the pet with, at the end of the profile of newer runtimes
+struct CoOwners(Vec<AccountId32>);
+
+// By hand, for the same reason as `CareMoments`
+impl Decode for CoOwners {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Ok(Self(Vec::decode(input).unwrap_or_default()))
+    }
+}
+
+fn item_list(items: &[(u8, u32)]) -> String {
+    if items.is_empty() {
+        return "none".to_string();
+    }
+    items
+        .iter()
+        .filter_map(|(item, count)| {
+            let (name, _) = ITEMS.get(*item as usize)?;
+            Some(match count {
+                1 => name.to_string(),
+                _ => format!("{name} x{count}"),
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn cosmetic_name(cosmetic: u8) -> &'static str {
+    COSMETICS
+        .get(cosmetic as usize)
+        .map_or("cosmetic", |(_, name)| *name)
+}
+
+impl HistoryEntry {
+    fn describe(&self) -> String {
+        match self {
+            HistoryEntry::Minted => "minted".to_string(),
+            HistoryEntry::Hatched => "hatched".to_string(),
+            HistoryEntry::Renamed => "renamed".to_string(),
+            HistoryEntry::ChangedHands {
+                to,
+                price: Some(price),
+                ..
+            } => format!("sold to {} for {}", short(to), format_balance(*price)),
+            HistoryEntry::ChangedHands {
+                to, price: None, ..
+            } => {
+                format!("given to {}", short(to))
+            }
+            HistoryEntry::Adopted { adopter } => format!("adopted by {}", short(adopter)),
+            HistoryEntry::Battled {
+                opponent,
+                won: true,
+            } => format!("won a battle against pet #{opponent}"),
+            HistoryEntry::Battled {
+                opponent,
+                won: false,
+            } => format!("lost a battle against pet #{opponent}"),
+            HistoryEntry::CosmeticClaimed { cosmetic } => {
+                format!("got the {}", cosmetic_name(*cosmetic))
+            }
+        }
+    }
+}
+
+impl PetProfile {
+    // Accounts the profile shows, written by their display names once they're read
+    fn accounts(&self) -> Vec<AccountId32> {
+        let mut accounts = vec![self.owner.clone()];
+        accounts.extend(self.breeder.clone());
+        for (_, entry) in &self.history {
+            match entry {
+                HistoryEntry::ChangedHands { from, to, .. } => {
+                    accounts.extend([from.clone(), to.clone()])
+                }
+                HistoryEntry::Adopted { adopter } => accounts.push(adopter.clone()),
+                _ => {}
+            }
+        }
+        accounts.sort();
+        accounts.dedup();
+        accounts
+    }
+
+    // The owners the recent history knows of, the current one last
+    fn lineage(&self) -> String {
+        let mut owners: Vec<String> = Vec::new();
+        for (_, entry) in &self.history {
+            let (from, to) = match entry {
+                HistoryEntry::ChangedHands { from, to, .. } => (Some(from), to),
+                HistoryEntry::Adopted { adopter } => (None, adopter),
+                _ => continue,
+            };
+            if owners.is_empty() {
+                owners.extend(from.map(short));
+            }
+            owners.push(short(to));
+        }
+        if owners.is_empty() {
+            owners.push(short(&self.owner));
+        }
+        let breeder = self.breeder.as_ref().map_or("none".to_string(), short);
+        format!("Bred by {breeder}, owned by {}", owners.join(" → "))
+    }
+
+    fn lines(&self, clock: &ChainClock) -> Vec<String> {
+        let stats = &self.stats;
+        let mood = match (stats.hungry, stats.tired) {
+            (false, false) => "happy",
+            (true, false) => "hungry",
+            (false, true) => "tired",
+            (true, true) => "miserable",
+        };
+        let status = match (self.hatch_at, self.listing) {
+            (Some(block), _) => format!("Still an egg, hatches {}", clock.block(block)),
+            (None, Some(price)) => format!("For sale at {}", format_balance(price)),
+            (None, None) => "Not for sale".to_string(),
+        };
+        let cosmetics = if self.cosmetics.is_empty() {
+            "none".to_string()
+        } else {
+            self.cosmetics
+                .iter()
+                .map(|cosmetic| {
+                    let name = cosmetic_name(*cosmetic);
+                    if self.equipped_cosmetic == Some(*cosmetic) {
+                        format!("{name} (worn)")
+                    } else {
+                        name.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut lines = vec![
+            format!(
+                "{} the {:?} (pet #{})",
+                String::from_utf8_lossy(&self.info.name),
+                self.info.species,
+                self.pet_id
+            ),
+            format!("Owner: {}", self.owner),
+            self.lineage(),
+            status,
+            format!(
+                "Level {} ({} experience), {mood}",
+                stats.level, self.experience
+            ),
+            format!("Last fed {}", clock.at(self.last_feed, self.moments.fed_at)),
+            format!(
+                "Last slept {}",
+                self.last_sleep.map_or("never".to_string(), |block| {
+                    clock.at(block, self.moments.slept_at)
+                })
+            ),
+            format!(
+                "Today: fed {}, slept {}, won {} battles",
+                self.today.feeds, self.today.sleeps, self.today.battles_won
+            ),
+            format!("Equipment: {}", item_list(&self.equipment)),
+            format!("Badges: {}", item_list(&self.achievements)),
+            format!("Cosmetics: {cosmetics}"),
+            "Recent history:".to_string(),
+        ];
+        lines.extend(
+            self.history
+                .iter()
+                .rev()
+                .map(|(block, entry)| format!("  {} {}", clock.block(*block), entry.describe())),
+        );
+        lines
+    }
+
+    // Axes of the radar chart, each from 0 to 1
+    fn radar(&self) -> [(&'static str, f32); 5] {
+        let stats = &self.stats;
+        let full = pet_core::FULL as f32;
+        let size = self
+            .dna
+            .as_ref()
+            .map_or(100, |dna| pet_gene::traits(dna).size);
+        let activity = self.today.feeds + self.today.sleeps + self.today.battles_won;
+        [
+            ("Fullness", stats.fullness as f32 / full),
+            ("Energy", stats.energy as f32 / full),
+            ("Level", stats.level as f32 / RADAR_LEVEL),
+            // Sizes go from 80 to 120 percent
+            ("Size", (size as f32 - 70.0) / 50.0),
+            ("Activity", activity as f32 / 10.0),
+        ]
+        .map(|(label, value)| (label, value.clamp(0.05, 1.0)))
+    }
+}
+
+// A filled polygon with a corner on each axis, at `radius` times the axis value
+fn radar_mesh(values: &[f32]) -> Mesh {
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    for (axis, value) in values.iter().enumerate() {
+        let angle = FRAC_PI_2 + TAU * axis as f32 / values.len() as f32;
+        positions.push([
+            RADAR_RADIUS * value * angle.cos(),
+            RADAR_RADIUS * value * angle.sin(),
+            0.0,
+        ]);
+    }
+    let corners = values.len() as u32;
+    let indices = (0..corners)
+        .flat_map(|corner| [0, corner + 1, (corner + 1) % corners + 1])
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+// Open the profile of the pet of a clicked `ProfileLink`, from any screen
+fn open_profile(
+    links: Query<(&Interaction, &ProfileLink), Changed<Interaction>>,
+    state: Res<State<GameState>>,
+    mut viewed: ResMut<ViewedProfile>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, link) in &links {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        viewed.pet_id = link.0;
+        if state.0 != GameState::PetProfile {
+            viewed.back = state.0;
+        }
+        game_state.set(GameState::PetProfile);
+    }
+}
+
+fn profile_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    viewed: Res<ViewedProfile>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let pet_id = viewed.pet_id;
+    match client.api() {
+        Ok(api) => {
+            client.spawn(async move {
+                let profile = api
+                    .rpc()
+                    .state_call("PetApi_pet_profile", Some(&pet_id.encode()), None)
+                    .await;
+                let _ = sender.send(profile.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(ProfileLoad(Mutex::new(receiver)));
+
+    // The sprite and the chart are drawn in the world on the left half, over this background
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::DARK_GREEN,
+                custom_size: Some(Vec2::splat(4000.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, -10.0),
+            ..default()
+        },
+        OnPetProfileScreen,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    justify_content: JustifyContent::FlexEnd,
+                    ..default()
+                },
+                ..default()
+            },
+            OnPetProfileScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::width(Val::Percent(50.0)),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::Center,
+                            gap: Size::all(Val::Px(6.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ProfileColumn,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Reading pet #{pet_id}..."),
+                        text_style.clone(),
+                    ));
+                });
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    text_style: &TextStyle,
+    bundle: impl Bundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(220.0), Val::Px(40.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..default()
+            },
+            bundle,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+// Show the profile once it's read: the sprite and the chart on the left, the rest on the
+// right with the actions open to the player
+fn receive_profile(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    load: Option<Res<ProfileLoad>>,
+    client: Res<ChainClient>,
+    clock: Res<ChainClock>,
+    palette: Res<Palette>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    column: Query<Entity, With<ProfileColumn>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<ProfileLoad>();
+    let Ok(column) = column.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+
+    let profile = match result {
+        Ok(Some(profile)) => profile,
+        failed => {
+            let message = match failed {
+                Err(e) => format!("Can't read the pet: {e}"),
+                _ => "Nobody owns this pet".to_string(),
+            };
+            commands
+                .entity(column)
+                .despawn_descendants()
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(message, text_style.clone()));
+                    spawn_button(parent, "Back", &text_style, ProfileAction::Back);
+                });
+            return;
+        }
+    };
+
+    let left = -windows.get_single().map_or(800.0, Window::width) / 4.0;
+    let species = format!("{:?}", profile.info.species);
+    let traits = profile.dna.as_ref().map(pet_gene::traits);
+    let size = traits.map_or(100, |traits| traits.size);
+    let mut sprite = assets.pet_sprite(&species);
+    sprite.sprite.color = traits.map_or(Color::WHITE, |traits| {
+        let [r, g, b] = traits.tint;
+        Color::rgb_u8(r, g, b)
+    });
+    sprite.transform =
+        Transform::from_xyz(left, 150.0, 0.0).with_scale(Vec3::splat(0.6 * size as f32 / 100.0));
+    commands.spawn((sprite, OnPetProfileScreen));
+
+    let radar = profile.radar();
+    let chart = Vec3::new(left, -150.0, 0.0);
+    let (stat_color, _, _) = palette.stat_bars();
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(radar_mesh(&[1.0; 5])).into(),
+            material: materials.add(ColorMaterial::from(Color::rgba(1.0, 1.0, 1.0, 0.2))),
+            transform: Transform::from_translation(chart - Vec3::Z),
+            ..default()
+        },
+        OnPetProfileScreen,
+    ));
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(radar_mesh(&radar.map(|(_, value)| value)))
+                .into(),
+            material: materials.add(ColorMaterial::from(stat_color.with_a(0.8))),
+            transform: Transform::from_translation(chart),
+            ..default()
+        },
+        OnPetProfileScreen,
+    ));
+    for (axis, (label, _)) in radar.iter().enumerate() {
+        let angle = FRAC_PI_2 + TAU * axis as f32 / radar.len() as f32;
+        let tip = Vec3::new(angle.cos(), angle.sin(), 0.0) * (RADAR_RADIUS + 25.0);
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(*label, text_style.clone()),
+                transform: Transform::from_translation(chart + tip),
+                ..default()
+            },
+            OnPetProfileScreen,
+        ));
+    }
+
+    let owned = profile.owner == client.account_id();
+    let address_qr = owned
+        .then(|| qr::address_qr(&client.account_id().to_string()))
+        .flatten()
+        .map(|image| images.add(image));
+    let co_owners: Vec<_> = profile
+        .co_owners
+        .0
+        .iter()
+        .map(|co_owner| (co_owner.clone(), images.add(identity::avatar(co_owner))))
+        .collect();
+    commands
+        .entity(column)
+        .despawn_descendants()
+        .with_children(|parent| {
+            let accounts = profile.accounts();
+            for line in profile.lines(&clock) {
+                parent.spawn((
+                    TextBundle::from_section(line, text_style.clone()),
+                    AccountNames(accounts.clone()),
+                ));
+            }
+            if !co_owners.is_empty() {
+                parent.spawn(TextBundle::from_section("Shared with", text_style.clone()));
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (co_owner, avatar) in co_owners {
+                            parent.spawn(ImageBundle {
+                                style: Style {
+                                    size: Size::all(Val::Px(AVATAR_SIZE)),
+                                    ..default()
+                                },
+                                image: avatar.into(),
+                                ..default()
+                            });
+                            parent.spawn((
+                                TextBundle::from_section(short(&co_owner), text_style.clone()),
+                                AccountNames(vec![co_owner]),
+                            ));
+                        }
+                    });
+            }
+            if owned {
+                spawn_button(parent, "Care for it", &text_style, ProfileAction::Care);
+            } else {
+                if let (Some(price), None) = (profile.listing, profile.hatch_at) {
+                    spawn_button(
+                        parent,
+                        &format!("Buy for {}", format_balance(price)),
+                        &text_style,
+                        (ProfileAction::Buy(price), CallHelp::pet("buy")),
+                    );
+                    spawn_button(
+                        parent,
+                        "Message the owner",
+                        &text_style,
+                        ChatLink {
+                            pet_id: profile.pet_id,
+                            with: profile.owner.clone(),
+                        },
+                    );
+                }
+                spawn_button(
+                    parent,
+                    "Watch",
+                    &text_style,
+                    ProfileAction::Watch(profile.owner.clone()),
+                );
+            }
+            if let Some(address_qr) = address_qr {
+                parent.spawn(TextBundle::from_section("Your address", text_style.clone()));
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        size: Size::all(Val::Px(QR_SIZE)),
+                        ..default()
+                    },
+                    image: address_qr.into(),
+                    ..default()
+                });
+            }
+            spawn_button(parent, "Back", &text_style, ProfileAction::Back);
+        });
+}
+
+fn profile_action(
+    interaction_query: Query<(&Interaction, &ProfileAction), Changed<Interaction>>,
+    viewed: Res<ViewedProfile>,
+    mut client: ResMut<ChainClient>,
+    mut queue: ResMut<TxQueue>,
+    mut spectating: ResMut<Spectating>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            ProfileAction::Care => {
+                spectating.0 = None;
+                game_state.set(GameState::Game);
+            }
+            // The price is in the label, the chain charges the listing price
+            ProfileAction::Buy(price) => {
+                queue.push(
+                    format!("Buy pet #{} for {}", viewed.pet_id, format_balance(*price)),
+                    subxt::dynamic::tx("PetModule", "buy", vec![Value::u128(viewed.pet_id.into())]),
+                );
+            }
+            // Watch the pet in spectator mode, no keys are needed to read it
+            ProfileAction::Watch(owner) => match PetSnapshot::fetch(&mut client, owner) {
+                Ok(snapshot) => {
+                    spectating.0 = Some(snapshot);
+                    game_state.set(GameState::Game);
+                }
+                Err(e) => error!("error watching pet #{}: {e}", viewed.pet_id),
+            },
+            ProfileAction::Back => game_state.set(viewed.back),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+
+use super::{
+    client::{
+        format_balance,
+        pet_events::{PetRenamed, StatsChanged, StreakRewarded},
+        polkadot, AccountChanged, ChainClient, ChainEvent, PetId, PetInfo, PetVitals,
+        StorageChanged,
+    },
+    toast::Toast,
+};
+
+// Storage watches for the player's pet entry, and for its care times and egg once the pet
+// is known
+const PET_WATCH: &str = "pet";
+const CARE_WATCH: &str = "pet-care";
+const EGG_WATCH: &str = "pet-egg";
+const STREAK_WATCH: &str = "pet-streak";
+
+// Blocks in a day, with 6 second blocks, the `BlocksPerDay` of the runtime
+const BLOCKS_PER_DAY: u32 = 14_400;
+
+// Storage watch for the care rules, governance can change them at any time
+const RULES_WATCH: &str = "care-rules";
+
+// This plugin keeps `PlayerPet` in sync with the chain, so changes made by someone else
+// (a pet transferred to us, a game key feeding the pet) show up right away. It also follows
+// the `CareRules` stats are computed with.
+pub struct PetWatchPlugin;
+
+impl Plugin for PetWatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerPet>()
+            .init_resource::<CareRules>()
+            .add_startup_systems((watch_player_pet, watch_care_rules))
+            .add_systems((rebind_player_pet, player_pet_changes).chain())
+            .add_systems((
+                rename_fees,
+                streak_rewards,
+                experience_changes,
+                care_rules_changes,
+            ));
+    }
+}
+
+#[derive(Clone)]
+pub struct OwnedPet {
+    pub id: PetId,
+    pub name: String,
+    pub species: String,
+}
+
+// The player's pet as last seen on chain
+#[derive(Resource, Default)]
+pub struct PlayerPet {
+    pub pet: Option<OwnedPet>,
+    pub last_feed_block: Option<u32>,
+    pub last_sleep_block: Option<u32>,
+    // Wall-clock times of the care in milliseconds, when the chain recorded them
+    pub fed_at: Option<u64>,
+    pub slept_at: Option<u64>,
+    // Read when the pet is found, then kept up to date from `StatsChanged` events
+    pub experience: u64,
+    // Block from which the pet can hatch, `None` once it's out of its egg
+    pub hatch_block: Option<u32>,
+    // The latest care streak of the pet, see `care_streak` for the current one
+    pub streak: CareStreak,
+    // Raw storage keys of the watched entries, and whether their first value arrived
+    pet_key: Vec<u8>,
+    care_key: Option<Vec<u8>>,
+    egg_key: Option<Vec<u8>>,
+    streak_key: Option<Vec<u8>>,
+    pet_loaded: bool,
+    care_loaded: bool,
+    egg_loaded: bool,
+}
+
+impl PlayerPet {
+    // Whether the pet of the account, its care and its egg were read since it was bound
+    pub fn loaded(&self) -> bool {
+        self.pet_loaded && (self.pet.is_none() || (self.care_loaded && self.egg_loaded))
+    }
+
+    // What `pet_core` derives the pet's stats from
+    pub fn care(&self) -> pet_core::Care {
+        pet_core::Care {
+            last_feed: self.last_feed_block,
+            last_sleep: self.last_sleep_block,
+            experience: self.experience,
+        }
+    }
+
+    // The days in a row the pet was both fed and put to sleep, as of `block`. Like the
+    // chain, a streak is over once a whole day went by without both.
+    pub fn care_streak(&self, block: u32) -> u32 {
+        if self.streak.last_day + 1 >= block / BLOCKS_PER_DAY {
+            self.streak.days
+        } else {
+            0
+        }
+    }
+}
+
+// `CareStreak` of the pet pallet
+#[derive(Decode, Clone, Copy, Default)]
+pub struct CareStreak {
+    pub days: u32,
+    pub last_day: u32,
+}
+
+// How fast stats drop and when pets mind it, as set on chain. Stats drawn with other rules
+// would drift from the ones the chain computes.
+#[derive(Resource, Default)]
+pub struct CareRules {
+    pub rules: pet_core::Rules,
+    // `DefaultCareRules` of the runtime, in force until governance sets others
+    default: pet_core::Rules,
+    key: Vec<u8>,
+}
+
+// `CareParameters` of the pet pallet
+#[derive(Decode)]
+struct CareParameters {
+    fullness_lasts: u32,
+    energy_lasts: u32,
+    hungry_below: u16,
+    tired_below: u16,
+}
+
+impl From<CareParameters> for pet_core::Rules {
+    fn from(rules: CareParameters) -> Self {
+        pet_core::Rules {
+            fullness_lasts: rules.fullness_lasts,
+            energy_lasts: rules.energy_lasts,
+            hungry_below: rules.hungry_below,
+            tired_below: rules.tired_below,
+        }
+    }
+}
+
+fn watch_player_pet(mut client: ResMut<ChainClient>, mut player_pet: ResMut<PlayerPet>) {
+    let account = client.account_id();
+    let watched = client
+        .storage_key(&polkadot::storage().pet_module().pets_info(&account))
+        .and_then(|key| {
+            client.watch_storage(PET_WATCH, vec![key.clone()])?;
+            Ok(key)
+        });
+    match watched {
+        Ok(key) => player_pet.pet_key = key,
+        Err(e) => warn!("not watching the player's pet: {e:?}"),
+    }
+}
+
+// Start from the runtime's default rules, then follow the ones governance sets
+fn watch_care_rules(mut client: ResMut<ChainClient>, mut care_rules: ResMut<CareRules>) {
+    let default = client.api().and_then(|api| {
+        let value = api
+            .constants()
+            .at(&subxt::dynamic::constant("PetModule", "DefaultCareRules"))?;
+        Ok::<_, subxt::Error>(CareParameters::decode(&mut value.encoded())?)
+    });
+    match default {
+        Ok(default) => {
+            care_rules.default = default.into();
+            care_rules.rules = care_rules.default;
+        }
+        Err(e) => warn!("using the built-in care rules: {e:?}"),
+    }
+    let watched = client
+        .storage_key(&subxt::dynamic::storage(
+            "PetModule",
+            "CareRules",
+            Vec::<Value>::new(),
+        ))
+        .and_then(|key| {
+            client.watch_storage(RULES_WATCH, vec![key.clone()])?;
+            Ok(key)
+        });
+    match watched {
+        Ok(key) => care_rules.key = key,
+        Err(e) => warn!("not watching the care rules: {e:?}"),
+    }
+}
+
+// Forget the pet of the previous account and watch the one of the new account
+pub fn rebind_player_pet(
+    mut events: EventReader<AccountChanged>,
+    mut client: ResMut<ChainClient>,
+    mut player_pet: ResMut<PlayerPet>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    client.unwatch_storage(CARE_WATCH);
+    client.unwatch_storage(EGG_WATCH);
+    client.unwatch_storage(STREAK_WATCH);
+    *player_pet = PlayerPet::default();
+    watch_player_pet(client, player_pet);
+}
+
+// Follow care times of the pet with the given id, the pallet keeps them in its `PetVitals`
+fn watch_care(client: &mut ChainClient, player_pet: &mut PlayerPet, pet_id: Option<PetId>) {
+    player_pet.care_key = None;
+    player_pet.care_loaded = false;
+    let Some(pet_id) = pet_id else {
+        client.unwatch_storage(CARE_WATCH);
+        return;
+    };
+    let key = client
+        .storage_key(&PetVitals::address(pet_id))
+        .and_then(|key| {
+            client.watch_storage(CARE_WATCH, vec![key.clone()])?;
+            Ok(key)
+        });
+    match key {
+        Ok(key) => player_pet.care_key = Some(key),
+        Err(e) => warn!("not watching the care of pet {pet_id}: {e:?}"),
+    }
+}
+
+// Follow the egg of the pet with the given id, the pallet keeps it in `Eggs` until it hatches
+fn watch_egg(client: &mut ChainClient, player_pet: &mut PlayerPet, pet_id: Option<PetId>) {
+    player_pet.egg_key = None;
+    player_pet.hatch_block = None;
+    player_pet.egg_loaded = false;
+    let Some(pet_id) = pet_id else {
+        client.unwatch_storage(EGG_WATCH);
+        return;
+    };
+    let key = client
+        .storage_key(&subxt::dynamic::storage(
+            "PetModule",
+            "Eggs",
+            vec![Value::u128(pet_id.into())],
+        ))
+        .and_then(|key| {
+            client.watch_storage(EGG_WATCH, vec![key.clone()])?;
+            Ok(key)
+        });
+    match key {
+        Ok(key) => player_pet.egg_key = Some(key),
+        Err(e) => warn!("not watching the egg of pet {pet_id}: {e:?}"),
+    }
+}
+
+// Follow the care streak of the pet with the given id, kept in `CareStreaks`
+fn watch_streak(client: &mut ChainClient, player_pet: &mut PlayerPet, pet_id: Option<PetId>) {
+    player_pet.streak_key = None;
+    player_pet.streak = CareStreak::default();
+    let Some(pet_id) = pet_id else {
+        client.unwatch_storage(STREAK_WATCH);
+        return;
+    };
+    let key = client
+        .storage_key(&subxt::dynamic::storage(
+            "PetModule",
+            "CareStreaks",
+            vec![Value::u128(pet_id.into())],
+        ))
+        .and_then(|key| {
+            client.watch_storage(STREAK_WATCH, vec![key.clone()])?;
+            Ok(key)
+        });
+    match key {
+        Ok(key) => player_pet.streak_key = Some(key),
+        Err(e) => warn!("not watching the care streak of pet {pet_id}: {e:?}"),
+    }
+}
+
+// Experience isn't in the bundled metadata yet, so it's read dynamically
+fn fetch_experience(client: &mut ChainClient, pet_id: PetId) -> Result<u64, subxt::Error> {
+    let api = client.api()?;
+    client.block_on(async {
+        let experience = api
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&subxt::dynamic::storage(
+                "PetModule",
+                "Experience",
+                vec![Value::u128(pet_id.into())],
+            ))
+            .await?
+            .map(|value| u64::decode(&mut value.encoded()))
+            .transpose()?;
+        Ok(experience.unwrap_or_default())
+    })
+}
+
+fn decode_block(value: &Option<Vec<u8>>) -> Option<u32> {
+    value
+        .as_ref()
+        .and_then(|value| u32::decode(&mut &value[..]).ok())
+}
+
+// Apply the watched changes, with a toast for the ones that happen while playing
+fn player_pet_changes(
+    mut events: EventReader<StorageChanged>,
+    mut client: ResMut<ChainClient>,
+    mut player_pet: ResMut<PlayerPet>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for change in events.iter() {
+        if change.watch == PET_WATCH && change.key == player_pet.pet_key {
+            let pet = change
+                .value
+                .as_ref()
+                .and_then(|value| <(PetId, PetInfo)>::decode(&mut &value[..]).ok())
+                .map(|(id, info)| OwnedPet {
+                    id,
+                    name: String::from_utf8_lossy(&info.name.0).into_owned(),
+                    species: format!("{:?}", info.species),
+                });
+            if player_pet.pet_loaded {
+                match (&player_pet.pet, &pet) {
+                    (None, Some(pet)) => toasts.send(Toast(format!(
+                        "{} the {} is now yours!",
+                        pet.name, pet.species
+                    ))),
+                    (Some(pet), None) => {
+                        toasts.send(Toast(format!("{} left your account", pet.name)))
+                    }
+                    _ => {}
+                }
+            }
+            let new_id = pet.as_ref().map(|pet| pet.id);
+            let id_changed =
+                !player_pet.pet_loaded || player_pet.pet.as_ref().map(|pet| pet.id) != new_id;
+            player_pet.pet = pet;
+            player_pet.pet_loaded = true;
+            if id_changed {
+                watch_care(&mut client, &mut player_pet, new_id);
+                watch_egg(&mut client, &mut player_pet, new_id);
+                watch_streak(&mut client, &mut player_pet, new_id);
+                player_pet.experience = match new_id.map(|id| fetch_experience(&mut client, id)) {
+                    Some(Ok(experience)) => experience,
+                    Some(Err(e)) => {
+                        error!("error reading the experience of the player's pet: {e:?}");
+                        0
+                    }
+                    None => 0,
+                };
+            }
+        } else if change.watch == CARE_WATCH && Some(&change.key) == player_pet.care_key.as_ref() {
+            let vitals = PetVitals::from_raw(&change.value);
+            let last_feed_block = vitals.map(|vitals| vitals.last_fed);
+            let last_sleep_block = vitals.and_then(|vitals| vitals.last_slept);
+            let name = player_pet
+                .pet
+                .as_ref()
+                .map_or("Your pet".to_string(), |pet| pet.name.clone());
+            // Both times are in the entry, only toast the one that moved
+            if player_pet.care_loaded {
+                if last_feed_block.is_some() && last_feed_block != player_pet.last_feed_block {
+                    toasts.send(Toast(format!("{name} was fed")));
+                }
+                if last_sleep_block.is_some() && last_sleep_block != player_pet.last_sleep_block {
+                    toasts.send(Toast(format!("{name} went to sleep")));
+                }
+            }
+            player_pet.last_feed_block = last_feed_block;
+            player_pet.last_sleep_block = last_sleep_block;
+            player_pet.fed_at = vitals.and_then(|vitals| vitals.fed_at);
+            player_pet.slept_at = vitals.and_then(|vitals| vitals.slept_at);
+            player_pet.care_loaded = true;
+        } else if change.watch == EGG_WATCH && Some(&change.key) == player_pet.egg_key.as_ref() {
+            let hatch_block = decode_block(&change.value);
+            if player_pet.hatch_block.is_some() && hatch_block.is_none() {
+                let name = player_pet
+                    .pet
+                    .as_ref()
+                    .map_or("Your pet".to_string(), |pet| pet.name.clone());
+                toasts.send(Toast(format!("{name} hatched!")));
+            }
+            player_pet.hatch_block = hatch_block;
+            player_pet.egg_loaded = true;
+        } else if change.watch == STREAK_WATCH
+            && Some(&change.key) == player_pet.streak_key.as_ref()
+        {
+            player_pet.streak = change
+                .value
+                .as_ref()
+                .and_then(|value| CareStreak::decode(&mut &value[..]).ok())
+                .unwrap_or_default();
+        }
+    }
+}
+
+fn care_rules_changes(mut events: EventReader<StorageChanged>, mut care_rules: ResMut<CareRules>) {
+    for change in events.iter() {
+        if change.watch != RULES_WATCH || change.key != care_rules.key {
+            continue;
+        }
+        // Nothing stored means the default rules
+        care_rules.rules = match &change.value {
+            Some(value) => match CareParameters::decode(&mut &value[..]) {
+                Ok(rules) => rules.into(),
+                Err(e) => {
+                    error!("error decoding the care rules: {e:?}");
+                    continue;
+                }
+            },
+            None => care_rules.default,
+        };
+    }
+}
+
+// Battles and scores don't touch watched storage, their events carry the new experience
+fn experience_changes(mut events: EventReader<ChainEvent>, mut player_pet: ResMut<PlayerPet>) {
+    let pet_id = player_pet.pet.as_ref().map(|pet| pet.id);
+    for ChainEvent(event) in events.iter() {
+        match event.as_event::<StatsChanged>() {
+            Ok(Some(stats)) if Some(stats.pet_id) == pet_id => {
+                player_pet.experience = stats.experience
+            }
+            Ok(_) => {}
+            Err(e) => error!("error decoding stats event: {e:?}"),
+        }
+    }
+}
+
+// Celebrate the milestones of the care streak of the player's pet, the chain hands out the
+// reward on its own
+fn streak_rewards(
+    mut events: EventReader<ChainEvent>,
+    client: Res<ChainClient>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let account = client.account_id();
+    for ChainEvent(event) in events.iter() {
+        match event.as_event::<StreakRewarded>() {
+            Ok(Some(rewarded)) if rewarded.owner == account => {
+                toasts.send(Toast(format!(
+                    "{} days of care in a row! {} reward items are in your inventory",
+                    rewarded.days, rewarded.amount
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => error!("error decoding streak reward event: {e:?}"),
+        }
+    }
+}
+
+// Tell what the player's renames cost, only the first one of a pet is free
+fn rename_fees(
+    mut events: EventReader<ChainEvent>,
+    client: Res<ChainClient>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let account = client.account_id();
+    for ChainEvent(event) in events.iter() {
+        match event.as_event::<PetRenamed>() {
+            Ok(Some(renamed)) if renamed.owner == account => {
+                let name = String::from_utf8_lossy(&renamed.name.0);
+                toasts.send(Toast(if renamed.fee == 0 {
+                    format!("Renamed to {name}, the first rename is free")
+                } else {
+                    format!("Renamed to {name} for {}", format_balance(renamed.fee))
+                }));
+            }
+            Ok(_) => {}
+            Err(e) => error!("error decoding rename event: {e:?}"),
+        }
+    }
+}
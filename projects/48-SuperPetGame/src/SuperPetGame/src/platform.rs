@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy::window::CompositeAlphaMode;
+
+// What the desktop the game runs on lets its window do. Transparency and click-through work
+// differently on each desktop, so each has its own module below and the rest of the game only
+// asks this.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Desktop {
+    pub name: &'static str,
+    // How the window's alpha is composited with the desktop, `None` when the window can't be
+    // transparent and is drawn opaque instead
+    pub alpha_mode: Option<CompositeAlphaMode>,
+    // Whether clicks can go through the window to what's under it, used by the widget to only
+    // catch the clicks on the pet
+    pub click_through: bool,
+}
+
+impl Desktop {
+    // The desktop of this build and session. `--opaque` turns transparency off, for desktops
+    // that say they support it but draw black instead, like X11 without a compositor.
+    pub fn detect() -> Self {
+        let desktop = imp::desktop();
+        if std::env::args().any(|arg| arg == "--opaque") {
+            return Self {
+                alpha_mode: None,
+                ..desktop
+            };
+        }
+        desktop
+    }
+
+    pub fn transparent(&self) -> bool {
+        self.alpha_mode.is_some()
+    }
+
+    // Set the window up for this desktop, transparent when it can be
+    pub fn configure(&self, window: &mut Window) {
+        window.transparent = self.transparent();
+        window.composite_alpha_mode = self.alpha_mode.unwrap_or(CompositeAlphaMode::Auto);
+    }
+
+    // Color behind everything, see-through when the window is transparent
+    pub fn clear_color(&self) -> ClearColor {
+        if self.transparent() {
+            ClearColor(Color::NONE)
+        } else {
+            ClearColor(Color::DARK_GREEN)
+        }
+    }
+}
+
+// macOS only draws a transparent window with post-multiplied alpha, and winit lets clicks
+// through by ignoring mouse events on the NSWindow
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::*;
+
+    pub fn desktop() -> Desktop {
+        Desktop {
+            name: "macOS",
+            alpha_mode: Some(CompositeAlphaMode::PostMultiplied),
+            click_through: true,
+        }
+    }
+}
+
+// Windows composites the window through DWM with the alpha the surface picks, and winit lets
+// clicks through by making it a layered, transparent window (`WS_EX_LAYERED` and
+// `WS_EX_TRANSPARENT`)
+#[cfg(target_os = "windows")]
+mod imp {
+    use super::*;
+
+    pub fn desktop() -> Desktop {
+        Desktop {
+            name: "Windows",
+            alpha_mode: Some(CompositeAlphaMode::Auto),
+            click_through: true,
+        }
+    }
+}
+
+// Wayland compositors take pre-multiplied alpha. On X11 transparency needs a compositing
+// window manager, which can't be told from here: the surface picks the alpha it supports and
+// `--opaque` is the fallback when it draws black. Without a display server the window is
+// opaque.
+#[cfg(all(unix, not(target_os = "macos")))]
+mod imp {
+    use super::*;
+
+    pub fn desktop() -> Desktop {
+        let var = |name| std::env::var_os(name).map_or(false, |value| !value.is_empty());
+        if var("WAYLAND_DISPLAY") {
+            Desktop {
+                name: "Wayland",
+                alpha_mode: Some(CompositeAlphaMode::PreMultiplied),
+                click_through: true,
+            }
+        } else if var("DISPLAY") {
+            Desktop {
+                name: "X11",
+                alpha_mode: Some(CompositeAlphaMode::Auto),
+                click_through: true,
+            }
+        } else {
+            Desktop {
+                name: "unknown Unix desktop",
+                alpha_mode: None,
+                click_through: false,
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+mod imp {
+    use super::*;
+
+    pub fn desktop() -> Desktop {
+        Desktop {
+            name: "unknown desktop",
+            alpha_mode: None,
+            click_through: false,
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+// Chain queries run in a frame. Each one blocks the frame until the node answers, so they
+// are spread over the next frames when more are due at once
+const QUERIES_PER_FRAME: usize = 1;
+
+// This plugin spaces out the chain reads systems repeat while playing. A system asks the
+// `PollScheduler` before each read, and only reads when it was given a slot: at most
+// `QUERIES_PER_FRAME` a frame, the most urgent first, and never more often than the system
+// asked for. Opening a screen that reads a lot then costs a few frames instead of one long
+// stall.
+pub struct PollPlugin;
+
+impl Plugin for PollPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PollScheduler>()
+            .add_system(grant_queries.in_base_set(CoreSet::First));
+    }
+}
+
+// Which reads go first when several are due in the same frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // Nothing on screen waits for it
+    Background,
+    // Refreshes a panel already showing
+    Normal,
+    // Drives what's on screen every frame
+    High,
+}
+
+struct Job {
+    priority: Priority,
+    every: f32,
+    // When it last read, in seconds since startup
+    last: Option<f32>,
+    // Whether it asked for a slot since the last grants
+    asked: bool,
+}
+
+impl Job {
+    // Seconds it's been waiting for a slot, reads never done wait forever
+    fn waiting(&self, now: f32) -> f32 {
+        self.last
+            .map_or(f32::INFINITY, |last| now - last - self.every)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PollScheduler {
+    // Seconds since startup, at the start of the frame
+    now: f32,
+    jobs: HashMap<&'static str, Job>,
+    // Jobs allowed to read this frame
+    granted: Vec<&'static str>,
+}
+
+impl PollScheduler {
+    // Whether the job `name` can read the chain now. Systems ask every frame they'd like to
+    // read, and read when this is true: the first time right away, then at most once every
+    // `every` seconds, in a frame with a slot left.
+    pub fn ready(&mut self, name: &'static str, priority: Priority, every: f32) -> bool {
+        let now = self.now;
+        let job = self.jobs.entry(name).or_insert(Job {
+            priority,
+            every,
+            last: None,
+            asked: false,
+        });
+        job.priority = priority;
+        job.every = every;
+        if self.granted.contains(&name) && job.waiting(now) >= 0.0 {
+            job.last = Some(now);
+            return true;
+        }
+        job.asked = true;
+        false
+    }
+
+    // Drop what's known about the job `name`, so it reads right away the next time it asks,
+    // e.g. when its screen opens again
+    pub fn forget(&mut self, name: &'static str) {
+        self.jobs.remove(name);
+    }
+}
+
+// Hand this frame's slots to the due jobs that asked, by priority then by how long they
+// waited
+fn grant_queries(time: Res<Time>, mut scheduler: ResMut<PollScheduler>) {
+    let scheduler = &mut *scheduler;
+    scheduler.now = time.elapsed_seconds();
+    let now = scheduler.now;
+    let mut due: Vec<_> = scheduler
+        .jobs
+        .iter_mut()
+        .filter_map(|(name, job)| {
+            let asked = std::mem::take(&mut job.asked);
+            let waiting = job.waiting(now);
+            (asked && waiting >= 0.0).then_some((*name, job.priority, waiting))
+        })
+        .collect();
+    due.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.total_cmp(&a.2)));
+    scheduler.granted = due
+        .into_iter()
+        .take(QUERIES_PER_FRAME)
+        .map(|(name, _, _)| name)
+        .collect();
+}
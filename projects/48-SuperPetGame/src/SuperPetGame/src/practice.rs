@@ -0,0 +1,425 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use pet_core::Mood;
+
+use super::{
+    despawn_screen,
+    pet_watch::{CareRules, PlayerPet},
+    settings::Palette,
+    GameState, TEXT_COLOR,
+};
+
+// Highest level a practice pet can be set to
+const MAX_LEVEL: u32 = 50;
+
+const MOODS: [(Mood, &str); 4] = [
+    (Mood::Happy, "Happy"),
+    (Mood::Hungry, "Hungry"),
+    (Mood::Tired, "Tired"),
+    (Mood::Miserable, "Miserable"),
+];
+
+// A sparring partner, always in the same shape
+struct Bot {
+    name: &'static str,
+    level: u32,
+    mood: Mood,
+}
+
+const BOTS: [Bot; 4] = [
+    Bot {
+        name: "Straw Dummy",
+        level: 1,
+        mood: Mood::Happy,
+    },
+    Bot {
+        name: "Sleepy Tortoise",
+        level: 8,
+        mood: Mood::Tired,
+    },
+    Bot {
+        name: "Gym Coach",
+        level: 10,
+        mood: Mood::Happy,
+    },
+    Bot {
+        name: "Champion",
+        level: 25,
+        mood: Mood::Happy,
+    },
+];
+
+// This plugin simulates battles against bots on this device, with the `pet_core` odds and
+// roll the chain resolves challenges with. The player picks the level and mood of their pet to
+// see how they change the odds. Nothing is sent to the chain: no fees, no experience, no record.
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            practice_setup.in_schedule(OnEnter(GameState::Practice)),
+            despawn_screen::<OnPracticeScreen>.in_schedule(OnExit(GameState::Practice)),
+        ))
+        .add_systems(
+            (
+                practice_action,
+                show_practice.after(practice_action),
+                color_choices.after(practice_action),
+            )
+                .in_set(OnUpdate(GameState::Practice)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the practice screen
+#[derive(Component)]
+struct OnPracticeScreen;
+
+#[derive(Component)]
+struct LevelText;
+
+// Text of the spar button of the bot, with the odds of winning against it
+#[derive(Component)]
+struct BotText(usize);
+
+#[derive(Component)]
+struct SparResultText;
+
+#[derive(Component)]
+enum PracticeAction {
+    Level(i32),
+    Mood(Mood),
+    Spar(usize),
+    Back,
+}
+
+// Xorshift, random enough for practice rolls
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |now| now.as_nanos() as u64);
+        Self(seed | 1)
+    }
+
+    fn roll(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+struct Spar {
+    bot: usize,
+    won: bool,
+    chance: f32,
+}
+
+// The pet the player practices with and the spars fought since the screen opened
+#[derive(Resource)]
+struct Practice {
+    name: String,
+    level: u32,
+    mood: Mood,
+    last: Option<Spar>,
+    won: u32,
+    lost: u32,
+    rng: Rng,
+}
+
+// The battle weight of a pet of `level` feeling `mood`, as `pet_core` computes it on chain
+fn battle_weight(rules: &pet_core::Rules, level: u32, mood: Mood) -> u64 {
+    // Full stats at the start of block 0, emptied to match the mood
+    let care = pet_core::Care {
+        last_feed: (!mood.is_hungry()).then_some(0),
+        last_sleep: (!mood.is_tired()).then_some(0),
+        experience: pet_core::experience_for(level),
+    };
+    care.battle_weight(rules, 0)
+}
+
+impl Practice {
+    // Weights of the player's pet and of the bot
+    fn weights(&self, rules: &pet_core::Rules, bot: &Bot) -> (u64, u64) {
+        (
+            battle_weight(rules, self.level, self.mood),
+            battle_weight(rules, bot.level, bot.mood),
+        )
+    }
+
+    fn win_chance(&self, rules: &pet_core::Rules, bot: &Bot) -> f32 {
+        let (weight, bot_weight) = self.weights(rules, bot);
+        weight as f32 / (weight + bot_weight) as f32
+    }
+}
+
+fn mood_name(mood: Mood) -> &'static str {
+    MOODS
+        .iter()
+        .find(|(choice, _)| *choice == mood)
+        .map_or("", |(_, name)| name)
+}
+
+// Start from the level of the player's pet, happy
+fn practice_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_pet: Res<PlayerPet>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let small_style = TextStyle {
+        font_size: 18.0,
+        ..text_style.clone()
+    };
+    let choice_style = Style {
+        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+        ..default()
+    };
+
+    commands.insert_resource(Practice {
+        name: player_pet
+            .pet
+            .as_ref()
+            .map_or_else(|| "Your pet".to_string(), |pet| pet.name.clone()),
+        level: pet_core::level(player_pet.experience).min(MAX_LEVEL),
+        mood: Mood::Happy,
+        last: None,
+        won: 0,
+        lost: 0,
+        rng: Rng::new(),
+    });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::MAROON.into(),
+                ..default()
+            },
+            OnPracticeScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Practice battles",
+                text_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Simulated on this device: nothing is sent to the chain, no fees are paid and no XP is won",
+                TextStyle {
+                    color: Color::GOLD,
+                    ..small_style.clone()
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, step) in [("-", -1), ("+", 1)] {
+                        if step > 0 {
+                            parent.spawn((
+                                TextBundle::from_section(String::new(), text_style.clone()),
+                                LevelText,
+                            ));
+                        }
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: choice_style.clone(),
+                                    ..default()
+                                },
+                                PracticeAction::Level(step),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (mood, name) in MOODS {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: choice_style.clone(),
+                                    ..default()
+                                },
+                                PracticeAction::Mood(mood),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(name, small_style.clone()));
+                            });
+                    }
+                });
+            for index in 0..BOTS.len() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                size: Size::width(Val::Px(560.0)),
+                                padding: UiRect::all(Val::Px(8.0)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        PracticeAction::Spar(index),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(String::new(), small_style.clone()),
+                            BotText(index),
+                        ));
+                    });
+            }
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                SparResultText,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    PracticeAction::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style.clone()));
+                });
+        });
+}
+
+// Fight a bot with the same roll the chain uses for challenges
+fn practice_action(
+    interaction_query: Query<(&Interaction, &PracticeAction), Changed<Interaction>>,
+    practice: Option<ResMut<Practice>>,
+    care_rules: Res<CareRules>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut practice) = practice else {
+        return;
+    };
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            PracticeAction::Level(step) => {
+                practice.level = practice
+                    .level
+                    .saturating_add_signed(*step)
+                    .clamp(1, MAX_LEVEL);
+            }
+            PracticeAction::Mood(mood) => practice.mood = *mood,
+            PracticeAction::Spar(index) => {
+                let bot = &BOTS[*index];
+                let (weight, bot_weight) = practice.weights(&care_rules.rules, bot);
+                let roll = practice.rng.roll();
+                let won = pet_core::wins_battle(roll, weight, bot_weight);
+                if won {
+                    practice.won += 1;
+                } else {
+                    practice.lost += 1;
+                }
+                practice.last = Some(Spar {
+                    bot: *index,
+                    won,
+                    chance: practice.win_chance(&care_rules.rules, bot),
+                });
+            }
+            PracticeAction::Back => game_state.set(GameState::Battle),
+        }
+    }
+}
+
+fn show_practice(
+    practice: Option<Res<Practice>>,
+    care_rules: Res<CareRules>,
+    mut level_text: Query<&mut Text, With<LevelText>>,
+    mut bot_texts: Query<(&mut Text, &BotText), Without<LevelText>>,
+    mut result_text: Query<&mut Text, (With<SparResultText>, Without<LevelText>, Without<BotText>)>,
+) {
+    let Some(practice) = practice else {
+        return;
+    };
+    if !practice.is_changed() && !care_rules.is_changed() {
+        return;
+    }
+    for mut text in &mut level_text {
+        text.sections[0].value = format!("{}, level {}", practice.name, practice.level);
+    }
+    for (mut text, BotText(index)) in &mut bot_texts {
+        let bot = &BOTS[*index];
+        text.sections[0].value = format!(
+            "Spar with {} (level {}, {}): {:.0}% to win",
+            bot.name,
+            bot.level,
+            mood_name(bot.mood),
+            practice.win_chance(&care_rules.rules, bot) * 100.0
+        );
+    }
+    for mut text in &mut result_text {
+        text.sections[0].value = match &practice.last {
+            Some(spar) => format!(
+                "{} against {} with {:.0}% odds. Practice record: {} won, {} lost",
+                if spar.won { "Won" } else { "Lost" },
+                BOTS[spar.bot].name,
+                spar.chance * 100.0,
+                practice.won,
+                practice.lost
+            ),
+            None => "Pick a sparring partner".to_string(),
+        };
+    }
+}
+
+// Highlight the mood the pet practices in
+fn color_choices(
+    practice: Option<Res<Practice>>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &PracticeAction, &mut BackgroundColor)>,
+) {
+    let Some(practice) = practice else {
+        return;
+    };
+    for (interaction, action, mut color) in &mut buttons {
+        if !practice.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = matches!(action, PracticeAction::Mood(mood) if *mood == practice.mood);
+        *color = palette.button(*interaction, selected).into();
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Profiles other than the default one, a directory each
+const PROFILES_DIR: &str = "profiles";
+
+// The profile used last, started again when none is chosen
+const LAST_PROFILE_FILE: &str = "profiles/last.json";
+
+// The profile of the files next to the game, the only one before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+
+// Longest profile name
+const MAX_NAME_LEN: usize = 32;
+
+// This plugin keeps one profile per player sharing the computer, so each cares for their own
+// pet with their own keys. Accounts, settings and caches are all files in the working
+// directory, which `select` moves to the profile's directory before anything reads them. A
+// profile is chosen at launch with `--profile <name>` or `SUPERPET_PROFILE`, and created the
+// first time. Choosing another one on the settings screen starts the game again with it.
+pub struct ProfilePlugin(pub Profiles);
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .add_system(switch_profile);
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct Profiles {
+    pub current: String,
+    // Every profile, the current one included, by name
+    pub all: Vec<String>,
+    // Directory the game was started in, where the profiles are
+    root: PathBuf,
+    // Problems that didn't stop the launch, logged once logging is up
+    pub warnings: Vec<String>,
+}
+
+impl Profiles {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+// Button starting the game again with a profile
+#[derive(Component)]
+pub struct ProfileButton(pub String);
+
+#[derive(Serialize, Deserialize)]
+struct LastProfile {
+    name: String,
+}
+
+fn valid_name(name: &str) -> bool {
+    (1..=MAX_NAME_LEN).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// The profile chosen at launch, else the last one used
+fn chosen() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--profile")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| std::env::var("SUPERPET_PROFILE").ok())
+        .or_else(|| {
+            let json = std::fs::read(LAST_PROFILE_FILE).ok()?;
+            serde_json::from_slice::<LastProfile>(&json)
+                .ok()
+                .map(|last| last.name)
+        })
+}
+
+// The profiles in the profiles directory, the default one and `current`, which may not have
+// a directory yet
+fn list(current: &str) -> Vec<String> {
+    let mut all: Vec<String> = std::fs::read_dir(PROFILES_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().map_or(false, |kind| kind.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| valid_name(name))
+        .collect();
+    all.push(DEFAULT_PROFILE.to_string());
+    all.push(current.to_string());
+    all.sort();
+    all.dedup();
+    all
+}
+
+// Move to the directory of the chosen profile, creating it the first time. Call it first
+// thing, the log is kept per profile too. Nothing logs yet, so errors and warnings are
+// returned to be logged once it does.
+pub fn select() -> Result<Profiles, String> {
+    let name = chosen().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    if !valid_name(&name) {
+        return Err(format!(
+            "invalid profile name {name}, use up to {MAX_NAME_LEN} letters, digits, - or _"
+        ));
+    }
+    let root =
+        std::env::current_dir().map_err(|e| format!("can't read the game directory: {e}"))?;
+    let all = list(&name);
+
+    let last = serde_json::to_vec_pretty(&LastProfile { name: name.clone() })
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            std::fs::create_dir_all(PROFILES_DIR)
+                .and_then(|()| std::fs::write(LAST_PROFILE_FILE, json))
+                .map_err(|e| e.to_string())
+        });
+    let mut warnings = Vec::new();
+    if let Err(e) = last {
+        warnings.push(format!("can't save {LAST_PROFILE_FILE}: {e}"));
+    }
+
+    if name != DEFAULT_PROFILE {
+        let dir = Path::new(PROFILES_DIR).join(&name);
+        std::fs::create_dir_all(&dir)
+            .and_then(|()| std::env::set_current_dir(&dir))
+            .map_err(|e| format!("can't open the profile {name}: {e}"))?;
+    }
+    Ok(Profiles {
+        current: name,
+        all,
+        root,
+        warnings,
+    })
+}
+
+// The arguments the game was started with, like `--widget`, without the `options` and their
+// values, to start it again with other ones
+pub fn restart_args(options: &[&str]) -> Vec<String> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    for option in options {
+        if let Some(i) = args.iter().position(|arg| arg == *option) {
+            args.drain(i..(i + 2).min(args.len()));
+        }
+    }
+    args
+}
+
+// Start the game again with the profile of the clicked button
+fn switch_profile(
+    profiles: Res<Profiles>,
+    buttons: Query<(&Interaction, &ProfileButton), Changed<Interaction>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked || button.0 == profiles.current {
+            continue;
+        }
+        // The other profile has its own chains, and starts on the one it used last
+        let args = restart_args(&["--profile", "--chain"]);
+        let started = std::env::current_exe().and_then(|exe| {
+            Command::new(exe)
+                .args(args)
+                .arg("--profile")
+                .arg(&button.0)
+                .current_dir(&profiles.root)
+                .spawn()
+        });
+        match started {
+            Ok(_) => {
+                info!("switching to the profile {}", button.0);
+                exit.send(AppExit);
+            }
+            Err(e) => error!("can't start the game with the profile {}: {e}", button.0),
+        }
+    }
+}
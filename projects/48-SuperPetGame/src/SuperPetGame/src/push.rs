@@ -0,0 +1,249 @@
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use subxt::utils::AccountId32;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::Message;
+
+use super::client::{pet_events::BattleLog, PetId};
+use super::indexer::{IndexDb, IndexedEvent};
+
+// Path the events are pushed on
+const EVENTS_PATH: &str = "/events";
+
+// Pet events as the indexer pushes them over websocket, one text message each. Numbers that
+// don't fit in a JavaScript number are strings.
+#[derive(Serialize, Deserialize)]
+pub struct PushedEvent {
+    pub block: u32,
+    pub idx: u32,
+    pub name: String,
+    pub account: Option<AccountId32>,
+    pub other: Option<AccountId32>,
+    pub pet_id: Option<PetId>,
+    pub amount: Option<String>,
+    pub battle: Option<PushedBattle>,
+    pub text: Option<String>,
+}
+
+// Loser and log of a battle, the winner is the event's pet
+#[derive(Serialize, Deserialize)]
+pub struct PushedBattle {
+    pub loser: PetId,
+    pub seed: String,
+    pub challenger_weight: String,
+    pub opponent_weight: String,
+}
+
+impl PushedEvent {
+    fn new(block: u32, idx: u32, event: &IndexedEvent) -> Self {
+        Self {
+            block,
+            idx,
+            name: event.name.clone(),
+            account: event.account.clone(),
+            other: event.other.clone(),
+            pet_id: event.pet_id,
+            amount: event.amount.map(|amount| amount.to_string()),
+            battle: event.battle.as_ref().map(|(loser, log)| PushedBattle {
+                loser: *loser,
+                seed: log.seed.to_string(),
+                challenger_weight: log.challenger_weight.to_string(),
+                opponent_weight: log.opponent_weight.to_string(),
+            }),
+            text: event.text.clone(),
+        }
+    }
+
+    // The event to store, `None` when a number doesn't parse
+    fn into_indexed(self) -> Option<(u32, u32, IndexedEvent)> {
+        let amount = match self.amount {
+            Some(amount) => Some(amount.parse().ok()?),
+            None => None,
+        };
+        let battle = match self.battle {
+            Some(battle) => Some((
+                battle.loser,
+                BattleLog {
+                    seed: battle.seed.parse().ok()?,
+                    challenger_weight: battle.challenger_weight.parse().ok()?,
+                    opponent_weight: battle.opponent_weight.parse().ok()?,
+                },
+            )),
+            None => None,
+        };
+        let event = IndexedEvent {
+            name: self.name,
+            account: self.account,
+            other: self.other,
+            pet_id: self.pet_id,
+            amount,
+            battle,
+            text: self.text,
+        };
+        Some((self.block, self.idx, event))
+    }
+}
+
+// The accounts and pets a subscriber follows, everything when both are empty
+#[derive(Default)]
+struct Filter {
+    accounts: Vec<AccountId32>,
+    pets: Vec<PetId>,
+}
+
+impl Filter {
+    // From a query like `account=<address>&pet=<id>`, each can be given several times
+    fn parse(query: &str) -> Result<Self, String> {
+        let mut filter = Filter::default();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            match pair.split_once('=') {
+                Some(("account", address)) => filter.accounts.push(
+                    address
+                        .parse()
+                        .map_err(|_| format!("invalid account {address}"))?,
+                ),
+                Some(("pet", id)) => filter
+                    .pets
+                    .push(id.parse().map_err(|_| format!("invalid pet {id}"))?),
+                _ => return Err(format!("unknown filter {pair}")),
+            }
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, event: &IndexedEvent) -> bool {
+        if self.accounts.is_empty() && self.pets.is_empty() {
+            return true;
+        }
+        let accounts = [&event.account, &event.other];
+        let pets = [event.pet_id, event.battle.as_ref().map(|(loser, _)| *loser)];
+        accounts
+            .into_iter()
+            .flatten()
+            .any(|account| self.accounts.contains(account))
+            || pets
+                .into_iter()
+                .flatten()
+                .any(|pet| self.pets.contains(&pet))
+    }
+}
+
+struct Subscriber {
+    filter: Filter,
+    sender: mpsc::Sender<String>,
+}
+
+// The websocket subscribers of the indexer. `follow_chain` publishes every indexed block to
+// it, which is cheap when nobody subscribed.
+#[derive(Clone, Default)]
+pub struct EventFeed(Arc<Mutex<Vec<Subscriber>>>);
+
+impl EventFeed {
+    // Send the events of a block to the subscribers they match, forgetting the ones that left
+    pub fn publish(&self, block: u32, events: &[(u32, IndexedEvent)]) {
+        let mut subscribers = self.0.lock().unwrap();
+        subscribers.retain(|subscriber| {
+            events
+                .iter()
+                .filter(|(_, event)| subscriber.filter.matches(event))
+                .all(|(idx, event)| {
+                    let message = serde_json::to_string(&PushedEvent::new(block, *idx, event));
+                    message.map_or(true, |message| subscriber.sender.send(message).is_ok())
+                })
+        });
+    }
+
+    fn subscribe(&self, filter: Filter) -> mpsc::Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.lock().unwrap().push(Subscriber { filter, sender });
+        receiver
+    }
+}
+
+fn error_response(status: StatusCode, error: String) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(error));
+    *response.status_mut() = status;
+    response
+}
+
+// Push the events of the feed to one subscriber until it leaves
+fn push_events(stream: TcpStream, feed: &EventFeed) -> Result<(), String> {
+    let mut filter = None;
+    let check = |request: &Request, response: Response| {
+        if request.uri().path() != EVENTS_PATH {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                "not found".to_string(),
+            ));
+        }
+        match Filter::parse(request.uri().query().unwrap_or_default()) {
+            Ok(parsed) => {
+                filter = Some(parsed);
+                Ok(response)
+            }
+            Err(e) => Err(error_response(StatusCode::BAD_REQUEST, e)),
+        }
+    };
+    let mut socket = tungstenite::accept_hdr(stream, check).map_err(|e| e.to_string())?;
+    let Some(filter) = filter else {
+        return Ok(());
+    };
+    for message in feed.subscribe(filter) {
+        socket
+            .send(Message::Text(message))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Push the indexed events over websocket, so web and mobile clients get them live without
+// following the chain themselves: connect to `ws://<address>/events` for every event, or add
+// `?account=<address>&pet=<id>`, any number of each, for the ones about those accounts and
+// pets
+pub fn serve(address: &str, feed: EventFeed) {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("not pushing events on {address}: {e}");
+            return;
+        }
+    };
+    info!("pushing events on ws://{address}{EVENTS_PATH}");
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let feed = feed.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = push_events(stream, &feed) {
+                    debug!("event subscriber left: {e}");
+                }
+            });
+        }
+    });
+}
+
+// Store the events a gateway, an indexer pushing events at `url`, pushes, instead of
+// following the chain. Only the events pushed while connected are stored, the ones missed
+// meanwhile aren't sent again.
+pub fn follow_gateway(url: &str, mut db: IndexDb) -> Result<(), String> {
+    let (mut socket, _) =
+        tungstenite::connect(format!("{url}{EVENTS_PATH}")).map_err(|e| e.to_string())?;
+    info!("storing the events pushed by {url}");
+    loop {
+        let text = match socket.read().map_err(|e| e.to_string())? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Ok(()),
+            _ => continue,
+        };
+        let pushed = serde_json::from_str::<PushedEvent>(&text).map_err(|e| e.to_string())?;
+        let Some((block, idx, event)) = pushed.into_indexed() else {
+            warn!("ignoring a pushed event with an invalid number: {text}");
+            continue;
+        };
+        db.insert_block(block, &[(idx, event)])
+            .map_err(|e| e.to_string())?;
+    }
+}
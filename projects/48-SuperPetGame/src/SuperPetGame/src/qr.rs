@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use subxt::utils::AccountId32;
+
+use super::{
+    address_book::{AddressField, FillAddress},
+    toast::Toast,
+};
+
+// Image files a QR code can be read from
+const QR_IMAGE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+// Pixels per module of a generated QR code, and modules of white border around it
+const MODULE_PIXELS: usize = 4;
+const QUIET_ZONE: usize = 4;
+
+// This plugin reads addresses into the visible address field from QR codes, pasted with
+// Ctrl+V or dropped as an image file, and from addresses pasted as text. QR codes of
+// addresses are drawn with `address_qr`.
+pub struct QrPlugin;
+
+impl Plugin for QrPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((paste_address, drop_qr_image));
+    }
+}
+
+// QR code of an address, as a texture with a white border
+pub fn address_qr(address: &str) -> Option<Image> {
+    let code = qrcode::QrCode::new(address.as_bytes()).ok()?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size = (modules + 2 * QUIET_ZONE) * MODULE_PIXELS;
+    let mut data = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let (column, row) = (x / MODULE_PIXELS, y / MODULE_PIXELS);
+            let dark = (QUIET_ZONE..QUIET_ZONE + modules).contains(&column)
+                && (QUIET_ZONE..QUIET_ZONE + modules).contains(&row)
+                && colors[(row - QUIET_ZONE) * modules + column - QUIET_ZONE]
+                    == qrcode::Color::Dark;
+            data.extend(if dark { [0, 0, 0, 255] } else { [255; 4] });
+        }
+    }
+    Some(Image::new(
+        Extent3d {
+            width: size as u32,
+            height: size as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    ))
+}
+
+// The address in a scanned or pasted text: the text itself, or one of its `:` separated
+// parts for `substrate:<address>:...` codes of wallets
+fn address_in(text: &str) -> Option<String> {
+    text.split(':')
+        .map(str::trim)
+        .find(|part| part.parse::<AccountId32>().is_ok())
+        .map(str::to_string)
+}
+
+// Text of the first QR code found in a grayscale image
+fn scan(width: usize, height: usize, luma: impl Fn(usize, usize) -> u8) -> Option<String> {
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(width, height, luma);
+    prepared
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok())
+        .map(|(_, text)| text)
+}
+
+// Whether a dropped file is an image to read a QR code from
+pub fn is_qr_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| {
+            QR_IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+// Address in the QR code of an image file
+fn scan_file(path: &Path) -> Result<String, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(&extension),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(|e| e.to_string())?;
+    let luma = image
+        .try_into_dynamic()
+        .map_err(|e| e.to_string())?
+        .to_luma8();
+    let text = scan(luma.width() as usize, luma.height() as usize, |x, y| {
+        luma.get_pixel(x as u32, y as u32).0[0]
+    })
+    .ok_or("no QR code in the image")?;
+    address_in(&text).ok_or_else(|| "the QR code has no address".to_string())
+}
+
+// Address pasted as text, or as the image of a QR code
+fn pasted_address() -> Result<String, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    if let Ok(text) = clipboard.get_text() {
+        return address_in(&text).ok_or_else(|| "the pasted text isn't an address".to_string());
+    }
+    let image = clipboard.get_image().map_err(|e| e.to_string())?;
+    let text = scan(image.width, image.height, |x, y| {
+        let pixel = &image.bytes[(y * image.width + x) * 4..][..3];
+        ((u32::from(pixel[0]) * 299 + u32::from(pixel[1]) * 587 + u32::from(pixel[2]) * 114) / 1000)
+            as u8
+    })
+    .ok_or("no QR code in the pasted image")?;
+    address_in(&text).ok_or_else(|| "the QR code has no address".to_string())
+}
+
+fn field_visible(fields: &Query<&ComputedVisibility, With<AddressField>>) -> bool {
+    fields.iter().any(ComputedVisibility::is_visible)
+}
+
+fn paste_address(
+    kbd: Res<Input<KeyCode>>,
+    fields: Query<&ComputedVisibility, With<AddressField>>,
+    mut fill: EventWriter<FillAddress>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let modifier = [
+        KeyCode::LControl,
+        KeyCode::RControl,
+        KeyCode::LWin,
+        KeyCode::RWin,
+    ];
+    if !kbd.just_pressed(KeyCode::V) || !kbd.any_pressed(modifier) || !field_visible(&fields) {
+        return;
+    }
+    match pasted_address() {
+        Ok(address) => fill.send(FillAddress(address)),
+        Err(e) => toasts.send(Toast(format!("Can't paste an address: {e}"))),
+    }
+}
+
+// Read the QR code of an image file dropped while an address field is shown
+fn drop_qr_image(
+    mut events: EventReader<FileDragAndDrop>,
+    fields: Query<&ComputedVisibility, With<AddressField>>,
+    mut fill: EventWriter<FillAddress>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for event in events.iter() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if !is_qr_image(path_buf) || !field_visible(&fields) {
+            continue;
+        }
+        match scan_file(path_buf) {
+            Ok(address) => fill.send(FillAddress(address)),
+            Err(e) => toasts.send(Toast(format!("Can't read {}: {e}", path_buf.display()))),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{format_balance, polkadot, ChainClient},
+    despawn_screen,
+    game::Spectating,
+    inventory::ITEMS,
+    poll::{PollScheduler, Priority},
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// How often the progress is read again while playing
+const REFRESH_SECONDS: f32 = 15.0;
+
+// This plugin lists today's quests of the quests pallet on the game screen, with the pet's
+// progress, and claims their reward once they're complete
+pub struct QuestsPlugin;
+
+impl Plugin for QuestsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            quests_setup.in_schedule(OnEnter(GameState::Game)),
+            despawn_screen::<OnQuestsPanel>.in_schedule(OnExit(GameState::Game)),
+        ))
+        .add_systems((refresh_quests, claim_reward).in_set(OnUpdate(GameState::Game)));
+    }
+}
+
+// Mirrors of the quests pallet types, decoded from the pallet constants and storage
+#[derive(Decode, Clone, Copy)]
+enum Objective {
+    Feed(u32),
+    Sleep(u32),
+    WinBattles(u32),
+}
+
+#[derive(Decode, Clone, Copy)]
+enum Reward {
+    // Index in `ITEMS`
+    Item(u8),
+    Tokens(u128),
+}
+
+#[derive(Decode, Clone, Copy)]
+struct Quest {
+    objective: Objective,
+    reward: Reward,
+}
+
+// `Activity` of the pet pallet, what the pet did during `day`
+#[derive(Decode, Default)]
+struct Activity {
+    day: u32,
+    feeds: u32,
+    sleeps: u32,
+    battles_won: u32,
+}
+
+struct QuestStatus {
+    quest: Quest,
+    done: u32,
+    claimed: bool,
+}
+
+impl QuestStatus {
+    fn needed(&self) -> u32 {
+        match self.quest.objective {
+            Objective::Feed(times) | Objective::Sleep(times) | Objective::WinBattles(times) => {
+                times
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        let task = match self.quest.objective {
+            Objective::Feed(times) => format!("Feed {times} times"),
+            Objective::Sleep(times) => format!("Sleep {times} times"),
+            Objective::WinBattles(1) => "Win a battle".to_string(),
+            Objective::WinBattles(wins) => format!("Win {wins} battles"),
+        };
+        let reward = match self.quest.reward {
+            Reward::Item(item) => ITEMS
+                .get(item as usize)
+                .map_or("an item", |(name, _)| *name)
+                .to_string(),
+            Reward::Tokens(amount) => format_balance(amount),
+        };
+        format!(
+            "{task}  {}/{}  -> {reward}",
+            self.done.min(self.needed()),
+            self.needed()
+        )
+    }
+}
+
+// Tag component used to tag the quests panel
+#[derive(Component)]
+struct OnQuestsPanel;
+
+// Claim button of the quest with this index among today's
+#[derive(Component)]
+struct ClaimQuest(u32);
+
+// Today's quests and the player's progress, read from the chain. The quests rotate like
+// `active_quests` of the pallet does.
+fn load_quests(
+    client: &mut ChainClient,
+    account: &AccountId32,
+) -> Result<Vec<QuestStatus>, subxt::Error> {
+    let api = client.api()?;
+    client.block_on(async {
+        let constant = |pallet, name| {
+            api.constants()
+                .at(&subxt::dynamic::constant(pallet, name))
+                .map(|value| value.encoded().to_vec())
+        };
+        let pool = Vec::<Quest>::decode(&mut &constant("QuestsModule", "QuestPool")?[..])?;
+        let per_day = u32::decode(&mut &constant("QuestsModule", "QuestsPerDay")?[..])?;
+        let blocks_per_day = u32::decode(&mut &constant("PetModule", "BlocksPerDay")?[..])?;
+        if pool.is_empty() || blocks_per_day == 0 {
+            return Ok(Vec::new());
+        }
+
+        let block = api.blocks().at_latest().await?;
+        let storage = api.storage().at(block.hash());
+        let day = block.number() / blocks_per_day;
+
+        let pet = storage
+            .fetch(&polkadot::storage().pet_module().pets_info(account))
+            .await?;
+        let pet_id = pet.map(|(pet_id, _)| pet_id);
+        let activity = match pet_id {
+            Some(pet_id) => storage
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "DailyActivity",
+                    vec![Value::u128(pet_id.into())],
+                ))
+                .await?
+                .and_then(|value| Activity::decode(&mut value.encoded()).ok())
+                .filter(|activity| activity.day == day)
+                .unwrap_or_default(),
+            None => Activity::default(),
+        };
+        // Claims are kept per pet, a pet passed on keeps the quests claimed for it today
+        let (claimed_day, claimed) = match pet_id {
+            Some(pet_id) => storage
+                .fetch(&subxt::dynamic::storage(
+                    "QuestsModule",
+                    "Claimed",
+                    vec![Value::u128(pet_id.into())],
+                ))
+                .await?
+                .and_then(|value| <(u32, u32)>::decode(&mut value.encoded()).ok())
+                .unwrap_or_default(),
+            None => (0, 0),
+        };
+        let claimed = if claimed_day == day { claimed } else { 0 };
+
+        let per_day = per_day.min(pool.len() as u32).min(32) as usize;
+        let first = (day as usize).wrapping_mul(per_day);
+        Ok((0..per_day)
+            .map(|i| {
+                let quest = pool[first.wrapping_add(i) % pool.len()];
+                let done = match quest.objective {
+                    Objective::Feed(_) => activity.feeds,
+                    Objective::Sleep(_) => activity.sleeps,
+                    Objective::WinBattles(_) => activity.battles_won,
+                };
+                QuestStatus {
+                    quest,
+                    done,
+                    claimed: claimed & (1 << i) != 0,
+                }
+            })
+            .collect())
+    })
+}
+
+fn quests_setup(mut commands: Commands, spectating: Res<Spectating>) {
+    if spectating.0.is_some() {
+        return;
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                gap: Size::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        },
+        OnQuestsPanel,
+    ));
+}
+
+// Rebuild the panel when the game screen opens, then every `REFRESH_SECONDS`
+fn refresh_quests(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    panel: Query<Entity, With<OnQuestsPanel>>,
+    mut scheduler: ResMut<PollScheduler>,
+) {
+    let Ok(panel) = panel.get_single() else {
+        scheduler.forget("quests");
+        return;
+    };
+    if !scheduler.ready("quests", Priority::Normal, REFRESH_SECONDS) {
+        return;
+    }
+
+    let account = client.account_id();
+    let quests = match load_quests(&mut client, &account) {
+        Ok(quests) => quests,
+        Err(e) => {
+            error!("error reading quests: {e:?}");
+            return;
+        }
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    commands.entity(panel).despawn_descendants();
+    commands.entity(panel).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            "Today's quests",
+            text_style.clone(),
+        ));
+        for (index, status) in quests.iter().enumerate() {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        status.describe(),
+                        text_style.clone(),
+                    ));
+                    if status.claimed {
+                        parent.spawn(TextBundle::from_section(
+                            "Claimed",
+                            TextStyle {
+                                color: Color::GRAY,
+                                ..text_style.clone()
+                            },
+                        ));
+                    } else if status.done >= status.needed() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::all(Val::Px(4.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GREEN.into(),
+                                    ..default()
+                                },
+                                ClaimQuest(index as u32),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section("Claim", text_style.clone()));
+                            });
+                    }
+                });
+        }
+    });
+}
+
+fn claim_reward(
+    interaction_query: Query<(&Interaction, &ClaimQuest), Changed<Interaction>>,
+    mut queue: ResMut<TxQueue>,
+) {
+    for (interaction, claim) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            queue.push(
+                "Claim quest",
+                subxt::dynamic::tx(
+                    "QuestsModule",
+                    "claim_quest_reward",
+                    vec![Value::u128(claim.0.into())],
+                ),
+            );
+        }
+    }
+}
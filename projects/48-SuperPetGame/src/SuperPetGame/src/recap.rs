@@ -0,0 +1,399 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use super::{
+    client::{ChainClient, PetId},
+    despawn_screen,
+    game::{PlayerSprite, Spectating},
+    indexer::IndexDb,
+    pet_watch::{CareRules, PlayerPet},
+    poll::{PollScheduler, Priority},
+    upgrade, GameState, TEXT_COLOR,
+};
+
+// Last block each pet was seen on the game screen, kept across restarts
+const LAST_SEEN_FILE: &str = "last-seen.json";
+
+// Absence after which the recap plays (1 hour of 6 second blocks)
+const AWAY_BLOCKS: u32 = 600;
+const BLOCKS_PER_HOUR: u32 = 600;
+
+// How long each moment of the recap stays on screen, and how many are shown at most
+const STEP_SECONDS: f32 = 1.5;
+const MAX_STEPS: usize = 8;
+
+// How often the last seen block is saved while playing
+const SAVE_SECONDS: f32 = 60.0;
+
+// This plugin replays what happened to the player's pet while the game was closed: the care
+// actions and battles found in the event index, and the times the pet went hungry or tired,
+// each played as a short animation with a caption before the player gets control back
+pub struct RecapPlugin;
+
+impl Plugin for RecapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recap>()
+            .add_systems((
+                reset_recap.in_schedule(OnExit(GameState::Game)),
+                despawn_screen::<OnRecapOverlay>.in_schedule(OnExit(GameState::Game)),
+            ))
+            .add_systems(
+                (
+                    // The hungry and tired times come from the game's own rules
+                    start_recap.run_if(upgrade::rules_match),
+                    play_recap.before(skip_recap),
+                    skip_recap,
+                    save_last_seen,
+                )
+                    .in_set(OnUpdate(GameState::Game)),
+            );
+    }
+}
+
+// How the pet sprite moves during a moment of the recap
+#[derive(Clone, Copy)]
+enum Animation {
+    Hop,
+    Doze,
+    Droop,
+    Shake,
+}
+
+struct Moment {
+    block: u32,
+    caption: String,
+    animation: Animation,
+}
+
+// Moments left to play, the recap is over once the queue is empty
+#[derive(Resource, Default)]
+pub struct Recap {
+    // Whether the pet's history was already checked on this visit of the game screen
+    checked: bool,
+    moments: VecDeque<Moment>,
+    // Head of the chain when the recap started, to tell how long ago a moment was
+    now: u32,
+    timer: Option<Timer>,
+}
+
+// Run condition, false while the recap plays
+pub fn recap_finished(recap: Res<Recap>) -> bool {
+    recap.moments.is_empty()
+}
+
+// Tag component used to tag the recap overlay
+#[derive(Component)]
+struct OnRecapOverlay;
+
+#[derive(Component)]
+struct RecapCaption;
+
+fn read_last_seen() -> BTreeMap<PetId, u32> {
+    std::fs::read(LAST_SEEN_FILE)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_last_seen(pet_id: PetId, block: u32) {
+    let mut last_seen = read_last_seen();
+    last_seen.insert(pet_id, block);
+    let written = serde_json::to_vec_pretty(&last_seen)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(LAST_SEEN_FILE, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        error!("can't save {LAST_SEEN_FILE}: {e}");
+    }
+}
+
+fn head_block(client: &mut ChainClient) -> Option<u32> {
+    let api = client.api().ok()?;
+    client
+        .block_on(async { Ok::<_, subxt::Error>(api.blocks().at_latest().await?.number()) })
+        .ok()
+}
+
+// Length of a span of blocks in hours
+fn hours(blocks: u32) -> String {
+    match blocks / BLOCKS_PER_HOUR {
+        0 => "less than an hour".to_string(),
+        1 => "1 hour".to_string(),
+        hours => format!("{hours} hours"),
+    }
+}
+
+// Times a care gap went past `limit` between the care `blocks`. The first block is the last
+// care before the absence, the last one the end of the absence.
+fn gaps(blocks: &[u32], limit: u32, since: u32) -> Vec<u32> {
+    blocks
+        .windows(2)
+        .filter(|pair| pair[1].saturating_sub(pair[0]) > limit)
+        .map(|pair| pair[0] + limit)
+        .filter(|block| *block >= since)
+        .collect()
+}
+
+// What happened to the pet between `since` and the last indexed block
+fn load_moments(
+    db: &IndexDb,
+    pet_id: PetId,
+    name: &str,
+    since: u32,
+    rules: &pet_core::Rules,
+) -> Option<Vec<Moment>> {
+    let until = db.last_block().ok()??;
+    if until <= since {
+        return None;
+    }
+    let events = db.pet_events(pet_id, since, until).ok()?;
+
+    let mut moments: Vec<Moment> = events
+        .iter()
+        .filter_map(|(block, event)| {
+            let (caption, animation) = match event.as_str() {
+                // Nobody was playing, so a game key or a household batch did it
+                "PetFeeded" => (format!("Someone fed {name}"), Animation::Hop),
+                "PetSleeped" => (format!("{name} took a nap"), Animation::Doze),
+                // Battles are indexed under the winning pet
+                "BattleResolved" => (format!("{name} won a battle"), Animation::Shake),
+                "PetRenamed" => (format!("{name} got a new name"), Animation::Hop),
+                _ => return None,
+            };
+            Some(Moment {
+                block: *block,
+                caption,
+                animation,
+            })
+        })
+        .collect();
+
+    // Care times from the last one before the absence to its end, to find the neglect
+    let care_blocks = |event, limit: u32| {
+        let mut blocks: Vec<u32> = db
+            .pet_event_blocks(pet_id, event, since.saturating_sub(limit))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|block| *block <= until)
+            .collect();
+        blocks.push(until);
+        blocks
+    };
+    // Told with the rules in force now, they rarely change
+    let (hungry_after, tired_after) = (rules.hungry_after(), rules.tired_after());
+    for block in gaps(&care_blocks("PetFeeded", hungry_after), hungry_after, since) {
+        moments.push(Moment {
+            block,
+            caption: format!("{name} got hungry"),
+            animation: Animation::Droop,
+        });
+    }
+    for block in gaps(&care_blocks("PetSleeped", tired_after), tired_after, since) {
+        moments.push(Moment {
+            block,
+            caption: format!("{name} got tired"),
+            animation: Animation::Doze,
+        });
+    }
+
+    moments.sort_by_key(|moment| moment.block);
+    // Keep the latest moments of long absences
+    let skip = moments.len().saturating_sub(MAX_STEPS);
+    Some(moments.into_iter().skip(skip).collect())
+}
+
+fn reset_recap(mut recap: ResMut<Recap>) {
+    *recap = Recap::default();
+}
+
+// Remove the overlay and give the sprite back its idle pose
+fn end_recap(
+    commands: &mut Commands,
+    sprites: &mut Query<(&mut Transform, &mut TextureAtlasSprite), With<PlayerSprite>>,
+    overlay: &Query<Entity, With<OnRecapOverlay>>,
+) {
+    for (mut transform, mut sprite) in sprites {
+        transform.translation = Vec3::ZERO;
+        sprite.color = Color::WHITE;
+    }
+    for entity in overlay {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Once the player's pet is known, look for what happened since it was last seen
+fn start_recap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    care_rules: Res<CareRules>,
+    spectating: Res<Spectating>,
+    mut recap: ResMut<Recap>,
+) {
+    if recap.checked || spectating.0.is_some() {
+        return;
+    }
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    let Some(now) = head_block(&mut client) else {
+        return;
+    };
+    recap.checked = true;
+    let last_seen = read_last_seen().get(&pet.id).copied();
+    write_last_seen(pet.id, now);
+
+    let Some(since) = last_seen.filter(|since| now.saturating_sub(*since) >= AWAY_BLOCKS) else {
+        return;
+    };
+    let moments = IndexDb::open()
+        .ok()
+        .and_then(|db| load_moments(&db, pet.id, &pet.name, since, &care_rules.rules))
+        .unwrap_or_default();
+    if moments.is_empty() {
+        return;
+    }
+    recap.moments = moments.into();
+    recap.now = now;
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 26.0,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(40.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(),
+                // Clicks skip the recap instead of reaching the buttons below
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(15),
+                ..default()
+            },
+            OnRecapOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("While you were away ({})", hours(now - since)),
+                text_style.clone(),
+            ));
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                RecapCaption,
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Click to skip",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::GRAY,
+                    ..text_style
+                },
+            ));
+        });
+}
+
+// Play the moments one after the other
+fn play_recap(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut recap: ResMut<Recap>,
+    mut captions: Query<&mut Text, With<RecapCaption>>,
+    mut sprites: Query<(&mut Transform, &mut TextureAtlasSprite), With<PlayerSprite>>,
+    overlay: Query<Entity, With<OnRecapOverlay>>,
+) {
+    let Some(moment) = recap.moments.front() else {
+        return;
+    };
+    let animation = moment.animation;
+    let ago = match recap.now.saturating_sub(moment.block) {
+        blocks if blocks < BLOCKS_PER_HOUR => "Less than an hour ago".to_string(),
+        blocks => format!("{} ago", hours(blocks)),
+    };
+    let caption = format!("{ago}: {}", moment.caption);
+    for mut text in &mut captions {
+        if text.sections[0].value != caption {
+            text.sections[0].value = caption.clone();
+        }
+    }
+
+    let timer = recap
+        .timer
+        .get_or_insert_with(|| Timer::from_seconds(STEP_SECONDS, TimerMode::Repeating));
+    let t = timer.elapsed_secs();
+    let done = timer.tick(time.delta()).just_finished();
+
+    for (mut transform, mut sprite) in &mut sprites {
+        transform.translation = Vec3::ZERO;
+        sprite.color = Color::WHITE;
+        match animation {
+            Animation::Hop => transform.translation.y = (t * 8.0).sin().abs() * 20.0,
+            Animation::Doze => {
+                transform.translation.y = (t * 1.5).sin() * 3.0;
+                sprite.color = Color::rgb(0.5, 0.5, 0.7);
+            }
+            Animation::Droop => {
+                transform.translation.y = -8.0;
+                sprite.color = Color::rgb(0.6, 0.6, 0.6);
+            }
+            Animation::Shake => transform.translation.x = (t * 40.0).sin() * 6.0,
+        }
+    }
+
+    if !done {
+        return;
+    }
+    recap.moments.pop_front();
+    if recap.moments.is_empty() {
+        end_recap(&mut commands, &mut sprites, &overlay);
+    }
+}
+
+// A click or a key press ends the recap right away
+fn skip_recap(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut recap: ResMut<Recap>,
+    mut sprites: Query<(&mut Transform, &mut TextureAtlasSprite), With<PlayerSprite>>,
+    overlay: Query<Entity, With<OnRecapOverlay>>,
+) {
+    if recap.moments.is_empty() {
+        return;
+    }
+    if mouse.get_just_pressed().next().is_some() || keys.get_just_pressed().next().is_some() {
+        recap.moments.clear();
+        end_recap(&mut commands, &mut sprites, &overlay);
+    }
+}
+
+// Keep the last seen block current while playing, so only time away is recapped
+fn save_last_seen(
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    recap: Res<Recap>,
+    mut scheduler: ResMut<PollScheduler>,
+) {
+    if !recap.checked {
+        return;
+    }
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    if !scheduler.ready("last seen", Priority::Background, SAVE_SECONDS) {
+        return;
+    }
+    if let Some(now) = head_block(&mut client) {
+        write_last_seen(pet.id, now);
+    }
+}
@@ -0,0 +1,561 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use pet_core::{Blow, REPLAY_HEALTH};
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    assets::GameAssets,
+    battle::roll_tag,
+    client::{ChainClient, PetId, PetSpecies},
+    clock::ChainClock,
+    despawn_screen,
+    indexer::{IndexDb, IndexerError, RecordedBattle},
+    pet_watch::PlayerPet,
+    settings::Palette,
+    GameState, TEXT_COLOR,
+};
+
+// Battles of the player's pet listed
+const LISTED: u32 = 8;
+
+// Time between two blows
+const BLOW_SECONDS: f32 = 0.8;
+
+// How far a pet lunges at the other one when it hits
+const LUNGE: f32 = 80.0;
+
+// This plugin replays the latest battles of the player's pet. Battles are settled by a single
+// roll on chain; the event index keeps that roll and the weights of both pets, and `pet_core`
+// turns them into the same blows on every client, ending with the winner the chain chose.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            replay_setup.in_schedule(OnEnter(GameState::Replay)),
+            despawn_screen::<OnReplayScreen>.in_schedule(OnExit(GameState::Replay)),
+        ))
+        .add_systems(
+            (
+                receive_battles,
+                replay_action.after(receive_battles),
+                receive_fighters.after(replay_action),
+                play_blows.after(replay_action),
+                lunge_animation.after(play_blows),
+                color_choices.after(replay_action),
+            )
+                .in_set(OnUpdate(GameState::Replay)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the replay screen
+#[derive(Component)]
+struct OnReplayScreen;
+
+#[derive(Component)]
+struct BattleList;
+
+#[derive(Component)]
+struct ReplayText;
+
+// Health bar of the challenger (`true`) or of the opponent
+#[derive(Component)]
+struct HealthBar(bool);
+
+#[derive(Component)]
+struct FighterName(bool);
+
+// Sprite of a fighter, lunging for a while after it hits
+#[derive(Component)]
+struct ReplayFighter {
+    challenger: bool,
+    lunge: Timer,
+}
+
+#[derive(Component)]
+enum ReplayAction {
+    Play(usize),
+    Back,
+}
+
+// The head of `PetProfile` from the pet runtime api, enough to draw a fighter
+#[derive(Decode)]
+struct FighterInfo {
+    _pet_id: PetId,
+    _owner: AccountId32,
+    name: Vec<u8>,
+    species: PetSpecies,
+}
+
+#[derive(Resource)]
+struct Battles(Vec<RecordedBattle>);
+
+// Result of the background read of the battle list
+#[derive(Resource)]
+struct BattlesLoad(Mutex<mpsc::Receiver<Result<Vec<RecordedBattle>, String>>>);
+
+// Name and species of both fighters, `None` for pets nobody owns anymore
+type Fighters = [Option<FighterInfo>; 2];
+
+#[derive(Resource)]
+struct FightersLoad(Mutex<mpsc::Receiver<Result<Fighters, String>>>);
+
+// The battle being replayed and how far it got
+#[derive(Resource)]
+struct Replay {
+    index: usize,
+    battle: RecordedBattle,
+    // Names of the challenger and of the opponent
+    names: [String; 2],
+    blows: Vec<Blow>,
+    shown: usize,
+    // Whether the bars still show the previous battle
+    fresh: bool,
+    timer: Timer,
+}
+
+impl Replay {
+    fn new(index: usize, battle: RecordedBattle) -> Self {
+        let (challenger_pet, opponent_pet) = battle.pets();
+        Self {
+            index,
+            names: [
+                format!("Pet #{challenger_pet}"),
+                format!("Pet #{opponent_pet}"),
+            ],
+            blows: battle.log.blows().collect(),
+            battle,
+            shown: 0,
+            fresh: true,
+            timer: Timer::from_seconds(BLOW_SECONDS, TimerMode::Repeating),
+        }
+    }
+
+    fn name(&self, challenger: bool) -> &str {
+        &self.names[usize::from(!challenger)]
+    }
+}
+
+async fn load_fighters(
+    api: OnlineClient<PolkadotConfig>,
+    pets: (PetId, PetId),
+) -> Result<Fighters, subxt::Error> {
+    let mut fighters = [None, None];
+    for (fighter, pet_id) in fighters.iter_mut().zip([pets.0, pets.1]) {
+        *fighter = api
+            .rpc()
+            .state_call("PetApi_pet_profile", Some(&pet_id.encode()), None)
+            .await?;
+    }
+    Ok(fighters)
+}
+
+fn replay_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    match &player_pet.pet {
+        Some(pet) => {
+            let pet_id = pet.id;
+            client.spawn(async move {
+                let battles = IndexDb::open().and_then(|db| db.pet_battles(pet_id, LISTED));
+                let _ = sender.send(battles.map_err(|e: IndexerError| e.to_string()));
+            });
+        }
+        None => {
+            let _ = sender.send(Err("You have no pet".to_string()));
+        }
+    }
+    commands.insert_resource(BattlesLoad(Mutex::new(receiver)));
+    commands.remove_resource::<Battles>();
+    commands.remove_resource::<Replay>();
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::MAROON,
+                custom_size: Some(Vec2::splat(4000.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, -10.0),
+            ..default()
+        },
+        OnReplayScreen,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            OnReplayScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Percent(100.0)),
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for challenger in [true, false] {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    gap: Size::all(Val::Px(6.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(String::new(), text_style.clone()),
+                                    FighterName(challenger),
+                                ));
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            size: Size::new(Val::Px(300.0), Val::Px(20.0)),
+                                            ..default()
+                                        },
+                                        background_color: Color::DARK_GRAY.into(),
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            NodeBundle {
+                                                style: Style {
+                                                    size: Size::new(
+                                                        Val::Percent(100.0),
+                                                        Val::Percent(100.0),
+                                                    ),
+                                                    ..default()
+                                                },
+                                                background_color: Color::LIME_GREEN.into(),
+                                                ..default()
+                                            },
+                                            HealthBar(challenger),
+                                        ));
+                                    });
+                            });
+                    }
+                });
+            parent.spawn((
+                TextBundle::from_section("Reading the battles...", text_style.clone()),
+                ReplayText,
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        gap: Size::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                BattleList,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ReplayAction::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style.clone()));
+                });
+        });
+}
+
+// List the battles, the latest one starts right away
+fn receive_battles(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<BattlesLoad>>,
+    player_pet: Res<PlayerPet>,
+    clock: Res<ChainClock>,
+    list: Query<Entity, With<BattleList>>,
+    mut replay_text: Query<&mut Text, With<ReplayText>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<BattlesLoad>();
+    let battles = match result {
+        Ok(battles) if !battles.is_empty() => battles,
+        failed => {
+            replay_text.single_mut().sections[0].value = match failed {
+                Err(e) => format!("Can't read the battles: {e}"),
+                _ => "No battles to replay yet".to_string(),
+            };
+            return;
+        }
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+
+    let own = player_pet.pet.as_ref().map(|pet| pet.id);
+    commands.entity(list.single()).with_children(|parent| {
+        for (index, battle) in battles.iter().enumerate() {
+            let (result, opponent) = if Some(battle.winner) == own {
+                ("Won", battle.loser)
+            } else {
+                ("Lost", battle.winner)
+            };
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ReplayAction::Play(index),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!(
+                            "{}: {result} vs pet #{opponent}, {}",
+                            clock.block(battle.block),
+                            roll_tag(&battle.log)
+                        ),
+                        text_style.clone(),
+                    ));
+                });
+        }
+    });
+    commands.insert_resource(Battles(battles));
+}
+
+// Start a battle over: reset the bars and read who fought it
+fn start_replay(
+    commands: &mut Commands,
+    client: &mut ChainClient,
+    index: usize,
+    battle: RecordedBattle,
+) {
+    let (sender, receiver) = mpsc::channel();
+    let pets = battle.pets();
+    match client.api() {
+        Ok(api) => {
+            client.spawn(async move {
+                let fighters = load_fighters(api, pets).await;
+                let _ = sender.send(fighters.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(FightersLoad(Mutex::new(receiver)));
+    commands.insert_resource(Replay::new(index, battle));
+}
+
+fn replay_action(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &ReplayAction), Changed<Interaction>>,
+    battles: Option<Res<Battles>>,
+    replay: Option<Res<Replay>>,
+    mut client: ResMut<ChainClient>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(battles) = battles else {
+        return;
+    };
+    // Play the latest battle as soon as the list is read
+    if replay.is_none() && battles.is_added() {
+        start_replay(&mut commands, &mut client, 0, battles.0[0].clone());
+    }
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            ReplayAction::Play(index) => {
+                start_replay(
+                    &mut commands,
+                    &mut client,
+                    *index,
+                    battles.0[*index].clone(),
+                );
+            }
+            ReplayAction::Back => game_state.set(GameState::Battle),
+        }
+    }
+}
+
+// Draw both fighters once known, on each side of the arena
+fn receive_fighters(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    load: Option<Res<FightersLoad>>,
+    replay: Option<ResMut<Replay>>,
+    sprites: Query<Entity, With<ReplayFighter>>,
+    mut names: Query<(&mut Text, &FighterName)>,
+) {
+    let (Some(load), Some(mut replay)) = (load, replay) else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<FightersLoad>();
+    for sprite in &sprites {
+        commands.entity(sprite).despawn_recursive();
+    }
+
+    let fighters = result.unwrap_or_else(|e| {
+        warn!("can't read the fighters of the replay: {e}");
+        [None, None]
+    });
+    for (fighter, challenger) in fighters.iter().zip([true, false]) {
+        let mut species = String::new();
+        if let Some(fighter) = fighter {
+            replay.names[usize::from(!challenger)] =
+                String::from_utf8_lossy(&fighter.name).into_owned();
+            species = format!("{:?}", fighter.species);
+        }
+        for (mut text, FighterName(side)) in &mut names {
+            if *side == challenger {
+                text.sections[0].value = replay.name(challenger).to_string();
+            }
+        }
+        let direction = if challenger { 1.0 } else { -1.0 };
+        let mut sprite = assets.pet_sprite(&species);
+        // Both pets face each other
+        sprite.sprite.flip_x = !challenger;
+        sprite.transform = Transform::from_xyz(-200.0 * direction, 0.0, 0.0);
+        let mut lunge = Timer::from_seconds(BLOW_SECONDS / 2.0, TimerMode::Once);
+        lunge.pause();
+        commands.spawn((sprite, ReplayFighter { challenger, lunge }, OnReplayScreen));
+    }
+}
+
+// Show the next blow every `BLOW_SECONDS`, then who won
+fn play_blows(
+    time: Res<Time>,
+    replay: Option<ResMut<Replay>>,
+    clock: Res<ChainClock>,
+    mut fighters: Query<&mut ReplayFighter>,
+    mut bars: Query<(&mut Style, &HealthBar)>,
+    mut replay_text: Query<&mut Text, With<ReplayText>>,
+) {
+    let Some(mut replay) = replay else {
+        return;
+    };
+    if replay.fresh {
+        replay.fresh = false;
+        for (mut style, _) in &mut bars {
+            style.size.width = Val::Percent(100.0);
+        }
+        replay_text.single_mut().sections[0].value = format!(
+            "Replaying the battle of {}",
+            clock.block(replay.battle.block)
+        );
+    }
+    if replay.shown == replay.blows.len() {
+        return;
+    }
+    // Ticking alone isn't a change, the buttons are only recolored for a new battle
+    let timer = &mut replay.bypass_change_detection().timer;
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let blow = replay.blows[replay.shown];
+    replay.shown += 1;
+    let hit = !blow.by_challenger;
+    for (mut style, HealthBar(side)) in &mut bars {
+        if *side == hit {
+            style.size.width = Val::Percent(100.0 * blow.health_left as f32 / REPLAY_HEALTH as f32);
+        }
+    }
+    for mut fighter in &mut fighters {
+        if fighter.challenger == blow.by_challenger {
+            fighter.lunge.reset();
+            fighter.lunge.unpause();
+        }
+    }
+
+    let attacker = replay.name(blow.by_challenger);
+    replay_text.single_mut().sections[0].value = if replay.shown == replay.blows.len() {
+        format!(
+            "{attacker} wins, as the chain rolled {} with {}",
+            clock.block(replay.battle.block),
+            roll_tag(&replay.battle.log)
+        )
+    } else {
+        format!("{attacker} hits for {}", blow.damage)
+    };
+}
+
+fn lunge_animation(time: Res<Time>, mut fighters: Query<(&mut ReplayFighter, &mut Transform)>) {
+    for (mut fighter, mut transform) in &mut fighters {
+        let direction = if fighter.challenger { 1.0 } else { -1.0 };
+        fighter.lunge.tick(time.delta());
+        let lunge = if fighter.lunge.paused() || fighter.lunge.finished() {
+            0.0
+        } else {
+            (fighter.lunge.percent() * std::f32::consts::PI).sin() * LUNGE
+        };
+        transform.translation.x = (lunge - 200.0) * direction;
+    }
+}
+
+// Highlight the battle being replayed
+fn color_choices(
+    replay: Option<Res<Replay>>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &ReplayAction, &mut BackgroundColor)>,
+) {
+    let changed = replay.as_ref().map_or(false, |replay| replay.is_changed());
+    for (interaction, action, mut color) in &mut buttons {
+        if !changed && !interaction.is_changed() {
+            continue;
+        }
+        let selected = matches!(
+            (action, &replay),
+            (ReplayAction::Play(index), Some(replay)) if *index == replay.index
+        );
+        *color = palette.button(*interaction, selected).into();
+    }
+}
@@ -0,0 +1,526 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{ChainClient, ChainEvent},
+    clock::ChainClock,
+    despawn_screen,
+    settings::Palette,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Blocks in an hour, with 6 second blocks
+const BLOCKS_PER_HOUR: u32 = 600;
+
+// Furthest the first run can be scheduled from the screen, in hours
+const MAX_START_HOURS: u32 = 72;
+
+// Repeats offered, in hours, none runs once. The chain takes an hour at least.
+const REPEATS: [Option<u32>; 3] = [None, Some(12), Some(24)];
+
+// This plugin shows the care the player scheduled on chain for their pet, fed or put to
+// sleep by the scheduler while the game is closed, and schedules or cancels it. Blocks are
+// shown as local times by the `ChainClock`, so they drift if the chain runs late.
+pub struct SchedulePlugin;
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            schedule_setup.in_schedule(OnEnter(GameState::Schedule)),
+            despawn_screen::<OnScheduleScreen>.in_schedule(OnExit(GameState::Schedule)),
+        ))
+        .add_systems(
+            (
+                schedule_events,
+                receive_schedules.after(schedule_events),
+                show_schedules.after(receive_schedules),
+                schedule_action,
+                show_draft.after(schedule_action),
+                color_choices.after(schedule_action),
+            )
+                .in_set(OnUpdate(GameState::Schedule)),
+        );
+    }
+}
+
+// Tag component used to tag entities added on the schedule screen
+#[derive(Component)]
+struct OnScheduleScreen;
+
+#[derive(Component)]
+struct ScheduleArea;
+
+#[derive(Component)]
+struct DraftText;
+
+// `CareAction` of the pet pallet
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CareAction {
+    Feed,
+    Sleep,
+}
+
+impl CareAction {
+    const ALL: [CareAction; 2] = [CareAction::Feed, CareAction::Sleep];
+
+    fn variant(self) -> &'static str {
+        match self {
+            CareAction::Feed => "Feed",
+            CareAction::Sleep => "Sleep",
+        }
+    }
+
+    fn value(self) -> Value {
+        Value::unnamed_variant(self.variant(), [])
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum ScheduleAction {
+    Pick(CareAction),
+    Later,
+    Sooner,
+    Repeat(Option<u32>),
+    Schedule,
+    Cancel(CareAction),
+    Back,
+}
+
+// Mirror of `CareSchedule` of the pet pallet
+#[derive(Decode)]
+struct CareSchedule {
+    next: u32,
+    every: Option<u32>,
+}
+
+// The player's schedules at the latest block
+#[derive(Resource)]
+struct Schedules {
+    block: u32,
+    schedules: Vec<(CareAction, CareSchedule)>,
+}
+
+// Result of the background schedules read
+#[derive(Resource)]
+struct ScheduleLoad(Mutex<mpsc::Receiver<Result<Schedules, String>>>);
+
+// The schedule being made
+#[derive(Resource)]
+struct Draft {
+    action: CareAction,
+    in_hours: u32,
+    every_hours: Option<u32>,
+}
+
+impl Default for Draft {
+    fn default() -> Self {
+        Self {
+            action: CareAction::Sleep,
+            in_hours: 8,
+            every_hours: Some(24),
+        }
+    }
+}
+
+fn repeat_label(every_hours: Option<u32>) -> String {
+    match every_hours {
+        None => "once".to_string(),
+        Some(24) => "every day".to_string(),
+        Some(hours) => format!("every {hours} h"),
+    }
+}
+
+async fn load_schedules(
+    api: OnlineClient<PolkadotConfig>,
+    account: AccountId32,
+) -> Result<Schedules, subxt::Error> {
+    let block = api.blocks().at_latest().await?;
+    let storage = api.storage().at(block.hash());
+    let mut schedules = Vec::new();
+    for action in CareAction::ALL {
+        let address = subxt::dynamic::storage(
+            "PetModule",
+            "ScheduledCare",
+            vec![Value::from_bytes(&account), action.value()],
+        );
+        if let Some(value) = storage.fetch(&address).await? {
+            schedules.push((action, CareSchedule::decode(&mut value.encoded())?));
+        }
+    }
+    Ok(Schedules {
+        block: block.number(),
+        schedules,
+    })
+}
+
+fn start_load(commands: &mut Commands, client: &mut ChainClient) {
+    let (sender, receiver) = mpsc::channel();
+    match client.api() {
+        Ok(api) => {
+            let account = client.account_id();
+            client.spawn(async move {
+                let schedules = load_schedules(api, account).await;
+                let _ = sender.send(schedules.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(ScheduleLoad(Mutex::new(receiver)));
+}
+
+fn button(parent: &mut ChildBuilder, text_style: &TextStyle, label: &str, action: ScheduleAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn schedule_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let row = Style {
+        align_items: AlignItems::Center,
+        gap: Size::all(Val::Px(10.0)),
+        ..default()
+    };
+
+    start_load(&mut commands, &mut client);
+    commands.remove_resource::<Schedules>();
+    commands.insert_resource(Draft::default());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(15.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnScheduleScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Care scheduled on chain, done even while the game is closed",
+                text_style.clone(),
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ScheduleArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the schedule...",
+                        text_style.clone(),
+                    ));
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: row.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for action in CareAction::ALL {
+                        button(
+                            parent,
+                            &text_style,
+                            action.variant(),
+                            ScheduleAction::Pick(action),
+                        );
+                    }
+                    button(parent, &text_style, "-1 h", ScheduleAction::Sooner);
+                    button(parent, &text_style, "+1 h", ScheduleAction::Later);
+                    for every_hours in REPEATS {
+                        button(
+                            parent,
+                            &text_style,
+                            &repeat_label(every_hours),
+                            ScheduleAction::Repeat(every_hours),
+                        );
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: row.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(String::new(), text_style.clone()),
+                        DraftText,
+                    ));
+                    button(parent, &text_style, "Schedule", ScheduleAction::Schedule);
+                });
+            button(parent, &text_style, "Back to Main", ScheduleAction::Back);
+        });
+}
+
+// Read the schedules again once one of the player's changes or runs
+fn schedule_events(
+    mut commands: Commands,
+    mut events: EventReader<ChainEvent>,
+    mut client: ResMut<ChainClient>,
+) {
+    let account = client.account_id();
+    let ours = events.iter().any(|ChainEvent(event)| {
+        event.pallet_name() == "PetModule"
+            && matches!(
+                event.variant_name(),
+                "CareScheduled" | "CareCancelled" | "ScheduledCareRan"
+            )
+            && AccountId32::decode(&mut event.field_bytes()).map_or(false, |owner| owner == account)
+    });
+    if ours {
+        start_load(&mut commands, &mut client);
+    }
+}
+
+fn receive_schedules(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load: Option<Res<ScheduleLoad>>,
+    area: Query<Entity, With<ScheduleArea>>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<ScheduleLoad>();
+    match result {
+        Ok(schedules) => commands.insert_resource(schedules),
+        Err(e) => {
+            let text_style = TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 24.0,
+                color: TEXT_COLOR,
+            };
+            for area in &area {
+                commands
+                    .entity(area)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Can't read the schedule: {e}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+        }
+    }
+}
+
+// One line per care action, when it runs next and how often, with a button to cancel it
+fn show_schedules(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    schedules: Option<Res<Schedules>>,
+    clock: Res<ChainClock>,
+    area: Query<Entity, With<ScheduleArea>>,
+) {
+    let Some(schedules) = schedules else {
+        return;
+    };
+    if !schedules.is_changed() {
+        return;
+    }
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for action in CareAction::ALL {
+                let schedule = schedules
+                    .schedules
+                    .iter()
+                    .find(|(scheduled, _)| *scheduled == action)
+                    .map(|(_, schedule)| schedule);
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        let Some(schedule) = schedule else {
+                            parent.spawn(TextBundle::from_section(
+                                format!("{}: not scheduled", action.variant()),
+                                text_style.clone(),
+                            ));
+                            return;
+                        };
+                        let every = schedule
+                            .every
+                            .map(|every| repeat_label(Some(every / BLOCKS_PER_HOUR)));
+                        parent.spawn(TextBundle::from_section(
+                            format!(
+                                "{}: next {}, {}",
+                                action.variant(),
+                                clock.block(schedule.next),
+                                every.unwrap_or_else(|| repeat_label(None)),
+                            ),
+                            text_style.clone(),
+                        ));
+                        button(
+                            parent,
+                            &text_style,
+                            "Cancel",
+                            ScheduleAction::Cancel(action),
+                        );
+                    });
+            }
+        });
+}
+
+fn schedule_action(
+    interaction_query: Query<(&Interaction, &ScheduleAction), Changed<Interaction>>,
+    schedules: Option<Res<Schedules>>,
+    mut draft: ResMut<Draft>,
+    mut queue: ResMut<TxQueue>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match *action {
+            ScheduleAction::Pick(action) => draft.action = action,
+            ScheduleAction::Later => draft.in_hours = (draft.in_hours + 1).min(MAX_START_HOURS),
+            ScheduleAction::Sooner => draft.in_hours = draft.in_hours.saturating_sub(1).max(1),
+            ScheduleAction::Repeat(every_hours) => draft.every_hours = every_hours,
+            ScheduleAction::Schedule => {
+                // Counted from the latest block read, it's at least an hour ahead
+                let Some(schedules) = &schedules else {
+                    continue;
+                };
+                let when = schedules.block + draft.in_hours * BLOCKS_PER_HOUR;
+                let every = draft
+                    .every_hours
+                    .map(|hours| Value::u128((hours * BLOCKS_PER_HOUR).into()));
+                queue.push(
+                    format!(
+                        "Schedule {} {}",
+                        draft.action.variant().to_lowercase(),
+                        repeat_label(draft.every_hours)
+                    ),
+                    subxt::dynamic::tx(
+                        "PetModule",
+                        "schedule_care",
+                        vec![
+                            draft.action.value(),
+                            Value::u128(when.into()),
+                            match every {
+                                Some(every) => Value::unnamed_variant("Some", [every]),
+                                None => Value::unnamed_variant("None", []),
+                            },
+                        ],
+                    ),
+                );
+            }
+            ScheduleAction::Cancel(action) => {
+                queue.push(
+                    format!("Cancel {} schedule", action.variant().to_lowercase()),
+                    subxt::dynamic::tx("PetModule", "cancel_care", vec![action.value()]),
+                );
+            }
+            ScheduleAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// What the Schedule button would schedule
+fn show_draft(
+    draft: Res<Draft>,
+    schedules: Option<Res<Schedules>>,
+    clock: Res<ChainClock>,
+    mut text: Query<&mut Text, With<DraftText>>,
+) {
+    let loaded = schedules
+        .as_ref()
+        .map_or(false, |schedules| schedules.is_changed());
+    if !draft.is_changed() && !loaded {
+        return;
+    }
+    // The time tells how far ahead it is, before the schedules are read only the hours can
+    let when = schedules.map_or(format!("in {} h", draft.in_hours), |schedules| {
+        clock.block(schedules.block + draft.in_hours * BLOCKS_PER_HOUR)
+    });
+    for mut text in &mut text {
+        text.sections[0].value = format!(
+            "{} {when}, {}",
+            draft.action.variant(),
+            repeat_label(draft.every_hours)
+        );
+    }
+}
+
+// Highlight the choices of the schedule being made
+fn color_choices(
+    draft: Res<Draft>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &ScheduleAction, &mut BackgroundColor)>,
+) {
+    for (interaction, action, mut color) in &mut buttons {
+        if !draft.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = match action {
+            ScheduleAction::Pick(action) => *action == draft.action,
+            ScheduleAction::Repeat(every_hours) => *every_hours == draft.every_hours,
+            _ => false,
+        };
+        *color = palette.button(*interaction, selected).into();
+    }
+}
@@ -0,0 +1,406 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+
+use super::{
+    client::{ChainClient, PetId},
+    despawn_screen,
+    game::{PlayerSprite, Spectating},
+    pet_watch::PlayerPet,
+    poll::{PollScheduler, Priority},
+    tooltip::CallHelp,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// How often the events and the pet's cosmetics are read again while playing
+const REFRESH_SECONDS: f32 = 12.0;
+
+// Events end on block numbers, shown in hours of 6 second blocks
+const BLOCKS_PER_HOUR: u32 = 600;
+
+// The cosmetics of the pet pallet, in the order of its `Cosmetic` enum, and their names
+pub const COSMETICS: [(&str, &str); 4] = [
+    ("SnowHat", "Snow hat"),
+    ("PumpkinMask", "Pumpkin mask"),
+    ("FlowerCrown", "Flower crown"),
+    ("PartyHat", "Party hat"),
+];
+
+// This plugin shows a banner on the game screen while a seasonal event runs, with a button to
+// claim its cosmetic for the player's pet, and the pet's wardrobe to put one on. The cosmetic
+// the pet wears is drawn over its sprite.
+pub struct SeasonalPlugin;
+
+impl Plugin for SeasonalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wardrobe>()
+            .add_systems((
+                banner_setup.in_schedule(OnEnter(GameState::Game)),
+                despawn_screen::<OnSeasonalBanner>.in_schedule(OnExit(GameState::Game)),
+            ))
+            .add_systems(
+                (refresh_seasonal, seasonal_action, dress_pet).in_set(OnUpdate(GameState::Game)),
+            );
+    }
+}
+
+// Mirror of `SeasonalEvent` of the pet pallet
+#[derive(Decode)]
+struct SeasonalEvent {
+    starts: u32,
+    ends: u32,
+}
+
+// The cosmetics of the player's pet, as last read
+#[derive(Resource, Default)]
+struct Wardrobe {
+    owned: Vec<usize>,
+    // Index in `COSMETICS` of the one it wears
+    equipped: Option<usize>,
+}
+
+// Tag component used to tag the seasonal banner
+#[derive(Component)]
+struct OnSeasonalBanner;
+
+// Shapes drawn over the pet sprite for the cosmetic it wears
+#[derive(Component)]
+struct CosmeticShape;
+
+#[derive(Component)]
+enum SeasonalAction {
+    Claim(usize),
+    Wear(Option<usize>),
+}
+
+fn cosmetic_value(cosmetic: usize) -> Value {
+    Value::unnamed_variant(COSMETICS[cosmetic].0, [])
+}
+
+// The events running now with the blocks they have left, and the pet's cosmetics
+fn load_seasonal(
+    client: &mut ChainClient,
+    pet_id: PetId,
+) -> Result<(Vec<(usize, u32)>, Wardrobe), subxt::Error> {
+    let api = client.api()?;
+    client.block_on(async {
+        let block = api.blocks().at_latest().await?;
+        let now = block.number();
+        let storage = api.storage().at(block.hash());
+        let mut running = Vec::new();
+        let mut owned = Vec::new();
+        for cosmetic in 0..COSMETICS.len() {
+            let event = storage
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "SeasonalEvents",
+                    vec![cosmetic_value(cosmetic)],
+                ))
+                .await?
+                .map(|value| SeasonalEvent::decode(&mut value.encoded()))
+                .transpose()?;
+            if let Some(event) = event.filter(|event| event.starts <= now && now < event.ends) {
+                running.push((cosmetic, event.ends - now));
+            }
+            let claimed = storage
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "PetCosmetics",
+                    vec![Value::u128(pet_id.into()), cosmetic_value(cosmetic)],
+                ))
+                .await?;
+            if claimed.is_some() {
+                owned.push(cosmetic);
+            }
+        }
+        let equipped = storage
+            .fetch(&subxt::dynamic::storage(
+                "PetModule",
+                "EquippedCosmetic",
+                vec![Value::u128(pet_id.into())],
+            ))
+            .await?
+            .map(|value| u8::decode(&mut value.encoded()))
+            .transpose()?
+            .map(usize::from)
+            .filter(|cosmetic| *cosmetic < COSMETICS.len());
+        Ok((running, Wardrobe { owned, equipped }))
+    })
+}
+
+fn banner_setup(mut commands: Commands, spectating: Res<Spectating>) {
+    if spectating.0.is_some() {
+        return;
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                gap: Size::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        },
+        OnSeasonalBanner,
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    text_style: &TextStyle,
+    bundle: impl Bundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            bundle,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+// Rebuild the banner when the game screen opens, then every `REFRESH_SECONDS`
+fn refresh_seasonal(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    mut wardrobe: ResMut<Wardrobe>,
+    banner: Query<Entity, With<OnSeasonalBanner>>,
+    mut scheduler: ResMut<PollScheduler>,
+) {
+    let Ok(banner) = banner.get_single() else {
+        scheduler.forget("seasonal");
+        return;
+    };
+    let Some(pet) = &player_pet.pet else {
+        return;
+    };
+    if !scheduler.ready("seasonal", Priority::Normal, REFRESH_SECONDS) {
+        return;
+    }
+
+    let (running, loaded) = match load_seasonal(&mut client, pet.id) {
+        Ok(seasonal) => seasonal,
+        Err(e) => {
+            error!("error reading seasonal events: {e:?}");
+            return;
+        }
+    };
+    if (&loaded.owned, loaded.equipped) != (&wardrobe.owned, wardrobe.equipped) {
+        *wardrobe = loaded;
+    }
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    let row_style = Style {
+        align_items: AlignItems::Center,
+        gap: Size::all(Val::Px(8.0)),
+        ..default()
+    };
+    commands.entity(banner).despawn_descendants();
+    commands.entity(banner).with_children(|parent| {
+        for (cosmetic, blocks_left) in running {
+            let hours = blocks_left / BLOCKS_PER_HOUR;
+            let left = if hours >= 24 {
+                format!("{}d {}h left", hours / 24, hours % 24)
+            } else {
+                format!("{hours}h left")
+            };
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    background_color: Color::rgba(0.5, 0.1, 0.4, 0.8).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Limited time: {} ({left})", COSMETICS[cosmetic].1),
+                        text_style.clone(),
+                    ));
+                    if wardrobe.owned.contains(&cosmetic) {
+                        parent.spawn(TextBundle::from_section("Claimed", text_style.clone()));
+                    } else {
+                        spawn_button(
+                            parent,
+                            "Claim",
+                            &text_style,
+                            (
+                                SeasonalAction::Claim(cosmetic),
+                                CallHelp::pet("claim_seasonal_cosmetic"),
+                            ),
+                        );
+                    }
+                });
+        }
+        if wardrobe.owned.is_empty() {
+            return;
+        }
+        parent
+            .spawn(NodeBundle {
+                style: row_style.clone(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section("Wear", text_style.clone()));
+                for &cosmetic in &wardrobe.owned {
+                    if wardrobe.equipped != Some(cosmetic) {
+                        spawn_button(
+                            parent,
+                            COSMETICS[cosmetic].1,
+                            &text_style,
+                            (
+                                SeasonalAction::Wear(Some(cosmetic)),
+                                CallHelp::pet("equip_cosmetic"),
+                            ),
+                        );
+                    }
+                }
+                if wardrobe.equipped.is_some() {
+                    spawn_button(
+                        parent,
+                        "Nothing",
+                        &text_style,
+                        (SeasonalAction::Wear(None), CallHelp::pet("equip_cosmetic")),
+                    );
+                }
+            });
+    });
+}
+
+fn seasonal_action(
+    interaction_query: Query<(&Interaction, &SeasonalAction), Changed<Interaction>>,
+    mut queue: ResMut<TxQueue>,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+) {
+    let pet = player_pet.pet.as_ref().map(|pet| pet.id);
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match *action {
+            SeasonalAction::Claim(cosmetic) => queue.push_pet_call(
+                &client,
+                pet,
+                format!("Claim the {}", COSMETICS[cosmetic].1),
+                "claim_seasonal_cosmetic",
+                vec![cosmetic_value(cosmetic)],
+            ),
+            SeasonalAction::Wear(Some(cosmetic)) => queue.push_pet_call(
+                &client,
+                pet,
+                format!("Wear the {}", COSMETICS[cosmetic].1),
+                "equip_cosmetic",
+                vec![Value::unnamed_variant("Some", [cosmetic_value(cosmetic)])],
+            ),
+            SeasonalAction::Wear(None) => queue.push_pet_call(
+                &client,
+                pet,
+                "Take off the cosmetic",
+                "equip_cosmetic",
+                vec![Value::unnamed_variant("None", [])],
+            ),
+        };
+    }
+}
+
+// Shapes of a cosmetic, with their color and place over the sprite's center
+fn cosmetic_shapes(cosmetic: usize) -> Vec<(Mesh, Color, Vec2)> {
+    let hat = |color: Color| {
+        vec![
+            (
+                shape::RegularPolygon::new(40.0, 3).into(),
+                color,
+                Vec2::new(0.0, 110.0),
+            ),
+            (
+                shape::Circle::new(10.0).into(),
+                Color::WHITE,
+                Vec2::new(0.0, 150.0),
+            ),
+        ]
+    };
+    match COSMETICS[cosmetic].0 {
+        "SnowHat" => hat(Color::rgb(0.8, 0.1, 0.1)),
+        "PumpkinMask" => vec![(
+            shape::Circle::new(45.0).into(),
+            Color::rgba(1.0, 0.5, 0.0, 0.9),
+            Vec2::new(0.0, 50.0),
+        )],
+        "FlowerCrown" => [-40.0, -20.0, 0.0, 20.0, 40.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let color = if i % 2 == 0 {
+                    Color::PINK
+                } else {
+                    Color::YELLOW
+                };
+                (
+                    shape::Circle::new(10.0).into(),
+                    color,
+                    Vec2::new(x, 100.0 + 6.0 * (i as f32 - 2.0).abs()),
+                )
+            })
+            .collect(),
+        _ => hat(Color::rgb(0.2, 0.4, 0.9)),
+    }
+}
+
+// Draw the cosmetic the pet wears over its sprite, again when it changes or the game screen
+// opens
+fn dress_pet(
+    mut commands: Commands,
+    wardrobe: Res<Wardrobe>,
+    sprites: Query<(Entity, Ref<PlayerSprite>)>,
+    shapes: Query<Entity, With<CosmeticShape>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Ok((sprite, added)) = sprites.get_single() else {
+        return;
+    };
+    if !wardrobe.is_changed() && !added.is_added() {
+        return;
+    }
+    for shape in &shapes {
+        commands.entity(shape).despawn_recursive();
+    }
+    let Some(cosmetic) = wardrobe.equipped else {
+        return;
+    };
+    commands.entity(sprite).with_children(|parent| {
+        for (mesh, color, offset) in cosmetic_shapes(cosmetic) {
+            parent.spawn((
+                MaterialMesh2dBundle {
+                    mesh: meshes.add(mesh).into(),
+                    material: materials.add(ColorMaterial::from(color)),
+                    transform: Transform::from_translation(offset.extend(1.0)),
+                    ..default()
+                },
+                CosmeticShape,
+            ));
+        }
+    });
+}
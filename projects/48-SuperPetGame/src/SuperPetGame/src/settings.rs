@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{address_book::AddressBook, sounds::Muted, telemetry::ErrorReports};
+
+// Accessibility settings, the consent to error reports, whether pets are muted and the
+// address book, chosen on the settings screen and kept across restarts
+const SETTINGS_FILE: &str = "settings.json";
+
+// Range of the UI scale slider
+pub const MIN_UI_SCALE: f64 = 0.75;
+pub const MAX_UI_SCALE: f64 = 1.5;
+
+// Smallest button size with large hit targets, comfortable to hit with a finger
+const TOUCH_TARGET: f32 = 48.0;
+
+// This plugin holds the accessibility settings: the scale of the whole UI, the colors of
+// buttons and stat bars, and larger buttons for touch screens
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let saved = SavedSettings::load();
+        app.insert_resource(UiScale {
+            scale: saved.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE),
+        })
+        .insert_resource(saved.palette)
+        .insert_resource(LargeTargets(saved.large_targets))
+        .insert_resource(ErrorReports(saved.error_reports))
+        .insert_resource(Muted(saved.muted))
+        .insert_resource(saved.address_book)
+        .add_systems((enlarge_buttons, save_settings));
+    }
+}
+
+// Colors of buttons and stat bars
+#[derive(
+    Resource, Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug,
+)]
+pub enum Palette {
+    #[default]
+    Standard,
+    HighContrast,
+    // Okabe-Ito colors, told apart with any kind of color blindness
+    Colorblind,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Standard,
+        Palette::HighContrast,
+        Palette::Colorblind,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::HighContrast => "High contrast",
+            Palette::Colorblind => "Colorblind",
+        }
+    }
+
+    // Background of a button, `selected` for the current choice of an option
+    pub fn button(self, interaction: Interaction, selected: bool) -> Color {
+        let (normal, hovered, hovered_selected, selected_color) = match self {
+            Palette::Standard => (
+                Color::rgb(0.15, 0.15, 0.15),
+                Color::rgb(0.25, 0.25, 0.25),
+                Color::rgb(0.25, 0.65, 0.25),
+                Color::rgb(0.35, 0.75, 0.35),
+            ),
+            Palette::HighContrast => (
+                Color::BLACK,
+                Color::rgb(0.35, 0.35, 0.35),
+                Color::rgb(0.0, 0.2, 0.6),
+                Color::rgb(0.0, 0.3, 0.9),
+            ),
+            Palette::Colorblind => (
+                Color::rgb(0.15, 0.15, 0.15),
+                Color::rgb(0.25, 0.25, 0.25),
+                Color::rgb(0.0, 0.35, 0.55),
+                Color::rgb(0.0, 0.45, 0.7),
+            ),
+        };
+        match (interaction, selected) {
+            (Interaction::Clicked, _) | (Interaction::None, true) => selected_color,
+            (Interaction::Hovered, true) => hovered_selected,
+            (Interaction::Hovered, false) => hovered,
+            (Interaction::None, false) => normal,
+        }
+    }
+
+    // Colors of the food and sleep bars, and the legend naming them
+    pub fn stat_bars(self) -> (Color, Color, &'static str) {
+        match self {
+            Palette::Standard => (
+                Color::LIME_GREEN,
+                Color::ALICE_BLUE,
+                "green: food, blue: sleep",
+            ),
+            Palette::HighContrast => (Color::WHITE, Color::YELLOW, "white: food, yellow: sleep"),
+            Palette::Colorblind => (
+                Color::rgb(0.9, 0.6, 0.0),
+                Color::rgb(0.34, 0.71, 0.91),
+                "orange: food, sky blue: sleep",
+            ),
+        }
+    }
+}
+
+// Whether buttons are at least `TOUCH_TARGET` pixels wide and high
+#[derive(Resource, Component, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LargeTargets(pub bool);
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ui_scale: f64,
+    palette: Palette,
+    large_targets: bool,
+    // Off until the player turns it on, also for settings saved before it existed
+    #[serde(default)]
+    error_reports: bool,
+    #[serde(default)]
+    muted: bool,
+    #[serde(default)]
+    address_book: AddressBook,
+}
+
+impl SavedSettings {
+    fn load() -> Self {
+        std::fs::read(SETTINGS_FILE)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or(Self {
+                ui_scale: 1.0,
+                palette: Palette::Standard,
+                large_targets: false,
+                error_reports: false,
+                muted: false,
+                address_book: AddressBook::default(),
+            })
+    }
+}
+
+// Apply the minimum size to new buttons, or to all of them when the setting changes
+fn enlarge_buttons(large: Res<LargeTargets>, mut buttons: Query<(&mut Style, Ref<Button>)>) {
+    let min_size = if large.0 {
+        Size::all(Val::Px(TOUCH_TARGET))
+    } else {
+        Size::AUTO
+    };
+    for (mut style, button) in &mut buttons {
+        if (large.is_changed() || button.is_added()) && style.min_size != min_size {
+            style.min_size = min_size;
+        }
+    }
+}
+
+fn save_settings(
+    ui_scale: Res<UiScale>,
+    palette: Res<Palette>,
+    large: Res<LargeTargets>,
+    reports: Res<ErrorReports>,
+    muted: Res<Muted>,
+    address_book: Res<AddressBook>,
+) {
+    let changed = [
+        (ui_scale.is_changed(), ui_scale.is_added()),
+        (palette.is_changed(), palette.is_added()),
+        (large.is_changed(), large.is_added()),
+        (reports.is_changed(), reports.is_added()),
+        (muted.is_changed(), muted.is_added()),
+        (address_book.is_changed(), address_book.is_added()),
+    ];
+    if !changed.iter().any(|(changed, added)| *changed && !added) {
+        return;
+    }
+    let saved = SavedSettings {
+        ui_scale: ui_scale.scale,
+        palette: *palette,
+        large_targets: large.0,
+        error_reports: reports.0,
+        muted: muted.0,
+        address_book: address_book.clone(),
+    };
+    let written = serde_json::to_vec_pretty(&saved)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(SETTINGS_FILE, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        error!("can't save {SETTINGS_FILE}: {e}");
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use bevy::utils::HashMap;
+
+use super::assets::{known_species, GameAssets, Stage};
+
+// Where players drop their skins, in their profile unless `SUPERPET_SKINS_DIR` says otherwise
+const SKINS_DIR: &str = "skins";
+
+// How often the directory is checked for changed skins
+const CHECK_SECONDS: f32 = 2.0;
+
+// Size of the bundled sprites, in pixels. Sprites are drawn at their own size, so a skin of
+// another size wouldn't fit the screens.
+const SKIN_SIZE: u32 = 256;
+
+// This plugin draws pets with the skins players drop in the skins directory, community-made
+// sprites read at runtime: `<species>.png` draws the hatched pets of a species, and
+// `<species>-egg.png` their egg. A skin that isn't a PNG image of the size of the bundled
+// sprites is skipped with a warning, and the bundled sprite is drawn instead. The directory
+// is checked every few seconds, so a saved skin shows right away, and deleting it brings the
+// bundled sprite back.
+pub struct SkinsPlugin;
+
+impl Plugin for SkinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Skins::new())
+            .add_system(reload_skins.run_if(resource_exists::<GameAssets>()));
+    }
+}
+
+#[derive(Resource)]
+struct Skins {
+    dir: PathBuf,
+    timer: Timer,
+    // Whether the directory was read once, skins are read at startup without waiting
+    checked: bool,
+    // When each file of the directory was changed when it was last read, skins or not, so
+    // they're only read, or warned about, again once they change
+    read: HashMap<PathBuf, SystemTime>,
+}
+
+impl Skins {
+    fn new() -> Self {
+        Self {
+            dir: std::env::var_os("SUPERPET_SKINS_DIR")
+                .map_or_else(|| PathBuf::from(SKINS_DIR), PathBuf::from),
+            timer: Timer::from_seconds(CHECK_SECONDS, TimerMode::Repeating),
+            checked: false,
+            read: HashMap::default(),
+        }
+    }
+}
+
+// The species and stage a skin is for, from its file name
+fn skin_of(path: &Path) -> Option<(&'static str, Stage)> {
+    if path.extension()? != "png" {
+        return None;
+    }
+    let name = path.file_stem()?.to_str()?;
+    let (species, stage) = match name.strip_suffix("-egg") {
+        Some(species) => (species, Stage::Egg),
+        None => (name, Stage::Hatched),
+    };
+    Some((known_species(species)?, stage))
+}
+
+fn load_skin(path: &Path) -> Result<Image, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(|e| e.to_string())?;
+    let size = image.texture_descriptor.size;
+    if (size.width, size.height) != (SKIN_SIZE, SKIN_SIZE) {
+        return Err(format!(
+            "it's {}x{} pixels, skins are {SKIN_SIZE}x{SKIN_SIZE}",
+            size.width, size.height
+        ));
+    }
+    Ok(image)
+}
+
+// Read the skins that changed since the last check, and forget those deleted
+fn reload_skins(
+    time: Res<Time>,
+    mut skins: ResMut<Skins>,
+    mut assets: ResMut<GameAssets>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !skins.timer.tick(time.delta()).just_finished() && skins.checked {
+        return;
+    }
+    skins.checked = true;
+
+    // A missing directory has no skins
+    let found: HashMap<PathBuf, SystemTime> = std::fs::read_dir(&skins.dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .collect();
+
+    let deleted: Vec<PathBuf> = skins
+        .read
+        .keys()
+        .filter(|path| !found.contains_key(*path))
+        .cloned()
+        .collect();
+    for path in deleted {
+        skins.read.remove(&path);
+        if let Some((species, stage)) = skin_of(&path) {
+            info!("skin {} deleted", path.display());
+            assets.set_skin(species, stage, None, &mut images);
+        }
+    }
+
+    for (path, modified) in found {
+        if skins.read.insert(path.clone(), modified) == Some(modified) {
+            continue;
+        }
+        let Some((species, stage)) = skin_of(&path) else {
+            warn!(
+                "ignoring {}, skins are named <species>.png or <species>-egg.png",
+                path.display()
+            );
+            continue;
+        };
+        match load_skin(&path) {
+            Ok(image) => {
+                info!("skin {} loaded", path.display());
+                assets.set_skin(species, stage, Some(image), &mut images);
+            }
+            Err(e) => {
+                warn!("ignoring the skin {}: {e}", path.display());
+                assets.set_skin(species, stage, None, &mut images);
+            }
+        }
+    }
+}
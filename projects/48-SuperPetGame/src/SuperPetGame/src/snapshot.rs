@@ -0,0 +1,192 @@
+use std::path::Path;
+
+use pet_gene::Dna;
+use serde::{Deserialize, Serialize};
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::AccountId32;
+use thiserror::Error as ThisError;
+
+use super::client::{polkadot, ChainClient, PetId, PetVitals};
+use super::inventory::ITEMS;
+
+// File written by "Export Pet" and opened by the pet viewer
+pub const SNAPSHOT_FILE: &str = "pet-snapshot.json";
+
+#[derive(Debug, ThisError)]
+pub enum SnapshotError {
+    #[error("chain error: {0}")]
+    Chain(#[from] subxt::Error),
+    #[error("file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid snapshot: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("account has no pet")]
+    NoPet,
+}
+
+// Everything the chain knows about a pet, in a form that can be shared without chain access
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetSnapshot {
+    pub owner: String,
+    pub pet_id: PetId,
+    pub name: String,
+    pub species: String,
+    pub experience: u64,
+    pub last_feed_block: Option<u32>,
+    pub last_sleep_block: Option<u32>,
+    pub snapshot_block: u32,
+    // Missing in snapshots exported before pets had DNA
+    #[serde(default)]
+    pub dna: Option<Dna>,
+    // Badges of the owner and how many times they earned each, by item name
+    #[serde(default)]
+    pub badges: Vec<(String, u32)>,
+}
+
+impl PetSnapshot {
+    // Read the pet of `owner` from the latest block
+    pub fn fetch(client: &mut ChainClient, owner: &AccountId32) -> Result<Self, SnapshotError> {
+        let api = client.api()?;
+        client.block_on(async {
+            let block = api.blocks().at_latest().await?;
+            let storage = api.storage().at(block.hash());
+
+            let (pet_id, info) = storage
+                .fetch(&polkadot::storage().pet_module().pets_info(owner))
+                .await?
+                .ok_or(SnapshotError::NoPet)?;
+            let vitals = storage
+                .fetch(&PetVitals::address(pet_id))
+                .await?
+                .and_then(|value| PetVitals::decode(&mut value.encoded()).ok());
+            // Not in the bundled metadata yet, so query it dynamically
+            let experience = storage
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "Experience",
+                    vec![subxt::dynamic::Value::u128(pet_id as u128)],
+                ))
+                .await?
+                .map(|value| value.to_value())
+                .transpose()?
+                .and_then(|value| value.as_u128())
+                .unwrap_or_default() as u64;
+            let dna = storage
+                .fetch(&subxt::dynamic::storage(
+                    "PetModule",
+                    "PetDna",
+                    vec![subxt::dynamic::Value::u128(pet_id as u128)],
+                ))
+                .await?
+                .and_then(|value| Dna::decode(&mut value.encoded()).ok());
+            // Items are decoded as their index in `ITEMS`
+            let badges: Vec<(u8, u32)> = api
+                .rpc()
+                .state_call("PetApi_badges", Some(&pet_id.encode()), Some(block.hash()))
+                .await?;
+            let badges = badges
+                .into_iter()
+                .filter_map(|(item, count)| {
+                    ITEMS
+                        .get(item as usize)
+                        .map(|(name, _)| (name.to_string(), count))
+                })
+                .collect();
+
+            Ok::<_, SnapshotError>(Self {
+                owner: owner.to_string(),
+                pet_id,
+                name: String::from_utf8_lossy(&info.name.0).into_owned(),
+                species: format!("{:?}", info.species),
+                experience,
+                last_feed_block: vitals.map(|vitals| vitals.last_fed),
+                last_sleep_block: vitals.and_then(|vitals| vitals.last_slept),
+                snapshot_block: block.number(),
+                dna,
+                badges,
+            })
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    // Stats of the pet when it was exported, the same the chain computed at that block under
+    // the same rules
+    pub fn stats(&self, rules: &pet_core::Rules) -> pet_core::Stats {
+        self.care()
+            .stats(rules, pet_core::at_block(self.snapshot_block))
+    }
+
+    // Odds of the pet in a battle when it was exported
+    pub fn battle_weight(&self, rules: &pet_core::Rules) -> u64 {
+        self.care()
+            .battle_weight(rules, pet_core::at_block(self.snapshot_block))
+    }
+
+    fn care(&self) -> pet_core::Care {
+        pet_core::Care {
+            last_feed: self.last_feed_block,
+            last_sleep: self.last_sleep_block,
+            experience: self.experience,
+        }
+    }
+
+    // Traits encoded in the pet's DNA, the same the chain derives
+    pub fn traits(&self) -> Option<pet_gene::Traits> {
+        self.dna.as_ref().map(pet_gene::traits)
+    }
+
+    fn looks(&self) -> String {
+        match self.traits() {
+            Some(traits) => format!(
+                "{:?}, size {}%, tint #{:02x}{:02x}{:02x}",
+                traits.pattern, traits.size, traits.tint[0], traits.tint[1], traits.tint[2]
+            ),
+            None => "unknown".to_string(),
+        }
+    }
+
+    fn badges(&self) -> String {
+        if self.badges.is_empty() {
+            return "none yet".to_string();
+        }
+        self.badges
+            .iter()
+            .map(|(name, count)| {
+                let name = name.trim_end_matches("Badge");
+                match count {
+                    1 => name.to_string(),
+                    _ => format!("{name} x{count}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Lines shown by the pet viewer
+    pub fn describe(&self) -> Vec<String> {
+        let block = |b: Option<u32>| b.map_or("never".to_string(), |b| format!("block #{b}"));
+        vec![
+            format!("{} the {}", self.name, self.species),
+            format!("Pet Id      {}", self.pet_id),
+            format!("Owner       {}", self.owner),
+            format!(
+                "Experience  {} (level {})",
+                self.experience,
+                pet_core::level(self.experience)
+            ),
+            format!("Looks       {}", self.looks()),
+            format!("Badges      {}", self.badges()),
+            format!("Last fed    {}", block(self.last_feed_block)),
+            format!("Last slept  {}", block(self.last_sleep_block)),
+            format!("Exported at block #{}", self.snapshot_block),
+        ]
+    }
+}
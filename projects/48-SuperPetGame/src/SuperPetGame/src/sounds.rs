@@ -0,0 +1,264 @@
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::audio::{AddAudioSource, Decodable, Source};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashMap;
+
+use super::assets::{known_species, PET_SPECIES};
+
+// Sample rate of the synthesized sounds, plenty for chirps and squeaks
+const SAMPLE_RATE: u32 = 22_050;
+
+// Takes of each sound, told apart by their pitch
+const TAKES: u32 = 3;
+
+// This plugin gives each species a voice: turtles chirp, snakes hiss and rabbits squeak when
+// the player cares for their pet, and when its mood changes. The sound banks are synthesized
+// at startup and added to the audio assets, so they need no files, and each sound is played
+// from a random take at a slightly random speed and volume so it doesn't sound canned. Pet
+// sounds can be muted on the settings screen.
+pub struct SoundsPlugin;
+
+impl Plugin for SoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<PetVoice>()
+            .add_event::<PetCry>()
+            .add_startup_system(build_banks)
+            .add_system(play_cries);
+    }
+}
+
+// What a pet makes a sound about
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Cry {
+    // The player fed it or put it to sleep
+    Care,
+    // It's happy again
+    Happy,
+    // It got hungry or tired
+    Unhappy,
+}
+
+// Event making a pet of `species` make a sound
+pub struct PetCry {
+    pub species: String,
+    pub cry: Cry,
+}
+
+// Whether pet sounds are muted, chosen on the settings screen
+#[derive(Resource, Component, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Muted(pub bool);
+
+// A synthesized sound, mono samples at `SAMPLE_RATE`
+#[derive(TypeUuid)]
+#[uuid = "5d0f6a3e-8c1b-4e7a-9f42-1b6c3d8e2a71"]
+pub struct PetVoice {
+    samples: Arc<[f32]>,
+}
+
+pub struct PetVoiceDecoder {
+    samples: Arc<[f32]>,
+    next: usize,
+}
+
+impl Iterator for PetVoiceDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.next).copied();
+        self.next += 1;
+        sample
+    }
+}
+
+impl Source for PetVoiceDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.next))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+impl Decodable for PetVoice {
+    type DecoderItem = f32;
+    type Decoder = PetVoiceDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        PetVoiceDecoder {
+            samples: self.samples.clone(),
+            next: 0,
+        }
+    }
+}
+
+// How a species sounds
+struct Voice {
+    // Pitch at the start of a call, in Hz
+    pitch: f32,
+    // Length of a call, in seconds
+    length: f32,
+    // Pitch at the end of a call, relative to the start
+    sweep: f32,
+    // Share of noise in the sound, a hiss is all noise
+    noise: f32,
+    calls: u32,
+}
+
+impl Voice {
+    fn of(species: &str) -> Self {
+        match species {
+            "snake" => Voice {
+                pitch: 300.0,
+                length: 0.45,
+                sweep: 1.0,
+                noise: 0.9,
+                calls: 1,
+            },
+            "rabbit" => Voice {
+                pitch: 2800.0,
+                length: 0.12,
+                sweep: 0.75,
+                noise: 0.05,
+                calls: 1,
+            },
+            _ => Voice {
+                pitch: 2200.0,
+                length: 0.07,
+                sweep: 1.3,
+                noise: 0.0,
+                calls: 2,
+            },
+        }
+    }
+
+    // The voice making `cry`: higher and more calls when happy, lower, longer and falling
+    // when not
+    fn crying(self, cry: Cry) -> Self {
+        match cry {
+            Cry::Care => self,
+            Cry::Happy => Voice {
+                pitch: self.pitch * 1.2,
+                calls: self.calls + 1,
+                ..self
+            },
+            Cry::Unhappy => Voice {
+                pitch: self.pitch * 0.75,
+                length: self.length * 1.6,
+                sweep: self.sweep.min(1.0 / self.sweep),
+                ..self
+            },
+        }
+    }
+
+    fn synthesize(&self, rng: &mut Rng) -> Vec<f32> {
+        let call_len = (self.length * SAMPLE_RATE as f32) as usize;
+        let gap = call_len / 2;
+        let mut samples = Vec::with_capacity((call_len + gap) * self.calls as usize);
+        for _ in 0..self.calls {
+            let mut phase = 0.0;
+            for i in 0..call_len {
+                let progress = i as f32 / call_len as f32;
+                phase += TAU * self.pitch * self.sweep.powf(progress) / SAMPLE_RATE as f32;
+                let tone = phase.sin();
+                let noise = rng.fraction() * 2.0 - 1.0;
+                // Fade in and out, a cut sound clicks
+                let envelope = (PI * progress).sin();
+                samples.push(envelope * ((1.0 - self.noise) * tone + self.noise * noise) * 0.5);
+            }
+            samples.extend(std::iter::repeat(0.0).take(gap));
+        }
+        samples
+    }
+}
+
+// Xorshift, random enough to vary the sounds
+struct Rng(u32);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |now| now.subsec_nanos());
+        Self(seed | 1)
+    }
+
+    // A number in 0..1
+    fn fraction(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32
+    }
+}
+
+// The takes of each sound of each species
+#[derive(Resource)]
+struct SoundBanks {
+    banks: HashMap<(&'static str, Cry), Vec<Handle<PetVoice>>>,
+    rng: Rng,
+}
+
+fn build_banks(mut commands: Commands, mut voices: ResMut<Assets<PetVoice>>) {
+    let mut rng = Rng::new();
+    let mut banks = HashMap::default();
+    for species in PET_SPECIES {
+        for cry in [Cry::Care, Cry::Happy, Cry::Unhappy] {
+            let takes = (0..TAKES)
+                .map(|take| {
+                    let voice = Voice::of(species).crying(cry);
+                    let voice = Voice {
+                        pitch: voice.pitch * (1.0 + 0.04 * (take as f32 - 1.0)),
+                        ..voice
+                    };
+                    voices.add(PetVoice {
+                        samples: voice.synthesize(&mut rng).into(),
+                    })
+                })
+                .collect();
+            banks.insert((species, cry), takes);
+        }
+    }
+    commands.insert_resource(SoundBanks { banks, rng });
+}
+
+fn play_cries(
+    mut cries: EventReader<PetCry>,
+    muted: Res<Muted>,
+    mut banks: ResMut<SoundBanks>,
+    audio: Res<Audio<PetVoice>>,
+) {
+    if muted.0 {
+        cries.clear();
+        return;
+    }
+    let SoundBanks { banks, rng } = &mut *banks;
+    for cry in cries.iter() {
+        let Some(takes) =
+            known_species(&cry.species).and_then(|species| banks.get(&(species, cry.cry)))
+        else {
+            continue;
+        };
+        let take = &takes[(rng.fraction() * takes.len() as f32) as usize % takes.len()];
+        audio.play_with_settings(
+            take.clone(),
+            PlaybackSettings::ONCE
+                .with_speed(0.9 + 0.2 * rng.fraction())
+                .with_volume(0.6 + 0.2 * rng.fraction()),
+        );
+    }
+}
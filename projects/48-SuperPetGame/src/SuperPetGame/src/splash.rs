@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::{despawn_screen, GameState};
+use super::{assets::GameAssets, despawn_screen, onboarding::Onboarding, GameState};
 
 // This plugin will display a splash screen with Bevy logo for 1 second before switching to the menu
 pub struct SplashPlugin;
@@ -57,13 +57,21 @@ fn splash_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(SplashTimer(Timer::from_seconds(1.0, TimerMode::Once)));
 }
 
-// Tick the timer, and change state when finished
+// Tick the timer, and change state when finished and the shared assets are loaded. New
+// players go through the onboarding wizard first.
 fn countdown(
     mut game_state: ResMut<NextState<GameState>>,
     time: Res<Time>,
     mut timer: ResMut<SplashTimer>,
+    onboarding: Res<Onboarding>,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
 ) {
-    if timer.tick(time.delta()).finished() {
-        game_state.set(GameState::Menu);
+    if timer.tick(time.delta()).finished() && assets.ready(&asset_server) {
+        game_state.set(if onboarding.finished() {
+            GameState::Menu
+        } else {
+            GameState::Onboarding
+        });
     }
 }
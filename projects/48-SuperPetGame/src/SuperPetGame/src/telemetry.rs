@@ -0,0 +1,205 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::utils::tracing::{Event, Level, Subscriber};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{layer::Context, Layer};
+
+use super::{client::ChainClient, logging::Fields};
+
+// Every report, kept on the player's machine
+const REPORTS_FILE: &str = "error-reports.jsonl";
+
+// The report of a crash, sent on the next start since the game is gone by then
+const CRASH_FILE: &str = "crash-report.json";
+
+// Whether the player agreed to reports, kept in sync with `ErrorReports` for the panic hook
+// and the log subscriber, which can't read resources
+static CONSENT: AtomicBool = AtomicBool::new(false);
+
+// Reports waiting to be uploaded
+static PENDING: Mutex<Vec<Report>> = Mutex::new(Vec::new());
+
+// This plugin records crashes and errors, chain call failures included, once the player
+// agreed to it in the settings. Reports are written to `REPORTS_FILE`, and also uploaded when
+// `SUPERPET_REPORT_URL` is set, to help triage issues players run into. Secrets and accounts
+// are removed from them first.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((sync_consent, upload_reports).chain());
+    }
+}
+
+// Whether crashes and errors are reported, chosen on the settings screen
+#[derive(Resource, Component, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ErrorReports(pub bool);
+
+#[derive(Serialize, Deserialize)]
+struct Report {
+    // Seconds since the Unix epoch
+    time: u64,
+    kind: ReportKind,
+    message: String,
+    version: String,
+    os: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ReportKind {
+    Panic,
+    Error,
+}
+
+impl Report {
+    fn new(kind: ReportKind, message: &str) -> Self {
+        Self {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            kind,
+            message: sanitize(message),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+        }
+    }
+
+    // Append the report to `REPORTS_FILE`. Reporting can't log its own failures, they would
+    // be reported again
+    fn save(&self) {
+        let Ok(json) = serde_json::to_string(self) else {
+            return;
+        };
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(REPORTS_FILE);
+        if let Ok(mut file) = file {
+            let _ = writeln!(file, "{json}");
+        }
+    }
+}
+
+// Replace what could identify the player or give their account away: secret URIs, mnemonic
+// phrases, hex seeds and account addresses. Block and transaction hashes go with the seeds,
+// they can't be told apart, and so would a sentence of 12 lowercase words.
+fn sanitize(message: &str) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    // Lowercase words in a row, a mnemonic phrase has at least 12
+    let mut phrase = 0;
+    for word in message.split_whitespace() {
+        let bare = word.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '/');
+        let hex = bare.strip_prefix("0x").unwrap_or(bare);
+        let redacted = if bare.contains("//") {
+            Some("<secret uri>")
+        } else if hex.len() >= 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some("<hex>")
+        } else if (46..=48).contains(&bare.len()) && bare.chars().all(is_base58) {
+            Some("<account>")
+        } else {
+            None
+        };
+        phrase = if bare.chars().all(|c| c.is_ascii_lowercase()) && !bare.is_empty() {
+            phrase + 1
+        } else {
+            0
+        };
+        if phrase == 12 {
+            words.truncate(words.len() - 11);
+            words.push("<phrase>");
+        } else if phrase < 12 {
+            words.push(redacted.unwrap_or(word));
+        }
+    }
+    words.join(" ")
+}
+
+fn is_base58(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+// Record panics before the default hook prints them, and queue the report of the last run's
+// crash. Call it once at startup.
+pub fn init() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if CONSENT.load(Ordering::Relaxed) {
+            let report = Report::new(ReportKind::Panic, &info.to_string());
+            report.save();
+            if let Ok(json) = serde_json::to_vec(&report) {
+                let _ = std::fs::write(CRASH_FILE, json);
+            }
+        }
+        default_hook(info);
+    }));
+
+    let crash = std::fs::read(CRASH_FILE)
+        .ok()
+        .and_then(|json| serde_json::from_slice::<Report>(&json).ok());
+    if let Some(crash) = crash {
+        let _ = std::fs::remove_file(CRASH_FILE);
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.push(crash);
+        }
+    }
+}
+
+// Reports the errors logged while the player agrees to it
+pub struct ReportLayer;
+
+impl<S: Subscriber> Layer<S> for ReportLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR || !CONSENT.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let report = Report::new(ReportKind::Error, &fields.0);
+        report.save();
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.push(report);
+        }
+    }
+}
+
+fn sync_consent(reports: Res<ErrorReports>) {
+    if reports.is_changed() {
+        CONSENT.store(reports.0, Ordering::Relaxed);
+    }
+}
+
+// Send the pending reports in the background, if an endpoint is set and the player agrees
+fn upload_reports(client: Res<ChainClient>, reports: Res<ErrorReports>) {
+    // Taken out at once, errors logged while the lock is held would wait for it forever
+    let Ok(batch) = PENDING
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+    else {
+        return;
+    };
+    if batch.is_empty() {
+        return;
+    }
+    let Ok(url) = std::env::var("SUPERPET_REPORT_URL") else {
+        return;
+    };
+    if !reports.0 {
+        return;
+    }
+    client.spawn(async move {
+        let sent = reqwest::Client::new()
+            .post(&url)
+            .json(&batch)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = sent {
+            warn!("can't upload error reports: {e}");
+        }
+    });
+}
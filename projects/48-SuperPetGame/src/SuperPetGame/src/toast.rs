@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use super::TEXT_COLOR;
+
+// How long a toast stays on screen
+const TOAST_SECONDS: f32 = 4.0;
+
+// This plugin shows short notifications at the bottom of the window, over every screen
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_startup_system(toast_area_setup)
+            .add_systems((show_toasts, expire_toasts));
+    }
+}
+
+// Message to show to the player for a few seconds
+pub struct Toast(pub String);
+
+// Column holding the toasts currently displayed
+#[derive(Component)]
+struct ToastArea;
+
+#[derive(Component, Deref, DerefMut)]
+struct ToastTimer(Timer);
+
+fn toast_area_setup(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                gap: Size::all(Val::Px(6.0)),
+                ..default()
+            },
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        ToastArea,
+    ));
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut toasts: EventReader<Toast>,
+    area: Query<Entity, With<ToastArea>>,
+) {
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    for Toast(message) in toasts.iter() {
+        let toast = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+                    ..default()
+                },
+                ToastTimer(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    message,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                    },
+                ));
+            })
+            .id();
+        commands.entity(area).add_child(toast);
+    }
+}
+
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut ToastTimer)>,
+) {
+    for (entity, mut timer) in &mut toasts {
+        if timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use subxt::{ext::codec::Decode, Metadata};
+
+use super::{client::ChainClient, txqueue::Locked, TEXT_COLOR};
+
+// Metadata the client was generated against, used when the node can't be reached
+const BUNDLED_METADATA: &[u8] = include_bytes!("../metadata.scale");
+
+// This plugin shows the documentation of a pallet call when hovering a button that submits it.
+// The docs come from the runtime metadata, so the in-game help follows the chain's behavior.
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CallDocs>()
+            .add_startup_system(load_call_docs)
+            .add_system(call_help_system);
+    }
+}
+
+// Component marking a button that submits the given pallet call
+#[derive(Component, Clone, Copy)]
+pub struct CallHelp {
+    pub pallet: &'static str,
+    pub call: &'static str,
+}
+
+impl CallHelp {
+    pub const fn pet(call: &'static str) -> Self {
+        Self {
+            pallet: "PetModule",
+            call,
+        }
+    }
+}
+
+// Documentation of every pallet call, keyed by (pallet, call)
+#[derive(Resource, Default)]
+pub struct CallDocs(HashMap<(String, String), String>);
+
+impl CallDocs {
+    fn from_metadata(metadata: &Metadata) -> Self {
+        let mut docs = HashMap::new();
+        for pallet in metadata.pallets() {
+            for call in pallet.call_variants().unwrap_or_default() {
+                let text = call
+                    .docs
+                    .iter()
+                    .map(|line| line.trim())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                docs.insert((pallet.name().to_string(), call.name.clone()), text);
+            }
+        }
+        Self(docs)
+    }
+
+    pub fn get(&self, pallet: &str, call: &str) -> Option<&str> {
+        self.0
+            .get(&(pallet.to_string(), call.to_string()))
+            .map(String::as_str)
+            .filter(|docs| !docs.is_empty())
+    }
+}
+
+// Tag component used to tag the tooltip currently displayed
+#[derive(Component)]
+struct OnTooltip;
+
+// Read the call docs from the connected node, falling back to the bundled metadata
+fn load_call_docs(mut docs: ResMut<CallDocs>, mut client: ResMut<ChainClient>) {
+    let metadata = match client.api() {
+        Ok(api) => api.metadata(),
+        Err(e) => {
+            warn!("using bundled metadata for call docs: {e:?}");
+            match Metadata::decode(&mut &BUNDLED_METADATA[..]) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    error!("error decoding bundled metadata: {e:?}");
+                    return;
+                }
+            }
+        }
+    };
+    *docs = CallDocs::from_metadata(&metadata);
+}
+
+// Show the call docs next to the cursor while a `CallHelp` button is hovered, after the reason
+// the button is greyed out if it is
+fn call_help_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    docs: Res<CallDocs>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interaction_query: Query<(&Interaction, &CallHelp, Option<&Locked>), Changed<Interaction>>,
+    tooltips: Query<Entity, With<OnTooltip>>,
+) {
+    for (interaction, help, locked) in &interaction_query {
+        for entity in &tooltips {
+            commands.entity(entity).despawn_recursive();
+        }
+        if *interaction != Interaction::Hovered {
+            continue;
+        }
+        let text = match (locked, docs.get(help.pallet, help.call)) {
+            (Some(locked), Some(docs)) => format!("{}\n\n{docs}", locked.0),
+            (Some(locked), None) => locked.0.clone(),
+            (None, Some(docs)) => docs.to_string(),
+            (None, None) => continue,
+        };
+        let Ok(window) = windows.get_single() else {
+            continue;
+        };
+        let Some(cursor) = window.cursor_position() else {
+            continue;
+        };
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        // The cursor position starts from the bottom left of the window
+                        position: UiRect {
+                            left: Val::Px(cursor.x + 16.0),
+                            top: Val::Px(window.height() - cursor.y + 16.0),
+                            ..default()
+                        },
+                        max_size: Size::new(Val::Px(320.0), Val::Auto),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+                OnTooltip,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 16.0,
+                        color: TEXT_COLOR,
+                    },
+                ));
+            });
+    }
+}
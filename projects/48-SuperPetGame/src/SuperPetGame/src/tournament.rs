@@ -0,0 +1,841 @@
+use std::f32::consts::PI;
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::{
+    client::{format_balance, ChainClient, ChainEvent, PetId},
+    despawn_screen,
+    pet_profile::ProfileLink,
+    settings::Palette,
+    toast::Toast,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+type TournamentId = u32;
+
+const BLOCK_SECONDS: f32 = 6.0;
+
+// How long a match whose battle was just fought pulses
+const FLASH_SECONDS: f32 = 1.5;
+
+const MATCH_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const WINNER_COLOR: Color = Color::GOLD;
+const LOSER_COLOR: Color = Color::GRAY;
+
+// This plugin shows the tournaments signing up or running, read from the tournament runtime
+// api: the bracket of the chosen one, the player's next match with the time left before it,
+// and a button to enter while sign-up is open. The brackets are read again whenever a
+// tournament event is finalized, and the matches fought since the last read pulse in the
+// winner's color.
+pub struct TournamentPlugin;
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewedTournament>()
+            .add_systems((
+                tournament_setup.in_schedule(OnEnter(GameState::Tournament)),
+                despawn_screen::<OnTournamentScreen>.in_schedule(OnExit(GameState::Tournament)),
+            ))
+            .add_systems(
+                (
+                    tournament_events,
+                    receive_brackets.after(tournament_events),
+                    show_bracket.after(receive_brackets),
+                    next_match_countdown.after(show_bracket),
+                    flash_results,
+                    tournament_action,
+                    color_choices.after(tournament_action),
+                )
+                    .in_set(OnUpdate(GameState::Tournament)),
+            );
+    }
+}
+
+// Tag component used to tag entities added on the tournament screen
+#[derive(Component)]
+struct OnTournamentScreen;
+
+// Row of the buttons choosing the tournament shown
+#[derive(Component)]
+struct TournamentTabs;
+
+// Column of the status of the tournament shown
+#[derive(Component)]
+struct TournamentInfo;
+
+#[derive(Component)]
+struct BracketArea;
+
+#[derive(Component)]
+struct NextMatchText;
+
+// A match fought since the last read, pulsing until the timer ends
+#[derive(Component)]
+struct ResultFlash(Timer);
+
+#[derive(Component)]
+enum TournamentAction {
+    Show(TournamentId),
+    Enter(TournamentId, u128),
+    Back,
+}
+
+// Tournament shown, kept between visits
+#[derive(Resource, Default)]
+struct ViewedTournament(Option<TournamentId>);
+
+// Mirrors of the `Bracket` returned by the tournament runtime api and of its parts
+#[derive(Decode)]
+enum Status {
+    SignUp,
+    Running { round: u32 },
+    Finished { winner: PetId },
+    Cancelled,
+}
+
+#[derive(Decode)]
+struct Tournament {
+    entry_fee: u128,
+    _signup_ends: u32,
+    _round_interval: u32,
+    next_step_at: u32,
+    pool: u128,
+    status: Status,
+}
+
+#[derive(Decode)]
+struct Match {
+    first: PetId,
+    second: Option<PetId>,
+    winner: Option<PetId>,
+}
+
+#[derive(Decode)]
+struct Bracket {
+    tournament: Tournament,
+    entrants: Vec<(PetId, AccountId32)>,
+    rounds: Vec<Vec<Match>>,
+}
+
+// The tournaments shown and the chain head they were read at
+#[derive(Resource)]
+struct Brackets {
+    head: u32,
+    // `Time::elapsed_seconds` of the read, to count blocks down until the next one
+    read_at: f32,
+    brackets: Vec<(TournamentId, Bracket)>,
+    // Matches fought since the previous read, as tournament, round and match
+    fought: Vec<(TournamentId, usize, usize)>,
+}
+
+// Result of the background read of the brackets
+#[derive(Resource)]
+struct BracketLoad(Mutex<mpsc::Receiver<Result<(u32, Vec<(TournamentId, Bracket)>), String>>>);
+
+impl Bracket {
+    fn pet_of(&self, account: &AccountId32) -> Option<PetId> {
+        self.entrants
+            .iter()
+            .find(|(_, owner)| owner == account)
+            .map(|(pet_id, _)| *pet_id)
+    }
+
+    // Rounds fought until one pet is left, each one halving the pets
+    fn round_count(&self) -> usize {
+        let mut pets = self.entrants.len();
+        let mut rounds = 0;
+        while pets > 1 {
+            pets = (pets + 1) / 2;
+            rounds += 1;
+        }
+        rounds
+    }
+
+    fn round_name(&self, round: usize) -> String {
+        match self.round_count().saturating_sub(round) {
+            1 => "Final".to_string(),
+            2 => "Semi-final".to_string(),
+            _ => format!("Round {}", round + 1),
+        }
+    }
+
+    fn status(&self) -> String {
+        match self.tournament.status {
+            Status::SignUp => "signing up".to_string(),
+            Status::Running { round } => self.round_name(round as usize).to_lowercase(),
+            Status::Finished { winner } => format!("won by pet #{winner}"),
+            Status::Cancelled => "cancelled".to_string(),
+        }
+    }
+
+    // What's next for the player in the tournament, and the block it happens at
+    fn next_match(&self, me: &AccountId32) -> (String, Option<u32>) {
+        let tournament = &self.tournament;
+        let next_step = Some(tournament.next_step_at);
+        let Some(pet) = self.pet_of(me) else {
+            return match tournament.status {
+                Status::SignUp => ("Sign-up ends".to_string(), next_step),
+                Status::Running { round } => (
+                    format!(
+                        "Your pet isn't in it, the {} is fought",
+                        self.round_name(round as usize).to_lowercase()
+                    ),
+                    next_step,
+                ),
+                _ => (String::new(), None),
+            };
+        };
+        match tournament.status {
+            Status::SignUp => (
+                format!("Your pet #{pet} entered, the bracket is drawn"),
+                next_step,
+            ),
+            Status::Running { round } => {
+                let fight = self.rounds.get(round as usize).and_then(|matches| {
+                    matches
+                        .iter()
+                        .find(|fight| fight.first == pet || fight.second == Some(pet))
+                });
+                match fight {
+                    None => (format!("Your pet #{pet} is out"), None),
+                    Some(Match { second: None, .. }) => (
+                        format!("Your pet #{pet} has a bye, the next round is drawn"),
+                        next_step,
+                    ),
+                    Some(Match {
+                        winner: Some(winner),
+                        ..
+                    }) if *winner == pet => (
+                        format!("Your pet #{pet} won its match, the next round is drawn"),
+                        next_step,
+                    ),
+                    Some(Match {
+                        winner: Some(_), ..
+                    }) => (format!("Your pet #{pet} is out"), None),
+                    Some(Match {
+                        first,
+                        second: Some(second),
+                        ..
+                    }) => {
+                        let opponent = if *first == pet { *second } else { *first };
+                        (format!("Your pet #{pet} fights pet #{opponent}"), next_step)
+                    }
+                }
+            }
+            Status::Finished { winner } if winner == pet => {
+                (format!("Your pet #{pet} won the tournament!"), None)
+            }
+            Status::Finished { .. } => (format!("Your pet #{pet} is out"), None),
+            Status::Cancelled => ("Your entry fee was refunded".to_string(), None),
+        }
+    }
+}
+
+impl Brackets {
+    // The tournament chosen, or the first one when it's gone
+    fn viewed(&self, viewed: Option<TournamentId>) -> Option<&(TournamentId, Bracket)> {
+        self.brackets
+            .iter()
+            .find(|(id, _)| Some(*id) == viewed)
+            .or_else(|| self.brackets.first())
+    }
+}
+
+// Matches with a winner now that had none before, byes aside
+fn newly_fought(
+    before: &[(TournamentId, Bracket)],
+    after: &[(TournamentId, Bracket)],
+) -> Vec<(TournamentId, usize, usize)> {
+    let mut fought = Vec::new();
+    for (id, bracket) in after {
+        let Some((_, old)) = before.iter().find(|(old_id, _)| old_id == id) else {
+            continue;
+        };
+        for (round, matches) in bracket.rounds.iter().enumerate() {
+            for (index, fight) in matches.iter().enumerate() {
+                let was_fought = old
+                    .rounds
+                    .get(round)
+                    .and_then(|matches| matches.get(index))
+                    .map_or(false, |old| old.winner.is_some());
+                if fight.second.is_some() && fight.winner.is_some() && !was_fought {
+                    fought.push((*id, round, index));
+                }
+            }
+        }
+    }
+    fought
+}
+
+fn clock(seconds: u32) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// Read the active tournaments, and the `shown` ones which may have ended since
+async fn load_brackets(
+    api: OnlineClient<PolkadotConfig>,
+    shown: Vec<TournamentId>,
+) -> Result<(u32, Vec<(TournamentId, Bracket)>), subxt::Error> {
+    let head = api.blocks().at_latest().await?.number();
+    let mut ids: Vec<TournamentId> = api
+        .rpc()
+        .state_call("TournamentApi_active_tournaments", None, None)
+        .await?;
+    ids.extend(shown);
+    ids.sort();
+    ids.dedup();
+    let mut brackets = Vec::new();
+    for id in ids {
+        let bracket: Option<Bracket> = api
+            .rpc()
+            .state_call("TournamentApi_bracket", Some(&id.encode()), None)
+            .await?;
+        brackets.extend(bracket.map(|bracket| (id, bracket)));
+    }
+    Ok((head, brackets))
+}
+
+// Start reading the brackets in the background, replacing a read under way
+fn start_load(commands: &mut Commands, client: &mut ChainClient, shown: Vec<TournamentId>) {
+    let (sender, receiver) = mpsc::channel();
+    match client.api() {
+        Ok(api) => {
+            client.spawn(async move {
+                let brackets = load_brackets(api, shown).await;
+                let _ = sender.send(brackets.map_err(|e| e.to_string()));
+            });
+        }
+        Err(e) => {
+            let _ = sender.send(Err(e.to_string()));
+        }
+    }
+    commands.insert_resource(BracketLoad(Mutex::new(receiver)));
+}
+
+fn tournament_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let row_style = Style {
+        gap: Size::all(Val::Px(8.0)),
+        ..default()
+    };
+
+    start_load(&mut commands, &mut client, Vec::new());
+    commands.remove_resource::<Brackets>();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    gap: Size::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnTournamentScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Tournaments", text_style.clone()));
+            parent.spawn((
+                NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                },
+                TournamentTabs,
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(6.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    TournamentInfo,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the tournaments...",
+                        text_style.clone(),
+                    ));
+                });
+            // Rounds side by side, the first one on the left
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_grow: 1.0,
+                        gap: Size::all(Val::Px(16.0)),
+                        align_items: AlignItems::Center,
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    ..default()
+                },
+                BracketArea,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    TournamentAction::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back to Main", text_style.clone()));
+                });
+        });
+}
+
+// Read the brackets again when a tournament changes, and toast the prizes won
+fn tournament_events(
+    mut commands: Commands,
+    mut client: ResMut<ChainClient>,
+    mut events: EventReader<ChainEvent>,
+    brackets: Option<Res<Brackets>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut changed = false;
+    for ChainEvent(event) in events.iter() {
+        if event.pallet_name() != "TournamentModule" {
+            continue;
+        }
+        changed = true;
+        if event.variant_name() != "TournamentWon" {
+            continue;
+        }
+        if let Ok((tournament_id, winner, prize)) =
+            <(TournamentId, PetId, u128)>::decode(&mut event.field_bytes())
+        {
+            toasts.send(Toast(format!(
+                "Pet #{winner} won tournament #{tournament_id} and {}",
+                format_balance(prize)
+            )));
+        }
+    }
+    if changed {
+        let shown = brackets.map_or_else(Vec::new, |brackets| {
+            brackets.brackets.iter().map(|(id, _)| *id).collect()
+        });
+        start_load(&mut commands, &mut client, shown);
+    }
+}
+
+// Keep the brackets once they are read, with the matches fought since the last read
+fn receive_brackets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    load: Option<Res<BracketLoad>>,
+    previous: Option<Res<Brackets>>,
+    info: Query<Entity, With<TournamentInfo>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+
+    let (head, brackets) = match result {
+        Ok(read) => read,
+        // The brackets read before stay shown
+        Err(e) if previous.is_some() => {
+            toasts.send(Toast(format!("Can't read the tournaments: {e}")));
+            return;
+        }
+        Err(e) => {
+            let text_style = TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 24.0,
+                color: TEXT_COLOR,
+            };
+            for info in &info {
+                commands
+                    .entity(info)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Can't read the tournaments: {e}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+            return;
+        }
+    };
+    let fought = previous.map_or_else(Vec::new, |previous| {
+        newly_fought(&previous.brackets, &brackets)
+    });
+    commands.insert_resource(Brackets {
+        head,
+        read_at: time.elapsed_seconds(),
+        brackets,
+        fought,
+    });
+}
+
+fn spawn_pet(
+    parent: &mut ChildBuilder,
+    pet_id: PetId,
+    fight: &Match,
+    mine: bool,
+    text_style: &TextStyle,
+) {
+    let color = match fight.winner {
+        Some(winner) if winner == pet_id => WINNER_COLOR,
+        Some(_) => LOSER_COLOR,
+        None => TEXT_COLOR,
+    };
+    let label = if mine {
+        format!("#{pet_id} (you)")
+    } else {
+        format!("#{pet_id}")
+    };
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            ProfileLink(pet_id),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    color,
+                    ..text_style.clone()
+                },
+            ));
+        });
+}
+
+// Show the tabs, the status and the bracket of the chosen tournament
+fn show_bracket(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    client: Res<ChainClient>,
+    brackets: Option<Res<Brackets>>,
+    viewed: Res<ViewedTournament>,
+    tabs: Query<Entity, With<TournamentTabs>>,
+    info: Query<Entity, With<TournamentInfo>>,
+    area: Query<Entity, With<BracketArea>>,
+) {
+    let Some(brackets) = brackets else {
+        return;
+    };
+    if !brackets.is_changed() && !viewed.is_changed() {
+        return;
+    }
+    let (Ok(tabs), Ok(info), Ok(area)) = (tabs.get_single(), info.get_single(), area.get_single())
+    else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: TEXT_COLOR,
+    };
+    let small_style = TextStyle {
+        font_size: 16.0,
+        ..text_style.clone()
+    };
+
+    commands
+        .entity(tabs)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (id, _) in &brackets.brackets {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        TournamentAction::Show(*id),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("#{id}"),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+        });
+    commands.entity(info).despawn_descendants();
+    commands.entity(area).despawn_descendants();
+    let Some((id, bracket)) = brackets.viewed(viewed.0) else {
+        commands.entity(info).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "No tournament is signing up right now",
+                text_style.clone(),
+            ));
+        });
+        return;
+    };
+    let me = client.account_id();
+    let my_pet = bracket.pet_of(&me);
+    let tournament = &bracket.tournament;
+
+    commands.entity(info).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("Tournament #{id}, {}", bracket.status()),
+            text_style.clone(),
+        ));
+        parent.spawn(TextBundle::from_section(
+            format!(
+                "{} pets entered, entry fee {}, prize pool {}",
+                bracket.entrants.len(),
+                format_balance(tournament.entry_fee),
+                format_balance(tournament.pool)
+            ),
+            text_style.clone(),
+        ));
+        parent.spawn((
+            TextBundle::from_section(String::new(), text_style.clone()),
+            NextMatchText,
+        ));
+        if matches!(tournament.status, Status::SignUp) && my_pet.is_none() {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    TournamentAction::Enter(*id, tournament.entry_fee),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Enter for {}", format_balance(tournament.entry_fee)),
+                        text_style.clone(),
+                    ));
+                });
+        }
+    });
+
+    // Only a new read has new results, switching tournaments doesn't replay them
+    let flash = brackets.is_changed();
+    commands.entity(area).with_children(|parent| {
+        for (round, matches) in bracket.rounds.iter().enumerate() {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        gap: Size::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        bracket.round_name(round),
+                        text_style.clone(),
+                    ));
+                    for (index, fight) in matches.iter().enumerate() {
+                        let mut node = parent.spawn(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                padding: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            background_color: MATCH_COLOR.into(),
+                            ..default()
+                        });
+                        if flash && brackets.fought.contains(&(*id, round, index)) {
+                            node.insert(ResultFlash(Timer::from_seconds(
+                                FLASH_SECONDS,
+                                TimerMode::Once,
+                            )));
+                        }
+                        node.with_children(|parent| {
+                            spawn_pet(
+                                parent,
+                                fight.first,
+                                fight,
+                                my_pet == Some(fight.first),
+                                &small_style,
+                            );
+                            match fight.second {
+                                Some(second) => {
+                                    parent
+                                        .spawn(TextBundle::from_section("vs", small_style.clone()));
+                                    spawn_pet(
+                                        parent,
+                                        second,
+                                        fight,
+                                        my_pet == Some(second),
+                                        &small_style,
+                                    );
+                                }
+                                None => {
+                                    parent.spawn(TextBundle::from_section(
+                                        "bye",
+                                        small_style.clone(),
+                                    ));
+                                }
+                            }
+                        });
+                    }
+                });
+        }
+    });
+}
+
+// Count down to the player's next match from the chain head and the time since it was read
+fn next_match_countdown(
+    time: Res<Time>,
+    client: Res<ChainClient>,
+    brackets: Option<Res<Brackets>>,
+    viewed: Res<ViewedTournament>,
+    mut texts: Query<&mut Text, With<NextMatchText>>,
+) {
+    let Some(brackets) = brackets else {
+        return;
+    };
+    let Some((_, bracket)) = brackets.viewed(viewed.0) else {
+        return;
+    };
+    let (label, block) = bracket.next_match(&client.account_id());
+    let value = match block {
+        Some(block) => {
+            let seconds = block.saturating_sub(brackets.head) as f32 * BLOCK_SECONDS
+                - (time.elapsed_seconds() - brackets.read_at);
+            if seconds < 1.0 {
+                format!("{label} now")
+            } else {
+                format!("{label} in {}", clock(seconds as u32))
+            }
+        }
+        None => label,
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+// Pulse the matches fought since the last read, fading from the winner's color
+fn flash_results(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(
+        Entity,
+        &mut ResultFlash,
+        &mut Transform,
+        &mut BackgroundColor,
+    )>,
+) {
+    for (entity, mut flash, mut transform, mut color) in &mut flashes {
+        flash.0.tick(time.delta());
+        let progress = flash.0.percent();
+        if flash.0.finished() {
+            transform.scale = Vec3::ONE;
+            *color = MATCH_COLOR.into();
+            commands.entity(entity).remove::<ResultFlash>();
+            continue;
+        }
+        let pulse = (progress * 3.0 * PI).sin().abs() * (1.0 - progress);
+        transform.scale = Vec3::splat(1.0 + 0.2 * pulse);
+        let [r, g, b, _] = WINNER_COLOR.as_rgba_f32();
+        let [to_r, to_g, to_b, _] = MATCH_COLOR.as_rgba_f32();
+        *color = Color::rgb(
+            r + (to_r - r) * progress,
+            g + (to_g - g) * progress,
+            b + (to_b - b) * progress,
+        )
+        .into();
+    }
+}
+
+fn tournament_action(
+    interaction_query: Query<(&Interaction, &TournamentAction), Changed<Interaction>>,
+    mut viewed: ResMut<ViewedTournament>,
+    mut queue: ResMut<TxQueue>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            TournamentAction::Show(id) => viewed.0 = Some(*id),
+            // The fee is in the label, the chain charges the tournament's entry fee
+            TournamentAction::Enter(id, fee) => {
+                queue.push(
+                    format!("Enter tournament #{id} for {}", format_balance(*fee)),
+                    subxt::dynamic::tx(
+                        "TournamentModule",
+                        "enter_tournament",
+                        vec![Value::u128((*id).into())],
+                    ),
+                );
+            }
+            TournamentAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// Highlight the tournament shown
+fn color_choices(
+    viewed: Res<ViewedTournament>,
+    brackets: Option<Res<Brackets>>,
+    palette: Res<Palette>,
+    mut buttons: Query<(Ref<Interaction>, &TournamentAction, &mut BackgroundColor)>,
+) {
+    let shown = brackets
+        .as_ref()
+        .and_then(|brackets| brackets.viewed(viewed.0))
+        .map(|(id, _)| *id);
+    for (interaction, action, mut color) in &mut buttons {
+        if !viewed.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+        let selected = matches!(action, TournamentAction::Show(id) if shown == Some(*id));
+        *color = palette.button(*interaction, selected).into();
+    }
+}
@@ -0,0 +1,463 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::dynamic::Value;
+use subxt::ext::codec::Decode;
+use subxt::utils::AccountId32;
+
+use super::{
+    client::{AnyCall, ChainClient, ChainEvent, PetId},
+    despawn_screen,
+    fee::FeeText,
+    identity::{AccountNames, Names},
+    indexer::{IndexDb, IndexerError, TradeMessage},
+    settings::Palette,
+    txqueue::TxQueue,
+    GameState, TEXT_COLOR,
+};
+
+// Longest message the chain takes, `MaxMessageLen` of the runtime
+const MAX_MESSAGE_LEN: usize = 280;
+
+// Latest messages shown, the thread scrolls by dropping the oldest ones
+const MESSAGES_SHOWN: usize = 12;
+
+// This plugin shows the messages exchanged about a pet on the market between its owner and
+// someone interested in it, read from the event index and followed live, and sends new ones.
+// Each message is a transaction with a fee, and the chain limits how many an account sends a
+// day. Buttons with a `ChatLink` open a thread from any screen, and it goes back there when
+// closed.
+pub struct TradeChatPlugin;
+
+impl Plugin for TradeChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewedChat>()
+            .add_systems((
+                chat_setup.in_schedule(OnEnter(GameState::TradeChat)),
+                despawn_screen::<OnTradeChatScreen>.in_schedule(OnExit(GameState::TradeChat)),
+            ))
+            .add_system(open_chat)
+            .add_systems(
+                (
+                    receive_thread,
+                    thread_events.after(receive_thread),
+                    show_thread.after(thread_events),
+                    type_message,
+                    chat_action.after(type_message),
+                    color_choices,
+                )
+                    .in_set(OnUpdate(GameState::TradeChat)),
+            );
+    }
+}
+
+// Button opening the thread about a pet with another account
+#[derive(Component)]
+pub struct ChatLink {
+    pub pet_id: PetId,
+    pub with: AccountId32,
+}
+
+// Thread shown, and the screen it was opened from
+#[derive(Resource)]
+struct ViewedChat {
+    pet_id: PetId,
+    with: Option<AccountId32>,
+    back: GameState,
+}
+
+impl Default for ViewedChat {
+    fn default() -> Self {
+        Self {
+            pet_id: 0,
+            with: None,
+            back: GameState::Menu,
+        }
+    }
+}
+
+// Tag component used to tag entities added on the trade chat screen
+#[derive(Component)]
+struct OnTradeChatScreen;
+
+#[derive(Component)]
+struct ThreadArea;
+
+#[derive(Component)]
+struct DraftText;
+
+#[derive(Component)]
+struct ChatStatusText;
+
+#[derive(Component)]
+enum ChatAction {
+    Send,
+    Back,
+}
+
+// Messages of the thread, oldest first
+#[derive(Resource)]
+struct Thread(Vec<TradeMessage>);
+
+// Result of the background thread read
+#[derive(Resource)]
+struct ThreadLoad(Mutex<mpsc::Receiver<Result<Vec<TradeMessage>, String>>>);
+
+// Message being typed
+#[derive(Resource, Default)]
+struct Draft(String);
+
+fn open_chat(
+    links: Query<(&Interaction, &ChatLink), Changed<Interaction>>,
+    state: Res<State<GameState>>,
+    mut viewed: ResMut<ViewedChat>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, link) in &links {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        viewed.pet_id = link.pet_id;
+        viewed.with = Some(link.with.clone());
+        if state.0 != GameState::TradeChat {
+            viewed.back = state.0;
+        }
+        game_state.set(GameState::TradeChat);
+    }
+}
+
+fn chat_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut client: ResMut<ChainClient>,
+    viewed: Res<ViewedChat>,
+    names: Res<Names>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 22.0,
+        color: TEXT_COLOR,
+    };
+    let Some(with) = viewed.with.clone() else {
+        return;
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let account = client.account_id();
+    let pet_id = viewed.pet_id;
+    let other = with.clone();
+    client.spawn(async move {
+        let thread = IndexDb::open().and_then(|db| db.trade_messages(pet_id, &account, &other));
+        let _ = sender.send(thread.map_err(|e: IndexerError| e.to_string()));
+    });
+    commands.insert_resource(ThreadLoad(Mutex::new(receiver)));
+    commands.remove_resource::<Thread>();
+    commands.insert_resource(Draft::default());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    gap: Size::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+            OnTradeChatScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("Pet #{pet_id}: messages with {}", names.display(&with)),
+                    text_style.clone(),
+                ),
+                AccountNames(vec![with.clone()]),
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(700.0), Val::Px(360.0)),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::FlexEnd,
+                            gap: Size::all(Val::Px(6.0)),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            overflow: Overflow::Hidden,
+                            ..default()
+                        },
+                        background_color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(),
+                        ..default()
+                    },
+                    ThreadArea,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Reading the messages...",
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(700.0), Val::Px(50.0)),
+                        align_items: AlignItems::Center,
+                        padding: UiRect::horizontal(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            String::new(),
+                            TextStyle {
+                                font_size: 18.0,
+                                ..text_style.clone()
+                            },
+                        ),
+                        DraftText,
+                    ));
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                ChatStatusText,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::all(Val::Px(15.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, action) in [("Send", ChatAction::Send), ("Back", ChatAction::Back)]
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                FeeText(AnyCall::new(subxt::dynamic::tx(
+                    "PetModule",
+                    "send_trade_message",
+                    vec![
+                        Value::u128(pet_id.into()),
+                        Value::from_bytes(&with),
+                        Value::from_bytes([0u8; 64]),
+                    ],
+                ))),
+            ));
+        });
+}
+
+fn receive_thread(mut commands: Commands, load: Option<Res<ThreadLoad>>) {
+    let Some(load) = load else {
+        return;
+    };
+    let Ok(result) = load.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<ThreadLoad>();
+    match result {
+        Ok(messages) => commands.insert_resource(Thread(messages)),
+        Err(e) => {
+            warn!("can't read the trade messages: {e}");
+            commands.insert_resource(Thread(Vec::new()));
+        }
+    }
+}
+
+// Add the messages of the thread finalized while it's open
+fn thread_events(
+    mut events: EventReader<ChainEvent>,
+    client: Res<ChainClient>,
+    viewed: Res<ViewedChat>,
+    thread: Option<ResMut<Thread>>,
+) {
+    let (Some(mut thread), Some(with)) = (thread, &viewed.with) else {
+        return;
+    };
+    let account = client.account_id();
+    for ChainEvent(event) in events.iter() {
+        if event.pallet_name() != "PetModule" || event.variant_name() != "TradeMessageSent" {
+            continue;
+        }
+        let Ok((pet_id, from, to, text)) =
+            <(PetId, AccountId32, AccountId32, Vec<u8>)>::decode(&mut event.field_bytes())
+        else {
+            continue;
+        };
+        let ours = (from == account && to == *with) || (from == *with && to == account);
+        if pet_id == viewed.pet_id && ours {
+            thread.0.push(TradeMessage {
+                from,
+                text: String::from_utf8_lossy(&text).into_owned(),
+            });
+        }
+    }
+}
+
+fn show_thread(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    thread: Option<Res<Thread>>,
+    client: Res<ChainClient>,
+    names: Res<Names>,
+    area: Query<Entity, With<ThreadArea>>,
+) {
+    let Some(thread) = thread else {
+        return;
+    };
+    if !thread.is_changed() {
+        return;
+    }
+    let Ok(area) = area.get_single() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+
+    let account = client.account_id();
+    let shown = thread.0.len().saturating_sub(MESSAGES_SHOWN);
+    commands
+        .entity(area)
+        .despawn_descendants()
+        .with_children(|parent| {
+            if thread.0.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No messages yet, say hello",
+                    text_style.clone(),
+                ));
+            }
+            for message in &thread.0[shown..] {
+                // Our messages on the right, theirs on the left
+                let (align, color) = if message.from == account {
+                    (AlignSelf::FlexEnd, Color::rgb(0.2, 0.35, 0.6))
+                } else {
+                    (AlignSelf::FlexStart, Color::rgb(0.25, 0.25, 0.25))
+                };
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_self: align,
+                            max_size: Size::width(Val::Percent(80.0)),
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                format!("{}: {}", names.display(&message.from), message.text),
+                                text_style.clone(),
+                            ),
+                            AccountNames(vec![message.from.clone()]),
+                        ));
+                    });
+            }
+        });
+}
+
+fn type_message(
+    mut events: EventReader<ReceivedCharacter>,
+    kbd: Res<Input<KeyCode>>,
+    mut draft: ResMut<Draft>,
+    mut draft_text: Query<&mut Text, With<DraftText>>,
+) {
+    let before = draft.0.len();
+    if kbd.just_pressed(KeyCode::Back) {
+        draft.0.pop();
+    }
+    for event in events.iter() {
+        if !event.char.is_control() && draft.0.len() + event.char.len_utf8() <= MAX_MESSAGE_LEN {
+            draft.0.push(event.char);
+        }
+    }
+    if draft.0.len() != before {
+        for mut text in &mut draft_text {
+            text.sections[0].value = draft.0.clone();
+        }
+    }
+}
+
+fn chat_action(
+    interaction_query: Query<(&Interaction, &ChatAction), Changed<Interaction>>,
+    viewed: Res<ViewedChat>,
+    mut draft: ResMut<Draft>,
+    mut draft_text: Query<&mut Text, With<DraftText>>,
+    mut status_text: Query<&mut Text, (With<ChatStatusText>, Without<DraftText>)>,
+    mut queue: ResMut<TxQueue>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match action {
+            // The message shows up in the thread once its block is finalized
+            ChatAction::Send => {
+                let (Some(with), false) = (&viewed.with, draft.0.trim().is_empty()) else {
+                    status_text.single_mut().sections[0].value = "Type a message first".into();
+                    continue;
+                };
+                let text = std::mem::take(&mut draft.0);
+                queue.push(
+                    format!("Message about pet #{}", viewed.pet_id),
+                    subxt::dynamic::tx(
+                        "PetModule",
+                        "send_trade_message",
+                        vec![
+                            Value::u128(viewed.pet_id.into()),
+                            Value::from_bytes(with),
+                            Value::from_bytes(text.as_bytes()),
+                        ],
+                    ),
+                );
+                for mut text in &mut draft_text {
+                    text.sections[0].value.clear();
+                }
+                status_text.single_mut().sections[0].value = "Sending...".into();
+            }
+            ChatAction::Back => game_state.set(viewed.back),
+        }
+    }
+}
+
+fn color_choices(
+    palette: Res<Palette>,
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ChatAction>),
+    >,
+) {
+    for (interaction, mut color) in &mut buttons {
+        *color = palette.button(*interaction, false).into();
+    }
+}
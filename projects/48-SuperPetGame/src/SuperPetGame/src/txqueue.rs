@@ -0,0 +1,362 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::utils::tracing::{Instrument, Span};
+use subxt::{dynamic::Value, tx::TxPayload};
+
+use super::{
+    client::{AccountChanged, AnyCall, ChainClient, PendingAction, PetId, PetLock},
+    pet_watch::PlayerPet,
+    settings::Palette,
+    tooltip::CallHelp,
+    TEXT_COLOR,
+};
+
+// Time a queued transaction waits before it's broadcast, during which it can be cancelled
+const HOLD_SECONDS: f64 = 2.0;
+
+// Care actions only change the pet's stats, so they can queue up behind each other. Any
+// other action on a pet waits until the pending ones are finalized, and they wait for it.
+const CARE_CALLS: &[&str] = &["feed", "sleep"];
+
+// Background of a button whose action waits for another one to be finalized
+const LOCKED_BUTTON: Color = Color::rgba(0.3, 0.3, 0.3, 0.5);
+
+// This plugin queues the player's actions and broadcasts them one after the other with
+// sequential nonces, so quick actions don't replace each other in the transaction pool.
+// The pending transactions are listed in a corner of the window until they're sent.
+pub struct TxQueuePlugin;
+
+impl Plugin for TxQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TxQueue>()
+            .add_systems((drop_queue, broadcast_queue, queue_panel, queue_cancel).chain())
+            // After the hover colors are set, so they don't bring locked buttons back
+            .add_system(lock_buttons.in_base_set(CoreSet::PostUpdate));
+    }
+}
+
+// A call waiting in the queue
+struct PendingTx {
+    id: u64,
+    label: String,
+    call: AnyCall,
+    queued_at: f64,
+    // Open from the action that queued it until it's finalized, building and submitting
+    // the extrinsic are logged in it
+    span: Span,
+    // Keeps conflicting actions on the pet from being queued until it's finalized
+    lock: Option<PetLock>,
+}
+
+#[derive(Resource, Default)]
+pub struct TxQueue {
+    next_id: u64,
+    pending: Vec<PendingTx>,
+    // Time of the current frame, used to stamp new transactions
+    now: f64,
+    last_error: Option<String>,
+    // Transactions broadcast and not finalized yet, counted down by the tasks following them
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl TxQueue {
+    // Queue a call to be signed by the player, `label` is shown in the pending list
+    pub fn push(
+        &mut self,
+        label: impl Into<String>,
+        call: impl TxPayload + Send + Sync + 'static,
+    ) -> u64 {
+        self.next_id += 1;
+        self.last_error = None;
+        let label = label.into();
+        let span = info_span!("tx", id = self.next_id, label = %label);
+        info!(parent: &span, "queued");
+        self.pending.push(PendingTx {
+            id: self.next_id,
+            label,
+            call: AnyCall::new(call),
+            queued_at: self.now,
+            span,
+            lock: None,
+        });
+        self.next_id
+    }
+
+    // Queue a call of the pet pallet acting on the player's `pet`, unless it conflicts with
+    // an action still pending on it. The pet stays locked for conflicting actions until the
+    // call is finalized, fails or is cancelled.
+    pub fn push_pet_call(
+        &mut self,
+        client: &ChainClient,
+        pet: Option<PetId>,
+        label: impl Into<String>,
+        call: &'static str,
+        args: Vec<Value>,
+    ) -> Option<u64> {
+        let label = label.into();
+        let lock = match pet {
+            Some(pet) => {
+                if let Some(pending) = conflicting(&client.pending_actions(pet), call) {
+                    self.last_error = Some(format!(
+                        "{label} has to wait, {} isn't finalized yet",
+                        pending.label
+                    ));
+                    return None;
+                }
+                Some(client.lock_pet(PendingAction {
+                    pet,
+                    call,
+                    label: label.clone(),
+                }))
+            }
+            None => None,
+        };
+        let id = self.push(label, subxt::dynamic::tx("PetModule", call, args));
+        if let Some(tx) = self.pending.last_mut() {
+            tx.lock = lock;
+        }
+        Some(id)
+    }
+
+    // Transactions waiting in the queue, and broadcast but not finalized yet
+    pub fn pending_count(&self) -> (usize, usize) {
+        (self.pending.len(), self.in_flight.load(Ordering::Relaxed))
+    }
+
+    // Why the last transaction couldn't be queued or broadcast, until another one is queued
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    pub fn clear_error(&mut self) {
+        self.last_error = None;
+    }
+
+    // Drop a transaction that wasn't broadcast yet
+    pub fn cancel(&mut self, id: u64) {
+        self.pending.retain(|tx| tx.id != id);
+    }
+}
+
+// Tag component used to tag the pending transactions panel
+#[derive(Component)]
+struct OnQueuePanel;
+
+#[derive(Component)]
+struct CancelTx(u64);
+
+// Component of a button greyed out while a conflicting action is pending, with the reason
+// shown in its tooltip
+#[derive(Component)]
+pub struct Locked(pub String);
+
+// The pending action `call` has to wait for, if any
+fn conflicting<'a>(pending: &'a [PendingAction], call: &str) -> Option<&'a PendingAction> {
+    let care = CARE_CALLS.contains(&call);
+    pending
+        .iter()
+        .find(|action| !(care && CARE_CALLS.contains(&action.call)))
+}
+
+// The queued calls were meant to be signed by the previous account, so they aren't sent
+fn drop_queue(mut events: EventReader<AccountChanged>, mut queue: ResMut<TxQueue>) {
+    if events.iter().count() == 0 || queue.pending.is_empty() {
+        return;
+    }
+    queue.last_error = Some(format!(
+        "{} pending transactions dropped after switching account",
+        queue.pending.len()
+    ));
+    queue.pending.clear();
+}
+
+// Broadcast the transactions whose hold time is over, oldest first, then follow them in the
+// background until they're finalized
+fn broadcast_queue(time: Res<Time>, mut queue: ResMut<TxQueue>, mut client: ResMut<ChainClient>) {
+    let now = time.elapsed_seconds_f64();
+    // The panel is rebuilt when the queue changes, and the clock alone shouldn't do that
+    queue.bypass_change_detection().now = now;
+    let ready = queue
+        .pending
+        .iter()
+        .take_while(|tx| now - tx.queued_at >= HOLD_SECONDS)
+        .count();
+    if ready == 0 {
+        return;
+    }
+    let sending: Vec<_> = queue.pending.drain(..ready).collect();
+    for tx in sending {
+        let _entered = tx.span.enter();
+        match client.submit_and_watch(&tx.call) {
+            Ok(progress) => {
+                info!(hash = ?progress.extrinsic_hash(), "submitted");
+                let lock = tx.lock;
+                let in_flight = queue.in_flight.clone();
+                in_flight.fetch_add(1, Ordering::Relaxed);
+                let finalized = async move {
+                    match progress.wait_for_finalized_success().await {
+                        Ok(events) => info!(block = ?events.block_hash(), "finalized"),
+                        Err(e) => warn!("not finalized: {e}"),
+                    }
+                    in_flight.fetch_sub(1, Ordering::Relaxed);
+                    drop(lock);
+                };
+                client.spawn(finalized.instrument(info_span!("finalization")));
+            }
+            Err(e) => {
+                error!("error sending {}: {e:?}", tx.label);
+                queue.last_error = Some(format!("{} failed: {e}", tx.label));
+            }
+        }
+    }
+}
+
+// List the pending transactions with a cancel button each
+fn queue_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    queue: Res<TxQueue>,
+    panels: Query<Entity, With<OnQueuePanel>>,
+) {
+    if !queue.is_changed() {
+        return;
+    }
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+    if queue.pending.is_empty() && queue.last_error.is_none() {
+        return;
+    }
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(10.0),
+                        top: Val::Px(10.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    gap: Size::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            OnQueuePanel,
+        ))
+        .with_children(|parent| {
+            if let Some(error) = &queue.last_error {
+                parent.spawn(TextBundle::from_section(
+                    error,
+                    TextStyle {
+                        color: Color::ORANGE_RED,
+                        ..text_style.clone()
+                    },
+                ));
+            }
+            for tx in &queue.pending {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Pending: {}", tx.label),
+                            text_style.clone(),
+                        ));
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::horizontal(Val::Px(6.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::MAROON.into(),
+                                    ..default()
+                                },
+                                CancelTx(tx.id),
+                            ))
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section("Cancel", text_style.clone()));
+                            });
+                    });
+            }
+        });
+}
+
+fn queue_cancel(
+    interaction_query: Query<(&Interaction, &CancelTx), Changed<Interaction>>,
+    mut queue: ResMut<TxQueue>,
+) {
+    for (interaction, cancel) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            queue.cancel(cancel.0);
+            queue.last_error = None;
+        }
+    }
+}
+
+// Grey out the buttons of the pet calls conflicting with an action pending on the player's
+// pet, and bring their color back once it's finalized
+fn lock_buttons(
+    mut commands: Commands,
+    client: Res<ChainClient>,
+    player_pet: Res<PlayerPet>,
+    palette: Res<Palette>,
+    mut buttons: Query<
+        (
+            Entity,
+            &CallHelp,
+            &Interaction,
+            &mut BackgroundColor,
+            Option<&Locked>,
+        ),
+        With<Button>,
+    >,
+) {
+    let pending = player_pet
+        .pet
+        .as_ref()
+        .map(|pet| client.pending_actions(pet.id))
+        .unwrap_or_default();
+    for (entity, help, interaction, mut color, locked) in &mut buttons {
+        let blocking = match help.pallet {
+            "PetModule" => conflicting(&pending, help.call),
+            _ => None,
+        };
+        match (blocking, locked) {
+            (Some(action), _) => {
+                let reason = format!("Waiting for {} to be finalized", action.label);
+                if locked.map_or(true, |locked| locked.0 != reason) {
+                    commands.entity(entity).insert(Locked(reason));
+                }
+                if color.0 != LOCKED_BUTTON {
+                    color.0 = LOCKED_BUTTON;
+                }
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Locked>();
+                color.0 = palette.button(*interaction, false);
+            }
+            (None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::prelude::*;
+use subxt::ext::codec::Decode;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use super::client::{polkadot, ChainClient};
+
+// This plugin follows runtime upgrades of the chain. The client switches to the new metadata,
+// and a banner asks the player to update the game, which was built for the previous runtime.
+// It also checks that the runtime computes stats and battles with the same `pet_core` rules
+// as the game. When they differ, the features showing what the game computes itself are off
+// until the game is updated, see `rules_match`.
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRules>()
+            .add_startup_systems((
+                upgrade_banner_setup,
+                follow_runtime_upgrades,
+                check_rules_version,
+            ))
+            .add_systems((show_upgrade_banner, show_rules_banner).chain());
+    }
+}
+
+// `GameRulesVersion` of the runtime, `None` until it's read or when it can't be
+#[derive(Resource, Default)]
+pub struct GameRules {
+    pub chain_version: Option<u32>,
+}
+
+impl GameRules {
+    // Whether the runtime plays by the rules the game was built with. A runtime whose version
+    // can't be read is trusted, the game can't tell.
+    pub fn matches(&self) -> bool {
+        self.chain_version
+            .map_or(true, |version| version == pet_core::GAME_RULES_VERSION)
+    }
+}
+
+// Run condition for the features computing what the chain would: the stats between blocks,
+// hunger notices and the times the pet went hungry or tired in the recap
+pub fn rules_match(rules: Res<GameRules>) -> bool {
+    rules.matches()
+}
+
+// Runtime the node switched to
+struct RuntimeUpgrade {
+    spec_version: u32,
+    // Whether the calls and storage generated from `metadata.scale` are still valid
+    static_interface: bool,
+    rules_version: Option<u32>,
+}
+
+#[derive(Resource)]
+struct UpgradeReceiver(Mutex<mpsc::Receiver<RuntimeUpgrade>>);
+
+#[derive(Component)]
+struct UpgradeBanner;
+
+fn upgrade_banner_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(0.0),
+                        left: Val::Px(0.0),
+                        right: Val::Px(0.0),
+                        ..default()
+                    },
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: Color::rgb(0.6, 0.3, 0.0).into(),
+                z_index: ZIndex::Global(30),
+                ..default()
+            },
+            UpgradeBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn read_rules_version(api: &OnlineClient<PolkadotConfig>) -> Result<u32, subxt::Error> {
+    let value = api
+        .constants()
+        .at(&subxt::dynamic::constant("PetModule", "GameRulesVersion"))?;
+    Ok(u32::decode(&mut value.encoded())?)
+}
+
+fn check_rules_version(mut client: ResMut<ChainClient>, mut rules: ResMut<GameRules>) {
+    match client.api().and_then(|api| read_rules_version(&api)) {
+        Ok(version) => rules.chain_version = Some(version),
+        Err(e) => warn!("can't read the game rules version of the chain: {e:?}"),
+    }
+}
+
+fn follow_runtime_upgrades(mut commands: Commands, mut client: ResMut<ChainClient>) {
+    let api = match client.api() {
+        Ok(api) => api,
+        Err(e) => {
+            warn!("not following runtime upgrades of {}: {e:?}", client.url());
+            return;
+        }
+    };
+    let (sender, receiver) = mpsc::channel();
+    client.spawn(async move {
+        if let Err(e) = apply_runtime_upgrades(api, sender).await {
+            warn!("runtime upgrade subscription ended: {e:?}");
+        }
+    });
+    commands.insert_resource(UpgradeReceiver(Mutex::new(receiver)));
+}
+
+async fn apply_runtime_upgrades(
+    api: OnlineClient<PolkadotConfig>,
+    sender: mpsc::Sender<RuntimeUpgrade>,
+) -> Result<(), subxt::Error> {
+    let updater = api.updater();
+    let mut updates = updater.runtime_updates().await?;
+    while let Some(update) = updates.next().await {
+        let update = update?;
+        let spec_version = update.runtime_version().spec_version;
+        // The subscription starts with the current version, which isn't an upgrade
+        if spec_version <= api.runtime_version().spec_version {
+            continue;
+        }
+        // From now on everything is encoded and decoded with the new metadata
+        if updater.apply_update(update).is_err() {
+            continue;
+        }
+        let upgrade = RuntimeUpgrade {
+            spec_version,
+            static_interface: polkadot::validate_codegen(&api).is_ok(),
+            rules_version: read_rules_version(&api).ok(),
+        };
+        if sender.send(upgrade).is_err() {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+fn show_upgrade_banner(
+    receiver: Option<Res<UpgradeReceiver>>,
+    mut client: ResMut<ChainClient>,
+    mut rules: ResMut<GameRules>,
+    banner: Query<(&mut Style, &Children), With<UpgradeBanner>>,
+    texts: Query<&mut Text>,
+) {
+    let Some(upgrade) = receiver.and_then(|receiver| receiver.0.lock().unwrap().try_iter().last())
+    else {
+        return;
+    };
+    client.set_static_interface(upgrade.static_interface);
+    if upgrade.rules_version.is_some() {
+        rules.chain_version = upgrade.rules_version;
+    }
+    let message = if upgrade.static_interface {
+        format!(
+            "The chain was upgraded to runtime version {}. Please update the game.",
+            upgrade.spec_version
+        )
+    } else {
+        format!(
+            "The chain was upgraded to runtime version {} and this game is out of date. \
+             Please update it, some actions may fail until then.",
+            upgrade.spec_version
+        )
+    };
+    show_banner(message, banner, texts);
+}
+
+// Say why parts of the game are off, at startup or after an upgrade changed the rules
+fn show_rules_banner(
+    rules: Res<GameRules>,
+    banner: Query<(&mut Style, &Children), With<UpgradeBanner>>,
+    texts: Query<&mut Text>,
+) {
+    let Some(chain_version) = rules.chain_version else {
+        return;
+    };
+    if !rules.is_changed() || rules.matches() {
+        return;
+    }
+    let message = format!(
+        "The chain plays by game rules version {chain_version} and this game by version {}. \
+         Please update it, stats between blocks, hunger notices and the recap are off until \
+         then.",
+        pet_core::GAME_RULES_VERSION
+    );
+    show_banner(message, banner, texts);
+}
+
+fn show_banner(
+    message: String,
+    mut banner: Query<(&mut Style, &Children), With<UpgradeBanner>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (mut style, children) in &mut banner {
+        style.display = Display::Flex;
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = message.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowLevel, WindowPosition, WindowResolution};
+use bevy::winit::WinitWindows;
+use serde::{Deserialize, Serialize};
+
+use crate::{game::PlayerSprite, platform::Desktop};
+
+// Where the widget was last, kept across restarts
+const POSITION_FILE: &str = "widget-position.json";
+
+// Size of the widget window, in logical pixels
+const WIDGET_SIZE: f32 = 240.0;
+
+// Room left for the taskbar at the bottom of the screen, winit can't tell where it is
+const TASKBAR_HEIGHT: f64 = 48.0;
+
+// Walking speed along the screen edge, in logical pixels per second
+const WALK_SPEED: f64 = 40.0;
+
+// How often the position is saved while walking
+const SAVE_SECONDS: f32 = 10.0;
+
+// How far from the pet the widget still catches clicks, in world units
+const PET_REACH: f32 = 80.0;
+
+// How long clicks go through before the widget looks at the cursor again
+const PROBE_SECONDS: f32 = 0.25;
+
+// This plugin turns the game into a desktop widget when started with `--widget`: a small
+// window without decorations, above the other windows, walking along the taskbar at the
+// bottom of its screen. It moves to another screen when its screen goes away or changes,
+// and starts where it was last time. Where the desktop allows it, clicks away from the pet go
+// through to the windows under the widget.
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut App) {
+        if !std::env::args().any(|arg| arg == "--widget") {
+            return;
+        }
+        app.insert_resource(Widget::load())
+            .add_startup_system(widget_window)
+            .add_systems((walk_along_edge, save_position).chain())
+            .add_system(click_through);
+    }
+}
+
+// A screen, identified by its name, with its position and size in physical pixels
+#[derive(Clone, PartialEq)]
+struct Monitor {
+    name: Option<String>,
+    position: IVec2,
+    size: UVec2,
+    scale: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SavedPosition {
+    monitor: Option<String>,
+    // Distance from the left of the screen, in physical pixels
+    x: f64,
+}
+
+#[derive(Resource)]
+struct Widget {
+    saved: SavedPosition,
+    monitor: Option<Monitor>,
+    x: f64,
+    // 1 when walking right, -1 when walking left
+    direction: f64,
+    save_timer: Timer,
+    probe_timer: Timer,
+}
+
+impl Widget {
+    fn load() -> Self {
+        let saved: SavedPosition = std::fs::read(POSITION_FILE)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default();
+        Self {
+            x: saved.x,
+            saved,
+            monitor: None,
+            direction: 1.0,
+            save_timer: Timer::from_seconds(SAVE_SECONDS, TimerMode::Repeating),
+            probe_timer: Timer::from_seconds(PROBE_SECONDS, TimerMode::Once),
+        }
+    }
+
+    fn save(&self) {
+        let saved = SavedPosition {
+            monitor: self
+                .monitor
+                .as_ref()
+                .and_then(|monitor| monitor.name.clone()),
+            x: self.x,
+        };
+        let written = serde_json::to_vec_pretty(&saved)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(POSITION_FILE, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            error!("can't save {POSITION_FILE}: {e}");
+        }
+    }
+}
+
+fn widget_window(desktop: Res<Desktop>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    window.resolution = WindowResolution::new(WIDGET_SIZE, WIDGET_SIZE);
+    window.decorations = false;
+    desktop.configure(&mut window);
+    window.window_level = WindowLevel::AlwaysOnTop;
+}
+
+// Let clicks away from the pet through to what's under the widget. The window doesn't see the
+// cursor while they go through, so it catches it again now and then to look where it is.
+fn click_through(
+    time: Res<Time>,
+    desktop: Res<Desktop>,
+    mut widget: ResMut<Widget>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    pets: Query<&GlobalTransform, With<PlayerSprite>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !desktop.click_through || !desktop.transparent() {
+        return;
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    if !window.cursor.hit_test {
+        if widget.probe_timer.tick(time.delta()).finished() {
+            window.cursor.hit_test = true;
+        }
+        return;
+    }
+    let Some(position) = window.cursor_position() else {
+        return;
+    };
+    let on_pet = cameras
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| camera.viewport_to_world_2d(transform, position))
+        .map_or(true, |cursor| {
+            pets.iter()
+                .any(|pet| pet.translation().truncate().distance(cursor) <= PET_REACH)
+        });
+    if !on_pet {
+        window.cursor.hit_test = false;
+        widget.probe_timer.reset();
+    }
+}
+
+// The screen called `name` if it's still there, else the one the window is on, or the main
+// screen when it's on none
+fn find_monitor(window: &winit::window::Window, name: Option<&str>) -> Option<Monitor> {
+    let named = name.and_then(|name| {
+        window
+            .available_monitors()
+            .find(|monitor| monitor.name().as_deref() == Some(name))
+    });
+    let monitor = named
+        .or_else(|| window.current_monitor())
+        .or_else(|| window.primary_monitor())?;
+    let (position, size) = (monitor.position(), monitor.size());
+    Some(Monitor {
+        name: monitor.name(),
+        position: IVec2::new(position.x, position.y),
+        size: UVec2::new(size.width, size.height),
+        scale: monitor.scale_factor(),
+    })
+}
+
+// Walk to one end of the bottom edge and back, following the screen when it changes
+fn walk_along_edge(
+    time: Res<Time>,
+    winit_windows: NonSend<WinitWindows>,
+    mut widget: ResMut<Widget>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+) {
+    let Ok((entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+    let Some(winit_window) = winit_windows.get_window(entity) else {
+        return;
+    };
+    // Stay on the same screen, the saved one at first
+    let name = match &widget.monitor {
+        Some(monitor) => monitor.name.clone(),
+        None => widget.saved.monitor.clone(),
+    };
+    let Some(monitor) = find_monitor(winit_window, name.as_deref()) else {
+        return;
+    };
+    if widget.monitor.as_ref() != Some(&monitor) {
+        // Keep the same share of the way along the new screen
+        if let Some(previous) = &widget.monitor {
+            widget.x *= monitor.size.x as f64 / previous.size.x.max(1) as f64;
+        }
+        widget.monitor = Some(monitor.clone());
+    }
+
+    let (width, height) = (
+        window.physical_width() as f64,
+        window.physical_height() as f64,
+    );
+    let end = (monitor.size.x as f64 - width).max(0.0);
+    widget.x += widget.direction * WALK_SPEED * monitor.scale * time.delta_seconds_f64();
+    if widget.x >= end {
+        widget.x = end;
+        widget.direction = -1.0;
+    } else if widget.x <= 0.0 {
+        widget.x = 0.0;
+        widget.direction = 1.0;
+    }
+    let y = monitor.size.y as f64 - height - TASKBAR_HEIGHT * monitor.scale;
+    window.position = WindowPosition::At(monitor.position + IVec2::new(widget.x as i32, y as i32));
+}
+
+fn save_position(time: Res<Time>, mut widget: ResMut<Widget>, mut exits: EventReader<AppExit>) {
+    let exiting = exits.iter().count() > 0;
+    if widget.save_timer.tick(time.delta()).just_finished() || exiting {
+        widget.save();
+    }
+}